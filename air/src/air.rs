@@ -1,3 +1,5 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use core::ops::{Add, Mul, Sub};
 
 use p3_field::{AbstractExtensionField, AbstractField, ExtensionField, Field};
@@ -12,6 +14,31 @@ pub trait BaseAir<F>: Sync {
     fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
         None
     }
+
+    /// The rows of the main trace the constraints see: row `j` of `AirBuilder::main` is row
+    /// `i + window_offsets()[j]` of the trace when the constraints are evaluated on row `i`,
+    /// wrapping around the trace. Defaults to the local and next rows.
+    ///
+    /// The preprocessed trace and the later stages are always seen at the local and next rows.
+    fn window_offsets(&self) -> Vec<isize> {
+        vec![0, 1]
+    }
+
+    /// Columns which repeat with a period dividing the trace height, e.g. round constants, seen
+    /// through `PeriodicAirBuilder::periodic_values`. Row `i` of a column `c` is
+    /// `c[i % c.len()]`, and `c.len()` must be a power of two.
+    ///
+    /// These aren't committed to; the prover and verifier evaluate them directly.
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        vec![]
+    }
+
+    /// A row the main trace can be padded with to a power of two height, which the constraints
+    /// must hold on, as well as transitions between copies of it. Defaults to none, i.e. rows of
+    /// zeros.
+    fn padding_row(&self) -> Option<Vec<F>> {
+        None
+    }
 }
 
 ///  An AIR with 0 or more public values.
@@ -21,6 +48,37 @@ pub trait BaseAirWithPublicValues<F>: BaseAir<F> {
     }
 }
 
+/// An AIR whose trace is committed to in stages, with challenges sampled from the transcript
+/// before each stage after the first, which the columns of that stage may depend on. Stage 0 is
+/// the main trace.
+pub trait MultiStageAir<F>: BaseAir<F> {
+    /// The number of stages, including the main trace.
+    fn num_stages(&self) -> usize {
+        1
+    }
+
+    /// The number of columns in the given stage.
+    fn stage_width(&self, stage: usize) -> usize {
+        assert_eq!(stage, 0, "the AIR only has a main trace");
+        self.width()
+    }
+
+    /// The number of challenges sampled before the given stage, which is zero for stage 0.
+    fn num_stage_challenges(&self, stage: usize) -> usize {
+        let _ = stage;
+        0
+    }
+
+    /// Whether the columns of the given stage after the main trace are extension field elements,
+    /// e.g. running sums over challenges. `stage_width` then counts extension field columns,
+    /// which are committed to as `D` base field columns each and read with
+    /// `MultiStageAirBuilder::stage_ext`.
+    fn is_extension_stage(&self, stage: usize) -> bool {
+        let _ = stage;
+        false
+    }
+}
+
 /// An AIR that works with a particular `AirBuilder`.
 pub trait Air<AB: AirBuilder>: BaseAir<AB::F> {
     fn eval(&self, builder: &mut AB);
@@ -54,6 +112,7 @@ pub trait AirBuilder: Sized {
 
     type M: Matrix<Self::Var>;
 
+    /// The window of the main trace, with a row for each of `BaseAir::window_offsets`.
     fn main(&self) -> Self::M;
 
     fn is_first_row(&self) -> Self::Expr;
@@ -61,6 +120,8 @@ pub trait AirBuilder: Sized {
     fn is_transition(&self) -> Self::Expr {
         self.is_transition_window(2)
     }
+    /// A selector which is zero on the last `size - 1` rows, for constraints between `size`
+    /// consecutive rows.
     fn is_transition_window(&self, size: usize) -> Self::Expr;
 
     /// Returns a sub-builder whose constraints are enforced only when `condition` is nonzero.
@@ -115,12 +176,88 @@ pub trait AirBuilder: Sized {
         let x = x.into();
         self.assert_zero(x.clone() * (x - Self::Expr::ONE));
     }
+
+    /// Name the constraints asserted after this, up to the next call, so that builders which
+    /// check them one by one can say which failed. Other builders ignore it.
+    fn name_constraints(&mut self, name: &str) {
+        let _ = name;
+    }
+
+    /// Assert that `x` is zero, naming just this constraint, which takes precedence over the name
+    /// from `name_constraints`.
+    fn assert_zero_named<I: Into<Self::Expr>>(&mut self, name: &str, x: I) {
+        let _ = name;
+        self.assert_zero(x);
+    }
 }
 
 pub trait AirBuilderWithPublicValues: AirBuilder {
     type PublicVar: Into<Self::Expr> + Copy;
 
     fn public_values(&self) -> &[Self::PublicVar];
+
+    /// The public value in the extension field whose coefficients are the `EF::D` public values
+    /// from `start`, e.g. the output of an accumulator.
+    fn public_value_ext(&self, start: usize) -> Self::ExprEF
+    where
+        Self: ExtensionBuilder,
+    {
+        let d = <Self::EF as AbstractExtensionField<Self::F>>::D;
+        self.ext_from_base(
+            self.public_values()[start..start + d]
+                .iter()
+                .map(|&v| v.into()),
+        )
+    }
+}
+
+/// An `AirBuilder` which can see the values of `BaseAir::periodic_columns` on the current row.
+pub trait PeriodicAirBuilder: AirBuilder {
+    type PeriodicVar: Into<Self::Expr> + Copy;
+
+    /// The value of each periodic column, in the order of `BaseAir::periodic_columns`.
+    fn periodic_values(&self) -> &[Self::PeriodicVar];
+}
+
+/// An `AirBuilder` with a selector for every row of the trace, not just the first and last.
+pub trait AirBuilderWithRowSelectors: AirBuilder {
+    /// A selector which is nonzero on the given row of the trace, and zero on the others.
+    fn is_row(&self, row: usize) -> Self::Expr;
+
+    /// Returns a sub-builder whose constraints are enforced only on the given row.
+    fn when_row(&mut self, row: usize) -> FilteredAirBuilder<'_, Self> {
+        self.when(self.is_row(row))
+    }
+
+    /// Assert that the main trace has the public value at `public_index` in the given row and
+    /// column.
+    fn bind_public_value(&mut self, row: usize, column: usize, public_index: usize)
+    where
+        Self: AirBuilderWithPublicValues,
+    {
+        let main = self.main();
+        let cell = main.row_slice(0)[column];
+        let public_value = self.public_values()[public_index];
+        self.when_row(row).assert_eq(cell, public_value);
+    }
+
+    /// Assert that the `EF::D` columns of the main trace from `column`, as the coefficients of an
+    /// extension field element, have the extension field public value from `public_start`, as in
+    /// `public_value_ext`, in the given row.
+    fn bind_public_value_ext(&mut self, row: usize, column: usize, public_start: usize)
+    where
+        Self: AirBuilderWithPublicValues + ExtensionBuilder,
+    {
+        let d = <Self::EF as AbstractExtensionField<Self::F>>::D;
+        let main = self.main();
+        let value = self.ext_from_base(
+            main.row_slice(0)[column..column + d]
+                .iter()
+                .map(|&v| v.into()),
+        );
+        let public_value = self.public_value_ext(public_start);
+        self.when_row(row).assert_eq_ext(value, public_value);
+    }
 }
 
 pub trait PairBuilder: AirBuilder {
@@ -152,6 +289,21 @@ pub trait ExtensionBuilder: AirBuilder {
     {
         self.assert_eq_ext(x, Self::ExprEF::ONE)
     }
+
+    /// The extension field element whose coefficients, in the basis of `EF` over `F`, are
+    /// `coeffs`, e.g. an extension field value stored in `EF::D` base field columns.
+    fn ext_from_base<I>(&self, coeffs: I) -> Self::ExprEF
+    where
+        I: IntoIterator<Item = Self::Expr>,
+    {
+        coeffs
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| {
+                Self::ExprEF::from_f(<Self::EF as AbstractExtensionField<Self::F>>::monomial(i)) * c
+            })
+            .sum()
+    }
 }
 
 pub trait PermutationAirBuilder: ExtensionBuilder {
@@ -164,6 +316,32 @@ pub trait PermutationAirBuilder: ExtensionBuilder {
     fn permutation_randomness(&self) -> &[Self::RandomVar];
 }
 
+/// An `AirBuilder` for a `MultiStageAir`, whose constraints can refer to the columns of every
+/// stage and the challenges sampled before each.
+pub trait MultiStageAirBuilder: ExtensionBuilder {
+    type Challenge: Into<Self::ExprEF> + Copy;
+
+    /// The columns of the given stage, as `main` is for stage 0.
+    fn stage(&self, stage: usize) -> Self::M;
+
+    /// The challenges sampled before the given stage.
+    fn stage_challenges(&self, stage: usize) -> &[Self::Challenge];
+
+    /// The local and next rows of the given stage as extension field elements, each made of
+    /// `EF::D` consecutive base field columns, as for a stage which `is_extension_stage`.
+    fn stage_ext(&self, stage: usize) -> [Vec<Self::ExprEF>; 2] {
+        let d = <Self::EF as AbstractExtensionField<Self::F>>::D;
+        let columns = self.stage(stage);
+        [0, 1].map(|row| {
+            columns
+                .row_slice(row)
+                .chunks_exact(d)
+                .map(|coeffs| self.ext_from_base(coeffs.iter().map(|&c| c.into())))
+                .collect()
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct FilteredAirBuilder<'a, AB: AirBuilder> {
     pub inner: &'a mut AB,
@@ -201,6 +379,37 @@ impl<'a, AB: AirBuilder> AirBuilder for FilteredAirBuilder<'a, AB> {
     fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
         self.inner.assert_zero(self.condition() * x.into());
     }
+
+    fn name_constraints(&mut self, name: &str) {
+        self.inner.name_constraints(name);
+    }
+
+    fn assert_zero_named<I: Into<Self::Expr>>(&mut self, name: &str, x: I) {
+        self.inner
+            .assert_zero_named(name, self.condition() * x.into());
+    }
+}
+
+impl<'a, AB: AirBuilderWithPublicValues> AirBuilderWithPublicValues for FilteredAirBuilder<'a, AB> {
+    type PublicVar = AB::PublicVar;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.inner.public_values()
+    }
+}
+
+impl<'a, AB: PeriodicAirBuilder> PeriodicAirBuilder for FilteredAirBuilder<'a, AB> {
+    type PeriodicVar = AB::PeriodicVar;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        self.inner.periodic_values()
+    }
+}
+
+impl<'a, AB: AirBuilderWithRowSelectors> AirBuilderWithRowSelectors for FilteredAirBuilder<'a, AB> {
+    fn is_row(&self, row: usize) -> Self::Expr {
+        self.inner.is_row(row)
+    }
 }
 
 impl<'a, AB: ExtensionBuilder> ExtensionBuilder for FilteredAirBuilder<'a, AB> {
@@ -214,6 +423,13 @@ impl<'a, AB: ExtensionBuilder> ExtensionBuilder for FilteredAirBuilder<'a, AB> {
     {
         self.inner.assert_zero_ext(x.into() * self.condition());
     }
+
+    fn ext_from_base<I>(&self, coeffs: I) -> Self::ExprEF
+    where
+        I: IntoIterator<Item = Self::Expr>,
+    {
+        self.inner.ext_from_base(coeffs)
+    }
 }
 
 impl<'a, AB: PermutationAirBuilder> PermutationAirBuilder for FilteredAirBuilder<'a, AB> {
@@ -229,3 +445,19 @@ impl<'a, AB: PermutationAirBuilder> PermutationAirBuilder for FilteredAirBuilder
         self.inner.permutation_randomness()
     }
 }
+
+impl<'a, AB: MultiStageAirBuilder> MultiStageAirBuilder for FilteredAirBuilder<'a, AB> {
+    type Challenge = AB::Challenge;
+
+    fn stage(&self, stage: usize) -> Self::M {
+        self.inner.stage(stage)
+    }
+
+    fn stage_challenges(&self, stage: usize) -> &[Self::Challenge] {
+        self.inner.stage_challenges(stage)
+    }
+
+    fn stage_ext(&self, stage: usize) -> [Vec<Self::ExprEF>; 2] {
+        self.inner.stage_ext(stage)
+    }
+}
@@ -5,7 +5,13 @@
 extern crate alloc;
 
 mod air;
+mod lookup;
+mod memory;
+mod sub_air;
 mod virtual_column;
 
 pub use air::*;
+pub use lookup::*;
+pub use memory::*;
+pub use sub_air::*;
 pub use virtual_column::*;
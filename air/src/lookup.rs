@@ -0,0 +1,134 @@
+use alloc::vec::Vec;
+use core::iter;
+
+use p3_field::{AbstractExtensionField, AbstractField};
+use p3_matrix::Matrix;
+
+use crate::{AirBuilder, ExtensionBuilder, FilteredAirBuilder, PermutationAirBuilder};
+
+/// A tuple of values sent to, or received from, a lookup bus, with a multiplicity.
+///
+/// The lookups of an AIR are balanced if, for each bus and tuple, the multiplicities with which it
+/// is sent add up to those with which it is received, over all rows.
+#[derive(Clone, Debug)]
+pub struct Lookup<Expr> {
+    pub bus: usize,
+    pub values: Vec<Expr>,
+    /// Positive for sends and negative for receives.
+    pub multiplicity: Expr,
+}
+
+/// An `AirBuilder` which supports lookups between the rows of an AIR, proven with a LogUp
+/// argument.
+pub trait AirBuilderWithLookups: AirBuilder {
+    fn lookup(&mut self, lookup: Lookup<Self::Expr>);
+
+    /// Send `values` to `bus`, `multiplicity` times.
+    fn send<I, M>(&mut self, bus: usize, values: I, multiplicity: M)
+    where
+        I: IntoIterator,
+        I::Item: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        self.lookup(Lookup {
+            bus,
+            values: values.into_iter().map(Into::into).collect(),
+            multiplicity: multiplicity.into(),
+        });
+    }
+
+    /// Receive `values` from `bus`, `multiplicity` times.
+    fn receive<I, M>(&mut self, bus: usize, values: I, multiplicity: M)
+    where
+        I: IntoIterator,
+        I::Item: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        self.send(bus, values, -multiplicity.into());
+    }
+}
+
+impl<'a, AB: AirBuilderWithLookups> AirBuilderWithLookups for FilteredAirBuilder<'a, AB> {
+    fn lookup(&mut self, lookup: Lookup<Self::Expr>) {
+        let multiplicity = self.condition() * lookup.multiplicity;
+        self.inner.lookup(Lookup {
+            multiplicity,
+            ..lookup
+        });
+    }
+}
+
+/// The denominator of the LogUp term of a lookup, `gamma + bus + sum_i beta^(i + 1) values[i]`.
+pub fn lookup_fingerprint<Expr, ExprEF, I>(
+    bus: usize,
+    values: I,
+    gamma: ExprEF,
+    beta: ExprEF,
+) -> ExprEF
+where
+    Expr: AbstractField,
+    ExprEF: AbstractExtensionField<Expr>,
+    I: IntoIterator<Item = Expr>,
+{
+    iter::once(Expr::from_canonical_usize(bus))
+        .chain(values)
+        .zip(beta.powers())
+        .fold(gamma, |acc, (value, beta_power)| acc + beta_power * value)
+}
+
+/// The number of constraints `eval_logup` asserts for `num_lookups` lookups.
+pub const fn num_logup_constraints(num_lookups: usize) -> usize {
+    num_lookups + 3
+}
+
+/// Assert the LogUp constraints for `lookups`, which must be all the lookups of the AIR on the
+/// current row.
+///
+/// `builder.permutation()` must have one column per lookup, holding
+/// `multiplicity / lookup_fingerprint(..)` on each row, followed by the running sum of these over
+/// all columns and all rows up to and including the current one. Its randomness must be
+/// `[gamma, beta]`. The last row of the running sum is asserted to be `cumulative_sum`, which is
+/// zero if the lookups are balanced.
+pub fn eval_logup<AB: PermutationAirBuilder>(
+    builder: &mut AB,
+    lookups: &[Lookup<AB::Expr>],
+    cumulative_sum: AB::ExprEF,
+) {
+    let num_lookups = lookups.len();
+    let permutation = builder.permutation();
+    assert_eq!(permutation.width(), num_lookups + 1);
+    let (local, next) = (permutation.row_slice(0), permutation.row_slice(1));
+    let (local, next): (Vec<AB::ExprEF>, Vec<AB::ExprEF>) = (
+        local.iter().map(|&v| v.into()).collect(),
+        next.iter().map(|&v| v.into()).collect(),
+    );
+    let randomness = builder.permutation_randomness();
+    let (gamma, beta): (AB::ExprEF, AB::ExprEF) = (randomness[0].into(), randomness[1].into());
+
+    for (lookup, term) in lookups.iter().zip(&local) {
+        let fingerprint = lookup_fingerprint(
+            lookup.bus,
+            lookup.values.clone(),
+            gamma.clone(),
+            beta.clone(),
+        );
+        builder.assert_eq_ext(
+            term.clone() * fingerprint,
+            AB::ExprEF::from_base(lookup.multiplicity.clone()),
+        );
+    }
+
+    let local_sum: AB::ExprEF = local[..num_lookups].iter().cloned().sum();
+    let next_sum: AB::ExprEF = next[..num_lookups].iter().cloned().sum();
+    let running_sum = local[num_lookups].clone();
+    let next_running_sum = next[num_lookups].clone();
+    builder
+        .when_first_row()
+        .assert_eq_ext(running_sum.clone(), local_sum);
+    builder
+        .when_transition()
+        .assert_eq_ext(next_running_sum - running_sum.clone(), next_sum);
+    builder
+        .when_last_row()
+        .assert_eq_ext(running_sum, cumulative_sum);
+}
@@ -0,0 +1,273 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use p3_field::{AbstractField, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::{AirBuilder, AirBuilderWithLookups, BaseSubAir, SubAir, SubBuilder};
+
+/// A read or a write of `value` at `addr`, at time `timestamp`, as a chip emits it to a memory
+/// bus with `MemoryBuilder` and `MemoryAir` receives it.
+///
+/// `is_write` is one for writes and zero for reads, and a read must see the value of the latest
+/// access to its address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryAccess<T> {
+    pub addr: T,
+    pub timestamp: T,
+    pub value: T,
+    pub is_write: T,
+}
+
+impl<T> MemoryAccess<T> {
+    pub fn map<U, Fun: FnMut(T) -> U>(self, mut f: Fun) -> MemoryAccess<U> {
+        MemoryAccess {
+            addr: f(self.addr),
+            timestamp: f(self.timestamp),
+            value: f(self.value),
+            is_write: f(self.is_write),
+        }
+    }
+
+    /// The values sent to the memory bus.
+    pub fn into_values(self) -> [T; 4] {
+        [self.addr, self.timestamp, self.value, self.is_write]
+    }
+}
+
+impl<T: AbstractField> MemoryAccess<T> {
+    pub fn read(addr: T, timestamp: T, value: T) -> Self {
+        Self {
+            addr,
+            timestamp,
+            value,
+            is_write: T::ZERO,
+        }
+    }
+
+    pub fn write(addr: T, timestamp: T, value: T) -> Self {
+        Self {
+            addr,
+            timestamp,
+            value,
+            is_write: T::ONE,
+        }
+    }
+}
+
+/// Memory accesses for any AIR with lookups, to be checked by a `MemoryAir` on the same bus.
+///
+/// Lookups only balance within an AIR, so the `MemoryAir` must be a piece of the same AIR as the
+/// chips which access memory.
+pub trait MemoryBuilder: AirBuilderWithLookups {
+    /// Emit `access` to the memory on `bus`, `multiplicity` times, which is one for an access and
+    /// zero for none.
+    fn memory_access<T, M>(&mut self, bus: usize, access: MemoryAccess<T>, multiplicity: M)
+    where
+        T: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        self.send(bus, access.into_values(), multiplicity);
+    }
+
+    /// Emit a read of `value` at `addr`, at time `timestamp`.
+    fn read_memory<A, T, V, M>(
+        &mut self,
+        bus: usize,
+        addr: A,
+        timestamp: T,
+        value: V,
+        multiplicity: M,
+    ) where
+        A: Into<Self::Expr>,
+        T: Into<Self::Expr>,
+        V: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        let access = MemoryAccess::read(addr.into(), timestamp.into(), value.into());
+        self.memory_access(bus, access, multiplicity);
+    }
+
+    /// Emit a write of `value` at `addr`, at time `timestamp`.
+    fn write_memory<A, T, V, M>(
+        &mut self,
+        bus: usize,
+        addr: A,
+        timestamp: T,
+        value: V,
+        multiplicity: M,
+    ) where
+        A: Into<Self::Expr>,
+        T: Into<Self::Expr>,
+        V: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        let access = MemoryAccess::write(addr.into(), timestamp.into(), value.into());
+        self.memory_access(bus, access, multiplicity);
+    }
+}
+
+impl<AB: AirBuilderWithLookups> MemoryBuilder for AB {}
+
+const ADDR: usize = 0;
+const TIMESTAMP: usize = 1;
+const VALUE: usize = 2;
+const IS_WRITE: usize = 3;
+const IS_REAL: usize = 4;
+const IS_NEW_ADDR: usize = 5;
+const NUM_ACCESS_COLS: usize = 6;
+
+/// A sub-AIR checking that the memory accesses sent to `bus` are consistent, i.e. that each read
+/// sees the value of the latest access to its address, and that the first access to each address
+/// is a write.
+///
+/// Each row receives one access, and the rows are sorted by address and then by timestamp. The
+/// sorting is checked by range checking the gap between consecutive addresses, or consecutive
+/// timestamps of the same address, minus one, as `num_limbs` limbs of `limb_bits` bits sent to
+/// `range_bus`, where a table such as `p3-range-air`'s must receive them. Addresses and timestamps
+/// must be less than `2^(limb_bits num_limbs)`, which must be much smaller than the field.
+///
+/// The columns are the access, a flag for the rows receiving one, a flag for the rows starting a
+/// new address, and the limbs of the gap to the next row. See `generate_memory_trace`.
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryAir {
+    pub bus: usize,
+    pub range_bus: usize,
+    pub limb_bits: usize,
+    pub num_limbs: usize,
+}
+
+impl MemoryAir {
+    pub const fn new(bus: usize, range_bus: usize, limb_bits: usize, num_limbs: usize) -> Self {
+        Self {
+            bus,
+            range_bus,
+            limb_bits,
+            num_limbs,
+        }
+    }
+
+    /// The columns, relative to this piece, with the limbs sent to `range_bus`, e.g. for counting
+    /// the multiplicities of the range-check table.
+    pub const fn limb_columns(&self) -> Range<usize> {
+        NUM_ACCESS_COLS..NUM_ACCESS_COLS + self.num_limbs
+    }
+}
+
+impl BaseSubAir for MemoryAir {
+    fn width(&self) -> usize {
+        NUM_ACCESS_COLS + self.num_limbs
+    }
+}
+
+impl<AB: AirBuilderWithLookups> SubAir<AB> for MemoryAir {
+    fn eval(&self, builder: &mut SubBuilder<'_, AB>) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+
+        let access = MemoryAccess {
+            addr: local[ADDR],
+            timestamp: local[TIMESTAMP],
+            value: local[VALUE],
+            is_write: local[IS_WRITE],
+        };
+        builder.receive(self.bus, access.into_values(), local[IS_REAL]);
+        for &limb in &local[self.limb_columns()] {
+            builder.send(self.range_bus, [limb], AB::Expr::ONE);
+        }
+
+        builder.assert_bool(local[IS_WRITE]);
+        builder.assert_bool(local[IS_REAL]);
+        builder.assert_bool(local[IS_NEW_ADDR]);
+        builder.when_first_row().assert_one(local[IS_WRITE]);
+
+        // The padding rows, which receive nothing, come last. They must be writes, so they can't
+        // change what a later read sees.
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_zero((AB::Expr::ONE - local[IS_REAL]) * next[IS_REAL]);
+        when_transition.assert_zero(next[IS_NEW_ADDR] * (AB::Expr::ONE - next[IS_WRITE]));
+        when_transition
+            .when_ne(next[IS_NEW_ADDR], AB::Expr::ONE)
+            .assert_eq(next[ADDR], local[ADDR]);
+        when_transition
+            .when_ne(next[IS_WRITE], AB::Expr::ONE)
+            .assert_eq(next[VALUE], local[VALUE]);
+
+        let addr_gap = next[ADDR] - local[ADDR] - AB::Expr::ONE;
+        let timestamp_gap = next[TIMESTAMP] - local[TIMESTAMP] - AB::Expr::ONE;
+        let gap =
+            addr_gap * next[IS_NEW_ADDR] + timestamp_gap * (AB::Expr::ONE - next[IS_NEW_ADDR]);
+        let base = AB::F::from_canonical_u64(1 << self.limb_bits);
+        let limbs = local[self.limb_columns()]
+            .iter()
+            .rev()
+            .fold(AB::Expr::ZERO, |acc, &limb| acc * base + limb);
+        when_transition.assert_eq(gap, limbs);
+    }
+}
+
+/// The columns of `air` for `accesses`, in any order, on `height` rows. The rows after the
+/// accesses are padding: writes of zero to the last address at the following timestamps.
+pub fn generate_memory_trace<F: PrimeField64>(
+    air: &MemoryAir,
+    accesses: &[MemoryAccess<F>],
+    height: usize,
+) -> RowMajorMatrix<F> {
+    assert!(
+        accesses.len() <= height,
+        "{} accesses don't fit in {height} rows",
+        accesses.len()
+    );
+
+    let mut rows: Vec<MemoryAccess<u64>> = accesses
+        .iter()
+        .map(|access| access.map(|x| x.as_canonical_u64()))
+        .collect();
+    rows.sort_by_key(|access| (access.addr, access.timestamp));
+    let last = rows.last().copied();
+    let (addr, timestamp) = last.map_or((0, 0), |last| (last.addr, last.timestamp + 1));
+    rows.extend((0..(height - accesses.len()) as u64).map(|i| MemoryAccess {
+        addr,
+        timestamp: timestamp + i,
+        value: 0,
+        is_write: 1,
+    }));
+
+    let width = air.width();
+    let mut trace = vec![F::ZERO; height * width];
+    for (i, row) in trace.chunks_exact_mut(width).enumerate() {
+        let access = rows[i];
+        row[..IS_REAL].copy_from_slice(&access.map(F::from_canonical_u64).into_values());
+        row[IS_REAL] = F::from_bool(i < accesses.len());
+        row[IS_NEW_ADDR] = F::from_bool(i == 0 || access.addr != rows[i - 1].addr);
+
+        let Some(next) = rows.get(i + 1) else {
+            continue;
+        };
+        let gap = if next.addr != access.addr {
+            next.addr - access.addr - 1
+        } else {
+            next.timestamp
+                .checked_sub(access.timestamp + 1)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "two accesses to {} at timestamp {}",
+                        access.addr, access.timestamp
+                    )
+                })
+        };
+        assert!(
+            gap >> (air.limb_bits * air.num_limbs) == 0,
+            "the gap after the access to {} at timestamp {} doesn't fit in the limbs",
+            access.addr,
+            access.timestamp
+        );
+        let mask = (1 << air.limb_bits) - 1;
+        for (j, limb) in row[air.limb_columns()].iter_mut().enumerate() {
+            *limb = F::from_canonical_u64((gap >> (air.limb_bits * j)) & mask);
+        }
+    }
+    RowMajorMatrix::new(trace, width)
+}
@@ -0,0 +1,213 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::{
+    Air, AirBuilder, AirBuilderWithLookups, AirBuilderWithPublicValues, AirBuilderWithRowSelectors,
+    BaseAir, ExtensionBuilder, Lookup, PairBuilder, PeriodicAirBuilder,
+};
+
+/// A piece of an AIR, such as an adder or a range check, which owns `width()` consecutive columns
+/// of the main trace. Pieces are assembled into an AIR with `Composed`.
+pub trait BaseSubAir: Sync {
+    /// The number of columns this piece owns.
+    fn width(&self) -> usize;
+
+    /// The AIR whose columns are those of `self` followed by those of `other`.
+    fn then<B: BaseSubAir>(self, other: B) -> Composed<Self, B>
+    where
+        Self: Sized,
+    {
+        Composed::new(self, other)
+    }
+}
+
+/// A sub-AIR that works with a particular `AirBuilder`.
+pub trait SubAir<AB: AirBuilder>: BaseSubAir {
+    /// Assert the constraints of this piece, whose columns are those of `builder.main()`.
+    fn eval(&self, builder: &mut SubBuilder<'_, AB>);
+}
+
+/// An `AirBuilder` which only sees a range of the columns of the main trace, as column 0 onwards,
+/// for a `SubAir`. Everything else, such as the selectors and the public values, is that of the
+/// whole AIR.
+#[derive(Debug)]
+pub struct SubBuilder<'a, AB: AirBuilder> {
+    inner: &'a mut AB,
+    columns: Range<usize>,
+}
+
+impl<'a, AB: AirBuilder> SubBuilder<'a, AB> {
+    pub fn new(inner: &'a mut AB, columns: Range<usize>) -> Self {
+        Self { inner, columns }
+    }
+
+    /// The columns of the whole main trace which this builder sees.
+    pub fn columns(&self) -> Range<usize> {
+        self.columns.clone()
+    }
+
+    /// A builder for the given range of the columns this builder sees.
+    pub fn sub_builder(&mut self, columns: Range<usize>) -> SubBuilder<'_, AB> {
+        assert!(columns.end <= self.columns.len());
+        let start = self.columns.start;
+        SubBuilder {
+            inner: &mut *self.inner,
+            columns: start + columns.start..start + columns.end,
+        }
+    }
+}
+
+impl<'a, AB: AirBuilder> AirBuilder for SubBuilder<'a, AB> {
+    type F = AB::F;
+    type Expr = AB::Expr;
+    type Var = AB::Var;
+    type M = RowMajorMatrix<AB::Var>;
+
+    fn main(&self) -> Self::M {
+        let main = self.inner.main();
+        let values: Vec<AB::Var> = (0..main.height())
+            .flat_map(|r| main.row_slice(r)[self.columns.clone()].to_vec())
+            .collect();
+        RowMajorMatrix::new(values, self.columns.len())
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        self.inner.is_first_row()
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        self.inner.is_last_row()
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        self.inner.is_transition_window(size)
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        self.inner.assert_zero(x);
+    }
+
+    fn name_constraints(&mut self, name: &str) {
+        self.inner.name_constraints(name);
+    }
+
+    fn assert_zero_named<I: Into<Self::Expr>>(&mut self, name: &str, x: I) {
+        self.inner.assert_zero_named(name, x);
+    }
+}
+
+impl<'a, AB: AirBuilderWithPublicValues> AirBuilderWithPublicValues for SubBuilder<'a, AB> {
+    type PublicVar = AB::PublicVar;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.inner.public_values()
+    }
+}
+
+impl<'a, AB: PeriodicAirBuilder> PeriodicAirBuilder for SubBuilder<'a, AB> {
+    type PeriodicVar = AB::PeriodicVar;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        self.inner.periodic_values()
+    }
+}
+
+impl<'a, AB: AirBuilderWithRowSelectors> AirBuilderWithRowSelectors for SubBuilder<'a, AB> {
+    fn is_row(&self, row: usize) -> Self::Expr {
+        self.inner.is_row(row)
+    }
+}
+
+impl<'a, AB: PairBuilder> PairBuilder for SubBuilder<'a, AB> {
+    /// All the preprocessed columns, since they aren't divided among the pieces.
+    fn preprocessed(&self) -> Self::M {
+        let preprocessed = self.inner.preprocessed();
+        let values: Vec<AB::Var> = (0..preprocessed.height())
+            .flat_map(|r| preprocessed.row_slice(r).to_vec())
+            .collect();
+        RowMajorMatrix::new(values, preprocessed.width())
+    }
+}
+
+impl<'a, AB: AirBuilderWithLookups> AirBuilderWithLookups for SubBuilder<'a, AB> {
+    fn lookup(&mut self, lookup: Lookup<Self::Expr>) {
+        self.inner.lookup(lookup);
+    }
+}
+
+impl<'a, AB: ExtensionBuilder> ExtensionBuilder for SubBuilder<'a, AB> {
+    type EF = AB::EF;
+    type ExprEF = AB::ExprEF;
+    type VarEF = AB::VarEF;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.inner.assert_zero_ext(x);
+    }
+
+    fn ext_from_base<I>(&self, coeffs: I) -> Self::ExprEF
+    where
+        I: IntoIterator<Item = Self::Expr>,
+    {
+        self.inner.ext_from_base(coeffs)
+    }
+}
+
+/// Two sub-AIRs side by side: the columns of `first`, followed by those of `second`. Longer
+/// chains are built with `BaseSubAir::then`, e.g. `a.then(b).then(c)`.
+///
+/// This is an AIR of its own, as well as a sub-AIR of a larger composition.
+#[derive(Clone, Debug)]
+pub struct Composed<A, B> {
+    pub first: A,
+    pub second: B,
+}
+
+impl<A: BaseSubAir, B: BaseSubAir> Composed<A, B> {
+    pub const fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// The columns of `first`, e.g. for filling them in the trace.
+    pub fn first_columns(&self) -> Range<usize> {
+        0..self.first.width()
+    }
+
+    /// The columns of `second`.
+    pub fn second_columns(&self) -> Range<usize> {
+        self.first.width()..BaseSubAir::width(self)
+    }
+}
+
+impl<A: BaseSubAir, B: BaseSubAir> BaseSubAir for Composed<A, B> {
+    fn width(&self) -> usize {
+        self.first.width() + self.second.width()
+    }
+}
+
+impl<AB: AirBuilder, A: SubAir<AB>, B: SubAir<AB>> SubAir<AB> for Composed<A, B> {
+    fn eval(&self, builder: &mut SubBuilder<'_, AB>) {
+        self.first
+            .eval(&mut builder.sub_builder(self.first_columns()));
+        self.second
+            .eval(&mut builder.sub_builder(self.second_columns()));
+    }
+}
+
+impl<F, A: BaseSubAir, B: BaseSubAir> BaseAir<F> for Composed<A, B> {
+    fn width(&self) -> usize {
+        BaseSubAir::width(self)
+    }
+}
+
+impl<AB: AirBuilder, A: SubAir<AB>, B: SubAir<AB>> Air<AB> for Composed<A, B> {
+    fn eval(&self, builder: &mut AB) {
+        let width = BaseSubAir::width(self);
+        SubAir::eval(self, &mut SubBuilder::new(builder, 0..width));
+    }
+}
@@ -0,0 +1,56 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_field::{Field, PackedValue};
+use p3_poseidon2::{
+    DiffusionPermutation, MdsLightPermutation, Poseidon2, Poseidon2ExternalMatrixGeneral,
+};
+use p3_symmetric::Permutation;
+use rand::{thread_rng, Rng};
+
+type F = BabyBear;
+type Packed = <F as Field>::Packing;
+
+fn bench_poseidon2(c: &mut Criterion) {
+    poseidon2_internal_layer::<16>(c);
+    poseidon2_internal_layer::<24>(c);
+    poseidon2::<16>(c);
+    poseidon2::<24>(c);
+}
+
+/// Throughput of the internal linear layer alone on packed inputs.
+fn poseidon2_internal_layer<const WIDTH: usize>(c: &mut Criterion)
+where
+    DiffusionMatrixBabyBear: DiffusionPermutation<Packed, WIDTH>,
+{
+    let mut group = c.benchmark_group("poseidon2_internal_layer");
+    group.throughput(Throughput::Elements((WIDTH * Packed::WIDTH) as u64));
+    let internal_layer = DiffusionMatrixBabyBear::default();
+    let input: [Packed; WIDTH] = core::array::from_fn(|_| thread_rng().gen());
+    group.bench_with_input(BenchmarkId::new("BabyBear", WIDTH), &input, |b, &input| {
+        b.iter(|| internal_layer.permute(black_box(input)))
+    });
+    group.finish();
+}
+
+/// Throughput of the whole permutation on packed inputs.
+fn poseidon2<const WIDTH: usize>(c: &mut Criterion)
+where
+    DiffusionMatrixBabyBear: DiffusionPermutation<Packed, WIDTH>,
+    Poseidon2ExternalMatrixGeneral: MdsLightPermutation<Packed, WIDTH>,
+{
+    let mut group = c.benchmark_group("poseidon2");
+    group.throughput(Throughput::Elements((WIDTH * Packed::WIDTH) as u64));
+    let poseidon2 = Poseidon2::<F, _, _, WIDTH, 7>::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let input: [Packed; WIDTH] = core::array::from_fn(|_| thread_rng().gen());
+    group.bench_with_input(BenchmarkId::new("BabyBear", WIDTH), &input, |b, &input| {
+        b.iter(|| poseidon2.permute(black_box(input)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_poseidon2);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 use p3_field::{exp_1725656503, exp_u64_by_squaring, AbstractField, Field};
 use p3_monty_31::{
     BarrettParameters, BinomialExtensionData, FieldParameters, MontyField31, MontyParameters,
-    PackedMontyParameters, TwoAdicData,
+    PackedMontyParameters, ThreeAdicData, TwoAdicData,
 };
 
 /// The prime field `2^31 - 2^27 + 1`, a.k.a. the Baby Bear field.
@@ -86,6 +86,12 @@ impl TwoAdicData for BabyBearParameters {
     ]);
 }
 
+impl ThreeAdicData for BabyBearParameters {
+    const THREE_ADICITY: usize = 1;
+
+    const THREE_ADIC_GENERATOR: BabyBear = BabyBear::new(0x4e5d1533);
+}
+
 impl BinomialExtensionData<4> for BabyBearParameters {
     const W: BabyBear = BabyBear::new(11);
     const DTH_ROOT: BabyBear = BabyBear::new(1728404513);
@@ -111,7 +117,7 @@ impl BinomialExtensionData<5> for BabyBearParameters {
 mod tests {
     use core::array;
 
-    use p3_field::{PrimeField32, PrimeField64, TwoAdicField};
+    use p3_field::{PrimeField32, PrimeField64, ThreeAdicField, TwoAdicField};
     use p3_field_testing::{test_field, test_field_dft, test_two_adic_field};
 
     use super::*;
@@ -129,6 +135,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_baby_bear_three_adicity_generator() {
+        let g = BabyBear::three_adic_generator(1);
+        assert_ne!(g, F::ONE);
+        assert_eq!(g.cube(), F::ONE);
+
+        let g = BabyBear::mixed_radix_generator(4, 1);
+        assert_eq!(g.exp_u64(3), BabyBear::two_adic_generator(4));
+        assert_eq!(g.exp_u64(16), BabyBear::three_adic_generator(1));
+    }
+
     #[test]
     fn test_to_babybear_array() {
         let range_array: [u32; 32] = array::from_fn(|i| i as u32);
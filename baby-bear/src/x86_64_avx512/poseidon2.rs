@@ -61,4 +61,26 @@ mod tests {
 
         assert_eq!(avx512_output, expected);
     }
+
+    /// Test that the internal layer matches the scalar version in every lane, including inputs
+    /// which are `0` or `P - 1`.
+    #[test]
+    fn test_avx512_internal_layer_lanes() {
+        let mut rng = rand::thread_rng();
+
+        let mut input: [[F; 16]; 24] = rng.gen();
+        input[0][0] = F::ZERO;
+        input[1][1] = F::NEG_ONE;
+        input[2] = [F::NEG_ONE; 16];
+
+        let mut avx512_state = input.map(PackedBabyBearAVX512);
+        DiffusionMatrixBabyBear::default().permute_mut(&mut avx512_state);
+
+        for lane in 0..16 {
+            let mut expected: [F; 24] = core::array::from_fn(|i| input[i][lane]);
+            DiffusionMatrixBabyBear::default().permute_mut(&mut expected);
+            let avx512_output: [F; 24] = core::array::from_fn(|i| avx512_state[i].0[lane]);
+            assert_eq!(avx512_output, expected);
+        }
+    }
 }
@@ -0,0 +1,75 @@
+use alloc::vec::Vec;
+
+use p3_air::{Air, AirBuilderWithLookups, BaseAir, PairBuilder};
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::{bitwise_table, BitwiseBuilder, BITWISE_BUS, NUM_BITWISE_OPS};
+
+/// The number of columns of each operation `BitwiseAir` checks: `[op, a, b, output]`.
+pub const BITWISE_OP_WIDTH: usize = 4;
+
+/// Checks `num_ops` operations per row against the table of every operation on every pair of
+/// limbs of `bits` bits.
+///
+/// The table is the preprocessed trace, so the trace has exactly `2^(2 bits)` rows. Each row of
+/// the main trace has `num_ops` groups of `[op, a, b, output]`, each checked with a lookup on
+/// `bus`, followed by the number of times each operation on the table's row is looked up, in the
+/// order of `BitwiseOp::ALL`. See `generate_bitwise_trace`.
+#[derive(Clone, Copy, Debug)]
+pub struct BitwiseAir {
+    pub bits: usize,
+    pub num_ops: usize,
+    pub bus: usize,
+}
+
+impl BitwiseAir {
+    pub const fn new(bits: usize, num_ops: usize, bus: usize) -> Self {
+        assert!(
+            num_ops > 0,
+            "a bitwise AIR needs at least one operation column"
+        );
+        Self { bits, num_ops, bus }
+    }
+
+    /// Operations on bytes, on `BITWISE_BUS`, with a table of `2^16` rows.
+    pub const fn u8(num_ops: usize) -> Self {
+        Self::new(8, num_ops, BITWISE_BUS)
+    }
+
+    /// The height of the trace, i.e. the size of the table.
+    pub const fn height(&self) -> usize {
+        1 << (2 * self.bits)
+    }
+
+    /// The first of the `NUM_BITWISE_OPS` columns of the main trace with the multiplicities of
+    /// the table.
+    pub const fn multiplicity_columns(&self) -> usize {
+        self.num_ops * BITWISE_OP_WIDTH
+    }
+}
+
+impl<F: Field> BaseAir<F> for BitwiseAir {
+    fn width(&self) -> usize {
+        self.multiplicity_columns() + NUM_BITWISE_OPS
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(bitwise_table(self.bits))
+    }
+}
+
+impl<AB: PairBuilder + AirBuilderWithLookups> Air<AB> for BitwiseAir {
+    fn eval(&self, builder: &mut AB) {
+        let table: Vec<AB::Var> = builder.preprocessed().row_slice(0).to_vec();
+        let main = builder.main();
+        let local: Vec<AB::Var> = main.row_slice(0).to_vec();
+        let (ops, multiplicities) = local.split_at(self.multiplicity_columns());
+
+        for op in ops.chunks_exact(BITWISE_OP_WIDTH) {
+            builder.bitwise(self.bus, op[0], op[1], op[2], op[3]);
+        }
+        builder.bitwise_table(self.bus, &table, multiplicities);
+    }
+}
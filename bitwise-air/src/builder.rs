@@ -0,0 +1,204 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_air::AirBuilderWithLookups;
+use p3_field::{AbstractField, Field};
+
+use crate::{BitwiseOp, NUM_BITWISE_OPS};
+
+/// Lookups into a bitwise table such as `BitwiseAir`'s, for any AIR with lookups.
+///
+/// Lookups only balance within an AIR, so the table must be part of the same AIR as the
+/// operations it checks, with a receive from `bitwise_table` on each of its rows. The table also
+/// range checks the limbs, since it only has limbs of its number of bits.
+pub trait BitwiseBuilder: AirBuilderWithLookups {
+    /// Assert that `output` is `op` applied to `a` and `b`, where `op` is a `BitwiseOp` as a field
+    /// element.
+    fn bitwise<Op, A, B, O>(&mut self, bus: usize, op: Op, a: A, b: B, output: O)
+    where
+        Op: Into<Self::Expr>,
+        A: Into<Self::Expr>,
+        B: Into<Self::Expr>,
+        O: Into<Self::Expr>,
+    {
+        self.send(
+            bus,
+            [op.into(), a.into(), b.into(), output.into()],
+            Self::Expr::ONE,
+        );
+    }
+
+    /// Assert that `output` is `a ^ b`.
+    fn xor<A, B, O>(&mut self, bus: usize, a: A, b: B, output: O)
+    where
+        A: Into<Self::Expr>,
+        B: Into<Self::Expr>,
+        O: Into<Self::Expr>,
+    {
+        self.bitwise(bus, op_expr::<Self::Expr>(BitwiseOp::Xor), a, b, output);
+    }
+
+    /// Assert that `output` is `a & b`.
+    fn and<A, B, O>(&mut self, bus: usize, a: A, b: B, output: O)
+    where
+        A: Into<Self::Expr>,
+        B: Into<Self::Expr>,
+        O: Into<Self::Expr>,
+    {
+        self.bitwise(bus, op_expr::<Self::Expr>(BitwiseOp::And), a, b, output);
+    }
+
+    /// Assert that `output` is `a | b`.
+    fn or<A, B, O>(&mut self, bus: usize, a: A, b: B, output: O)
+    where
+        A: Into<Self::Expr>,
+        B: Into<Self::Expr>,
+        O: Into<Self::Expr>,
+    {
+        self.bitwise(bus, op_expr::<Self::Expr>(BitwiseOp::Or), a, b, output);
+    }
+
+    /// Assert that `output` is `a << b`, truncated to the limb size of the table.
+    fn shl<A, B, O>(&mut self, bus: usize, a: A, b: B, output: O)
+    where
+        A: Into<Self::Expr>,
+        B: Into<Self::Expr>,
+        O: Into<Self::Expr>,
+    {
+        self.bitwise(bus, op_expr::<Self::Expr>(BitwiseOp::Shl), a, b, output);
+    }
+
+    /// The limbs of a word rotated left by `rotation` bits, where the word is given by `limbs` of
+    /// `limb_bits` bits each, least significant first, and `limb_bits` is the limb size of the
+    /// table on `bus`.
+    ///
+    /// `shifted` are witness columns holding each limb shifted left by `rotation % limb_bits`,
+    /// which are checked against the table; see `BitwiseCounts::rotate_left`. The rotation by
+    /// whole limbs is free.
+    fn rotate_left<L, S>(
+        &mut self,
+        bus: usize,
+        limb_bits: usize,
+        limbs: &[L],
+        shifted: &[S],
+        rotation: usize,
+    ) -> Vec<Self::Expr>
+    where
+        L: Into<Self::Expr> + Clone,
+        S: Into<Self::Expr> + Clone,
+    {
+        let n = limbs.len();
+        assert_eq!(shifted.len(), n, "every limb needs a shifted limb");
+        let (limb_rotation, bit_rotation) = ((rotation / limb_bits) % n, rotation % limb_bits);
+
+        // The bits of each limb which are shifted out, into the next limb.
+        let scale = Self::F::from_canonical_u32(1 << bit_rotation);
+        let inv_base = Self::F::from_canonical_u32(1 << limb_bits).inverse();
+        let carries: Vec<Self::Expr> = limbs
+            .iter()
+            .zip(shifted)
+            .map(|(limb, shifted)| {
+                self.shl(
+                    bus,
+                    limb.clone(),
+                    Self::Expr::from_canonical_usize(bit_rotation),
+                    shifted.clone(),
+                );
+                let (limb, shifted): (Self::Expr, Self::Expr) =
+                    (limb.clone().into(), shifted.clone().into());
+                (limb * scale - shifted) * inv_base
+            })
+            .collect();
+
+        let mut output = vec![Self::Expr::ZERO; n];
+        for (i, shifted) in shifted.iter().enumerate() {
+            let shifted: Self::Expr = shifted.clone().into();
+            output[(i + limb_rotation) % n] = shifted + carries[(i + n - 1) % n].clone();
+        }
+        output
+    }
+
+    /// The limbs of a word shifted right by `shift` bits, where the word is given by `limbs` of
+    /// `limb_bits` bits each, least significant first, and `limb_bits` is the limb size of the
+    /// table on `bus`.
+    ///
+    /// `low` are witness columns holding the low `shift % limb_bits` bits of each limb, which are
+    /// checked against the table; see `BitwiseCounts::shift_right`. The shift by whole limbs is
+    /// free.
+    fn shift_right<L, W>(
+        &mut self,
+        bus: usize,
+        limb_bits: usize,
+        limbs: &[L],
+        low: &[W],
+        shift: usize,
+    ) -> Vec<Self::Expr>
+    where
+        L: Into<Self::Expr> + Clone,
+        W: Into<Self::Expr> + Clone,
+    {
+        let n = limbs.len();
+        assert_eq!(low.len(), n, "every limb needs its low bits");
+        let (limb_shift, bit_shift) = (shift / limb_bits, shift % limb_bits);
+
+        // The bits of each limb which stay in it, and the low bits, which are shifted out into
+        // the previous limb.
+        let mask = Self::Expr::from_canonical_u32((1 << bit_shift) - 1);
+        let inv_scale = Self::F::from_canonical_u32(1 << bit_shift).inverse();
+        let carry_scale = Self::F::from_canonical_u32(1 << (limb_bits - bit_shift));
+        let (high, carries): (Vec<Self::Expr>, Vec<Self::Expr>) = limbs
+            .iter()
+            .zip(low)
+            .map(|(limb, low)| {
+                self.and(bus, limb.clone(), mask.clone(), low.clone());
+                let (limb, low): (Self::Expr, Self::Expr) =
+                    (limb.clone().into(), low.clone().into());
+                ((limb - low.clone()) * inv_scale, low * carry_scale)
+            })
+            .unzip();
+
+        (0..n)
+            .map(|i| {
+                let j = i + limb_shift;
+                let high = high.get(j).cloned().unwrap_or(Self::Expr::ZERO);
+                let carry = carries.get(j + 1).cloned().unwrap_or(Self::Expr::ZERO);
+                high + carry
+            })
+            .collect()
+    }
+
+    /// Provide a row of the table on `bus`, which is `[a, b]` followed by the output of each
+    /// operation in the order of `BitwiseOp::ALL`, as in `bitwise_table`. Each operation is
+    /// looked up the corresponding number of `multiplicities` times, as counted by
+    /// `BitwiseCounts`.
+    fn bitwise_table<V, M>(&mut self, bus: usize, row: &[V], multiplicities: &[M])
+    where
+        V: Into<Self::Expr> + Clone,
+        M: Into<Self::Expr> + Clone,
+    {
+        assert_eq!(row.len(), 2 + NUM_BITWISE_OPS);
+        assert_eq!(multiplicities.len(), NUM_BITWISE_OPS);
+        for ((op, output), multiplicity) in BitwiseOp::ALL
+            .into_iter()
+            .zip(&row[2..])
+            .zip(multiplicities)
+        {
+            self.receive(
+                bus,
+                [
+                    op_expr::<Self::Expr>(op),
+                    row[0].clone().into(),
+                    row[1].clone().into(),
+                    output.clone().into(),
+                ],
+                multiplicity.clone(),
+            );
+        }
+    }
+}
+
+impl<AB: AirBuilderWithLookups> BitwiseBuilder for AB {}
+
+fn op_expr<Expr: AbstractField>(op: BitwiseOp) -> Expr {
+    Expr::from_canonical_usize(op as usize)
+}
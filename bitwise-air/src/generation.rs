@@ -0,0 +1,166 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+
+use crate::{BitwiseAir, BitwiseOp, BITWISE_OP_WIDTH, NUM_BITWISE_OPS};
+
+/// The table of every operation on every pair of limbs of `bits` bits. The row of `a` and `b` is
+/// `(a << bits) | b`, and holds `[a, b]` followed by the output of each operation in the order of
+/// `BitwiseOp::ALL`.
+pub fn bitwise_table<F: Field>(bits: usize) -> RowMajorMatrix<F> {
+    let width = 2 + NUM_BITWISE_OPS;
+    let mut values = Vec::with_capacity((1 << (2 * bits)) * width);
+    for a in 0..1 << bits {
+        for b in 0..1 << bits {
+            values.extend([a, b].map(F::from_canonical_u32));
+            values.extend(BitwiseOp::ALL.map(|op| F::from_canonical_u32(op.eval(a, b, bits))));
+        }
+    }
+    RowMajorMatrix::new(values, width)
+}
+
+/// The number of times each operation on each row of a bitwise table of limbs of `bits` bits is
+/// looked up, for the multiplicities a table receives with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitwiseCounts {
+    bits: usize,
+    counts: Vec<[u32; NUM_BITWISE_OPS]>,
+}
+
+impl BitwiseCounts {
+    pub fn new(bits: usize) -> Self {
+        Self {
+            bits,
+            counts: vec![[0; NUM_BITWISE_OPS]; 1 << (2 * bits)],
+        }
+    }
+
+    /// Count a lookup of `op` on `a` and `b`, which must be limbs of the table, and return its
+    /// output.
+    pub fn add(&mut self, op: BitwiseOp, a: u32, b: u32) -> u32 {
+        let len = 1u32 << self.bits;
+        assert!(
+            a < len && b < len,
+            "({a}, {b}) is out of the range 0..{len}"
+        );
+        let row = self.row(a, b);
+        self.counts[row][op as usize] += 1;
+        op.eval(a, b, self.bits)
+    }
+
+    pub fn extend<I: IntoIterator<Item = (BitwiseOp, u32, u32)>>(&mut self, ops: I) {
+        for (op, a, b) in ops {
+            self.add(op, a, b);
+        }
+    }
+
+    pub fn count(&self, op: BitwiseOp, a: u32, b: u32) -> u32 {
+        self.counts[self.row(a, b)][op as usize]
+    }
+
+    /// Count the lookups of `BitwiseBuilder::rotate_left` rotating `limbs` by `rotation` bits, and
+    /// return the shifted limbs it takes as witnesses.
+    pub fn rotate_left(&mut self, limbs: &[u32], rotation: usize) -> Vec<u32> {
+        let shift = (rotation % self.bits) as u32;
+        limbs
+            .iter()
+            .map(|&limb| self.add(BitwiseOp::Shl, limb, shift))
+            .collect()
+    }
+
+    /// Count the lookups of `BitwiseBuilder::shift_right` shifting `limbs` right by `shift` bits,
+    /// and return the low bits of each limb it takes as witnesses.
+    pub fn shift_right(&mut self, limbs: &[u32], shift: usize) -> Vec<u32> {
+        let mask = (1 << (shift % self.bits)) - 1;
+        limbs
+            .iter()
+            .map(|&limb| self.add(BitwiseOp::And, limb, mask))
+            .collect()
+    }
+
+    /// The multiplicities of each row of the table, in the order of `BitwiseOp::ALL`.
+    pub fn multiplicities<F: Field>(&self) -> RowMajorMatrix<F> {
+        let values = self
+            .counts
+            .iter()
+            .flat_map(|row| row.map(F::from_canonical_u32))
+            .collect();
+        RowMajorMatrix::new(values, NUM_BITWISE_OPS)
+    }
+
+    fn row(&self, a: u32, b: u32) -> usize {
+        ((a as usize) << self.bits) | b as usize
+    }
+}
+
+/// The limbs of the word given by `limbs` of `bits` bits each, least significant first, rotated
+/// left by `rotation` bits.
+pub fn rotate_limbs_left(limbs: &[u32], bits: usize, rotation: usize) -> Vec<u32> {
+    let n = limbs.len();
+    let mask = (1 << bits) - 1;
+    let (limb_rotation, bit_rotation) = ((rotation / bits) % n, rotation % bits);
+    let mut output = vec![0; n];
+    for i in 0..n {
+        let carry = if bit_rotation == 0 {
+            0
+        } else {
+            limbs[(i + n - 1) % n] >> (bits - bit_rotation)
+        };
+        output[(i + limb_rotation) % n] = ((limbs[i] << bit_rotation) & mask) | carry;
+    }
+    output
+}
+
+/// The limbs of the word given by `limbs` of `bits` bits each, least significant first, shifted
+/// right by `shift` bits.
+pub fn shift_limbs_right(limbs: &[u32], bits: usize, shift: usize) -> Vec<u32> {
+    let mask = (1 << bits) - 1;
+    let (limb_shift, bit_shift) = (shift / bits, shift % bits);
+    let limb = |i: usize| limbs.get(i).copied().unwrap_or(0);
+    (0..limbs.len())
+        .map(|i| {
+            let j = i + limb_shift;
+            (limb(j) >> bit_shift) | ((limb(j + 1) << (bits - bit_shift)) & mask)
+        })
+        .collect()
+}
+
+/// The main trace of `air` checking `ops`, each of which is an operation and its two inputs. The
+/// rows after the last operation are filled with XORs of zeros, which are also checked.
+pub fn generate_bitwise_trace<F: Field>(
+    air: &BitwiseAir,
+    ops: &[(BitwiseOp, u32, u32)],
+) -> RowMajorMatrix<F> {
+    let height = air.height();
+    let num_ops = air.num_ops;
+    assert!(
+        ops.len() <= height * num_ops,
+        "{} operations don't fit in {height} rows of {num_ops}",
+        ops.len()
+    );
+
+    let mut padded = ops.to_vec();
+    padded.resize(height * num_ops, (BitwiseOp::Xor, 0, 0));
+    let mut counts = BitwiseCounts::new(air.bits);
+    let outputs: Vec<u32> = padded
+        .iter()
+        .map(|&(op, a, b)| counts.add(op, a, b))
+        .collect();
+
+    let width = num_ops * BITWISE_OP_WIDTH + NUM_BITWISE_OPS;
+    let mut trace = Vec::with_capacity(height * width);
+    let multiplicities = counts.multiplicities::<F>();
+    for ((row, outputs), multiplicities) in padded
+        .chunks_exact(num_ops)
+        .zip(outputs.chunks_exact(num_ops))
+        .zip(multiplicities.values.chunks_exact(NUM_BITWISE_OPS))
+    {
+        for (&(op, a, b), &output) in row.iter().zip(outputs) {
+            trace.extend([op as u32, a, b, output].map(F::from_canonical_u32));
+        }
+        trace.extend_from_slice(multiplicities);
+    }
+    RowMajorMatrix::new(trace, width)
+}
@@ -0,0 +1,20 @@
+//! An AIR for bitwise operations on limbs of a few bits, such as bytes, against a table of every
+//! operation on every pair of limbs, with helpers for other AIRs to check XORs, ANDs, ORs, shifts
+//! and rotations of limb-sliced words with lookups into it.
+
+#![no_std]
+
+extern crate alloc;
+
+mod air;
+mod builder;
+mod generation;
+mod op;
+
+pub use air::*;
+pub use builder::*;
+pub use generation::*;
+pub use op::*;
+
+/// The lookup bus `BitwiseAir::u8` uses.
+pub const BITWISE_BUS: usize = 1;
@@ -0,0 +1,32 @@
+/// An operation of the bitwise table, on two limbs of `bits` bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BitwiseOp {
+    Xor = 0,
+    And = 1,
+    Or = 2,
+    /// `a << b`, truncated to `bits` bits, which is zero if `b >= bits`.
+    Shl = 3,
+}
+
+impl BitwiseOp {
+    pub const ALL: [Self; NUM_BITWISE_OPS] = [Self::Xor, Self::And, Self::Or, Self::Shl];
+
+    /// The output of this operation on limbs of `bits` bits.
+    pub const fn eval(self, a: u32, b: u32, bits: usize) -> u32 {
+        let mask = (1 << bits) - 1;
+        match self {
+            Self::Xor => a ^ b,
+            Self::And => a & b,
+            Self::Or => a | b,
+            Self::Shl => {
+                if b as usize >= bits {
+                    0
+                } else {
+                    (a << b) & mask
+                }
+            }
+        }
+    }
+}
+
+pub const NUM_BITWISE_OPS: usize = 4;
@@ -0,0 +1,231 @@
+use p3_air::{Air, AirBuilderWithLookups, BaseAir, PairBuilder};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_bitwise_air::{
+    bitwise_table, generate_bitwise_trace, rotate_limbs_left, shift_limbs_right, BitwiseAir,
+    BitwiseBuilder, BitwiseCounts, BitwiseOp, BITWISE_BUS, NUM_BITWISE_OPS,
+};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove_with_preprocessed, setup_preprocessed, verify_with_preprocessed, StarkConfig,
+};
+use rand::{thread_rng, Rng};
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+/// Limbs of 4 bits, so that the table has 256 rows.
+const BITS: usize = 4;
+
+fn prove_and_verify_bitwise(air: &BitwiseAir, trace: RowMajorMatrix<Val>) {
+    let (config, perm) = setup();
+    let preprocessed = setup_preprocessed(&config, air).unwrap();
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_preprocessed(
+        &config,
+        air,
+        &mut challenger,
+        trace,
+        &vec![],
+        Some(&preprocessed),
+    );
+
+    let mut challenger = Challenger::new(perm);
+    let vk = preprocessed.verifier_key();
+    verify_with_preprocessed(&config, air, &mut challenger, &proof, &vec![], Some(&vk))
+        .expect("verification failed");
+}
+
+#[test]
+fn test_bitwise_ops() {
+    let air = BitwiseAir::new(BITS, 2, BITWISE_BUS);
+    let mut rng = thread_rng();
+    // Fewer operations than fit, so the last rows are padding.
+    let ops: Vec<_> = (0..400)
+        .map(|i| {
+            let op = BitwiseOp::ALL[i % NUM_BITWISE_OPS];
+            (op, rng.gen_range(0..1 << BITS), rng.gen_range(0..1 << BITS))
+        })
+        .collect();
+    let trace = generate_bitwise_trace(&air, &ops);
+    assert_eq!((trace.width(), trace.height()), (12, 256));
+    prove_and_verify_bitwise(&air, trace);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "the lookups aren't balanced")]
+fn test_wrong_output() {
+    let air = BitwiseAir::new(BITS, 1, BITWISE_BUS);
+    let mut trace = generate_bitwise_trace::<Val>(&air, &[(BitwiseOp::And, 0b1100, 0b1010)]);
+    // The output column of the first operation, which should be 0b1000.
+    trace.values[3] = Val::from_canonical_u32(0b1110);
+    prove_and_verify_bitwise(&air, trace);
+}
+
+/// Rotates a 16-bit word, split into four limbs of 4 bits, left by `ROTATION` bits, with the
+/// bitwise table in the same AIR.
+///
+/// The columns are the limbs of the word, the shifted limbs, the limbs of the rotated word, and
+/// the multiplicities of the table.
+struct RotateAir;
+
+const NUM_LIMBS: usize = 4;
+const ROTATION: usize = 7;
+
+impl<F: Field> BaseAir<F> for RotateAir {
+    fn width(&self) -> usize {
+        3 * NUM_LIMBS + NUM_BITWISE_OPS
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(bitwise_table(BITS))
+    }
+}
+
+impl<AB: PairBuilder + AirBuilderWithLookups> Air<AB> for RotateAir {
+    fn eval(&self, builder: &mut AB) {
+        let table: Vec<AB::Var> = builder.preprocessed().row_slice(0).to_vec();
+        let main = builder.main();
+        let local: Vec<AB::Var> = main.row_slice(0).to_vec();
+        let (limbs, rest) = local.split_at(NUM_LIMBS);
+        let (shifted, rest) = rest.split_at(NUM_LIMBS);
+        let (rotated, multiplicities) = rest.split_at(NUM_LIMBS);
+
+        let output = builder.rotate_left(BITWISE_BUS, BITS, limbs, shifted, ROTATION);
+        for (output, &rotated) in output.into_iter().zip(rotated) {
+            builder.assert_eq(output, rotated);
+        }
+        builder.bitwise_table(BITWISE_BUS, &table, multiplicities);
+    }
+}
+
+fn to_limbs(word: u32) -> Vec<u32> {
+    (0..NUM_LIMBS).map(|i| (word >> (BITS * i)) & 0xf).collect()
+}
+
+#[test]
+fn test_rotate_left() {
+    let (config, perm) = setup();
+    let air = RotateAir;
+    let mut rng = thread_rng();
+    let mut counts = BitwiseCounts::new(BITS);
+
+    let height = 1 << (2 * BITS);
+    let mut rows = Vec::with_capacity(height);
+    for _ in 0..height {
+        let word: u16 = rng.gen();
+        let limbs = to_limbs(word as u32);
+        let shifted = counts.rotate_left(&limbs, ROTATION);
+        let rotated = rotate_limbs_left(&limbs, BITS, ROTATION);
+        assert_eq!(rotated, to_limbs(word.rotate_left(ROTATION as u32) as u32));
+        rows.push([limbs, shifted, rotated].concat());
+    }
+    let multiplicities = counts.multiplicities::<Val>();
+    let values = rows
+        .into_iter()
+        .zip(multiplicities.rows())
+        .flat_map(|(row, multiplicities)| {
+            row.into_iter()
+                .map(Val::from_canonical_u32)
+                .chain(multiplicities)
+        })
+        .collect();
+    let trace = RowMajorMatrix::new(values, BaseAir::<Val>::width(&air));
+    let preprocessed = setup_preprocessed(&config, &air).unwrap();
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_preprocessed(
+        &config,
+        &air,
+        &mut challenger,
+        trace,
+        &vec![],
+        Some(&preprocessed),
+    );
+
+    let mut challenger = Challenger::new(perm);
+    let vk = preprocessed.verifier_key();
+    verify_with_preprocessed(&config, &air, &mut challenger, &proof, &vec![], Some(&vk))
+        .expect("verification failed");
+}
+
+#[test]
+fn test_shift_limbs_right() {
+    let mut rng = thread_rng();
+    let mut counts = BitwiseCounts::new(BITS);
+    for shift in 0..16 {
+        let word: u16 = rng.gen();
+        let limbs = to_limbs(word as u32);
+        let shifted = shift_limbs_right(&limbs, BITS, shift);
+        assert_eq!(shifted, to_limbs((word >> shift) as u32));
+
+        let mask = (1 << (shift % BITS)) - 1;
+        let low: Vec<u32> = limbs.iter().map(|limb| limb & mask).collect();
+        assert_eq!(counts.shift_right(&limbs, shift), low);
+    }
+}
+
+#[test]
+fn test_counts() {
+    let mut counts = BitwiseCounts::new(BITS);
+    assert_eq!(counts.add(BitwiseOp::Xor, 0b1100, 0b1010), 0b0110);
+    assert_eq!(counts.add(BitwiseOp::Xor, 0b1100, 0b1010), 0b0110);
+    assert_eq!(counts.add(BitwiseOp::Shl, 0b1011, 2), 0b1100);
+    assert_eq!(counts.add(BitwiseOp::Shl, 0b1011, 4), 0);
+    assert_eq!(counts.count(BitwiseOp::Xor, 0b1100, 0b1010), 2);
+    assert_eq!(counts.count(BitwiseOp::And, 0b1100, 0b1010), 0);
+
+    let multiplicities = counts.multiplicities::<Val>();
+    let row = (0b1100 << BITS) | 0b1010;
+    assert_eq!(multiplicities.row_slice(row)[0], Val::TWO);
+}
+
+#[test]
+#[should_panic(expected = "(16, 0) is out of the range 0..16")]
+fn test_counts_out_of_range() {
+    BitwiseCounts::new(BITS).add(BitwiseOp::Or, 16, 0);
+}
@@ -30,7 +30,8 @@ impl<AF: AbstractField<F = Bn254Fr>> DiffusionPermutation<AF, 3> for DiffusionMa
 #[cfg(test)]
 mod tests {
     use ff::PrimeField;
-    use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixHL};
+    use num_bigint::BigUint;
+    use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixHL, Poseidon2Spec};
     use rand::Rng;
     use zkhash::ark_ff::{BigInteger, PrimeField as ark_PrimeField};
     use zkhash::fields::bn256::FpBN256 as ark_FpBN256;
@@ -137,4 +138,62 @@ mod tests {
 
         assert_eq!(output, expected);
     }
+
+    #[test]
+    fn test_poseidon2_bn254_from_spec() {
+        type F = Bn254Fr;
+        type Perm = Poseidon2<F, Poseidon2ExternalMatrixHL, DiffusionMatrixBN254, 3, 5>;
+        const ROUNDS_F: usize = 8;
+        const ROUNDS_P: usize = 56;
+
+        let to_biguint = |x: &ark_FpBN256| BigUint::from_bytes_le(&x.into_bigint().to_bytes_le());
+        let mut rows = RC3
+            .iter()
+            .map(|row| row.iter().map(to_biguint).collect::<Vec<_>>());
+        let mut external_constants = rows.by_ref().take(ROUNDS_F / 2).collect::<Vec<_>>();
+        let internal_constants = rows
+            .by_ref()
+            .take(ROUNDS_P)
+            .map(|row| row[0].clone())
+            .collect();
+        external_constants.extend(rows);
+        let spec = Poseidon2Spec {
+            width: 3,
+            sbox_degree: 5,
+            rounds_f: ROUNDS_F,
+            rounds_p: ROUNDS_P,
+            external_constants,
+            internal_constants,
+        };
+
+        // The constants are written as hex strings, as they're too large for JSON integers.
+        let json = serde_json::to_string(&spec).unwrap();
+        let spec: Poseidon2Spec = serde_json::from_str(&json).unwrap();
+        let poseidon2 =
+            Perm::from_spec(&spec, Poseidon2ExternalMatrixHL, DiffusionMatrixBN254).unwrap();
+        assert_eq!(poseidon2.to_spec(), spec);
+
+        // The known answer of the reference implementation.
+        let output = poseidon2.permute([F::ZERO, F::ONE, F::TWO]);
+        let expected = [
+            "0bb61d24daca55eebcb1929a82650f328134334da98ea4f847f760054f4a3033",
+            "303b6f7c86d043bfcbcc80214f26a30277a15d3f74ca654992defe7ff8d03570",
+            "1ed25194542b12eef8617361c3ba7c52e660b145994427cc86296242cf766ec8",
+        ]
+        .map(|hex| BigUint::parse_bytes(hex.as_bytes(), 16).unwrap());
+        assert_eq!(
+            output.map(|x| p3_field::PrimeField::as_canonical_biguint(&x)),
+            expected
+        );
+
+        let input = [0, 1, 2].map(|i| ark_FpBN256::from(i as u64));
+        let output_ref = Poseidon2Ref::new(&POSEIDON2_BN256_PARAMS).permutation(&input);
+        assert_eq!(
+            output.to_vec(),
+            output_ref
+                .into_iter()
+                .map(bn254_from_ark_ff)
+                .collect::<Vec<_>>()
+        );
+    }
 }
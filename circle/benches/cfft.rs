@@ -1,7 +1,7 @@
 use criterion::measurement::Measurement;
 use criterion::{criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion};
 use p3_baby_bear::BabyBear;
-use p3_circle::{CircleDomain, CircleEvaluations};
+use p3_circle::{CircleDomain, CircleEvaluations, CircleTwiddleCache};
 use p3_dft::{Radix2Bowers, Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
 use p3_field::TwoAdicField;
 use p3_matrix::dense::RowMajorMatrix;
@@ -42,6 +42,45 @@ fn lde_cfft<M: Measurement>(g: &mut BenchmarkGroup<M>, log_n: usize, log_w: usiz
     );
 }
 
+/// Repeated LDEs over the same domains, as when committing to several traces and the quotient in
+/// a proof, with the twiddle cache kept warm between them or cleared before each.
+fn bench_repeated_lde(c: &mut Criterion) {
+    type F = Mersenne31;
+    let log_n = 16;
+    let log_w = 6;
+    let m = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, 1 << log_w);
+
+    let mut g = c.benchmark_group("repeated_lde");
+    g.sample_size(10);
+    for clear in [false, true] {
+        let name = if clear {
+            "cold twiddles"
+        } else {
+            "warm twiddles"
+        };
+        g.bench_with_input(
+            BenchmarkId::new(name, format!("log_n={log_n},log_w={log_w}")),
+            &m,
+            |b, m| {
+                b.iter_batched(
+                    || {
+                        if clear {
+                            CircleTwiddleCache::<F>::global().clear();
+                        }
+                        m.clone()
+                    },
+                    |m| {
+                        let evals =
+                            CircleEvaluations::from_natural_order(CircleDomain::standard(log_n), m);
+                        evals.extrapolate(CircleDomain::standard(log_n + 1))
+                    },
+                    criterion::BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+}
+
 fn lde_twoadic<F: TwoAdicField, Dft: TwoAdicSubgroupDft<F>, M: Measurement>(
     g: &mut BenchmarkGroup<M>,
     log_n: usize,
@@ -67,5 +106,5 @@ fn lde_twoadic<F: TwoAdicField, Dft: TwoAdicSubgroupDft<F>, M: Measurement>(
     );
 }
 
-criterion_group!(benches, bench_lde);
+criterion_group!(benches, bench_lde, bench_repeated_lde);
 criterion_main!(benches);
@@ -0,0 +1,125 @@
+//! Measures how the circle FFT and the `CirclePcs` prover scale with the number of threads.
+//!
+//! Run with `cargo bench -p p3-circle --features parallel --bench circle_prover`; without the
+//! `parallel` feature every thread count runs serially.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use p3_challenger::{CanObserve, FieldChallenger, HashChallenger, SerializingChallenger32};
+use p3_circle::{CircleDomain, CircleEvaluations, CirclePcs};
+use p3_commit::{ExtensionMmcs, Pcs};
+use p3_fri::{FriConfig, SecurityAssumption};
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_mersenne_31::{Mersenne31, Qm31};
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use rayon::ThreadPoolBuilder;
+
+type Val = Mersenne31;
+type Challenge = Qm31;
+
+type ByteHash = Keccak256Hash;
+type FieldHash = SerializingHasher32<ByteHash>;
+type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+type MyPcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+
+const NUM_COLS: usize = 16;
+const THREAD_COUNTS: [usize; 6] = [1, 2, 4, 8, 16, 32];
+
+fn thread_counts() -> impl Iterator<Item = usize> {
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    THREAD_COUNTS.into_iter().filter(move |&n| n <= max_threads)
+}
+
+fn bench_cfft_lde(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+
+    let mut group = c.benchmark_group("circle_lde");
+    group.sample_size(10);
+
+    for log_n in [16, 18, 20] {
+        let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, NUM_COLS);
+
+        for num_threads in thread_counts() {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(format!("log_n={log_n}"), num_threads),
+                &num_threads,
+                |b, _| {
+                    b.iter_batched(
+                        || evals.clone(),
+                        |evals| {
+                            pool.install(|| {
+                                CircleEvaluations::from_natural_order(
+                                    CircleDomain::standard(log_n),
+                                    evals,
+                                )
+                                .extrapolate(CircleDomain::standard(log_n + 1))
+                            })
+                        },
+                        BatchSize::LargeInput,
+                    )
+                },
+            );
+        }
+    }
+}
+
+fn bench_circle_pcs_open(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let val_mmcs = ValMmcs::new(FieldHash::new(ByteHash {}), MyCompress::new(ByteHash {}));
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 100,
+        proof_of_work_bits: 0,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+    };
+    let pcs = MyPcs::new(val_mmcs, fri_config);
+    let challenger = Challenger::from_hasher(vec![], ByteHash {});
+
+    let mut group = c.benchmark_group("circle_pcs_open");
+    group.sample_size(10);
+
+    for log_n in [16, 18, 20] {
+        let domain =
+            <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_n);
+        let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, NUM_COLS);
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+        for num_threads in thread_counts() {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(format!("log_n={log_n}"), num_threads),
+                &num_threads,
+                |b, _| {
+                    b.iter(|| {
+                        pool.install(|| {
+                            let mut challenger = challenger.clone();
+                            challenger.observe(commit.clone());
+                            let zeta: Challenge = challenger.sample_ext_element();
+                            pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger)
+                        })
+                    })
+                },
+            );
+        }
+    }
+}
+
+criterion_group!(benches, bench_cfft_lde, bench_circle_pcs_open);
+criterion_main!(benches);
@@ -5,7 +5,7 @@ use itertools::{iterate, izip, Itertools};
 use p3_commit::PolynomialSpace;
 use p3_dft::{divide_by_height, Butterfly, DifButterfly, DitButterfly};
 use p3_field::extension::ComplexExtendable;
-use p3_field::{batch_multiplicative_inverse, ExtensionField, Field};
+use p3_field::{ExtensionField, Field};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
@@ -14,7 +14,7 @@ use tracing::{debug_span, instrument};
 
 use crate::domain::CircleDomain;
 use crate::point::{compute_lagrange_den_batched, Point};
-use crate::{cfft_permute_index, cfft_permute_slice, CfftPermutable, CfftView};
+use crate::{cfft_permute_index, CfftPermutable, CfftView, CircleTwiddleCache};
 
 #[derive(Clone)]
 pub struct CircleEvaluations<F, M = RowMajorMatrix<F>> {
@@ -47,17 +47,8 @@ impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
         let CircleEvaluations { domain, values } = self;
         let mut values = debug_span!("to_rmm").in_scope(|| values.to_row_major_matrix());
 
-        let mut twiddles = debug_span!("twiddles").in_scope(|| {
-            compute_twiddles(domain)
-                .into_iter()
-                .map(|ts| {
-                    batch_multiplicative_inverse(&ts)
-                        .into_iter()
-                        .map(|t| DifButterfly(t))
-                        .collect_vec()
-                })
-                .peekable()
-        });
+        let inverse_twiddles = CircleTwiddleCache::global().inverse_twiddles(domain);
+        let mut twiddles = inverse_twiddles.iter().peekable();
 
         assert_eq!(twiddles.len(), domain.log_n);
 
@@ -75,14 +66,14 @@ impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
                             let twiddle_chunk_sz = ts.len() / min_blks;
                             let twiddle_chunk = &ts
                                 [(twiddle_chunk_sz * chunk_i)..(twiddle_chunk_sz * (chunk_i + 1))];
-                            serial_layer(submat.values, twiddle_chunk);
+                            serial_layer(submat.values, twiddle_chunk, DifButterfly);
                         }
                     });
             });
         }
 
         for ts in twiddles {
-            par_within_blk_layer(&mut values.values, &ts);
+            par_within_blk_layer(&mut values.values, ts, DifButterfly);
         }
 
         // TODO: omit this?
@@ -103,11 +94,11 @@ impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
         // Compute z_H
         let lagrange_num = self.domain.zeroifier(point);
 
-        // Permute the domain to get it into the right format.
-        let permuted_points = cfft_permute_slice(&self.domain.points().collect_vec());
+        // The points in the same order as the evaluations.
+        let points = self.domain.cfft_points();
 
         // Compute the lagrange denominators. This is batched as it lets us make use of batched_multiplicative_inverse.
-        let lagrange_den = compute_lagrange_den_batched(&permuted_points, point, self.domain.log_n);
+        let lagrange_den = compute_lagrange_den_batched(&points, point, self.domain.log_n);
 
         // The columnwise_dot_product here consumes about 5% of the runtime for example prove_poseidon2_m31_keccak.
         // Definately something worth optimising further.
@@ -147,25 +138,26 @@ impl<F: ComplexExtendable> CircleEvaluations<F, RowMajorMatrix<F>> {
             // both `x_1` and `x_2` are set to `x_1`).
             // So instead we directly repeat the coeffs and skip the initial layers.
             debug_span!("extend coeffs").in_scope(|| {
-                coeffs.values.reserve(domain.size() * coeffs.width());
-                for _ in log_n..domain.log_n {
-                    coeffs.values.extend_from_within(..);
-                }
+                let len = coeffs.values.len();
+                coeffs
+                    .values
+                    .resize(domain.size() * coeffs.width(), F::ZERO);
+                let (head, tail) = coeffs.values.split_at_mut(len);
+                tail.par_chunks_exact_mut(len)
+                    .for_each(|copy| copy.copy_from_slice(head));
             });
         }
         assert_eq!(coeffs.height(), 1 << domain.log_n);
 
-        let mut twiddles = debug_span!("twiddles").in_scope(|| {
-            compute_twiddles(domain)
-                .into_iter()
-                .map(|ts| ts.into_iter().map(|t| DitButterfly(t)).collect_vec())
-                .rev()
-                .skip(domain.log_n - log_n)
-                .peekable()
-        });
+        let all_twiddles = CircleTwiddleCache::global().twiddles(domain);
+        let mut twiddles = all_twiddles
+            .iter()
+            .rev()
+            .skip(domain.log_n - log_n)
+            .peekable();
 
         for ts in twiddles.peeking_take_while(|ts| ts.len() < desired_num_jobs()) {
-            par_within_blk_layer(&mut coeffs.values, &ts);
+            par_within_blk_layer(&mut coeffs.values, ts, DitButterfly);
         }
 
         let par_twiddles = twiddles.collect_vec();
@@ -180,7 +172,7 @@ impl<F: ComplexExtendable> CircleEvaluations<F, RowMajorMatrix<F>> {
                             let twiddle_chunk_sz = ts.len() / min_blks;
                             let twiddle_chunk = &ts
                                 [(twiddle_chunk_sz * chunk_i)..(twiddle_chunk_sz * (chunk_i + 1))];
-                            serial_layer(submat.values, twiddle_chunk);
+                            serial_layer(submat.values, twiddle_chunk, DitButterfly);
                         }
                     });
             });
@@ -190,20 +182,35 @@ impl<F: ComplexExtendable> CircleEvaluations<F, RowMajorMatrix<F>> {
     }
 }
 
+/// Applies one layer of butterflies, `butterfly(twiddles[i])` on the `i`th block, in serial.
+///
+/// Butterflies are made from the cached twiddles as they're applied, so no per-call tables are
+/// allocated.
 #[inline]
-fn serial_layer<F: Field, B: Butterfly<F>>(values: &mut [F], twiddles: &[B]) {
+fn serial_layer<F: Field, B: Butterfly<F>>(
+    values: &mut [F],
+    twiddles: &[F],
+    butterfly: impl Fn(F) -> B,
+) {
     let blk_sz = values.len() / twiddles.len();
     for (&t, blk) in izip!(twiddles, values.chunks_exact_mut(blk_sz)) {
         let (lo, hi) = blk.split_at_mut(blk_sz / 2);
-        t.apply_to_rows(lo, hi);
+        butterfly(t).apply_to_rows(lo, hi);
     }
 }
 
+/// Like `serial_layer`, for layers with fewer blocks than threads, so each block is split into
+/// jobs spanning whole rows and parts of rows alike, which are run in parallel.
 #[inline]
 #[instrument(level = "debug", skip_all, fields(log_blks = log2_strict_usize(twiddles.len())))]
-fn par_within_blk_layer<F: Field, B: Butterfly<F>>(values: &mut [F], twiddles: &[B]) {
+fn par_within_blk_layer<F: Field, B: Butterfly<F>>(
+    values: &mut [F],
+    twiddles: &[F],
+    butterfly: impl Fn(F) -> B,
+) {
     let blk_sz = values.len() / twiddles.len();
     for (&t, blk) in izip!(twiddles, values.chunks_exact_mut(blk_sz)) {
+        let t = butterfly(t);
         let (lo, hi) = blk.split_at_mut(blk_sz / 2);
         let job_sz = core::cmp::max(1, lo.len() >> log2_ceil_usize(desired_num_jobs()));
         lo.par_chunks_mut(job_sz)
@@ -241,27 +248,6 @@ impl<F: ComplexExtendable> CircleDomain<F> {
     }
 }
 
-fn compute_twiddles<F: ComplexExtendable>(domain: CircleDomain<F>) -> Vec<Vec<F>> {
-    assert!(domain.log_n >= 1);
-    let mut pts = domain.coset0().collect_vec();
-    reverse_slice_index_bits(&mut pts);
-    let mut twiddles = vec![pts.iter().map(|p| p.y).collect_vec()];
-    if domain.log_n >= 2 {
-        twiddles.push(pts.iter().step_by(2).map(|p| p.x).collect_vec());
-        for i in 0..(domain.log_n - 2) {
-            let prev = twiddles.last().unwrap();
-            assert_eq!(prev.len(), 1 << (domain.log_n - 2 - i));
-            let cur = prev
-                .iter()
-                .step_by(2)
-                .map(|x| x.square().double() - F::ONE)
-                .collect_vec();
-            twiddles.push(cur);
-        }
-    }
-    twiddles
-}
-
 pub fn circle_basis<F: Field>(p: Point<F>, log_n: usize) -> Vec<F> {
     let mut b = vec![F::ONE, p.y];
     let mut x = p.x;
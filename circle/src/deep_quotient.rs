@@ -8,9 +8,9 @@ use p3_maybe_rayon::prelude::*;
 use p3_util::log2_strict_usize;
 use tracing::instrument;
 
-use crate::domain::CircleDomain;
+use crate::domain::{forward_backward_index, CircleDomain};
 use crate::point::Point;
-use crate::{cfft_permute_slice, CircleEvaluations};
+use crate::{cfft_permute_index, CircleEvaluations};
 
 /// Compute numerator and denominator of the "vanishing part" of the DEEP quotient
 /// Section 6, Remark 21 of Circle Starks (page 30 of first edition PDF)
@@ -46,31 +46,71 @@ pub(crate) fn deep_quotient_reduce_row<F: ComplexExtendable, EF: ExtensionField<
 
 impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
     /// Same as `deep_quotient_reduce_row`, but reduces a whole matrix into a column, taking advantage of batch inverses.
-    #[instrument(skip_all, fields(dims = %self.values.dimensions()))]
+    #[cfg(test)]
     pub(crate) fn deep_quotient_reduce<EF: ExtensionField<F>>(
         &self,
         alpha: EF,
         zeta: Point<EF>,
         ps_at_zeta: &[EF],
     ) -> Vec<EF> {
-        let alpha_pow_width = alpha.exp_u64(self.values.width() as u64);
-        let points = cfft_permute_slice(&self.domain.points().collect_vec());
-        let (vp_nums, vp_denoms): (Vec<_>, Vec<_>) = points
-            .into_iter()
-            .map(|x| deep_quotient_vanishing_part(x, zeta, alpha_pow_width))
-            .unzip();
-        let vp_denom_invs = batch_multiplicative_inverse(&vp_denoms);
+        let mut reduced = EF::zero_vec(self.values.height());
+        self.accumulate_deep_quotients(
+            alpha,
+            &[(zeta, ps_at_zeta.to_vec(), EF::ONE)],
+            &mut reduced,
+        );
+        reduced
+    }
 
-        let alpha_reduced_ps_at_zeta: EF = dot_product(alpha.powers(), ps_at_zeta.iter().copied());
+    /// Adds `alpha_offset * deep_quotient_reduce(alpha, zeta, ps_at_zeta)` to `acc`, for each
+    /// `(zeta, ps_at_zeta, alpha_offset)` in `openings`.
+    ///
+    /// Reducing each row with powers of alpha doesn't depend on the opening point, so however many
+    /// points the matrix is opened at, it's done once per row, and the quotients for every point are
+    /// accumulated in the same pass.
+    #[instrument(skip_all, fields(dims = %self.values.dimensions(), num_points = openings.len()))]
+    pub(crate) fn accumulate_deep_quotients<EF: ExtensionField<F>>(
+        &self,
+        alpha: EF,
+        openings: &[(Point<EF>, Vec<EF>, EF)],
+        acc: &mut [EF],
+    ) {
+        if openings.is_empty() {
+            return;
+        }
+        let alpha_pow_width = alpha.exp_u64(self.values.width() as u64);
+        let points = self.domain.cfft_points();
+
+        // For each point, the reduced opened values, and for each row the vanishing part scaled by
+        // the point's alpha offset.
+        let point_terms = openings
+            .iter()
+            .map(|(zeta, ps_at_zeta, alpha_offset)| {
+                let (vp_nums, vp_denoms): (Vec<_>, Vec<_>) = points
+                    .par_iter()
+                    .map(|&x| deep_quotient_vanishing_part(x, *zeta, alpha_pow_width))
+                    .unzip();
+                let vp_denom_invs = batch_multiplicative_inverse(&vp_denoms);
+                let vps: Vec<EF> = vp_nums
+                    .into_par_iter()
+                    .zip(vp_denom_invs)
+                    .map(|(vp_num, vp_denom_inv)| *alpha_offset * vp_num * vp_denom_inv)
+                    .collect();
+                let alpha_reduced_ps_at_zeta: EF =
+                    dot_product(alpha.powers(), ps_at_zeta.iter().copied());
+                (alpha_reduced_ps_at_zeta, vps)
+            })
+            .collect_vec();
 
         self.values
             .dot_ext_powers(alpha)
-            .zip(vp_nums.into_par_iter())
-            .zip(vp_denom_invs.into_par_iter())
-            .map(|((reduced_ps_at_x, vp_num), vp_denom_inv)| {
-                vp_num * vp_denom_inv * (reduced_ps_at_x - alpha_reduced_ps_at_zeta)
-            })
-            .collect()
+            .zip(acc.par_iter_mut())
+            .enumerate()
+            .for_each(|(i, (reduced_ps_at_x, ro))| {
+                for (alpha_reduced_ps_at_zeta, vps) in &point_terms {
+                    *ro += vps[i] * (reduced_ps_at_x - *alpha_reduced_ps_at_zeta);
+                }
+            });
     }
 }
 
@@ -96,25 +136,28 @@ pub fn extract_lambda<F: ComplexExtendable, EF: ExtensionField<F>>(
 
     // The unique values are repeated over the rest of the domain like
     // 0 1 2 .. n-1 n n n-1 .. 1 0 0 1 ..
-    let v_d = v_d_init
-        .iter()
-        .chain(v_d_init.iter().rev())
-        .cycle()
-        .copied();
+    // in natural order, which we index into in CFFT order, the order of `lde`.
+    let v_d: Vec<F> = (0..lde.len())
+        .into_par_iter()
+        .map(|i| {
+            v_d_init[forward_backward_index(cfft_permute_index(i, log_lde_size), v_d_init.len())]
+        })
+        .collect();
 
     // < v_d, v_d >
     // This formula was determined experimentally...
     let v_d_2 = F::TWO.exp_u64(log_lde_size as u64 - 1);
 
-    let v_d = v_d.take(lde.len()).collect_vec();
-    let v_d = cfft_permute_slice(&v_d);
+    let lambda = lde
+        .par_iter()
+        .zip(v_d.par_iter())
+        .map(|(&y, &v_x)| y * v_x)
+        .sum::<EF>()
+        * v_d_2.inverse();
 
-    let lambda =
-        dot_product::<EF, _, _>(lde.iter().copied(), v_d.iter().copied()) * v_d_2.inverse();
-
-    for (y, v_x) in izip!(lde, v_d) {
-        *y -= lambda * v_x;
-    }
+    lde.par_iter_mut()
+        .zip(v_d)
+        .for_each(|(y, v_x)| *y -= lambda * v_x);
 
     lambda
 }
@@ -130,6 +173,7 @@ mod tests {
     use rand::{random, thread_rng};
 
     use super::*;
+    use crate::cfft_permute_slice;
 
     type F = Mersenne31;
     type EF = BinomialExtensionField<F, 3>;
@@ -158,6 +202,37 @@ mod tests {
         assert_eq!(cfft_permute_slice(&mat_reduced), row_reduced);
     }
 
+    #[test]
+    fn accumulate_many_points_same_as_reduce_each() {
+        let domain = CircleDomain::standard(5);
+        let evals = CircleEvaluations::from_cfft_order(
+            domain,
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << domain.log_n, 1 << 3),
+        );
+
+        let alpha: EF = random();
+        let openings = (0..3)
+            .map(|_| {
+                let zeta: Point<EF> = Point::from_projective_line(random());
+                (zeta, evals.evaluate_at_point(zeta), random())
+            })
+            .collect_vec();
+
+        let mut accumulated = vec![EF::ZERO; 1 << domain.log_n];
+        evals.accumulate_deep_quotients(alpha, &openings, &mut accumulated);
+
+        let mut expected = vec![EF::ZERO; 1 << domain.log_n];
+        for (zeta, ps_at_zeta, alpha_offset) in &openings {
+            for (e, ro) in izip!(
+                &mut expected,
+                evals.deep_quotient_reduce(alpha, *zeta, ps_at_zeta)
+            ) {
+                *e += *alpha_offset * ro;
+            }
+        }
+        assert_eq!(accumulated, expected);
+    }
+
     #[test]
     fn reduce_evaluations_low_degree() {
         let log_n = 5;
@@ -7,10 +7,11 @@ use p3_field::extension::ComplexExtendable;
 use p3_field::ExtensionField;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
-use p3_util::{log2_ceil_usize, log2_strict_usize};
+use p3_util::{log2_ceil_usize, log2_strict_usize, reverse_slice_index_bits};
 use tracing::instrument;
 
 use crate::point::Point;
+use crate::CircleEvaluations;
 
 /// A twin-coset of the circle group on F. It has a power-of-two size and an arbitrary shift.
 ///
@@ -71,6 +72,13 @@ impl<F: ComplexExtendable> CircleDomain<F> {
     pub(crate) fn points(&self) -> impl Iterator<Item = Point<F>> {
         self.coset0().interleave(self.coset1())
     }
+    /// The points of the domain in CFFT order, i.e. the order of committed evaluations: the first
+    /// coset in bit-reversed order, with each point followed by its conjugate.
+    pub(crate) fn cfft_points(&self) -> Vec<Point<F>> {
+        let mut pts = self.coset0().collect_vec();
+        reverse_slice_index_bits(&mut pts);
+        pts.into_iter().flat_map(|p| [p, -p]).collect()
+    }
     pub(crate) fn nth_point(&self, idx: usize) -> Point<F> {
         let (idx, lsb) = (idx >> 1, idx & 1);
         if lsb == 0 {
@@ -117,6 +125,21 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
         }
     }
 
+    fn rotated_point<Ext: ExtensionField<Self::Val>>(&self, x: Ext, offset: isize) -> Option<Ext> {
+        if self.is_standard() {
+            let point = Point::from_projective_line(x);
+            let step = Point::generator(self.log_n) * offset.unsigned_abs();
+            let rotated = if offset < 0 {
+                point - step
+            } else {
+                point + step
+            };
+            Some(rotated.to_projective_line().unwrap())
+        } else {
+            None
+        }
+    }
+
     fn create_disjoint_domain(&self, min_size: usize) -> Self {
         // Right now we simply guarantee the domain is disjoint by returning a
         // larger standard position coset, which is fine because we always ask for a larger
@@ -171,6 +194,12 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
         self.zeroifier(Point::from_projective_line(point))
     }
 
+    /// A line through the last point meets the circle at a second point of the domain, so
+    /// `is_transition` is built from the vanishing polynomial, as the other selectors are.
+    fn has_linear_transition_selector(&self) -> bool {
+        false
+    }
+
     fn selectors_at_point<Ext: ExtensionField<Self::Val>>(
         &self,
         point: Ext,
@@ -204,9 +233,40 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
     // todo: batch inverses
     #[instrument(skip_all, fields(log_n = %coset.log_n))]
     fn selectors_on_coset(&self, coset: Self) -> LagrangeSelectors<Vec<Self::Val>> {
+        self.selectors_on_coset_for_height(coset, self.size())
+    }
+
+    fn selectors_at_point_for_height<Ext: ExtensionField<Self::Val>>(
+        &self,
+        point: Ext,
+        height: usize,
+    ) -> LagrangeSelectors<Ext> {
+        assert!(0 < height && height <= self.size());
+        if height == self.size() {
+            return self.selectors_at_point(point);
+        }
+        // Rows are only ordered by `next_point` in standard position.
+        assert!(self.is_standard());
+        let last = self.shift + Point::generator(self.log_n) * (height - 1);
+        let point = Point::from_projective_line(point);
+        LagrangeSelectors {
+            is_first_row: self.s_p(self.shift, point),
+            is_last_row: self.s_p(last, point),
+            is_transition: Ext::ONE
+                - self.s_p_normalized(last, point)
+                - self.s_p_normalized(-self.shift, point),
+            inv_zeroifier: self.zeroifier(point).inverse(),
+        }
+    }
+
+    fn selectors_on_coset_for_height(
+        &self,
+        coset: Self,
+        height: usize,
+    ) -> LagrangeSelectors<Vec<Self::Val>> {
         let sels = coset
             .points()
-            .map(|p| self.selectors_at_point(p.to_projective_line().unwrap()))
+            .map(|p| self.selectors_at_point_for_height(p.to_projective_line().unwrap(), height))
             .collect_vec();
         LagrangeSelectors {
             is_first_row: sels.iter().map(|s| s.is_first_row).collect(),
@@ -215,10 +275,80 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
             inv_zeroifier: sels.iter().map(|s| s.inv_zeroifier).collect(),
         }
     }
+
+    /// With `k = values.len()`, doubling the `i`-th point of a standard position domain of size
+    /// `n` `log(n / k)` times gives the `i % k`-th point of the standard position domain of size
+    /// `k`, so the column is `q` composed with that doubling, where `q` interpolates `values`
+    /// over the smaller domain.
+    fn periodic_column_at_point<Ext: ExtensionField<Self::Val>>(
+        &self,
+        values: &[Self::Val],
+        point: Ext,
+    ) -> Ext {
+        let log_period = log2_strict_usize(values.len());
+        assert!(log_period <= self.log_n);
+        assert!(
+            self.is_standard(),
+            "periodic columns are only supported over standard position domains"
+        );
+        let mut point = Point::from_projective_line(point);
+        for _ in log_period..self.log_n {
+            point = point.double();
+        }
+        interpolate_over_standard_domain(values, point)
+    }
+
+    /// The doubling of `coset`, if it's in standard position, repeats every
+    /// `coset.size() * k / n` points, so `q` is only interpolated at those.
+    fn periodic_column_on_coset(&self, values: &[Self::Val], coset: Self) -> Vec<Self::Val> {
+        let log_period = log2_strict_usize(values.len());
+        assert!(log_period <= self.log_n);
+        assert!(coset.log_n >= self.log_n);
+        assert!(
+            self.is_standard() && coset.is_standard(),
+            "periodic columns are only supported over standard position domains"
+        );
+        let log_repeats = self.log_n - log_period;
+        let evals = coset
+            .points()
+            .take(1 << (coset.log_n - log_repeats))
+            .map(|point| {
+                let point = (0..log_repeats).fold(point, |point, _| point.double());
+                interpolate_over_standard_domain(values, point)
+            })
+            .collect_vec();
+        evals.into_iter().cycle().take(coset.size()).collect()
+    }
+}
+
+/// The evaluation at `point` of the polynomial which takes the value `values[i]` at the `i`-th
+/// point of the standard position domain of size `values.len()`. `point` must be outside that
+/// domain.
+fn interpolate_over_standard_domain<F: ComplexExtendable, EF: ExtensionField<F>>(
+    values: &[F],
+    point: Point<EF>,
+) -> EF {
+    let log_k = log2_strict_usize(values.len());
+    if log_k == 0 {
+        return EF::from_base(values[0]);
+    }
+    if log_k == 1 {
+        // The two points are conjugates, so the interpolant is linear in `y`. `evaluate_at_point`
+        // needs a domain of at least 4 points.
+        let domain = CircleDomain::<F>::standard(1);
+        let (p0, p1) = (domain.nth_point(0), domain.nth_point(1));
+        let inv = (p0.y - p1.y).inverse();
+        return (point.y - p1.y) * (inv * values[0]) - (point.y - p0.y) * (inv * values[1]);
+    }
+    CircleEvaluations::from_natural_order(
+        CircleDomain::standard(log_k),
+        RowMajorMatrix::new_col(values.to_vec()),
+    )
+    .evaluate_at_point(point)[0]
 }
 
 // 0 1 2 .. len-1 len len len-1 .. 1 0 0 1 ..
-fn forward_backward_index(mut i: usize, len: usize) -> usize {
+pub(crate) fn forward_backward_index(mut i: usize, len: usize) -> usize {
     i %= 2 * len;
     if i < len {
         i
@@ -235,10 +365,10 @@ mod tests {
     use itertools::izip;
     use p3_field::{batch_multiplicative_inverse, AbstractField};
     use p3_mersenne_31::Mersenne31;
-    use rand::thread_rng;
+    use rand::{thread_rng, Rng};
 
     use super::*;
-    use crate::CircleEvaluations;
+    use crate::cfft_permute_slice;
 
     fn assert_is_twin_coset<F: ComplexExtendable>(d: CircleDomain<F>) {
         let pts = d.points().collect_vec();
@@ -265,6 +395,14 @@ mod tests {
         }
         assert_eq!(d.next_point(p1).unwrap(), p0);
 
+        // rotating by an offset is the same as stepping with next_point
+        let mut p2 = p0;
+        for i in 0..n {
+            assert_eq!(d.rotated_point(p0, i as isize).unwrap(), p2);
+            assert_eq!(d.rotated_point(p2, -(i as isize)).unwrap(), p0);
+            p2 = d.next_point(p2).unwrap();
+        }
+
         // .points() is the same as first_point -> next_point
         let mut uni_point = d.first_point();
         for p in d.points() {
@@ -396,9 +534,97 @@ mod tests {
         );
     }
 
+    #[test]
+    fn periodic_columns() {
+        type F = Mersenne31;
+        let log_n = 5;
+        let n = 1 << log_n;
+        let d = CircleDomain::<F>::standard(log_n);
+        let coset = d.create_disjoint_domain(4 * n);
+
+        for log_period in 0..=log_n {
+            let values: Vec<F> = (0..1 << log_period).map(|_| thread_rng().gen()).collect();
+
+            // The column is the low degree extension of `values` repeated over the domain.
+            let column = values.iter().copied().cycle().take(n).collect_vec();
+            let expected =
+                CircleEvaluations::from_natural_order(d, RowMajorMatrix::new_col(column))
+                    .extrapolate(coset)
+                    .to_natural_order()
+                    .to_row_major_matrix()
+                    .values;
+            assert_eq!(d.periodic_column_on_coset(&values, coset), expected);
+
+            for (p, &eval) in izip!(coset.points(), &expected).take(8) {
+                let at_point = d.periodic_column_at_point(&values, p.to_projective_line().unwrap());
+                assert_eq!(at_point, eval);
+            }
+        }
+    }
+
+    #[test]
+    fn selectors_for_height() {
+        type F = Mersenne31;
+        let log_n = 5;
+        let n = 1 << log_n;
+        let height = 21;
+
+        let d = CircleDomain::<F>::standard(log_n);
+        let coset = d.create_disjoint_domain(n);
+        let sels = d.selectors_on_coset_for_height(coset, height);
+
+        let mut pt = coset.first_point();
+        for i in 0..coset.size() {
+            let pt_sels = d.selectors_at_point_for_height(pt, height);
+            assert_eq!(sels.is_first_row[i], pt_sels.is_first_row);
+            assert_eq!(sels.is_last_row[i], pt_sels.is_last_row);
+            assert_eq!(sels.is_transition[i], pt_sels.is_transition);
+            assert_eq!(sels.inv_zeroifier[i], pt_sels.inv_zeroifier);
+            pt = coset.next_point(pt).unwrap();
+        }
+
+        let coset_to_d = |evals: &[F]| {
+            let evals = CircleEvaluations::from_natural_order(
+                coset,
+                RowMajorMatrix::new_col(evals.to_vec()),
+            );
+            let coeffs = evals.interpolate().to_row_major_matrix();
+            let (lo, hi) = coeffs.split_rows(n);
+            assert_eq!(hi.values, vec![F::ZERO; n]);
+            CircleEvaluations::evaluate(d, lo.to_row_major_matrix())
+                .to_natural_order()
+                .to_row_major_matrix()
+                .values
+        };
+
+        // Nonzero at row `height - 1`, zero everywhere else on domain
+        let is_last_row = coset_to_d(&sels.is_last_row);
+        for (i, &x) in is_last_row.iter().enumerate() {
+            assert_eq!(x == F::ZERO, i != height - 1);
+        }
+
+        // Zero at rows `height - 1` and `n - 1`, nonzero everywhere else on domain
+        let is_transition = coset_to_d(&sels.is_transition);
+        for (i, &x) in is_transition.iter().enumerate() {
+            assert_eq!(x == F::ZERO, i == height - 1 || i == n - 1);
+        }
+    }
+
     #[test]
     fn test_circle_domain() {
         do_test_circle_domain(4, 8);
         do_test_circle_domain(10, 32);
     }
+
+    #[test]
+    fn cfft_points() {
+        for log_n in 1..8 {
+            let shift = Point::generator(Mersenne31::CIRCLE_TWO_ADICITY) * thread_rng().gen();
+            let d = CircleDomain::<Mersenne31>::new(log_n, shift);
+            assert_eq!(
+                d.cfft_points(),
+                cfft_permute_slice(&d.points().collect_vec())
+            );
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::Debug;
 use core::marker::PhantomData;
@@ -5,9 +6,13 @@ use core::marker::PhantomData;
 use itertools::Itertools;
 use p3_commit::Mmcs;
 use p3_field::extension::ComplexExtendable;
-use p3_field::{batch_multiplicative_inverse, ExtensionField};
+use p3_field::{
+    batch_multiplicative_inverse, AbstractExtensionField, AbstractField, ExtensionField,
+    PackedValue,
+};
 use p3_fri::FriGenericConfig;
 use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
 use p3_util::{log2_strict_usize, reverse_bits_len};
 
 use crate::domain::CircleDomain;
@@ -46,23 +51,78 @@ impl<F: ComplexExtendable, EF: ExtensionField<F>, InputProof, InputError: Debug>
     fn fold_matrix<M: Matrix<EF>>(&self, beta: EF, m: M) -> Vec<EF> {
         fold_x(beta, m)
     }
+
+    fn interpolate_final_poly(&self, evals: Vec<EF>) -> Vec<EF> {
+        // Circle FRI always folds down to a constant; see `FriConfig::log_final_poly_len`.
+        let constant = evals[0];
+        assert!(
+            evals.iter().all(|&x| x == constant),
+            "circle FRI only supports a constant final polynomial"
+        );
+        let mut coeffs = vec![EF::ZERO; evals.len()];
+        coeffs[0] = constant;
+        coeffs
+    }
+
+    fn eval_final_poly(&self, _index: usize, _log_height: usize, coeffs: &[EF]) -> EF {
+        assert_eq!(
+            coeffs.len(),
+            1,
+            "circle FRI only supports a constant final polynomial"
+        );
+        coeffs[0]
+    }
 }
 
+/// Folds each row `(lo, hi)` with twiddle `t` into `(lo + hi + beta (lo - hi) / t) / 2`, where
+/// `twiddles` holds the inverse twiddles.
+///
+/// Rows are folded `F::Packing::WIDTH` at a time, one per lane of `EF::ExtensionPacking`, so that
+/// extension fields with packed arithmetic, like `Qm31`, use their SIMD kernels, and the packed
+/// chunks are folded in parallel.
 fn fold<F: ComplexExtendable, EF: ExtensionField<F>>(
     evals: impl Matrix<EF>,
     beta: EF,
     twiddles: &[F],
 ) -> Vec<EF> {
-    evals
-        .rows()
-        .zip(twiddles)
-        .map(|(mut row, &t)| {
-            let (lo, hi) = row.next_tuple().unwrap();
-            let sum = lo + hi;
-            let diff = (lo - hi) * t;
-            (sum + beta * diff).halve()
+    let width = F::Packing::WIDTH;
+    let height = evals.height();
+    let packed_height = height - height % width;
+
+    let beta_packed = EF::ExtensionPacking::from_f(beta);
+    let half = F::Packing::from(F::TWO.inverse());
+    let pack = |r: usize, c: usize| {
+        EF::ExtensionPacking::from_base_fn(|i| {
+            F::Packing::from_fn(|lane| evals.get(r + lane, c).as_base_slice()[i])
         })
-        .collect_vec()
+    };
+
+    let mut folded = EF::zero_vec(packed_height);
+    folded
+        .par_chunks_exact_mut(width)
+        .zip(twiddles.par_chunks_exact(width))
+        .enumerate()
+        .for_each(|(chunk, (out, ts))| {
+            let r = chunk * width;
+            let (lo, hi) = (pack(r, 0), pack(r, 1));
+            let t = *F::Packing::from_slice(ts);
+            let res = (lo + hi + beta_packed * ((lo - hi) * t)) * half;
+            for (lane, o) in out.iter_mut().enumerate() {
+                *o = EF::unpack_lane(&res, lane);
+            }
+        });
+    folded.extend(
+        (packed_height..height)
+            .map(|r| evals.row(r))
+            .zip(&twiddles[packed_height..])
+            .map(|(mut row, &t)| {
+                let (lo, hi) = row.next_tuple().unwrap();
+                let sum = lo + hi;
+                let diff = (lo - hi) * t;
+                (sum + beta * diff).halve()
+            }),
+    );
+    folded
 }
 
 pub(crate) fn fold_y<F: ComplexExtendable, EF: ExtensionField<F>>(
@@ -132,7 +192,8 @@ mod tests {
     use itertools::iproduct;
     use p3_field::extension::BinomialExtensionField;
     use p3_matrix::dense::RowMajorMatrix;
-    use p3_mersenne_31::Mersenne31;
+    use p3_mersenne_31::{Mersenne31, Qm31};
+    use rand::distributions::{Distribution, Standard};
     use rand::{random, thread_rng};
 
     use super::*;
@@ -141,9 +202,10 @@ mod tests {
     type F = Mersenne31;
     type EF = BinomialExtensionField<F, 3>;
 
-    #[test]
-    fn fold_matrix_same_as_row() {
-        let log_folded_height = 5;
+    fn fold_matrix_same_as_row<EF: ExtensionField<F>>(log_folded_height: usize)
+    where
+        Standard: Distribution<EF>,
+    {
         let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_folded_height, 2);
         let beta: EF = random();
 
@@ -160,6 +222,21 @@ mod tests {
         assert_eq!(mat_x_folded, row_x_folded);
     }
 
+    #[test]
+    fn fold_matrix_same_as_row_binomial() {
+        // Small heights are folded one row at a time, larger ones a packed vector at a time.
+        for log_folded_height in [1, 5] {
+            fold_matrix_same_as_row::<EF>(log_folded_height);
+        }
+    }
+
+    #[test]
+    fn fold_matrix_same_as_row_qm31() {
+        for log_folded_height in [1, 5] {
+            fold_matrix_same_as_row::<Qm31>(log_folded_height);
+        }
+    }
+
     #[test]
     fn folded_matrix_remains_low_degree() {
         let vec_dim = |evals: &[F]| {
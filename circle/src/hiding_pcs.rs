@@ -0,0 +1,339 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::{izip, Itertools};
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace};
+use p3_field::extension::ComplexExtendable;
+use p3_field::{AbstractExtensionField, ExtensionField, Field};
+use p3_fri::verifier::FriError;
+use p3_fri::FriConfig;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use spin::Mutex;
+use tracing::instrument;
+
+use crate::domain::CircleDomain;
+use crate::{CircleEvaluations, CirclePcs, CirclePcsProof, InputError};
+
+/// A zero-knowledge variant of `CirclePcs`.
+///
+/// Each committed polynomial `f` over a domain `H` of size `n` is blinded as `f + Z_H r`, where
+/// `Z_H` is the vanishing polynomial of `H` and `r` is a random polynomial with `n` coefficients.
+/// This has the same evaluations over `H`, i.e. it pads the trace with random rows, and is
+/// committed to as a polynomial over a domain of size `2n`. The chunks of a quotient are masked in
+/// the same way, with masks which cancel out when the chunks are recombined; see
+/// `Pcs::commit_quotient`. Finally, a random polynomial as large as the largest committed one is
+/// added to the batch which FRI proves to be of low degree, so that the FRI proof doesn't reveal
+/// anything about the committed polynomials either.
+///
+/// The blinding only hides the committed polynomials if the openings and queries reveal fewer
+/// evaluations of each than it has random coefficients, i.e. if the number of opening points
+/// and FRI queries is well below `n`. `InputMmcs` and `FriMmcs` should be hiding, such as
+/// `MerkleTreeHidingMmcs`, so that the commitments don't reveal the rows which aren't opened.
+///
+/// `R` should be a cryptographically secure pseudorandom number generator, as for
+/// `MerkleTreeHidingMmcs`. It's kept behind a lock, so that the PCS is `Sync` whenever `R` is
+/// `Send`.
+#[derive(Debug)]
+pub struct HidingCirclePcs<Val: Field, InputMmcs, FriMmcs, R> {
+    inner: CirclePcs<Val, InputMmcs, FriMmcs>,
+    rng: Mutex<R>,
+}
+
+impl<Val: Field, InputMmcs, FriMmcs, R> HidingCirclePcs<Val, InputMmcs, FriMmcs, R> {
+    pub fn new(mmcs: InputMmcs, fri_config: FriConfig<FriMmcs>, rng: R) -> Self {
+        Self {
+            inner: CirclePcs::new(mmcs, fri_config),
+            rng: Mutex::new(rng),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize",
+    deserialize = "Witness: Deserialize<'de>"
+))]
+pub struct HidingCirclePcsProof<
+    Val: Field,
+    Challenge: Field,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    Witness,
+> {
+    /// The commitment to the random polynomial added to the batch.
+    random_commitment: InputMmcs::Commitment,
+    /// The random polynomial's openings, at the first point at which anything is opened.
+    random_opened_values: Vec<Vec<Challenge>>,
+    inner_proof: CirclePcsProof<Val, Challenge, InputMmcs, FriMmcs, Witness>,
+}
+
+/// The evaluations of `f + weight * Z * r` over the standard position domain of twice the size of
+/// `domain`, where `f` interpolates `evals` over `domain`, `Z` is the vanishing polynomial of
+/// `domain`, and `r` interpolates `random` over the standard position domain of its size.
+#[instrument(skip_all, fields(dims = %evals.dimensions()))]
+fn blind<Val: ComplexExtendable>(
+    domain: CircleDomain<Val>,
+    evals: RowMajorMatrix<Val>,
+    random: RowMajorMatrix<Val>,
+    weight: Val,
+) -> (CircleDomain<Val>, RowMajorMatrix<Val>) {
+    let blinded_domain = CircleDomain::standard(domain.log_n + 1);
+    let extend = |domain, evals| {
+        CircleEvaluations::from_natural_order(domain, evals)
+            .extrapolate(blinded_domain)
+            .to_natural_order()
+            .to_row_major_matrix()
+    };
+    let mut blinded = extend(domain, evals);
+    let random = extend(CircleDomain::standard(domain.log_n), random);
+    let points = blinded_domain.points().collect_vec();
+    blinded
+        .par_rows_mut()
+        .zip(random.par_row_slices())
+        .zip(points)
+        .for_each(|((row, random_row), point)| {
+            let scale = weight * domain.zeroifier(point);
+            for (x, &r) in row.iter_mut().zip(random_row) {
+                *x += scale * r;
+            }
+        });
+    (blinded_domain, blinded)
+}
+
+impl<Val, InputMmcs, FriMmcs, R, Challenge, Challenger> Pcs<Challenge, Challenger>
+    for HidingCirclePcs<Val, InputMmcs, FriMmcs, R>
+where
+    Val: ComplexExtendable,
+    Standard: Distribution<Val>,
+    Challenge: ExtensionField<Val>,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    R: Rng + Send,
+    Challenger: FieldChallenger<Val>
+        + GrindingChallenger
+        + CanObserve<FriMmcs::Commitment>
+        + CanObserve<InputMmcs::Commitment>,
+{
+    type Domain = CircleDomain<Val>;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
+    type Proof = HidingCirclePcsProof<Val, Challenge, InputMmcs, FriMmcs, Challenger::Witness>;
+    type Error = FriError<FriMmcs::Error, InputError<InputMmcs::Error, FriMmcs::Error>>;
+
+    const ZK: bool = true;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        CircleDomain::standard(log2_strict_usize(degree))
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let blinded = evaluations
+            .into_iter()
+            .map(|(domain, evals)| {
+                let random =
+                    RowMajorMatrix::rand(&mut *self.rng.lock(), evals.height(), evals.width());
+                blind(domain, evals, random, Val::ONE)
+            })
+            .collect();
+        Pcs::<Challenge, Challenger>::commit(&self.inner, blinded)
+    }
+
+    fn commit_quotient(
+        &self,
+        quotients: Vec<Vec<(Self::Domain, RowMajorMatrix<Val>)>>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let blinded = quotients
+            .into_iter()
+            .flat_map(|chunks| {
+                let domains = chunks.iter().map(|(domain, _)| *domain).collect_vec();
+                let (height, width) = chunks
+                    .first()
+                    .map_or((0, 0), |(_, evals)| (evals.height(), evals.width()));
+
+                // The masks must sum to zero, so the last is minus the sum of the others.
+                let mut masks = {
+                    let mut rng = self.rng.lock();
+                    (1..chunks.len())
+                        .map(|_| RowMajorMatrix::rand(&mut *rng, height, width))
+                        .collect_vec()
+                };
+                let mut last_mask = RowMajorMatrix::new(vec![Val::ZERO; height * width], width);
+                for mask in &masks {
+                    for (x, &m) in last_mask.values.iter_mut().zip(&mask.values) {
+                        *x -= m;
+                    }
+                }
+                masks.push(last_mask);
+
+                // Chunk `i` is recombined with the weight `prod_{j != i} Z_j / Z_j(x_i)`, where
+                // `x_i` is any point of its domain, so scaling its mask by the denominator leaves
+                // `prod_j Z_j` times the sum of the masks.
+                izip!(chunks, masks)
+                    .enumerate()
+                    .map(|(i, ((domain, evals), mask))| {
+                        let weight = domains
+                            .iter()
+                            .enumerate()
+                            .filter(|&(j, _)| j != i)
+                            .map(|(_, other)| other.zp_at_point(domain.first_point()))
+                            .product();
+                        blind(domain, evals, mask, weight)
+                    })
+                    .collect_vec()
+            })
+            .collect();
+        Pcs::<Challenge, Challenger>::commit(&self.inner, blinded)
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> impl Matrix<Val> + 'a {
+        Pcs::<Challenge, Challenger>::get_evaluations_on_domain(&self.inner, data, idx, domain)
+    }
+
+    fn get_committed_evaluations<'a>(
+        &self,
+        data: &'a Self::ProverData,
+        idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val> + 'a)> {
+        Pcs::<Challenge, Challenger>::get_committed_evaluations(&self.inner, data, idx)
+    }
+
+    fn get_committed_evaluations_bit_reversed<'a>(
+        &self,
+        data: &'a Self::ProverData,
+        idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val> + 'a)> {
+        Pcs::<Challenge, Challenger>::get_committed_evaluations_bit_reversed(&self.inner, data, idx)
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Challenge>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        // Commit to a random polynomial of the largest committed degree, with as many columns as
+        // an element of `Challenge` has coefficients, so that the batch FRI proves to be of low
+        // degree is random too.
+        let log_max_height = rounds
+            .iter()
+            .map(|(data, _)| log2_strict_usize(self.inner.mmcs.get_max_height(data)))
+            .max()
+            .expect("nothing to open");
+        let random_domain =
+            CircleDomain::standard(log_max_height - self.inner.fri_config.log_blowup);
+        let random = RowMajorMatrix::rand(
+            &mut *self.rng.lock(),
+            random_domain.size(),
+            <Challenge as AbstractExtensionField<Val>>::D,
+        );
+        let (random_commitment, random_data) =
+            Pcs::<Challenge, Challenger>::commit(&self.inner, vec![(random_domain, random)]);
+        challenger.observe(random_commitment.clone());
+
+        let first_point = rounds
+            .iter()
+            .flat_map(|(_, points_for_mats)| points_for_mats.iter().flatten())
+            .next()
+            .copied();
+        let rounds = rounds
+            .into_iter()
+            .chain([(&random_data, vec![first_point.into_iter().collect()])])
+            .collect_vec();
+        let (mut values, inner_proof) =
+            Pcs::<Challenge, Challenger>::open(&self.inner, rounds, challenger);
+        let random_opened_values = values.pop().unwrap().pop().unwrap();
+        (
+            values,
+            HidingCirclePcsProof {
+                random_commitment,
+                random_opened_values,
+                inner_proof,
+            },
+        )
+    }
+
+    fn verify(
+        &self,
+        // For each round:
+        rounds: Vec<(
+            Self::Commitment,
+            // for each matrix:
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // for each point:
+                Vec<(
+                    // the point,
+                    Challenge,
+                    // values at the point
+                    Vec<Challenge>,
+                )>,
+            )>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        let HidingCirclePcsProof {
+            random_commitment,
+            random_opened_values,
+            inner_proof,
+        } = proof;
+        challenger.observe(random_commitment.clone());
+
+        let first_point = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats.iter().flat_map(|(_, points)| points.first()))
+            .next()
+            .map(|(point, _)| *point);
+        if random_opened_values.len() != first_point.iter().len() {
+            return Err(FriError::InvalidProofShape);
+        }
+
+        // Each polynomial was blinded to twice the size of its domain when it was committed to.
+        let mut rounds = rounds
+            .into_iter()
+            .map(|(commitment, mats)| {
+                let mats = mats
+                    .into_iter()
+                    .map(|(domain, points)| (CircleDomain::standard(domain.log_n + 1), points))
+                    .collect_vec();
+                (commitment, mats)
+            })
+            .collect_vec();
+        let log_max_size = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats.iter().map(|(domain, _)| domain.log_n))
+            .max()
+            .expect("nothing to verify");
+        rounds.push((
+            random_commitment.clone(),
+            vec![(
+                CircleDomain::standard(log_max_size),
+                izip!(first_point, random_opened_values.clone()).collect(),
+            )],
+        ));
+        Pcs::<Challenge, Challenger>::verify(&self.inner, rounds, inner_proof, challenger)
+    }
+}
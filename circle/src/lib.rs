@@ -9,15 +9,21 @@ mod cfft;
 mod deep_quotient;
 mod domain;
 mod folding;
+mod hiding_pcs;
 mod ordering;
 mod pcs;
 mod point;
 mod proof;
 mod prover;
+mod serialization;
+mod twiddles;
 mod verifier;
 
 pub use cfft::*;
 pub use domain::*;
+pub use hiding_pcs::*;
 pub use ordering::*;
 pub use pcs::*;
 pub use proof::*;
+pub use serialization::*;
+pub use twiddles::*;
@@ -1,8 +1,7 @@
-use alloc::vec::Vec;
-
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::row_index_mapped::{RowIndexMap, RowIndexMappedView};
 use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
 use p3_util::{log2_strict_usize, reverse_bits_len};
 
 #[inline]
@@ -18,37 +17,20 @@ pub(crate) fn cfft_permute_index(index: usize, log_n: usize) -> usize {
     )
 }
 
-pub(crate) fn cfft_permute_slice<T: Clone>(xs: &[T]) -> Vec<T> {
+#[cfg(test)]
+pub(crate) fn cfft_permute_slice<T: Clone>(xs: &[T]) -> alloc::vec::Vec<T> {
     let log_n = log2_strict_usize(xs.len());
     (0..xs.len())
         .map(|i| xs[cfft_permute_index(i, log_n)].clone())
         .collect()
 }
 
-pub(crate) fn cfft_permute_slice_chunked_in_place<T>(xs: &mut [T], chunk_size: usize) {
-    assert_eq!(xs.len() % chunk_size, 0);
-    let n_chunks = xs.len() / chunk_size;
-    let log_n = log2_strict_usize(n_chunks);
-    for i in 0..n_chunks {
-        let j = cfft_permute_index(i, log_n);
-        if i < j {
-            // somehow this is slightly faster than the unsafe block below
-            for k in 0..chunk_size {
-                xs.swap(i * chunk_size + k, j * chunk_size + k);
-            }
-            /*
-            unsafe {
-                core::ptr::swap_nonoverlapping(
-                    xs.as_mut_ptr().add(i * chunk_size),
-                    xs.as_mut_ptr().add(j * chunk_size),
-                    chunk_size,
-                );
-            }
-            */
-        }
-    }
-}
-
+/// A view of a matrix of evaluations over a circle domain, in natural order, as a matrix in CFFT
+/// order, or vice versa since the permutation is an involution.
+///
+/// CFFT order is how evaluations are stored everywhere in the circle PCS, from the LDEs which are
+/// committed to, through the opened values and DEEP quotients, to FRI. Natural order is only ever
+/// a view over them, so rows are permuted as they're read rather than in separate passes.
 pub type CfftView<M> = RowIndexMappedView<CfftPerm, M>;
 
 #[derive(Copy, Clone)]
@@ -67,9 +49,12 @@ impl RowIndexMap for CfftPerm {
         &self,
         inner: Inner,
     ) -> RowMajorMatrix<T> {
-        let mut inner = inner.to_row_major_matrix();
-        cfft_permute_slice_chunked_in_place(&mut inner.values, inner.width);
-        inner
+        // Gather the rows in CFFT order while copying them, rather than copying and then swapping.
+        let values = (0..self.height())
+            .into_par_iter()
+            .flat_map_iter(|r| inner.row(self.map_row_index(r)))
+            .collect();
+        RowMajorMatrix::new(values, inner.width())
     }
 }
 
@@ -115,4 +100,18 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn cfft_view_to_row_major_matrix() {
+        let m = RowMajorMatrix::new((0..16 * 3).collect_vec(), 3);
+        let view = m.as_view().cfft_perm_rows();
+        let dense = view.to_row_major_matrix();
+        for r in 0..16 {
+            assert_eq!(dense.row(r).collect_vec(), view.row(r).collect_vec());
+            assert_eq!(
+                dense.row(r).collect_vec(),
+                m.row(cfft_permute_index(r, 4)).collect_vec()
+            );
+        }
+    }
 }
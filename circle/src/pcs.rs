@@ -25,6 +25,14 @@ use crate::prover::prove;
 use crate::verifier::verify;
 use crate::{cfft_permute_index, CfftPermutable, CircleEvaluations, CircleFriProof};
 
+/// A PCS over the circle group of a field such as Mersenne31, which proves openings with circle
+/// FRI.
+///
+/// With a hiding `InputMmcs` and `FriMmcs`, such as `MerkleTreeHidingMmcs`, the commitments are
+/// salted, so the rows which aren't opened stay hidden. This PCS isn't zero-knowledge though: the
+/// committed polynomials aren't blinded, so the opened rows and the evaluations at the
+/// out-of-domain points reveal information about them. See `HidingCirclePcs` for a PCS which
+/// blinds them.
 #[derive(Debug)]
 pub struct CirclePcs<Val: Field, InputMmcs, FriMmcs> {
     pub mmcs: InputMmcs,
@@ -32,6 +40,16 @@ pub struct CirclePcs<Val: Field, InputMmcs, FriMmcs> {
     pub _phantom: PhantomData<Val>,
 }
 
+impl<Val: Field, InputMmcs, FriMmcs> CirclePcs<Val, InputMmcs, FriMmcs> {
+    pub const fn new(mmcs: InputMmcs, fri_config: FriConfig<FriMmcs>) -> Self {
+        Self {
+            mmcs,
+            fri_config,
+            _phantom: PhantomData,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
@@ -111,6 +129,10 @@ where
                     "CirclePcs cannot commit to a matrix with fewer than 4 rows.",
                     // (because we bivariate fold one bit, and fri needs one more bit)
                 );
+                // The first layer is folded before FRI, so its inputs are half the LDE's height.
+                self.fri_config.check_pow_bits::<Val, Challenge>(
+                    domain.log_n + self.fri_config.log_blowup - 1,
+                );
                 CircleEvaluations::from_natural_order(domain, evals)
                     .extrapolate(CircleDomain::standard(
                         domain.log_n + self.fri_config.log_blowup,
@@ -141,6 +163,27 @@ where
         }
     }
 
+    fn get_committed_evaluations<'a>(
+        &self,
+        data: &'a Self::ProverData,
+        idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val> + 'a)> {
+        let mat = self.mmcs.get_matrices(data)[idx];
+        let domain = CircleDomain::standard(log2_strict_usize(mat.height()));
+        Some((domain, mat.as_view().cfft_perm_rows()))
+    }
+
+    fn get_committed_evaluations_bit_reversed<'a>(
+        &self,
+        data: &'a Self::ProverData,
+        idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val> + 'a)> {
+        // It was committed in cfft order.
+        let mat = self.mmcs.get_matrices(data)[idx];
+        let domain = CircleDomain::standard(log2_strict_usize(mat.height()));
+        Some((domain, mat.as_view()))
+    }
+
     fn open(
         &self,
         // For each round,
@@ -188,7 +231,7 @@ where
                                 (Challenge::ONE, vec![Challenge::ZERO; 1 << log_height])
                             });
 
-                        points_for_mat
+                        let openings = points_for_mat
                             .iter()
                             .map(|&zeta| {
                                 let zeta = Point::from_projective_line(zeta);
@@ -200,22 +243,26 @@ where
                                     info_span!("compute opened values with Lagrange interpolation")
                                         .in_scope(|| evals.evaluate_at_point(zeta));
 
-                                // Reduce this matrix, as a deep quotient, into one column with powers of α.
-                                let mat_ros = evals.deep_quotient_reduce(alpha, zeta, &ps_at_zeta);
-
-                                // Fold it into our running reduction, offset by alpha_offset.
-                                reduced_opening_for_log_height
-                                    .par_iter_mut()
-                                    .zip(mat_ros)
-                                    .for_each(|(ro, mat_ro)| {
-                                        *ro += *alpha_offset * mat_ro;
-                                    });
-
-                                // Update alpha_offset from α^i -> α^(i + 2 * width)
+                                // Each point's quotient is offset by the next α^i, and
+                                // α^i -> α^(i + 2 * width).
+                                let point_alpha_offset = *alpha_offset;
                                 *alpha_offset *= alpha.exp_u64(2 * evals.values.width() as u64);
 
-                                ps_at_zeta
+                                (zeta, ps_at_zeta, point_alpha_offset)
                             })
+                            .collect_vec();
+
+                        // Reduce this matrix, as a deep quotient at each point, into one column
+                        // with powers of α, and fold it into our running reduction.
+                        evals.accumulate_deep_quotients(
+                            alpha,
+                            &openings,
+                            reduced_opening_for_log_height,
+                        );
+
+                        openings
+                            .into_iter()
+                            .map(|(_, ps_at_zeta, _)| ps_at_zeta)
                             .collect()
                     })
                     .collect()
@@ -475,10 +522,10 @@ where
 mod tests {
     use p3_challenger::{HashChallenger, SerializingChallenger32};
     use p3_commit::ExtensionMmcs;
-    use p3_field::extension::BinomialExtensionField;
+    use p3_fri::SecurityAssumption;
     use p3_keccak::Keccak256Hash;
     use p3_merkle_tree::MerkleTreeMmcs;
-    use p3_mersenne_31::Mersenne31;
+    use p3_mersenne_31::{Mersenne31, Qm31};
     use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
     use rand::{Rng, SeedableRng};
     use rand_chacha::ChaCha8Rng;
@@ -492,7 +539,7 @@ mod tests {
         let mut rng = ChaCha8Rng::from_seed([0; 32]);
 
         type Val = Mersenne31;
-        type Challenge = BinomialExtensionField<Mersenne31, 3>;
+        type Challenge = Qm31;
 
         type ByteHash = Keccak256Hash;
         type FieldHash = SerializingHasher32<ByteHash>;
@@ -512,8 +559,11 @@ mod tests {
 
         let fri_config = FriConfig {
             log_blowup: 1,
+            log_final_poly_len: 0,
             num_queries: 2,
             proof_of_work_bits: 1,
+            target_soundness_bits: None,
+            security_assumption: SecurityAssumption::CapacityBound,
             mmcs: challenge_mmcs,
         };
 
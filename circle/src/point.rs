@@ -3,6 +3,7 @@ use core::ops::{Add, AddAssign, Mul, Neg, Sub};
 
 use p3_field::extension::ComplexExtendable;
 use p3_field::{batch_multiplicative_inverse, ExtensionField, Field};
+use p3_maybe_rayon::prelude::*;
 
 /// Affine representation of a point on the circle.
 /// x^2 + y^2 == 1
@@ -117,7 +118,7 @@ pub fn compute_lagrange_den_batched<F: Field, EF: ExtensionField<F>>(
     // Would be nice to find further speedups.
     // Maybe modify to use packed fields here?
     let (numer, denom): (Vec<_>, Vec<_>) = points
-        .iter()
+        .par_iter()
         .map(|&pt| {
             let diff = at - pt;
             let numer = diff.x + F::ONE;
@@ -129,8 +130,8 @@ pub fn compute_lagrange_den_batched<F: Field, EF: ExtensionField<F>>(
     let inv_d = batch_multiplicative_inverse(&denom);
 
     numer
-        .iter()
-        .zip(inv_d.iter())
+        .par_iter()
+        .zip(inv_d.par_iter())
         .map(|(&num, &inv_d)| num * inv_d)
         .collect()
 }
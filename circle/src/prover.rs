@@ -33,12 +33,21 @@ where
         .iter()
         .tuple_windows()
         .all(|(l, r)| l.len() >= r.len()));
+    assert_eq!(
+        config.log_final_poly_len, 0,
+        "circle FRI only supports a constant final polynomial"
+    );
 
     let log_max_height = log2_strict_usize(inputs[0].len());
 
+    config.check_pow_bits::<Val, Challenge>(log_max_height);
+
     let commit_phase_result = commit_phase(g, config, inputs, challenger);
 
-    let pow_witness = challenger.grind(config.proof_of_work_bits);
+    let pow_bits = config
+        .bind_pow_bits::<Val, Challenge, _>(log_max_height, challenger)
+        .expect("checked before the commit phase");
+    let pow_witness = challenger.grind(pow_bits);
 
     let query_proofs = info_span!("query phase").in_scope(|| {
         iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
@@ -0,0 +1,46 @@
+//! Stable byte encodings for circle PCS proofs.
+//!
+//! These follow the FRI encodings in `p3_fri`: a four byte magic identifying what was encoded,
+//! then [`CIRCLE_FORMAT_VERSION`], then the payload in the canonical encoding from
+//! `p3_util::canonical_serialization`. Commitments and opened values are the same for every PCS,
+//! so they're encoded with `p3_fri::commitment_to_bytes` and `p3_fri::opened_values_to_bytes`.
+//!
+//! Any change to the layout of the types encoded here must bump [`CIRCLE_FORMAT_VERSION`].
+
+use alloc::vec::Vec;
+
+use p3_commit::Mmcs;
+use p3_field::Field;
+use p3_util::canonical_serialization::{
+    from_bytes_with_header, to_bytes_with_header, SerializationError,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::CirclePcsProof;
+
+/// The version of the encodings produced by this module.
+pub const CIRCLE_FORMAT_VERSION: u16 = 1;
+
+/// Magic prefix of an encoded [`CirclePcsProof`].
+pub const CIRCLE_PCS_PROOF_MAGIC: [u8; 4] = *b"P3CP";
+
+impl<Val, Challenge, InputMmcs, FriMmcs, Witness>
+    CirclePcsProof<Val, Challenge, InputMmcs, FriMmcs, Witness>
+where
+    Val: Field,
+    Challenge: Field,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    Self: Serialize + DeserializeOwned,
+{
+    /// Encode this proof in the canonical, versioned format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        to_bytes_with_header(CIRCLE_PCS_PROOF_MAGIC, CIRCLE_FORMAT_VERSION, self)
+    }
+
+    /// Decode a proof produced by [`CirclePcsProof::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        from_bytes_with_header(CIRCLE_PCS_PROOF_MAGIC, CIRCLE_FORMAT_VERSION, bytes)
+    }
+}
@@ -1,78 +1,170 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
 use alloc::vec::Vec;
-use core::mem;
+use core::any::{Any, TypeId};
 
 use itertools::Itertools;
-use p3_field::extension::{Complex, ComplexExtendable};
-use p3_field::{batch_multiplicative_inverse, Field};
+use p3_field::batch_multiplicative_inverse;
+use p3_field::extension::ComplexExtendable;
 use p3_util::linear_map::LinearMap;
-use tracing::instrument;
+use p3_util::reverse_slice_index_bits;
+use spin::RwLock;
 
 use crate::domain::CircleDomain;
+use crate::point::Point;
 
-#[derive(Debug, Default)]
-pub(crate) struct TwiddleCache<F: Field>(
-    // (log_n, shift) -> (twiddles, inverse_twiddles)
-    #[allow(clippy::type_complexity)]
-    LinearMap<(usize, Complex<F>), (Vec<Vec<F>>, Option<Vec<Vec<F>>>)>,
-);
-
-impl<F: ComplexExtendable> TwiddleCache<F> {
-    pub(crate) fn get_twiddles(
-        &mut self,
-        log_n: usize,
-        shift: Complex<F>,
-        inv: bool,
-    ) -> &Vec<Vec<F>> {
-        let cache = self
-            .0
-            .get_or_insert_with((log_n, shift), || (compute_twiddles(log_n, shift), None));
-        if !inv {
-            &cache.0
-        } else {
-            cache.1.get_or_insert_with(|| {
-                cache
-                    .0
-                    .iter()
-                    .map(|xs| batch_multiplicative_inverse(xs))
-                    .collect()
-            })
+/// The caches returned by `CircleTwiddleCache::global`, keyed by field.
+static GLOBAL_CACHES: RwLock<BTreeMap<TypeId, Box<dyn Any + Send + Sync>>> =
+    RwLock::new(BTreeMap::new());
+
+/// A thread-safe cache of the twiddle factors of the circle FFT, like `p3_dft::TwiddleCache` for
+/// the two-adic DFTs. Clones share the same tables.
+///
+/// `CircleTwiddleCache::global()` is shared by everything using it for the same field, and is
+/// what `CircleEvaluations` uses, so the tables of each domain are computed once however many
+/// times it's interpolated or evaluated over, e.g. when committing, computing the quotient and
+/// opening. Tables are kept until `clear` is called.
+#[derive(Clone, Debug, Default)]
+pub struct CircleTwiddleCache<F> {
+    tables: Arc<RwLock<CircleTwiddleTables<F>>>,
+}
+
+/// Tables keyed by `(log_n, shift)` of the domain. There are only a few domains in a proof, so a
+/// `LinearMap` is enough, and doesn't need points to be ordered.
+#[derive(Debug)]
+#[allow(clippy::type_complexity)]
+struct CircleTwiddleTables<F> {
+    twiddles: LinearMap<(usize, Point<F>), Arc<[Vec<F>]>>,
+    inverse_twiddles: LinearMap<(usize, Point<F>), Arc<[Vec<F>]>>,
+}
+
+impl<F> Default for CircleTwiddleTables<F> {
+    fn default() -> Self {
+        Self {
+            twiddles: LinearMap::default(),
+            inverse_twiddles: LinearMap::default(),
         }
     }
 }
 
-/// Computes all (non-inverted) twiddles for the FFT over a circle domain of size 2^log_n, for all layers of the FFT.
-#[instrument(skip(shift))]
-fn compute_twiddles<F: ComplexExtendable>(log_n: usize, shift: Complex<F>) -> Vec<Vec<F>> {
-    let size = 1 << (log_n - 1);
-
-    let init_domain = CircleDomain::new(log_n, shift)
-        .points()
-        .take(size)
-        .collect_vec();
-
-    // After the first step we only need the real part.
-    let mut working_domain: Vec<_> = init_domain
-        .iter()
-        .take(size / 2)
-        .map(|x| x.real())
-        .collect();
-
-    (0..log_n)
-        .map(|i| {
-            let size = working_domain.len();
-            let output = if i == 0 {
-                // The twiddles in step one are the inverse imaginary parts.
-                init_domain.iter().map(|x| x.imag()).collect_vec()
-            } else {
-                let new_working_domain = working_domain
-                    .iter()
-                    .take(size / 2)
-                    // When we square a point, the real part changes as x -> 2x^2 - 1.
-                    .map(|x| x.square().double() - F::ONE)
-                    .collect();
-                mem::replace(&mut working_domain, new_working_domain)
-            };
-            output
-        })
-        .collect()
+impl<F: ComplexExtendable> CircleTwiddleCache<F> {
+    /// The cache shared by everything using it for the field `F`.
+    pub fn global() -> Self {
+        let type_id = TypeId::of::<F>();
+        if let Some(cache) = GLOBAL_CACHES.read().get(&type_id) {
+            return cache.downcast_ref::<Self>().unwrap().clone();
+        }
+        GLOBAL_CACHES
+            .write()
+            .entry(type_id)
+            .or_insert_with(|| Box::new(Self::default()))
+            .downcast_ref::<Self>()
+            .unwrap()
+            .clone()
+    }
+
+    /// The twiddles of each layer of the circle FFT over `domain`, in the order they're applied
+    /// when interpolating: first the `y` coordinates, then the `x` coordinates of each layer.
+    pub fn twiddles(&self, domain: CircleDomain<F>) -> Arc<[Vec<F>]> {
+        let key = (domain.log_n, domain.shift);
+        if let Some(twiddles) = self.tables.read().twiddles.get(&key) {
+            return twiddles.clone();
+        }
+        let twiddles: Arc<[Vec<F>]> = compute_twiddles(domain).into();
+        self.tables
+            .write()
+            .twiddles
+            .get_or_insert_with(key, || twiddles)
+            .clone()
+    }
+
+    /// The inverses of `twiddles`.
+    pub fn inverse_twiddles(&self, domain: CircleDomain<F>) -> Arc<[Vec<F>]> {
+        let key = (domain.log_n, domain.shift);
+        if let Some(twiddles) = self.tables.read().inverse_twiddles.get(&key) {
+            return twiddles.clone();
+        }
+        let inverse_twiddles: Arc<[Vec<F>]> = self
+            .twiddles(domain)
+            .iter()
+            .map(|ts| batch_multiplicative_inverse(ts))
+            .collect();
+        self.tables
+            .write()
+            .inverse_twiddles
+            .get_or_insert_with(key, || inverse_twiddles)
+            .clone()
+    }
+
+    /// Drop all tables, e.g. once proving is done.
+    pub fn clear(&self) {
+        *self.tables.write() = CircleTwiddleTables::default();
+    }
+}
+
+fn compute_twiddles<F: ComplexExtendable>(domain: CircleDomain<F>) -> Vec<Vec<F>> {
+    assert!(domain.log_n >= 1);
+    let mut pts = domain.coset0().collect_vec();
+    reverse_slice_index_bits(&mut pts);
+    let mut twiddles = vec![pts.iter().map(|p| p.y).collect_vec()];
+    if domain.log_n >= 2 {
+        twiddles.push(pts.iter().step_by(2).map(|p| p.x).collect_vec());
+        for i in 0..(domain.log_n - 2) {
+            let prev = twiddles.last().unwrap();
+            assert_eq!(prev.len(), 1 << (domain.log_n - 2 - i));
+            let cur = prev
+                .iter()
+                .step_by(2)
+                .map(|x| x.square().double() - F::ONE)
+                .collect_vec();
+            twiddles.push(cur);
+        }
+    }
+    twiddles
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+
+    use p3_field::AbstractField;
+    use p3_mersenne_31::Mersenne31;
+
+    use super::*;
+
+    type F = Mersenne31;
+
+    #[test]
+    fn global_cache_is_shared() {
+        let domain = CircleDomain::<F>::standard(5);
+        let twiddles = CircleTwiddleCache::<F>::global().twiddles(domain);
+        assert!(Arc::ptr_eq(
+            &twiddles,
+            &CircleTwiddleCache::<F>::global().twiddles(domain)
+        ));
+        assert_eq!(twiddles[..], compute_twiddles(domain)[..]);
+    }
+
+    #[test]
+    fn tables() {
+        let cache = CircleTwiddleCache::<F>::default();
+        let domain = CircleDomain::<F>::standard(6);
+        let twiddles = cache.twiddles(domain);
+        let inverse_twiddles = cache.inverse_twiddles(domain);
+        assert_eq!(twiddles.len(), 6);
+        for (ts, inv_ts) in twiddles.iter().zip(inverse_twiddles.iter()) {
+            for (&t, &inv_t) in ts.iter().zip(inv_ts) {
+                assert_eq!(t * inv_t, F::ONE);
+            }
+        }
+
+        // Each domain has its own tables.
+        let other = cache.twiddles(CircleDomain::standard(5));
+        assert_eq!(other.len(), 5);
+
+        cache.clear();
+        assert!(!Arc::ptr_eq(&twiddles, &cache.twiddles(domain)));
+    }
 }
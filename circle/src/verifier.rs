@@ -1,15 +1,13 @@
-use alloc::vec;
 use alloc::vec::Vec;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_field::{ExtensionField, Field};
-use p3_fri::verifier::FriError;
+use p3_fri::verifier::{verify_commit_phase_openings, FriError};
 use p3_fri::{FriConfig, FriGenericConfig};
-use p3_matrix::Dimensions;
 
-use crate::{CircleCommitPhaseProofStep, CircleFriProof};
+use crate::CircleFriProof;
 
 pub fn verify<G, Val, Challenge, M, Challenger>(
     g: &G,
@@ -35,39 +33,89 @@ where
         .collect();
     challenger.observe_ext_element(proof.final_poly);
 
-    if proof.query_proofs.len() != config.num_queries {
+    // Circle FRI always folds down to a constant.
+    if proof.query_proofs.len() != config.num_queries
+        || config.log_final_poly_len != 0
+        || proof
+            .query_proofs
+            .iter()
+            .any(|qp| qp.commit_phase_openings.len() != proof.commit_phase_commits.len())
+    {
         return Err(FriError::InvalidProofShape);
     }
 
+    let log_max_height = proof.commit_phase_commits.len() + config.log_blowup;
+
     // Check PoW.
-    if !challenger.check_witness(config.proof_of_work_bits, proof.pow_witness) {
+    let pow_bits = config
+        .bind_pow_bits::<Val, Challenge, _>(log_max_height, challenger)
+        .ok_or(FriError::InvalidProofShape)?;
+    if !challenger.check_witness(pow_bits, proof.pow_witness) {
         return Err(FriError::InvalidPowWitness);
     }
 
-    let log_max_height = proof.commit_phase_commits.len() + config.log_blowup;
+    // Sample every query and open its inputs, then fold the queries together a layer at a time,
+    // so that the openings of each commit phase commitment are verified at once.
+    let mut queries = proof
+        .query_proofs
+        .iter()
+        .map(|qp| {
+            let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
+            let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
+
+            debug_assert!(
+                ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
+                "reduced openings sorted by height descending"
+            );
+
+            Ok((
+                index >> g.extra_query_index_bits(),
+                Challenge::ZERO,
+                ro.into_iter().peekable(),
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (layer, (log_folded_height, &beta, comm)) in izip!(
+        (config.log_blowup..log_max_height).rev(),
+        &betas,
+        &proof.commit_phase_commits
+    )
+    .enumerate()
+    {
+        for (_, folded_eval, ro_iter) in &mut queries {
+            if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
+                *folded_eval += ro;
+            }
+        }
 
-    for qp in &proof.query_proofs {
-        let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
-        let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
+        let openings = izip!(&queries, &proof.query_proofs)
+            .map(|(&(index, folded_eval, _), qp)| {
+                let opening = &qp.commit_phase_openings[layer];
+                (
+                    index,
+                    folded_eval,
+                    opening.sibling_value,
+                    &opening.opening_proof,
+                )
+            })
+            .collect_vec();
+        let rows = verify_commit_phase_openings(&config.mmcs, comm, log_folded_height, &openings)
+            .map_err(FriError::CommitPhaseMmcsError)?;
 
+        for ((index, folded_eval, _), row) in izip!(&mut queries, rows) {
+            *index >>= 1;
+            *folded_eval = g.fold_row(*index, log_folded_height, beta, row.into_iter());
+        }
+    }
+
+    for (index, folded_eval, mut ro_iter) in queries {
+        debug_assert!(index < config.blowup(), "index was {}", index);
         debug_assert!(
-            ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
-            "reduced openings sorted by height descending"
+            ro_iter.next().is_none(),
+            "verifier reduced_openings were not in descending order?"
         );
 
-        let folded_eval = verify_query(
-            g,
-            config,
-            index >> g.extra_query_index_bits(),
-            izip!(
-                &betas,
-                &proof.commit_phase_commits,
-                &qp.commit_phase_openings
-            ),
-            ro,
-            log_max_height,
-        )?;
-
         if folded_eval != proof.final_poly {
             return Err(FriError::FinalPolyMismatch);
         }
@@ -75,65 +123,3 @@ where
 
     Ok(())
 }
-
-type CommitStep<'a, F, M> = (
-    &'a F,
-    &'a <M as Mmcs<F>>::Commitment,
-    &'a CircleCommitPhaseProofStep<F, M>,
-);
-
-fn verify_query<'a, G, F, M>(
-    g: &G,
-    config: &FriConfig<M>,
-    mut index: usize,
-    steps: impl Iterator<Item = CommitStep<'a, F, M>>,
-    reduced_openings: Vec<(usize, F)>,
-    log_max_height: usize,
-) -> Result<F, FriError<M::Error, G::InputError>>
-where
-    F: Field,
-    M: Mmcs<F> + 'a,
-    G: FriGenericConfig<F>,
-{
-    let mut folded_eval = F::ZERO;
-    let mut ro_iter = reduced_openings.into_iter().peekable();
-
-    for (log_folded_height, (&beta, comm, opening)) in izip!((0..log_max_height).rev(), steps) {
-        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
-            folded_eval += ro;
-        }
-
-        let index_sibling = index ^ 1;
-        let index_pair = index >> 1;
-
-        let mut evals = vec![folded_eval; 2];
-        evals[index_sibling % 2] = opening.sibling_value;
-
-        let dims = &[Dimensions {
-            width: 2,
-            height: 1 << log_folded_height,
-        }];
-        config
-            .mmcs
-            .verify_batch(
-                comm,
-                dims,
-                index_pair,
-                &[evals.clone()],
-                &opening.opening_proof,
-            )
-            .map_err(FriError::CommitPhaseMmcsError)?;
-
-        index = index_pair;
-
-        folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
-    }
-
-    debug_assert!(index < config.blowup(), "index was {}", index);
-    debug_assert!(
-        ro_iter.next().is_none(),
-        "verifier reduced_openings were not in descending order?"
-    );
-
-    Ok(folded_eval)
-}
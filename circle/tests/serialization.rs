@@ -0,0 +1,129 @@
+use p3_challenger::{CanObserve, FieldChallenger, HashChallenger, SerializingChallenger32};
+use p3_circle::{CirclePcs, CIRCLE_FORMAT_VERSION, CIRCLE_PCS_PROOF_MAGIC};
+use p3_commit::{ExtensionMmcs, OpenedValues, Pcs};
+use p3_fri::{
+    commitment_from_bytes, commitment_to_bytes, opened_values_from_bytes, opened_values_to_bytes,
+    FriConfig, SecurityAssumption, FRI_PROOF_MAGIC,
+};
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_mersenne_31::{Mersenne31, Qm31};
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+use p3_util::canonical_serialization::SerializationError;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+type Val = Mersenne31;
+type Challenge = Qm31;
+
+type ByteHash = Keccak256Hash;
+type FieldHash = SerializingHasher32<ByteHash>;
+type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+type MyPcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+type MyProof = <MyPcs as Pcs<Challenge, Challenger>>::Proof;
+
+fn get_pcs() -> (MyPcs, Challenger) {
+    let val_mmcs = ValMmcs::new(FieldHash::new(ByteHash {}), MyCompress::new(ByteHash {}));
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 10,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+    };
+    let pcs = MyPcs::new(val_mmcs, fri_config);
+    (pcs, Challenger::from_hasher(vec![], ByteHash {}))
+}
+
+#[test]
+fn test_circle_pcs_proof_round_trip() {
+    let (pcs, challenger) = get_pcs();
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+
+    let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 5);
+    let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << 5, 3);
+    let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+    let mut p_challenger = challenger.clone();
+    p_challenger.observe(commit.clone());
+    let zeta: Challenge = p_challenger.sample_ext_element();
+    let (opened_values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+
+    // Round trip every component through bytes.
+    let proof_bytes = proof.to_bytes().unwrap();
+    assert_eq!(&proof_bytes[..4], &CIRCLE_PCS_PROOF_MAGIC);
+    assert_eq!(&proof_bytes[4..6], &CIRCLE_FORMAT_VERSION.to_le_bytes());
+    let decoded_proof = MyProof::from_bytes(&proof_bytes).unwrap();
+    assert_eq!(decoded_proof.to_bytes().unwrap(), proof_bytes);
+
+    let commit_bytes = commitment_to_bytes(&commit).unwrap();
+    let decoded_commit: <MyPcs as Pcs<Challenge, Challenger>>::Commitment =
+        commitment_from_bytes(&commit_bytes).unwrap();
+    assert_eq!(commit, decoded_commit);
+
+    let opened_bytes = opened_values_to_bytes(&opened_values).unwrap();
+    let decoded_opened: OpenedValues<Challenge> = opened_values_from_bytes(&opened_bytes).unwrap();
+    assert_eq!(opened_values, decoded_opened);
+
+    // The decoded proof must still verify.
+    let mut v_challenger = challenger.clone();
+    v_challenger.observe(decoded_commit.clone());
+    let verifier_zeta: Challenge = v_challenger.sample_ext_element();
+    assert_eq!(verifier_zeta, zeta);
+    pcs.verify(
+        vec![(
+            decoded_commit,
+            vec![(domain, vec![(zeta, decoded_opened[0][0][0].clone())])],
+        )],
+        &decoded_proof,
+        &mut v_challenger,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_rejects_other_encodings() {
+    let (pcs, challenger) = get_pcs();
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+
+    let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 4);
+    let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << 4, 2);
+    let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+    let mut p_challenger = challenger;
+    p_challenger.observe(commit.clone());
+    let zeta: Challenge = p_challenger.sample_ext_element();
+    let (_, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+    let proof_bytes = proof.to_bytes().unwrap();
+
+    // A FRI proof header is not a circle PCS proof header.
+    let mut fri_bytes = proof_bytes.clone();
+    fri_bytes[..4].copy_from_slice(&FRI_PROOF_MAGIC);
+    assert_eq!(
+        MyProof::from_bytes(&fri_bytes).err(),
+        Some(SerializationError::BadMagic {
+            expected: CIRCLE_PCS_PROOF_MAGIC,
+            found: FRI_PROOF_MAGIC,
+        })
+    );
+
+    // Unknown version.
+    let mut future_bytes = proof_bytes.clone();
+    future_bytes[4] = 2;
+    assert!(matches!(
+        MyProof::from_bytes(&future_bytes),
+        Err(SerializationError::UnsupportedVersion { found: 2, .. })
+    ));
+
+    // Truncated input.
+    assert!(matches!(
+        MyProof::from_bytes(&proof_bytes[..proof_bytes.len() - 1]),
+        Err(SerializationError::UnexpectedEof)
+    ));
+}
@@ -2,7 +2,7 @@ use alloc::vec::Vec;
 use core::marker::PhantomData;
 
 use p3_field::{ExtensionField, Field};
-use p3_matrix::extension::FlatMatrixView;
+use p3_matrix::extension::{FlatMatrixView, PackedExtensionMatrix};
 use p3_matrix::{Dimensions, Matrix};
 
 use crate::Mmcs;
@@ -22,6 +22,38 @@ impl<F, EF, InnerMmcs> ExtensionMmcs<F, EF, InnerMmcs> {
     }
 }
 
+impl<F, EF, InnerMmcs> ExtensionMmcs<F, EF, InnerMmcs>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    InnerMmcs: Mmcs<F>,
+{
+    /// Commit to matrices stored as packed extension field elements, as SIMD code produces them,
+    /// without flattening them first. The coefficients are hashed in the same, canonical order as
+    /// `commit` would hash them, so the commitment is the same, and openings are verified with
+    /// `verify_batch` as usual.
+    #[allow(clippy::type_complexity)]
+    pub fn commit_packed(
+        &self,
+        inputs: Vec<PackedExtensionMatrix<F, EF>>,
+    ) -> (
+        InnerMmcs::Commitment,
+        InnerMmcs::ProverData<PackedExtensionMatrix<F, EF>>,
+    ) {
+        self.inner.commit(inputs)
+    }
+
+    /// Like `open_batch`, but for prover data from `commit_packed`.
+    pub fn open_batch_packed(
+        &self,
+        index: usize,
+        prover_data: &InnerMmcs::ProverData<PackedExtensionMatrix<F, EF>>,
+    ) -> (Vec<Vec<EF>>, InnerMmcs::Proof) {
+        let (opened_base_values, proof) = self.inner.open_batch(index, prover_data);
+        (unflatten_rows(opened_base_values), proof)
+    }
+}
+
 impl<F, EF, InnerMmcs> Mmcs<EF> for ExtensionMmcs<F, EF, InnerMmcs>
 where
     F: Field,
@@ -44,11 +76,19 @@ where
         prover_data: &Self::ProverData<M>,
     ) -> (Vec<Vec<EF>>, Self::Proof) {
         let (opened_base_values, proof) = self.inner.open_batch(index, prover_data);
-        let opened_ext_values = opened_base_values
+        (unflatten_rows(opened_base_values), proof)
+    }
+
+    fn open_many<M: Matrix<EF>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> Vec<(Vec<Vec<EF>>, Self::Proof)> {
+        self.inner
+            .open_many(indices, prover_data)
             .into_iter()
-            .map(|row| row.chunks(EF::D).map(EF::from_base_slice).collect())
-            .collect();
-        (opened_ext_values, proof)
+            .map(|(opened_base_values, proof)| (unflatten_rows(opened_base_values), proof))
+            .collect()
     }
 
     fn get_matrices<'a, M: Matrix<EF>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M> {
@@ -67,23 +107,62 @@ where
         opened_values: &[Vec<EF>],
         proof: &Self::Proof,
     ) -> Result<(), Self::Error> {
-        let opened_base_values: Vec<Vec<F>> = opened_values
+        let opened_base_values = flatten_rows::<F, EF>(opened_values);
+        self.inner.verify_batch(
+            commit,
+            &base_dimensions::<F, EF>(dimensions),
+            index,
+            &opened_base_values,
+            proof,
+        )
+    }
+
+    fn verify_batches(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        openings: &[(usize, &[Vec<EF>], &Self::Proof)],
+    ) -> Result<(), Self::Error> {
+        let opened_base_values: Vec<Vec<Vec<F>>> = openings
             .iter()
-            .map(|row| {
-                row.iter()
-                    .flat_map(|el| el.as_base_slice())
-                    .copied()
-                    .collect()
-            })
+            .map(|(_, opened_values, _)| flatten_rows::<F, EF>(opened_values))
             .collect();
-        let base_dimensions = dimensions
+        let base_openings: Vec<_> = openings
             .iter()
-            .map(|dim| Dimensions {
-                width: dim.width * EF::D,
-                height: dim.height,
-            })
-            .collect::<Vec<_>>();
-        self.inner
-            .verify_batch(commit, &base_dimensions, index, &opened_base_values, proof)
+            .zip(&opened_base_values)
+            .map(|(&(index, _, proof), opened_values)| (index, opened_values.as_slice(), proof))
+            .collect();
+        self.inner.verify_batches(
+            commit,
+            &base_dimensions::<F, EF>(dimensions),
+            &base_openings,
+        )
     }
 }
+
+fn flatten_rows<F: Field, EF: ExtensionField<F>>(rows: &[Vec<EF>]) -> Vec<Vec<F>> {
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .flat_map(|el| el.as_base_slice())
+                .copied()
+                .collect()
+        })
+        .collect()
+}
+
+fn unflatten_rows<F: Field, EF: ExtensionField<F>>(rows: Vec<Vec<F>>) -> Vec<Vec<EF>> {
+    rows.into_iter()
+        .map(|row| row.chunks(EF::D).map(EF::from_base_slice).collect())
+        .collect()
+}
+
+fn base_dimensions<F: Field, EF: ExtensionField<F>>(dimensions: &[Dimensions]) -> Vec<Dimensions> {
+    dimensions
+        .iter()
+        .map(|dim| Dimensions {
+            width: dim.width * EF::D,
+            height: dim.height,
+        })
+        .collect()
+}
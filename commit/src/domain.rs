@@ -1,4 +1,5 @@
 use alloc::vec::Vec;
+use core::fmt::Debug;
 
 use itertools::Itertools;
 use p3_field::{
@@ -9,7 +10,7 @@ use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_util::{log2_ceil_usize, log2_strict_usize};
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct LagrangeSelectors<T> {
     pub is_first_row: T,
     pub is_last_row: T,
@@ -17,7 +18,7 @@ pub struct LagrangeSelectors<T> {
     pub inv_zeroifier: T,
 }
 
-pub trait PolynomialSpace: Copy {
+pub trait PolynomialSpace: Copy + PartialEq + Debug + Send + Sync + 'static {
     type Val: Field;
 
     fn size(&self) -> usize;
@@ -27,6 +28,30 @@ pub trait PolynomialSpace: Copy {
     // This is only defined for cosets.
     fn next_point<Ext: ExtensionField<Self::Val>>(&self, x: Ext) -> Option<Ext>;
 
+    /// The point `offset` rows after `x`, or `-offset` rows before it if `offset` is negative.
+    /// Like `next_point`, this is only defined for cosets; `next_point(x)` is
+    /// `rotated_point(x, 1)`.
+    fn rotated_point<Ext: ExtensionField<Self::Val>>(&self, x: Ext, offset: isize) -> Option<Ext> {
+        if offset < 0 {
+            return None;
+        }
+        (0..offset).try_fold(x, |x, _| self.next_point(x))
+    }
+
+    /// The points at which a trace must be opened to access the rows at `offsets` relative to
+    /// the row of `x`. For example, a constraint window over the current and next rows uses
+    /// offsets `[0, 1]`.
+    fn rotated_points<Ext: ExtensionField<Self::Val>>(
+        &self,
+        x: Ext,
+        offsets: &[isize],
+    ) -> Option<Vec<Ext>> {
+        offsets
+            .iter()
+            .map(|&offset| self.rotated_point(x, offset))
+            .collect()
+    }
+
     // There are many choices for this, but we must pick a canonical one
     // for both prover/verifier determinism and LDE caching.
     fn create_disjoint_domain(&self, min_size: usize) -> Self;
@@ -52,9 +77,46 @@ pub trait PolynomialSpace: Copy {
 
     // Unnormalized
     fn selectors_on_coset(&self, coset: Self) -> LagrangeSelectors<Vec<Self::Val>>;
+
+    /// Like `selectors_at_point`, but for a trace which only fills the first `height` rows of this
+    /// domain, the rest being padding. `is_last_row` selects row `height - 1`, and
+    /// `is_transition` vanishes there as well as on the last row of the domain, so that no
+    /// transition links the trace to its padding. If `height` is the size of the domain, these
+    /// are the usual selectors.
+    fn selectors_at_point_for_height<Ext: ExtensionField<Self::Val>>(
+        &self,
+        point: Ext,
+        height: usize,
+    ) -> LagrangeSelectors<Ext>;
+
+    /// Like `selectors_on_coset`, for the selectors of `selectors_at_point_for_height`.
+    fn selectors_on_coset_for_height(
+        &self,
+        coset: Self,
+        height: usize,
+    ) -> LagrangeSelectors<Vec<Self::Val>>;
+
+    /// Whether `is_transition` is linear, as over a multiplicative coset, where it only has to
+    /// vanish at the last point. Otherwise it has the degree of `is_first_row`, and adds as much
+    /// to the degree of the constraints it selects.
+    fn has_linear_transition_selector(&self) -> bool {
+        true
+    }
+
+    /// The evaluation at `point` of the periodic column `values`, i.e. of the polynomial of degree
+    /// less than the size of this domain which takes the value `values[i % values.len()]` on its
+    /// `i`-th point. `values.len()` must be a power of two no larger than this domain.
+    fn periodic_column_at_point<Ext: ExtensionField<Self::Val>>(
+        &self,
+        values: &[Self::Val],
+        point: Ext,
+    ) -> Ext;
+
+    /// Like `periodic_column_at_point`, for every point of `coset`.
+    fn periodic_column_on_coset(&self, values: &[Self::Val], coset: Self) -> Vec<Self::Val>;
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct TwoAdicMultiplicativeCoset<Val: TwoAdicField> {
     pub log_n: usize,
     pub shift: Val,
@@ -79,6 +141,14 @@ impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
     fn next_point<Ext: ExtensionField<Val>>(&self, x: Ext) -> Option<Ext> {
         Some(x * self.gen())
     }
+    fn rotated_point<Ext: ExtensionField<Val>>(&self, x: Ext, offset: isize) -> Option<Ext> {
+        let gen = if offset < 0 {
+            self.gen().inverse()
+        } else {
+            self.gen()
+        };
+        Some(x * gen.exp_u64(offset.unsigned_abs() as u64))
+    }
 
     fn create_disjoint_domain(&self, min_size: usize) -> Self {
         Self {
@@ -127,7 +197,36 @@ impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
     }
 
     fn selectors_on_coset(&self, coset: Self) -> LagrangeSelectors<Vec<Val>> {
+        self.selectors_on_coset_for_height(coset, self.size())
+    }
+
+    fn selectors_at_point_for_height<Ext: ExtensionField<Val>>(
+        &self,
+        point: Ext,
+        height: usize,
+    ) -> LagrangeSelectors<Ext> {
+        assert!(0 < height && height <= self.size());
+        if height == self.size() {
+            return self.selectors_at_point(point);
+        }
+        let unshifted_point = point * self.shift.inverse();
+        let z_h = unshifted_point.exp_power_of_2(self.log_n) - Ext::ONE;
+        let last = self.gen().exp_u64(height as u64 - 1);
+        LagrangeSelectors {
+            is_first_row: z_h / (unshifted_point - Ext::ONE),
+            is_last_row: z_h / (unshifted_point - last),
+            is_transition: (unshifted_point - last) * (unshifted_point - self.gen().inverse()),
+            inv_zeroifier: z_h.inverse(),
+        }
+    }
+
+    fn selectors_on_coset_for_height(
+        &self,
+        coset: Self,
+        height: usize,
+    ) -> LagrangeSelectors<Vec<Val>> {
         assert_eq!(self.shift, Val::ONE);
+        assert!(0 < height && height <= self.size());
         assert_ne!(coset.shift, Val::ONE);
         assert!(coset.log_n >= self.log_n);
         let rate_bits = coset.log_n - self.log_n;
@@ -156,11 +255,19 @@ impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
         };
 
         let subgroup_last = self.gen().inverse();
+        let last = self.gen().exp_u64(height as u64 - 1);
+        let is_transition = if height == self.size() {
+            xs.iter().map(|&x| x - subgroup_last).collect()
+        } else {
+            xs.iter()
+                .map(|&x| (x - last) * (x - subgroup_last))
+                .collect()
+        };
 
         LagrangeSelectors {
             is_first_row: single_point_selector(0),
-            is_last_row: single_point_selector((1 << self.log_n) - 1),
-            is_transition: xs.into_iter().map(|x| x - subgroup_last).collect(),
+            is_last_row: single_point_selector(height as u64 - 1),
+            is_transition,
             inv_zeroifier: batch_multiplicative_inverse(&evals)
                 .into_iter()
                 .cycle()
@@ -168,4 +275,62 @@ impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
                 .collect(),
         }
     }
+
+    /// With `k = values.len()`, the column is `q((x / shift)^(n / k))`, where `q` interpolates
+    /// `values` over the subgroup of order `k`.
+    fn periodic_column_at_point<Ext: ExtensionField<Val>>(
+        &self,
+        values: &[Val],
+        point: Ext,
+    ) -> Ext {
+        let log_period = log2_strict_usize(values.len());
+        assert!(log_period <= self.log_n);
+        let y = (point * self.shift.inverse()).exp_power_of_2(self.log_n - log_period);
+        interpolate_over_subgroup(values, y)
+    }
+
+    /// `(x / shift)^(n / k)` only takes `k << rate_bits` values over `coset`, so `q` is only
+    /// interpolated at those.
+    fn periodic_column_on_coset(&self, values: &[Val], coset: Self) -> Vec<Val> {
+        let log_period = log2_strict_usize(values.len());
+        assert!(log_period <= self.log_n);
+        assert!(coset.log_n >= self.log_n);
+        let log_repeats = self.log_n - log_period;
+        let log_distinct = coset.log_n - log_repeats;
+
+        let shift = (coset.shift * self.shift.inverse()).exp_power_of_2(log_repeats);
+        let evals = cyclic_subgroup_coset_known_order(
+            Val::two_adic_generator(log_distinct),
+            shift,
+            1 << log_distinct,
+        )
+        .map(|y| interpolate_over_subgroup(values, y))
+        .collect_vec();
+        evals.into_iter().cycle().take(coset.size()).collect()
+    }
+}
+
+/// The evaluation at `y` of the polynomial which takes the value `values[i]` at `g^i`, where `g`
+/// generates the subgroup of order `values.len()`, by the barycentric formula
+/// `q(y) = (y^k - 1) / k * sum_i values[i] g^i / (y - g^i)`. `y` must be outside the subgroup.
+fn interpolate_over_subgroup<Val: TwoAdicField, Ext: ExtensionField<Val>>(
+    values: &[Val],
+    y: Ext,
+) -> Ext {
+    let log_k = log2_strict_usize(values.len());
+    if log_k == 0 {
+        return Ext::from_base(values[0]);
+    }
+    let points = Val::two_adic_generator(log_k)
+        .powers()
+        .take(values.len())
+        .collect_vec();
+    let diffs = points.iter().map(|&g_i| y - g_i).collect_vec();
+    let sum: Ext = points
+        .iter()
+        .zip(values)
+        .zip(batch_multiplicative_inverse(&diffs))
+        .map(|((&g_i, &v), inv)| inv * (g_i * v))
+        .sum();
+    sum * (y.exp_power_of_2(log_k) - Ext::ONE) * Val::from_canonical_usize(values.len()).inverse()
 }
@@ -2,7 +2,9 @@ use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
+use p3_matrix::bitrev::{RowOrder, RowOrderPerm, RowOrderedMatrixView};
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::row_block::RowBlockMatrix;
 use p3_matrix::{Dimensions, Matrix};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -28,6 +30,38 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         self.commit(vec![input])
     }
 
+    /// Like `commit`, but for matrices which are best accessed a block of rows at a time, such as
+    /// matrices which are computed on the fly. Implementations should avoid materializing more
+    /// than one block of each matrix at once. The commitment must be the same as `commit` would
+    /// produce for the same data.
+    ///
+    /// The default implementation simply calls `commit`.
+    fn commit_row_blocks<M: RowBlockMatrix<T>>(
+        &self,
+        inputs: Vec<M>,
+    ) -> (Self::Commitment, Self::ProverData<M>) {
+        self.commit(inputs)
+    }
+
+    /// Like `commit`, but with the rows of each input used in the order given in `row_orders`.
+    /// A matrix with `RowOrder::BitReversed` is committed to, and opened, as if its rows had been
+    /// bit-reversed, without copying it, so e.g. bit-reversed LDEs can be committed alongside
+    /// naturally ordered data.
+    fn commit_with_row_orders<M: Matrix<T>>(
+        &self,
+        inputs: Vec<M>,
+        row_orders: &[RowOrder],
+    ) -> (Self::Commitment, Self::ProverData<RowOrderedMatrixView<M>>) {
+        assert_eq!(inputs.len(), row_orders.len(), "wrong number of row orders");
+        self.commit(
+            inputs
+                .into_iter()
+                .zip(row_orders)
+                .map(|(input, &order)| RowOrderPerm::new_view(input, order))
+                .collect(),
+        )
+    }
+
     fn commit_vec(&self, input: Vec<T>) -> (Self::Commitment, Self::ProverData<RowMajorMatrix<T>>)
     where
         T: Clone + Send + Sync,
@@ -45,6 +79,22 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         prover_data: &Self::ProverData<M>,
     ) -> (Vec<Vec<T>>, Self::Proof);
 
+    /// Open a batch of rows at each of `indices`, returning what `open_batch` would for each.
+    ///
+    /// Implementations can share work between the indices, e.g. reading each row and each layer
+    /// of a tree once. The default implementation opens each index in turn.
+    #[allow(clippy::type_complexity)]
+    fn open_many<M: Matrix<T>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> Vec<(Vec<Vec<T>>, Self::Proof)> {
+        indices
+            .iter()
+            .map(|&index| self.open_batch(index, prover_data))
+            .collect()
+    }
+
     /// Get the matrices that were committed to.
     fn get_matrices<'a, M: Matrix<T>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M>;
 
@@ -76,4 +126,23 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         opened_values: &[Vec<T>],
         proof: &Self::Proof,
     ) -> Result<(), Self::Error>;
+
+    /// Verify several batch openings of the same commitment, each given as
+    /// `(index, opened_values, proof)` with the same semantics as in `verify_batch`.
+    ///
+    /// Implementations can share work between the openings, e.g. by hashing several of them at
+    /// once with packed values. The default implementation verifies each opening in turn.
+    #[allow(clippy::type_complexity)]
+    fn verify_batches(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        openings: &[(usize, &[Vec<T>], &Self::Proof)],
+    ) -> Result<(), Self::Error> {
+        openings
+            .iter()
+            .try_for_each(|&(index, opened_values, proof)| {
+                self.verify_batch(commit, dimensions, index, opened_values, proof)
+            })
+    }
 }
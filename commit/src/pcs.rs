@@ -32,6 +32,15 @@ where
 
     type Error: Debug;
 
+    /// Whether the PCS is zero-knowledge, i.e. blinds the polynomials it commits to, so that the
+    /// openings reveal nothing else about them.
+    ///
+    /// If so, `commit` adds a random multiple of the vanishing polynomial of each matrix's domain
+    /// to its polynomial, which doesn't change its evaluations over the domain but doubles its
+    /// degree, and a quotient must be committed to with `commit_quotient`, so that the masks of
+    /// its chunks cancel out when they're recombined.
+    const ZK: bool = false;
+
     /// This should return a coset domain (s.t. Domain::next_point returns Some)
     fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain;
 
@@ -41,6 +50,22 @@ where
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
     ) -> (Self::Commitment, Self::ProverData);
 
+    /// Commit to the chunks of some quotient polynomials, given for each quotient as the
+    /// evaluations over the domains from `split_domains`, in order. The chunks are committed to
+    /// together, as if by `commit`.
+    ///
+    /// A zero-knowledge PCS masks each chunk with a random multiple of its domain's vanishing
+    /// polynomial, such that the masks of a quotient's chunks sum to zero once each chunk is
+    /// weighted by the vanishing polynomials of the other chunks' domains, normalized to one
+    /// over its own, as the chunks are recombined at the opening point.
+    #[allow(clippy::type_complexity)]
+    fn commit_quotient(
+        &self,
+        quotients: Vec<Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        self.commit(quotients.into_iter().flatten().collect())
+    }
+
     fn get_evaluations_on_domain<'a>(
         &self,
         prover_data: &'a Self::ProverData,
@@ -48,6 +73,36 @@ where
         domain: Self::Domain,
     ) -> impl Matrix<Val<Self::Domain>> + 'a;
 
+    /// The log of the largest ratio between the size of a domain and that of the domain a matrix
+    /// was committed over for which `get_evaluations_on_domain` works, usually the blowup of the
+    /// committed low-degree extensions. `None` if there's no such limit.
+    fn max_log_evaluation_blowup(&self) -> Option<usize> {
+        None
+    }
+
+    /// The committed evaluations of the `idx`th matrix in `prover_data`, in natural order, along
+    /// with the domain they're over. This is usually a low-degree extension of the domain that
+    /// was given to `commit`.
+    ///
+    /// Unlike `get_evaluations_on_domain`, this never copies or recomputes anything; the result is
+    /// a view into `prover_data`. Returns `None` if the prover data doesn't hold evaluations.
+    #[allow(clippy::type_complexity)]
+    fn get_committed_evaluations<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val<Self::Domain>> + 'a)>;
+
+    /// Like `get_committed_evaluations`, but with the rows in the order in which they were
+    /// committed, which is bit-reversed order for two-adic domains (or the analogous CFFT order
+    /// for circle domains).
+    #[allow(clippy::type_complexity)]
+    fn get_committed_evaluations_bit_reversed<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val<Self::Domain>> + 'a)>;
+
     fn open(
         &self,
         // For each round,
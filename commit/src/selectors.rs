@@ -0,0 +1,119 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+
+use spin::Mutex;
+
+use crate::{LagrangeSelectors, PolynomialSpace};
+
+/// A cache of the selectors of `PolynomialSpace::selectors_on_coset_for_height`, keyed by the
+/// domain, the coset and the height, so that a prover proving many statements of the same shape
+/// computes them once.
+///
+/// Selectors at a point aren't cached: they only take a few field operations, and the point is
+/// usually a fresh challenge.
+#[derive(Debug, Default)]
+pub struct LagrangeSelectorCache {
+    /// A `CacheEntry<D>` for each domain type `D` and key.
+    entries: Mutex<Vec<Box<dyn Any + Send + Sync>>>,
+}
+
+struct CacheEntry<D: PolynomialSpace> {
+    domain: D,
+    coset: D,
+    height: usize,
+    selectors: Arc<LagrangeSelectors<Vec<D::Val>>>,
+}
+
+impl LagrangeSelectorCache {
+    pub const fn new() -> Self {
+        Self {
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Like `domain.selectors_on_coset_for_height(coset, height)`, but only computed the first
+    /// time.
+    pub fn selectors_on_coset_for_height<D: PolynomialSpace>(
+        &self,
+        domain: D,
+        coset: D,
+        height: usize,
+    ) -> Arc<LagrangeSelectors<Vec<D::Val>>> {
+        if let Some(selectors) = self.get(domain, coset, height) {
+            return selectors;
+        }
+        // Computed without holding the lock, so other keys can be looked up in the meantime.
+        let selectors = Arc::new(domain.selectors_on_coset_for_height(coset, height));
+        if let Some(selectors) = self.get(domain, coset, height) {
+            return selectors;
+        }
+        self.entries.lock().push(Box::new(CacheEntry {
+            domain,
+            coset,
+            height,
+            selectors: selectors.clone(),
+        }));
+        selectors
+    }
+
+    /// Drop all cached selectors.
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    fn get<D: PolynomialSpace>(
+        &self,
+        domain: D,
+        coset: D,
+        height: usize,
+    ) -> Option<Arc<LagrangeSelectors<Vec<D::Val>>>> {
+        self.entries.lock().iter().find_map(|entry| {
+            let entry = entry.downcast_ref::<CacheEntry<D>>()?;
+            (entry.domain == domain && entry.coset == coset && entry.height == height)
+                .then(|| entry.selectors.clone())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::{AbstractField, Field};
+
+    use crate::{LagrangeSelectorCache, PolynomialSpace, TwoAdicMultiplicativeCoset};
+
+    type F = BabyBear;
+
+    #[test]
+    fn cache_matches_uncached() {
+        let cache = LagrangeSelectorCache::new();
+        let domain = TwoAdicMultiplicativeCoset {
+            log_n: 3,
+            shift: F::ONE,
+        };
+        let coset = TwoAdicMultiplicativeCoset {
+            log_n: 5,
+            shift: F::GENERATOR,
+        };
+        let sels = cache.selectors_on_coset_for_height(domain, coset, 6);
+        assert_eq!(*sels, domain.selectors_on_coset_for_height(coset, 6));
+        assert!(Arc::ptr_eq(
+            &sels,
+            &cache.selectors_on_coset_for_height(domain, coset, 6)
+        ));
+
+        let other = cache.selectors_on_coset_for_height(domain, coset, 8);
+        assert!(!Arc::ptr_eq(&sels, &other));
+        assert_eq!(*other, domain.selectors_on_coset(coset));
+
+        cache.clear();
+        assert!(!Arc::ptr_eq(
+            &sels,
+            &cache.selectors_on_coset_for_height(domain, coset, 6)
+        ));
+    }
+}
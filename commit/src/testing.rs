@@ -2,7 +2,7 @@ use alloc::vec;
 use alloc::vec::Vec;
 use core::marker::PhantomData;
 
-use p3_challenger::CanSample;
+use p3_challenger::FieldChallenger;
 use p3_dft::TwoAdicSubgroupDft;
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::dense::RowMajorMatrix;
@@ -13,6 +13,11 @@ use serde::{Deserialize, Serialize};
 use crate::{OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
 
 /// A trivial PCS: its commitment is simply the coefficients of each poly.
+///
+/// This is useful for debugging, and for differential testing against real PCSs. It can be used
+/// in place of e.g. `TwoAdicFriPcs` in any config, including ones which open at extension field
+/// points, and it uses the challenger in the same way: both `open` and `verify` sample a batch
+/// combination challenge before doing anything else. Its proofs are empty.
 #[derive(Debug)]
 pub struct TrivialPcs<Val: TwoAdicField, Dft: TwoAdicSubgroupDft<Val>> {
     pub dft: Dft,
@@ -21,6 +26,19 @@ pub struct TrivialPcs<Val: TwoAdicField, Dft: TwoAdicSubgroupDft<Val>> {
     pub _phantom: PhantomData<Val>,
 }
 
+/// The reasons a `TrivialPcs` opening can be rejected.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TrivialPcsError {
+    /// The claimed openings don't have the same shape as the commitment.
+    InvalidShape,
+    /// A claimed value doesn't match the committed polynomial.
+    WrongOpenedValues {
+        round: usize,
+        matrix: usize,
+        point: usize,
+    },
+}
+
 pub fn eval_coeffs_at_pt<F: Field, EF: ExtensionField<F>>(
     coeffs: &RowMajorMatrix<F>,
     x: EF,
@@ -40,7 +58,7 @@ impl<Val, Dft, Challenge, Challenger> Pcs<Challenge, Challenger> for TrivialPcs<
 where
     Val: TwoAdicField,
     Challenge: ExtensionField<Val>,
-    Challenger: CanSample<Challenge>,
+    Challenger: FieldChallenger<Val>,
 
     Dft: TwoAdicSubgroupDft<Val>,
 
@@ -50,7 +68,7 @@ where
     type Commitment = Vec<Vec<Val>>;
     type ProverData = Vec<RowMajorMatrix<Val>>;
     type Proof = ();
-    type Error = ();
+    type Error = TrivialPcsError;
 
     fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
         TwoAdicMultiplicativeCoset {
@@ -96,14 +114,30 @@ where
         domain: Self::Domain,
     ) -> impl Matrix<Val> + 'a {
         let mut coeffs = prover_data[idx].clone();
-        assert!(domain.log_n >= self.log_n);
-        coeffs.values.resize(
-            coeffs.values.len() << (domain.log_n - self.log_n),
-            Val::ZERO,
-        );
+        assert!(domain.size() >= coeffs.height());
+        coeffs
+            .values
+            .resize(domain.size() * coeffs.width(), Val::ZERO);
         self.dft.coset_dft_batch(coeffs, domain.shift)
     }
 
+    fn get_committed_evaluations<'a>(
+        &self,
+        _prover_data: &'a Self::ProverData,
+        _idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val> + 'a)> {
+        // We only store coefficients.
+        None::<(Self::Domain, RowMajorMatrix<Val>)>
+    }
+
+    fn get_committed_evaluations_bit_reversed<'a>(
+        &self,
+        _prover_data: &'a Self::ProverData,
+        _idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val> + 'a)> {
+        None::<(Self::Domain, RowMajorMatrix<Val>)>
+    }
+
     fn open(
         &self,
         // For each round,
@@ -115,8 +149,11 @@ where
                 Vec<Challenge>,
             >,
         )>,
-        _challenger: &mut Challenger,
+        challenger: &mut Challenger,
     ) -> (OpenedValues<Challenge>, Self::Proof) {
+        // Batch combination challenge, which we don't need, but sample to stay in sync with
+        // other PCSs.
+        let _alpha: Challenge = challenger.sample_ext_element();
         (
             rounds
                 .into_iter()
@@ -155,15 +192,30 @@ where
             )>,
         )>,
         _proof: &Self::Proof,
-        _challenger: &mut Challenger,
+        challenger: &mut Challenger,
     ) -> Result<(), Self::Error> {
-        for (comm, round_opening) in rounds {
-            for (coeff_vec, (domain, points_and_values)) in comm.into_iter().zip(round_opening) {
+        let _alpha: Challenge = challenger.sample_ext_element();
+
+        for (round, (comm, round_opening)) in rounds.into_iter().enumerate() {
+            if comm.len() != round_opening.len() {
+                return Err(TrivialPcsError::InvalidShape);
+            }
+            for (matrix, (coeff_vec, (domain, points_and_values))) in
+                comm.into_iter().zip(round_opening).enumerate()
+            {
                 let width = coeff_vec.len() / domain.size();
-                assert_eq!(width * domain.size(), coeff_vec.len());
+                if width == 0 || width * domain.size() != coeff_vec.len() {
+                    return Err(TrivialPcsError::InvalidShape);
+                }
                 let coeffs = RowMajorMatrix::new(coeff_vec, width);
-                for (pt, values) in points_and_values {
-                    assert_eq!(eval_coeffs_at_pt(&coeffs, pt), values);
+                for (point, (pt, values)) in points_and_values.into_iter().enumerate() {
+                    if eval_coeffs_at_pt(&coeffs, pt) != values {
+                        return Err(TrivialPcsError::WrongOpenedValues {
+                            round,
+                            matrix,
+                            point,
+                        });
+                    }
                 }
             }
         }
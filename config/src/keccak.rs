@@ -0,0 +1,113 @@
+use alloc::vec::Vec;
+
+use p3_baby_bear::BabyBear;
+use p3_challenger::{HashChallenger, SerializingChallenger32};
+use p3_circle::CirclePcs;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::TwoAdicFriPcs;
+use p3_keccak::Keccak256Hash;
+use p3_koala_bear::KoalaBear;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_mersenne_31::{Mersenne31, Qm31};
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+use p3_uni_stark::StarkConfig;
+
+use crate::{ConfigOptions, Preset};
+
+type FieldHash = SerializingHasher32<Keccak256Hash>;
+type Compress = CompressionFunctionFromHasher<Keccak256Hash, 2, 32>;
+type KeccakMmcs<F> = MerkleTreeMmcs<F, u8, FieldHash, Compress, 32>;
+type KeccakChallenger<F> = SerializingChallenger32<F, HashChallenger<u8, Keccak256Hash, 32>>;
+
+/// A field with a degree 4 extension for challenges, Merkle trees of Keccak-256 digests of the
+/// serialized field elements, and a Keccak-256 challenger.
+pub type KeccakConfig<F> = StarkConfig<
+    TwoAdicFriPcs<
+        F,
+        Radix2DitParallel<F>,
+        KeccakMmcs<F>,
+        ExtensionMmcs<F, BinomialExtensionField<F, 4>, KeccakMmcs<F>>,
+    >,
+    BinomialExtensionField<F, 4>,
+    KeccakChallenger<F>,
+>;
+
+pub type BabyBearKeccakConfig = KeccakConfig<BabyBear>;
+
+pub type KoalaBearKeccakConfig = KeccakConfig<KoalaBear>;
+
+/// Mersenne31, which isn't two-adic, committed with circle FRI, with its degree 4 extension `Qm31`
+/// for challenges, Merkle trees of Keccak-256 digests of the serialized field elements, and a
+/// Keccak-256 challenger.
+pub type Mersenne31CircleKeccakConfig = StarkConfig<
+    CirclePcs<
+        Mersenne31,
+        KeccakMmcs<Mersenne31>,
+        ExtensionMmcs<Mersenne31, Qm31, KeccakMmcs<Mersenne31>>,
+    >,
+    Qm31,
+    KeccakChallenger<Mersenne31>,
+>;
+
+impl ConfigOptions {
+    pub fn baby_bear_keccak(&self) -> Preset<BabyBearKeccakConfig> {
+        let val_mmcs = KeccakMmcs::new(
+            FieldHash::new(Keccak256Hash {}),
+            Compress::new(Keccak256Hash {}),
+        );
+        let fri_config = self.fri_config(ExtensionMmcs::new(val_mmcs.clone()));
+        let pcs = TwoAdicFriPcs::new(Radix2DitParallel::default(), val_mmcs, fri_config);
+        Preset {
+            config: StarkConfig::new(pcs),
+            challenger: KeccakChallenger::from_hasher(Vec::new(), Keccak256Hash {}),
+        }
+    }
+
+    pub fn koala_bear_keccak(&self) -> Preset<KoalaBearKeccakConfig> {
+        let val_mmcs = KeccakMmcs::new(
+            FieldHash::new(Keccak256Hash {}),
+            Compress::new(Keccak256Hash {}),
+        );
+        let fri_config = self.fri_config(ExtensionMmcs::new(val_mmcs.clone()));
+        let pcs = TwoAdicFriPcs::new(Radix2DitParallel::default(), val_mmcs, fri_config);
+        Preset {
+            config: StarkConfig::new(pcs),
+            challenger: KeccakChallenger::from_hasher(Vec::new(), Keccak256Hash {}),
+        }
+    }
+
+    pub fn mersenne_31_circle_keccak(&self) -> Preset<Mersenne31CircleKeccakConfig> {
+        let val_mmcs = KeccakMmcs::new(
+            FieldHash::new(Keccak256Hash {}),
+            Compress::new(Keccak256Hash {}),
+        );
+        let fri_config = self.fri_config(ExtensionMmcs::new(val_mmcs.clone()));
+        let pcs = CirclePcs::new(val_mmcs, fri_config);
+        Preset {
+            config: StarkConfig::new(pcs),
+            challenger: KeccakChallenger::from_hasher(Vec::new(), Keccak256Hash {}),
+        }
+    }
+}
+
+/// A [`BabyBearKeccakConfig`] with `security_bits` bits of soundness and the other
+/// [`ConfigOptions`] at their defaults.
+pub fn baby_bear_keccak_config(security_bits: usize) -> Preset<BabyBearKeccakConfig> {
+    ConfigOptions::new(security_bits).baby_bear_keccak()
+}
+
+/// A [`KoalaBearKeccakConfig`] with `security_bits` bits of soundness and the other
+/// [`ConfigOptions`] at their defaults.
+pub fn koala_bear_keccak_config(security_bits: usize) -> Preset<KoalaBearKeccakConfig> {
+    ConfigOptions::new(security_bits).koala_bear_keccak()
+}
+
+/// A [`Mersenne31CircleKeccakConfig`] with `security_bits` bits of soundness and the other
+/// [`ConfigOptions`] at their defaults.
+pub fn mersenne_31_circle_keccak_config(
+    security_bits: usize,
+) -> Preset<Mersenne31CircleKeccakConfig> {
+    ConfigOptions::new(security_bits).mersenne_31_circle_keccak()
+}
@@ -0,0 +1,13 @@
+//! Ready-to-use `StarkConfig`s for common choices of field and hash.
+
+#![no_std]
+
+extern crate alloc;
+
+mod keccak;
+mod options;
+mod poseidon2;
+
+pub use keccak::*;
+pub use options::*;
+pub use poseidon2::*;
@@ -0,0 +1,75 @@
+use p3_fri::{FriConfig, SecurityAssumption};
+use p3_uni_stark::StarkGenericConfig;
+
+/// A config, with the challenger in the state which both proving and verifying start from.
+///
+/// Clone `challenger` for each proof or verification.
+pub struct Preset<SC: StarkGenericConfig> {
+    pub config: SC,
+    pub challenger: SC::Challenger,
+}
+
+/// The parameters of a preset which don't depend on its field or hash.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ConfigOptions {
+    /// The bits of soundness to reach under `security_assumption`, with both queries and
+    /// proof-of-work.
+    pub security_bits: usize,
+    /// The log of the FRI blowup, which must be at least the log of the quotient degree of any
+    /// AIR proven with the config.
+    pub log_blowup: usize,
+    pub log_final_poly_len: usize,
+    /// The number of proof-of-work bits the queries can rely on; see
+    /// `FriConfig::for_security_level`.
+    pub proof_of_work_bits: usize,
+    pub security_assumption: SecurityAssumption,
+}
+
+impl ConfigOptions {
+    /// Options reaching `security_bits` bits of soundness under the capacity bound conjecture,
+    /// with a blowup of 2 and 16 bits of proof-of-work.
+    pub const fn new(security_bits: usize) -> Self {
+        Self {
+            security_bits,
+            log_blowup: 1,
+            log_final_poly_len: 0,
+            proof_of_work_bits: 16,
+            security_assumption: SecurityAssumption::CapacityBound,
+        }
+    }
+
+    pub const fn with_log_blowup(mut self, log_blowup: usize) -> Self {
+        self.log_blowup = log_blowup;
+        self
+    }
+
+    pub const fn with_log_final_poly_len(mut self, log_final_poly_len: usize) -> Self {
+        self.log_final_poly_len = log_final_poly_len;
+        self
+    }
+
+    pub const fn with_proof_of_work_bits(mut self, proof_of_work_bits: usize) -> Self {
+        self.proof_of_work_bits = proof_of_work_bits;
+        self
+    }
+
+    pub const fn with_security_assumption(
+        mut self,
+        security_assumption: SecurityAssumption,
+    ) -> Self {
+        self.security_assumption = security_assumption;
+        self
+    }
+
+    /// The FRI config for these options, committing with `mmcs`.
+    pub fn fri_config<M>(&self, mmcs: M) -> FriConfig<M> {
+        FriConfig::for_security_level(
+            self.log_blowup,
+            self.log_final_poly_len,
+            self.security_assumption,
+            self.security_bits,
+            self.proof_of_work_bits,
+            mmcs,
+        )
+    }
+}
@@ -0,0 +1,127 @@
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::Field;
+use p3_fri::TwoAdicFriPcs;
+use p3_koala_bear::{DiffusionMatrixKoalaBear, KoalaBear};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::StarkConfig;
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128Plus;
+
+use crate::{ConfigOptions, Preset};
+
+/// The seed of the round constants of the presets' Poseidon2 permutations. These are the constants
+/// of the `Xoroshiro128Plus` test vectors of the field crates, so other implementations can
+/// reproduce them.
+const ROUND_CONSTANTS_SEED: u64 = 1;
+
+/// The width 16 Poseidon2 permutation of the BabyBear presets.
+pub type BabyBearPoseidon2 =
+    Poseidon2<BabyBear, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+
+type BabyBearHash = PaddingFreeSponge<BabyBearPoseidon2, 16, 8, 8>;
+type BabyBearCompress = TruncatedPermutation<BabyBearPoseidon2, 2, 8, 16>;
+type BabyBearMmcs = MerkleTreeMmcs<
+    <BabyBear as Field>::Packing,
+    <BabyBear as Field>::Packing,
+    BabyBearHash,
+    BabyBearCompress,
+    8,
+>;
+type BabyBearChallenge = BinomialExtensionField<BabyBear, 4>;
+
+/// BabyBear with a degree 4 extension for challenges, Merkle trees of Poseidon2 digests, and a
+/// Poseidon2 duplex challenger.
+pub type BabyBearPoseidon2Config = StarkConfig<
+    TwoAdicFriPcs<
+        BabyBear,
+        Radix2DitParallel<BabyBear>,
+        BabyBearMmcs,
+        ExtensionMmcs<BabyBear, BabyBearChallenge, BabyBearMmcs>,
+    >,
+    BabyBearChallenge,
+    DuplexChallenger<BabyBear, BabyBearPoseidon2, 16, 8>,
+>;
+
+/// The width 16 Poseidon2 permutation of the KoalaBear presets.
+pub type KoalaBearPoseidon2 =
+    Poseidon2<KoalaBear, Poseidon2ExternalMatrixGeneral, DiffusionMatrixKoalaBear, 16, 3>;
+
+type KoalaBearHash = PaddingFreeSponge<KoalaBearPoseidon2, 16, 8, 8>;
+type KoalaBearCompress = TruncatedPermutation<KoalaBearPoseidon2, 2, 8, 16>;
+type KoalaBearMmcs = MerkleTreeMmcs<
+    <KoalaBear as Field>::Packing,
+    <KoalaBear as Field>::Packing,
+    KoalaBearHash,
+    KoalaBearCompress,
+    8,
+>;
+type KoalaBearChallenge = BinomialExtensionField<KoalaBear, 4>;
+
+/// KoalaBear with a degree 4 extension for challenges, Merkle trees of Poseidon2 digests, and a
+/// Poseidon2 duplex challenger.
+pub type KoalaBearPoseidon2Config = StarkConfig<
+    TwoAdicFriPcs<
+        KoalaBear,
+        Radix2DitParallel<KoalaBear>,
+        KoalaBearMmcs,
+        ExtensionMmcs<KoalaBear, KoalaBearChallenge, KoalaBearMmcs>,
+    >,
+    KoalaBearChallenge,
+    DuplexChallenger<KoalaBear, KoalaBearPoseidon2, 16, 8>,
+>;
+
+impl ConfigOptions {
+    pub fn baby_bear_poseidon2(&self) -> Preset<BabyBearPoseidon2Config> {
+        let perm = BabyBearPoseidon2::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut Xoroshiro128Plus::seed_from_u64(ROUND_CONSTANTS_SEED),
+        );
+        let val_mmcs = BabyBearMmcs::new(
+            BabyBearHash::new(perm.clone()),
+            BabyBearCompress::new(perm.clone()),
+        );
+        let fri_config = self.fri_config(ExtensionMmcs::new(val_mmcs.clone()));
+        let pcs = TwoAdicFriPcs::new(Radix2DitParallel::default(), val_mmcs, fri_config);
+        Preset {
+            config: StarkConfig::new(pcs),
+            challenger: DuplexChallenger::new(perm),
+        }
+    }
+
+    pub fn koala_bear_poseidon2(&self) -> Preset<KoalaBearPoseidon2Config> {
+        let perm = KoalaBearPoseidon2::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixKoalaBear::default(),
+            &mut Xoroshiro128Plus::seed_from_u64(ROUND_CONSTANTS_SEED),
+        );
+        let val_mmcs = KoalaBearMmcs::new(
+            KoalaBearHash::new(perm.clone()),
+            KoalaBearCompress::new(perm.clone()),
+        );
+        let fri_config = self.fri_config(ExtensionMmcs::new(val_mmcs.clone()));
+        let pcs = TwoAdicFriPcs::new(Radix2DitParallel::default(), val_mmcs, fri_config);
+        Preset {
+            config: StarkConfig::new(pcs),
+            challenger: DuplexChallenger::new(perm),
+        }
+    }
+}
+
+/// A [`BabyBearPoseidon2Config`] with `security_bits` bits of soundness and the other
+/// [`ConfigOptions`] at their defaults.
+pub fn baby_bear_poseidon2_config(security_bits: usize) -> Preset<BabyBearPoseidon2Config> {
+    ConfigOptions::new(security_bits).baby_bear_poseidon2()
+}
+
+/// A [`KoalaBearPoseidon2Config`] with `security_bits` bits of soundness and the other
+/// [`ConfigOptions`] at their defaults.
+pub fn koala_bear_poseidon2_config(security_bits: usize) -> Preset<KoalaBearPoseidon2Config> {
+    ConfigOptions::new(security_bits).koala_bear_poseidon2()
+}
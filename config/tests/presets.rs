@@ -0,0 +1,98 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_config::{
+    baby_bear_keccak_config, baby_bear_poseidon2_config, koala_bear_keccak_config,
+    koala_bear_poseidon2_config, mersenne_31_circle_keccak_config, ConfigOptions, Preset,
+};
+use p3_field::AbstractField;
+use p3_fri::SecurityAssumption;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{prove, verify, StarkGenericConfig, Val};
+
+/// A single column counting up from zero.
+struct CounterAir;
+
+impl<F> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+        builder.when_first_row().assert_zero(local);
+        builder
+            .when_transition()
+            .assert_eq(next, local + AB::Expr::ONE);
+    }
+}
+
+fn prove_and_verify<SC>(preset: Preset<SC>)
+where
+    SC: StarkGenericConfig,
+    SC::Challenger: Clone,
+{
+    let trace = RowMajorMatrix::new_col((0..1 << 6).map(Val::<SC>::from_canonical_u64).collect());
+    let proof = prove(
+        &preset.config,
+        &CounterAir,
+        &mut preset.challenger.clone(),
+        trace,
+        &vec![],
+    );
+    verify(
+        &preset.config,
+        &CounterAir,
+        &mut preset.challenger.clone(),
+        &proof,
+        &vec![],
+    )
+    .expect("verification failed");
+}
+
+#[test]
+fn test_baby_bear_poseidon2() {
+    prove_and_verify(baby_bear_poseidon2_config(100));
+}
+
+#[test]
+fn test_koala_bear_poseidon2() {
+    prove_and_verify(koala_bear_poseidon2_config(100));
+}
+
+#[test]
+fn test_baby_bear_keccak() {
+    prove_and_verify(baby_bear_keccak_config(100));
+}
+
+#[test]
+fn test_koala_bear_keccak() {
+    prove_and_verify(koala_bear_keccak_config(100));
+}
+
+#[test]
+fn test_mersenne_31_circle_keccak() {
+    prove_and_verify(mersenne_31_circle_keccak_config(100));
+}
+
+#[test]
+fn test_options() {
+    // Each query gives `log_blowup` bits under the capacity bound, and proof-of-work the rest.
+    let options = ConfigOptions::new(100);
+    assert_eq!(options.fri_config(()).num_queries, 84);
+    assert_eq!(options.with_log_blowup(2).fri_config(()).num_queries, 42);
+    assert_eq!(
+        options
+            .with_proof_of_work_bits(20)
+            .fri_config(())
+            .num_queries,
+        80
+    );
+
+    let options = options
+        .with_log_blowup(2)
+        .with_security_assumption(SecurityAssumption::JohnsonBound);
+    prove_and_verify(options.baby_bear_poseidon2());
+}
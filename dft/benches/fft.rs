@@ -1,6 +1,9 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use p3_baby_bear::BabyBear;
-use p3_dft::{Radix2Bowers, Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_dft::{
+    DftWorkspace, Radix2Bowers, Radix2Dit, Radix2DitParallel, SixStepDft, SplitRadixDft,
+    TwoAdicSubgroupDft,
+};
 use p3_field::extension::Complex;
 use p3_field::TwoAdicField;
 use p3_goldilocks::Goldilocks;
@@ -20,13 +23,23 @@ fn bench_fft(c: &mut Criterion) {
 
     const BATCH_SIZE: usize = 256;
 
+    // Sizes at which the radix-2 networks no longer fit in cache, with few enough columns to fit
+    // in memory.
+    let log_large_sizes = &[24, 25, 26, 27];
+    const LARGE_BATCH_SIZE: usize = 4;
+
     fft::<BabyBear, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
     fft::<BabyBear, RecursiveDft<_>, BATCH_SIZE>(c, log_sizes);
     fft::<BabyBear, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
     fft::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
+    fft::<BabyBear, SplitRadixDft<_>, BATCH_SIZE>(c, log_sizes);
+    fft::<BabyBear, Radix2DitParallel<_>, LARGE_BATCH_SIZE>(c, log_large_sizes);
+    fft::<BabyBear, SixStepDft<_>, LARGE_BATCH_SIZE>(c, log_large_sizes);
     fft::<Goldilocks, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
     fft::<Goldilocks, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
     fft::<Goldilocks, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
+    fft::<Goldilocks, Radix2DitParallel<_>, LARGE_BATCH_SIZE>(c, log_large_sizes);
+    fft::<Goldilocks, SixStepDft<_>, LARGE_BATCH_SIZE>(c, log_large_sizes);
     fft::<Complex<Mersenne31>, Radix2Dit<_>, BATCH_SIZE>(c, log_half_sizes);
     fft::<Complex<Mersenne31>, Radix2Bowers, BATCH_SIZE>(c, log_half_sizes);
     fft::<Complex<Mersenne31>, Radix2DitParallel<_>, BATCH_SIZE>(c, log_half_sizes);
@@ -41,7 +54,9 @@ fn bench_fft(c: &mut Criterion) {
     coset_lde::<BabyBear, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
+    coset_lde::<BabyBear, SplitRadixDft<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<Goldilocks, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
+    coset_lde_workspace::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
 }
 
 fn fft<F, Dft, const BATCH_SIZE: usize>(c: &mut Criterion, log_sizes: &[usize])
@@ -157,5 +172,36 @@ where
     }
 }
 
+fn coset_lde_workspace<F, Dft, const BATCH_SIZE: usize>(c: &mut Criterion, log_sizes: &[usize])
+where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F>,
+    Standard: Distribution<F>,
+{
+    let mut group = c.benchmark_group(format!(
+        "coset_lde_workspace/{}/{}/ncols={}",
+        pretty_name::<F>(),
+        pretty_name::<Dft>(),
+        BATCH_SIZE
+    ));
+    group.sample_size(10);
+
+    let mut rng = thread_rng();
+    for n_log in log_sizes {
+        let n = 1 << n_log;
+
+        let messages = RowMajorMatrix::rand(&mut rng, n, BATCH_SIZE);
+
+        let dft = Dft::default();
+        let mut workspace = DftWorkspace::new();
+        group.bench_with_input(BenchmarkId::from_parameter(n), &dft, |b, dft| {
+            b.iter(|| {
+                let lde = workspace.coset_lde_batch(dft, messages.as_view(), 1, F::GENERATOR);
+                workspace.recycle_evaluations(lde);
+            });
+        });
+    }
+}
+
 criterion_group!(benches, bench_fft);
 criterion_main!(benches);
@@ -0,0 +1,209 @@
+use core::fmt::Debug;
+
+use p3_field::TwoAdicField;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
+use tracing::{instrument, warn};
+
+use crate::TwoAdicSubgroupDft;
+
+/// The smallest `log_height` for which `OffloadDft` uses its backend by default. Below this, the
+/// cost of moving the data to and from an accelerator usually outweighs the transform.
+pub const DEFAULT_OFFLOAD_MIN_LOG_H: usize = 16;
+
+/// The shape of a batch of columns passed to a `DftBackend`.
+///
+/// The data itself is a row-major slice of field elements in their in-memory representation (e.g.
+/// Montgomery form for `MontyField31`), so it can be handed across an FFI boundary as a pointer
+/// and length along with this.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DftBatchLayout {
+    /// The log of the height of the input.
+    pub log_height: u32,
+    /// The number of columns.
+    pub width: u32,
+    /// The log of the ratio of the output height to the input height, zero for a plain DFT.
+    pub added_bits: u32,
+}
+
+impl DftBatchLayout {
+    pub fn new(log_height: usize, width: usize, added_bits: usize) -> Self {
+        Self {
+            log_height: log_height.try_into().unwrap(),
+            width: width.try_into().unwrap(),
+            added_bits: added_bits.try_into().unwrap(),
+        }
+    }
+
+    /// The number of elements of the output.
+    pub const fn output_len(&self) -> usize {
+        (self.width as usize) << (self.log_height + self.added_bits)
+    }
+}
+
+/// A device which can run DFTs, e.g. a GPU, to which `OffloadDft` hands large transforms.
+///
+/// The results must match those of the CPU DFTs in this crate, with rows in their natural order.
+/// On an error, `values` must be left unmodified, so that the transform can be retried on the CPU.
+pub trait DftBackend<F: TwoAdicField>: Clone + Default {
+    type Error: Debug;
+
+    /// Replace `values`, a row-major matrix with the given layout, whose `added_bits` is zero, by
+    /// the DFT of each of its columns.
+    fn dft_batch(&self, layout: DftBatchLayout, values: &mut [F]) -> Result<(), Self::Error>;
+
+    /// Replace `values` by the LDE of each column onto the coset `shift H`, where `H` is the
+    /// subgroup of order `2^(log_height + added_bits)`. On input, the first
+    /// `width << log_height` elements hold the evaluations over the subgroup of order
+    /// `2^log_height`, and the rest are zero.
+    fn coset_lde_batch(
+        &self,
+        layout: DftBatchLayout,
+        shift: F,
+        values: &mut [F],
+    ) -> Result<(), Self::Error>;
+}
+
+/// A DFT which runs transforms of at least `2^min_log_h` rows on `Backend`, and smaller ones, or
+/// ones the backend fails on, with `Cpu`.
+#[derive(Clone, Debug)]
+pub struct OffloadDft<Backend, Cpu> {
+    backend: Backend,
+    cpu: Cpu,
+    min_log_h: usize,
+}
+
+impl<Backend, Cpu> OffloadDft<Backend, Cpu> {
+    pub const fn new(backend: Backend, cpu: Cpu, min_log_h: usize) -> Self {
+        Self {
+            backend,
+            cpu,
+            min_log_h,
+        }
+    }
+
+    fn offload(&self, log_h: usize, width: usize) -> bool {
+        log_h >= self.min_log_h && width > 0
+    }
+}
+
+impl<Backend: Default, Cpu: Default> Default for OffloadDft<Backend, Cpu> {
+    fn default() -> Self {
+        Self::new(
+            Backend::default(),
+            Cpu::default(),
+            DEFAULT_OFFLOAD_MIN_LOG_H,
+        )
+    }
+}
+
+impl<F, Backend, Cpu> TwoAdicSubgroupDft<F> for OffloadDft<Backend, Cpu>
+where
+    F: TwoAdicField,
+    Backend: DftBackend<F>,
+    Cpu: TwoAdicSubgroupDft<F>,
+{
+    type Evaluations = RowMajorMatrix<F>;
+
+    #[instrument(skip_all, fields(dims = %mat.dimensions()))]
+    fn dft_batch(&self, mut mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let log_h = log2_strict_usize(mat.height());
+        if self.offload(log_h, mat.width()) {
+            let layout = DftBatchLayout::new(log_h, mat.width(), 0);
+            match self.backend.dft_batch(layout, &mut mat.values) {
+                Ok(()) => return mat,
+                Err(err) => warn!("DFT backend failed, falling back to the CPU: {err:?}"),
+            }
+        }
+        self.cpu.dft_batch(mat).to_row_major_matrix()
+    }
+
+    #[instrument(skip_all, fields(dims = %mat.dimensions(), added_bits = added_bits))]
+    fn coset_lde_batch(
+        &self,
+        mut mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let log_h = log2_strict_usize(mat.height());
+        if self.offload(log_h + added_bits, mat.width()) {
+            let layout = DftBatchLayout::new(log_h, mat.width(), added_bits);
+            let len = mat.values.len();
+            mat.values.resize(layout.output_len(), F::ZERO);
+            match self.backend.coset_lde_batch(layout, shift, &mut mat.values) {
+                Ok(()) => return mat,
+                Err(err) => warn!("DFT backend failed, falling back to the CPU: {err:?}"),
+            }
+            mat.values.truncate(len);
+        }
+        self.cpu
+            .coset_lde_batch(mat, added_bits, shift)
+            .to_row_major_matrix()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::Field;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::Matrix;
+    use rand::thread_rng;
+
+    use super::{DftBackend, DftBatchLayout, OffloadDft};
+    use crate::{NaiveDft, Radix2Dit, TwoAdicSubgroupDft};
+
+    type F = BabyBear;
+
+    /// A backend running on the CPU, which fails on odd widths.
+    #[derive(Clone, Debug, Default)]
+    struct TestBackend;
+
+    impl DftBackend<F> for TestBackend {
+        type Error = ();
+
+        fn dft_batch(&self, layout: DftBatchLayout, values: &mut [F]) -> Result<(), ()> {
+            if layout.width % 2 == 1 {
+                return Err(());
+            }
+            let mat = RowMajorMatrix::new(values.to_vec(), layout.width as usize);
+            values.copy_from_slice(&NaiveDft.dft_batch(mat).values);
+            Ok(())
+        }
+
+        fn coset_lde_batch(
+            &self,
+            layout: DftBatchLayout,
+            shift: F,
+            values: &mut [F],
+        ) -> Result<(), ()> {
+            if layout.width % 2 == 1 {
+                return Err(());
+            }
+            let input_len = (layout.width as usize) << layout.log_height;
+            let mat = RowMajorMatrix::new(values[..input_len].to_vec(), layout.width as usize);
+            let lde = NaiveDft.coset_lde_batch(mat, layout.added_bits as usize, shift);
+            values.copy_from_slice(&lde.values);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn offload_matches_cpu() {
+        let dft = OffloadDft::new(TestBackend, Radix2Dit::<F>::default(), 3);
+        for (log_h, width) in [(2, 2), (4, 2), (4, 3)] {
+            let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_h, width);
+            let expected = Radix2Dit::default().dft_batch(mat.clone());
+            assert_eq!(dft.dft_batch(mat.clone()), expected);
+
+            let lde = dft.coset_lde_batch(mat.clone(), 2, F::GENERATOR);
+            assert_eq!(lde.height(), 4 << log_h);
+            assert_eq!(
+                lde,
+                Radix2Dit::default().coset_lde_batch(mat, 2, F::GENERATOR)
+            );
+        }
+    }
+}
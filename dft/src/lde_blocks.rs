@@ -0,0 +1,140 @@
+use core::iter::FusedIterator;
+
+use p3_field::TwoAdicField;
+use p3_matrix::bitrev::{BitReversableMatrix, RowOrder};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::{log2_strict_usize, reverse_bits_len};
+
+use crate::TwoAdicSubgroupDft;
+
+/// The coset LDE of a matrix with its rows in bit-reversed order, as committed to by FRI, yielded
+/// in blocks of rows which are each computed when they're reached, for consumers which can't hold
+/// the whole LDE in memory.
+///
+/// With `n` the height of the input, each block of `n` rows of the bit-reversed LDE holds the
+/// evaluations over a coset of the subgroup of order `n`, so each block is a single coset DFT of
+/// the coefficients, which are all that's kept in memory.
+#[derive(Clone, Debug)]
+pub struct CosetLdeBlocks<F, Dft> {
+    dft: Dft,
+    coeffs: RowMajorMatrix<F>,
+    added_bits: usize,
+    shift: F,
+    next_block: usize,
+}
+
+impl<F: TwoAdicField, Dft: TwoAdicSubgroupDft<F>> CosetLdeBlocks<F, Dft> {
+    /// The blocks of the LDE of `mat`, as in `dft.coset_lde_batch(mat, added_bits, shift)`.
+    pub fn new(dft: Dft, mat: RowMajorMatrix<F>, added_bits: usize, shift: F) -> Self {
+        let coeffs = dft.idft_batch(mat);
+        Self::from_coeffs(dft, coeffs, added_bits, shift)
+    }
+
+    /// Like `new`, but given the coefficients, with that of `X^i` in row `i`.
+    pub const fn from_coeffs(
+        dft: Dft,
+        coeffs: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> Self {
+        Self {
+            dft,
+            coeffs,
+            added_bits,
+            shift,
+            next_block: 0,
+        }
+    }
+
+    /// The number of rows in each block, i.e. the height of the input.
+    pub fn block_height(&self) -> usize {
+        self.coeffs.height()
+    }
+
+    /// The number of blocks, i.e. `2^added_bits`.
+    pub const fn num_blocks(&self) -> usize {
+        1 << self.added_bits
+    }
+
+    /// Compute the given block, i.e. rows `block * block_height()..(block + 1) * block_height()`
+    /// of the bit-reversed LDE, regardless of the iterator's position.
+    pub fn block(&self, block: usize) -> RowMajorMatrix<F> {
+        assert!(block < self.num_blocks());
+        let log_lde_height = log2_strict_usize(self.block_height()) + self.added_bits;
+        // The block holds the evaluations over the coset
+        // `shift * g^reverse_bits(block) * <g^(2^added_bits)>`, in bit-reversed order.
+        let block_shift = self.shift
+            * F::two_adic_generator(log_lde_height)
+                .exp_u64(reverse_bits_len(block, self.added_bits) as u64);
+        self.dft
+            .coset_dft_batch(self.coeffs.clone(), block_shift)
+            .to_row_major_matrix_in_order(RowOrder::BitReversed)
+    }
+}
+
+impl<F: TwoAdicField, Dft: TwoAdicSubgroupDft<F>> Iterator for CosetLdeBlocks<F, Dft> {
+    type Item = RowMajorMatrix<F>;
+
+    fn next(&mut self) -> Option<RowMajorMatrix<F>> {
+        if self.next_block == self.num_blocks() {
+            return None;
+        }
+        let block = self.block(self.next_block);
+        self.next_block += 1;
+        Some(block)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.num_blocks() - self.next_block;
+        (remaining, Some(remaining))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<RowMajorMatrix<F>> {
+        self.next_block = self.num_blocks().min(self.next_block.saturating_add(n));
+        self.next()
+    }
+}
+
+impl<F: TwoAdicField, Dft: TwoAdicSubgroupDft<F>> ExactSizeIterator for CosetLdeBlocks<F, Dft> {}
+
+impl<F: TwoAdicField, Dft: TwoAdicSubgroupDft<F>> FusedIterator for CosetLdeBlocks<F, Dft> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::Field;
+    use p3_matrix::bitrev::BitReversableMatrix;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::Matrix;
+    use rand::thread_rng;
+
+    use crate::{CosetLdeBlocks, Radix2Dit, TwoAdicSubgroupDft};
+
+    type F = BabyBear;
+
+    #[test]
+    fn blocks_match_coset_lde() {
+        let dft = Radix2Dit::<F>::default();
+        let shift = F::GENERATOR;
+        for (log_h, added_bits) in [(0, 2), (3, 0), (4, 3)] {
+            let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_h, 3);
+            let lde = dft
+                .coset_lde_batch(mat.clone(), added_bits, shift)
+                .bit_reverse_rows()
+                .to_row_major_matrix();
+
+            let blocks = CosetLdeBlocks::new(dft.clone(), mat, added_bits, shift);
+            assert_eq!(blocks.len(), 1 << added_bits);
+            let last_block_start = (lde.height() - (1 << log_h)) * lde.width();
+            assert_eq!(
+                blocks.block(blocks.len() - 1).values[..],
+                lde.values[last_block_start..]
+            );
+            let values: Vec<F> = blocks.flat_map(|block| block.values).collect();
+            assert_eq!(values, lde.values);
+        }
+    }
+}
@@ -4,18 +4,32 @@
 
 extern crate alloc;
 
+mod backend;
 mod butterflies;
+mod lde_blocks;
+mod mixed_radix;
 mod naive;
 mod radix_2_bowers;
 mod radix_2_dit;
 mod radix_2_dit_parallel;
+mod six_step;
+mod split_radix;
 mod traits;
+mod twiddles;
 mod util;
+mod workspace;
 
+pub use backend::*;
 pub use butterflies::*;
+pub use lde_blocks::*;
+pub use mixed_radix::*;
 pub use naive::*;
 pub use radix_2_bowers::*;
 pub use radix_2_dit::*;
 pub use radix_2_dit_parallel::*;
+pub use six_step::*;
+pub use split_radix::*;
 pub use traits::*;
+pub use twiddles::*;
 pub use util::*;
+pub use workspace::*;
@@ -0,0 +1,230 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_field::ThreeAdicField;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::util::swap_rows;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use tracing::instrument;
+
+use crate::util::{coset_shift_cols, divide_by_height};
+use crate::TwoAdicSubgroupDft;
+
+/// A DFT over multiplicative subgroups of order `2^a 3^b`, evaluating over the powers of
+/// `F::mixed_radix_generator(a, b)`.
+///
+/// This is kept apart from `TwoAdicSubgroupDft`, whose callers rely on power-of-two heights. It
+/// computes coset LDEs of any `2^a 3^b` height (see `coset_lde_batch`), but the PCSs only commit
+/// over two-adic domains, and FRI only folds by two, so traces committed by a PCS must still be
+/// padded to a power of two.
+pub trait MixedRadixSubgroupDft<F: ThreeAdicField>: Clone + Default {
+    /// Compute the DFT of each column in `mat`, whose height must be of the form `2^a 3^b`.
+    fn dft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F>;
+
+    /// Compute the "coset DFT" of each column in `mat`.
+    fn coset_dft_batch(&self, mut mat: RowMajorMatrix<F>, shift: F) -> RowMajorMatrix<F> {
+        coset_shift_cols(&mut mat, shift);
+        self.dft_batch(mat)
+    }
+
+    /// Compute the inverse DFT of each column in `mat`.
+    fn idft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let mut dft = self.dft_batch(mat);
+        let h = dft.height();
+
+        divide_by_height(&mut dft);
+
+        // Swap rows `k` and `h - k`. Unlike in the two-adic case, the height may be odd.
+        for row in 1..h.div_ceil(2) {
+            swap_rows(&mut dft, row, h - row);
+        }
+
+        dft
+    }
+
+    /// Compute the "coset iDFT" of each column in `mat`.
+    fn coset_idft_batch(&self, mut mat: RowMajorMatrix<F>, shift: F) -> RowMajorMatrix<F> {
+        mat = self.idft_batch(mat);
+        coset_shift_cols(&mut mat, shift.inverse());
+        mat
+    }
+
+    /// Compute the low-degree extension of each column in `mat` onto a coset of the subgroup of
+    /// order `mat.height() << added_bits`.
+    fn coset_lde_batch(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let mut coeffs = self.idft_batch(mat);
+        coeffs
+            .values
+            .resize(coeffs.values.len() << added_bits, F::ZERO);
+        self.coset_dft_batch(coeffs, shift)
+    }
+}
+
+/// A `MixedRadixSubgroupDft`, so that a trace whose height is close to `3 * 2^a` needn't be padded
+/// to `4 * 2^a`.
+///
+/// A DFT of height `3^b 2^a` is done as `2^a` naive DFTs of height `3^b`, which are cheap since
+/// `3^b` is small in practice, followed by a batch of `3^b` DFTs of height `2^a` with `Inner`.
+/// Power-of-two heights go straight to `Inner`.
+#[derive(Default, Clone, Debug)]
+pub struct MixedRadixDft<Inner> {
+    inner: Inner,
+}
+
+impl<Inner> MixedRadixDft<Inner> {
+    pub const fn new(inner: Inner) -> Self {
+        Self { inner }
+    }
+}
+
+impl<F, Inner> MixedRadixSubgroupDft<F> for MixedRadixDft<Inner>
+where
+    F: ThreeAdicField,
+    Inner: TwoAdicSubgroupDft<F>,
+{
+    #[instrument(skip_all, fields(dims = %mat.dimensions()))]
+    fn dft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let h = mat.height();
+        let w = mat.width();
+        let (log_three, three_part) = three_adic_part(h);
+        if w == 0 {
+            return mat;
+        }
+        if log_three == 0 {
+            return self.inner.dft_batch(mat).to_row_major_matrix();
+        }
+        assert!(
+            log_three <= F::THREE_ADICITY,
+            "height has more factors of three than the field supports"
+        );
+        let two_part = h / three_part;
+        let log_two = log2_strict_usize(two_part);
+
+        // Writing row indices as `n = n1 * two_part + n2` and `k = k1 + k2 * three_part`, we have
+        //     y_k = \sum_{n2} g^{n2 k1} (\sum_{n1} x_n g_3^{n1 k1}) g_2^{n2 k2},
+        // where g_3 = g^two_part and g_2 = g^three_part. So we compute the inner sums and the
+        // twiddles g^{n2 k1} into a matrix with row n2 and column block k1, whose columns then
+        // each take a DFT of height two_part, after which the rows are in natural order.
+        let g = F::mixed_radix_generator(log_two, log_three);
+        let small_roots: Vec<F> = g
+            .exp_u64(two_part as u64)
+            .powers()
+            .take(three_part)
+            .collect();
+
+        let mut twiddled = RowMajorMatrix::new(vec![F::ZERO; h * w], three_part * w);
+        twiddled.par_rows_mut().enumerate().for_each(|(n2, row)| {
+            let twiddle = g.exp_u64(n2 as u64);
+            for (k1, (out, k1_twiddle)) in row.chunks_exact_mut(w).zip(twiddle.powers()).enumerate()
+            {
+                for n1 in 0..three_part {
+                    let coeff = small_roots[n1 * k1 % three_part] * k1_twiddle;
+                    let input = &mat.values[(n1 * two_part + n2) * w..][..w];
+                    for (o, &x) in out.iter_mut().zip(input) {
+                        *o += coeff * x;
+                    }
+                }
+            }
+        });
+
+        let evals = self.inner.dft_batch(twiddled).to_row_major_matrix();
+        RowMajorMatrix::new(evals.values, w)
+    }
+}
+
+/// The largest power of three dividing `n`, and its exponent.
+fn three_adic_part(mut n: usize) -> (usize, usize) {
+    assert_ne!(n, 0);
+    let mut log = 0;
+    let mut part = 1;
+    while n % 3 == 0 {
+        n /= 3;
+        log += 1;
+        part *= 3;
+    }
+    (log, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::{AbstractField, Field, ThreeAdicField};
+    use p3_goldilocks::Goldilocks;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::Matrix;
+    use p3_util::log2_strict_usize;
+    use rand::thread_rng;
+
+    use super::three_adic_part;
+    use crate::{MixedRadixDft, MixedRadixSubgroupDft, Radix2Dit};
+
+    /// Evaluate each column of `mat` at the powers of `F::mixed_radix_generator`, naively.
+    fn naive_mixed_radix_dft<F: ThreeAdicField>(
+        mat: &RowMajorMatrix<F>,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let h = mat.height();
+        let w = mat.width();
+        let (log_three, three_part) = three_adic_part(h);
+        let g = F::mixed_radix_generator(log2_strict_usize(h / three_part), log_three);
+        let mut res = RowMajorMatrix::new(vec![F::ZERO; h * w], w);
+        for (res_r, point) in g.shifted_powers(shift).take(h).enumerate() {
+            for (src_r, point_power) in point.powers().take(h).enumerate() {
+                for c in 0..w {
+                    res.values[res_r * w + c] += point_power * mat.values[src_r * w + c];
+                }
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn dft_matches_naive() {
+        type F = BabyBear;
+        let dft = MixedRadixDft::new(Radix2Dit::<F>::default());
+        for h in [1, 2, 3, 6, 12, 48, 64, 96] {
+            let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), h, 3);
+            assert_eq!(
+                dft.dft_batch(mat.clone()),
+                naive_mixed_radix_dft(&mat, F::ONE),
+                "height {h}"
+            );
+        }
+    }
+
+    #[test]
+    fn dft_idft_consistency() {
+        type F = Goldilocks;
+        let dft = MixedRadixDft::new(Radix2Dit::<F>::default());
+        for h in [3, 24, 32] {
+            let original = RowMajorMatrix::<F>::rand(&mut thread_rng(), h, 4);
+            let evals = dft.dft_batch(original.clone());
+            assert_eq!(evals, naive_mixed_radix_dft(&original, F::ONE));
+            assert_eq!(dft.idft_batch(evals), original);
+        }
+    }
+
+    #[test]
+    fn coset_lde_matches_naive() {
+        type F = BabyBear;
+        let dft = MixedRadixDft::new(Radix2Dit::<F>::default());
+        let shift = F::GENERATOR;
+        let coeffs = RowMajorMatrix::<F>::rand(&mut thread_rng(), 24, 2);
+        let evals = dft.dft_batch(coeffs.clone());
+
+        let lde = dft.coset_lde_batch(evals, 1, shift);
+        let mut padded_coeffs = coeffs;
+        padded_coeffs.pad_to_height(48, F::ZERO);
+        assert_eq!(lde.height(), 48);
+        assert_eq!(lde, naive_mixed_radix_dft(&padded_coeffs, shift));
+    }
+}
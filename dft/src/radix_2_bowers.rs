@@ -1,3 +1,4 @@
+use alloc::vec;
 use alloc::vec::Vec;
 
 use p3_field::{Field, Powers, TwoAdicField};
@@ -5,7 +6,7 @@ use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixViewMut};
 use p3_matrix::util::reverse_matrix_index_bits;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
-use p3_util::{log2_strict_usize, reverse_bits, reverse_slice_index_bits};
+use p3_util::{log2_strict_usize, reverse_bits, reverse_bits_len, reverse_slice_index_bits};
 use tracing::instrument;
 
 use crate::butterflies::{Butterfly, DifButterfly, DitButterfly, TwiddleFreeButterfly};
@@ -42,6 +43,17 @@ impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Bowers {
         mat
     }
 
+    fn dft_bitrev_in(&self, mut mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        bowers_g(&mut mat.as_view_mut());
+        mat
+    }
+
+    fn idft_bitrev_out(&self, mut mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        bowers_g_t(&mut mat.as_view_mut());
+        divide_by_height(&mut mat);
+        mat
+    }
+
     #[instrument(skip_all, fields(dims = %mat.dimensions(), added_bits))]
     fn coset_lde_batch(
         &self,
@@ -73,6 +85,45 @@ impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Bowers {
 
         mat
     }
+
+    #[instrument(skip_all, fields(dims = %mat.dimensions(), added_bits))]
+    fn coset_lde_batch_bitrev_out(
+        &self,
+        mut mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let w = mat.width();
+        let h = mat.height();
+        let log_h = log2_strict_usize(h);
+        let lde_h = h << added_bits;
+        let h_inv = F::from_canonical_usize(h).inverse();
+
+        bowers_g_t(&mut mat.as_view_mut());
+
+        // G^T evaluates at the inverse roots, with its output in bit-reversed order, i.e.
+        //     G^T(x)_k = \sum_i x_i g^{-i reverse_bits(k)}.
+        // So with x_i = c_{-i} s^{-i}, where the c_j are the coefficients zero-padded to the LDE
+        // height, G^T(x)_k is the coset LDE at `s g^reverse_bits(k)`. We write the coefficients,
+        // which are in bit-reversed order, to their negated rows, rescaled as in `coset_lde_batch`.
+        let mut lde = RowMajorMatrix::new(vec![F::ZERO; lde_h * w], w);
+        let weights = Powers {
+            base: shift,
+            current: h_inv,
+        }
+        .take(h);
+        for (j, weight) in weights.enumerate() {
+            let src = &mat.values[reverse_bits_len(j, log_h) * w..][..w];
+            let dst = &mut lde.values[(lde_h - j) % lde_h * w..][..w];
+            for (d, &s) in dst.iter_mut().zip(src) {
+                *d = s * weight;
+            }
+        }
+
+        bowers_g_t(&mut lde.as_view_mut());
+
+        lde
+    }
 }
 
 /// Executes the Bowers G network. This is like a DFT, except it assumes the input is in
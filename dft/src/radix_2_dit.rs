@@ -1,7 +1,3 @@
-use alloc::collections::BTreeMap;
-use alloc::vec::Vec;
-use core::cell::RefCell;
-
 use p3_field::{Field, TwoAdicField};
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixViewMut};
 use p3_matrix::util::reverse_matrix_index_bits;
@@ -10,13 +6,26 @@ use p3_maybe_rayon::prelude::*;
 use p3_util::log2_strict_usize;
 
 use crate::butterflies::{Butterfly, DitButterfly, TwiddleFreeButterfly};
-use crate::TwoAdicSubgroupDft;
+use crate::{TwiddleCache, TwoAdicSubgroupDft};
 
 /// The DIT FFT algorithm.
-#[derive(Default, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Radix2Dit<F: TwoAdicField> {
     /// Memoized twiddle factors for each length log_n.
-    twiddles: RefCell<BTreeMap<usize, Vec<F>>>,
+    twiddles: TwiddleCache<F>,
+}
+
+impl<F: TwoAdicField> Radix2Dit<F> {
+    /// A DFT taking its twiddles from `twiddles`, rather than from `TwiddleCache::global()`.
+    pub const fn new(twiddles: TwiddleCache<F>) -> Self {
+        Self { twiddles }
+    }
+}
+
+impl<F: TwoAdicField> Default for Radix2Dit<F> {
+    fn default() -> Self {
+        Self::new(TwiddleCache::global())
+    }
 }
 
 impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Dit<F> {
@@ -26,22 +35,36 @@ impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Dit<F> {
         let h = mat.height();
         let log_h = log2_strict_usize(h);
 
-        // Compute twiddle factors, or take memoized ones if already available.
-        let mut twiddles_ref_mut = self.twiddles.borrow_mut();
-        let twiddles = twiddles_ref_mut.entry(log_h).or_insert_with(|| {
-            let root = F::two_adic_generator(log_h);
-            root.powers().take(1 << log_h).collect()
-        });
+        let twiddles = self.twiddles.roots(log_h);
+        dit_in_place(&mut mat.as_view_mut(), &twiddles);
+        mat
+    }
 
-        // DIT butterfly
-        reverse_matrix_index_bits(&mut mat);
-        for layer in 0..log_h {
-            dit_layer(&mut mat.as_view_mut(), layer, twiddles);
-        }
+    fn dft_bitrev_in(&self, mut mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let h = mat.height();
+        let log_h = log2_strict_usize(h);
+
+        let twiddles = self.twiddles.roots(log_h);
+        dit_bitrev_in_place(&mut mat.as_view_mut(), &twiddles);
         mat
     }
 }
 
+/// A DIT FFT of each column of `mat`, in place. `twiddles` must be the powers of the generator of
+/// the subgroup of order `mat.height()`.
+pub(crate) fn dit_in_place<F: Field>(mat: &mut RowMajorMatrixViewMut<'_, F>, twiddles: &[F]) {
+    reverse_matrix_index_bits(mat);
+    dit_bitrev_in_place(mat, twiddles);
+}
+
+/// Like `dit_in_place`, but for a matrix whose rows are in bit-reversed order.
+fn dit_bitrev_in_place<F: Field>(mat: &mut RowMajorMatrixViewMut<'_, F>, twiddles: &[F]) {
+    let log_h = log2_strict_usize(mat.height());
+    for layer in 0..log_h {
+        dit_layer(mat, layer, twiddles);
+    }
+}
+
 /// One layer of a DIT butterfly network.
 fn dit_layer<F: Field>(mat: &mut RowMajorMatrixViewMut<'_, F>, layer: usize, twiddles: &[F]) {
     let h = mat.height();
@@ -1,7 +1,7 @@
 use alloc::collections::BTreeMap;
 use alloc::slice;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
-use core::cell::RefCell;
 use core::mem::{transmute, MaybeUninit};
 
 use itertools::{izip, Itertools};
@@ -12,6 +12,7 @@ use p3_matrix::util::reverse_matrix_index_bits;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
 use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits};
+use spin::RwLock;
 use tracing::{debug_span, instrument};
 
 use crate::butterflies::{Butterfly, DitButterfly};
@@ -24,17 +25,31 @@ use crate::TwoAdicSubgroupDft;
 /// the same network but in bit-reversed order. This way we're always working with small blocks,
 /// so within each half, we can have a certain amount of parallelism with no cross-thread
 /// communication.
-#[derive(Default, Clone, Debug)]
+///
+/// The twiddle caches are behind locks, so that a DFT, and any config holding one, can be shared
+/// between threads. A lock is only held while looking up or inserting a table, never during a
+/// transform, and clones share the cached tables.
+#[derive(Default, Debug)]
 pub struct Radix2DitParallel<F> {
     /// Twiddles based on roots of unity, used in the forward DFT.
-    twiddles: RefCell<BTreeMap<usize, VectorPair<F>>>,
+    twiddles: RwLock<BTreeMap<usize, Arc<VectorPair<F>>>>,
 
     /// A map from `(log_h, shift)` to forward DFT twiddles with that coset shift baked in.
     #[allow(clippy::type_complexity)]
-    coset_twiddles: RefCell<BTreeMap<(usize, F), Vec<Vec<F>>>>,
+    coset_twiddles: RwLock<BTreeMap<(usize, F), Arc<Vec<Vec<F>>>>>,
 
     /// Twiddles based on inverse roots of unity, used in the inverse DFT.
-    inverse_twiddles: RefCell<BTreeMap<usize, VectorPair<F>>>,
+    inverse_twiddles: RwLock<BTreeMap<usize, Arc<VectorPair<F>>>>,
+}
+
+impl<F: Clone> Clone for Radix2DitParallel<F> {
+    fn clone(&self) -> Self {
+        Self {
+            twiddles: RwLock::new(self.twiddles.read().clone()),
+            coset_twiddles: RwLock::new(self.coset_twiddles.read().clone()),
+            inverse_twiddles: RwLock::new(self.inverse_twiddles.read().clone()),
+        }
+    }
 }
 
 /// A pair of vectors, one with twiddle factors in their natural order, the other bit-reversed.
@@ -44,6 +59,20 @@ struct VectorPair<F> {
     bitrev_twiddles: Vec<F>,
 }
 
+/// Look up `key` in `cache`, computing and inserting the entry if it's missing. The lock isn't held
+/// while computing, or by the caller afterwards.
+fn get_or_compute<K: Ord, V>(
+    cache: &RwLock<BTreeMap<K, Arc<V>>>,
+    key: K,
+    compute: impl FnOnce() -> V,
+) -> Arc<V> {
+    if let Some(value) = cache.read().get(&key) {
+        return value.clone();
+    }
+    let value = Arc::new(compute());
+    cache.write().entry(key).or_insert(value).clone()
+}
+
 #[instrument(level = "debug", skip_all)]
 fn compute_twiddles<F: TwoAdicField + Ord>(log_h: usize) -> VectorPair<F> {
     let half_h = (1 << log_h) >> 1;
@@ -107,10 +136,7 @@ impl<F: TwoAdicField + Ord> TwoAdicSubgroupDft<F> for Radix2DitParallel<F> {
         let log_h = log2_strict_usize(h);
 
         // Compute twiddle factors, or take memoized ones if already available.
-        let mut twiddles_ref_mut = self.twiddles.borrow_mut();
-        let twiddles = twiddles_ref_mut
-            .entry(log_h)
-            .or_insert_with(|| compute_twiddles(log_h));
+        let twiddles = get_or_compute(&self.twiddles, log_h, || compute_twiddles(log_h));
 
         let mid = log_h.div_ceil(2);
 
@@ -137,10 +163,9 @@ impl<F: TwoAdicField + Ord> TwoAdicSubgroupDft<F> for Radix2DitParallel<F> {
         let log_h = log2_strict_usize(h);
         let mid = log_h.div_ceil(2);
 
-        let mut inverse_twiddles_ref_mut = self.inverse_twiddles.borrow_mut();
-        let inverse_twiddles = inverse_twiddles_ref_mut
-            .entry(log_h)
-            .or_insert_with(|| compute_inverse_twiddles(log_h));
+        let inverse_twiddles = get_or_compute(&self.inverse_twiddles, log_h, || {
+            compute_inverse_twiddles(log_h)
+        });
 
         // The first half looks like a normal DIT.
         reverse_matrix_index_bits(&mut mat);
@@ -197,18 +222,17 @@ fn coset_dft<F: TwoAdicField + Ord>(
     let log_h = log2_strict_usize(mat.height());
     let mid = log_h.div_ceil(2);
 
-    let mut twiddles_ref_mut = dft.coset_twiddles.borrow_mut();
-    let twiddles = twiddles_ref_mut
-        .entry((log_h, shift))
-        .or_insert_with(|| compute_coset_twiddles(log_h, shift));
+    let twiddles = get_or_compute(&dft.coset_twiddles, (log_h, shift), || {
+        compute_coset_twiddles(log_h, shift)
+    });
 
     // The first half looks like a normal DIT.
-    first_half_general(mat, mid, twiddles);
+    first_half_general(mat, mid, &twiddles);
 
     // For the second half, we flip the DIT, working in bit-reversed order.
     reverse_matrix_index_bits(mat);
 
-    second_half_general(mat, mid, twiddles);
+    second_half_general(mat, mid, &twiddles);
 }
 
 /// Like `coset_dft`, except out-of-place.
@@ -235,13 +259,12 @@ fn coset_dft_oop<F: TwoAdicField + Ord>(
 
     let mid = log_h.div_ceil(2);
 
-    let mut twiddles_ref_mut = dft.coset_twiddles.borrow_mut();
-    let twiddles = twiddles_ref_mut
-        .entry((log_h, shift))
-        .or_insert_with(|| compute_coset_twiddles(log_h, shift));
+    let twiddles = get_or_compute(&dft.coset_twiddles, (log_h, shift), || {
+        compute_coset_twiddles(log_h, shift)
+    });
 
     // The first half looks like a normal DIT.
-    first_half_general_oop(src, dst_maybe, mid, twiddles);
+    first_half_general_oop(src, dst_maybe, mid, &twiddles);
 
     // dst is now initialized.
     let dst = unsafe {
@@ -253,7 +276,7 @@ fn coset_dft_oop<F: TwoAdicField + Ord>(
     // For the second half, we flip the DIT, working in bit-reversed order.
     reverse_matrix_index_bits(dst);
 
-    second_half_general(dst, mid, twiddles);
+    second_half_general(dst, mid, &twiddles);
 }
 
 /// This can be used as the first half of a DIT butterfly network.
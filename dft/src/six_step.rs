@@ -0,0 +1,162 @@
+use alloc::vec;
+
+use p3_field::TwoAdicField;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixViewMut};
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use tracing::{debug_span, instrument};
+
+use crate::radix_2_dit::dit_in_place;
+use crate::{Radix2DitParallel, TwiddleCache, TwoAdicSubgroupDft};
+
+/// The smallest `log_h` for which `SixStepDft` uses the six-step algorithm by default.
+pub const DEFAULT_SIX_STEP_MIN_LOG_H: usize = 22;
+
+/// The six-step FFT, for heights too large for the radix-2 networks to stay in cache.
+///
+/// A DFT of height `n1 * n2` is split into `n2` DFTs of height `n1`, a twiddle, and `n1` DFTs of
+/// height `n2`, with transposes in between so that each of the smaller DFTs runs over a
+/// contiguous block of rows, which fits in cache, and the blocks are processed in parallel.
+///
+/// Heights below `2^min_log_h` go to `Inner` instead.
+#[derive(Clone, Debug)]
+pub struct SixStepDft<F, Inner = Radix2DitParallel<F>> {
+    inner: Inner,
+    min_log_h: usize,
+    /// Memoized twiddle factors for each length log_n.
+    twiddles: TwiddleCache<F>,
+}
+
+impl<F: TwoAdicField, Inner> SixStepDft<F, Inner> {
+    pub fn new(inner: Inner, min_log_h: usize) -> Self {
+        Self::with_twiddles(inner, min_log_h, TwiddleCache::global())
+    }
+
+    /// Like `new`, but taking twiddles from `twiddles` rather than from `TwiddleCache::global()`.
+    pub const fn with_twiddles(inner: Inner, min_log_h: usize, twiddles: TwiddleCache<F>) -> Self {
+        Self {
+            inner,
+            min_log_h,
+            twiddles,
+        }
+    }
+}
+
+impl<F: TwoAdicField, Inner: Default> Default for SixStepDft<F, Inner> {
+    fn default() -> Self {
+        Self::new(Inner::default(), DEFAULT_SIX_STEP_MIN_LOG_H)
+    }
+}
+
+impl<F, Inner> TwoAdicSubgroupDft<F> for SixStepDft<F, Inner>
+where
+    F: TwoAdicField,
+    Inner: TwoAdicSubgroupDft<F>,
+{
+    type Evaluations = RowMajorMatrix<F>;
+
+    #[instrument(skip_all, fields(dims = %mat.dimensions()))]
+    fn dft_batch(&self, mut mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let h = mat.height();
+        let w = mat.width();
+        let log_h = log2_strict_usize(h);
+        if log_h < self.min_log_h || w == 0 {
+            return self.inner.dft_batch(mat).to_row_major_matrix();
+        }
+
+        let log_n1 = log_h / 2;
+        let log_n2 = log_h - log_n1;
+        let (n1, n2) = (1 << log_n1, 1 << log_n2);
+
+        let (n1_twiddles, n2_twiddles) = (self.twiddles.roots(log_n1), self.twiddles.roots(log_n2));
+        let root = F::two_adic_generator(log_h);
+
+        // Writing row indices as `n = n1' * n2 + n2'` and `k = k1 + k2 * n1`, we have
+        //     y_k = \sum_{n2'} g^{n2' k1} (\sum_{n1'} x_n g_1^{n1' k1}) g_2^{n2' k2},
+        // where g_1 = g^n2 and g_2 = g^n1.
+        let mut buf = vec![F::ZERO; h * w];
+
+        // Gather the rows into blocks by n2', so that the inner sums are DFTs over contiguous
+        // blocks, and apply the twiddles while each block is in cache.
+        transpose_blocks(&mat.values, &mut buf, n1, n2, w);
+        debug_span!("dft columns").in_scope(|| {
+            buf.par_chunks_exact_mut(n1 * w)
+                .enumerate()
+                .for_each(|(j2, block)| {
+                    let mut block = RowMajorMatrixViewMut::new(block, w);
+                    dit_in_place(&mut block, &n1_twiddles);
+                    for (row, twiddle) in block.rows_mut().zip(root.exp_u64(j2 as u64).powers()) {
+                        row.iter_mut().for_each(|x| *x *= twiddle);
+                    }
+                });
+        });
+
+        // Gather the rows into blocks by k1 for the outer sums.
+        transpose_blocks(&buf, &mut mat.values, n2, n1, w);
+        debug_span!("dft rows").in_scope(|| {
+            mat.values.par_chunks_exact_mut(n2 * w).for_each(|block| {
+                dit_in_place(&mut RowMajorMatrixViewMut::new(block, w), &n2_twiddles);
+            });
+        });
+
+        // The rows are now ordered by (k1, k2), and we want them ordered by k.
+        transpose_blocks(&mat.values, &mut buf, n1, n2, w);
+        RowMajorMatrix::new(buf, w)
+    }
+}
+
+/// View `src` as a `rows x cols` grid of blocks of `block_len` elements, and write its transpose
+/// to `dst`.
+#[instrument(level = "debug", skip_all)]
+fn transpose_blocks<F: Copy + Send + Sync>(
+    src: &[F],
+    dst: &mut [F],
+    rows: usize,
+    cols: usize,
+    block_len: usize,
+) {
+    debug_assert_eq!(src.len(), rows * cols * block_len);
+    debug_assert_eq!(dst.len(), src.len());
+    dst.par_chunks_exact_mut(rows * block_len)
+        .enumerate()
+        .for_each(|(c, dst_row)| {
+            for (r, dst_block) in dst_row.chunks_exact_mut(block_len).enumerate() {
+                let start = (r * cols + c) * block_len;
+                dst_block.copy_from_slice(&src[start..start + block_len]);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_goldilocks::Goldilocks;
+    use p3_matrix::dense::RowMajorMatrix;
+    use rand::thread_rng;
+
+    use crate::{NaiveDft, Radix2Dit, SixStepDft, TwoAdicSubgroupDft};
+
+    #[test]
+    fn matches_radix_2_dit() {
+        type F = BabyBear;
+        let dft = SixStepDft::<F, Radix2Dit<F>>::new(Radix2Dit::default(), 0);
+        for log_h in 0..12 {
+            let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_h, 3);
+            assert_eq!(
+                dft.dft_batch(mat.clone()),
+                Radix2Dit::default().dft_batch(mat),
+                "log_h {log_h}"
+            );
+        }
+    }
+
+    #[test]
+    fn matches_naive() {
+        type F = Goldilocks;
+        let dft = SixStepDft::<F, Radix2Dit<F>>::new(Radix2Dit::default(), 0);
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 32, 5);
+        assert_eq!(dft.dft_batch(mat.clone()), NaiveDft.dft_batch(mat.clone()));
+        assert_eq!(dft.idft_batch(dft.dft_batch(mat.clone())), mat);
+    }
+}
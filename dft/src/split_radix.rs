@@ -0,0 +1,151 @@
+use alloc::vec;
+
+use itertools::izip;
+use p3_field::{Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use tracing::instrument;
+
+use crate::{TwiddleCache, TwoAdicSubgroupDft};
+
+/// Below this many elements, the recursive sub-DFTs are done on the current thread.
+const PARALLEL_THRESHOLD: usize = 1 << 14;
+
+/// The split-radix FFT, which splits a DFT of height `n` into one of height `n/2` and two of height
+/// `n/4`. It takes about a third fewer multiplications than a radix-2 FFT, which pays off for the
+/// 31-bit fields, where multiplications dominate.
+#[derive(Clone, Debug)]
+pub struct SplitRadixDft<F> {
+    /// Memoized roots of unity for each length log_n.
+    roots: TwiddleCache<F>,
+}
+
+impl<F> SplitRadixDft<F> {
+    /// A DFT taking its roots of unity from `roots`, rather than from `TwiddleCache::global()`.
+    pub const fn new(roots: TwiddleCache<F>) -> Self {
+        Self { roots }
+    }
+}
+
+impl<F: TwoAdicField> Default for SplitRadixDft<F> {
+    fn default() -> Self {
+        Self::new(TwiddleCache::global())
+    }
+}
+
+impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for SplitRadixDft<F> {
+    type Evaluations = RowMajorMatrix<F>;
+
+    #[instrument(skip_all, fields(dims = %mat.dimensions()))]
+    fn dft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let h = mat.height();
+        let w = mat.width();
+        let log_h = log2_strict_usize(h);
+
+        let roots = self.roots.roots(log_h);
+        let mut output = vec![F::ZERO; h * w];
+        if w > 0 {
+            split_radix_dft(&mat.values, 1, &mut output, w, &roots);
+        }
+        RowMajorMatrix::new(output, w)
+    }
+}
+
+/// Write the DFT of rows `0, stride, 2 * stride, ...` of the matrix of width `w` in `input` to
+/// `output`, whose height is that of the DFT. `roots` are the powers of the generator of the
+/// subgroup of order `output.len() / w * stride`.
+fn split_radix_dft<F: Field>(input: &[F], stride: usize, output: &mut [F], w: usize, roots: &[F]) {
+    let n = output.len() / w;
+    match n {
+        1 => output.copy_from_slice(&input[..w]),
+        2 => {
+            let (out_0, out_1) = output.split_at_mut(w);
+            let (x_0, x_1) = (&input[..w], &input[stride * w..][..w]);
+            for (o_0, o_1, &x_0, &x_1) in izip!(out_0, out_1, x_0, x_1) {
+                *o_0 = x_0 + x_1;
+                *o_1 = x_0 - x_1;
+            }
+        }
+        _ => {
+            let quarter = n / 4 * w;
+            let (u, z) = output.split_at_mut(2 * quarter);
+            let (z_1, z_3) = z.split_at_mut(quarter);
+
+            // The even rows, and the rows which are 1 and 3 mod 4.
+            let mut evens = || split_radix_dft(input, 2 * stride, u, w, roots);
+            let mut odds = || {
+                split_radix_dft(&input[stride * w..], 4 * stride, z_1, w, roots);
+                split_radix_dft(&input[3 * stride * w..], 4 * stride, z_3, w, roots);
+            };
+            if n * w >= PARALLEL_THRESHOLD {
+                join(evens, odds);
+            } else {
+                evens();
+                odds();
+            }
+
+            // With a the twiddled DFT of the rows 1 mod 4, and b that of the rows 3 mod 4,
+            //     y_k          = u_k + (a_k + b_k)
+            //     y_{k + n/2}  = u_k - (a_k + b_k)
+            //     y_{k + n/4}  = u_{k + n/4} + i (a_k - b_k)
+            //     y_{k + 3n/4} = u_{k + n/4} - i (a_k - b_k)
+            // where i is the fourth root of unity g^{n/4}.
+            let i = roots[stride * n / 4];
+            let (u_0, u_1) = u.split_at_mut(quarter);
+            u_0.par_chunks_exact_mut(w)
+                .zip(u_1.par_chunks_exact_mut(w))
+                .zip(z_1.par_chunks_exact_mut(w))
+                .zip(z_3.par_chunks_exact_mut(w))
+                .enumerate()
+                .for_each(|(k, (((u_0, u_1), z_1), z_3))| {
+                    let (twiddle_1, twiddle_3) = (roots[stride * k], roots[3 * stride * k]);
+                    for (u_0, u_1, z_1, z_3) in izip!(u_0, u_1, z_1, z_3) {
+                        let (a, b) = if k == 0 {
+                            (*z_1, *z_3)
+                        } else {
+                            (*z_1 * twiddle_1, *z_3 * twiddle_3)
+                        };
+                        let (sum, diff) = (a + b, i * (a - b));
+                        (*u_0, *z_1) = (*u_0 + sum, *u_0 - sum);
+                        (*u_1, *z_3) = (*u_1 + diff, *u_1 - diff);
+                    }
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_goldilocks::Goldilocks;
+    use p3_matrix::dense::RowMajorMatrix;
+    use rand::thread_rng;
+
+    use crate::{NaiveDft, SplitRadixDft, TwoAdicSubgroupDft};
+
+    #[test]
+    fn matches_naive() {
+        type F = BabyBear;
+        let dft = SplitRadixDft::<F>::default();
+        for log_h in 0..9 {
+            let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_h, 3);
+            assert_eq!(
+                dft.dft_batch(mat.clone()),
+                NaiveDft.dft_batch(mat),
+                "log_h {log_h}"
+            );
+        }
+    }
+
+    #[test]
+    fn dft_idft_consistency() {
+        type F = Goldilocks;
+        let dft = SplitRadixDft::<F>::default();
+        let original = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << 10, 5);
+        let evals = dft.dft_batch(original.clone());
+        assert_eq!(evals, NaiveDft.dft_batch(original.clone()));
+        assert_eq!(dft.idft_batch(evals), original);
+    }
+}
@@ -1,12 +1,16 @@
+use alloc::vec;
 use alloc::vec::Vec;
 
-use p3_field::TwoAdicField;
-use p3_matrix::bitrev::BitReversableMatrix;
-use p3_matrix::dense::RowMajorMatrix;
-use p3_matrix::util::swap_rows;
+use p3_field::{ExtensionField, TwoAdicField};
+use p3_matrix::bitrev::{BitReversableMatrix, RowOrder};
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::periodic::PeriodicMatrix;
+use p3_matrix::util::{reverse_matrix_index_bits, swap_rows};
 use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
 
-use crate::util::{coset_shift_cols, divide_by_height};
+use crate::util::{coset_shift_cols, divide_by_height, reconstitute_from_base};
 
 pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
     // Effectively this is either RowMajorMatrix or BitReversedMatrixView<RowMajorMatrix>.
@@ -45,6 +49,154 @@ pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
         self.dft_batch(mat)
     }
 
+    /// Compute the DFT of each column of `mat`, as if it were padded with zero rows to height
+    /// `mat.height() << added_bits`, e.g. for coefficients of bounded degree.
+    ///
+    /// The butterflies on the padding are skipped: the DFT is split into `2^added_bits` coset DFTs
+    /// of the original height, done as one batch. A padded DFT of height `n` takes about
+    /// `n/2 log n` multiplications per column, and the pruned one `n/2 log(n >> added_bits) + n`,
+    /// so the pruned transform is only used for `added_bits > 2`.
+    fn dft_batch_pruned_input(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+    ) -> RowMajorMatrix<F> {
+        self.coset_dft_batch_pruned_input(mat, added_bits, F::ONE)
+    }
+
+    /// Like `dft_batch_pruned_input`, but over a coset, as in `coset_dft_batch`.
+    fn coset_dft_batch_pruned_input(
+        &self,
+        mut mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let w = mat.width();
+        let h = mat.height();
+        if added_bits <= 2 {
+            mat.values.resize(mat.values.len() << added_bits, F::ZERO);
+            return self.coset_dft_batch(mat, shift).to_row_major_matrix();
+        }
+
+        // Writing output row indices as `i = r + s 2^added_bits`, with g the generator of the
+        // larger subgroup, we have
+        //     y_i = \sum_j (c_j shift^j g^{rj}) (g^{2^added_bits})^{sj},
+        // which for each r is a DFT of height h, so we put the coefficients for each r side by
+        // side, after which the rows of the DFT are the output rows in order.
+        coset_shift_cols(&mut mat, shift);
+        let g = F::two_adic_generator(log2_strict_usize(h) + added_bits);
+        let num_cosets = 1 << added_bits;
+        let mut cosets = RowMajorMatrix::new(vec![F::ZERO; (h * w) << added_bits], w * num_cosets);
+        cosets
+            .par_rows_mut()
+            .zip(mat.par_row_slices())
+            .enumerate()
+            .for_each(|(j, (row, coeffs))| {
+                for (block, weight) in row.chunks_exact_mut(w).zip(g.exp_u64(j as u64).powers()) {
+                    for (x, &c) in block.iter_mut().zip(coeffs) {
+                        *x = c * weight;
+                    }
+                }
+            });
+        let evals = self.dft_batch(cosets).to_row_major_matrix();
+        RowMajorMatrix::new(evals.values, w)
+    }
+
+    /// Compute only the first `mat.height() >> removed_bits` rows of the DFT of each column of
+    /// `mat`.
+    ///
+    /// The butterflies which only feed the other rows are skipped: the input is split into
+    /// `2^removed_bits` interleaved parts, whose DFTs of the output height are done as one batch
+    /// and then combined with one multiplication per input element. As with
+    /// `dft_batch_pruned_input`, this only pays off for `removed_bits > 2`, below which the full
+    /// DFT is computed and truncated.
+    fn dft_batch_pruned_output(
+        &self,
+        mat: RowMajorMatrix<F>,
+        removed_bits: usize,
+    ) -> RowMajorMatrix<F> {
+        self.coset_dft_batch_pruned_output(mat, removed_bits, F::ONE)
+    }
+
+    /// Like `dft_batch_pruned_output`, but over a coset, as in `coset_dft_batch`, e.g. when only
+    /// some of the quotient domain is needed.
+    fn coset_dft_batch_pruned_output(
+        &self,
+        mut mat: RowMajorMatrix<F>,
+        removed_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let w = mat.width();
+        let h = mat.height();
+        let out_h = h >> removed_bits;
+        if removed_bits <= 2 {
+            let mut evals = self.coset_dft_batch(mat, shift).to_row_major_matrix();
+            evals.values.truncate(out_h * w);
+            return evals;
+        }
+
+        // Writing input row indices as `j = a + b 2^removed_bits`, with g the generator of the
+        // larger subgroup, we have, for `k < out_h`,
+        //     y_k = \sum_a g^{ka} \sum_b (x_j shift^j) (g^{2^removed_bits})^{kb},
+        // where the inner sums are DFTs of height out_h of the matrix whose row b holds the rows
+        // of x for each a side by side, which is just x with wider rows.
+        coset_shift_cols(&mut mat, shift);
+        let num_parts = 1 << removed_bits;
+        let parts = self
+            .dft_batch(RowMajorMatrix::new(mat.values, w * num_parts))
+            .to_row_major_matrix();
+        let g = F::two_adic_generator(log2_strict_usize(h));
+        let mut evals = RowMajorMatrix::new(vec![F::ZERO; out_h * w], w);
+        evals
+            .par_rows_mut()
+            .zip(parts.par_row_slices())
+            .enumerate()
+            .for_each(|(k, (row, parts))| {
+                for (part, weight) in parts.chunks_exact(w).zip(g.exp_u64(k as u64).powers()) {
+                    for (y, &x) in row.iter_mut().zip(part) {
+                        *y += x * weight;
+                    }
+                }
+            });
+        evals
+    }
+
+    /// Compute the DFT of each column of `mat`, whose entries are in an extension field.
+    ///
+    /// The twiddles are in the base field, so this is a DFT of each coordinate of the entries,
+    /// which we do as a base field DFT of `EF::D` times as many columns. That way we use (packed)
+    /// base field arithmetic rather than extension field multiplications.
+    fn dft_extension_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        let evals = self.dft_batch(mat.flatten_to_base()).to_row_major_matrix();
+        reconstitute_from_base(evals)
+    }
+
+    /// Compute the inverse DFT of each column of `mat`, whose entries are in an extension field.
+    /// See `dft_extension_batch`.
+    fn idft_extension_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        reconstitute_from_base(self.idft_batch(mat.flatten_to_base()))
+    }
+
+    /// Compute the low-degree extension of each column in `mat`, whose entries are in an
+    /// extension field, onto a coset of a larger subgroup. See `dft_extension_batch`.
+    fn coset_lde_extension_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<EF> {
+        let lde = self
+            .coset_lde_batch(mat.flatten_to_base(), added_bits, shift)
+            .to_row_major_matrix();
+        reconstitute_from_base(lde)
+    }
+
     /// Compute the inverse DFT of `vec`.
     fn idft(&self, vec: Vec<F>) -> Vec<F> {
         self.idft_batch(RowMajorMatrix::new(vec, 1)).values
@@ -123,4 +275,286 @@ pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
         );
         self.coset_dft_batch(coeffs, shift)
     }
+
+    /// Like `coset_lde_batch`, but the LDE is computed in `buffer`, whose allocation is reused if
+    /// it's large enough, rather than in a new allocation the size of the LDE. The input is only
+    /// read, so it needn't be cloned if it's still needed afterwards.
+    ///
+    /// The input is copied to the start of `buffer`, and the zero-extension and the transforms are
+    /// then done in place. Once the LDE is no longer needed, its storage can be recovered with
+    /// `to_row_major_matrix().values`, which doesn't copy for the DFTs in this crate, and passed
+    /// to the next call.
+    fn coset_lde_batch_into(
+        &self,
+        mat: RowMajorMatrixView<'_, F>,
+        added_bits: usize,
+        shift: F,
+        mut buffer: Vec<F>,
+    ) -> Self::Evaluations {
+        // PANICS: possible panic if the LDE length overflows
+        let lde_len = mat
+            .values
+            .len()
+            .checked_shl(added_bits.try_into().unwrap())
+            .unwrap();
+        buffer.clear();
+        buffer.reserve_exact(lde_len);
+        buffer.extend_from_slice(mat.values);
+        self.coset_lde_batch(RowMajorMatrix::new(buffer, mat.width()), added_bits, shift)
+    }
+
+    /// Like `coset_lde_batch`, but for a matrix whose rows repeat with a period dividing its
+    /// height. The LDE repeats with a period `2^added_bits` times as long, and is computed with a
+    /// single LDE of the period, rather than of the whole matrix.
+    fn coset_lde_batch_periodic(
+        &self,
+        mat: PeriodicMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> PeriodicMatrix<F> {
+        let lde_height = mat.height() << added_bits;
+        // Each column is `q(X^k)` with `k = height / period_height` and `q` interpolating the
+        // period, so on `shift H` it takes the values of `q` on `shift^k H^k`.
+        let k = mat.height() / mat.period_height();
+        let period_lde = self
+            .coset_lde_batch(mat.into_period(), added_bits, shift.exp_u64(k as u64))
+            .to_row_major_matrix();
+        PeriodicMatrix::new(period_lde, lde_height)
+    }
+
+    /// Compute the DFT of each column of `mat`, whose rows are in bit-reversed order, e.g.
+    /// coefficients from `idft_bitrev_out`.
+    ///
+    /// Some networks naturally take their input in this order, in which case this skips a
+    /// bit-reversal permutation of the whole matrix.
+    fn dft_bitrev_in(&self, mut mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        reverse_matrix_index_bits(&mut mat);
+        self.dft_batch(mat).to_row_major_matrix()
+    }
+
+    /// Compute the inverse DFT of each column of `mat`, with the rows of the result in
+    /// bit-reversed order.
+    ///
+    /// Some networks naturally produce their output in this order, in which case this skips a
+    /// bit-reversal permutation of the whole matrix.
+    fn idft_bitrev_out(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let mut coeffs = self.idft_batch(mat);
+        reverse_matrix_index_bits(&mut coeffs);
+        coeffs
+    }
+
+    /// Like `coset_lde_batch`, but with the rows of the result in bit-reversed order, as committed
+    /// to by FRI. Implementations override this where that order needn't be materialized by a
+    /// separate permutation of the LDE.
+    fn coset_lde_batch_bitrev_out(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        self.coset_lde_batch(mat, added_bits, shift)
+            .to_row_major_matrix_in_order(RowOrder::BitReversed)
+    }
+
+    /// Like `coset_lde_batch`, but with the rows of the result stored in `order`, going through
+    /// `coset_lde_batch_bitrev_out` for `RowOrder::BitReversed`, so that no pass permuting the
+    /// whole LDE is needed where the DFT can produce that order directly.
+    fn coset_lde_batch_in_order(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+        order: RowOrder,
+    ) -> RowMajorMatrix<F> {
+        match order {
+            RowOrder::Natural => self
+                .coset_lde_batch(mat, added_bits, shift)
+                .to_row_major_matrix(),
+            RowOrder::BitReversed => self.coset_lde_batch_bitrev_out(mat, added_bits, shift),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{AbstractExtensionField, AbstractField, Field};
+    use p3_matrix::bitrev::{BitReversableMatrix, RowOrder, RowOrderPerm};
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::periodic::PeriodicMatrix;
+    use p3_matrix::util::reverse_matrix_index_bits;
+    use p3_matrix::Matrix;
+    use rand::thread_rng;
+
+    use crate::{Radix2Bowers, Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
+
+    type F = BabyBear;
+
+    #[test]
+    fn extension_dft_matches_extension_arithmetic() {
+        type EF = BinomialExtensionField<F, 4>;
+        let dft = Radix2Dit::<F>::default();
+        let ext_dft = Radix2Dit::<EF>::default();
+        let shift = F::GENERATOR;
+        let mat = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 32, 3);
+
+        let evals = dft.dft_extension_batch(mat.clone());
+        assert_eq!(evals, ext_dft.dft_batch(mat.clone()));
+        assert_eq!(dft.idft_extension_batch(evals), mat);
+        assert_eq!(
+            dft.coset_lde_extension_batch(mat.clone(), 2, shift),
+            ext_dft.coset_lde_batch(mat, 2, EF::from_base(shift))
+        );
+    }
+
+    #[test]
+    fn coset_lde_into_reuses_buffer() {
+        let dft = Radix2Dit::<F>::default();
+        let shift = F::GENERATOR;
+        let mut buffer = vec![F::ZERO; 4 * 32 * 3];
+        for (h, added_bits) in [(32, 2), (16, 1), (8, 3)] {
+            let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), h, 3);
+            let ptr = buffer.as_ptr();
+            let lde = dft.coset_lde_batch_into(mat.as_view(), added_bits, shift, buffer);
+            assert_eq!(lde.values.as_ptr(), ptr);
+            assert_eq!(lde, dft.coset_lde_batch(mat, added_bits, shift));
+            buffer = lde.values;
+        }
+
+        let dft = Radix2DitParallel::<F>::default();
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 32, 3);
+        let lde = dft.coset_lde_batch_into(mat.as_view(), 2, shift, buffer);
+        assert_eq!(
+            lde.to_row_major_matrix(),
+            dft.coset_lde_batch(mat, 2, shift).to_row_major_matrix()
+        );
+    }
+
+    #[test]
+    fn bitrev_entry_points_match() {
+        let shift = F::GENERATOR;
+        let coeffs = RowMajorMatrix::<F>::rand(&mut thread_rng(), 32, 3);
+        let mut coeffs_bitrev = coeffs.clone();
+        reverse_matrix_index_bits(&mut coeffs_bitrev);
+
+        let evals = Radix2Dit::default().dft_batch(coeffs);
+        let lde_bitrev = Radix2Dit::default()
+            .coset_lde_batch(evals.clone(), 2, shift)
+            .bit_reverse_rows()
+            .to_row_major_matrix();
+
+        assert_eq!(
+            Radix2Dit::default().dft_bitrev_in(coeffs_bitrev.clone()),
+            evals
+        );
+        assert_eq!(Radix2Bowers.dft_bitrev_in(coeffs_bitrev.clone()), evals);
+        assert_eq!(Radix2Bowers.idft_bitrev_out(evals.clone()), coeffs_bitrev);
+        assert_eq!(
+            Radix2Dit::default().idft_bitrev_out(evals.clone()),
+            coeffs_bitrev
+        );
+        assert_eq!(
+            Radix2Bowers.coset_lde_batch_bitrev_out(evals.clone(), 2, shift),
+            lde_bitrev
+        );
+        assert_eq!(
+            Radix2DitParallel::default().coset_lde_batch_bitrev_out(evals, 2, shift),
+            lde_bitrev
+        );
+    }
+
+    #[test]
+    fn lde_in_order() {
+        let shift = F::GENERATOR;
+        let evals = RowMajorMatrix::<F>::rand(&mut thread_rng(), 16, 3);
+        let lde = Radix2Dit::default().coset_lde_batch(evals.clone(), 1, shift);
+        let mut lde_bitrev = lde.clone();
+        reverse_matrix_index_bits(&mut lde_bitrev);
+
+        let dft = Radix2DitParallel::default();
+        assert_eq!(
+            dft.coset_lde_batch_in_order(evals.clone(), 1, shift, RowOrder::Natural),
+            lde
+        );
+        assert_eq!(
+            dft.coset_lde_batch_in_order(evals.clone(), 1, shift, RowOrder::BitReversed),
+            lde_bitrev
+        );
+        assert_eq!(
+            Radix2Bowers.coset_lde_batch_in_order(evals, 1, shift, RowOrder::BitReversed),
+            lde_bitrev
+        );
+
+        // A view with a runtime order converts to either order.
+        let view = RowOrderPerm::new_view(lde.clone(), RowOrder::BitReversed);
+        assert_eq!(
+            view.to_row_major_matrix_in_order(RowOrder::Natural),
+            lde_bitrev
+        );
+        let view = RowOrderPerm::new_view(lde_bitrev.clone(), RowOrder::Natural);
+        assert_eq!(
+            view.to_row_major_matrix_in_order(RowOrder::BitReversed),
+            lde
+        );
+    }
+
+    #[test]
+    fn periodic_lde_matches_dense() {
+        let dft = Radix2Dit::<F>::default();
+        let period = RowMajorMatrix::<F>::rand(&mut thread_rng(), 4, 3);
+        let mat = PeriodicMatrix::new(period, 32);
+        let lde = dft.coset_lde_batch_periodic(mat.clone(), 2, F::GENERATOR);
+        assert_eq!(lde.period_height(), 16);
+        assert_eq!(
+            lde.to_row_major_matrix(),
+            dft.coset_lde_batch(mat.to_row_major_matrix(), 2, F::GENERATOR)
+        );
+    }
+
+    #[test]
+    fn pruned_input_matches_padded() {
+        let dft = Radix2Dit::<F>::default();
+        let shift = F::GENERATOR;
+        for added_bits in [0, 1, 3, 5] {
+            let coeffs = RowMajorMatrix::<F>::rand(&mut thread_rng(), 16, 3);
+            let mut padded = coeffs.clone();
+            padded.pad_to_height(16 << added_bits, F::ZERO);
+
+            assert_eq!(
+                dft.dft_batch_pruned_input(coeffs.clone(), added_bits),
+                dft.dft_batch(padded.clone())
+            );
+            assert_eq!(
+                dft.coset_dft_batch_pruned_input(coeffs, added_bits, shift),
+                dft.coset_dft_batch(padded, shift)
+            );
+        }
+    }
+
+    #[test]
+    fn pruned_output_matches_truncated() {
+        let dft = Radix2Dit::<F>::default();
+        let shift = F::GENERATOR;
+        for removed_bits in [0, 2, 3, 6] {
+            let coeffs = RowMajorMatrix::<F>::rand(&mut thread_rng(), 64, 3);
+            let out_h = 64 >> removed_bits;
+
+            let mut evals = dft.dft_batch(coeffs.clone());
+            evals.values.truncate(out_h * evals.width());
+            assert_eq!(
+                dft.dft_batch_pruned_output(coeffs.clone(), removed_bits),
+                evals
+            );
+
+            let mut evals = dft.coset_dft_batch(coeffs.clone(), shift);
+            evals.values.truncate(out_h * evals.width());
+            assert_eq!(
+                dft.coset_dft_batch_pruned_output(coeffs, removed_bits, shift),
+                evals
+            );
+        }
+    }
 }
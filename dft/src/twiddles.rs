@@ -0,0 +1,163 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::any::{Any, TypeId};
+
+use p3_field::TwoAdicField;
+use spin::RwLock;
+
+/// The caches returned by `TwiddleCache::global`, keyed by field.
+static GLOBAL_CACHES: RwLock<BTreeMap<TypeId, Box<dyn Any + Send + Sync>>> =
+    RwLock::new(BTreeMap::new());
+
+/// A thread-safe cache of twiddle factors. Clones share the same tables, so a cache can be passed
+/// to every DFT (and anything else needing roots of unity) in a prover to compute each table once.
+///
+/// `TwiddleCache::global()` is shared by everything using it for the same field, and is what the
+/// DFTs in this crate use by default. Tables are kept until `clear` is called.
+#[derive(Clone, Debug, Default)]
+pub struct TwiddleCache<F> {
+    tables: Arc<RwLock<TwiddleTables<F>>>,
+}
+
+#[derive(Debug)]
+struct TwiddleTables<F> {
+    roots: BTreeMap<usize, Arc<[F]>>,
+    inverse_roots: BTreeMap<usize, Arc<[F]>>,
+    coset_points: BTreeMap<(usize, F), Arc<[F]>>,
+}
+
+impl<F> Default for TwiddleTables<F> {
+    fn default() -> Self {
+        Self {
+            roots: BTreeMap::new(),
+            inverse_roots: BTreeMap::new(),
+            coset_points: BTreeMap::new(),
+        }
+    }
+}
+
+impl<F: TwoAdicField> TwiddleCache<F> {
+    /// The cache shared by everything using it for the field `F`.
+    pub fn global() -> Self {
+        let type_id = TypeId::of::<F>();
+        if let Some(cache) = GLOBAL_CACHES.read().get(&type_id) {
+            return cache.downcast_ref::<Self>().unwrap().clone();
+        }
+        GLOBAL_CACHES
+            .write()
+            .entry(type_id)
+            .or_insert_with(|| Box::new(Self::default()))
+            .downcast_ref::<Self>()
+            .unwrap()
+            .clone()
+    }
+
+    /// The powers `g^0, ..., g^{n-1}` of the generator `g` of the subgroup of order `n = 2^log_n`.
+    pub fn roots(&self, log_n: usize) -> Arc<[F]> {
+        if let Some(roots) = self.tables.read().roots.get(&log_n) {
+            return roots.clone();
+        }
+        let roots = F::two_adic_generator(log_n)
+            .powers()
+            .take(1 << log_n)
+            .collect();
+        self.tables
+            .write()
+            .roots
+            .entry(log_n)
+            .or_insert(roots)
+            .clone()
+    }
+
+    /// Like `roots`, but for `g^{-1}`.
+    pub fn inverse_roots(&self, log_n: usize) -> Arc<[F]> {
+        if let Some(roots) = self.tables.read().inverse_roots.get(&log_n) {
+            return roots.clone();
+        }
+        let roots = F::two_adic_generator(log_n)
+            .inverse()
+            .powers()
+            .take(1 << log_n)
+            .collect();
+        self.tables
+            .write()
+            .inverse_roots
+            .entry(log_n)
+            .or_insert(roots)
+            .clone()
+    }
+
+    /// The points `shift g^0, ..., shift g^{n-1}` of the coset of the subgroup of order
+    /// `n = 2^log_n`, e.g. the LDE domain, at which quotient computation evaluates selectors.
+    pub fn coset_points(&self, log_n: usize, shift: F) -> Arc<[F]>
+    where
+        F: Ord,
+    {
+        if let Some(points) = self.tables.read().coset_points.get(&(log_n, shift)) {
+            return points.clone();
+        }
+        let points = F::two_adic_generator(log_n)
+            .shifted_powers(shift)
+            .take(1 << log_n)
+            .collect();
+        self.tables
+            .write()
+            .coset_points
+            .entry((log_n, shift))
+            .or_insert(points)
+            .clone()
+    }
+
+    /// Drop all tables, e.g. once proving is done.
+    pub fn clear(&self) {
+        *self.tables.write() = TwiddleTables::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::{AbstractField, Field, TwoAdicField};
+    use p3_goldilocks::Goldilocks;
+
+    use crate::TwiddleCache;
+
+    type F = BabyBear;
+
+    #[test]
+    fn global_cache_is_shared() {
+        let roots = TwiddleCache::<F>::global().roots(5);
+        assert!(Arc::ptr_eq(&roots, &TwiddleCache::<F>::global().roots(5)));
+        assert_eq!(
+            roots[..],
+            F::two_adic_generator(5)
+                .powers()
+                .take(32)
+                .collect::<Vec<_>>()[..]
+        );
+        // Other fields get their own cache.
+        assert_eq!(
+            TwiddleCache::<Goldilocks>::global().roots(5)[1],
+            Goldilocks::two_adic_generator(5)
+        );
+    }
+
+    #[test]
+    fn tables() {
+        let cache = TwiddleCache::<F>::default();
+        let roots = cache.roots(4);
+        let inverse_roots = cache.inverse_roots(4);
+        for (&x, &y) in roots.iter().zip(inverse_roots.iter()) {
+            assert_eq!(x * y, F::ONE);
+        }
+        let points = cache.coset_points(4, F::GENERATOR);
+        assert_eq!(points[3], F::GENERATOR * roots[3]);
+
+        cache.clear();
+        assert!(!Arc::ptr_eq(&roots, &cache.roots(4)));
+    }
+}
@@ -1,6 +1,6 @@
 use core::borrow::BorrowMut;
 
-use p3_field::Field;
+use p3_field::{ExtensionField, Field};
 use p3_matrix::dense::{DenseMatrix, DenseStorage, RowMajorMatrix};
 use p3_matrix::Matrix;
 use tracing::instrument;
@@ -23,3 +23,17 @@ pub(crate) fn coset_shift_cols<F: Field>(mat: &mut RowMajorMatrix<F>, shift: F)
             })
         });
 }
+
+/// The inverse of `RowMajorMatrix::flatten_to_base`, gathering each `EF::D` consecutive base
+/// field values into one extension field element.
+pub(crate) fn reconstitute_from_base<F: Field, EF: ExtensionField<F>>(
+    mat: RowMajorMatrix<F>,
+) -> RowMajorMatrix<EF> {
+    let width = mat.width() / EF::D;
+    let values = mat
+        .values
+        .chunks_exact(EF::D)
+        .map(EF::from_base_slice)
+        .collect();
+    RowMajorMatrix::new(values, width)
+}
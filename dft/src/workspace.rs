@@ -0,0 +1,165 @@
+use alloc::vec::Vec;
+
+use p3_field::TwoAdicField;
+use p3_matrix::bitrev::{BitReversableMatrix, RowOrder};
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::Matrix;
+
+use crate::TwoAdicSubgroupDft;
+
+/// Buffers to run DFTs in, which are kept and reused rather than allocated afresh for each DFT.
+///
+/// A prover can create one workspace and run all of its DFTs in it, recycling the results once
+/// they're no longer needed, so that repeated proofs don't allocate (and page-fault) the same
+/// memory over and over.
+#[derive(Clone, Debug)]
+pub struct DftWorkspace<F> {
+    buffers: Vec<Vec<F>>,
+}
+
+impl<F> Default for DftWorkspace<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> DftWorkspace<F> {
+    pub const fn new() -> Self {
+        Self {
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Take an empty buffer with capacity for at least `len` elements: the smallest recycled one
+    /// that's large enough, or a new one if there isn't one.
+    pub fn take(&mut self, len: usize) -> Vec<F> {
+        let best_fit = self
+            .buffers
+            .iter()
+            .enumerate()
+            .filter(|(_, buffer)| buffer.capacity() >= len)
+            .min_by_key(|(_, buffer)| buffer.capacity())
+            .map(|(i, _)| i);
+        match best_fit {
+            Some(i) => self.buffers.swap_remove(i),
+            None => Vec::with_capacity(len),
+        }
+    }
+
+    /// Keep `buffer` to be reused by a later `take`.
+    pub fn recycle(&mut self, mut buffer: Vec<F>) {
+        if buffer.capacity() > 0 {
+            buffer.clear();
+            self.buffers.push(buffer);
+        }
+    }
+
+    /// Keep the storage of `evals`, e.g. the result of one of the DFTs below, to be reused.
+    pub fn recycle_evaluations<M>(&mut self, evals: M)
+    where
+        F: Clone + Send + Sync,
+        M: Matrix<F>,
+    {
+        self.recycle(evals.to_row_major_matrix().values);
+    }
+
+    /// Drop all recycled buffers.
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+    }
+}
+
+impl<F: TwoAdicField> DftWorkspace<F> {
+    /// Like `dft.dft_batch`, but run in a buffer from this workspace.
+    pub fn dft_batch<Dft: TwoAdicSubgroupDft<F>>(
+        &mut self,
+        dft: &Dft,
+        mat: RowMajorMatrixView<'_, F>,
+    ) -> Dft::Evaluations {
+        let mut buffer = self.take(mat.values.len());
+        buffer.extend_from_slice(mat.values);
+        dft.dft_batch(RowMajorMatrix::new(buffer, mat.width()))
+    }
+
+    /// Like `dft.coset_lde_batch`, but run in a buffer from this workspace.
+    pub fn coset_lde_batch<Dft: TwoAdicSubgroupDft<F>>(
+        &mut self,
+        dft: &Dft,
+        mat: RowMajorMatrixView<'_, F>,
+        added_bits: usize,
+        shift: F,
+    ) -> Dft::Evaluations {
+        let buffer = self.take(mat.values.len() << added_bits);
+        dft.coset_lde_batch_into(mat, added_bits, shift, buffer)
+    }
+
+    /// Like `coset_lde_batch`, but with the rows of the result in bit-reversed order, as committed
+    /// to by FRI.
+    pub fn coset_lde_batch_bit_reversed<Dft: TwoAdicSubgroupDft<F>>(
+        &mut self,
+        dft: &Dft,
+        mat: RowMajorMatrixView<'_, F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        self.coset_lde_batch(dft, mat, added_bits, shift)
+            .to_row_major_matrix_in_order(RowOrder::BitReversed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::Field;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::Matrix;
+    use rand::thread_rng;
+
+    use crate::{DftWorkspace, Radix2DitParallel, TwoAdicSubgroupDft};
+
+    type F = BabyBear;
+
+    #[test]
+    fn workspace_reuses_buffers() {
+        let mut workspace = DftWorkspace::new();
+        let small = workspace.take(10);
+        let large = workspace.take(100);
+        let (small_ptr, large_ptr) = (small.as_ptr(), large.as_ptr());
+        workspace.recycle(large);
+        workspace.recycle(small);
+        let (small, large) = (workspace.take(5), workspace.take(50));
+        assert_eq!(small.as_ptr(), small_ptr);
+        assert_eq!(large.as_ptr(), large_ptr);
+        assert!(workspace.take(5).capacity() >= 5);
+        workspace.recycle(Vec::<F>::new());
+        assert!(workspace.buffers.is_empty());
+    }
+
+    #[test]
+    fn workspace_dfts_match() {
+        let dft = Radix2DitParallel::<F>::default();
+        let mut workspace = DftWorkspace::new();
+        for _ in 0..3 {
+            let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 64, 5);
+
+            let evals = workspace
+                .dft_batch(&dft, mat.as_view())
+                .to_row_major_matrix();
+            assert_eq!(evals, dft.dft_batch(mat.clone()).to_row_major_matrix());
+            workspace.recycle_evaluations(evals);
+
+            let lde = workspace
+                .coset_lde_batch(&dft, mat.as_view(), 2, F::GENERATOR)
+                .to_row_major_matrix();
+            assert_eq!(
+                lde,
+                dft.coset_lde_batch(mat, 2, F::GENERATOR)
+                    .to_row_major_matrix()
+            );
+            workspace.recycle_evaluations(lde);
+        }
+        assert_eq!(workspace.buffers.len(), 2);
+    }
+}
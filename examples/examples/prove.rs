@@ -0,0 +1,69 @@
+//! Prove the Fibonacci and VM AIRs under every preset of `p3_config`.
+
+use p3_config::{ConfigOptions, Preset};
+use p3_examples::{generate_fibonacci_trace, prove_and_verify, FibonacciAir, Instruction, VmAir};
+use p3_field::PrimeField64;
+use p3_uni_stark::{StarkGenericConfig, Val};
+use tracing::info_span;
+use tracing_forest::util::LevelFilter;
+use tracing_forest::ForestLayer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry};
+
+const LOG_FIBONACCI_ROWS: usize = 16;
+const SECURITY_BITS: usize = 100;
+
+/// Runs `(x + 3) * 5 - 7` many times over.
+fn vm_program() -> Vec<Instruction> {
+    (0..20_000)
+        .flat_map(|_| {
+            [
+                Instruction::Add(3),
+                Instruction::Mul(5),
+                Instruction::Sub(7),
+            ]
+        })
+        .collect()
+}
+
+fn prove_all<SC>(name: &str, preset: Preset<SC>)
+where
+    SC: StarkGenericConfig,
+    SC::Challenger: Clone,
+    Val<SC>: PrimeField64,
+{
+    let _span = info_span!("config", name).entered();
+
+    let (trace, public_values) = generate_fibonacci_trace(0, 1, 1 << LOG_FIBONACCI_ROWS);
+    info_span!("fibonacci")
+        .in_scope(|| prove_and_verify(&preset, &FibonacciAir, trace, &public_values))
+        .expect("verification failed");
+
+    let vm = VmAir::new(vm_program());
+    let (trace, public_values) = vm.generate_trace(2);
+    info_span!("vm")
+        .in_scope(|| prove_and_verify(&preset, &vm, trace, &public_values))
+        .expect("verification failed");
+}
+
+fn main() {
+    let env_filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env_lossy();
+
+    Registry::default()
+        .with(env_filter)
+        .with(ForestLayer::default())
+        .init();
+
+    let options = ConfigOptions::new(SECURITY_BITS);
+    prove_all("BabyBear Poseidon2", options.baby_bear_poseidon2());
+    prove_all("KoalaBear Poseidon2", options.koala_bear_poseidon2());
+    prove_all("BabyBear Keccak", options.baby_bear_keccak());
+    prove_all("KoalaBear Keccak", options.koala_bear_keccak());
+    prove_all(
+        "Mersenne31 circle Keccak",
+        options.mersenne_31_circle_keccak(),
+    );
+}
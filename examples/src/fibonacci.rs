@@ -0,0 +1,90 @@
+use core::borrow::Borrow;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{Field, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+/// The number of columns of `FibonacciAir`.
+pub const NUM_FIBONACCI_COLS: usize = 2;
+
+/// Proves that `x` is the `n`-th term of the Fibonacci-like sequence starting with `a, b`, where
+/// the trace has `n` rows and the public values are `[a, b, x]`.
+///
+/// Each row holds two consecutive terms, and each transition shifts them along by one.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FibonacciAir;
+
+/// A row of the trace of `FibonacciAir`.
+#[repr(C)]
+pub struct FibonacciRow<F> {
+    pub left: F,
+    pub right: F,
+}
+
+impl<F> Borrow<FibonacciRow<F>> for [F] {
+    fn borrow(&self) -> &FibonacciRow<F> {
+        debug_assert_eq!(self.len(), NUM_FIBONACCI_COLS);
+        let (prefix, rows, suffix) = unsafe { self.align_to::<FibonacciRow<F>>() };
+        debug_assert!(prefix.is_empty(), "Alignment should match");
+        debug_assert!(suffix.is_empty(), "Alignment should match");
+        debug_assert_eq!(rows.len(), 1);
+        &rows[0]
+    }
+}
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        NUM_FIBONACCI_COLS
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let public_values = builder.public_values();
+        let (a, b, x) = (public_values[0], public_values[1], public_values[2]);
+
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let local: &FibonacciRow<AB::Var> = (*local).borrow();
+        let next: &FibonacciRow<AB::Var> = (*next).borrow();
+
+        let mut when_first_row = builder.when_first_row();
+        when_first_row.assert_eq(local.left, a);
+        when_first_row.assert_eq(local.right, b);
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(next.left, local.right);
+        when_transition.assert_eq(next.right, local.left + local.right);
+
+        builder.when_last_row().assert_eq(local.right, x);
+    }
+}
+
+/// The trace of `FibonacciAir` with `n` rows, starting with `a, b`, and its public values.
+pub fn generate_fibonacci_trace<F: PrimeField64>(
+    a: u64,
+    b: u64,
+    n: usize,
+) -> (RowMajorMatrix<F>, Vec<F>) {
+    assert!(n.is_power_of_two());
+    let (a, b) = (F::from_canonical_u64(a), F::from_canonical_u64(b));
+
+    let mut values = Vec::with_capacity(n * NUM_FIBONACCI_COLS);
+    let (mut left, mut right) = (a, b);
+    for _ in 0..n {
+        values.extend([left, right]);
+        (left, right) = (right, left + right);
+    }
+    let trace = RowMajorMatrix::new(values, NUM_FIBONACCI_COLS);
+
+    let x = trace.get(n - 1, 1);
+    (trace, vec![a, b, x])
+}
+
+/// The `n`-th term of the sequence starting with `a, b`, computed directly.
+pub fn fibonacci<F: Field>(a: F, b: F, n: usize) -> F {
+    (1..n)
+        .fold((a, b), |(left, right), _| (right, left + right))
+        .1
+}
@@ -0,0 +1,14 @@
+//! End-to-end examples of proving and verifying AIRs with `p3-uni-stark`.
+//!
+//! - [`FibonacciAir`] and [`VmAir`] are small AIRs, along with their trace generators.
+//! - [`prove_and_verify`] runs the prover and the verifier with any [`p3_config`] preset.
+//!
+//! `cargo run --release -p p3-examples --example prove` proves both AIRs under every preset.
+
+mod fibonacci;
+mod prove;
+mod vm;
+
+pub use fibonacci::*;
+pub use prove::*;
+pub use vm::*;
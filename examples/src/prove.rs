@@ -0,0 +1,44 @@
+use p3_air::Air;
+use p3_config::Preset;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::{
+    prove, verify, PcsError, Proof, ProverConstraintFolder, StarkGenericConfig, SymbolicAirBuilder,
+    Val, VerificationError, VerifierConstraintFolder,
+};
+
+/// Prove that `trace` satisfies `air` with `public_values`, and verify the proof, each starting
+/// from a copy of the challenger of `preset`.
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_and_verify<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<p3_uni_stark::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    preset: &Preset<SC>,
+    air: &A,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+) -> Result<Proof<SC>, VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    SC::Challenger: Clone,
+    A: Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<ProverConstraintFolder<'a, SC>>
+        + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let proof = prove(
+        &preset.config,
+        air,
+        &mut preset.challenger.clone(),
+        trace,
+        public_values,
+    );
+    verify(
+        &preset.config,
+        air,
+        &mut preset.challenger.clone(),
+        &proof,
+        public_values,
+    )?;
+    Ok(proof)
+}
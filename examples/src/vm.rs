@@ -0,0 +1,107 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, PeriodicAirBuilder};
+use p3_field::{Field, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+/// An instruction of the accumulator machine proven by `VmAir`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    /// `acc <- acc + imm`
+    Add(u32),
+    /// `acc <- acc - imm`
+    Sub(u32),
+    /// `acc <- acc * imm`
+    Mul(u32),
+}
+
+/// Proves the execution of a fixed program on a machine with a single accumulator register. The
+/// public values are `[input, output]`: the accumulator starts as `input`, and is `output` once the
+/// program has run.
+///
+/// The trace has a single column, the accumulator, with row `i` holding its value before the
+/// `i`-th instruction. The program is known to the verifier, so rather than committing to it, it's
+/// given as periodic columns whose period is the trace height, padded with instructions which do
+/// nothing. Every instruction is `acc <- acc * mul + add` for two of these columns.
+#[derive(Clone, Debug)]
+pub struct VmAir {
+    pub program: Vec<Instruction>,
+}
+
+impl VmAir {
+    pub const fn new(program: Vec<Instruction>) -> Self {
+        Self { program }
+    }
+
+    /// The height of the trace: the number of instructions, plus a row for the output, rounded up
+    /// to a power of two.
+    pub fn trace_height(&self) -> usize {
+        (self.program.len() + 1).next_power_of_two()
+    }
+
+    /// Run the program on `input`.
+    pub fn execute<F: Field>(&self, input: F) -> F {
+        self.program
+            .iter()
+            .fold(input, |acc, &instruction| step(acc, instruction))
+    }
+
+    /// The trace of running the program on `input`, and its public values.
+    pub fn generate_trace<F: PrimeField64>(&self, input: u64) -> (RowMajorMatrix<F>, Vec<F>) {
+        let input = F::from_canonical_u64(input);
+        let mut values = Vec::with_capacity(self.trace_height());
+        values.push(input);
+        for &instruction in &self.program {
+            values.push(step(*values.last().unwrap(), instruction));
+        }
+        // The padding instructions leave the accumulator as it is.
+        values.resize(self.trace_height(), *values.last().unwrap());
+        let trace = RowMajorMatrix::new_col(values);
+
+        let output = trace.get(trace.height() - 1, 0);
+        (trace, vec![input, output])
+    }
+}
+
+fn step<F: Field>(acc: F, instruction: Instruction) -> F {
+    match instruction {
+        Instruction::Add(imm) => acc + F::from_canonical_u32(imm),
+        Instruction::Sub(imm) => acc - F::from_canonical_u32(imm),
+        Instruction::Mul(imm) => acc * F::from_canonical_u32(imm),
+    }
+}
+
+impl<F: Field> BaseAir<F> for VmAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        let (mut mul, mut add): (Vec<F>, Vec<F>) = self
+            .program
+            .iter()
+            .map(|&instruction| match instruction {
+                Instruction::Add(imm) => (F::ONE, F::from_canonical_u32(imm)),
+                Instruction::Sub(imm) => (F::ONE, -F::from_canonical_u32(imm)),
+                Instruction::Mul(imm) => (F::from_canonical_u32(imm), F::ZERO),
+            })
+            .unzip();
+        mul.resize(self.trace_height(), F::ONE);
+        add.resize(self.trace_height(), F::ZERO);
+        vec![mul, add]
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues + PeriodicAirBuilder> Air<AB> for VmAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+        let periodic = builder.periodic_values();
+        let (mul, add): (AB::Expr, AB::Expr) = (periodic[0].into(), periodic[1].into());
+        let public_values = builder.public_values();
+        let (input, output) = (public_values[0], public_values[1]);
+
+        builder.when_first_row().assert_eq(local, input);
+        builder.when_transition().assert_eq(next, mul * local + add);
+        builder.when_last_row().assert_eq(local, output);
+    }
+}
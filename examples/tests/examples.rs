@@ -0,0 +1,81 @@
+use p3_config::{ConfigOptions, Preset};
+use p3_examples::{
+    fibonacci, generate_fibonacci_trace, prove_and_verify, FibonacciAir, Instruction, VmAir,
+};
+use p3_field::{AbstractField, PrimeField64};
+use p3_uni_stark::{StarkGenericConfig, Val};
+
+/// Fast to prove, and not meant to be secure.
+const OPTIONS: ConfigOptions = ConfigOptions::new(20).with_proof_of_work_bits(1);
+
+fn vm() -> VmAir {
+    VmAir::new(vec![
+        Instruction::Add(3),
+        Instruction::Mul(5),
+        Instruction::Sub(7),
+        Instruction::Mul(11),
+        Instruction::Add(1),
+    ])
+}
+
+fn prove_examples<SC>(preset: Preset<SC>)
+where
+    SC: StarkGenericConfig,
+    SC::Challenger: Clone,
+    Val<SC>: PrimeField64,
+{
+    let (trace, public_values) = generate_fibonacci_trace::<Val<SC>>(0, 1, 1 << 5);
+    assert_eq!(
+        public_values[2],
+        fibonacci(Val::<SC>::ZERO, Val::<SC>::ONE, 1 << 5)
+    );
+    prove_and_verify(&preset, &FibonacciAir, trace, &public_values).expect("verification failed");
+
+    let vm = vm();
+    let (trace, public_values) = vm.generate_trace::<Val<SC>>(2);
+    assert_eq!(public_values[1], vm.execute(Val::<SC>::TWO));
+    prove_and_verify(&preset, &vm, trace, &public_values).expect("verification failed");
+
+    // A different output doesn't verify. Debug builds check the constraints before proving.
+    if !cfg!(debug_assertions) {
+        let (trace, mut public_values) = vm.generate_trace::<Val<SC>>(2);
+        public_values[1] += Val::<SC>::ONE;
+        assert!(prove_and_verify(&preset, &vm, trace, &public_values).is_err());
+    }
+}
+
+#[test]
+fn baby_bear_poseidon2() {
+    prove_examples(OPTIONS.baby_bear_poseidon2());
+}
+
+#[test]
+fn koala_bear_poseidon2() {
+    prove_examples(OPTIONS.koala_bear_poseidon2());
+}
+
+#[test]
+fn baby_bear_keccak() {
+    prove_examples(OPTIONS.baby_bear_keccak());
+}
+
+#[test]
+fn koala_bear_keccak() {
+    prove_examples(OPTIONS.koala_bear_keccak());
+}
+
+#[test]
+fn mersenne_31_circle_keccak() {
+    prove_examples(OPTIONS.mersenne_31_circle_keccak());
+}
+
+#[test]
+fn vm_execution() {
+    let vm = vm();
+    assert_eq!(vm.trace_height(), 8);
+    // ((2 + 3) * 5 - 7) * 11 + 1
+    assert_eq!(
+        vm.execute(p3_baby_bear::BabyBear::TWO),
+        p3_baby_bear::BabyBear::from_canonical_u32(199)
+    );
+}
@@ -8,13 +8,15 @@ pub mod bench_func;
 pub mod dft_testing;
 pub mod packedfield_testing;
 
+use alloc::vec::Vec;
 pub use bench_func::*;
 pub use dft_testing::*;
 use num_bigint::BigUint;
 use num_traits::identities::One;
 use p3_field::{
     cyclic_subgroup_coset_known_order, cyclic_subgroup_known_order, two_adic_coset_zerofier,
-    two_adic_subgroup_zerofier, ExtensionField, Field, TwoAdicField,
+    two_adic_subgroup_zerofier, AbstractExtensionField, AbstractField, ExtensionField, Field,
+    PackedValue, TwoAdicField,
 };
 pub use packedfield_testing::*;
 use rand::distributions::{Distribution, Standard};
@@ -133,6 +135,24 @@ pub fn test_ef_two_adic_generator_consistency<
     );
 }
 
+/// Packs random extension elements, and checks that a packed multiplication agrees with each lane.
+pub fn test_ext_packing_lanes<F: Field, EF: ExtensionField<F>>()
+where
+    Standard: Distribution<EF>,
+{
+    let mut rng = rand::thread_rng();
+    let xs: Vec<EF> = (0..F::Packing::WIDTH).map(|_| rng.gen()).collect();
+    let y: EF = rng.gen();
+    let packed = EF::ExtensionPacking::from_base_fn(|i| {
+        F::Packing::from_fn(|lane| xs[lane].as_base_slice()[i])
+    });
+    let product = packed * EF::ExtensionPacking::from_f(y);
+    for (lane, &x) in xs.iter().enumerate() {
+        assert_eq!(EF::unpack_lane(&packed, lane), x);
+        assert_eq!(EF::unpack_lane(&product, lane), x * y);
+    }
+}
+
 #[macro_export]
 macro_rules! test_field {
     ($field:ty) => {
@@ -190,6 +210,11 @@ macro_rules! test_two_adic_extension_field {
             fn test_ef_two_adic_generator_consistency() {
                 $crate::test_ef_two_adic_generator_consistency::<$field, $ef>();
             }
+
+            #[test]
+            fn test_ext_packing_lanes() {
+                $crate::test_ext_packing_lanes::<$field, $ef>();
+            }
         }
     };
 }
@@ -198,6 +198,11 @@ where
                 cubic_square(&self.value, &mut res.value, AF::F::W);
                 res
             }
+            5 => {
+                let mut res = Self::default();
+                quintic_square(&self.value, &mut res.value, AF::F::W);
+                res
+            }
             _ => <Self as Mul<Self>>::mul(self.clone(), self.clone()),
         }
     }
@@ -420,6 +425,7 @@ where
                 res.value[1] = a[0].clone() * b[1].clone() + a[1].clone() * b[0].clone();
             }
             3 => cubic_mul(&a, &b, &mut res.value, w_af),
+            5 => quintic_mul(&a, &b, &mut res.value, w_af),
             _ =>
             {
                 #[allow(clippy::needless_range_loop)]
@@ -630,6 +636,55 @@ fn cubic_mul<AF: AbstractField, const D: usize>(
     res[2] = (a[0].clone() + a[2].clone()) * (b[0].clone() + b[2].clone()) - a0_b0 - a2_b2 + a1_b1;
 }
 
+/// Schoolbook multiplication for quintic extension fields, which multiplies the wrapped around
+/// coefficients by `w` once per coefficient of the result rather than once per product.
+#[inline]
+fn quintic_mul<AF: AbstractField, const D: usize>(
+    a: &[AF; D],
+    b: &[AF; D],
+    res: &mut [AF; D],
+    w: AF,
+) {
+    assert_eq!(D, 5);
+
+    // The coefficient of `X^k` in the product of the polynomials `a` and `b`.
+    let coeff = |k: usize| -> AF {
+        (k.saturating_sub(4)..=k.min(4))
+            .map(|i| a[i].clone() * b[k - i].clone())
+            .sum()
+    };
+
+    for (k, r) in res.iter_mut().enumerate().take(4) {
+        *r = coeff(k) + coeff(k + 5) * w.clone();
+    }
+    res[4] = coeff(4);
+}
+
+/// Squaring for quintic extension fields, computing each cross term once and doubling it.
+#[inline]
+fn quintic_square<AF: AbstractField, const D: usize>(a: &[AF; D], res: &mut [AF; D], w: AF::F) {
+    assert_eq!(D, 5);
+
+    // The coefficient of `X^k` in the square of the polynomial `a`.
+    let coeff = |k: usize| -> AF {
+        let cross: AF = (k.saturating_sub(4)..=k.min(4))
+            .filter(|&i| 2 * i < k)
+            .map(|i| a[i].clone() * a[k - i].clone())
+            .sum();
+        if k % 2 == 0 {
+            cross.double() + a[k / 2].square()
+        } else {
+            cross.double()
+        }
+    };
+
+    let w = AF::from_f(w);
+    for (k, r) in res.iter_mut().enumerate().take(4) {
+        *r = coeff(k) + coeff(k + 5) * w.clone();
+    }
+    res[4] = coeff(4);
+}
+
 /// Section 11.3.6a in Handbook of Elliptic and Hyperelliptic Curve Cryptography.
 #[inline]
 fn cubic_square<AF: AbstractField, const D: usize>(a: &[AF; D], res: &mut [AF; D], w: AF::F) {
@@ -408,6 +408,11 @@ pub trait ExtensionField<Base: Field>: Field + AbstractExtensionField<Base> {
 
         core::iter::successors(Some(current), move |&current| Some(current * multiplier))
     }
+
+    /// The element in lane `lane` of `packed`.
+    fn unpack_lane(packed: &Self::ExtensionPacking, lane: usize) -> Self {
+        Self::from_base_fn(|i| packed.as_base_slice()[i].as_slice()[lane])
+    }
 }
 
 impl<F: Field> ExtensionField<F> for F {
@@ -451,6 +456,42 @@ pub trait TwoAdicField: Field {
     fn two_adic_generator(bits: usize) -> Self;
 }
 
+/// A two-adic field whose multiplicative group also has subgroups of order `3^n`, so that it has
+/// subgroups of any order `2^a 3^b` with `a <= TWO_ADICITY` and `b <= THREE_ADICITY`.
+pub trait ThreeAdicField: TwoAdicField {
+    /// The number of factors of three in this field's multiplicative group.
+    const THREE_ADICITY: usize;
+
+    /// Returns a generator of the multiplicative group of order `3^bits`.
+    /// Assumes `bits <= THREE_ADICITY`, otherwise the result is undefined.
+    #[must_use]
+    fn three_adic_generator(bits: usize) -> Self;
+
+    /// Returns a generator `g` of the multiplicative group of order `2^two_bits 3^three_bits`,
+    /// chosen so that `g^(3^three_bits)` is `two_adic_generator(two_bits)` and `g^(2^two_bits)` is
+    /// `three_adic_generator(three_bits)`. This makes DFTs over such groups decompose into DFTs
+    /// over the two-adic and three-adic subgroups.
+    #[must_use]
+    fn mixed_radix_generator(two_bits: usize, three_bits: usize) -> Self {
+        let two_order = 1u64 << two_bits;
+        let three_order = 3u64.pow(three_bits as u32);
+        Self::two_adic_generator(two_bits).exp_u64(inverse_mod(three_order, two_order))
+            * Self::three_adic_generator(three_bits).exp_u64(inverse_mod(two_order, three_order))
+    }
+}
+
+/// The inverse of `x` modulo `m`, for coprime `x` and `m`.
+fn inverse_mod(x: u64, m: u64) -> u64 {
+    let (mut a, mut b) = (i128::from(x % m), i128::from(m));
+    let (mut s, mut t) = (1i128, 0i128);
+    while b != 0 {
+        let q = a / b;
+        (a, b) = (b, a - q * b);
+        (s, t) = (t, s - q * t);
+    }
+    s.rem_euclid(i128::from(m)) as u64
+}
+
 /// An iterator over the powers of a certain base element `b`: `b^0, b^1, b^2, ...`.
 #[derive(Clone, Debug)]
 pub struct Powers<F> {
@@ -0,0 +1,97 @@
+//! Measures how the FRI prover scales with the number of threads.
+//!
+//! Run with `cargo bench -p p3-fri --features parallel --bench fri_prover`; without the `parallel`
+//! feature every thread count runs serially.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
+use p3_commit::{ExtensionMmcs, Pcs};
+use p3_dft::Radix2Bowers;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::Field;
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use rayon::ThreadPoolBuilder;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+// The DFT is only used for committing; unlike the caching DFTs it can be shared across threads.
+type Dft = Radix2Bowers;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+const NUM_COLS: usize = 16;
+const THREAD_COUNTS: [usize; 6] = [1, 2, 4, 8, 16, 32];
+
+fn bench_fri_prover(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut rng,
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 100,
+        proof_of_work_bits: 0,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+    };
+    let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+    let challenger = Challenger::new(perm);
+
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    let mut group = c.benchmark_group("fri_prover");
+    group.sample_size(10);
+
+    for log_n in [16, 18, 20] {
+        let domain =
+            <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_n);
+        let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, NUM_COLS);
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+        for num_threads in THREAD_COUNTS.into_iter().filter(|&n| n <= max_threads) {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(format!("log_n={log_n}"), num_threads),
+                &num_threads,
+                |b, _| {
+                    b.iter(|| {
+                        pool.install(|| {
+                            let mut challenger = challenger.clone();
+                            challenger.observe(commit.clone());
+                            let zeta: Challenge = challenger.sample_ext_element();
+                            pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger)
+                        })
+                    })
+                },
+            );
+        }
+    }
+}
+
+criterion_group!(benches, bench_fri_prover);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+//! Compares verifying the queries' openings of a commit phase commitment one at a time, as the FRI
+//! verifier used to, with `verify_commit_phase_openings`, which verifies the distinct openings
+//! together.
+//!
+//! The smaller the layer, the more of the queries open the same pair, and the more is saved.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use itertools::Itertools;
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_commit::{ExtensionMmcs, Mmcs};
+use p3_field::extension::BinomialExtensionField;
+use p3_field::Field;
+use p3_fri::verifier::verify_commit_phase_openings;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Dimensions;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+const NUM_QUERIES: usize = 100;
+
+fn bench_commit_phase_openings(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut rng,
+    );
+    let mmcs = ChallengeMmcs::new(ValMmcs::new(
+        MyHash::new(perm.clone()),
+        MyCompress::new(perm),
+    ));
+
+    let mut group = c.benchmark_group("commit_phase_openings");
+
+    for log_folded_height in [4, 10, 16] {
+        let layer = RowMajorMatrix::<Challenge>::rand(&mut rng, 1 << log_folded_height, 2);
+        let (commit, data) = mmcs.commit_matrix(layer);
+        let dims = [Dimensions {
+            width: 2,
+            height: 1 << log_folded_height,
+        }];
+
+        // Each query's index in the layer before folding, its folded evaluation, its sibling's,
+        // and the proof of the pair.
+        let openings = (0..NUM_QUERIES)
+            .map(|_| {
+                let index = rng.gen_range(0..1 << (log_folded_height + 1));
+                let (mut opened_values, proof) = mmcs.open_batch(index >> 1, &data);
+                let row = opened_values.pop().unwrap();
+                (index, row[index % 2], row[(index ^ 1) % 2], proof)
+            })
+            .collect_vec();
+
+        group.bench_function(BenchmarkId::new("each", log_folded_height), |b| {
+            b.iter(|| {
+                for (index, folded_eval, sibling_value, proof) in &openings {
+                    let mut row = vec![*folded_eval; 2];
+                    row[(index ^ 1) % 2] = *sibling_value;
+                    mmcs.verify_batch(&commit, &dims, index >> 1, &[row], proof)
+                        .unwrap();
+                }
+            })
+        });
+
+        let openings = openings
+            .iter()
+            .map(|(index, folded_eval, sibling_value, proof)| {
+                (*index, *folded_eval, *sibling_value, proof)
+            })
+            .collect_vec();
+        group.bench_function(BenchmarkId::new("together", log_folded_height), |b| {
+            b.iter(|| {
+                verify_commit_phase_openings(&mmcs, &commit, log_folded_height, &openings).unwrap()
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_commit_phase_openings);
+criterion_main!(benches);
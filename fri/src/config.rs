@@ -1,30 +1,158 @@
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
-use p3_field::Field;
+use p3_challenger::CanObserve;
+use p3_field::{ExtensionField, Field};
 use p3_matrix::Matrix;
 
+use crate::SecurityAssumption;
+
 #[derive(Debug)]
 pub struct FriConfig<M> {
     pub log_blowup: usize,
+    /// The log of the number of coefficients of the final polynomial. Folding stops once the
+    /// codeword has `2^(log_blowup + log_final_poly_len)` evaluations, and the prover sends the
+    /// polynomial they encode. With 0, it folds all the way down to a constant.
+    pub log_final_poly_len: usize,
     pub num_queries: usize,
+    /// The number of proof-of-work bits, or if `target_soundness_bits` is set, the minimum number.
     pub proof_of_work_bits: usize,
+    /// If set, the number of proof-of-work bits is derived for each proof so as to reach this
+    /// many bits of soundness under `security_assumption`; see `pow_bits`.
+    pub target_soundness_bits: Option<usize>,
+    pub security_assumption: SecurityAssumption,
     pub mmcs: M,
 }
 
 impl<M> FriConfig<M> {
+    /// A config which reaches `security_bits` bits of soundness under `security_assumption`,
+    /// with as few queries as possible given that the prover grinds `proof_of_work_bits` bits.
+    ///
+    /// More proof-of-work bits may be derived for a proof, if the commit phase calls for it; see
+    /// `pow_bits`.
+    pub fn for_security_level(
+        log_blowup: usize,
+        log_final_poly_len: usize,
+        security_assumption: SecurityAssumption,
+        security_bits: usize,
+        proof_of_work_bits: usize,
+        mmcs: M,
+    ) -> Self {
+        let num_queries = security_assumption
+            .num_queries(log_blowup, security_bits.saturating_sub(proof_of_work_bits));
+        Self {
+            log_blowup,
+            log_final_poly_len,
+            num_queries,
+            proof_of_work_bits,
+            target_soundness_bits: Some(security_bits),
+            security_assumption,
+            mmcs,
+        }
+    }
+
     pub const fn blowup(&self) -> usize {
         1 << self.log_blowup
     }
 
+    pub const fn final_poly_len(&self) -> usize {
+        1 << self.log_final_poly_len
+    }
+
     /// Returns the soundness bits of this FRI instance based on the
     /// [ethSTARK](https://eprint.iacr.org/2021/582) conjecture.
     ///
-    /// Certain users may instead want to look at proven soundness, a more complex calculation which
-    /// isn't currently supported by this crate.
+    /// See `soundness_bits` for soundness under the configured `security_assumption`.
     pub fn conjectured_soundness_bits(&self) -> usize {
         self.log_blowup * self.num_queries + self.proof_of_work_bits
     }
+
+    /// Returns the soundness bits of the queries and the minimum proof-of-work, under
+    /// `security_assumption`.
+    pub fn soundness_bits(&self) -> usize {
+        self.security_assumption
+            .query_soundness_bits(self.log_blowup, self.num_queries)
+            + self.proof_of_work_bits
+    }
+
+    /// Returns the number of proof-of-work bits for a FRI instance whose largest input has height
+    /// `2^log_max_height`, with proof-of-work witnesses drawn from `Val` and challenges drawn from
+    /// `Challenge`.
+    ///
+    /// Without a `target_soundness_bits`, this is just `proof_of_work_bits`. Otherwise, it's
+    /// however many bits the queries are short of the target under `security_assumption`, and at
+    /// least `proof_of_work_bits`. Soundness is also capped by the commit phase no matter how much
+    /// we grind (see `SecurityAssumption::commit_phase_soundness_bits`), so this returns `None` if
+    /// the target can't be reached at this height. It also returns `None` if it would take more
+    /// than `max_derived_pow_bits` bits, as there may then be no witness to find.
+    pub fn pow_bits<Val: Field, Challenge: ExtensionField<Val>>(
+        &self,
+        log_max_height: usize,
+    ) -> Option<usize> {
+        let Some(target) = self.target_soundness_bits else {
+            return Some(self.proof_of_work_bits);
+        };
+        let commit_phase_bits = self
+            .security_assumption
+            .commit_phase_soundness_bits(Challenge::bits(), log_max_height);
+        if target > commit_phase_bits {
+            return None;
+        }
+        let query_bits = self
+            .security_assumption
+            .query_soundness_bits(self.log_blowup, self.num_queries);
+        let pow_bits = target
+            .saturating_sub(query_bits)
+            .max(self.proof_of_work_bits);
+        (pow_bits <= Self::max_derived_pow_bits::<Val>()).then_some(pow_bits)
+    }
+
+    /// The most proof-of-work bits `pow_bits` derives with witnesses drawn from `Val`.
+    ///
+    /// Grinding searches `Val` for a witness, of which we expect about `|Val| / 2^bits`. We keep
+    /// this at least 16, so that the search fails with probability below `e^-16`.
+    pub fn max_derived_pow_bits<Val: Field>() -> usize {
+        Val::bits().saturating_sub(5)
+    }
+
+    /// Panics if `pow_bits` isn't defined for a FRI instance whose largest input has height
+    /// `2^log_max_height`.
+    ///
+    /// `pow_bits` is only needed once the commit phase is done, so PCSs call this as soon as they
+    /// know the height of an input, before doing any work on it, rather than fail after it.
+    pub fn check_pow_bits<Val: Field, Challenge: ExtensionField<Val>>(
+        &self,
+        log_max_height: usize,
+    ) {
+        assert!(
+            self.pow_bits::<Val, Challenge>(log_max_height).is_some(),
+            "FRI config can't reach its target soundness for inputs of height 2^{log_max_height} \
+            with at most {} bits of proof-of-work",
+            Self::max_derived_pow_bits::<Val>(),
+        );
+    }
+
+    /// Like `pow_bits`, but if the bits are derived from a target, the parameters they were
+    /// derived from are also observed by the challenger, so that the prover and verifier can't
+    /// disagree on them. The prover and verifier must call this at the same point of the protocol.
+    pub fn bind_pow_bits<Val, Challenge, Challenger>(
+        &self,
+        log_max_height: usize,
+        challenger: &mut Challenger,
+    ) -> Option<usize>
+    where
+        Val: Field,
+        Challenge: ExtensionField<Val>,
+        Challenger: CanObserve<Val>,
+    {
+        let pow_bits = self.pow_bits::<Val, Challenge>(log_max_height)?;
+        if self.target_soundness_bits.is_some() {
+            challenger.observe(Val::from_canonical_usize(log_max_height));
+            challenger.observe(Val::from_canonical_usize(self.num_queries));
+            challenger.observe(Val::from_canonical_usize(pow_bits));
+        }
+        Some(pow_bits)
+    }
 }
 
 /// Whereas `FriConfig` encompasses parameters the end user can set, `FriGenericConfig` is
@@ -50,4 +178,14 @@ pub trait FriGenericConfig<F: Field> {
 
     /// Same as applying fold_row to every row, possibly faster.
     fn fold_matrix<M: Matrix<F>>(&self, beta: F, m: M) -> Vec<F>;
+
+    /// Given the final codeword, in the same order as the codewords being folded, return the
+    /// coefficients of the polynomial it encodes, in the basis used by `eval_final_poly`. There is
+    /// one coefficient per evaluation; all but the first `FriConfig::final_poly_len` of them
+    /// should be zero.
+    fn interpolate_final_poly(&self, evals: Vec<F>) -> Vec<F>;
+
+    /// Evaluate the final polynomial, given its coefficients, at the point corresponding to row
+    /// `index` of a codeword of height `2^log_height`.
+    fn eval_final_poly(&self, index: usize, log_height: usize, coeffs: &[F]) -> F;
 }
@@ -0,0 +1,63 @@
+use alloc::vec::Vec;
+
+use p3_commit::{Mmcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
+use p3_dft::TwoAdicSubgroupDft;
+use p3_field::TwoAdicField;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+/// Computes the low-degree extensions of polynomials and commits to them, which is usually the
+/// most expensive part of committing with `TwoAdicFriPcs`.
+///
+/// This is the extension point for delegating that work elsewhere, e.g. to a GPU, while the rest
+/// of the protocol (opening, FRI folding, the transcript) stays on the CPU. Any
+/// `TwoAdicSubgroupDft` is a backend, which computes the LDEs with that DFT and commits with the
+/// given MMCS; it serves as the reference implementation.
+pub trait LdeAndCommitBackend<Val: TwoAdicField, InputMmcs: Mmcs<Val>> {
+    /// Given the evaluations of each batch of polynomials over its domain, commit to their
+    /// extensions, in a single batch commitment of `mmcs`.
+    ///
+    /// For a domain of size `n`, the committed matrix must have height `n << log_blowup`, and row
+    /// `i` must hold the evaluations at `Val::GENERATOR * g^reverse_bits(i)`, where `g` generates
+    /// the subgroup of that height. In other words, it's the LDE over the coset
+    /// `Val::GENERATOR * <g>`, in bit-reversed order.
+    #[allow(clippy::type_complexity)]
+    fn lde_and_commit(
+        &self,
+        mmcs: &InputMmcs,
+        log_blowup: usize,
+        evaluations: Vec<(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)>,
+    ) -> (
+        InputMmcs::Commitment,
+        InputMmcs::ProverData<RowMajorMatrix<Val>>,
+    );
+}
+
+impl<Val, Dft, InputMmcs> LdeAndCommitBackend<Val, InputMmcs> for Dft
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val>,
+    InputMmcs: Mmcs<Val>,
+{
+    fn lde_and_commit(
+        &self,
+        mmcs: &InputMmcs,
+        log_blowup: usize,
+        evaluations: Vec<(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)>,
+    ) -> (
+        InputMmcs::Commitment,
+        InputMmcs::ProverData<RowMajorMatrix<Val>>,
+    ) {
+        let ldes: Vec<_> = evaluations
+            .into_iter()
+            .map(|(domain, evals)| {
+                assert_eq!(domain.size(), evals.height());
+                let shift = Val::GENERATOR / domain.shift;
+                // Commit to the bit-reversed LDE.
+                self.coset_lde_batch_bitrev_out(evals, log_blowup, shift)
+            })
+            .collect();
+
+        mmcs.commit(ldes)
+    }
+}
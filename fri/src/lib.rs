@@ -6,12 +6,20 @@ extern crate alloc;
 
 mod config;
 mod fold_even_odd;
+mod lde_backend;
 mod proof;
 pub mod prover;
+mod security;
+mod serialization;
+mod streaming;
 mod two_adic_pcs;
 pub mod verifier;
 
 pub use config::*;
 pub use fold_even_odd::*;
+pub use lde_backend::*;
 pub use proof::*;
+pub use security::*;
+pub use serialization::*;
+pub use streaming::*;
 pub use two_adic_pcs::*;
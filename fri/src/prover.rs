@@ -2,11 +2,12 @@ use alloc::vec;
 use alloc::vec::Vec;
 use core::iter;
 
-use itertools::{izip, Itertools};
+use itertools::Itertools;
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_field::{ExtensionField, Field};
 use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
 use p3_util::log2_strict_usize;
 use tracing::{info_span, instrument};
 
@@ -18,36 +19,56 @@ pub fn prove<G, Val, Challenge, M, Challenger>(
     config: &FriConfig<M>,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
-    open_input: impl Fn(usize) -> G::InputProof,
+    open_input: impl Fn(usize) -> G::InputProof + Sync,
 ) -> FriProof<Challenge, M, Challenger::Witness, G::InputProof>
 where
     Val: Field,
     Challenge: ExtensionField<Val>,
-    M: Mmcs<Challenge>,
+    M: Mmcs<Challenge> + Sync,
+    M::ProverData<RowMajorMatrix<Challenge>>: Sync,
+    M::Proof: Send,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
+    G::InputProof: Send,
 {
     // check sorted descending
     assert!(inputs
         .iter()
         .tuple_windows()
         .all(|(l, r)| l.len() >= r.len()));
+    assert!(
+        inputs.last().unwrap().len() >= config.blowup() * config.final_poly_len(),
+        "inputs must be at least as large as the final codeword"
+    );
 
     let log_max_height = log2_strict_usize(inputs[0].len());
 
+    config.check_pow_bits::<Val, Challenge>(log_max_height);
+
     let commit_phase_result = commit_phase(g, config, inputs, challenger);
 
-    let pow_witness = challenger.grind(config.proof_of_work_bits);
+    let pow_bits = config
+        .bind_pow_bits::<Val, Challenge, _>(log_max_height, challenger)
+        .expect("checked before the commit phase");
+    let pow_witness = challenger.grind(pow_bits);
 
-    let query_proofs = info_span!("query phase").in_scope(|| {
-        iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
+    // Query indices must be sampled in order from the transcript, but once we have them the
+    // queries are independent, so we answer them in parallel.
+    let extra_query_index_bits = g.extra_query_index_bits();
+    let query_indices =
+        iter::repeat_with(|| challenger.sample_bits(log_max_height + extra_query_index_bits))
             .take(config.num_queries)
+            .collect_vec();
+
+    let query_proofs = info_span!("query phase").in_scope(|| {
+        query_indices
+            .into_par_iter()
             .map(|index| QueryProof {
                 input_proof: open_input(index),
                 commit_phase_openings: answer_query(
                     config,
                     &commit_phase_result.data,
-                    index >> g.extra_query_index_bits(),
+                    index >> extra_query_index_bits,
                 ),
             })
             .collect()
@@ -64,9 +85,12 @@ where
 struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
     commits: Vec<M::Commitment>,
     data: Vec<M::ProverData<RowMajorMatrix<F>>>,
-    final_poly: F,
+    final_poly: Vec<F>,
 }
 
+/// Each round's commitment must be observed before the folding challenge for that round can be
+/// sampled, so rounds are inherently sequential; the work within a round (folding, and hashing
+/// in the MMCS) is parallelized instead.
 #[instrument(name = "commit phase", skip_all)]
 fn commit_phase<G, Val, Challenge, M, Challenger>(
     g: &G,
@@ -86,7 +110,7 @@ where
     let mut commits = vec![];
     let mut data = vec![];
 
-    while folded.len() > config.blowup() {
+    while folded.len() > config.blowup() * config.final_poly_len() {
         let leaves = RowMajorMatrix::new(folded, 2);
         let (commit, prover_data) = config.mmcs.commit_matrix(leaves);
         challenger.observe(commit.clone());
@@ -100,17 +124,27 @@ where
         data.push(prover_data);
 
         if let Some(v) = inputs_iter.next_if(|v| v.len() == folded.len()) {
-            izip!(&mut folded, v).for_each(|(c, x)| *c += x);
+            folded
+                .par_iter_mut()
+                .zip(v.par_iter())
+                .for_each(|(c, &x)| *c += x);
         }
     }
 
-    // We should be left with `blowup` evaluations of a constant polynomial.
-    assert_eq!(folded.len(), config.blowup());
-    let final_poly = folded[0];
-    for x in folded {
-        assert_eq!(x, final_poly);
+    // We should be left with `blowup * final_poly_len` evaluations of a polynomial with
+    // `final_poly_len` coefficients.
+    assert_eq!(folded.len(), config.blowup() * config.final_poly_len());
+    let mut final_poly = g.interpolate_final_poly(folded);
+    assert!(
+        final_poly[config.final_poly_len()..]
+            .iter()
+            .all(|c| c.is_zero()),
+        "final polynomial has too high a degree"
+    );
+    final_poly.truncate(config.final_poly_len());
+    for &coeff in &final_poly {
+        challenger.observe_ext_element(coeff);
     }
-    challenger.observe_ext_element(final_poly);
 
     CommitPhaseResult {
         commits,
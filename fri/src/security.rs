@@ -0,0 +1,128 @@
+use core::f64::consts::FRAC_1_SQRT_2;
+
+/// The assumption under which the soundness of FRI is measured, which determines how many bits of
+/// security each query contributes.
+///
+/// The stronger the assumption, the fewer queries (and so the smaller the proofs) for a given
+/// security level. See e.g. Section 3 of [A summary on the FRI low degree
+/// test](https://eprint.iacr.org/2022/1216) for an overview of the bounds.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SecurityAssumption {
+    /// Proven soundness within the unique decoding radius, `(1 - rate) / 2`. A query fails to
+    /// catch a far word with probability `(1 + rate) / 2`, so each contributes less than one bit.
+    UniqueDecoding,
+    /// Proven soundness up to the Johnson bound, `1 - sqrt(rate)`. Each query contributes about
+    /// `log_blowup / 2` bits, and the commit phase loses roughly twice as many bits to the size of
+    /// the domain as under the other assumptions.
+    JohnsonBound,
+    /// The [ethSTARK](https://eprint.iacr.org/2021/582) conjecture, that FRI is sound up to the
+    /// list decoding capacity. Each query contributes `log_blowup` bits.
+    CapacityBound,
+}
+
+impl SecurityAssumption {
+    /// The probability that a single query fails to catch a word which is far from the code.
+    fn query_error(self, log_blowup: usize) -> f64 {
+        let rate = 1.0 / (1u64 << log_blowup) as f64;
+        match self {
+            Self::UniqueDecoding => (1.0 + rate) / 2.0,
+            Self::JohnsonBound => {
+                // sqrt(2^-log_blowup), without needing `f64::sqrt` in `no_std`.
+                let sqrt_rate = 1.0 / (1u64 << (log_blowup / 2)) as f64;
+                if log_blowup % 2 == 1 {
+                    sqrt_rate * FRAC_1_SQRT_2
+                } else {
+                    sqrt_rate
+                }
+            }
+            Self::CapacityBound => rate,
+        }
+    }
+
+    /// The number of bits of security that `num_queries` queries give, rounded down.
+    pub fn query_soundness_bits(self, log_blowup: usize, num_queries: usize) -> usize {
+        let error = self.query_error(log_blowup);
+        // We track error^num_queries as `acc * 2^-bits` with `acc` in `(1/2, 1]`, so that it
+        // can't underflow, and `bits` is the rounded down bit count.
+        let mut acc = 1.0;
+        let mut bits = 0;
+        for _ in 0..num_queries {
+            acc *= error;
+            while acc <= 0.5 {
+                acc *= 2.0;
+                bits += 1;
+            }
+        }
+        bits
+    }
+
+    /// The smallest number of queries giving at least `bits` bits of security.
+    pub fn num_queries(self, log_blowup: usize, bits: usize) -> usize {
+        assert!(log_blowup > 0, "FRI without blowup has no soundness");
+        let mut num_queries = 0;
+        while self.query_soundness_bits(log_blowup, num_queries) < bits {
+            num_queries += 1;
+        }
+        num_queries
+    }
+
+    /// A rough bound on the bits of security of the commit phase, for a challenge field with
+    /// `challenge_bits` bits and a largest codeword of height `2^log_max_height`. Grinding and
+    /// queries can't make up for the commit phase, so this caps the overall soundness.
+    pub const fn commit_phase_soundness_bits(
+        self,
+        challenge_bits: usize,
+        log_max_height: usize,
+    ) -> usize {
+        match self {
+            Self::UniqueDecoding | Self::CapacityBound => {
+                challenge_bits.saturating_sub(log_max_height)
+            }
+            Self::JohnsonBound => challenge_bits.saturating_sub(2 * log_max_height),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_soundness_bits() {
+        // A query catches a far word with probability 1 - 2^-log_blowup.
+        assert_eq!(
+            SecurityAssumption::CapacityBound.query_soundness_bits(3, 10),
+            30
+        );
+        assert_eq!(
+            SecurityAssumption::JohnsonBound.query_soundness_bits(2, 10),
+            10
+        );
+        assert_eq!(
+            SecurityAssumption::JohnsonBound.query_soundness_bits(3, 11),
+            16
+        );
+        // log2(2 / (1 + 1/2)) ~= 0.415 bits per query.
+        assert_eq!(
+            SecurityAssumption::UniqueDecoding.query_soundness_bits(1, 100),
+            41
+        );
+    }
+
+    #[test]
+    fn num_queries() {
+        assert_eq!(SecurityAssumption::CapacityBound.num_queries(1, 100), 100);
+        assert_eq!(SecurityAssumption::CapacityBound.num_queries(3, 100), 34);
+        assert_eq!(SecurityAssumption::JohnsonBound.num_queries(3, 100), 67);
+        assert_eq!(SecurityAssumption::UniqueDecoding.num_queries(1, 100), 241);
+        for assumption in [
+            SecurityAssumption::UniqueDecoding,
+            SecurityAssumption::JohnsonBound,
+            SecurityAssumption::CapacityBound,
+        ] {
+            let num_queries = assumption.num_queries(2, 80);
+            assert!(assumption.query_soundness_bits(2, num_queries) >= 80);
+            assert!(assumption.query_soundness_bits(2, num_queries - 1) < 80);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Stable byte encodings for FRI proofs and the PCS data that accompanies them.
+//!
+//! Every encoding starts with a four byte magic identifying what was encoded, followed by
+//! [`FRI_FORMAT_VERSION`]. The payload uses the canonical encoding from
+//! `p3_util::canonical_serialization`: fixed-width little-endian integers, canonical field
+//! elements, `u32` length prefixes for vectors, and struct fields in declaration order.
+//!
+//! Any change to the layout of the types encoded here must bump [`FRI_FORMAT_VERSION`].
+
+use alloc::vec::Vec;
+
+use p3_commit::{Mmcs, OpenedValues};
+use p3_field::Field;
+use p3_util::canonical_serialization::{
+    from_bytes_with_header, to_bytes_with_header, SerializationError,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::FriProof;
+
+/// The version of the encodings produced by this module.
+pub const FRI_FORMAT_VERSION: u16 = 3;
+
+/// Magic prefix of an encoded [`FriProof`].
+pub const FRI_PROOF_MAGIC: [u8; 4] = *b"P3FP";
+
+/// Magic prefix of an encoded commitment.
+pub const COMMITMENT_MAGIC: [u8; 4] = *b"P3CM";
+
+/// Magic prefix of encoded [`OpenedValues`].
+pub const OPENED_VALUES_MAGIC: [u8; 4] = *b"P3OV";
+
+impl<F, M, Witness, InputProof> FriProof<F, M, Witness, InputProof>
+where
+    F: Field,
+    M: Mmcs<F>,
+    Self: Serialize + DeserializeOwned,
+{
+    /// Encode this proof in the canonical, versioned format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        to_bytes_with_header(FRI_PROOF_MAGIC, FRI_FORMAT_VERSION, self)
+    }
+
+    /// Decode a proof produced by [`FriProof::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        from_bytes_with_header(FRI_PROOF_MAGIC, FRI_FORMAT_VERSION, bytes)
+    }
+}
+
+/// Encode a commitment (e.g. a `Pcs::Commitment` or `Mmcs::Commitment`).
+pub fn commitment_to_bytes<C: Serialize>(commitment: &C) -> Result<Vec<u8>, SerializationError> {
+    to_bytes_with_header(COMMITMENT_MAGIC, FRI_FORMAT_VERSION, commitment)
+}
+
+/// Decode a commitment produced by [`commitment_to_bytes`].
+pub fn commitment_from_bytes<C: DeserializeOwned>(bytes: &[u8]) -> Result<C, SerializationError> {
+    from_bytes_with_header(COMMITMENT_MAGIC, FRI_FORMAT_VERSION, bytes)
+}
+
+/// Encode the values returned by `Pcs::open`.
+pub fn opened_values_to_bytes<F: Serialize>(
+    opened_values: &OpenedValues<F>,
+) -> Result<Vec<u8>, SerializationError> {
+    to_bytes_with_header(OPENED_VALUES_MAGIC, FRI_FORMAT_VERSION, opened_values)
+}
+
+/// Decode values produced by [`opened_values_to_bytes`].
+pub fn opened_values_from_bytes<F: DeserializeOwned>(
+    bytes: &[u8],
+) -> Result<OpenedValues<F>, SerializationError> {
+    from_bytes_with_header(OPENED_VALUES_MAGIC, FRI_FORMAT_VERSION, bytes)
+}
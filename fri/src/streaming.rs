@@ -0,0 +1,249 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
+use p3_dft::TwoAdicSubgroupDft;
+use p3_field::{ExtensionField, TwoAdicField};
+use p3_matrix::bitrev::{BitReversableMatrix, RowOrder};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::row_block::RowBlockMatrix;
+use p3_matrix::Matrix;
+use p3_util::{log2_strict_usize, reverse_bits_len};
+use tracing::instrument;
+
+use crate::verifier::FriError;
+use crate::{BatchOpening, FriConfig, FriProof, TwoAdicFriPcs};
+
+/// The bit-reversed low-degree extension of a batch of polynomials, computed on demand rather than
+/// stored.
+///
+/// Only the coefficients are kept in memory. Row `r` is the evaluation at
+/// `shift * g^reverse_bits(r)`, where `g` generates the subgroup whose size is the LDE height.
+/// The first `n` rows of such an LDE (where `n` is the number of coefficients) are the
+/// evaluations over a coset of the size `n` subgroup, as are the next `n` rows and so on, so each
+/// such block of rows can be computed with a single size `n` DFT, using the PCS's own `dft`.
+#[derive(Clone, Debug)]
+pub struct LazyCosetLde<Val, Dft> {
+    dft: Arc<Dft>,
+    coeffs: RowMajorMatrix<Val>,
+    shift: Val,
+    log_blowup: usize,
+}
+
+impl<Val, Dft> LazyCosetLde<Val, Dft>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val> + Send + Sync,
+{
+    /// `coeffs` holds one polynomial per column, with the coefficient of `X^i` in row `i`.
+    pub fn new(dft: Arc<Dft>, coeffs: RowMajorMatrix<Val>, shift: Val, log_blowup: usize) -> Self {
+        Self {
+            dft,
+            coeffs,
+            shift,
+            log_blowup,
+        }
+    }
+
+    /// Compute the given range of rows of the LDE.
+    #[instrument(name = "compute LDE rows", level = "debug", skip_all, fields(rows = ?rows))]
+    pub fn compute_rows(&self, rows: Range<usize>) -> RowMajorMatrix<Val> {
+        let n = self.coeffs.height();
+        let width = self.coeffs.width();
+        let g = Val::two_adic_generator(log2_strict_usize(self.height()));
+
+        let mut values = Vec::with_capacity(rows.len() * width);
+        // The DFT is done in place, so each block's coefficients are copied into this buffer,
+        // whose allocation is reused from one block to the next.
+        let mut buffer = Vec::with_capacity(n * width);
+        for block in rows.start / n..rows.end.div_ceil(n) {
+            // Rows `block * n..(block + 1) * n` are the evaluations over the coset
+            // `shift * g^reverse_bits(block) * <g^(2^log_blowup)>`, in bit-reversed order.
+            let block_shift =
+                self.shift * g.exp_u64(reverse_bits_len(block, self.log_blowup) as u64);
+            buffer.clear();
+            buffer.extend_from_slice(&self.coeffs.values);
+            let evals = self
+                .dft
+                .coset_dft_batch(RowMajorMatrix::new(buffer, width), block_shift)
+                .to_row_major_matrix_in_order(RowOrder::BitReversed);
+            let start = rows.start.max(block * n) - block * n;
+            let end = rows.end.min((block + 1) * n) - block * n;
+            values.extend_from_slice(&evals.values[start * width..end * width]);
+            buffer = evals.values;
+        }
+        RowMajorMatrix::new(values, width)
+    }
+}
+
+impl<Val: TwoAdicField, Dft: Send + Sync> Matrix<Val> for LazyCosetLde<Val, Dft> {
+    fn width(&self) -> usize {
+        self.coeffs.width()
+    }
+
+    fn height(&self) -> usize {
+        self.coeffs.height() << self.log_blowup
+    }
+
+    type Row<'a>
+        = alloc::vec::IntoIter<Val>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        // Evaluating a single row directly is much cheaper than computing its whole block.
+        let log_height = log2_strict_usize(self.height());
+        let x = self.shift
+            * Val::two_adic_generator(log_height).exp_u64(reverse_bits_len(r, log_height) as u64);
+        let x_powers: Vec<Val> = x.powers().take(self.coeffs.height()).collect();
+        self.coeffs.columnwise_dot_product(&x_powers).into_iter()
+    }
+}
+
+impl<Val, Dft> RowBlockMatrix<Val> for LazyCosetLde<Val, Dft>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val> + Send + Sync,
+{
+    fn block_height(&self) -> usize {
+        self.coeffs.height()
+    }
+
+    fn row_block(&self, rows: Range<usize>) -> impl Matrix<Val> + '_ {
+        self.compute_rows(rows)
+    }
+}
+
+/// A variant of `TwoAdicFriPcs` which never holds a full LDE in memory.
+///
+/// Committing computes the LDE one coset at a time and hashes it into the Merkle tree as it goes
+/// (see `Mmcs::commit_row_blocks`), keeping only the coefficients around. Opening then recomputes
+/// the LDE a coset at a time for a second pass. This trades roughly one extra LDE computation for
+/// a peak memory usage which is about `2^log_blowup` times smaller.
+///
+/// Commitments and proofs are identical to those of `TwoAdicFriPcs`, which can verify them.
+///
+/// The DFT is shared with every committed `LazyCosetLde`, so any configuration it has, as well as
+/// any twiddles it caches, is used for each block.
+#[derive(Debug)]
+pub struct StreamingTwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
+    inner: TwoAdicFriPcs<Val, Arc<Dft>, InputMmcs, FriMmcs>,
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs> StreamingTwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
+    pub fn new(dft: Dft, mmcs: InputMmcs, fri: FriConfig<FriMmcs>) -> Self {
+        Self {
+            inner: TwoAdicFriPcs::new(Arc::new(dft), mmcs, fri),
+        }
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
+    for StreamingTwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val> + Send + Sync,
+    InputMmcs: Mmcs<Val> + Sync,
+    InputMmcs::ProverData<RowMajorMatrix<Val>>: Sync,
+    InputMmcs::ProverData<LazyCosetLde<Val, Dft>>: Sync,
+    InputMmcs::Proof: Send,
+    FriMmcs: Mmcs<Challenge> + Sync,
+    FriMmcs::ProverData<RowMajorMatrix<Challenge>>: Sync,
+    FriMmcs::Proof: Send,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Challenger:
+        FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
+{
+    type Domain = TwoAdicMultiplicativeCoset<Val>;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = InputMmcs::ProverData<LazyCosetLde<Val, Dft>>;
+    type Proof = FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>;
+    type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        let log_n = log2_strict_usize(degree);
+        TwoAdicMultiplicativeCoset {
+            log_n,
+            shift: Val::ONE,
+        }
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let ldes = evaluations
+            .into_iter()
+            .map(|(domain, evals)| {
+                assert_eq!(domain.size(), evals.height());
+                self.inner
+                    .fri
+                    .check_pow_bits::<Val, Challenge>(domain.log_n + self.inner.fri.log_blowup);
+                let shift = Val::GENERATOR / domain.shift;
+                LazyCosetLde::new(
+                    self.inner.dft.clone(),
+                    self.inner.dft.idft_batch(evals),
+                    shift,
+                    self.inner.fri.log_blowup,
+                )
+            })
+            .collect();
+
+        self.inner.mmcs.commit_row_blocks(ldes)
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> impl Matrix<Val> + 'a {
+        assert_eq!(domain.shift, Val::GENERATOR);
+        let lde = self.inner.mmcs.get_matrices(prover_data)[idx];
+        assert!(lde.height() >= domain.size());
+        lde.compute_rows(0..domain.size()).bit_reverse_rows()
+    }
+
+    fn max_log_evaluation_blowup(&self) -> Option<usize> {
+        Some(self.inner.fri.log_blowup)
+    }
+
+    fn get_committed_evaluations<'a>(
+        &self,
+        _prover_data: &'a Self::ProverData,
+        _idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val> + 'a)> {
+        // The whole point is that we don't keep the LDEs around.
+        None::<(Self::Domain, RowMajorMatrix<Val>)>
+    }
+
+    fn get_committed_evaluations_bit_reversed<'a>(
+        &self,
+        _prover_data: &'a Self::ProverData,
+        _idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val> + 'a)> {
+        None::<(Self::Domain, RowMajorMatrix<Val>)>
+    }
+
+    fn open(
+        &self,
+        rounds: Vec<(&Self::ProverData, Vec<Vec<Challenge>>)>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        self.inner.open_row_blocks(rounds, challenger)
+    }
+
+    fn verify(
+        &self,
+        rounds: Vec<(
+            Self::Commitment,
+            Vec<(Self::Domain, Vec<(Challenge, Vec<Challenge>)>)>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        self.inner.verify_rounds(rounds, proof, challenger)
+    }
+}
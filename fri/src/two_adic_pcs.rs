@@ -7,14 +7,15 @@ use core::marker::PhantomData;
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
-use p3_dft::TwoAdicSubgroupDft;
+use p3_dft::{divide_by_height, Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::{
     batch_multiplicative_inverse, cyclic_subgroup_coset_known_order, dot_product, ExtensionField,
     Field, TwoAdicField,
 };
-use p3_interpolation::interpolate_coset;
+use p3_interpolation::interpolate_coset_batch;
 use p3_matrix::bitrev::{BitReversableMatrix, BitReversalPerm};
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::row_block::RowBlockMatrix;
 use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
 use p3_util::linear_map::LinearMap;
@@ -23,13 +24,17 @@ use serde::{Deserialize, Serialize};
 use tracing::{info_span, instrument};
 
 use crate::verifier::{self, FriError};
-use crate::{prover, FriConfig, FriGenericConfig, FriProof};
+use crate::{prover, FriConfig, FriGenericConfig, FriProof, LdeAndCommitBackend};
 
+/// A polynomial commitment scheme using FRI over two-adic subgroups.
+///
+/// `Dft` computes the LDEs of committed polynomials; it may be any `TwoAdicSubgroupDft`, or any
+/// other `LdeAndCommitBackend`.
 #[derive(Debug)]
 pub struct TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
-    dft: Dft,
-    mmcs: InputMmcs,
-    fri: FriConfig<FriMmcs>,
+    pub(crate) dft: Dft,
+    pub(crate) mmcs: InputMmcs,
+    pub(crate) fri: FriConfig<FriMmcs>,
     _phantom: PhantomData<Val>,
 }
 
@@ -127,15 +132,38 @@ impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
             })
             .collect()
     }
+
+    fn interpolate_final_poly(&self, evals: Vec<F>) -> Vec<F> {
+        // The codewords are evaluations over a subgroup, in bit-reversed order, which a DIT network
+        // takes as is. A forward DFT gives the coefficients scaled by the height, with that of
+        // x^j at row -j.
+        let mut coeffs = Radix2Dit::default().dft_bitrev_in(RowMajorMatrix::new_col(evals));
+        divide_by_height(&mut coeffs);
+        coeffs.values[1..].reverse();
+        coeffs.values
+    }
+
+    fn eval_final_poly(&self, index: usize, log_height: usize, coeffs: &[F]) -> F {
+        let x =
+            F::two_adic_generator(log_height).exp_u64(reverse_bits_len(index, log_height) as u64);
+        coeffs
+            .iter()
+            .rev()
+            .fold(F::ZERO, |acc, &coeff| acc * x + coeff)
+    }
 }
 
 impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
     for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
 where
     Val: TwoAdicField,
-    Dft: TwoAdicSubgroupDft<Val>,
-    InputMmcs: Mmcs<Val>,
-    FriMmcs: Mmcs<Challenge>,
+    Dft: LdeAndCommitBackend<Val, InputMmcs>,
+    InputMmcs: Mmcs<Val> + Sync,
+    InputMmcs::ProverData<RowMajorMatrix<Val>>: Sync,
+    InputMmcs::Proof: Send,
+    FriMmcs: Mmcs<Challenge> + Sync,
+    FriMmcs::ProverData<RowMajorMatrix<Challenge>>: Sync,
+    FriMmcs::Proof: Send,
     Challenge: TwoAdicField + ExtensionField<Val>,
     Challenger:
         FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
@@ -158,20 +186,13 @@ where
         &self,
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
     ) -> (Self::Commitment, Self::ProverData) {
-        let ldes: Vec<_> = evaluations
-            .into_iter()
-            .map(|(domain, evals)| {
-                assert_eq!(domain.size(), evals.height());
-                let shift = Val::GENERATOR / domain.shift;
-                // Commit to the bit-reversed LDE.
-                self.dft
-                    .coset_lde_batch(evals, self.fri.log_blowup, shift)
-                    .bit_reverse_rows()
-                    .to_row_major_matrix()
-            })
-            .collect();
-
-        self.mmcs.commit(ldes)
+        // The LDEs are FRI inputs, so we check FRI can handle them before computing them.
+        for (domain, _) in &evaluations {
+            self.fri
+                .check_pow_bits::<Val, Challenge>(domain.log_n + self.fri.log_blowup);
+        }
+        self.dft
+            .lde_and_commit(&self.mmcs, self.fri.log_blowup, evaluations)
     }
 
     fn get_evaluations_on_domain<'a>(
@@ -187,6 +208,28 @@ where
         lde.split_rows(domain.size()).0.bit_reverse_rows()
     }
 
+    fn max_log_evaluation_blowup(&self) -> Option<usize> {
+        Some(self.fri.log_blowup)
+    }
+
+    fn get_committed_evaluations<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val> + 'a)> {
+        let lde = self.mmcs.get_matrices(prover_data)[idx];
+        Some((lde_domain(lde), lde.as_view().bit_reverse_rows()))
+    }
+
+    fn get_committed_evaluations_bit_reversed<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+    ) -> Option<(Self::Domain, impl Matrix<Val> + 'a)> {
+        let lde = self.mmcs.get_matrices(prover_data)[idx];
+        Some((lde_domain(lde), lde.as_view()))
+    }
+
     fn open(
         &self,
         // For each round,
@@ -200,6 +243,61 @@ where
         )>,
         challenger: &mut Challenger,
     ) -> (OpenedValues<Challenge>, Self::Proof) {
+        self.open_row_blocks(rounds, challenger)
+    }
+
+    fn verify(
+        &self,
+        // For each round:
+        rounds: Vec<(
+            Self::Commitment,
+            // for each matrix:
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // for each point:
+                Vec<(
+                    // the point,
+                    Challenge,
+                    // values at the point
+                    Vec<Challenge>,
+                )>,
+            )>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        self.verify_rounds(rounds, proof, challenger)
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+where
+    Val: TwoAdicField,
+    InputMmcs: Mmcs<Val> + Sync,
+    InputMmcs::Proof: Send,
+{
+    /// The implementation of `Pcs::open`, generic over the committed matrix type.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn open_row_blocks<M, Challenge, Challenger>(
+        &self,
+        rounds: Vec<(&InputMmcs::ProverData<M>, Vec<Vec<Challenge>>)>,
+        challenger: &mut Challenger,
+    ) -> (
+        OpenedValues<Challenge>,
+        FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>,
+    )
+    where
+        M: RowBlockMatrix<Val>,
+        InputMmcs::ProverData<M>: Sync,
+        FriMmcs: Mmcs<Challenge> + Sync,
+        FriMmcs::ProverData<RowMajorMatrix<Challenge>>: Sync,
+        FriMmcs::Proof: Send,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+    {
         /*
 
         A quick rundown of the optimizations in this function:
@@ -235,6 +333,14 @@ where
 
             with alpha^i an extension, p_i[X] a base
 
+        - Matrices are accessed a block of rows at a time (see `RowBlockMatrix`), so that matrices
+        which are computed on the fly never need to be held in memory in their entirety.
+
+        - A matrix may be opened at any number of points. Since the hot loop doesn't depend on z,
+        we compute it once per row, and accumulate the quotients for all of the matrix's points
+        in a single pass:
+                reduced[X] += sum_z [ alpha_offset_z * inv_denom_z[X] * [ reduced_row[X] - reduced_ys_z ] ]
+
         */
 
         // Batch combination challenge
@@ -242,16 +348,7 @@ where
 
         let mats_and_points = rounds
             .iter()
-            .map(|(data, points)| {
-                (
-                    self.mmcs
-                        .get_matrices(data)
-                        .into_iter()
-                        .map(|m| m.as_view())
-                        .collect_vec(),
-                    points,
-                )
-            })
+            .map(|(data, points)| (self.mmcs.get_matrices(data), points))
             .collect_vec();
         let mats = mats_and_points
             .iter()
@@ -279,39 +376,65 @@ where
                 debug_assert_eq!(reduced_opening_for_log_height.len(), mat.height());
 
                 let opened_values_for_mat = opened_values_for_round.pushed_mut(vec![]);
-                for &point in points_for_mat {
-                    let _guard =
-                        info_span!("reduce matrix quotient", dims = %mat.dimensions()).entered();
-
-                    // Use Barycentric interpolation to evaluate the matrix at the given point.
-                    let ys = info_span!("compute opened values with Lagrange interpolation")
-                        .in_scope(|| {
-                            let (low_coset, _) =
-                                mat.split_rows(mat.height() >> self.fri.log_blowup);
-                            interpolate_coset(
-                                &BitReversalPerm::new_view(low_coset),
-                                Val::GENERATOR,
-                                point,
-                            )
-                        });
-
-                    let alpha_pow_offset = alpha.exp_u64(num_reduced[log_height] as u64);
-                    let reduced_ys: Challenge = dot_product(alpha.powers(), ys.iter().copied());
-
-                    info_span!("reduce rows").in_scope(|| {
-                        mat.dot_ext_powers(alpha)
-                            .zip(reduced_opening_for_log_height.par_iter_mut())
-                            // This might be longer, but zip will truncate to smaller subgroup
-                            // (which is ok because it's bitrev)
-                            .zip(inv_denoms.get(&point).unwrap().par_iter())
-                            .for_each(|((reduced_row, ro), &inv_denom)| {
-                                *ro += alpha_pow_offset * (reduced_row - reduced_ys) * inv_denom
-                            })
+                if points_for_mat.is_empty() {
+                    continue;
+                }
+
+                let _guard = info_span!(
+                    "reduce matrix quotient",
+                    dims = %mat.dimensions(),
+                    num_points = points_for_mat.len()
+                )
+                .entered();
+
+                let low_coset = mat.row_block(0..mat.height() >> self.fri.log_blowup);
+                let low_coset = BitReversalPerm::new_view(low_coset);
+
+                // Use Barycentric interpolation to evaluate the matrix at all of its points.
+                let ys_for_points = info_span!("compute opened values with Lagrange interpolation")
+                    .in_scope(|| {
+                        interpolate_coset_batch(&low_coset, Val::GENERATOR, points_for_mat)
                     });
 
-                    num_reduced[log_height] += mat.width();
-                    opened_values_for_mat.push(ys);
-                }
+                // For each point, the opened values, the alpha offset and the reduced opened
+                // values, along with the precomputed 1/(X - z).
+                let point_terms = izip!(points_for_mat, ys_for_points)
+                    .map(|(point, ys)| {
+                        let alpha_pow_offset = alpha.exp_u64(num_reduced[log_height] as u64);
+                        let reduced_ys: Challenge = dot_product(alpha.powers(), ys.iter().copied());
+                        num_reduced[log_height] += mat.width();
+
+                        opened_values_for_mat.push(ys);
+                        (alpha_pow_offset, reduced_ys, inv_denoms.get(point).unwrap())
+                    })
+                    .collect_vec();
+
+                // sum_i [ alpha^i * p_i[X] ] does not depend on the opening point, so we compute
+                // it once per row and accumulate the quotients for every point in the same pass.
+                info_span!("reduce rows").in_scope(|| {
+                    let block_height = mat.block_height().max(1);
+                    for (block_start, ro_block) in (0..)
+                        .step_by(block_height)
+                        .zip(reduced_opening_for_log_height.chunks_mut(block_height))
+                    {
+                        let block = mat.row_block(block_start..block_start + ro_block.len());
+                        block
+                            .dot_ext_powers(alpha)
+                            .zip(ro_block.par_iter_mut())
+                            .enumerate()
+                            .for_each(|(i, (reduced_row, ro))| {
+                                for &(alpha_pow_offset, reduced_ys, inv_denoms_for_point) in
+                                    &point_terms
+                                {
+                                    // inv_denoms may be longer, but we only need a prefix of it
+                                    // (which is ok because it's bitrev)
+                                    *ro += alpha_pow_offset
+                                        * (reduced_row - reduced_ys)
+                                        * inv_denoms_for_point[block_start + i];
+                                }
+                            })
+                    }
+                });
             }
         }
 
@@ -320,14 +443,16 @@ where
         let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
             TwoAdicFriGenericConfig(PhantomData);
 
+        // Queries are answered in parallel, so only borrow what we need here.
+        let mmcs = &self.mmcs;
         let fri_proof = prover::prove(&g, &self.fri, fri_input, challenger, |index| {
             rounds
                 .iter()
                 .map(|(data, _)| {
-                    let log_max_height = log2_strict_usize(self.mmcs.get_max_height(data));
+                    let log_max_height = log2_strict_usize(mmcs.get_max_height(data));
                     let bits_reduced = log_global_max_height - log_max_height;
                     let reduced_index = index >> bits_reduced;
-                    let (opened_values, opening_proof) = self.mmcs.open_batch(reduced_index, data);
+                    let (opened_values, opening_proof) = mmcs.open_batch(reduced_index, data);
                     BatchOpening {
                         opened_values,
                         opening_proof,
@@ -339,31 +464,32 @@ where
         (all_opened_values, fri_proof)
     }
 
-    fn verify(
+    /// The implementation of `Pcs::verify`, which doesn't depend on the DFT.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn verify_rounds<Challenge, Challenger>(
         &self,
-        // For each round:
         rounds: Vec<(
-            Self::Commitment,
-            // for each matrix:
+            InputMmcs::Commitment,
             Vec<(
-                // its domain,
-                Self::Domain,
-                // for each point:
-                Vec<(
-                    // the point,
-                    Challenge,
-                    // values at the point
-                    Vec<Challenge>,
-                )>,
+                TwoAdicMultiplicativeCoset<Val>,
+                Vec<(Challenge, Vec<Challenge>)>,
             )>,
         )>,
-        proof: &Self::Proof,
+        proof: &FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>,
         challenger: &mut Challenger,
-    ) -> Result<(), Self::Error> {
+    ) -> Result<(), FriError<FriMmcs::Error, InputMmcs::Error>>
+    where
+        FriMmcs: Mmcs<Challenge>,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+    {
         // Batch combination challenge
         let alpha: Challenge = challenger.sample_ext_element();
 
-        let log_global_max_height = proof.commit_phase_commits.len() + self.fri.log_blowup;
+        let log_global_max_height =
+            proof.commit_phase_commits.len() + self.fri.log_blowup + self.fri.log_final_poly_len;
 
         let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
             TwoAdicFriGenericConfig(PhantomData);
@@ -431,15 +557,20 @@ where
                 .map(|(log_height, (_alpha_pow, ro))| (log_height, ro))
                 .collect())
         })
-        .expect("fri err");
+    }
+}
 
-        Ok(())
+/// The domain of a committed LDE.
+fn lde_domain<Val: TwoAdicField>(lde: &RowMajorMatrix<Val>) -> TwoAdicMultiplicativeCoset<Val> {
+    TwoAdicMultiplicativeCoset {
+        log_n: log2_strict_usize(lde.height()),
+        shift: Val::GENERATOR,
     }
 }
 
 #[instrument(skip_all)]
 fn compute_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>, M: Matrix<F>>(
-    mats_and_points: &[(Vec<M>, &Vec<Vec<EF>>)],
+    mats_and_points: &[(Vec<&M>, &Vec<Vec<EF>>)],
     coset_shift: F,
 ) -> LinearMap<EF, Vec<EF>> {
     let mut max_log_height_for_point: LinearMap<EF, usize> = LinearMap::new();
@@ -1,5 +1,8 @@
+use alloc::collections::btree_map::Entry;
+use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::slice;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
@@ -7,7 +10,7 @@ use p3_commit::Mmcs;
 use p3_field::{ExtensionField, Field};
 use p3_matrix::Dimensions;
 
-use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof};
+use crate::{FriConfig, FriGenericConfig, FriProof};
 
 #[derive(Debug)]
 pub enum FriError<CommitMmcsErr, InputError> {
@@ -40,107 +43,153 @@ where
             challenger.sample_ext_element()
         })
         .collect();
-    challenger.observe_ext_element(proof.final_poly);
-
-    if proof.query_proofs.len() != config.num_queries {
+    if proof.query_proofs.len() != config.num_queries
+        || proof.final_poly.len() != config.final_poly_len()
+        || proof
+            .query_proofs
+            .iter()
+            .any(|qp| qp.commit_phase_openings.len() != proof.commit_phase_commits.len())
+    {
         return Err(FriError::InvalidProofShape);
     }
+    for &coeff in &proof.final_poly {
+        challenger.observe_ext_element(coeff);
+    }
+
+    let log_final_height = config.log_blowup + config.log_final_poly_len;
+    let log_max_height = proof.commit_phase_commits.len() + log_final_height;
 
     // Check PoW.
-    if !challenger.check_witness(config.proof_of_work_bits, proof.pow_witness) {
+    let pow_bits = config
+        .bind_pow_bits::<Val, Challenge, _>(log_max_height, challenger)
+        .ok_or(FriError::InvalidProofShape)?;
+    if !challenger.check_witness(pow_bits, proof.pow_witness) {
         return Err(FriError::InvalidPowWitness);
     }
 
-    let log_max_height = proof.commit_phase_commits.len() + config.log_blowup;
-
-    for qp in &proof.query_proofs {
-        let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
-        let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
-
-        debug_assert!(
-            ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
-            "reduced openings sorted by height descending"
-        );
-
-        let folded_eval = verify_query(
-            g,
-            config,
-            index >> g.extra_query_index_bits(),
-            izip!(
-                &betas,
-                &proof.commit_phase_commits,
-                &qp.commit_phase_openings
-            ),
-            ro,
-            log_max_height,
-        )?;
-
-        if folded_eval != proof.final_poly {
-            return Err(FriError::FinalPolyMismatch);
+    // Sample every query and open its inputs, then fold the queries together a layer at a time,
+    // so that the openings of each commit phase commitment are verified at once.
+    let mut queries = proof
+        .query_proofs
+        .iter()
+        .map(|qp| {
+            let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
+            let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
+
+            debug_assert!(
+                ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
+                "reduced openings sorted by height descending"
+            );
+
+            Ok((
+                index >> g.extra_query_index_bits(),
+                Challenge::ZERO,
+                ro.into_iter().peekable(),
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (layer, (log_folded_height, &beta, comm)) in izip!(
+        (log_final_height..log_max_height).rev(),
+        &betas,
+        &proof.commit_phase_commits
+    )
+    .enumerate()
+    {
+        for (_, folded_eval, ro_iter) in &mut queries {
+            if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
+                *folded_eval += ro;
+            }
         }
-    }
 
-    Ok(())
-}
+        let openings = izip!(&queries, &proof.query_proofs)
+            .map(|(&(index, folded_eval, _), qp)| {
+                let opening = &qp.commit_phase_openings[layer];
+                (
+                    index,
+                    folded_eval,
+                    opening.sibling_value,
+                    &opening.opening_proof,
+                )
+            })
+            .collect_vec();
+        let rows = verify_commit_phase_openings(&config.mmcs, comm, log_folded_height, &openings)
+            .map_err(FriError::CommitPhaseMmcsError)?;
 
-type CommitStep<'a, F, M> = (
-    &'a F,
-    &'a <M as Mmcs<F>>::Commitment,
-    &'a CommitPhaseProofStep<F, M>,
-);
+        for ((index, folded_eval, _), row) in izip!(&mut queries, rows) {
+            *index >>= 1;
+            *folded_eval = g.fold_row(*index, log_folded_height, beta, row.into_iter());
+        }
+    }
 
-fn verify_query<'a, G, F, M>(
-    g: &G,
-    config: &FriConfig<M>,
-    mut index: usize,
-    steps: impl Iterator<Item = CommitStep<'a, F, M>>,
-    reduced_openings: Vec<(usize, F)>,
-    log_max_height: usize,
-) -> Result<F, FriError<M::Error, G::InputError>>
-where
-    F: Field,
-    M: Mmcs<F> + 'a,
-    G: FriGenericConfig<F>,
-{
-    let mut folded_eval = F::ZERO;
-    let mut ro_iter = reduced_openings.into_iter().peekable();
+    // Queries often end up at the same index of the final codeword, so each point is only
+    // evaluated once.
+    let mut final_evals = BTreeMap::new();
+    for (index, mut folded_eval, mut ro_iter) in queries {
+        debug_assert!(index < 1 << log_final_height, "index was {}", index);
 
-    for (log_folded_height, (&beta, comm, opening)) in izip!((0..log_max_height).rev(), steps) {
-        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
+        // Inputs as small as the final codeword are added to it without folding.
+        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_final_height) {
             folded_eval += ro;
         }
+        // Anything smaller can't be checked against the final polynomial.
+        if ro_iter.next().is_some() {
+            return Err(FriError::InvalidProofShape);
+        }
 
-        let index_sibling = index ^ 1;
-        let index_pair = index >> 1;
-
-        let mut evals = vec![folded_eval; 2];
-        evals[index_sibling % 2] = opening.sibling_value;
-
-        let dims = &[Dimensions {
-            width: 2,
-            height: 1 << log_folded_height,
-        }];
-        config
-            .mmcs
-            .verify_batch(
-                comm,
-                dims,
-                index_pair,
-                &[evals.clone()],
-                &opening.opening_proof,
-            )
-            .map_err(FriError::CommitPhaseMmcsError)?;
-
-        index = index_pair;
-
-        folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
+        let final_eval = *final_evals
+            .entry(index)
+            .or_insert_with(|| g.eval_final_poly(index, log_final_height, &proof.final_poly));
+        if folded_eval != final_eval {
+            return Err(FriError::FinalPolyMismatch);
+        }
     }
 
-    debug_assert!(index < config.blowup(), "index was {}", index);
-    debug_assert!(
-        ro_iter.next().is_none(),
-        "verifier reduced_openings were not in descending order?"
-    );
+    Ok(())
+}
 
-    Ok(folded_eval)
+/// Verify the openings of one commit phase commitment, at a layer of height
+/// `2^log_folded_height`, given for each query as `(index, folded_eval, sibling_value, proof)`.
+/// Returns the row each query opened: its folded evaluation and the sibling value, in the order of
+/// their indices.
+///
+/// Queries often open the same pair, more so the smaller the layer, and an opening which repeats
+/// one that's already verified authenticates nothing new, so only the distinct openings are
+/// verified. They're verified together, which lets the MMCS share work between them.
+#[allow(clippy::type_complexity)]
+pub fn verify_commit_phase_openings<F: Field, M: Mmcs<F>>(
+    mmcs: &M,
+    comm: &M::Commitment,
+    log_folded_height: usize,
+    openings: &[(usize, F, F, &M::Proof)],
+) -> Result<Vec<Vec<F>>, M::Error> {
+    let rows = openings
+        .iter()
+        .map(|&(index, folded_eval, sibling_value, _)| {
+            let mut row = vec![folded_eval; 2];
+            row[(index ^ 1) % 2] = sibling_value;
+            row
+        })
+        .collect_vec();
+
+    let mut verified = BTreeMap::new();
+    let distinct_openings = izip!(openings, &rows)
+        .filter(|&(&(index, ..), row)| match verified.entry(index >> 1) {
+            Entry::Vacant(entry) => {
+                entry.insert(row);
+                true
+            }
+            // A different row at the same index can't be valid as well, but it's verified so that
+            // the MMCS reports the error.
+            Entry::Occupied(entry) => *entry.get() != row,
+        })
+        .map(|(&(index, _, _, proof), row)| (index >> 1, slice::from_ref(row), proof))
+        .collect_vec();
+
+    let dims = &[Dimensions {
+        width: 2,
+        height: 1 << log_folded_height,
+    }];
+    mmcs.verify_batches(comm, dims, &distinct_openings)?;
+    Ok(rows)
 }
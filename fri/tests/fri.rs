@@ -3,11 +3,12 @@ use std::marker::PhantomData;
 
 use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
 use p3_challenger::{CanSampleBits, DuplexChallenger, FieldChallenger};
-use p3_commit::ExtensionMmcs;
+use p3_commit::{ExtensionMmcs, Mmcs};
 use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{AbstractField, Field};
-use p3_fri::{prover, verifier, FriConfig, TwoAdicFriGenericConfig};
+use p3_fri::verifier::FriError;
+use p3_fri::{prover, verifier, FriConfig, SecurityAssumption, TwoAdicFriGenericConfig};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::util::reverse_matrix_index_bits;
 use p3_matrix::Matrix;
@@ -30,7 +31,11 @@ type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
 type MyFriConfig = FriConfig<ChallengeMmcs>;
 
-fn get_ldt_for_testing<R: Rng>(rng: &mut R) -> (Perm, MyFriConfig) {
+fn get_ldt_for_testing<R: Rng>(
+    rng: &mut R,
+    log_final_poly_len: usize,
+    target_soundness_bits: Option<usize>,
+) -> (Perm, MyFriConfig) {
     let perm = Perm::new_from_rng_128(
         Poseidon2ExternalMatrixGeneral,
         DiffusionMatrixBabyBear::default(),
@@ -41,15 +46,24 @@ fn get_ldt_for_testing<R: Rng>(rng: &mut R) -> (Perm, MyFriConfig) {
     let mmcs = ChallengeMmcs::new(ValMmcs::new(hash, compress));
     let fri_config = FriConfig {
         log_blowup: 1,
+        log_final_poly_len,
         num_queries: 10,
         proof_of_work_bits: 8,
+        target_soundness_bits,
+        security_assumption: SecurityAssumption::CapacityBound,
         mmcs,
     };
     (perm, fri_config)
 }
 
-fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
-    let (perm, fc) = get_ldt_for_testing(rng);
+/// Prove with `prover_target` as the target soundness, and verify with `verifier_target`.
+fn do_test_fri_ldt<R: Rng>(
+    rng: &mut R,
+    log_final_poly_len: usize,
+    prover_target: Option<usize>,
+    verifier_target: Option<usize>,
+) -> Result<(), FriError<<ChallengeMmcs as Mmcs<Challenge>>::Error, ()>> {
+    let (perm, fc) = get_ldt_for_testing(rng, log_final_poly_len, prover_target);
     let dft = Radix2Dit::default();
 
     let shift = Val::GENERATOR;
@@ -113,6 +127,10 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
         (proof, chal.sample_bits(8))
     };
 
+    let fc = FriConfig {
+        target_soundness_bits: verifier_target,
+        ..fc
+    };
     let mut v_challenger = Challenger::new(perm);
     let _alpha: Challenge = v_challenger.sample_ext_element();
     verifier::verify(
@@ -121,14 +139,14 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
         &proof,
         &mut v_challenger,
         |_index, proof| Ok(proof.clone()),
-    )
-    .unwrap();
+    )?;
 
     assert_eq!(
         p_sample,
         v_challenger.sample_bits(8),
         "prover and verifier transcript have same state after FRI"
     );
+    Ok(())
 }
 
 #[test]
@@ -136,6 +154,102 @@ fn test_fri_ldt() {
     // FRI is kind of flaky depending on indexing luck
     for i in 0..4 {
         let mut rng = ChaCha20Rng::seed_from_u64(i);
-        do_test_fri_ldt(&mut rng);
+        do_test_fri_ldt(&mut rng, 0, None, None).unwrap();
+    }
+}
+
+#[test]
+fn test_fri_ldt_final_poly() {
+    // The smallest input has height 2^4, which is the final height with `log_final_poly_len = 3`.
+    for log_final_poly_len in 1..=3 {
+        let mut rng = ChaCha20Rng::seed_from_u64(log_final_poly_len as u64);
+        do_test_fri_ldt(&mut rng, log_final_poly_len, None, None).unwrap();
+    }
+}
+
+#[test]
+fn test_fri_ldt_adaptive_pow() {
+    for i in 0..4 {
+        let mut rng = ChaCha20Rng::seed_from_u64(i);
+        do_test_fri_ldt(&mut rng, 0, Some(22), Some(22)).unwrap();
     }
 }
+
+#[test]
+fn test_fri_ldt_mismatched_target() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    assert!(do_test_fri_ldt(&mut rng, 0, Some(22), Some(23)).is_err());
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    assert!(do_test_fri_ldt(&mut rng, 0, Some(22), None).is_err());
+}
+
+#[test]
+fn test_fri_ldt_for_security_level() {
+    let (_, fc) = get_ldt_for_testing(&mut ChaCha20Rng::seed_from_u64(0), 0, None);
+    let fc =
+        FriConfig::for_security_level(2, 0, SecurityAssumption::JohnsonBound, 100, 16, fc.mmcs);
+    // 84 bits from queries, at one bit each.
+    assert_eq!(fc.num_queries, 84);
+    assert_eq!(fc.soundness_bits(), 100);
+    assert_eq!(fc.pow_bits::<Val, Challenge>(10), Some(16));
+    // The Johnson bound loses more to the commit phase.
+    assert_eq!(fc.pow_bits::<Val, Challenge>(13), None);
+}
+
+#[test]
+fn test_pow_bits() {
+    let (_, fc) = get_ldt_for_testing(&mut ChaCha20Rng::seed_from_u64(0), 0, None);
+    // Fixed bits, regardless of height.
+    assert_eq!(fc.pow_bits::<Val, Challenge>(10), Some(8));
+
+    // 1 bit per query, so 10 bits from queries.
+    let fc = FriConfig {
+        target_soundness_bits: Some(30),
+        ..fc
+    };
+    assert_eq!(fc.pow_bits::<Val, Challenge>(10), Some(20));
+    // Never less than `proof_of_work_bits`.
+    let fc = FriConfig {
+        target_soundness_bits: Some(12),
+        ..fc
+    };
+    assert_eq!(fc.pow_bits::<Val, Challenge>(10), Some(8));
+    // A ~124 bit challenge field can't give 120 bits of soundness for large degrees.
+    let fc = FriConfig {
+        target_soundness_bits: Some(120),
+        num_queries: 100,
+        ..fc
+    };
+    assert_eq!(fc.pow_bits::<Val, Challenge>(3), Some(20));
+    assert_eq!(fc.pow_bits::<Val, Challenge>(10), None);
+}
+
+#[test]
+fn test_pow_bits_capped() {
+    let (_, fc) = get_ldt_for_testing(&mut ChaCha20Rng::seed_from_u64(0), 0, Some(36));
+    // A 31 bit field has about 2^31 witnesses, so grinding for more than 26 bits might not find
+    // one.
+    assert_eq!(MyFriConfig::max_derived_pow_bits::<Val>(), 26);
+    assert_eq!(fc.pow_bits::<Val, Challenge>(10), Some(26));
+    let fc = FriConfig {
+        target_soundness_bits: Some(37),
+        ..fc
+    };
+    assert_eq!(fc.pow_bits::<Val, Challenge>(10), None);
+    // Fixed bits are up to the user.
+    let fc = FriConfig {
+        target_soundness_bits: None,
+        proof_of_work_bits: 40,
+        ..fc
+    };
+    assert_eq!(fc.pow_bits::<Val, Challenge>(10), Some(40));
+}
+
+#[test]
+#[should_panic(expected = "can't reach its target soundness")]
+fn test_fri_ldt_unreachable_target() {
+    // The inputs have height up to 2^10, which leaves the commit phase with about 114 bits, and
+    // the prover fails before its commit phase.
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let _ = do_test_fri_ldt(&mut rng, 0, Some(120), Some(120));
+}
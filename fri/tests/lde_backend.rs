@@ -0,0 +1,134 @@
+use itertools::Itertools;
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
+use p3_commit::{ExtensionMmcs, Mmcs, Pcs, TwoAdicMultiplicativeCoset};
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field, TwoAdicField};
+use p3_fri::{FriConfig, LdeAndCommitBackend, SecurityAssumption, TwoAdicFriPcs};
+use p3_interpolation::interpolate_coset;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_util::reverse_bits_len;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+
+/// A stand-in for an external backend, which computes each LDE point by point with Lagrange
+/// interpolation instead of with a DFT.
+#[derive(Clone, Debug, Default)]
+struct NaiveBackend;
+
+impl<InputMmcs: Mmcs<Val>> LdeAndCommitBackend<Val, InputMmcs> for NaiveBackend {
+    fn lde_and_commit(
+        &self,
+        mmcs: &InputMmcs,
+        log_blowup: usize,
+        evaluations: Vec<(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)>,
+    ) -> (
+        InputMmcs::Commitment,
+        InputMmcs::ProverData<RowMajorMatrix<Val>>,
+    ) {
+        let ldes = evaluations
+            .into_iter()
+            .map(|(domain, evals)| {
+                let log_lde_height = domain.log_n + log_blowup;
+                let g = Val::two_adic_generator(log_lde_height);
+                let values = (0..1 << log_lde_height)
+                    .flat_map(|i| {
+                        let x =
+                            Val::GENERATOR * g.exp_u64(reverse_bits_len(i, log_lde_height) as u64);
+                        interpolate_coset(&evals, domain.shift, x)
+                    })
+                    .collect();
+                RowMajorMatrix::new(values, evals.width())
+            })
+            .collect();
+        mmcs.commit(ldes)
+    }
+}
+
+fn get_val_mmcs_and_challenger() -> (ValMmcs, Challenger) {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut rng,
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    (ValMmcs::new(hash, compress), Challenger::new(perm))
+}
+
+#[test]
+fn test_custom_backend_matches_dft() {
+    let (val_mmcs, challenger) = get_val_mmcs_and_challenger();
+    let fri_config = || FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 10,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+    };
+    let pcs = TwoAdicFriPcs::<Val, _, _, _>::new(
+        Radix2DitParallel::default(),
+        val_mmcs.clone(),
+        fri_config(),
+    );
+    let naive_pcs =
+        TwoAdicFriPcs::<Val, _, _, _>::new(NaiveBackend, val_mmcs.clone(), fri_config());
+
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+    let domains_and_polys = [5, 3]
+        .into_iter()
+        .map(|log_n| {
+            (
+                TwoAdicMultiplicativeCoset {
+                    log_n,
+                    shift: Val::ONE,
+                },
+                RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 3),
+            )
+        })
+        .collect_vec();
+
+    let (commit, _) = Pcs::<Challenge, Challenger>::commit(&pcs, domains_and_polys.clone());
+    let (naive_commit, naive_data) =
+        Pcs::<Challenge, Challenger>::commit(&naive_pcs, domains_and_polys.clone());
+    assert_eq!(commit, naive_commit);
+
+    // Everything past the commitment is unaffected by the backend.
+    let mut p_challenger = challenger.clone();
+    p_challenger.observe(naive_commit);
+    let zeta: Challenge = p_challenger.sample_ext_element();
+    let (opened_values, proof) = naive_pcs.open(
+        vec![(&naive_data, vec![vec![zeta]; domains_and_polys.len()])],
+        &mut p_challenger,
+    );
+
+    let mut v_challenger = challenger.clone();
+    v_challenger.observe(commit.clone());
+    let _zeta: Challenge = v_challenger.sample_ext_element();
+    let claims = domains_and_polys
+        .iter()
+        .zip(&opened_values[0])
+        .map(|((domain, _), mat_openings)| (*domain, vec![(zeta, mat_openings[0].clone())]))
+        .collect_vec();
+    pcs.verify(vec![(commit, claims)], &proof, &mut v_challenger)
+        .unwrap();
+}
@@ -5,11 +5,13 @@ use p3_commit::{ExtensionMmcs, Pcs, PolynomialSpace};
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{ExtensionField, Field};
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
-use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_symmetric::{CompressionFunctionFromHasher, PaddingFreeSponge, TruncatedPermutation};
+use p3_util::canonical_serialization::{from_bytes, to_bytes};
 use rand::distributions::{Distribution, Standard};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
@@ -19,7 +21,7 @@ fn seeded_rng() -> impl Rng {
 }
 
 fn do_test_fri_pcs<Val, Challenge, Challenger, P>(
-    (pcs, challenger): &(P, Challenger),
+    pcs_and_challenger: &(P, Challenger),
     log_degrees_by_round: &[&[usize]],
 ) where
     P: Pcs<Challenge, Challenger>,
@@ -29,17 +31,47 @@ fn do_test_fri_pcs<Val, Challenge, Challenger, P>(
     Challenge: ExtensionField<Val>,
     Challenger: Clone + CanObserve<P::Commitment> + FieldChallenger<Val>,
 {
-    let num_rounds = log_degrees_by_round.len();
+    let log_degrees_and_num_points_by_round = log_degrees_by_round
+        .iter()
+        .map(|log_degrees| log_degrees.iter().map(|&d| (d, 1)).collect_vec())
+        .collect_vec();
+    do_test_fri_pcs_multi_point(
+        pcs_and_challenger,
+        &log_degrees_and_num_points_by_round
+            .iter()
+            .map(|v| v.as_slice())
+            .collect_vec(),
+    );
+}
+
+/// Like `do_test_fri_pcs`, but each matrix is given as `(log_degree, num_points)`, and is opened at
+/// the first `num_points` of a shared list of random points.
+fn do_test_fri_pcs_multi_point<Val, Challenge, Challenger, P>(
+    (pcs, challenger): &(P, Challenger),
+    log_degrees_and_num_points_by_round: &[&[(usize, usize)]],
+) where
+    P: Pcs<Challenge, Challenger>,
+    P::Domain: PolynomialSpace<Val = Val>,
+    Val: Field,
+    Standard: Distribution<Val>,
+    Challenge: ExtensionField<Val>,
+    Challenger: Clone + CanObserve<P::Commitment> + FieldChallenger<Val>,
+{
+    let num_rounds = log_degrees_and_num_points_by_round.len();
+    let max_num_points = log_degrees_and_num_points_by_round
+        .iter()
+        .flat_map(|mats| mats.iter().map(|&(_, num_points)| num_points))
+        .max()
+        .unwrap_or(0);
     let mut rng = seeded_rng();
 
     let mut p_challenger = challenger.clone();
 
-    let domains_and_polys_by_round = log_degrees_by_round
+    let domains_and_polys_by_round = log_degrees_and_num_points_by_round
         .iter()
-        .map(|log_degrees| {
-            log_degrees
-                .iter()
-                .map(|&log_degree| {
+        .map(|mats| {
+            mats.iter()
+                .map(|&(log_degree, _)| {
                     let d = 1 << log_degree;
                     // random width 5-15
                     let width = 5 + rng.gen_range(0..=10);
@@ -60,51 +92,122 @@ fn do_test_fri_pcs<Val, Challenge, Challenger, P>(
     assert_eq!(data_by_round.len(), num_rounds);
     p_challenger.observe_slice(&commits_by_round);
 
-    let zeta: Challenge = p_challenger.sample_ext_element();
+    let zetas: Vec<Challenge> = (0..max_num_points)
+        .map(|_| p_challenger.sample_ext_element())
+        .collect();
 
-    let points_by_round = log_degrees_by_round
+    let points_by_round = log_degrees_and_num_points_by_round
         .iter()
-        .map(|log_degrees| vec![vec![zeta]; log_degrees.len()])
+        .map(|mats| {
+            mats.iter()
+                .map(|&(_, num_points)| zetas[..num_points].to_vec())
+                .collect_vec()
+        })
         .collect_vec();
-    let data_and_points = data_by_round.iter().zip(points_by_round).collect();
+    let data_and_points = data_by_round.iter().zip(points_by_round.clone()).collect();
     let (opening_by_round, proof) = pcs.open(data_and_points, &mut p_challenger);
     assert_eq!(opening_by_round.len(), num_rounds);
 
     // Verify the proof.
     let mut v_challenger = challenger.clone();
     v_challenger.observe_slice(&commits_by_round);
-    let verifier_zeta: Challenge = v_challenger.sample_ext_element();
-    assert_eq!(verifier_zeta, zeta);
+    let verifier_zetas: Vec<Challenge> = (0..max_num_points)
+        .map(|_| v_challenger.sample_ext_element())
+        .collect();
+    assert_eq!(verifier_zetas, zetas);
 
     let commits_and_claims_by_round = izip!(
         commits_by_round,
         domains_and_polys_by_round,
+        points_by_round,
         opening_by_round
     )
-    .map(|(commit, domains_and_polys, openings)| {
-        let claims = domains_and_polys
-            .iter()
-            .zip(openings)
-            .map(|((domain, _), mat_openings)| (*domain, vec![(zeta, mat_openings[0].clone())]))
+    .map(|(commit, domains_and_polys, points, openings)| {
+        let claims = izip!(domains_and_polys, points, openings)
+            .map(|((domain, _), points_for_mat, mat_openings)| {
+                assert_eq!(mat_openings.len(), points_for_mat.len());
+                (domain, izip!(points_for_mat, mat_openings).collect_vec())
+            })
             .collect_vec();
         (commit, claims)
     })
     .collect_vec();
     assert_eq!(commits_and_claims_by_round.len(), num_rounds);
 
+    // The proof must survive serialization.
+    let proof_bytes = to_bytes(&proof).unwrap();
+    let proof: P::Proof = from_bytes(&proof_bytes).unwrap();
+
     pcs.verify(commits_and_claims_by_round, &proof, &mut v_challenger)
         .unwrap()
 }
 
+fn do_test_committed_evaluations<Val, Challenge, Challenger, P>(
+    (pcs, _): &(P, Challenger),
+    log_degrees: &[usize],
+) where
+    P: Pcs<Challenge, Challenger>,
+    P::Domain: PolynomialSpace<Val = Val>,
+    Val: Field,
+    Standard: Distribution<Val>,
+    Challenge: ExtensionField<Val>,
+{
+    let mut rng = seeded_rng();
+    let domains_and_polys = log_degrees
+        .iter()
+        .map(|&log_degree| {
+            let d = 1 << log_degree;
+            (
+                pcs.natural_domain_for_degree(d),
+                RowMajorMatrix::<Val>::rand(&mut rng, d, 3),
+            )
+        })
+        .collect_vec();
+    let (_, data) = pcs.commit(domains_and_polys.clone());
+
+    for (idx, (domain, _)) in domains_and_polys.iter().enumerate() {
+        let committed = pcs.get_committed_evaluations(&data, idx);
+        let bit_reversed = pcs.get_committed_evaluations_bit_reversed(&data, idx);
+        let (committed_domain, evals, bit_reversed_domain, bit_reversed_evals) =
+            match (committed, bit_reversed) {
+                (Some((d, e)), Some((bd, be))) => (d, e, bd, be),
+                // The prover data doesn't hold evaluations.
+                (None, None) => continue,
+                _ => panic!("committed evaluations are only available in one order"),
+            };
+        assert!(committed_domain.size() >= domain.size());
+        assert_eq!(committed_domain.size(), bit_reversed_domain.size());
+        assert_eq!(evals.height(), committed_domain.size());
+
+        let evals = evals.to_row_major_matrix();
+        assert_eq!(
+            evals,
+            pcs.get_evaluations_on_domain(&data, idx, committed_domain)
+                .to_row_major_matrix()
+        );
+        // The two orders hold the same rows.
+        let bit_reversed_evals = bit_reversed_evals.to_row_major_matrix();
+        assert_eq!(evals.dimensions(), bit_reversed_evals.dimensions());
+        for row in bit_reversed_evals.rows() {
+            let row = row.collect_vec();
+            assert!(evals.rows().any(|r| r.collect_vec() == row));
+        }
+    }
+}
+
 // Set it up so we create tests inside a module for each pcs, so we get nice error reports
 // specific to a failing PCS.
 macro_rules! make_tests_for_pcs {
     ($p:expr) => {
+        make_tests_for_pcs!($p, _);
+    };
+    // For PCSs which are generic over the challenge field.
+    ($p:expr, $challenge:ty) => {
         #[test]
         fn single() {
             let p = $p;
             for i in 3..6 {
-                $crate::do_test_fri_pcs(&p, &[&[i]]);
+                $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[i]]);
             }
         }
 
@@ -112,7 +215,7 @@ macro_rules! make_tests_for_pcs {
         fn many_equal() {
             let p = $p;
             for i in 5..8 {
-                $crate::do_test_fri_pcs(&p, &[&[i; 5]]);
+                $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[i; 5]]);
                 println!("{i} ok");
             }
         }
@@ -122,7 +225,7 @@ macro_rules! make_tests_for_pcs {
             let p = $p;
             for i in 3..8 {
                 let degrees = (3..3 + i).collect::<Vec<_>>();
-                $crate::do_test_fri_pcs(&p, &[&degrees]);
+                $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&degrees]);
             }
         }
 
@@ -131,22 +234,46 @@ macro_rules! make_tests_for_pcs {
             let p = $p;
             for i in 3..8 {
                 let degrees = (3..3 + i).rev().collect::<Vec<_>>();
-                $crate::do_test_fri_pcs(&p, &[&degrees]);
+                $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&degrees]);
             }
         }
 
         #[test]
         fn multiple_rounds() {
             let p = $p;
-            $crate::do_test_fri_pcs(&p, &[&[3]]);
-            $crate::do_test_fri_pcs(&p, &[&[3], &[3]]);
-            $crate::do_test_fri_pcs(&p, &[&[3], &[2]]);
-            $crate::do_test_fri_pcs(&p, &[&[2], &[3]]);
-            $crate::do_test_fri_pcs(&p, &[&[3, 4], &[3, 4]]);
-            $crate::do_test_fri_pcs(&p, &[&[4, 2], &[4, 2]]);
-            $crate::do_test_fri_pcs(&p, &[&[2, 2], &[3, 3]]);
-            $crate::do_test_fri_pcs(&p, &[&[3, 3], &[2, 2]]);
-            $crate::do_test_fri_pcs(&p, &[&[2], &[3, 3]]);
+            $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[3]]);
+            $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[3], &[3]]);
+            $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[3], &[2]]);
+            $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[2], &[3]]);
+            $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[3, 4], &[3, 4]]);
+            $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[4, 2], &[4, 2]]);
+            $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[2, 2], &[3, 3]]);
+            $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[3, 3], &[2, 2]]);
+            $crate::do_test_fri_pcs::<_, $challenge, _, _>(&p, &[&[2], &[3, 3]]);
+        }
+
+        #[test]
+        fn multiple_points() {
+            let p = $p;
+            $crate::do_test_fri_pcs_multi_point::<_, $challenge, _, _>(&p, &[&[(3, 2)]]);
+            $crate::do_test_fri_pcs_multi_point::<_, $challenge, _, _>(
+                &p,
+                &[&[(4, 3), (4, 1), (3, 4)]],
+            );
+            $crate::do_test_fri_pcs_multi_point::<_, $challenge, _, _>(
+                &p,
+                &[&[(3, 2), (4, 0)], &[(2, 5)]],
+            );
+            $crate::do_test_fri_pcs_multi_point::<_, $challenge, _, _>(
+                &p,
+                &[&[(5, 1), (3, 3)], &[(4, 2), (5, 0)]],
+            );
+        }
+
+        #[test]
+        fn committed_evaluations() {
+            let p = $p;
+            $crate::do_test_committed_evaluations::<_, $challenge, _, _>(&p, &[4, 2, 4]);
         }
     };
 }
@@ -169,7 +296,15 @@ mod babybear_fri_pcs {
     type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
     type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 
-    fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+    fn get_pcs(log_blowup: usize, log_final_poly_len: usize) -> (MyPcs, Challenger) {
+        get_pcs_with_cap_height(log_blowup, log_final_poly_len, 0)
+    }
+
+    fn get_pcs_with_cap_height(
+        log_blowup: usize,
+        log_final_poly_len: usize,
+        cap_height: usize,
+    ) -> (MyPcs, Challenger) {
         let perm = Perm::new_from_rng_128(
             Poseidon2ExternalMatrixGeneral,
             DiffusionMatrixBabyBear::default(),
@@ -178,13 +313,16 @@ mod babybear_fri_pcs {
         let hash = MyHash::new(perm.clone());
         let compress = MyCompress::new(perm.clone());
 
-        let val_mmcs = ValMmcs::new(hash, compress);
+        let val_mmcs = ValMmcs::new(hash, compress).with_cap_height(cap_height);
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
 
         let fri_config = FriConfig {
             log_blowup,
+            log_final_poly_len,
             num_queries: 10,
             proof_of_work_bits: 8,
+            target_soundness_bits: None,
+            security_assumption: SecurityAssumption::CapacityBound,
             mmcs: challenge_mmcs,
         };
 
@@ -193,21 +331,206 @@ mod babybear_fri_pcs {
     }
 
     mod blowup_1 {
-        make_tests_for_pcs!(super::get_pcs(1));
+        make_tests_for_pcs!(super::get_pcs(1, 0));
     }
     mod blowup_2 {
-        make_tests_for_pcs!(super::get_pcs(2));
+        make_tests_for_pcs!(super::get_pcs(2, 0));
+    }
+    // The smallest matrices in the tests have 4 rows, so their LDEs are the size of the final
+    // codeword.
+    mod final_poly_len_4 {
+        make_tests_for_pcs!(super::get_pcs(1, 2));
+    }
+    // Some of the trees are shallower than the cap.
+    mod cap_height_3 {
+        make_tests_for_pcs!(super::get_pcs_with_cap_height(1, 0, 3));
+    }
+
+    // Each FRI commit phase tree holds a single matrix, so any arity works for it, while the input
+    // trees hold matrices of all sorts of heights.
+    type MyCompress4 = CompressionFunctionFromHasher<MyHash, 4, 8>;
+    type FriValMmcs4 =
+        MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress4, 8, 4>;
+    type MyPcs4 = TwoAdicFriPcs<Val, Dft, ValMmcs, ExtensionMmcs<Val, Challenge, FriValMmcs4>>;
+
+    fn get_pcs_4ary_fri(log_blowup: usize) -> (MyPcs4, Challenger) {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut seeded_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+
+        let val_mmcs = ValMmcs::new(hash.clone(), compress);
+        let fri_val_mmcs = FriValMmcs4::new(hash.clone(), MyCompress4::new(hash));
+
+        let fri_config = FriConfig {
+            log_blowup,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            target_soundness_bits: None,
+            security_assumption: SecurityAssumption::CapacityBound,
+            mmcs: ExtensionMmcs::new(fri_val_mmcs),
+        };
+
+        let pcs = MyPcs4::new(Dft::default(), val_mmcs, fri_config);
+        (pcs, Challenger::new(perm.clone()))
+    }
+
+    mod fri_arity_4 {
+        make_tests_for_pcs!(super::get_pcs_4ary_fri(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "can't reach its target soundness for inputs of height 2^15")]
+    fn commit_rejects_unreachable_target() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut seeded_rng(),
+        );
+        let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+        // 100 bits from queries and 10 from grinding, but an LDE of height 2^15 leaves the commit
+        // phase with only about 109 bits. Committing fails before computing the LDE.
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            log_final_poly_len: 0,
+            num_queries: 100,
+            proof_of_work_bits: 8,
+            target_soundness_bits: Some(110),
+            security_assumption: SecurityAssumption::CapacityBound,
+            mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+        };
+        let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+        let domain =
+            <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 14);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << 14, 1);
+        <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
     }
 }
 
-mod m31_fri_pcs {
-    use std::marker::PhantomData;
+mod babybear_hiding_fri_pcs {
+    use p3_merkle_tree::MerkleTreeHidingMmcs;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+
+    type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+
+    type ValMmcs = MerkleTreeHidingMmcs<
+        <Val as Field>::Packing,
+        <Val as Field>::Packing,
+        MyHash,
+        MyCompress,
+        StdRng,
+        8,
+        4,
+    >;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+    type Dft = Radix2DitParallel<Val>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+    fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut seeded_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+
+        let val_mmcs = ValMmcs::new(hash.clone(), compress.clone(), StdRng::seed_from_u64(1));
+        let challenge_mmcs = ChallengeMmcs::new(
+            ValMmcs::new(hash, compress, StdRng::seed_from_u64(2)).with_cap_height(1),
+        );
+
+        let fri_config = FriConfig {
+            log_blowup,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            target_soundness_bits: None,
+            security_assumption: SecurityAssumption::CapacityBound,
+            mmcs: challenge_mmcs,
+        };
+
+        let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+        (pcs, Challenger::new(perm.clone()))
+    }
+
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(1));
+    }
+}
+
+mod goldilocks_fri_pcs {
+    use p3_goldilocks::{DiffusionMatrixGoldilocks, Goldilocks};
+
+    use super::*;
+
+    type Val = Goldilocks;
+    // With low blowups, the quintic extension is needed for challenges with ~128 bits of
+    // soundness.
+    type Challenge = BinomialExtensionField<Val, 5>;
+
+    type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixGoldilocks, 8, 7>;
+    type MyHash = PaddingFreeSponge<Perm, 8, 4, 4>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 4, 8>;
+
+    type ValMmcs =
+        MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 4>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 
+    type Dft = Radix2DitParallel<Val>;
+    type Challenger = DuplexChallenger<Val, Perm, 8, 4>;
+    type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+    fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixGoldilocks,
+            &mut seeded_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+
+        let val_mmcs = ValMmcs::new(hash, compress);
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+        let fri_config = FriConfig {
+            log_blowup,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            target_soundness_bits: None,
+            security_assumption: SecurityAssumption::CapacityBound,
+            mmcs: challenge_mmcs,
+        };
+
+        let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+        (pcs, Challenger::new(perm))
+    }
+
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(1));
+    }
+}
+
+mod m31_fri_pcs {
     use p3_challenger::{HashChallenger, SerializingChallenger32};
     use p3_circle::CirclePcs;
     use p3_keccak::Keccak256Hash;
     use p3_mersenne_31::Mersenne31;
-    use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+    use p3_symmetric::SerializingHasher32;
 
     use super::*;
 
@@ -235,15 +558,14 @@ mod m31_fri_pcs {
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
         let fri_config = FriConfig {
             log_blowup,
+            log_final_poly_len: 0,
             num_queries: 10,
             proof_of_work_bits: 8,
+            target_soundness_bits: None,
+            security_assumption: SecurityAssumption::CapacityBound,
             mmcs: challenge_mmcs,
         };
-        let pcs = Pcs {
-            mmcs: val_mmcs,
-            fri_config,
-            _phantom: PhantomData,
-        };
+        let pcs = Pcs::new(val_mmcs, fri_config);
         (pcs, Challenger::from_hasher(vec![], byte_hash))
     }
 
@@ -254,3 +576,166 @@ mod m31_fri_pcs {
         make_tests_for_pcs!(super::get_pcs(2));
     }
 }
+
+mod m31_hiding_fri_pcs {
+    use p3_challenger::{HashChallenger, SerializingChallenger32};
+    use p3_circle::CirclePcs;
+    use p3_keccak::Keccak256Hash;
+    use p3_merkle_tree::MerkleTreeHidingMmcs;
+    use p3_mersenne_31::Mersenne31;
+    use p3_symmetric::SerializingHasher32;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    type Val = Mersenne31;
+    type Challenge = BinomialExtensionField<Mersenne31, 3>;
+
+    type ByteHash = Keccak256Hash;
+    type FieldHash = SerializingHasher32<ByteHash>;
+
+    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+
+    type ValMmcs = MerkleTreeHidingMmcs<Val, u8, FieldHash, MyCompress, StdRng, 32, 4>;
+
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+
+    type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+
+    fn get_pcs(log_blowup: usize) -> (Pcs, Challenger) {
+        let byte_hash = ByteHash {};
+        let field_hash = FieldHash::new(byte_hash);
+        let compress = MyCompress::new(byte_hash);
+        let val_mmcs = ValMmcs::new(field_hash, compress.clone(), StdRng::seed_from_u64(1));
+        let challenge_mmcs = ChallengeMmcs::new(
+            ValMmcs::new(field_hash, compress, StdRng::seed_from_u64(2)).with_cap_height(1),
+        );
+        let fri_config = FriConfig {
+            log_blowup,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            target_soundness_bits: None,
+            security_assumption: SecurityAssumption::CapacityBound,
+            mmcs: challenge_mmcs,
+        };
+        let pcs = Pcs::new(val_mmcs, fri_config);
+        (pcs, Challenger::from_hasher(vec![], byte_hash))
+    }
+
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(1));
+    }
+}
+
+mod m31_zk_fri_pcs {
+    use p3_challenger::{HashChallenger, SerializingChallenger32};
+    use p3_circle::HidingCirclePcs;
+    use p3_keccak::Keccak256Hash;
+    use p3_merkle_tree::MerkleTreeHidingMmcs;
+    use p3_mersenne_31::Mersenne31;
+    use p3_symmetric::SerializingHasher32;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    type Val = Mersenne31;
+    type Challenge = BinomialExtensionField<Mersenne31, 3>;
+
+    type ByteHash = Keccak256Hash;
+    type FieldHash = SerializingHasher32<ByteHash>;
+
+    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+
+    type ValMmcs = MerkleTreeHidingMmcs<Val, u8, FieldHash, MyCompress, StdRng, 32, 4>;
+
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+
+    type Pcs = HidingCirclePcs<Val, ValMmcs, ChallengeMmcs, StdRng>;
+
+    fn get_pcs(log_blowup: usize) -> (Pcs, Challenger) {
+        let byte_hash = ByteHash {};
+        let field_hash = FieldHash::new(byte_hash);
+        let compress = MyCompress::new(byte_hash);
+        let val_mmcs = ValMmcs::new(field_hash, compress.clone(), StdRng::seed_from_u64(1));
+        let challenge_mmcs = ChallengeMmcs::new(
+            ValMmcs::new(field_hash, compress, StdRng::seed_from_u64(2)).with_cap_height(1),
+        );
+        let fri_config = FriConfig {
+            log_blowup,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            target_soundness_bits: None,
+            security_assumption: SecurityAssumption::CapacityBound,
+            mmcs: challenge_mmcs,
+        };
+        let pcs = Pcs::new(val_mmcs, fri_config, StdRng::seed_from_u64(3));
+        (pcs, Challenger::from_hasher(vec![], byte_hash))
+    }
+
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(1));
+    }
+}
+
+mod trivial_pcs {
+    use core::marker::PhantomData;
+
+    use p3_commit::testing::{TrivialPcs, TrivialPcsError};
+    use p3_field::AbstractField;
+
+    use super::*;
+
+    type Val = BabyBear;
+
+    type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+    type Dft = Radix2DitParallel<Val>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type MyPcs = TrivialPcs<Val, Dft>;
+
+    fn get_pcs() -> (MyPcs, Challenger) {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut seeded_rng(),
+        );
+        let pcs = MyPcs {
+            dft: Dft::default(),
+            log_n: 0,
+            _phantom: PhantomData,
+        };
+        (pcs, Challenger::new(perm))
+    }
+
+    make_tests_for_pcs!(get_pcs(), BinomialExtensionField<Val, 4>);
+
+    #[test]
+    fn rejects_wrong_opened_values() {
+        type Challenge = BinomialExtensionField<Val, 4>;
+        let (pcs, challenger) = get_pcs();
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 8);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 8, 2);
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+        let zeta = Challenge::from_canonical_u32(7);
+        let (mut opened_values, proof) =
+            pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger.clone());
+        opened_values[0][0][0][1] += Challenge::ONE;
+
+        let claims = vec![(domain, vec![(zeta, opened_values[0][0][0].clone())])];
+        assert_eq!(
+            pcs.verify(vec![(commit, claims)], &proof, &mut challenger.clone()),
+            Err(TrivialPcsError::WrongOpenedValues {
+                round: 0,
+                matrix: 0,
+                point: 0
+            })
+        );
+    }
+}
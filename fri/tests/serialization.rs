@@ -0,0 +1,233 @@
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
+use p3_commit::{ExtensionMmcs, Mmcs, OpenedValues, Pcs};
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractExtensionField, AbstractField, Field, PrimeField32};
+use p3_fri::{
+    commitment_from_bytes, commitment_to_bytes, opened_values_from_bytes, opened_values_to_bytes,
+    BatchOpening, CommitPhaseProofStep, FriConfig, FriProof, QueryProof, SecurityAssumption,
+    TwoAdicFriPcs, COMMITMENT_MAGIC, FRI_FORMAT_VERSION, FRI_PROOF_MAGIC,
+};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_util::canonical_serialization::SerializationError;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Dft = Radix2DitParallel<Val>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyProof = <MyPcs as Pcs<Challenge, Challenger>>::Proof;
+
+fn get_pcs() -> (MyPcs, Challenger) {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut rng,
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 10,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+    (pcs, Challenger::new(perm))
+}
+
+#[test]
+fn test_fri_proof_round_trip() {
+    let (pcs, challenger) = get_pcs();
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+
+    let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 5);
+    let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << 5, 3);
+    let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+    let mut p_challenger = challenger.clone();
+    p_challenger.observe(commit.clone());
+    let zeta: Challenge = p_challenger.sample_ext_element();
+    let (opened_values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+
+    // Round trip every component through bytes.
+    let proof_bytes = proof.to_bytes().unwrap();
+    assert_eq!(&proof_bytes[..4], &FRI_PROOF_MAGIC);
+    assert_eq!(&proof_bytes[4..6], &FRI_FORMAT_VERSION.to_le_bytes());
+    let decoded_proof = MyProof::from_bytes(&proof_bytes).unwrap();
+    assert_eq!(decoded_proof.to_bytes().unwrap(), proof_bytes);
+
+    let commit_bytes = commitment_to_bytes(&commit).unwrap();
+    let decoded_commit: <MyPcs as Pcs<Challenge, Challenger>>::Commitment =
+        commitment_from_bytes(&commit_bytes).unwrap();
+    assert_eq!(commit, decoded_commit);
+
+    let opened_bytes = opened_values_to_bytes(&opened_values).unwrap();
+    let decoded_opened: OpenedValues<Challenge> = opened_values_from_bytes(&opened_bytes).unwrap();
+    assert_eq!(opened_values, decoded_opened);
+
+    // The decoded proof must still verify.
+    let mut v_challenger = challenger.clone();
+    v_challenger.observe(decoded_commit.clone());
+    let verifier_zeta: Challenge = v_challenger.sample_ext_element();
+    assert_eq!(verifier_zeta, zeta);
+    pcs.verify(
+        vec![(
+            decoded_commit,
+            vec![(domain, vec![(zeta, decoded_opened[0][0][0].clone())])],
+        )],
+        &decoded_proof,
+        &mut v_challenger,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_opened_values_golden_encoding() {
+    // The layout of encoded opened values is part of the format; this must only change along with
+    // `FRI_FORMAT_VERSION`.
+    let opened_values: OpenedValues<Challenge> = vec![vec![vec![vec![
+        Challenge::from_base_slice(&[1, 2, 3, 4].map(Val::from_canonical_u32)),
+        -Challenge::ONE,
+    ]]]];
+    let bytes = opened_values_to_bytes(&opened_values).unwrap();
+
+    let minus_one = (Val::ORDER_U32 - 1).to_le_bytes();
+    #[rustfmt::skip]
+    let expected = [
+        &b"P3OV"[..], &[3, 0],
+        // rounds, matrices, points, values
+        &[1, 0, 0, 0], &[1, 0, 0, 0], &[1, 0, 0, 0], &[2, 0, 0, 0],
+        &[1, 0, 0, 0], &[2, 0, 0, 0], &[3, 0, 0, 0], &[4, 0, 0, 0],
+        &minus_one, &[0, 0, 0, 0], &[0, 0, 0, 0], &[0, 0, 0, 0],
+    ]
+    .concat();
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn test_fri_proof_golden_encoding() {
+    // Like opened values, the layout of an encoded proof must only change along with
+    // `FRI_FORMAT_VERSION`.
+    let digest = [Val::ONE; 8];
+    let proof: MyProof = FriProof {
+        commit_phase_commits: vec![vec![digest].into()],
+        query_proofs: vec![QueryProof {
+            input_proof: vec![BatchOpening {
+                opened_values: vec![vec![Val::TWO]],
+                opening_proof: vec![digest],
+            }],
+            commit_phase_openings: vec![CommitPhaseProofStep {
+                sibling_value: Challenge::from_canonical_u32(3),
+                opening_proof: vec![],
+            }],
+        }],
+        final_poly: vec![
+            Challenge::from_canonical_u32(4),
+            Challenge::from_canonical_u32(5),
+        ],
+        pow_witness: Val::from_canonical_u32(6),
+    };
+    let bytes = proof.to_bytes().unwrap();
+
+    let digest_bytes = [1, 0, 0, 0].repeat(8);
+    #[rustfmt::skip]
+    let expected = [
+        &b"P3FP"[..], &[3, 0],
+        // commit_phase_commits, each a cap of one digest
+        &[1, 0, 0, 0], &[1, 0, 0, 0], &digest_bytes,
+        // query_proofs, then the input proof: batches, matrices, values, and the Merkle path
+        &[1, 0, 0, 0],
+        &[1, 0, 0, 0], &[1, 0, 0, 0], &[1, 0, 0, 0], &[2, 0, 0, 0], &[1, 0, 0, 0], &digest_bytes,
+        // commit_phase_openings: the sibling value and an empty Merkle path
+        &[1, 0, 0, 0], &[3, 0, 0, 0], &[0; 12], &[0, 0, 0, 0],
+        // final_poly
+        &[2, 0, 0, 0], &[4, 0, 0, 0], &[0; 12], &[5, 0, 0, 0], &[0; 12],
+        // pow_witness
+        &[6, 0, 0, 0],
+    ]
+    .concat();
+    assert_eq!(bytes, expected);
+    assert_eq!(
+        MyProof::from_bytes(&bytes).unwrap().to_bytes().unwrap(),
+        bytes
+    );
+}
+
+#[test]
+fn test_commitment_golden_encoding() {
+    // A commitment is a Merkle cap, encoded as a vector of digests.
+    let cap: <ValMmcs as Mmcs<Val>>::Commitment =
+        vec![[Val::ONE; 8], [Val::from_canonical_u32(2); 8]].into();
+    let bytes = commitment_to_bytes(&cap).unwrap();
+
+    #[rustfmt::skip]
+    let expected = [
+        &b"P3CM"[..], &[3, 0],
+        &[2, 0, 0, 0], &[1, 0, 0, 0].repeat(8), &[2, 0, 0, 0].repeat(8),
+    ]
+    .concat();
+    assert_eq!(bytes, expected);
+    assert_eq!(
+        commitment_from_bytes::<<ValMmcs as Mmcs<Val>>::Commitment>(&bytes).unwrap(),
+        cap
+    );
+}
+
+#[test]
+fn test_rejects_malformed_encodings() {
+    let commit_bytes = commitment_to_bytes(&[Val::ONE; 8]).unwrap();
+
+    // Wrong magic: a commitment is not a proof.
+    assert_eq!(
+        MyProof::from_bytes(&commit_bytes).err(),
+        Some(SerializationError::BadMagic {
+            expected: FRI_PROOF_MAGIC,
+            found: COMMITMENT_MAGIC,
+        })
+    );
+
+    // Unknown version.
+    let mut future_bytes = commit_bytes.clone();
+    future_bytes[4..6].copy_from_slice(&(FRI_FORMAT_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        commitment_from_bytes::<[Val; 8]>(&future_bytes),
+        Err(SerializationError::UnsupportedVersion { found, .. }) if found == FRI_FORMAT_VERSION + 1
+    ));
+
+    // Non-canonical field element.
+    let mut non_canonical = commit_bytes.clone();
+    non_canonical[6..10].copy_from_slice(&Val::ORDER_U32.to_le_bytes());
+    assert!(matches!(
+        commitment_from_bytes::<[Val; 8]>(&non_canonical),
+        Err(SerializationError::Custom(_))
+    ));
+
+    // Truncated input.
+    assert_eq!(
+        commitment_from_bytes::<[Val; 8]>(&commit_bytes[..commit_bytes.len() - 1]),
+        Err(SerializationError::UnexpectedEof)
+    );
+
+    let decoded: [Val; 8] = commitment_from_bytes(&commit_bytes).unwrap();
+    assert_eq!(decoded, [Val::ONE; 8]);
+}
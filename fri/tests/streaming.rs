@@ -0,0 +1,200 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use itertools::Itertools;
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
+use p3_commit::{ExtensionMmcs, Pcs, PolynomialSpace};
+use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_field::extension::BinomialExtensionField;
+use p3_field::Field;
+use p3_fri::{FriConfig, SecurityAssumption, StreamingTwoAdicFriPcs, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Dft = Radix2DitParallel<Val>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyStreamingPcs = StreamingTwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+fn get_perm_and_mmcs() -> (Perm, ValMmcs) {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut rng,
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    (perm, ValMmcs::new(hash, compress))
+}
+
+fn get_fri_config(val_mmcs: &ValMmcs, log_blowup: usize) -> FriConfig<ChallengeMmcs> {
+    FriConfig {
+        log_blowup,
+        log_final_poly_len: 0,
+        num_queries: 10,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+    }
+}
+
+fn get_pcs(log_blowup: usize) -> (MyPcs, MyStreamingPcs, Challenger) {
+    let (perm, val_mmcs) = get_perm_and_mmcs();
+    let pcs = MyPcs::new(
+        Dft::default(),
+        val_mmcs.clone(),
+        get_fri_config(&val_mmcs, log_blowup),
+    );
+    let streaming_pcs = MyStreamingPcs::new(
+        Dft::default(),
+        val_mmcs.clone(),
+        get_fri_config(&val_mmcs, log_blowup),
+    );
+    (pcs, streaming_pcs, Challenger::new(perm))
+}
+
+fn do_test_streaming_matches(log_blowup: usize, log_degrees: &[usize]) {
+    let (pcs, streaming_pcs, challenger) = get_pcs(log_blowup);
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+
+    let domains_and_polys = log_degrees
+        .iter()
+        .map(|&d| {
+            (
+                <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << d),
+                RowMajorMatrix::<Val>::rand(&mut rng, 1 << d, 5),
+            )
+        })
+        .collect_vec();
+
+    let (commit, data) =
+        <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, domains_and_polys.clone());
+    let (streaming_commit, streaming_data) = <MyStreamingPcs as Pcs<Challenge, Challenger>>::commit(
+        &streaming_pcs,
+        domains_and_polys.clone(),
+    );
+    assert_eq!(commit, streaming_commit);
+
+    for (idx, (domain, _)) in domains_and_polys.iter().enumerate() {
+        let lde_domain = domain.create_disjoint_domain(domain.size() << log_blowup.min(1));
+        let evals = <MyPcs as Pcs<Challenge, Challenger>>::get_evaluations_on_domain(
+            &pcs, &data, idx, lde_domain,
+        )
+        .to_row_major_matrix();
+        let streaming_evals =
+            <MyStreamingPcs as Pcs<Challenge, Challenger>>::get_evaluations_on_domain(
+                &streaming_pcs,
+                &streaming_data,
+                idx,
+                lde_domain,
+            )
+            .to_row_major_matrix();
+        assert_eq!(evals, streaming_evals);
+    }
+
+    let mut p_challenger = challenger.clone();
+    p_challenger.observe(streaming_commit);
+    let zeta: Challenge = p_challenger.sample_ext_element();
+    let points = vec![vec![zeta]; log_degrees.len()];
+
+    let (opened_values, proof) = streaming_pcs.open(
+        vec![(&streaming_data, points.clone())],
+        &mut p_challenger.clone(),
+    );
+    let (expected_opened_values, expected_proof) =
+        pcs.open(vec![(&data, points)], &mut p_challenger);
+    assert_eq!(opened_values, expected_opened_values);
+    assert_eq!(
+        proof.to_bytes().unwrap(),
+        expected_proof.to_bytes().unwrap()
+    );
+
+    // The regular PCS accepts proofs made by the streaming one.
+    let mut v_challenger = challenger.clone();
+    v_challenger.observe(commit.clone());
+    let verifier_zeta: Challenge = v_challenger.sample_ext_element();
+    assert_eq!(verifier_zeta, zeta);
+    let claims = domains_and_polys
+        .iter()
+        .zip(&opened_values[0])
+        .map(|((domain, _), mat_openings)| (*domain, vec![(zeta, mat_openings[0].clone())]))
+        .collect_vec();
+    pcs.verify(vec![(commit, claims)], &proof, &mut v_challenger)
+        .unwrap();
+}
+
+#[test]
+fn test_streaming_matches_single() {
+    do_test_streaming_matches(1, &[6]);
+}
+
+#[test]
+fn test_streaming_matches_mixed_heights() {
+    do_test_streaming_matches(2, &[7, 7, 5, 3]);
+}
+
+#[test]
+fn test_streaming_matches_no_blowup() {
+    do_test_streaming_matches(0, &[4, 2]);
+}
+
+/// A DFT which counts the transforms done by it and its clones. A default one has its own count.
+#[derive(Clone, Default, Debug)]
+struct CountingDft {
+    inner: Dft,
+    calls: Arc<AtomicUsize>,
+}
+
+impl TwoAdicSubgroupDft<Val> for CountingDft {
+    type Evaluations = <Dft as TwoAdicSubgroupDft<Val>>::Evaluations;
+
+    fn dft_batch(&self, mat: RowMajorMatrix<Val>) -> Self::Evaluations {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.inner.dft_batch(mat)
+    }
+}
+
+#[test]
+fn test_streaming_uses_configured_dft() {
+    type CountingPcs = StreamingTwoAdicFriPcs<Val, CountingDft, ValMmcs, ChallengeMmcs>;
+
+    let log_blowup = 2;
+    let log_n = 4;
+    let (_perm, val_mmcs) = get_perm_and_mmcs();
+    let dft = CountingDft::default();
+    let calls = dft.calls.clone();
+    let pcs = CountingPcs::new(dft, val_mmcs.clone(), get_fri_config(&val_mmcs, log_blowup));
+
+    let domain =
+        <CountingPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_n);
+    let evals = RowMajorMatrix::<Val>::rand(&mut ChaCha20Rng::seed_from_u64(1), 1 << log_n, 3);
+    let (_commit, data) =
+        <CountingPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+    // One inverse DFT, then at least one DFT per coset of the LDE.
+    assert!(calls.load(Ordering::Relaxed) > 1 << log_blowup);
+
+    // Recomputing two cosets of the LDE takes two more DFTs.
+    let before = calls.load(Ordering::Relaxed);
+    let lde_domain = domain.create_disjoint_domain(domain.size() << 1);
+    <CountingPcs as Pcs<Challenge, Challenger>>::get_evaluations_on_domain(
+        &pcs, &data, 0, lde_domain,
+    );
+    assert_eq!(calls.load(Ordering::Relaxed), before + 2);
+}
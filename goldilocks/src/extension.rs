@@ -31,6 +31,42 @@ impl HasTwoAdicBionmialExtension<2> for Goldilocks {
     }
 }
 
+impl BinomiallyExtendable<5> for Goldilocks {
+    // Verifiable in Sage with
+    // `R.<x> = GF(p)[]; assert (x^5 - 3).is_irreducible()`.
+    const W: Self = Self::new(3);
+
+    // DTH_ROOT = W^((p - 1)/5).
+    const DTH_ROOT: Self = Self::new(1041288259238279555);
+
+    // X + 2 generates the multiplicative group, which can be checked against the factorization
+    // of p^5 - 1 = (p - 1) (p^4 + p^3 + p^2 + p + 1).
+    const EXT_GENERATOR: [Self; 5] = [
+        Self::new(2),
+        Self::new(1),
+        Self::new(0),
+        Self::new(0),
+        Self::new(0),
+    ];
+}
+
+impl HasTwoAdicBionmialExtension<5> for Goldilocks {
+    // 5 is odd, so the extension has no more two-adicity than the base field.
+    const EXT_TWO_ADICITY: usize = 32;
+
+    fn ext_two_adic_generator(bits: usize) -> [Self; 5] {
+        assert!(bits <= 32);
+
+        [
+            Self::two_adic_generator(bits),
+            Self::ZERO,
+            Self::ZERO,
+            Self::ZERO,
+            Self::ZERO,
+        ]
+    }
+}
+
 #[cfg(test)]
 mod test_quadratic_extension {
 
@@ -46,3 +82,54 @@ mod test_quadratic_extension {
 
     test_two_adic_extension_field!(super::F, super::EF);
 }
+
+#[cfg(test)]
+mod test_quintic_extension {
+
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{AbstractExtensionField, AbstractField};
+    use p3_field_testing::{
+        test_add_neg_sub_mul, test_inv_div, test_inverse, test_two_adic_extension_field,
+    };
+    use rand::Rng;
+
+    use crate::Goldilocks;
+
+    type F = Goldilocks;
+    type EF = BinomialExtensionField<F, 5>;
+
+    // Not `test_field!`, which also factors `p^5 - 1`: past `p - 1`, it has prime factors of 58
+    // bits and more, which Pollard rho won't find in a test's time.
+    #[test]
+    fn test_field_arithmetic() {
+        test_add_neg_sub_mul::<EF>();
+        test_inv_div::<EF>();
+        test_inverse::<EF>();
+    }
+
+    test_two_adic_extension_field!(super::F, super::EF);
+
+    #[test]
+    #[allow(clippy::needless_range_loop)]
+    fn test_mul_matches_schoolbook() {
+        let mut rng = rand::thread_rng();
+        let a: [F; 5] = rng.gen();
+        let b: [F; 5] = rng.gen();
+        let w = F::from_canonical_u8(3);
+
+        let mut expected = [F::ZERO; 5];
+        for i in 0..5 {
+            for j in 0..5 {
+                if i + j >= 5 {
+                    expected[i + j - 5] += w * a[i] * b[j];
+                } else {
+                    expected[i + j] += a[i] * b[j];
+                }
+            }
+        }
+
+        let (a, b) = (EF::from_base_slice(&a), EF::from_base_slice(&b));
+        assert_eq!(a * b, EF::from_base_slice(&expected));
+        assert_eq!(a.square(), a * a);
+    }
+}
@@ -10,18 +10,19 @@ use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 use num_bigint::BigUint;
 use p3_field::{
     exp_10540996611094048183, exp_u64_by_squaring, halve_u64, AbstractField, Field, Packable,
-    PrimeField, PrimeField64, TwoAdicField,
+    PrimeField, PrimeField64, ThreeAdicField, TwoAdicField,
 };
 use p3_util::{assume, branch_hint};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// The Goldilocks prime
 const P: u64 = 0xFFFF_FFFF_0000_0001;
 
 /// The prime field known as Goldilocks, defined as `F_p` where `p = 2^64 - 2^32 + 1`.
-#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default)]
 #[repr(transparent)] // Packed field implementations rely on this!
 pub struct Goldilocks {
     /// Not necessarily canonical.
@@ -47,6 +48,32 @@ impl Eq for Goldilocks {}
 
 impl Packable for Goldilocks {}
 
+impl Serialize for Goldilocks {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Serialize the canonical value so that equal elements have equal encodings.
+        serializer.serialize_u64(self.as_canonical_u64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Goldilocks {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let val = u64::deserialize(d)?;
+        if val >= P {
+            return Err(D::Error::custom("non-canonical Goldilocks element"));
+        }
+        Ok(Self::new(val))
+    }
+}
+
+// SAFETY: `Goldilocks` is a `repr(transparent)` wrapper of a `u64`, which needn't be canonical, so
+// every bit pattern is valid.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for Goldilocks {}
+
+// SAFETY: as for `Zeroable`.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for Goldilocks {}
+
 impl Hash for Goldilocks {
     fn hash<H: Hasher>(&self, state: &mut H) {
         state.write_u64(self.as_canonical_u64());
@@ -277,6 +304,18 @@ impl TwoAdicField for Goldilocks {
     }
 }
 
+impl ThreeAdicField for Goldilocks {
+    const THREE_ADICITY: usize = 1;
+
+    fn three_adic_generator(bits: usize) -> Self {
+        assert!(bits <= Self::THREE_ADICITY);
+        match bits {
+            0 => Self::ONE,
+            _ => Self::new(18_446_744_065_119_617_025), // a primitive cube root of unity
+        }
+    }
+}
+
 impl Add for Goldilocks {
     type Output = Self;
 
@@ -481,6 +520,17 @@ mod tests {
 
     type F = Goldilocks;
 
+    #[test]
+    fn test_goldilocks_three_adicity_generator() {
+        let g = F::three_adic_generator(1);
+        assert_ne!(g, F::ONE);
+        assert_eq!(g.cube(), F::ONE);
+
+        let g = F::mixed_radix_generator(5, 1);
+        assert_eq!(g.exp_u64(3), F::two_adic_generator(5));
+        assert_eq!(g.exp_u64(32), F::three_adic_generator(1));
+    }
+
     #[test]
     fn test_goldilocks() {
         let f = F::new(100);
@@ -15,10 +15,12 @@ extern crate alloc;
 mod extension;
 mod goldilocks;
 mod mds;
+mod poseidon;
 mod poseidon2;
 
 pub use goldilocks::*;
 pub use mds::*;
+pub use poseidon::*;
 pub use poseidon2::*;
 
 #[cfg(all(
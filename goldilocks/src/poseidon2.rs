@@ -83,48 +83,100 @@ pub const MATRIX_DIAG_20_GOLDILOCKS_U64: [u64; 20] = [
 ];
 
 // Convert the above arrays of u64's into arrays of Goldilocks field elements.
-const MATRIX_DIAG_8_GOLDILOCKS: [Goldilocks; 8] = to_goldilocks_array(MATRIX_DIAG_8_GOLDILOCKS_U64);
-const MATRIX_DIAG_12_GOLDILOCKS: [Goldilocks; 12] =
+pub(crate) const MATRIX_DIAG_8_GOLDILOCKS: [Goldilocks; 8] =
+    to_goldilocks_array(MATRIX_DIAG_8_GOLDILOCKS_U64);
+pub(crate) const MATRIX_DIAG_12_GOLDILOCKS: [Goldilocks; 12] =
     to_goldilocks_array(MATRIX_DIAG_12_GOLDILOCKS_U64);
-const MATRIX_DIAG_16_GOLDILOCKS: [Goldilocks; 16] =
+pub(crate) const MATRIX_DIAG_16_GOLDILOCKS: [Goldilocks; 16] =
     to_goldilocks_array(MATRIX_DIAG_16_GOLDILOCKS_U64);
-const MATRIX_DIAG_20_GOLDILOCKS: [Goldilocks; 20] =
+pub(crate) const MATRIX_DIAG_20_GOLDILOCKS: [Goldilocks; 20] =
     to_goldilocks_array(MATRIX_DIAG_20_GOLDILOCKS_U64);
 
+/// The internal linear layer of Poseidon2 for `Goldilocks` and its packed fields, which have
+/// specialized implementations on some targets.
 #[derive(Debug, Clone, Default)]
 pub struct DiffusionMatrixGoldilocks;
 
-impl<AF: AbstractField<F = Goldilocks>> Permutation<[AF; 8]> for DiffusionMatrixGoldilocks {
+impl Permutation<[Goldilocks; 8]> for DiffusionMatrixGoldilocks {
+    fn permute_mut(&self, state: &mut [Goldilocks; 8]) {
+        matmul_internal::<Goldilocks, Goldilocks, 8>(state, MATRIX_DIAG_8_GOLDILOCKS);
+    }
+}
+
+impl DiffusionPermutation<Goldilocks, 8> for DiffusionMatrixGoldilocks {}
+
+impl Permutation<[Goldilocks; 12]> for DiffusionMatrixGoldilocks {
+    fn permute_mut(&self, state: &mut [Goldilocks; 12]) {
+        matmul_internal::<Goldilocks, Goldilocks, 12>(state, MATRIX_DIAG_12_GOLDILOCKS);
+    }
+}
+
+impl DiffusionPermutation<Goldilocks, 12> for DiffusionMatrixGoldilocks {}
+
+impl Permutation<[Goldilocks; 16]> for DiffusionMatrixGoldilocks {
+    fn permute_mut(&self, state: &mut [Goldilocks; 16]) {
+        matmul_internal::<Goldilocks, Goldilocks, 16>(state, MATRIX_DIAG_16_GOLDILOCKS);
+    }
+}
+
+impl DiffusionPermutation<Goldilocks, 16> for DiffusionMatrixGoldilocks {}
+
+impl Permutation<[Goldilocks; 20]> for DiffusionMatrixGoldilocks {
+    fn permute_mut(&self, state: &mut [Goldilocks; 20]) {
+        matmul_internal::<Goldilocks, Goldilocks, 20>(state, MATRIX_DIAG_20_GOLDILOCKS);
+    }
+}
+
+impl DiffusionPermutation<Goldilocks, 20> for DiffusionMatrixGoldilocks {}
+
+/// Like `DiffusionMatrixGoldilocks`, but generalized to any `AbstractField` over `Goldilocks`, and
+/// without the specialized implementations for the packed fields.
+#[derive(Debug, Clone, Default)]
+pub struct GenericDiffusionMatrixGoldilocks;
+
+impl<AF: AbstractField<F = Goldilocks>> Permutation<[AF; 8]> for GenericDiffusionMatrixGoldilocks {
     fn permute_mut(&self, state: &mut [AF; 8]) {
         matmul_internal::<Goldilocks, AF, 8>(state, MATRIX_DIAG_8_GOLDILOCKS);
     }
 }
 
-impl<AF: AbstractField<F = Goldilocks>> DiffusionPermutation<AF, 8> for DiffusionMatrixGoldilocks {}
+impl<AF: AbstractField<F = Goldilocks>> DiffusionPermutation<AF, 8>
+    for GenericDiffusionMatrixGoldilocks
+{
+}
 
-impl<AF: AbstractField<F = Goldilocks>> Permutation<[AF; 12]> for DiffusionMatrixGoldilocks {
+impl<AF: AbstractField<F = Goldilocks>> Permutation<[AF; 12]> for GenericDiffusionMatrixGoldilocks {
     fn permute_mut(&self, state: &mut [AF; 12]) {
         matmul_internal::<Goldilocks, AF, 12>(state, MATRIX_DIAG_12_GOLDILOCKS);
     }
 }
 
-impl<AF: AbstractField<F = Goldilocks>> DiffusionPermutation<AF, 12> for DiffusionMatrixGoldilocks {}
+impl<AF: AbstractField<F = Goldilocks>> DiffusionPermutation<AF, 12>
+    for GenericDiffusionMatrixGoldilocks
+{
+}
 
-impl<AF: AbstractField<F = Goldilocks>> Permutation<[AF; 16]> for DiffusionMatrixGoldilocks {
+impl<AF: AbstractField<F = Goldilocks>> Permutation<[AF; 16]> for GenericDiffusionMatrixGoldilocks {
     fn permute_mut(&self, state: &mut [AF; 16]) {
         matmul_internal::<Goldilocks, AF, 16>(state, MATRIX_DIAG_16_GOLDILOCKS);
     }
 }
 
-impl<AF: AbstractField<F = Goldilocks>> DiffusionPermutation<AF, 16> for DiffusionMatrixGoldilocks {}
+impl<AF: AbstractField<F = Goldilocks>> DiffusionPermutation<AF, 16>
+    for GenericDiffusionMatrixGoldilocks
+{
+}
 
-impl<AF: AbstractField<F = Goldilocks>> Permutation<[AF; 20]> for DiffusionMatrixGoldilocks {
+impl<AF: AbstractField<F = Goldilocks>> Permutation<[AF; 20]> for GenericDiffusionMatrixGoldilocks {
     fn permute_mut(&self, state: &mut [AF; 20]) {
         matmul_internal::<Goldilocks, AF, 20>(state, MATRIX_DIAG_20_GOLDILOCKS);
     }
 }
 
-impl<AF: AbstractField<F = Goldilocks>> DiffusionPermutation<AF, 20> for DiffusionMatrixGoldilocks {}
+impl<AF: AbstractField<F = Goldilocks>> DiffusionPermutation<AF, 20>
+    for GenericDiffusionMatrixGoldilocks
+{
+}
 
 pub const HL_GOLDILOCKS_8_EXTERNAL_ROUND_CONSTANTS: [[u64; 8]; 8] = [
     [
@@ -1,3 +1,5 @@
+use core::array;
+
 use p3_mds::util::apply_circulant;
 use p3_mds::MdsPermutation;
 use p3_symmetric::Permutation;
@@ -7,20 +9,37 @@ use crate::{
     MdsMatrixGoldilocks, MATRIX_CIRC_MDS_12_SML_ROW, MATRIX_CIRC_MDS_16_SML_ROW,
     MATRIX_CIRC_MDS_24_GOLDILOCKS, MATRIX_CIRC_MDS_8_SML_ROW,
 };
-const fn convert_array<const N: usize>(arr: [i64; N]) -> [u64; N] {
-    let mut result: [u64; N] = [0; N];
+
+const fn convert_array<const N: usize>(arr: [i64; N]) -> [u32; N] {
+    let mut result: [u32; N] = [0; N];
     let mut i = 0;
     while i < N {
-        result[i] = arr[i] as u64;
+        assert!(0 <= arr[i] && arr[i] <= u16::MAX as i64);
+        result[i] = arr[i] as u32;
         i += 1;
     }
     result
 }
 
+/// Multiply `input` by the circulant matrix with first row `circ_row`, whose entries are small.
+///
+/// Each output is the dot product of `input` with a rotation of `circ_row`, computed on the 64-bit
+/// lanes with a single reduction, rather than with a field multiplication for every entry.
+#[inline]
+fn apply_circulant_small<const N: usize>(
+    circ_row: &[u32; N],
+    input: [PackedGoldilocksAVX2; N],
+) -> [PackedGoldilocksAVX2; N] {
+    array::from_fn(|i| {
+        let row = array::from_fn(|j| circ_row[(N + j - i) % N]);
+        PackedGoldilocksAVX2::dot_product_small(&input, &row)
+    })
+}
+
 impl Permutation<[PackedGoldilocksAVX2; 8]> for MdsMatrixGoldilocks {
     fn permute(&self, input: [PackedGoldilocksAVX2; 8]) -> [PackedGoldilocksAVX2; 8] {
-        const MATRIX_CIRC_MDS_8_SML_ROW_U64: [u64; 8] = convert_array(MATRIX_CIRC_MDS_8_SML_ROW);
-        apply_circulant(&MATRIX_CIRC_MDS_8_SML_ROW_U64, input)
+        const MATRIX_CIRC_MDS_8_SML_ROW_U32: [u32; 8] = convert_array(MATRIX_CIRC_MDS_8_SML_ROW);
+        apply_circulant_small(&MATRIX_CIRC_MDS_8_SML_ROW_U32, input)
     }
 
     fn permute_mut(&self, input: &mut [PackedGoldilocksAVX2; 8]) {
@@ -32,8 +51,8 @@ impl MdsPermutation<PackedGoldilocksAVX2, 8> for MdsMatrixGoldilocks {}
 
 impl Permutation<[PackedGoldilocksAVX2; 12]> for MdsMatrixGoldilocks {
     fn permute(&self, input: [PackedGoldilocksAVX2; 12]) -> [PackedGoldilocksAVX2; 12] {
-        const MATRIX_CIRC_MDS_12_SML_ROW_U64: [u64; 12] = convert_array(MATRIX_CIRC_MDS_12_SML_ROW);
-        apply_circulant(&MATRIX_CIRC_MDS_12_SML_ROW_U64, input)
+        const MATRIX_CIRC_MDS_12_SML_ROW_U32: [u32; 12] = convert_array(MATRIX_CIRC_MDS_12_SML_ROW);
+        apply_circulant_small(&MATRIX_CIRC_MDS_12_SML_ROW_U32, input)
     }
 
     fn permute_mut(&self, input: &mut [PackedGoldilocksAVX2; 12]) {
@@ -45,8 +64,8 @@ impl MdsPermutation<PackedGoldilocksAVX2, 12> for MdsMatrixGoldilocks {}
 
 impl Permutation<[PackedGoldilocksAVX2; 16]> for MdsMatrixGoldilocks {
     fn permute(&self, input: [PackedGoldilocksAVX2; 16]) -> [PackedGoldilocksAVX2; 16] {
-        const MATRIX_CIRC_MDS_16_SML_ROW_U64: [u64; 16] = convert_array(MATRIX_CIRC_MDS_16_SML_ROW);
-        apply_circulant(&MATRIX_CIRC_MDS_16_SML_ROW_U64, input)
+        const MATRIX_CIRC_MDS_16_SML_ROW_U32: [u32; 16] = convert_array(MATRIX_CIRC_MDS_16_SML_ROW);
+        apply_circulant_small(&MATRIX_CIRC_MDS_16_SML_ROW_U32, input)
     }
 
     fn permute_mut(&self, input: &mut [PackedGoldilocksAVX2; 16]) {
@@ -56,6 +75,7 @@ impl Permutation<[PackedGoldilocksAVX2; 16]> for MdsMatrixGoldilocks {
 
 impl MdsPermutation<PackedGoldilocksAVX2, 16> for MdsMatrixGoldilocks {}
 
+// The entries of this matrix are arbitrary field elements, so it keeps the generic implementation.
 impl Permutation<[PackedGoldilocksAVX2; 24]> for MdsMatrixGoldilocks {
     fn permute(&self, input: [PackedGoldilocksAVX2; 24]) -> [PackedGoldilocksAVX2; 24] {
         apply_circulant(&MATRIX_CIRC_MDS_24_GOLDILOCKS, input)
@@ -152,4 +172,31 @@ mod tests {
         let avx2_output = avx2_input.map(|x| x.0[0]);
         assert_eq!(avx2_output, expected);
     }
+
+    /// Check every lane of the packed MDS layer against the scalar one, which is computed with
+    /// integer convolutions.
+    fn check_mds_lanes<const N: usize>()
+    where
+        MdsMatrixGoldilocks: Permutation<[Goldilocks; N]> + Permutation<[PackedGoldilocksAVX2; N]>,
+    {
+        let mut rng = rand::thread_rng();
+        let mut input: [PackedGoldilocksAVX2; N] =
+            core::array::from_fn(|_| PackedGoldilocksAVX2(rng.gen()));
+        input[0] = PackedGoldilocksAVX2::from_f(Goldilocks::NEG_ONE);
+        input[N - 1].0[1] = Goldilocks::NEG_ONE;
+
+        let output = MdsMatrixGoldilocks.permute(input);
+        for lane in 0..4 {
+            let expected = MdsMatrixGoldilocks.permute(input.map(|x| x.0[lane]));
+            assert_eq!(output.map(|x| x.0[lane]), expected);
+        }
+    }
+
+    #[test]
+    fn test_avx2_mds_lanes() {
+        check_mds_lanes::<8>();
+        check_mds_lanes::<12>();
+        check_mds_lanes::<16>();
+        check_mds_lanes::<24>();
+    }
 }
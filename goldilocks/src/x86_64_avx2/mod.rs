@@ -1,3 +1,5 @@
 mod mds;
 mod packing;
+mod poseidon2;
+
 pub use packing::*;
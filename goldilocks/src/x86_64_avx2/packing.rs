@@ -35,6 +35,49 @@ impl PackedGoldilocksAVX2 {
     fn get(&self) -> __m256i {
         unsafe { transmute(*self) }
     }
+
+    /// Compute `self * rhs + addend`, reducing the 128-bit result only once.
+    #[inline]
+    pub(crate) fn mul_add(self, rhs: Self, addend: Self) -> Self {
+        Self::new(unsafe { reduce128(add128_64(mul64_64(self.get(), rhs.get()), addend.get())) })
+    }
+
+    /// Sum `xs` into a 128-bit accumulator, reducing only once at the end. The accumulator can't
+    /// overflow for fewer than `2^64` terms.
+    #[inline]
+    pub(crate) fn sum_lazy(xs: &[Self]) -> Self {
+        unsafe {
+            let zero = _mm256_setzero_si256();
+            let acc = xs
+                .iter()
+                .fold((zero, zero), |acc, x| add128_64(acc, x.get()));
+            Self::new(reduce128(acc))
+        }
+    }
+
+    /// Compute the dot product of `xs` with the small constants `cs`, reducing only once at the
+    /// end. The low and high halves of each `x` are multiplied by the constants separately, so the
+    /// sum of the constants must be less than `2^32` for the partial sums to fit in 64 bits.
+    #[inline]
+    pub(crate) fn dot_product_small<const N: usize>(xs: &[Self; N], cs: &[u32; N]) -> Self {
+        unsafe {
+            let zero = _mm256_setzero_si256();
+            let (acc_lo, acc_hi) = xs.iter().zip(cs).fold((zero, zero), |(lo, hi), (x, &c)| {
+                let x = x.get();
+                let c = _mm256_set1_epi64x(c as i64);
+                // `_mm256_mul_epu32` only reads the low 32 bits of each lane.
+                let prod_lo = _mm256_mul_epu32(x, c);
+                let prod_hi = _mm256_mul_epu32(_mm256_srli_epi64::<32>(x), c);
+                (_mm256_add_epi64(lo, prod_lo), _mm256_add_epi64(hi, prod_hi))
+            });
+            // The dot product is `acc_lo + acc_hi * 2^32`.
+            let acc = (
+                _mm256_srli_epi64::<32>(acc_hi),
+                _mm256_slli_epi64::<32>(acc_hi),
+            );
+            Self::new(reduce128(add128_64(acc, acc_lo)))
+        }
+    }
 }
 
 impl Add<Self> for PackedGoldilocksAVX2 {
@@ -522,6 +565,17 @@ unsafe fn sub_small_64s_64_s(x_s: __m256i, y: __m256i) -> __m256i {
     _mm256_sub_epi64(res_wrapped_s, wrapback_amt)
 }
 
+/// Add the `u64` `y` to the `u128` `x`, given as `(hi, lo)`. Assumes that the sum is less than
+/// `2^128`.
+#[inline]
+unsafe fn add128_64(x: (__m256i, __m256i), y: __m256i) -> (__m256i, __m256i) {
+    let (hi, lo) = x;
+    let res_lo = _mm256_add_epi64(lo, y);
+    // -1 if the addition carried (i.e. res_lo < lo as unsigned integers) else 0.
+    let carry = _mm256_cmpgt_epi64(shift(lo), shift(res_lo));
+    (_mm256_sub_epi64(hi, carry), res_lo)
+}
+
 #[inline]
 unsafe fn reduce128(x: (__m256i, __m256i)) -> __m256i {
     let (hi0, lo0) = x;
@@ -0,0 +1,77 @@
+use p3_poseidon2::{matmul_internal, DiffusionPermutation};
+use p3_symmetric::Permutation;
+
+use crate::{
+    DiffusionMatrixGoldilocks, Goldilocks, PackedGoldilocksAVX512, MATRIX_DIAG_12_GOLDILOCKS,
+    MATRIX_DIAG_16_GOLDILOCKS, MATRIX_DIAG_20_GOLDILOCKS, MATRIX_DIAG_8_GOLDILOCKS,
+};
+
+impl Permutation<[PackedGoldilocksAVX512; 8]> for DiffusionMatrixGoldilocks {
+    fn permute_mut(&self, state: &mut [PackedGoldilocksAVX512; 8]) {
+        matmul_internal::<Goldilocks, PackedGoldilocksAVX512, 8>(state, MATRIX_DIAG_8_GOLDILOCKS);
+    }
+}
+
+impl DiffusionPermutation<PackedGoldilocksAVX512, 8> for DiffusionMatrixGoldilocks {}
+
+impl Permutation<[PackedGoldilocksAVX512; 12]> for DiffusionMatrixGoldilocks {
+    fn permute_mut(&self, state: &mut [PackedGoldilocksAVX512; 12]) {
+        matmul_internal::<Goldilocks, PackedGoldilocksAVX512, 12>(state, MATRIX_DIAG_12_GOLDILOCKS);
+    }
+}
+
+impl DiffusionPermutation<PackedGoldilocksAVX512, 12> for DiffusionMatrixGoldilocks {}
+
+impl Permutation<[PackedGoldilocksAVX512; 16]> for DiffusionMatrixGoldilocks {
+    fn permute_mut(&self, state: &mut [PackedGoldilocksAVX512; 16]) {
+        matmul_internal::<Goldilocks, PackedGoldilocksAVX512, 16>(state, MATRIX_DIAG_16_GOLDILOCKS);
+    }
+}
+
+impl DiffusionPermutation<PackedGoldilocksAVX512, 16> for DiffusionMatrixGoldilocks {}
+
+impl Permutation<[PackedGoldilocksAVX512; 20]> for DiffusionMatrixGoldilocks {
+    fn permute_mut(&self, state: &mut [PackedGoldilocksAVX512; 20]) {
+        matmul_internal::<Goldilocks, PackedGoldilocksAVX512, 20>(state, MATRIX_DIAG_20_GOLDILOCKS);
+    }
+}
+
+impl DiffusionPermutation<PackedGoldilocksAVX512, 20> for DiffusionMatrixGoldilocks {}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::AbstractField;
+    use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+    use rand::Rng;
+
+    use super::*;
+
+    type F = Goldilocks;
+    const D: u64 = 7;
+    type Perm8 = Poseidon2<F, Poseidon2ExternalMatrixGeneral, DiffusionMatrixGoldilocks, 8, D>;
+
+    /// Test that the output is the same as the scalar version on a random input.
+    #[test]
+    fn test_avx512_poseidon2_width_8() {
+        let mut rng = rand::thread_rng();
+
+        // Our Poseidon2 implementation.
+        let poseidon2 = Perm8::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixGoldilocks,
+            &mut rng,
+        );
+
+        let input: [F; 8] = rng.gen();
+
+        let mut expected = input;
+        poseidon2.permute_mut(&mut expected);
+
+        let mut avx512_input = input.map(PackedGoldilocksAVX512::from_f);
+        poseidon2.permute_mut(&mut avx512_input);
+
+        let avx512_output = avx512_input.map(|x| x.0[0]);
+
+        assert_eq!(avx512_output, expected);
+    }
+}
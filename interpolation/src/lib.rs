@@ -4,13 +4,18 @@
 
 extern crate alloc;
 
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
 use alloc::vec::Vec;
+use core::mem;
 
 use p3_field::{
     batch_multiplicative_inverse, cyclic_subgroup_coset_known_order, scale_vec,
-    two_adic_coset_zerofier, ExtensionField, Field, TwoAdicField,
+    two_adic_coset_zerofier, AbstractExtensionField, AbstractField, ExtensionField, Field,
+    PackedValue, TwoAdicField,
 };
 use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
 use p3_util::log2_strict_usize;
 
 /// Given evaluations of a batch of polynomials over the canonical power-of-two subgroup, evaluate
@@ -62,6 +67,172 @@ where
     scale_vec(zerofier * denominator.inverse(), sum)
 }
 
+/// Given evaluations of a batch of polynomials over the given coset of the canonical power-of-two
+/// subgroup, evaluate the polynomials at each of `points`.
+///
+/// This is equivalent to calling `interpolate_coset` for each point, but the denominators of all
+/// points are inverted in a single batch, and the matrix is read once for all points, with the
+/// rows packed.
+///
+/// This assumes no point is in the coset, otherwise the behavior is undefined.
+pub fn interpolate_coset_batch<F, EF, Mat>(
+    coset_evals: &Mat,
+    shift: F,
+    points: &[EF],
+) -> Vec<Vec<EF>>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    Mat: Matrix<F>,
+{
+    if points.is_empty() || coset_evals.width() == 0 {
+        return vec![vec![]; points.len()];
+    }
+    let log_height = log2_strict_usize(coset_evals.height());
+    let diffs: Vec<EF> = coset_diffs(log_height, shift, points).collect();
+    let mut col_scales = batch_multiplicative_inverse(&diffs);
+    scale_by_subgroup(log_height, points.len(), &mut col_scales);
+    interpolate_with_col_scales(coset_evals, shift, points, &col_scales)
+}
+
+/// Given evaluations of batches of polynomials over canonical power-of-two subgroups, of any
+/// heights, evaluate each batch at each of `points`. The result is indexed by matrix, then point.
+///
+/// This is equivalent to calling `interpolate_subgroup` for each matrix and point, but the
+/// barycentric weights are computed once per height, with a single batch inversion across all
+/// heights and points, and each matrix is read once for all points.
+///
+/// This assumes no point is in any of the subgroups, otherwise the behavior is undefined.
+pub fn interpolate_batch<F, EF, Mat>(matrices: &[Mat], points: &[EF]) -> Vec<Vec<Vec<EF>>>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    Mat: Matrix<F>,
+{
+    let num_points = points.len();
+    let log_heights: BTreeSet<usize> = matrices
+        .iter()
+        .map(|mat| log2_strict_usize(mat.height()))
+        .collect();
+
+    let diffs: Vec<EF> = log_heights
+        .iter()
+        .flat_map(|&log_height| coset_diffs(log_height, F::ONE, points))
+        .collect();
+    let mut all_col_scales = batch_multiplicative_inverse(&diffs);
+
+    let mut col_scales_by_log_height = BTreeMap::new();
+    let mut rest = all_col_scales.as_mut_slice();
+    for log_height in log_heights {
+        let (col_scales, next) = mem::take(&mut rest).split_at_mut(num_points << log_height);
+        scale_by_subgroup(log_height, num_points, col_scales);
+        col_scales_by_log_height.insert(log_height, &*col_scales);
+        rest = next;
+    }
+
+    matrices
+        .iter()
+        .map(|mat| {
+            if num_points == 0 || mat.width() == 0 {
+                return vec![vec![]; num_points];
+            }
+            let col_scales = col_scales_by_log_height[&log2_strict_usize(mat.height())];
+            interpolate_with_col_scales(mat, F::ONE, points, col_scales)
+        })
+        .collect()
+}
+
+/// The differences `z - shift g^i`, where `g` generates the subgroup of order `2^log_height`, for
+/// each `i` and then each point `z`.
+fn coset_diffs<'a, F, EF>(
+    log_height: usize,
+    shift: F,
+    points: &'a [EF],
+) -> impl Iterator<Item = EF> + 'a
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F>,
+{
+    let g = F::two_adic_generator(log_height);
+    cyclic_subgroup_coset_known_order(g, shift, 1 << log_height)
+        .flat_map(move |subgroup_i| points.iter().map(move |&point| point - subgroup_i))
+}
+
+/// Turn the inverses of `coset_diffs` into the barycentric weights `g^i / (z - shift g^i)`.
+fn scale_by_subgroup<F, EF>(log_height: usize, num_points: usize, diff_invs: &mut [EF])
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F>,
+{
+    let g = F::two_adic_generator(log_height);
+    for (scales, sg) in diff_invs.chunks_exact_mut(num_points).zip(g.powers()) {
+        scales.iter_mut().for_each(|scale| *scale *= sg);
+    }
+}
+
+/// Evaluate the columns of `coset_evals` at each of `points`, given the barycentric weights from
+/// `scale_by_subgroup`, for a nonempty matrix and set of points.
+fn interpolate_with_col_scales<F, EF, Mat>(
+    coset_evals: &Mat,
+    shift: F,
+    points: &[EF],
+    col_scales: &[EF],
+) -> Vec<Vec<EF>>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    Mat: Matrix<F>,
+{
+    let num_points = points.len();
+    let height = coset_evals.height();
+    let width = coset_evals.width();
+    let log_height = log2_strict_usize(height);
+
+    let packed_width = width.div_ceil(F::Packing::WIDTH);
+    let packed_sums = coset_evals
+        .par_padded_horizontally_packed_rows::<F::Packing>()
+        .zip(col_scales.par_chunks_exact(num_points))
+        .par_fold_reduce(
+            || EF::ExtensionPacking::zero_vec(num_points * packed_width),
+            |mut acc, (row, scales)| {
+                for (j, packed_evals) in row.enumerate() {
+                    for (acc_for_point, &scale) in
+                        acc[j..].iter_mut().step_by(packed_width).zip(scales)
+                    {
+                        let scale = EF::ExtensionPacking::from_base_fn(|i| {
+                            F::Packing::from(scale.as_base_slice()[i])
+                        });
+                        *acc_for_point += scale * packed_evals;
+                    }
+                }
+                acc
+            },
+            |mut acc_l, acc_r| {
+                acc_l.iter_mut().zip(acc_r).for_each(|(l, r)| *l += r);
+                acc_l
+            },
+        );
+
+    let denominator = F::from_canonical_usize(height) * shift.exp_u64(height as u64 - 1);
+    let denominator_inv = denominator.inverse();
+    packed_sums
+        .chunks_exact(packed_width)
+        .zip(points)
+        .map(|(sums, &point)| {
+            let zerofier = two_adic_coset_zerofier::<EF>(log_height, EF::from_base(shift), point);
+            let scale = zerofier * denominator_inv;
+            sums.iter()
+                .flat_map(|p| {
+                    (0..F::Packing::WIDTH)
+                        .map(move |i| EF::from_base_fn(|j| p.as_base_slice()[j].as_slice()[i]))
+                })
+                .take(width)
+                .map(|sum| sum * scale)
+                .collect()
+        })
+        .collect()
+}
+
 /// `x += y * s`, where `s` is a scalar.
 pub fn add_scaled_base_slice_in_place<F, EF, Y>(x: &mut [EF], y: Y, s: EF)
 where
@@ -76,12 +247,17 @@ where
 #[cfg(test)]
 mod tests {
     use alloc::vec;
+    use alloc::vec::Vec;
 
     use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
     use p3_field::{AbstractField, Field};
     use p3_matrix::dense::RowMajorMatrix;
+    use rand::{thread_rng, Rng};
 
-    use crate::{interpolate_coset, interpolate_subgroup};
+    use crate::{
+        interpolate_batch, interpolate_coset, interpolate_coset_batch, interpolate_subgroup,
+    };
 
     #[test]
     fn test_interpolate_subgroup() {
@@ -111,4 +287,37 @@ mod tests {
         let result = interpolate_coset(&evals_mat, shift, point);
         assert_eq!(result, vec![F::from_canonical_u32(10203)]);
     }
+
+    #[test]
+    fn test_interpolate_coset_batch() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let shift = F::GENERATOR;
+        let evals_mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 32, 11);
+        let points: Vec<EF> = (0..3).map(|_| thread_rng().gen()).collect();
+        let result = interpolate_coset_batch(&evals_mat, shift, &points);
+        assert_eq!(result.len(), points.len());
+        for (ys, &point) in result.iter().zip(&points) {
+            assert_eq!(*ys, interpolate_coset(&evals_mat, shift, point));
+        }
+        assert!(interpolate_coset_batch::<F, EF, _>(&evals_mat, shift, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_interpolate_batch() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let mats = [(16, 3), (4, 9), (16, 1), (1, 2)]
+            .map(|(h, w)| RowMajorMatrix::<F>::rand(&mut thread_rng(), h, w));
+        let points: Vec<EF> = (0..3).map(|_| thread_rng().gen()).collect();
+        let result = interpolate_batch(&mats, &points);
+        assert_eq!(result.len(), mats.len());
+        for (ys_for_mat, mat) in result.iter().zip(&mats) {
+            let expected: Vec<_> = points
+                .iter()
+                .map(|&point| interpolate_subgroup(mat, point))
+                .collect();
+            assert_eq!(*ys_for_mat, expected);
+        }
+    }
 }
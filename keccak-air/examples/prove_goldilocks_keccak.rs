@@ -4,7 +4,7 @@ use p3_challenger::{HashChallenger, SerializingChallenger64};
 use p3_commit::ExtensionMmcs;
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
 use p3_goldilocks::Goldilocks;
 use p3_keccak::Keccak256Hash;
 use p3_keccak_air::{generate_trace_rows, KeccakAir};
@@ -57,8 +57,11 @@ fn main() -> Result<(), impl Debug> {
 
     let fri_config = FriConfig {
         log_blowup: 1,
+        log_final_poly_len: 0,
         num_queries: 100,
         proof_of_work_bits: 16,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
         mmcs: challenge_mmcs,
     };
     type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
@@ -68,8 +71,20 @@ fn main() -> Result<(), impl Debug> {
     let config = MyConfig::new(pcs);
 
     let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let proof = prove(&config, &KeccakAir {}, &mut challenger, trace, &vec![]);
+    let proof = prove(
+        &config,
+        &KeccakAir::default(),
+        &mut challenger,
+        trace,
+        &vec![],
+    );
 
     let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    verify(&config, &KeccakAir {}, &mut challenger, &proof, &vec![])
+    verify(
+        &config,
+        &KeccakAir::default(),
+        &mut challenger,
+        &proof,
+        &vec![],
+    )
 }
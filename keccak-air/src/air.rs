@@ -1,6 +1,8 @@
 use core::borrow::Borrow;
 
-use p3_air::{Air, AirBuilder, BaseAir};
+use alloc::vec::Vec;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithLookups, BaseAir, BaseSubAir, SubAir, SubBuilder};
 use p3_field::AbstractField;
 use p3_matrix::Matrix;
 
@@ -11,8 +13,22 @@ use crate::round_flags::eval_round_flags;
 use crate::{BITS_PER_LIMB, NUM_ROUNDS, U64_LIMBS};
 
 /// Assumes the field size is at least 16 bits.
-#[derive(Debug)]
-pub struct KeccakAir {}
+///
+/// As a sub-AIR of a larger AIR, with `with_bus`, it also receives each exported permutation from
+/// a bus, so that other pieces of the AIR can use it with `KeccakBuilder`.
+#[derive(Debug, Default)]
+pub struct KeccakAir {
+    bus: Option<usize>,
+}
+
+impl KeccakAir {
+    /// Receive each exported permutation from `bus`, as the limbs of its input followed by those
+    /// of its output, in the order of `state_limbs`, when this is a sub-AIR.
+    pub fn with_bus(mut self, bus: usize) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+}
 
 impl<F> BaseAir<F> for KeccakAir {
     fn width(&self) -> usize {
@@ -190,3 +206,31 @@ impl<AB: AirBuilder> Air<AB> for KeccakAir {
         }
     }
 }
+
+impl BaseSubAir for KeccakAir {
+    fn width(&self) -> usize {
+        NUM_KECCAK_COLS
+    }
+}
+
+impl<AB: AirBuilderWithLookups> SubAir<AB> for KeccakAir {
+    fn eval(&self, builder: &mut SubBuilder<'_, AB>) {
+        Air::eval(self, builder);
+
+        let Some(bus) = self.bus else {
+            return;
+        };
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &KeccakCols<AB::Var> = (*local).borrow();
+        let mut values: Vec<AB::Var> = local.preimage.iter().flatten().flatten().copied().collect();
+        for y in 0..5 {
+            for x in 0..5 {
+                for limb in 0..U64_LIMBS {
+                    values.push(local.a_prime_prime_prime(y, x, limb));
+                }
+            }
+        }
+        builder.receive(bus, values, local.export);
+    }
+}
@@ -0,0 +1,22 @@
+use p3_air::AirBuilderWithLookups;
+
+/// Permutations checked by a `KeccakAir`, for any AIR with lookups.
+///
+/// Lookups only balance within an AIR, so the `KeccakAir` must be a sub-AIR of the same AIR, with
+/// `KeccakAir::with_bus(bus)`. Each permutation it exports is received once, so a permutation used
+/// twice needs to be proven twice.
+pub trait KeccakBuilder: AirBuilderWithLookups {
+    /// Assert that `output` is the Keccak-f permutation of `input`, `multiplicity` times, where
+    /// both are limbs in the order of `state_limbs`.
+    fn keccak_f<I, O, M>(&mut self, bus: usize, input: I, output: O, multiplicity: M)
+    where
+        I: IntoIterator,
+        I::Item: Into<Self::Expr>,
+        O: IntoIterator<Item = I::Item>,
+        M: Into<Self::Expr>,
+    {
+        self.send(bus, input.into_iter().chain(output), multiplicity);
+    }
+}
+
+impl<AB: AirBuilderWithLookups> KeccakBuilder for AB {}
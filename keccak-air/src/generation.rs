@@ -1,16 +1,20 @@
 use alloc::vec::Vec;
 
-use p3_field::PrimeField64;
+use p3_field::{PackedValue, PrimeField64};
 use p3_matrix::dense::RowMajorMatrix;
-use p3_maybe_rayon::iter::repeat;
 use p3_maybe_rayon::prelude::*;
 use tracing::instrument;
 
 use crate::columns::{KeccakCols, NUM_KECCAK_COLS};
-use crate::constants::rc_value_limb;
-use crate::logic::{andn, xor};
+use crate::constants::{R, RC};
 use crate::{BITS_PER_LIMB, NUM_ROUNDS, U64_LIMBS};
 
+/// The trace of the permutations of `inputs`, each of which is exported on its last row. The
+/// inputs are padded with permutations of zero which aren't.
+///
+/// The permutations are split into chunks of `F::Packing::WIDTH`, which are generated in
+/// parallel. Within a chunk, the permutations advance one round at a time, and the bit columns
+/// are written a packed field element at a time.
 // TODO: Take generic iterable
 #[instrument(name = "generate Keccak trace", skip_all)]
 pub fn generate_trace_rows<F: PrimeField64>(inputs: Vec<[u64; 25]>) -> RowMajorMatrix<F> {
@@ -21,143 +25,115 @@ pub fn generate_trace_rows<F: PrimeField64>(inputs: Vec<[u64; 25]>) -> RowMajorM
     assert!(suffix.is_empty(), "Alignment should match");
     assert_eq!(rows.len(), num_rows);
 
-    let num_padding_inputs = num_rows.div_ceil(NUM_ROUNDS) - inputs.len();
-    let padded_inputs = inputs
-        .into_par_iter()
-        .chain(repeat([0; 25]).take(num_padding_inputs));
+    let mut padded_inputs: Vec<([u64; 25], bool)> =
+        inputs.into_iter().map(|input| (input, true)).collect();
+    padded_inputs.resize(num_rows.div_ceil(NUM_ROUNDS), ([0; 25], false));
 
-    rows.par_chunks_mut(NUM_ROUNDS)
-        .zip(padded_inputs)
-        .for_each(|(row, input)| {
-            generate_trace_rows_for_perm(row, input);
-        });
+    let perms_per_chunk = F::Packing::WIDTH;
+    rows.par_chunks_mut(perms_per_chunk * NUM_ROUNDS)
+        .zip(padded_inputs.par_chunks(perms_per_chunk))
+        .for_each(|(rows, inputs)| generate_trace_rows_for_perms(rows, inputs));
 
     trace
 }
 
-/// `rows` will normally consist of 24 rows, with an exception for the final row.
-fn generate_trace_rows_for_perm<F: PrimeField64>(rows: &mut [KeccakCols<F>], input: [u64; 25]) {
-    // Populate the preimage for each row.
-    for row in rows.iter_mut() {
-        for y in 0..5 {
-            for x in 0..5 {
-                let input_xy = input[y * 5 + x];
-                for limb in 0..U64_LIMBS {
-                    row.preimage[y][x][limb] =
-                        F::from_canonical_u64((input_xy >> (16 * limb)) & 0xFFFF);
+/// `rows` will normally consist of 24 rows per permutation, with an exception for the final
+/// permutation, which may be cut short.
+fn generate_trace_rows_for_perms<F: PrimeField64>(
+    rows: &mut [KeccakCols<F>],
+    inputs: &[([u64; 25], bool)],
+) {
+    let mut states: Vec<[u64; 25]> = inputs.iter().map(|&(input, _)| input).collect();
+
+    for round in 0..NUM_ROUNDS {
+        for (perm, state) in states.iter_mut().enumerate() {
+            let Some(row) = rows.get_mut(perm * NUM_ROUNDS + round) else {
+                continue;
+            };
+            let (input, export) = inputs[perm];
+
+            // Populate the preimage, and the round input, which is the previous round's output.
+            for y in 0..5 {
+                for x in 0..5 {
+                    write_limbs(&mut row.preimage[y][x], input[y * 5 + x]);
+                    write_limbs(&mut row.a[y][x], state[y * 5 + x]);
                 }
             }
-        }
-    }
-
-    // Populate the round input for the first round.
-    for y in 0..5 {
-        for x in 0..5 {
-            let input_xy = input[y * 5 + x];
-            for limb in 0..U64_LIMBS {
-                rows[0].a[y][x][limb] = F::from_canonical_u64((input_xy >> (16 * limb)) & 0xFFFF);
-            }
-        }
-    }
 
-    generate_trace_row_for_round(&mut rows[0], 0);
+            generate_trace_row_for_round(row, round, state);
 
-    for round in 1..rows.len() {
-        // Copy previous row's output to next row's input.
-        for y in 0..5 {
-            for x in 0..5 {
-                for limb in 0..U64_LIMBS {
-                    rows[round].a[y][x][limb] = rows[round - 1].a_prime_prime_prime(y, x, limb);
-                }
+            if export && round == NUM_ROUNDS - 1 {
+                row.export = F::ONE;
             }
         }
-
-        generate_trace_row_for_round(&mut rows[round], round);
     }
 }
 
-fn generate_trace_row_for_round<F: PrimeField64>(row: &mut KeccakCols<F>, round: usize) {
+/// Populate `row` for `round` of the permutation of `state`, and advance `state` by the round.
+fn generate_trace_row_for_round<F: PrimeField64>(
+    row: &mut KeccakCols<F>,
+    round: usize,
+    state: &mut [u64; 25],
+) {
     row.step_flags[round] = F::ONE;
 
     // Populate C[x] = xor(A[x, 0], A[x, 1], A[x, 2], A[x, 3], A[x, 4]).
-    for x in 0..5 {
-        for z in 0..64 {
-            let limb = z / BITS_PER_LIMB;
-            let bit_in_limb = z % BITS_PER_LIMB;
-            let a = (0..5).map(|y| {
-                let a_limb = row.a[y][x][limb].as_canonical_u64() as u16;
-                ((a_limb >> bit_in_limb) & 1) != 0
-            });
-            row.c[x][z] = F::from_bool(a.fold(false, |acc, x| acc ^ x));
-        }
-    }
+    let c: [u64; 5] = core::array::from_fn(|x| (0..5).fold(0, |acc, y| acc ^ state[y * 5 + x]));
 
     // Populate C'[x, z] = xor(C[x, z], C[x - 1, z], C[x + 1, z - 1]).
+    let c_prime: [u64; 5] =
+        core::array::from_fn(|x| c[x] ^ c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1));
+
     for x in 0..5 {
-        for z in 0..64 {
-            row.c_prime[x][z] = xor([
-                row.c[x][z],
-                row.c[(x + 4) % 5][z],
-                row.c[(x + 1) % 5][(z + 63) % 64],
-            ]);
-        }
+        write_bits(&mut row.c[x], c[x]);
+        write_bits(&mut row.c_prime[x], c_prime[x]);
     }
 
     // Populate A'. To avoid shifting indices, we rewrite
     //     A'[x, y, z] = xor(A[x, y, z], C[x - 1, z], C[x + 1, z - 1])
     // as
     //     A'[x, y, z] = xor(A[x, y, z], C[x, z], C'[x, z]).
-    for x in 0..5 {
-        for y in 0..5 {
-            for z in 0..64 {
-                let limb = z / BITS_PER_LIMB;
-                let bit_in_limb = z % BITS_PER_LIMB;
-                let a_limb = row.a[y][x][limb].as_canonical_u64() as u16;
-                let a_bit = F::from_bool(((a_limb >> bit_in_limb) & 1) != 0);
-                row.a_prime[y][x][z] = xor([a_bit, row.c[x][z], row.c_prime[x][z]]);
-            }
+    let a_prime: [u64; 25] = core::array::from_fn(|i| state[i] ^ c[i % 5] ^ c_prime[i % 5]);
+    for y in 0..5 {
+        for x in 0..5 {
+            write_bits(&mut row.a_prime[y][x], a_prime[y * 5 + x]);
         }
     }
 
+    // B is a rotation of A', as in `KeccakCols::b`.
+    let b = |x: usize, y: usize| {
+        let a = (x + 3 * y) % 5;
+        a_prime[x * 5 + a].rotate_left(R[a][x] as u32)
+    };
+
     // Populate A''.
     // A''[x, y] = xor(B[x, y], andn(B[x + 1, y], B[x + 2, y])).
     for y in 0..5 {
         for x in 0..5 {
-            for limb in 0..U64_LIMBS {
-                row.a_prime_prime[y][x][limb] = (limb * BITS_PER_LIMB..(limb + 1) * BITS_PER_LIMB)
-                    .rev()
-                    .fold(F::ZERO, |acc, z| {
-                        let bit = xor([
-                            row.b(x, y, z),
-                            andn(row.b((x + 1) % 5, y, z), row.b((x + 2) % 5, y, z)),
-                        ]);
-                        acc.double() + bit
-                    });
-            }
+            state[y * 5 + x] = b(x, y) ^ (!b((x + 1) % 5, y) & b((x + 2) % 5, y));
+            write_limbs(&mut row.a_prime_prime[y][x], state[y * 5 + x]);
         }
     }
 
     // For the XOR, we split A''[0, 0] to bits.
-    let mut val = 0;
-    for limb in 0..U64_LIMBS {
-        let val_limb = row.a_prime_prime[0][0][limb].as_canonical_u64();
-        val |= val_limb << (limb * BITS_PER_LIMB);
-    }
-    let val_bits: Vec<bool> = (0..64)
-        .scan(val, |acc, _| {
-            let bit = (*acc & 1) != 0;
-            *acc >>= 1;
-            Some(bit)
-        })
-        .collect();
-    for (i, bit) in row.a_prime_prime_0_0_bits.iter_mut().enumerate() {
-        *bit = F::from_bool(val_bits[i]);
-    }
+    write_bits(&mut row.a_prime_prime_0_0_bits, state[0]);
 
     // A''[0, 0] is additionally xor'd with RC.
-    for limb in 0..U64_LIMBS {
-        let rc_lo = rc_value_limb(round, limb);
-        row.a_prime_prime_prime_0_0_limbs[limb] =
-            F::from_canonical_u16(row.a_prime_prime[0][0][limb].as_canonical_u64() as u16 ^ rc_lo);
+    state[0] ^= RC[round];
+    write_limbs(&mut row.a_prime_prime_prime_0_0_limbs, state[0]);
+}
+
+/// Write the bits of `value` to `bits`, a packed field element at a time.
+fn write_bits<F: PrimeField64>(bits: &mut [F; 64], value: u64) {
+    let width = F::Packing::WIDTH;
+    for (i, packed) in F::Packing::pack_slice_mut(bits).iter_mut().enumerate() {
+        *packed = PackedValue::from_fn(|j| F::from_bool((value >> (i * width + j)) & 1 != 0));
+    }
+}
+
+/// Write the 16-bit limbs of `value` to `limbs`.
+fn write_limbs<F: PrimeField64>(limbs: &mut [F; U64_LIMBS], value: u64) {
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        *limb = F::from_canonical_u64((value >> (i * BITS_PER_LIMB)) & 0xFFFF);
     }
 }
@@ -5,16 +5,20 @@
 extern crate alloc;
 
 mod air;
+mod builder;
 mod columns;
 mod constants;
 mod generation;
 mod logic;
 mod round_flags;
+mod sponge;
 
 pub use air::*;
+pub use builder::*;
 pub use columns::*;
 pub use constants::*;
 pub use generation::*;
+pub use sponge::*;
 
 pub const NUM_ROUNDS: usize = 24;
 const BITS_PER_LIMB: usize = 16;
@@ -0,0 +1,82 @@
+use alloc::vec::Vec;
+
+use p3_field::AbstractField;
+use tiny_keccak::keccakf;
+
+use crate::{BITS_PER_LIMB, RATE_BITS, U64_LIMBS};
+
+/// The number of bytes of a message absorbed by each permutation of the Keccak-256 sponge.
+pub const KECCAK_RATE_BYTES: usize = RATE_BITS / 8;
+
+/// The number of lanes of the state which a block of the Keccak-256 sponge is XORed into.
+pub const KECCAK_RATE_LANES: usize = KECCAK_RATE_BYTES / 8;
+
+/// The number of limbs of a state, as in `state_limbs`.
+pub const KECCAK_STATE_LIMBS: usize = 25 * U64_LIMBS;
+
+/// The limbs of `state` in the order of the preimage columns of `KeccakCols`: lane by lane, each
+/// from its least significant limb.
+pub fn state_limbs<F: AbstractField>(state: &[u64; 25]) -> Vec<F> {
+    state
+        .iter()
+        .flat_map(|&lane| {
+            (0..U64_LIMBS)
+                .map(move |limb| F::from_canonical_u64((lane >> (BITS_PER_LIMB * limb)) & 0xFFFF))
+        })
+        .collect()
+}
+
+/// The blocks absorbed by the Keccak-256 sponge for `message`, i.e. the message with the
+/// multi-rate padding, as little-endian lanes.
+pub fn keccak256_blocks(message: &[u8]) -> Vec<[u64; KECCAK_RATE_LANES]> {
+    let mut padded = message.to_vec();
+    padded.push(0x01);
+    padded.resize(padded.len().next_multiple_of(KECCAK_RATE_BYTES), 0);
+    *padded.last_mut().unwrap() |= 0x80;
+
+    padded
+        .chunks_exact(KECCAK_RATE_BYTES)
+        .map(|block| {
+            core::array::from_fn(|i| {
+                u64::from_le_bytes(block[8 * i..8 * (i + 1)].try_into().unwrap())
+            })
+        })
+        .collect()
+}
+
+/// The permutations of the Keccak-256 sponge hashing a message, which `generate_trace_rows` takes
+/// as its inputs, and the digest they give.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeccakSponge {
+    /// The input of each permutation: the previous output with the next block XORed into it.
+    pub inputs: Vec<[u64; 25]>,
+    /// The output of each permutation.
+    pub outputs: Vec<[u64; 25]>,
+    /// The first four lanes of the last output, as bytes.
+    pub digest: [u8; 32],
+}
+
+impl KeccakSponge {
+    pub fn keccak256(message: &[u8]) -> Self {
+        let mut state = [0; 25];
+        let (mut inputs, mut outputs) = (Vec::new(), Vec::new());
+        for block in keccak256_blocks(message) {
+            for (lane, word) in state.iter_mut().zip(block) {
+                *lane ^= word;
+            }
+            inputs.push(state);
+            keccakf(&mut state);
+            outputs.push(state);
+        }
+
+        let mut digest = [0; 32];
+        for (bytes, lane) in digest.chunks_exact_mut(8).zip(state) {
+            bytes.copy_from_slice(&lane.to_le_bytes());
+        }
+        Self {
+            inputs,
+            outputs,
+            digest,
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use core::borrow::Borrow;
+
+use p3_air::{Air, AirBuilderWithLookups, BaseAir, SubAir, SubBuilder};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_keccak::Keccak256Hash;
+use p3_keccak_air::{
+    generate_trace_rows, state_limbs, KeccakAir, KeccakBuilder, KeccakCols, KeccakSponge,
+    KECCAK_RATE_BYTES, KECCAK_STATE_LIMBS, NUM_KECCAK_COLS, NUM_ROUNDS, U64_LIMBS,
+};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{CryptographicHasher, PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{check_constraints, prove, verify, StarkConfig};
+use rand::{thread_rng, Rng};
+
+type Val = BabyBear;
+
+fn random_message(len: usize) -> Vec<u8> {
+    let mut rng = thread_rng();
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+#[test]
+fn test_keccak256_sponge() {
+    for len in [0, KECCAK_RATE_BYTES - 1, KECCAK_RATE_BYTES, 300] {
+        let message = random_message(len);
+        let sponge = KeccakSponge::keccak256(&message);
+        assert_eq!(sponge.inputs.len(), len / KECCAK_RATE_BYTES + 1);
+        assert_eq!(sponge.digest, Keccak256Hash.hash_iter(message));
+    }
+}
+
+#[test]
+fn test_trace_exports_outputs() {
+    let sponge = KeccakSponge::keccak256(&random_message(200));
+    let trace = generate_trace_rows::<Val>(sponge.inputs.clone());
+
+    for i in 0..trace.height() {
+        let row = trace.row_slice(i);
+        let cols: &KeccakCols<Val> = (*row).borrow();
+        let perm = i / NUM_ROUNDS;
+        let exported = i % NUM_ROUNDS == NUM_ROUNDS - 1 && perm < sponge.inputs.len();
+        assert_eq!(cols.export, Val::from_bool(exported));
+        if exported {
+            let preimage: Vec<Val> = cols.preimage.iter().flatten().flatten().copied().collect();
+            assert_eq!(preimage, state_limbs::<Val>(&sponge.inputs[perm]));
+            let output: Vec<Val> = (0..25)
+                .flat_map(|lane| (0..U64_LIMBS).map(move |limb| (lane / 5, lane % 5, limb)))
+                .map(|(y, x, limb)| cols.a_prime_prime_prime(y, x, limb))
+                .collect();
+            assert_eq!(output, state_limbs::<Val>(&sponge.outputs[perm]));
+        }
+    }
+}
+
+#[test]
+fn test_trace_satisfies_constraints() {
+    // Several chunks of permutations, the last of which is cut short by the padding.
+    for num_perms in [1, 3, 37] {
+        let mut rng = thread_rng();
+        let inputs: Vec<[u64; 25]> = (0..num_perms).map(|_| rng.gen()).collect();
+        let trace = generate_trace_rows::<Val>(inputs);
+        assert_eq!(
+            check_constraints(&KeccakAir::default(), &trace, &vec![]),
+            Ok(())
+        );
+    }
+}
+
+const BUS: usize = 0;
+
+/// The columns of the chip: the limbs of an input and an output, and whether the row uses the
+/// permutation.
+const CHIP_WIDTH: usize = 2 * KECCAK_STATE_LIMBS + 1;
+
+/// A chip which uses the permutations of a `KeccakAir` in the same AIR.
+struct KeccakChipAir {
+    keccak: KeccakAir,
+}
+
+impl<F> BaseAir<F> for KeccakChipAir {
+    fn width(&self) -> usize {
+        CHIP_WIDTH + NUM_KECCAK_COLS
+    }
+}
+
+impl<AB: AirBuilderWithLookups> Air<AB> for KeccakChipAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local: Vec<AB::Var> = main.row_slice(0).to_vec();
+        let (input, output) = local[..2 * KECCAK_STATE_LIMBS].split_at(KECCAK_STATE_LIMBS);
+        let enabled = local[2 * KECCAK_STATE_LIMBS];
+        builder.assert_bool(enabled);
+        builder.keccak_f(BUS, input.iter().copied(), output.iter().copied(), enabled);
+
+        let columns = CHIP_WIDTH..CHIP_WIDTH + NUM_KECCAK_COLS;
+        SubAir::eval(&self.keccak, &mut SubBuilder::new(builder, columns));
+    }
+}
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+#[test]
+fn test_permutations_from_another_chip() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    // Two blocks, so two permutations on the first 48 of 64 rows.
+    let sponge = KeccakSponge::keccak256(&random_message(200));
+    let keccak_trace = generate_trace_rows::<Val>(sponge.inputs.clone());
+    let air = KeccakChipAir {
+        keccak: KeccakAir::default().with_bus(BUS),
+    };
+
+    let mut values = Vec::new();
+    for (i, keccak_row) in keccak_trace.rows().enumerate() {
+        let perm = i / NUM_ROUNDS;
+        if i % NUM_ROUNDS == NUM_ROUNDS - 1 && perm < sponge.inputs.len() {
+            values.extend(state_limbs::<Val>(&sponge.inputs[perm]));
+            values.extend(state_limbs::<Val>(&sponge.outputs[perm]));
+            values.push(Val::ONE);
+        } else {
+            values.extend([Val::ZERO; CHIP_WIDTH]);
+        }
+        values.extend(keccak_row);
+    }
+    let trace = RowMajorMatrix::new(values, BaseAir::<Val>::width(&air));
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
+}
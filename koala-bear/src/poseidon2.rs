@@ -94,9 +94,10 @@ impl PackedFieldPoseidon2Helpers<KoalaBearParameters> for KoalaBearDiffusionMatr
 #[cfg(test)]
 mod tests {
     use p3_field::AbstractField;
+    use p3_monty_31::GenericDiffusionMatrixMontyField31;
     use p3_poseidon2::{DiffusionPermutation, Poseidon2, Poseidon2ExternalMatrixGeneral};
     use p3_symmetric::Permutation;
-    use rand::SeedableRng;
+    use rand::{Rng, SeedableRng};
     use rand_xoshiro::Xoroshiro128Plus;
 
     use super::*;
@@ -171,4 +172,39 @@ mod tests {
         poseidon2_koalabear::<24, 3, _>(&mut input, DiffusionMatrixKoalaBear::default());
         assert_eq!(input, expected);
     }
+
+    /// Check the scalar internal layer, which uses shifts and a delayed reduction, against the
+    /// generic one, including on the inputs which make the unreduced sums largest. The reduction
+    /// divides by the Monty constant, so the scalar layer is the generic one times its inverse.
+    fn check_internal_layer<const WIDTH: usize>()
+    where
+        KoalaBearDiffusionMatrixParameters: DiffusionMatrixParameters<KoalaBearParameters, WIDTH>,
+    {
+        let mut rng = Xoroshiro128Plus::seed_from_u64(1);
+        let inputs = [
+            [F::ZERO; WIDTH],
+            [F::NEG_ONE; WIDTH],
+            core::array::from_fn(|i| if i == 0 { F::ZERO } else { F::NEG_ONE }),
+            rng.gen(),
+            rng.gen(),
+        ];
+        let generic = GenericDiffusionMatrixMontyField31::<
+            KoalaBearParameters,
+            KoalaBearDiffusionMatrixParameters,
+        >::new();
+        for input in inputs {
+            assert_eq!(
+                DiffusionMatrixKoalaBear::default().permute(input),
+                generic
+                    .permute(input)
+                    .map(|x| x * KoalaBearDiffusionMatrixParameters::MONTY_INVERSE)
+            );
+        }
+    }
+
+    #[test]
+    fn test_internal_layer_matches_generic() {
+        check_internal_layer::<16>();
+        check_internal_layer::<24>();
+    }
 }
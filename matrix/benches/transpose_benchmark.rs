@@ -5,6 +5,8 @@ use rand::thread_rng;
 fn transpose_benchmark(c: &mut Criterion) {
     const SMALL_DIMS: [(usize, usize); 4] = [(4, 4), (8, 8), (10, 10), (12, 12)];
     const LARGE_DIMS: [(usize, usize); 4] = [(20, 8), (21, 8), (22, 8), (23, 8)];
+    // Tall and narrow, like the traces and LDEs transposed by the six-step FFT.
+    const TALL_DIMS: [(usize, usize); 3] = [(20, 6), (22, 6), (24, 6)];
 
     let inner = |g: &mut BenchmarkGroup<_>, dims: &[(usize, usize)]| {
         let mut rng = thread_rng();
@@ -35,6 +37,7 @@ fn transpose_benchmark(c: &mut Criterion) {
     inner(&mut g, &SMALL_DIMS);
     g.sample_size(10);
     inner(&mut g, &LARGE_DIMS);
+    inner(&mut g, &TALL_DIMS);
 }
 
 criterion_group!(benches, transpose_benchmark);
@@ -11,6 +11,24 @@ use crate::Matrix;
 pub trait BitReversableMatrix<T: Send + Sync>: Matrix<T> {
     type BitRev: BitReversableMatrix<T>;
     fn bit_reverse_rows(self) -> Self::BitRev;
+
+    /// Convert to a dense matrix which is stored in `order`, i.e. whose row `reverse_bits(r)`
+    /// holds this matrix's row `r` for `RowOrder::BitReversed`.
+    ///
+    /// Rows are only permuted if they aren't stored in that order already, e.g. a bit-reversed
+    /// view of a dense matrix is converted to `RowOrder::BitReversed` without copying, so
+    /// consumers which want a particular order, like the Merkle commitments in FRI, can ask for
+    /// it rather than permuting unconditionally.
+    fn to_row_major_matrix_in_order(self, order: RowOrder) -> RowMajorMatrix<T>
+    where
+        Self: Sized,
+        T: Clone,
+    {
+        match order {
+            RowOrder::Natural => self.to_row_major_matrix(),
+            RowOrder::BitReversed => self.bit_reverse_rows().to_row_major_matrix(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -53,6 +71,90 @@ impl RowIndexMap for BitReversalPerm {
 
 pub type BitReversedMatrixView<Inner> = RowIndexMappedView<BitReversalPerm, Inner>;
 
+/// The order in which a matrix's rows are used, e.g. committed to and opened, relative to the order
+/// they're stored in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RowOrder {
+    /// Row `r` is stored row `r`.
+    #[default]
+    Natural,
+    /// Row `r` is stored row `reverse_bits(r)`. The height must be a power of two.
+    BitReversed,
+}
+
+impl RowOrder {
+    /// The order of the rows of a matrix stored in this order, once they're bit-reversed.
+    #[must_use]
+    pub const fn bit_reversed(self) -> Self {
+        match self {
+            Self::Natural => Self::BitReversed,
+            Self::BitReversed => Self::Natural,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RowOrderPerm {
+    order: RowOrder,
+    height: usize,
+    log_height: usize,
+}
+
+impl RowOrderPerm {
+    /// Panics if `order` is `RowOrder::BitReversed` and the inner matrix height isn't a power of
+    /// two.
+    pub fn new_view<T: Send + Sync, Inner: Matrix<T>>(
+        inner: Inner,
+        order: RowOrder,
+    ) -> RowOrderedMatrixView<Inner> {
+        let height = inner.height();
+        let log_height = match order {
+            RowOrder::Natural => 0,
+            RowOrder::BitReversed => log2_strict_usize(height),
+        };
+        RowIndexMappedView {
+            index_map: Self {
+                order,
+                height,
+                log_height,
+            },
+            inner,
+        }
+    }
+}
+
+impl RowOrderPerm {
+    pub const fn order(&self) -> RowOrder {
+        self.order
+    }
+}
+
+impl RowIndexMap for RowOrderPerm {
+    fn height(&self) -> usize {
+        self.height
+    }
+    fn map_row_index(&self, r: usize) -> usize {
+        match self.order {
+            RowOrder::Natural => r,
+            RowOrder::BitReversed => reverse_bits_len(r, self.log_height),
+        }
+    }
+    fn to_row_major_matrix<T: Clone + Send + Sync, Inner: Matrix<T>>(
+        &self,
+        inner: Inner,
+    ) -> RowMajorMatrix<T> {
+        let mut inner = inner.to_row_major_matrix();
+        if self.order == RowOrder::BitReversed {
+            reverse_matrix_index_bits(&mut inner);
+        }
+        inner
+    }
+}
+
+/// A matrix whose rows are used in a `RowOrder` chosen at runtime, so that matrices stored in
+/// different orders can be mixed without copying any of them.
+pub type RowOrderedMatrixView<Inner> = RowIndexMappedView<RowOrderPerm, Inner>;
+
 impl<T: Clone + Send + Sync, S: DenseStorage<T>> BitReversableMatrix<T>
     for BitReversedMatrixView<DenseMatrix<T, S>>
 {
@@ -62,6 +164,17 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> BitReversableMatrix<T>
     }
 }
 
+/// Bit-reversing the rows of a view with a runtime order just flips its order.
+impl<T: Clone + Send + Sync, S: DenseStorage<T>> BitReversableMatrix<T>
+    for RowOrderedMatrixView<DenseMatrix<T, S>>
+{
+    type BitRev = Self;
+    fn bit_reverse_rows(self) -> Self {
+        let order = self.index_map.order.bit_reversed();
+        RowOrderPerm::new_view(self.inner, order)
+    }
+}
+
 impl<T: Clone + Send + Sync, S: DenseStorage<T>> BitReversableMatrix<T> for DenseMatrix<T, S> {
     type BitRev = BitReversedMatrixView<DenseMatrix<T, S>>;
     fn bit_reverse_rows(self) -> Self::BitRev {
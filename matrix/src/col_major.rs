@@ -0,0 +1,219 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::BorrowMut;
+use core::marker::PhantomData;
+use core::{iter, slice};
+
+use itertools::Itertools;
+use p3_field::PackedValue;
+use serde::{Deserialize, Serialize};
+
+use crate::dense::{
+    transpose_values, DenseMatrix, DenseStorage, RowMajorMatrix, RowMajorMatrixView,
+};
+use crate::Matrix;
+
+/// A dense matrix stored in column-major form, i.e. with column `c` at
+/// `values[c * height..(c + 1) * height]`.
+///
+/// The values of a column-major matrix are those of its transpose in row-major form, so
+/// `from_transposed` and `as_transposed` convert between the two without copying.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColMajorMatrix<T, V = Vec<T>> {
+    pub values: V,
+    pub height: usize,
+    _phantom: PhantomData<T>,
+}
+
+pub type ColMajorMatrixView<'a, T> = ColMajorMatrix<T, &'a [T]>;
+pub type ColMajorMatrixViewMut<'a, T> = ColMajorMatrix<T, &'a mut [T]>;
+
+impl<T: Clone + Send + Sync, S: DenseStorage<T>> ColMajorMatrix<T, S> {
+    #[must_use]
+    pub fn new(values: S, height: usize) -> Self {
+        debug_assert!(height == 0 || values.borrow().len() % height == 0);
+        Self {
+            values,
+            height,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// View a row-major matrix as the column-major form of its transpose, i.e. its rows become
+    /// columns.
+    #[must_use]
+    pub fn from_transposed(mat: DenseMatrix<T, S>) -> Self {
+        Self::new(mat.values, mat.width)
+    }
+
+    /// The row-major form of the transpose of this matrix, i.e. its columns become rows.
+    #[must_use]
+    pub fn into_transposed(self) -> DenseMatrix<T, S> {
+        DenseMatrix::new(self.values, self.height)
+    }
+
+    /// Like `into_transposed`, but borrowing the values.
+    pub fn as_transposed(&self) -> RowMajorMatrixView<'_, T> {
+        RowMajorMatrixView::new(self.values.borrow(), self.height)
+    }
+
+    pub fn as_view(&self) -> ColMajorMatrixView<'_, T> {
+        ColMajorMatrixView::new(self.values.borrow(), self.height)
+    }
+
+    pub fn as_view_mut(&mut self) -> ColMajorMatrixViewMut<'_, T>
+    where
+        S: BorrowMut<[T]>,
+    {
+        ColMajorMatrixViewMut::new(self.values.borrow_mut(), self.height)
+    }
+
+    pub fn col_slice(&self, c: usize) -> &[T] {
+        &self.values.borrow()[c * self.height..(c + 1) * self.height]
+    }
+
+    pub fn col_mut(&mut self, c: usize) -> &mut [T]
+    where
+        S: BorrowMut<[T]>,
+    {
+        let height = self.height;
+        &mut self.values.borrow_mut()[c * height..(c + 1) * height]
+    }
+
+    pub fn col_slices(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks_exact` panics on a chunk size of zero, but then there are no values anyway.
+        self.values.borrow().chunks_exact(self.height.max(1))
+    }
+
+    /// Copy this matrix into row-major form.
+    pub fn to_row_major(&self) -> RowMajorMatrix<T>
+    where
+        T: Copy + Default,
+    {
+        let mut values = vec![T::default(); self.values.borrow().len()];
+        transpose_values(self.values.borrow(), &mut values, self.height, self.width());
+        RowMajorMatrix::new(values, self.width())
+    }
+}
+
+impl<T: Copy + Default + Send + Sync> ColMajorMatrix<T> {
+    /// Copy a row-major matrix into column-major form.
+    pub fn from_row_major<S: DenseStorage<T>>(mat: &DenseMatrix<T, S>) -> Self {
+        let mut values = vec![T::default(); mat.values.borrow().len()];
+        transpose_values(mat.values.borrow(), &mut values, mat.width, mat.height());
+        Self::new(values, mat.height())
+    }
+}
+
+impl<T: Clone + Send + Sync, S: DenseStorage<T>> Matrix<T> for ColMajorMatrix<T, S> {
+    #[inline]
+    fn width(&self) -> usize {
+        if self.height == 0 {
+            0
+        } else {
+            self.values.borrow().len() / self.height
+        }
+    }
+
+    #[inline]
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    fn get(&self, r: usize, c: usize) -> T {
+        self.values.borrow()[c * self.height + r].clone()
+    }
+
+    type Row<'a>
+        = iter::Cloned<iter::StepBy<slice::Iter<'a, T>>>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        assert!(r < self.height);
+        self.values.borrow()[r..]
+            .iter()
+            .step_by(self.height)
+            .cloned()
+    }
+
+    /// Like the default, but reading each packed value from a single column, rather than
+    /// gathering `P::WIDTH` rows first.
+    #[inline]
+    fn vertically_packed_row<P>(&self, r: usize) -> impl Iterator<Item = P>
+    where
+        T: Copy,
+        P: PackedValue<Value = T>,
+    {
+        self.col_slices()
+            .map(move |col| P::from_fn(|i| col[(r + i) % col.len()]))
+    }
+
+    #[inline]
+    fn vertically_packed_row_pair<P>(&self, r: usize, step: usize) -> Vec<P>
+    where
+        T: Copy,
+        P: PackedValue<Value = T>,
+    {
+        self.vertically_packed_row(r)
+            .chain(self.vertically_packed_row(r + step))
+            .collect_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use p3_baby_bear::BabyBear;
+    use rand::thread_rng;
+
+    use super::*;
+
+    type F = BabyBear;
+
+    #[test]
+    fn matches_row_major() {
+        for (height, width) in [(0, 0), (1, 5), (7, 1), (16, 3), (300, 300)] {
+            let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), height, width);
+            let col_major = ColMajorMatrix::from_row_major(&mat);
+            assert_eq!(col_major.dimensions(), mat.dimensions());
+            assert_eq!(col_major.to_row_major(), mat);
+            assert_eq!(col_major.clone().to_row_major_matrix(), mat);
+            for r in 0..height {
+                assert_eq!(col_major.row(r).collect_vec(), mat.row(r).collect_vec());
+            }
+            let transpose = mat.transpose();
+            for (c, col) in col_major.col_slices().enumerate() {
+                assert_eq!(col, &*transpose.row_slice(c));
+            }
+        }
+    }
+
+    #[test]
+    fn transposed_views() {
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 5, 3);
+        let col_major = ColMajorMatrix::from_transposed(mat.as_view());
+        assert_eq!(col_major.dimensions(), mat.transpose().dimensions());
+        assert_eq!(col_major.to_row_major(), mat.transpose());
+        assert_eq!(col_major.as_transposed(), mat.as_view());
+        assert_eq!(col_major.into_transposed(), mat.as_view());
+    }
+
+    #[test]
+    fn vertically_packed_rows() {
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 8, 5);
+        let col_major = ColMajorMatrix::from_row_major(&mat);
+        for r in 0..8 {
+            assert_eq!(
+                col_major.vertically_packed_row::<[F; 4]>(r).collect_vec(),
+                mat.vertically_packed_row::<[F; 4]>(r).collect_vec()
+            );
+            assert_eq!(
+                col_major.vertically_packed_row_pair::<[F; 4]>(r, 3),
+                mat.vertically_packed_row_pair::<[F; 4]>(r, 3)
+            );
+        }
+    }
+}
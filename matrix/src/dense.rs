@@ -13,6 +13,7 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
+use crate::util::par_transpose;
 use crate::Matrix;
 
 /// A dense matrix stored in row-major form.
@@ -405,9 +406,10 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> Matrix<T> for DenseMatrix<T, S>
     {
         let buf = &self.values.borrow()[r * self.width..(r + 1) * self.width];
         let (packed, sfx) = P::pack_slice_with_suffix(buf);
-        packed.iter().cloned().chain(iter::once(P::from_fn(|i| {
-            sfx.get(i).cloned().unwrap_or_default()
-        })))
+        // The tail is only padded if there is one, so aligned widths yield no extra zeros.
+        let tail =
+            (!sfx.is_empty()).then(|| P::from_fn(|i| sfx.get(i).cloned().unwrap_or_default()));
+        packed.iter().cloned().chain(tail)
     }
 }
 
@@ -447,14 +449,30 @@ impl<T: Copy + Default + Send + Sync> DenseMatrix<T, Vec<T>> {
     pub fn transpose(&self) -> Self {
         let nelts = self.height() * self.width();
         let mut values = vec![T::default(); nelts];
-        transpose::transpose(&self.values, &mut values, self.width(), self.height());
+        transpose_values(&self.values, &mut values, self.width(), self.height());
         Self::new(values, self.height())
     }
 
     pub fn transpose_into(&self, other: &mut Self) {
         assert_eq!(self.height(), other.width());
         assert_eq!(other.height(), self.width());
-        transpose::transpose(&self.values, &mut other.values, self.width(), self.height());
+        transpose_values(&self.values, &mut other.values, self.width(), self.height());
+    }
+}
+
+/// Below this many elements, a serial transpose beats a parallel one.
+const PAR_TRANSPOSE_THRESHOLD: usize = 1 << 16;
+
+pub(crate) fn transpose_values<T: Copy + Send + Sync>(
+    src: &[T],
+    dst: &mut [T],
+    width: usize,
+    height: usize,
+) {
+    if src.len() < PAR_TRANSPOSE_THRESHOLD {
+        transpose::transpose(src, dst, width, height);
+    } else {
+        par_transpose(src, dst, width, height);
     }
 }
 
@@ -464,6 +482,30 @@ impl<'a, T: Clone + Default + Send + Sync> DenseMatrix<T, &'a [T]> {
     }
 }
 
+#[cfg(feature = "bytemuck")]
+impl<T: Clone + Send + Sync + bytemuck::NoUninit, S: DenseStorage<T>> DenseMatrix<T, S> {
+    /// The values of this matrix as bytes, in their in-memory representation (e.g. Montgomery
+    /// form for `MontyField31`), without copying. They can be persisted or sent to another process
+    /// and viewed as a matrix again with `RowMajorMatrixView::try_from_bytes`.
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(self.values.borrow())
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+impl<'a, T: Clone + Send + Sync + bytemuck::CheckedBitPattern> DenseMatrix<T, &'a [T]> {
+    /// View bytes written from `as_bytes` as a matrix of the given width, without copying.
+    ///
+    /// Fails if `bytes` isn't aligned for `T`, if its length isn't a multiple of the size of `T`,
+    /// or if it holds invalid values, such as non-reduced field elements.
+    pub fn try_from_bytes(
+        bytes: &'a [u8],
+        width: usize,
+    ) -> Result<Self, bytemuck::checked::CheckedCastError> {
+        bytemuck::checked::try_cast_slice(bytes).map(|values| Self::new(values, width))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -515,6 +557,18 @@ mod tests {
         assert_eq!(transposed, should_be_transposed);
     }
 
+    #[test]
+    fn test_par_transpose_matches_serial() {
+        for (width, height) in [(1, 1), (33, 33), (37, 1000), (1000, 3), (64, 70)] {
+            let matrix = RowMajorMatrix::<u32>::rand(&mut rand::thread_rng(), height, width);
+            let mut expected = vec![0; width * height];
+            transpose::transpose(&matrix.values, &mut expected, width, height);
+            let mut transposed = vec![0; width * height];
+            par_transpose(&matrix.values, &mut transposed, width, height);
+            assert_eq!(transposed, expected, "{width} x {height}");
+        }
+    }
+
     #[test]
     fn test_transpose_larger_rectangular_matrix() {
         const START_INDEX: usize = 1;
@@ -562,4 +616,17 @@ mod tests {
             }
         }
     }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn test_byte_views() {
+        let matrix = RowMajorMatrix::new((0..12u32).collect(), 3);
+        let bytes = matrix.as_bytes();
+        assert_eq!(bytes.len(), 48);
+        assert_eq!(
+            RowMajorMatrixView::try_from_bytes(bytes, 3),
+            Ok(matrix.as_view())
+        );
+        assert!(RowMajorMatrixView::<u32>::try_from_bytes(&bytes[1..5], 1).is_err());
+    }
 }
@@ -1,9 +1,11 @@
+use alloc::vec;
 use alloc::vec::Vec;
 use core::iter;
 use core::marker::PhantomData;
 use core::ops::Deref;
 
-use p3_field::{ExtensionField, Field};
+use itertools::Itertools;
+use p3_field::{AbstractExtensionField, ExtensionField, Field, PackedValue};
 
 use crate::Matrix;
 
@@ -84,6 +86,122 @@ where
     }
 }
 
+/// A matrix of extension field elements, stored as `EF::ExtensionPacking`s which each hold an
+/// element of `F::Packing::WIDTH` consecutive rows, as SIMD code tends to produce them.
+///
+/// It's viewed as the matrix of base field elements `FlatMatrixView` would give, i.e. with the
+/// coefficients of each element in canonical order, but `vertically_packed_row` reads packed
+/// coefficients directly rather than transposing rows.
+#[derive(Clone, Debug)]
+pub struct PackedExtensionMatrix<F: Field, EF: ExtensionField<F>> {
+    /// The packed rows, each `width` elements wide. The lanes of the last one past `height` are
+    /// padding.
+    values: Vec<EF::ExtensionPacking>,
+    /// The width in extension field elements.
+    width: usize,
+    height: usize,
+}
+
+impl<F: Field, EF: ExtensionField<F>> PackedExtensionMatrix<F, EF> {
+    #[must_use]
+    pub fn new(values: Vec<EF::ExtensionPacking>, width: usize, height: usize) -> Self {
+        assert_eq!(
+            values.len(),
+            width * height.div_ceil(F::Packing::WIDTH),
+            "wrong number of packed values"
+        );
+        Self {
+            values,
+            width,
+            height,
+        }
+    }
+
+    /// Pack the rows of `mat`, padding the last packed row with zeros.
+    #[must_use]
+    pub fn pack<M: Matrix<EF>>(mat: &M) -> Self {
+        let packing_width = F::Packing::WIDTH;
+        let (width, height) = (mat.width(), mat.height());
+        let mut values = Vec::with_capacity(width * height.div_ceil(packing_width));
+        for first_row in (0..height).step_by(packing_width) {
+            let rows = (first_row..first_row + packing_width)
+                .map(|r| (r < height).then(|| mat.row_slice(r)))
+                .collect_vec();
+            values.extend((0..width).map(|c| {
+                EF::ExtensionPacking::from_base_fn(|k| {
+                    F::Packing::from_fn(|i| {
+                        rows[i]
+                            .as_ref()
+                            .map_or(F::ZERO, |row| row[c].as_base_slice()[k])
+                    })
+                })
+            }));
+        }
+        Self::new(values, width, height)
+    }
+
+    /// The width in extension field elements.
+    pub const fn ext_width(&self) -> usize {
+        self.width
+    }
+
+    /// The packed coefficients of each element of the packed row `packed_r`, in order.
+    fn packed_row(&self, packed_r: usize) -> impl Iterator<Item = &F::Packing> {
+        self.values[packed_r * self.width..(packed_r + 1) * self.width]
+            .iter()
+            .flat_map(<EF::ExtensionPacking as AbstractExtensionField<F::Packing>>::as_base_slice)
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> Matrix<F> for PackedExtensionMatrix<F, EF> {
+    fn width(&self) -> usize {
+        self.width * EF::D
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    type Row<'a>
+        = vec::IntoIter<F>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        assert!(r < self.height, "row index out of bounds");
+        let lane = r % F::Packing::WIDTH;
+        self.packed_row(r / F::Packing::WIDTH)
+            .map(|p| p.as_slice()[lane])
+            .collect_vec()
+            .into_iter()
+    }
+
+    fn vertically_packed_row<P>(&self, r: usize) -> impl Iterator<Item = P>
+    where
+        F: Copy,
+        P: PackedValue<Value = F>,
+    {
+        let packing_width = F::Packing::WIDTH;
+        let packed: Vec<P> = if P::WIDTH == packing_width
+            && r % packing_width == 0
+            && r + packing_width <= self.height
+        {
+            // The lanes are exactly the rows we want, so no transposition is needed.
+            self.packed_row(r / packing_width)
+                .map(|p| *P::from_slice(p.as_slice()))
+                .collect()
+        } else {
+            let rows = (0..P::WIDTH)
+                .map(|i| self.row_slice((r + i) % self.height))
+                .collect_vec();
+            (0..self.width())
+                .map(|c| P::from_fn(|i| rows[i][c]))
+                .collect()
+        };
+        packed.into_iter()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
@@ -116,4 +234,22 @@ mod tests {
             &[30, 31, 40, 41].map(F::from_canonical_usize)
         );
     }
+
+    #[test]
+    fn packed_extension_matrix_matches_flat() {
+        type P = <F as Field>::Packing;
+
+        // A height which isn't a multiple of the packing width, to exercise the padding.
+        let ext = RowMajorMatrix::<EF>::rand(&mut rand::thread_rng(), 2 * P::WIDTH + 1, 3);
+        let packed = PackedExtensionMatrix::<F, EF>::pack(&ext);
+        let flat = FlatMatrixView::<F, EF, _>::new(ext.clone());
+        assert_eq!(packed.dimensions(), flat.dimensions());
+        for r in 0..ext.height() {
+            assert_eq!(packed.row(r).collect_vec(), flat.row(r).collect_vec());
+            assert_eq!(
+                packed.vertically_packed_row::<P>(r).collect_vec(),
+                flat.vertically_packed_row::<P>(r).collect_vec()
+            );
+        }
+    }
 }
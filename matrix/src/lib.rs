@@ -3,6 +3,8 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "mmap")]
+extern crate std;
 
 use alloc::vec::Vec;
 use core::fmt::{Debug, Display, Formatter};
@@ -19,9 +21,15 @@ use tracing::instrument;
 use crate::dense::RowMajorMatrix;
 
 pub mod bitrev;
+pub mod col_major;
 pub mod dense;
 pub mod extension;
+#[cfg(feature = "mmap")]
+pub mod mmap;
 pub mod mul;
+pub mod padded;
+pub mod periodic;
+pub mod row_block;
 pub mod row_index_mapped;
 pub mod sparse;
 pub mod stack;
@@ -99,6 +107,9 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
         )
     }
 
+    /// Pack row `r` into `width / P::WIDTH` packed values, along with the remaining
+    /// `width % P::WIDTH` scalars, so that callers can process the main body of any width packed
+    /// and only handle the tail separately.
     fn horizontally_packed_row<'a, P>(
         &'a self,
         r: usize,
@@ -111,12 +122,15 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
         T: Clone + 'a,
     {
         let num_packed = self.width() / P::WIDTH;
-        let packed = (0..num_packed).map(move |c| P::from_fn(|i| self.get(r, P::WIDTH * c + i)));
-        let sfx = (num_packed * P::WIDTH..self.width()).map(move |c| self.get(r, c));
+        let mut row_iter = self.row(r);
+        // array::from_fn currently always calls in order, but it's not clear whether that's guaranteed.
+        let packed = (0..num_packed).map(move |_| P::from_fn(|_| row_iter.next().unwrap()));
+        let sfx = self.row(r).skip(num_packed * P::WIDTH);
         (packed, sfx)
     }
 
-    /// Zero padded.
+    /// Pack row `r` into `width.div_ceil(P::WIDTH)` packed values, with the tail, if any, padded
+    /// with zeros.
     fn padded_horizontally_packed_row<'a, P>(
         &'a self,
         r: usize,
@@ -208,6 +222,27 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
             .collect_vec()
     }
 
+    /// Pack together a collection of rows at each of the given offsets from the matrix.
+    ///
+    /// Returns a vector corresponding to `offsets.len()` packed rows, the j'th of which is
+    /// `vertically_packed_row(r + offsets[j] * step)`, wrapping around the matrix in either
+    /// direction. For the offsets `[0, 1]` this is `vertically_packed_row_pair(r, step)`.
+    #[inline]
+    fn vertically_packed_rows<P>(&self, r: usize, step: usize, offsets: &[isize]) -> Vec<P>
+    where
+        T: Copy,
+        P: PackedValue<Value = T>,
+    {
+        let height = self.height() as isize;
+        offsets
+            .iter()
+            .flat_map(|&offset| {
+                let row = (r as isize + offset * step as isize).rem_euclid(height);
+                self.vertically_packed_row(row as usize)
+            })
+            .collect()
+    }
+
     fn vertically_strided(self, stride: usize, offset: usize) -> VerticallyStridedMatrixView<Self>
     where
         Self: Sized,
@@ -268,16 +303,43 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
             .map(move |row_packed| {
                 let packed_sum_of_packed: EF::ExtensionPacking =
                     dot_product(powers_packed.iter().copied(), row_packed);
-                let sum_of_packed: EF = EF::from_base_fn(|i| {
-                    packed_sum_of_packed.as_base_slice()[i]
-                        .as_slice()
-                        .iter()
-                        .copied()
-                        .sum()
-                });
-                sum_of_packed
+                sum_lanes(packed_sum_of_packed)
             })
     }
+
+    /// Compute Mv, aka postmultiply this matrix by the given vector,
+    /// aka take the dot product of each row with `v`.
+    /// `v` can be a vector of extension elements.
+    #[instrument(level = "debug", skip_all, fields(dims = %self.dimensions()))]
+    fn vec_mul<EF>(&self, v: &[EF]) -> Vec<EF>
+    where
+        T: Field,
+        EF: ExtensionField<T>,
+    {
+        assert_eq!(v.len(), self.width());
+        // Pack v, zero padded to match the padded rows.
+        let v_packed = v
+            .chunks(T::Packing::WIDTH)
+            .map(|chunk| {
+                EF::ExtensionPacking::from_base_fn(|i| {
+                    T::Packing::from_fn(|j| chunk.get(j).map_or(T::ZERO, |x| x.as_base_slice()[i]))
+                })
+            })
+            .collect_vec();
+        self.par_padded_horizontally_packed_rows::<T::Packing>()
+            .map(|row_packed| {
+                let packed_sum_of_packed: EF::ExtensionPacking =
+                    dot_product(v_packed.iter().copied(), row_packed);
+                sum_lanes(packed_sum_of_packed)
+            })
+            .collect()
+    }
+}
+
+/// The sum of the lanes of a packed extension element.
+#[inline]
+fn sum_lanes<T: Field, EF: ExtensionField<T>>(packed: EF::ExtensionPacking) -> EF {
+    EF::from_base_fn(|i| packed.as_base_slice()[i].as_slice().iter().copied().sum())
 }
 
 #[cfg(test)]
@@ -291,6 +353,7 @@ mod tests {
     use rand::thread_rng;
 
     use super::*;
+    use crate::stack::VerticalChain;
 
     #[test]
     fn test_columnwise_dot_product() {
@@ -309,4 +372,69 @@ mod tests {
 
         assert_eq!(m.columnwise_dot_product(&v), expected);
     }
+
+    #[test]
+    fn test_vec_mul() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<BabyBear, 4>;
+
+        for width in [1, 7, 16, 21] {
+            let m = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << 6, width);
+            let v = RowMajorMatrix::<EF>::rand(&mut thread_rng(), width, 1).values;
+
+            let expected = m
+                .rows()
+                .map(|row| izip!(row, &v).map(|(x, &y)| y * x).sum())
+                .collect_vec();
+            assert_eq!(m.vec_mul(&v), expected);
+        }
+    }
+
+    #[test]
+    fn test_horizontally_packed_rows() {
+        type F = BabyBear;
+        type P = [F; 4];
+
+        for width in 1..10 {
+            let m = RowMajorMatrix::<F>::rand(&mut thread_rng(), 3, width);
+            // A view which uses the default implementations.
+            let view = VerticalChain::new(vec![m.as_view()]);
+            let (packed, sfx) = m.horizontally_packed_row::<P>(2);
+            let (packed, sfx) = (packed.collect_vec(), sfx.collect_vec());
+            let (default_packed, default_sfx) = view.horizontally_packed_row::<P>(2);
+            assert_eq!(default_packed.collect_vec(), packed);
+            assert_eq!(default_sfx.collect_vec(), sfx);
+            assert_eq!(packed.len(), width / 4);
+            assert_eq!(
+                packed.iter().flatten().chain(&sfx).copied().collect_vec(),
+                m.row_slice(2).to_vec()
+            );
+
+            let padded = m.padded_horizontally_packed_row::<P>(1).collect_vec();
+            assert_eq!(padded.len(), width.div_ceil(4));
+            assert_eq!(
+                padded,
+                view.padded_horizontally_packed_row::<P>(1).collect_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_vertically_packed_rows() {
+        type F = BabyBear;
+        type P = [F; 4];
+
+        let m = RowMajorMatrix::<F>::rand(&mut thread_rng(), 8, 3);
+        assert_eq!(
+            m.vertically_packed_rows::<P>(6, 3, &[0, 1]),
+            m.vertically_packed_row_pair::<P>(6, 3)
+        );
+        // Negative offsets wrap around to the end of the matrix.
+        let packed = m.vertically_packed_rows::<P>(1, 2, &[-1, 2]);
+        let expected = m
+            .vertically_packed_row::<P>(7)
+            .chain(m.vertically_packed_row::<P>(5))
+            .collect_vec();
+        assert_eq!(packed, expected);
+    }
 }
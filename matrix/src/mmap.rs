@@ -0,0 +1,197 @@
+use alloc::vec::Vec;
+use core::borrow::{Borrow, BorrowMut};
+use core::fmt::{self, Debug, Formatter};
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::{mem, slice};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use bytemuck::{NoUninit, Zeroable};
+use memmap2::MmapMut;
+
+use crate::dense::{DenseMatrix, DenseStorage};
+
+/// A fixed-length buffer of `T`s held in a memory-mapped temporary file rather than in memory,
+/// for matrices too large for memory, such as the traces and LDEs of very large provers.
+///
+/// The file is deleted when the buffer is dropped. Reads and writes page the data in and out as
+/// needed, and the OS is free to evict it under memory pressure.
+///
+/// Values are written to the file as their bytes, so `T` must be `NoUninit`, which the field
+/// types implement with their `bytemuck` features.
+pub struct MmapBuffer<T> {
+    mmap: MmapMut,
+    len: usize,
+    _phantom: PhantomData<T>,
+}
+
+/// A row-major matrix held in a memory-mapped file. Like any `DenseMatrix` with mutable storage,
+/// it can be viewed with `as_view` and `as_view_mut`, so code taking `Matrix` implementations or
+/// matrix views works on it unchanged.
+pub type MmapRowMajorMatrix<T> = DenseMatrix<T, MmapBuffer<T>>;
+
+impl<T: NoUninit + Zeroable> MmapBuffer<T> {
+    /// A buffer of `len` zeros, in a new temporary file in `dir`.
+    pub fn zeroed_in(dir: &Path, len: usize) -> io::Result<Self> {
+        let file = tempfile::tempfile_in(dir)?;
+        file.set_len((len * mem::size_of::<T>()) as u64)?;
+        // SAFETY: the file holds `len` all-zero values, which are valid since `T: Zeroable`.
+        unsafe { Self::map(&file, len) }
+    }
+}
+
+impl<T: NoUninit> MmapBuffer<T> {
+    /// Write `values` to a new temporary file in `dir`, and map it.
+    pub fn new_in(dir: &Path, values: &[T]) -> io::Result<Self> {
+        // SAFETY: `T: NoUninit`.
+        unsafe { Self::new_in_unchecked(dir, values) }
+    }
+
+    /// Like `new_in`, but writes the values as they're produced, so that they never all need to
+    /// be in memory at once.
+    pub fn from_iter_in(dir: &Path, values: impl IntoIterator<Item = T>) -> io::Result<Self> {
+        let file = tempfile::tempfile_in(dir)?;
+        let mut writer = BufWriter::new(&file);
+        let mut len = 0;
+        for value in values {
+            writer.write_all(bytemuck::cast_slice(slice::from_ref(&value)))?;
+            len += 1;
+        }
+        writer.flush()?;
+        drop(writer);
+        // SAFETY: the file holds the bytes of `len` values of type `T`.
+        unsafe { Self::map(&file, len) }
+    }
+}
+
+impl<T: Copy> MmapBuffer<T> {
+    /// Like `new_in`, for types which meet the requirements of `NoUninit` without implementing
+    /// it, such as arrays of field elements.
+    ///
+    /// # Safety
+    /// `T` must meet the requirements of `NoUninit`: it must have no padding bytes and no interior
+    /// mutability.
+    pub unsafe fn new_in_unchecked(dir: &Path, values: &[T]) -> io::Result<Self> {
+        let mut file = tempfile::tempfile_in(dir)?;
+        // SAFETY: the caller guarantees that every byte of `values` is initialized.
+        file.write_all(slice::from_raw_parts(
+            values.as_ptr().cast(),
+            mem::size_of_val(values),
+        ))?;
+        // SAFETY: the file holds the bytes of `values.len()` values of type `T`.
+        Self::map(&file, values.len())
+    }
+
+    /// # Safety
+    /// `file` must hold `len` valid values of type `T`.
+    unsafe fn map(file: &File, len: usize) -> io::Result<Self> {
+        // SAFETY: the file is unnamed and private to us, so nothing else can modify it while it's
+        // mapped.
+        let mmap = MmapMut::map_mut(file)?;
+        assert_eq!(mmap.len(), len * mem::size_of::<T>());
+        Ok(Self {
+            mmap,
+            len,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<T: Clone + Send + Sync + NoUninit + Zeroable> DenseMatrix<T, MmapBuffer<T>> {
+    /// A matrix of zeros with the given dimensions, in a new temporary file in `dir`.
+    pub fn zeroed_in(dir: &Path, width: usize, height: usize) -> io::Result<Self> {
+        Ok(Self::new(
+            MmapBuffer::zeroed_in(dir, width * height)?,
+            width,
+        ))
+    }
+}
+
+impl<T> Deref for MmapBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: the mapping is page-aligned, and holds `len` values of type `T`.
+        unsafe { slice::from_raw_parts(self.mmap.as_ptr().cast(), self.len) }
+    }
+}
+
+impl<T> DerefMut for MmapBuffer<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: as in `deref`.
+        unsafe { slice::from_raw_parts_mut(self.mmap.as_mut_ptr().cast(), self.len) }
+    }
+}
+
+impl<T> Borrow<[T]> for MmapBuffer<T> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> BorrowMut<[T]> for MmapBuffer<T> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T: Clone + Send + Sync> DenseStorage<T> for MmapBuffer<T> {
+    fn to_vec(self) -> Vec<T> {
+        <[T]>::to_vec(&self)
+    }
+}
+
+impl<T: Debug> Debug for MmapBuffer<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_maybe_rayon::prelude::*;
+    use rand::thread_rng;
+
+    use super::{MmapBuffer, MmapRowMajorMatrix};
+    use crate::dense::RowMajorMatrix;
+    use crate::Matrix;
+
+    type F = BabyBear;
+
+    #[test]
+    fn mmap_buffer_round_trip() {
+        let dir = std::env::temp_dir();
+        let values: Vec<F> = (0..1000).map(|_| rand::random()).collect();
+        assert_eq!(*MmapBuffer::new_in(&dir, &values).unwrap(), values[..]);
+        assert_eq!(
+            *MmapBuffer::from_iter_in(&dir, values.iter().copied()).unwrap(),
+            values[..]
+        );
+        assert!(MmapBuffer::<F>::zeroed_in(&dir, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn mmap_matrix_matches_in_memory() {
+        let dir = std::env::temp_dir();
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 100, 7);
+        let mut mmap_mat = MmapRowMajorMatrix::<F>::zeroed_in(&dir, 7, 100).unwrap();
+        mmap_mat
+            .par_rows_mut()
+            .zip(mat.par_row_slices())
+            .for_each(|(dst, src)| dst.copy_from_slice(src));
+
+        assert_eq!(mmap_mat.dimensions(), mat.dimensions());
+        assert_eq!(mmap_mat.as_view(), mat.as_view());
+        let v: Vec<F> = (0..100).map(|_| rand::random()).collect();
+        assert_eq!(
+            mmap_mat.columnwise_dot_product(&v),
+            mat.columnwise_dot_product(&v)
+        );
+        assert_eq!(mmap_mat.to_row_major_matrix(), mat);
+    }
+}
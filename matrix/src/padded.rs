@@ -0,0 +1,149 @@
+use core::iter::{self, Chain, Repeat, Take};
+
+use crate::row_index_mapped::{RowIndexMap, RowIndexMappedView};
+use crate::stack::EitherRow;
+use crate::Matrix;
+
+/// A view of a matrix extended to a larger width and height, with the new entries all set to
+/// `fill`, without copying the matrix.
+#[derive(Copy, Clone, Debug)]
+pub struct PaddedView<Inner, T> {
+    pub inner: Inner,
+    width: usize,
+    height: usize,
+    fill: T,
+}
+
+impl<Inner, T> PaddedView<Inner, T> {
+    pub fn new(inner: Inner, width: usize, height: usize, fill: T) -> Self
+    where
+        T: Send + Sync,
+        Inner: Matrix<T>,
+    {
+        assert!(width >= inner.width());
+        assert!(height >= inner.height());
+        Self {
+            inner,
+            width,
+            height,
+            fill,
+        }
+    }
+
+    /// Extend `inner` to the given height with rows of `fill`.
+    pub fn new_height(inner: Inner, height: usize, fill: T) -> Self
+    where
+        T: Send + Sync,
+        Inner: Matrix<T>,
+    {
+        let width = inner.width();
+        Self::new(inner, width, height, fill)
+    }
+}
+
+impl<T: Clone + Send + Sync, Inner: Matrix<T>> Matrix<T> for PaddedView<Inner, T> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, r: usize, c: usize) -> T {
+        debug_assert!(r < self.height && c < self.width);
+        if r < self.inner.height() && c < self.inner.width() {
+            self.inner.get(r, c)
+        } else {
+            self.fill.clone()
+        }
+    }
+
+    type Row<'a>
+        = EitherRow<Chain<Inner::Row<'a>, Take<Repeat<T>>>, Take<Repeat<T>>>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        assert!(r < self.height);
+        if r < self.inner.height() {
+            let padding = iter::repeat(self.fill.clone()).take(self.width - self.inner.width());
+            EitherRow::Left(self.inner.row(r).chain(padding))
+        } else {
+            EitherRow::Right(iter::repeat(self.fill.clone()).take(self.width))
+        }
+    }
+}
+
+/// Extends a matrix to a larger height by repeating its last row.
+#[derive(Debug)]
+pub struct RepeatLastRowIndexMap {
+    height: usize,
+    last_row: usize,
+}
+
+pub type RepeatLastRowView<Inner> = RowIndexMappedView<RepeatLastRowIndexMap, Inner>;
+
+impl RepeatLastRowIndexMap {
+    /// Panics if `inner` is empty, as it has no last row to repeat.
+    pub fn new_view<T: Send + Sync, Inner: Matrix<T>>(
+        inner: Inner,
+        height: usize,
+    ) -> RepeatLastRowView<Inner> {
+        let inner_height = inner.height();
+        assert!(
+            inner_height > 0,
+            "cannot repeat the last row of an empty matrix"
+        );
+        assert!(height >= inner_height);
+        RowIndexMappedView {
+            index_map: Self {
+                height,
+                last_row: inner_height - 1,
+            },
+            inner,
+        }
+    }
+}
+
+impl RowIndexMap for RepeatLastRowIndexMap {
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn map_row_index(&self, r: usize) -> usize {
+        debug_assert!(r < self.height);
+        r.min(self.last_row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::dense::RowMajorMatrix;
+
+    #[test]
+    fn padded_views() {
+        let mat = RowMajorMatrix::new(vec![1, 2, 3, 4, 5, 6], 2);
+
+        let padded = PaddedView::new(mat.as_view(), 3, 4, 0);
+        assert_eq!(padded.get(1, 1), 4);
+        assert_eq!(padded.get(3, 0), 0);
+        assert_eq!(
+            padded.to_row_major_matrix(),
+            RowMajorMatrix::new(vec![1, 2, 0, 3, 4, 0, 5, 6, 0, 0, 0, 0], 3)
+        );
+
+        let repeated = RepeatLastRowIndexMap::new_view(mat.as_view(), 5);
+        assert_eq!(repeated.height(), 5);
+        assert_eq!(repeated.row(4).collect_vec(), [5, 6]);
+        assert_eq!(
+            repeated.to_row_major_matrix(),
+            RowMajorMatrix::new(vec![1, 2, 3, 4, 5, 6, 5, 6, 5, 6], 2)
+        );
+    }
+}
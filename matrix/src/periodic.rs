@@ -0,0 +1,128 @@
+use core::ops::Deref;
+use core::{iter, slice};
+
+use crate::dense::RowMajorMatrix;
+use crate::Matrix;
+
+/// A matrix whose rows repeat with some period, stored as a single period, e.g. a preprocessed
+/// column marking every `k`th row.
+///
+/// Row `r` is row `r % period_height()` of the period. For a height and period which are powers of
+/// two, the columns are polynomials in `X^(height / period_height)`, so their LDE is periodic too
+/// and can be computed from the period alone; see `coset_lde_batch_periodic` in `p3-dft`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeriodicMatrix<T> {
+    period: RowMajorMatrix<T>,
+    height: usize,
+}
+
+impl<T: Clone + Send + Sync> PeriodicMatrix<T> {
+    /// A matrix of the given height, made of copies of `period`.
+    pub fn new(period: RowMajorMatrix<T>, height: usize) -> Self {
+        assert!(period.height() > 0, "the period must have at least one row");
+        assert_eq!(
+            height % period.height(),
+            0,
+            "the height must be a multiple of the period"
+        );
+        Self { period, height }
+    }
+
+    /// Represent `mat` by its shortest period which divides its height, found by halving the
+    /// period while both halves of it match, so for a height which is a power of two this is its
+    /// shortest period.
+    pub fn from_dense(mat: &RowMajorMatrix<T>) -> Self
+    where
+        T: PartialEq,
+    {
+        let height = mat.height();
+        let mut period_height = height;
+        while period_height > 0 && period_height % 2 == 0 {
+            let half_len = period_height / 2 * mat.width;
+            let (first, second) = mat.values[..2 * half_len].split_at(half_len);
+            if first != second {
+                break;
+            }
+            period_height /= 2;
+        }
+        if period_height == height {
+            return Self::new(mat.clone(), height);
+        }
+        // Since both halves of each period match, the whole matrix is made of copies of the first.
+        let period_len = period_height * mat.width;
+        let period = RowMajorMatrix::new(mat.values[..period_len].to_vec(), mat.width);
+        Self::new(period, height)
+    }
+
+    /// The rows of a single period.
+    pub const fn period(&self) -> &RowMajorMatrix<T> {
+        &self.period
+    }
+
+    pub fn into_period(self) -> RowMajorMatrix<T> {
+        self.period
+    }
+
+    pub fn period_height(&self) -> usize {
+        self.period.height()
+    }
+}
+
+impl<T: Clone + Send + Sync> Matrix<T> for PeriodicMatrix<T> {
+    #[inline]
+    fn width(&self) -> usize {
+        self.period.width()
+    }
+
+    #[inline]
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    fn get(&self, r: usize, c: usize) -> T {
+        debug_assert!(r < self.height);
+        self.period.get(r % self.period_height(), c)
+    }
+
+    type Row<'a>
+        = iter::Cloned<slice::Iter<'a, T>>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        debug_assert!(r < self.height);
+        self.period.row(r % self.period_height())
+    }
+
+    #[inline]
+    fn row_slice(&self, r: usize) -> impl Deref<Target = [T]> {
+        debug_assert!(r < self.height);
+        self.period.row_slice(r % self.period_height())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn from_dense_finds_period() {
+        let mat = RowMajorMatrix::new(vec![1, 0, 2, 0, 1, 0, 2, 0, 1, 0, 2, 0, 1, 0, 2, 0], 2);
+        let periodic = PeriodicMatrix::from_dense(&mat);
+        assert_eq!(periodic.period_height(), 2);
+        assert_eq!(periodic.dimensions(), mat.dimensions());
+        assert_eq!(periodic.clone().to_row_major_matrix(), mat);
+        assert_eq!(periodic.get(5, 0), 2);
+
+        // The second half doesn't match the first, so the matrix is its own period.
+        let mat = RowMajorMatrix::new(vec![1, 1, 1, 2], 1);
+        assert_eq!(PeriodicMatrix::from_dense(&mat).period_height(), 4);
+
+        let mat = RowMajorMatrix::new(vec![3; 12], 3);
+        assert_eq!(PeriodicMatrix::from_dense(&mat).period_height(), 1);
+    }
+}
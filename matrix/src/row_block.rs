@@ -0,0 +1,76 @@
+use core::ops::Range;
+
+use crate::dense::{DenseMatrix, DenseStorage, RowMajorMatrixView};
+use crate::stack::HorizontalPair;
+use crate::Matrix;
+
+/// A matrix which can efficiently produce contiguous blocks of rows.
+///
+/// This is useful for matrices whose rows are expensive to access individually, such as matrices
+/// which are computed on the fly. Consumers which need every row, like a Merkle tree builder, can
+/// then work through such a matrix one block at a time without holding all of it in memory.
+pub trait RowBlockMatrix<T: Send + Sync>: Matrix<T> {
+    /// The preferred number of rows per block. Blocks which start at a multiple of this and are
+    /// no taller than it are the cheapest to materialize.
+    fn block_height(&self) -> usize;
+
+    /// Get the given range of rows as a matrix. Matrices which are computed on the fly compute the
+    /// block here, while matrices which are already in memory can simply return a view.
+    fn row_block(&self, rows: Range<usize>) -> impl Matrix<T> + '_;
+}
+
+impl<T: Clone + Send + Sync, S: DenseStorage<T>> RowBlockMatrix<T> for DenseMatrix<T, S> {
+    fn block_height(&self) -> usize {
+        // Any block is as cheap as any other.
+        self.height()
+    }
+
+    fn row_block(&self, rows: Range<usize>) -> impl Matrix<T> + '_ {
+        let values = &self.values.borrow()[rows.start * self.width..rows.end * self.width];
+        RowMajorMatrixView::new(values, self.width)
+    }
+}
+
+impl<T, First, Second> RowBlockMatrix<T> for HorizontalPair<First, Second>
+where
+    T: Clone + Send + Sync,
+    First: RowBlockMatrix<T>,
+    Second: RowBlockMatrix<T>,
+{
+    fn block_height(&self) -> usize {
+        self.first.block_height().min(self.second.block_height())
+    }
+
+    fn row_block(&self, rows: Range<usize>) -> impl Matrix<T> + '_ {
+        HorizontalPair::new(
+            self.first.row_block(rows.clone()),
+            self.second.row_block(rows),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::dense::RowMajorMatrix;
+
+    #[test]
+    fn test_row_block() {
+        let left = RowMajorMatrix::new(vec![1, 2, 3, 4, 5, 6, 7, 8], 2);
+        let right = RowMajorMatrix::new(vec![10, 20, 30, 40], 1);
+
+        assert_eq!(
+            left.row_block(1..3).to_row_major_matrix(),
+            RowMajorMatrix::new(vec![3, 4, 5, 6], 2)
+        );
+
+        let pair = HorizontalPair::new(left.as_view(), right.as_view());
+        assert_eq!(pair.block_height(), 4);
+        assert_eq!(
+            pair.row_block(2..4).to_row_major_matrix(),
+            RowMajorMatrix::new(vec![5, 6, 30, 7, 8, 40], 3)
+        );
+    }
+}
@@ -31,6 +31,37 @@ pub struct RowIndexMappedView<IndexMap, Inner> {
     pub inner: Inner,
 }
 
+/// `outer` applied on top of `inner`, i.e. row `r` maps to `inner.map_row_index(outer.map_row_index(r))`.
+#[derive(Copy, Clone, Debug)]
+pub struct ComposedRowIndexMap<Outer, Inner> {
+    pub outer: Outer,
+    pub inner: Inner,
+}
+
+impl<Outer: RowIndexMap, Inner: RowIndexMap> RowIndexMap for ComposedRowIndexMap<Outer, Inner> {
+    fn height(&self) -> usize {
+        self.outer.height()
+    }
+    fn map_row_index(&self, r: usize) -> usize {
+        self.inner.map_row_index(self.outer.map_row_index(r))
+    }
+}
+
+impl<Outer, InnerMap, Inner> RowIndexMappedView<Outer, RowIndexMappedView<InnerMap, Inner>> {
+    /// Turn a view of a view into a single view of the innermost matrix, with the two index maps
+    /// composed, so that row accesses go through one view rather than delegating from one to the
+    /// next. Deeper stacks can be flattened by flattening repeatedly.
+    pub fn flatten(self) -> RowIndexMappedView<ComposedRowIndexMap<Outer, InnerMap>, Inner> {
+        RowIndexMappedView {
+            index_map: ComposedRowIndexMap {
+                outer: self.index_map,
+                inner: self.inner.index_map,
+            },
+            inner: self.inner.inner,
+        }
+    }
+}
+
 impl<T: Send + Sync, IndexMap: RowIndexMap, Inner: Matrix<T>> Matrix<T>
     for RowIndexMappedView<IndexMap, Inner>
 {
@@ -96,3 +127,26 @@ impl<T: Send + Sync, IndexMap: RowIndexMap, Inner: Matrix<T>> Matrix<T>
             .padded_horizontally_packed_row(self.index_map.map_row_index(r))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::bitrev::BitReversalPerm;
+    use crate::strided::VerticallyStridedRowIndexMap;
+
+    #[test]
+    fn flatten_matches_nested() {
+        let mat = RowMajorMatrix::new((0..64).collect::<Vec<u32>>(), 2);
+        let nested = || {
+            BitReversalPerm::new_view(VerticallyStridedRowIndexMap::new_view(mat.as_view(), 2, 1))
+        };
+        let expected = nested().to_row_major_matrix();
+
+        let flat = nested().flatten();
+        assert_eq!(flat.height(), 16);
+        assert_eq!(flat.get(3, 1), expected.get(3, 1));
+        assert_eq!(flat.to_row_major_matrix(), expected);
+    }
+}
@@ -1,5 +1,7 @@
-use core::iter::Chain;
+use alloc::vec::Vec;
+use core::iter::{self, Chain};
 use core::ops::Deref;
+use core::slice;
 
 use crate::Matrix;
 
@@ -17,6 +19,24 @@ pub struct HorizontalPair<First, Second> {
     pub second: Second,
 }
 
+/// Any number of matrices of the same width, stacked together vertically.
+#[derive(Clone, Debug)]
+pub struct VerticalChain<M> {
+    mats: Vec<M>,
+    /// The index of the first row of each matrix, followed by the total height.
+    row_offsets: Vec<usize>,
+    width: usize,
+}
+
+/// Any number of matrices of the same height, stacked together horizontally.
+#[derive(Clone, Debug)]
+pub struct HorizontalChain<M> {
+    mats: Vec<M>,
+    /// The index of the first column of each matrix, followed by the total width.
+    col_offsets: Vec<usize>,
+    height: usize,
+}
+
 impl<First, Second> VerticalPair<First, Second> {
     pub fn new<T>(first: First, second: Second) -> Self
     where
@@ -41,6 +61,84 @@ impl<First, Second> HorizontalPair<First, Second> {
     }
 }
 
+/// The prefix sums of `lens`, starting at zero.
+fn offsets(lens: impl Iterator<Item = usize>) -> Vec<usize> {
+    iter::once(0)
+        .chain(lens.scan(0, |total, len| {
+            *total += len;
+            Some(*total)
+        }))
+        .collect()
+}
+
+/// The index of the part containing `i`, given the offsets of the parts, and `i`'s index within it.
+/// Empty parts are skipped over.
+fn locate(offsets: &[usize], i: usize) -> (usize, usize) {
+    assert!(i < *offsets.last().unwrap(), "index out of bounds");
+    let part = offsets.partition_point(|&offset| offset <= i) - 1;
+    (part, i - offsets[part])
+}
+
+impl<M> VerticalChain<M> {
+    pub fn new<T>(mats: Vec<M>) -> Self
+    where
+        T: Send + Sync,
+        M: Matrix<T>,
+    {
+        let width = mats.first().map_or(0, |mat| mat.width());
+        assert!(mats.iter().all(|mat| mat.width() == width));
+        let row_offsets = offsets(mats.iter().map(|mat| mat.height()));
+        Self {
+            mats,
+            row_offsets,
+            width,
+        }
+    }
+
+    pub fn mats(&self) -> &[M] {
+        &self.mats
+    }
+
+    pub fn into_mats(self) -> Vec<M> {
+        self.mats
+    }
+
+    /// The index of the first row of each matrix within the chain.
+    pub fn row_offsets(&self) -> &[usize] {
+        &self.row_offsets[..self.mats.len()]
+    }
+}
+
+impl<M> HorizontalChain<M> {
+    pub fn new<T>(mats: Vec<M>) -> Self
+    where
+        T: Send + Sync,
+        M: Matrix<T>,
+    {
+        let height = mats.first().map_or(0, |mat| mat.height());
+        assert!(mats.iter().all(|mat| mat.height() == height));
+        let col_offsets = offsets(mats.iter().map(|mat| mat.width()));
+        Self {
+            mats,
+            col_offsets,
+            height,
+        }
+    }
+
+    pub fn mats(&self) -> &[M] {
+        &self.mats
+    }
+
+    pub fn into_mats(self) -> Vec<M> {
+        self.mats
+    }
+
+    /// The index of the first column of each matrix within the chain.
+    pub fn col_offsets(&self) -> &[usize] {
+        &self.col_offsets[..self.mats.len()]
+    }
+}
+
 impl<T: Send + Sync, First: Matrix<T>, Second: Matrix<T>> Matrix<T>
     for VerticalPair<First, Second>
 {
@@ -111,6 +209,85 @@ impl<T: Send + Sync, First: Matrix<T>, Second: Matrix<T>> Matrix<T>
     }
 }
 
+impl<T: Send + Sync, M: Matrix<T>> Matrix<T> for VerticalChain<M> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        *self.row_offsets.last().unwrap()
+    }
+
+    fn get(&self, r: usize, c: usize) -> T {
+        let (i, r) = locate(&self.row_offsets, r);
+        self.mats[i].get(r, c)
+    }
+
+    type Row<'a>
+        = M::Row<'a>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        let (i, r) = locate(&self.row_offsets, r);
+        self.mats[i].row(r)
+    }
+
+    fn row_slice(&self, r: usize) -> impl Deref<Target = [T]> {
+        let (i, r) = locate(&self.row_offsets, r);
+        self.mats[i].row_slice(r)
+    }
+}
+
+impl<T: Send + Sync, M: Matrix<T>> Matrix<T> for HorizontalChain<M> {
+    fn width(&self) -> usize {
+        *self.col_offsets.last().unwrap()
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, r: usize, c: usize) -> T {
+        let (i, c) = locate(&self.col_offsets, c);
+        self.mats[i].get(r, c)
+    }
+
+    type Row<'a>
+        = HorizontalChainRow<'a, T, M>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        assert!(r < self.height);
+        HorizontalChainRow {
+            mats: self.mats.iter(),
+            r,
+            current: None,
+        }
+    }
+}
+
+/// A row of a `HorizontalChain`, made of the rows of each of its matrices in turn.
+pub struct HorizontalChainRow<'a, T: Send + Sync, M: Matrix<T>> {
+    mats: slice::Iter<'a, M>,
+    r: usize,
+    current: Option<M::Row<'a>>,
+}
+
+impl<T: Send + Sync, M: Matrix<T>> Iterator for HorizontalChainRow<'_, T, M> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            if let Some(x) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(x);
+            }
+            self.current = Some(self.mats.next()?.row(self.r));
+        }
+    }
+}
+
 /// We use this to wrap both the row iterator and the row slice.
 #[derive(Debug)]
 pub enum EitherRow<L, R> {
@@ -146,3 +323,35 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::dense::RowMajorMatrix;
+
+    #[test]
+    fn chains_match_concatenation() {
+        let a = RowMajorMatrix::new(vec![1, 2, 3, 4], 2);
+        let empty = RowMajorMatrix::new(vec![], 2);
+        let b = RowMajorMatrix::new(vec![5, 6], 2);
+
+        let vertical = VerticalChain::new(vec![a.as_view(), empty.as_view(), b.as_view()]);
+        assert_eq!(vertical.row_offsets(), [0, 2, 2]);
+        assert_eq!(vertical.get(2, 1), 6);
+        assert_eq!(
+            vertical.to_row_major_matrix(),
+            RowMajorMatrix::new(vec![1, 2, 3, 4, 5, 6], 2)
+        );
+
+        let c = RowMajorMatrix::new(vec![7, 8], 1);
+        let horizontal = HorizontalChain::new(vec![a.as_view(), c.as_view()]);
+        assert_eq!(horizontal.col_offsets(), [0, 2]);
+        assert_eq!(horizontal.row(1).collect_vec(), [3, 4, 8]);
+        assert_eq!(horizontal.get(0, 2), 7);
+        assert_eq!(horizontal.width(), 3);
+    }
+}
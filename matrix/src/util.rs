@@ -27,6 +27,40 @@ where
     });
 }
 
+/// The side of the square tiles `par_transpose` copies at a time, small enough for a tile of the
+/// source and one of the destination to stay in L1.
+const TRANSPOSE_TILE: usize = 32;
+
+/// Write the transpose of `src`, a row-major matrix of the given width and height, to `dst`.
+///
+/// Bands of rows of `dst` are transposed in parallel, each a square tile at a time, so that both
+/// the reads and the writes stay in cache.
+#[instrument(level = "debug", skip_all, fields(width, height))]
+pub fn par_transpose<T: Copy + Send + Sync>(src: &[T], dst: &mut [T], width: usize, height: usize) {
+    assert_eq!(src.len(), width * height);
+    assert_eq!(dst.len(), src.len());
+    if src.is_empty() {
+        return;
+    }
+
+    // Each band is `TRANSPOSE_TILE` rows of `dst`, i.e. as many columns of `src`.
+    dst.par_chunks_mut(TRANSPOSE_TILE * height)
+        .enumerate()
+        .for_each(|(band, dst_band)| {
+            let col_start = band * TRANSPOSE_TILE;
+            let band_cols = dst_band.len() / height;
+            for row_start in (0..height).step_by(TRANSPOSE_TILE) {
+                let row_end = (row_start + TRANSPOSE_TILE).min(height);
+                for c in 0..band_cols {
+                    let dst_tile_row = &mut dst_band[c * height + row_start..c * height + row_end];
+                    for (r, d) in (row_start..row_end).zip(dst_tile_row) {
+                        *d = src[r * width + col_start + c];
+                    }
+                }
+            }
+        });
+}
+
 /// Assumes `i < j`.
 pub fn swap_rows<F: Clone + Send + Sync>(mat: &mut RowMajorMatrix<F>, i: usize, j: usize) {
     let w = mat.width();
@@ -0,0 +1,146 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use p3_field::PackedValue;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_symmetric::{CryptographicHasher, PseudoCompressionFunction};
+
+use crate::merkle_tree::{
+    assert_arity, children, compress, first_digest_layer, padded_len, padded_next_len,
+};
+use crate::MerkleTree;
+
+/// Builds a `MerkleTree` over a batch of matrices of equal height, whose rows arrive over time.
+///
+/// Rows are hashed as they're appended, and every complete group of `ARITY` digests is compressed
+/// right away, so `finalize` only has to compute the nodes along the right edge of the tree. The
+/// result is identical to the tree `MerkleTree::new` builds from the whole matrices.
+///
+/// Unlike `MerkleTree::new`, matrices of different heights aren't supported, as the rows of the
+/// shorter matrices would have to be injected at layers which haven't been computed yet.
+#[derive(Debug)]
+pub struct MerkleTreeBuilder<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize = 2>
+where
+    P: PackedValue,
+    PW: PackedValue,
+{
+    hash: H,
+    compress: C,
+    leaves: Vec<RowMajorMatrix<P::Value>>,
+    /// The digests computed so far, starting with the digests of the rows. Each layer holds the
+    /// compressions of the complete groups of `ARITY` digests of the layer below.
+    layers: Vec<Vec<[PW::Value; DIGEST_ELEMS]>>,
+    _phantom: PhantomData<P>,
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTreeBuilder<P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
+    H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
+    H: Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
+    C: Sync,
+{
+    /// A builder for a tree over matrices of the given widths.
+    pub fn new(hash: H, compress: C, widths: &[usize]) -> Self {
+        assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
+        assert_arity::<ARITY>();
+        assert!(!widths.is_empty(), "No matrices given?");
+        Self {
+            hash,
+            compress,
+            leaves: widths
+                .iter()
+                .map(|&width| RowMajorMatrix::new(vec![], width))
+                .collect(),
+            layers: vec![vec![]],
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The number of rows appended so far.
+    pub fn height(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    /// Append the next rows of each matrix. There must be as many rows for each matrix.
+    pub fn append_rows(&mut self, rows: Vec<RowMajorMatrix<P::Value>>) {
+        assert_eq!(rows.len(), self.leaves.len(), "wrong number of matrices");
+        let num_rows = rows[0].height();
+        for (leaf, new_rows) in self.leaves.iter_mut().zip(&rows) {
+            assert_eq!(new_rows.width(), leaf.width(), "wrong matrix width");
+            assert_eq!(new_rows.height(), num_rows, "matrix heights must be equal");
+            leaf.values.extend_from_slice(&new_rows.values);
+        }
+        if num_rows == 0 {
+            return;
+        }
+
+        let digests = first_digest_layer::<P, PW, H, _, DIGEST_ELEMS, ARITY>(
+            &self.hash,
+            rows.iter().collect(),
+        );
+        self.layers[0].extend_from_slice(&digests[..num_rows]);
+
+        // Compress any newly completed groups, all the way up.
+        for i in 0.. {
+            let done = self
+                .layers
+                .get(i + 1)
+                .map_or(0, |layer| layer.len() * ARITY);
+            let complete = (self.layers[i].len() - done) / ARITY * ARITY;
+            if complete == 0 {
+                break;
+            }
+            let compressed = compress::<PW, C, DIGEST_ELEMS, ARITY>(
+                &self.layers[i][done..done + complete],
+                &self.compress,
+            );
+            if self.layers.len() == i + 1 {
+                self.layers.push(vec![]);
+            }
+            self.layers[i + 1].extend_from_slice(&compressed[..complete / ARITY]);
+        }
+    }
+
+    /// Pad the last layers and compute the remaining nodes, up to the root.
+    pub fn finalize(
+        mut self,
+    ) -> MerkleTree<P::Value, PW::Value, RowMajorMatrix<P::Value>, DIGEST_ELEMS, ARITY> {
+        let height = self.height();
+        assert!(height > 0, "No rows given?");
+
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+        let mut layers = Vec::with_capacity(self.layers.len());
+        let mut bottom_layer = core::mem::take(&mut self.layers[0]);
+        bottom_layer.resize(padded_len::<ARITY>(height), default_digest);
+        layers.push(bottom_layer);
+
+        for i in 1.. {
+            let prev_layer: &Vec<_> = layers.last().unwrap();
+            if prev_layer.len() == 1 {
+                break;
+            }
+            // The complete groups were compressed as rows were appended; the rest only now have
+            // all their children, some of which may be padding.
+            let mut layer = self
+                .layers
+                .get_mut(i)
+                .map(core::mem::take)
+                .unwrap_or_default();
+            for j in layer.len()..prev_layer.len() / ARITY {
+                layer.push(self.compress.compress(children(prev_layer, j)));
+            }
+            layer.resize(padded_next_len::<ARITY>(prev_layer.len()), default_digest);
+            layers.push(layer);
+        }
+
+        MerkleTree::from_digest_layers(self.leaves, layers)
+    }
+}
@@ -0,0 +1,264 @@
+//! Checkpoints of `MerkleTree`s, so that proving can resume after the commit phase without
+//! rehashing the committed data.
+//!
+//! A checkpoint starts with [`MERKLE_CHECKPOINT_MAGIC`] and [`MERKLE_CHECKPOINT_VERSION`], followed
+//! by the canonical encoding from `p3_util::canonical_serialization` of the digest layers and,
+//! optionally, the leaf matrices.
+
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+use p3_field::PackedValue;
+use p3_matrix::Matrix;
+use p3_symmetric::{CryptographicHasher, PseudoCompressionFunction};
+use p3_util::canonical_serialization::{
+    from_bytes_with_header, to_bytes_with_header, SerializationError,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::merkle_tree::padded_len;
+use crate::{MerkleDigestLayers, MerkleTree};
+
+/// The version of the checkpoints produced by this module.
+pub const MERKLE_CHECKPOINT_VERSION: u16 = 1;
+
+/// Magic prefix of a `MerkleTree` checkpoint.
+pub const MERKLE_CHECKPOINT_MAGIC: [u8; 4] = *b"P3MT";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheckpointError {
+    /// The checkpoint could not be decoded.
+    Serialization(SerializationError),
+    /// The checkpoint has no leaves, and none were given.
+    MissingLeaves,
+    /// The checkpoint has leaves, but leaves were given too.
+    UnexpectedLeaves,
+    /// The digest layers aren't those of the leaves.
+    Corrupted,
+    /// The tree doesn't have the expected commitment.
+    CommitmentMismatch,
+}
+
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Serialization(err) => write!(f, "invalid checkpoint encoding: {err}"),
+            Self::MissingLeaves => write!(f, "checkpoint has no leaves, and none were given"),
+            Self::UnexpectedLeaves => write!(f, "checkpoint has leaves, but leaves were given"),
+            Self::Corrupted => write!(f, "checkpoint digests don't match its leaves"),
+            Self::CommitmentMismatch => write!(f, "checkpoint doesn't match the commitment"),
+        }
+    }
+}
+
+impl From<SerializationError> for CheckpointError {
+    fn from(err: SerializationError) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+#[derive(Serialize)]
+struct CheckpointRef<'a, M, W, const DIGEST_ELEMS: usize, const ARITY: usize> {
+    leaves: Option<&'a [M]>,
+    #[serde(bound(serialize = "[W; DIGEST_ELEMS]: Serialize"))]
+    digest_layers: &'a MerkleDigestLayers<W, DIGEST_ELEMS, ARITY>,
+}
+
+#[derive(Deserialize)]
+struct Checkpoint<M, W, const DIGEST_ELEMS: usize, const ARITY: usize> {
+    leaves: Option<Vec<M>>,
+    #[serde(bound(deserialize = "[W; DIGEST_ELEMS]: Deserialize<'de>"))]
+    digest_layers: MerkleDigestLayers<W, DIGEST_ELEMS, ARITY>,
+}
+
+impl<F, W, M, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTree<F, W, M, DIGEST_ELEMS, ARITY>
+where
+    F: Clone + Send + Sync,
+    W: Clone,
+    M: Matrix<F>,
+{
+    /// Encode the digest layers and, if `with_leaves`, the leaf matrices. Without them, the same
+    /// leaves must be given to `from_checkpoint_bytes`, e.g. if they're cheaper to recompute than
+    /// to store.
+    pub fn to_checkpoint_bytes(&self, with_leaves: bool) -> Result<Vec<u8>, SerializationError>
+    where
+        M: Serialize,
+        [W; DIGEST_ELEMS]: Serialize,
+    {
+        let checkpoint = CheckpointRef {
+            leaves: with_leaves.then_some(self.leaves.as_slice()),
+            digest_layers: &self.digest_layers,
+        };
+        to_bytes_with_header(
+            MERKLE_CHECKPOINT_MAGIC,
+            MERKLE_CHECKPOINT_VERSION,
+            &checkpoint,
+        )
+    }
+
+    /// Decode a tree encoded by `to_checkpoint_bytes`, with `leaves` if they weren't encoded.
+    ///
+    /// The layers above the leaf digests are recomputed and checked against the stored ones, as
+    /// are the digests of the rows of all but the tallest matrices, so any corruption of the
+    /// digests changes the root. The rows of the tallest matrices aren't rehashed, as that's the
+    /// bulk of the work a checkpoint saves, so the root should still be checked against the
+    /// commitment (see `MerkleTreeMmcs::prover_data_from_checkpoint`).
+    pub fn from_checkpoint_bytes<P, PW, H, C>(
+        h: &H,
+        c: &C,
+        bytes: &[u8],
+        leaves: Option<Vec<M>>,
+    ) -> Result<Self, CheckpointError>
+    where
+        P: PackedValue<Value = F>,
+        PW: PackedValue<Value = W>,
+        H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
+        H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
+        H: Sync,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>,
+        C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
+        C: Sync,
+        M: DeserializeOwned,
+        [W; DIGEST_ELEMS]: DeserializeOwned,
+    {
+        let checkpoint: Checkpoint<M, W, DIGEST_ELEMS, ARITY> =
+            from_bytes_with_header(MERKLE_CHECKPOINT_MAGIC, MERKLE_CHECKPOINT_VERSION, bytes)?;
+        let leaves = match (checkpoint.leaves, leaves) {
+            (Some(leaves), None) | (None, Some(leaves)) => leaves,
+            (None, None) => return Err(CheckpointError::MissingLeaves),
+            (Some(_), Some(_)) => return Err(CheckpointError::UnexpectedLeaves),
+        };
+        let max_height = leaves
+            .iter()
+            .map(|m| m.height())
+            .max()
+            .ok_or(CheckpointError::MissingLeaves)?;
+
+        let layers = checkpoint.digest_layers.layers();
+        let leaf_digests = layers
+            .first()
+            .filter(|layer| layer.len() == padded_len::<ARITY>(max_height))
+            .ok_or(CheckpointError::Corrupted)?[..max_height]
+            .to_vec();
+        let tree = Self::from_leaf_digests::<P, PW, H, C>(h, c, leaves, leaf_digests);
+
+        let recomputed_layers = tree.digest_layers.layers().iter().map(|layer| &**layer);
+        if recomputed_layers.eq(layers.iter().map(|layer| &**layer)) {
+            Ok(tree)
+        } else {
+            Err(CheckpointError::Corrupted)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use itertools::Itertools;
+    use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+    use p3_commit::Mmcs;
+    use p3_field::{AbstractField, Field};
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::Matrix;
+    use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use rand::thread_rng;
+
+    use super::CheckpointError;
+    use crate::MerkleTreeMmcs;
+
+    type F = BabyBear;
+
+    type Perm = Poseidon2<F, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type MyMmcs =
+        MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, MyCompress, 8>;
+
+    fn mmcs() -> MyMmcs {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        MyMmcs::new(hash, compress).with_cap_height(1)
+    }
+
+    #[test]
+    fn checkpoint_round_trip() {
+        let mmcs = mmcs();
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 100, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 13, 5),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats.clone());
+
+        let bytes = prover_data.to_checkpoint_bytes(true).unwrap();
+        let restored = mmcs
+            .prover_data_from_checkpoint::<RowMajorMatrix<F>>(&bytes, &commit, None)
+            .unwrap();
+        let (opened_values, proof) = mmcs.open_batch(42, &restored);
+        assert_eq!(
+            (opened_values.clone(), proof.clone()),
+            mmcs.open_batch(42, &prover_data)
+        );
+        mmcs.verify_batch(&commit, &dims, 42, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let bytes = prover_data.to_checkpoint_bytes(false).unwrap();
+        assert_eq!(
+            mmcs.prover_data_from_checkpoint::<RowMajorMatrix<F>>(&bytes, &commit, None)
+                .unwrap_err(),
+            CheckpointError::MissingLeaves
+        );
+        let restored = mmcs
+            .prover_data_from_checkpoint(&bytes, &commit, Some(mats))
+            .unwrap();
+        assert_eq!(restored.cap(1), commit);
+    }
+
+    #[test]
+    fn corrupted_checkpoint_fails() {
+        let mmcs = mmcs();
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 100, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 13, 5),
+        ];
+        let (commit, prover_data) = mmcs.commit(mats.clone());
+        let bytes = prover_data.to_checkpoint_bytes(false).unwrap();
+
+        // A flipped bit in the root, which is encoded last.
+        let mut corrupted = bytes.clone();
+        let len = corrupted.len();
+        corrupted[len - 4] ^= 1;
+        assert_eq!(
+            mmcs.prover_data_from_checkpoint(&corrupted, &commit, Some(mats.clone()))
+                .unwrap_err(),
+            CheckpointError::Corrupted
+        );
+
+        // Different rows of the shorter matrix.
+        let mut other_mats = mats.clone();
+        other_mats[1].values[0] += F::ONE;
+        assert_eq!(
+            mmcs.prover_data_from_checkpoint(&bytes, &commit, Some(other_mats))
+                .unwrap_err(),
+            CheckpointError::Corrupted
+        );
+
+        // A checkpoint of another tree.
+        let (other_commit, _) =
+            mmcs.commit(vec![RowMajorMatrix::<F>::rand(&mut thread_rng(), 100, 3)]);
+        assert_eq!(
+            mmcs.prover_data_from_checkpoint(&bytes, &other_commit, Some(mats))
+                .unwrap_err(),
+            CheckpointError::CommitmentMismatch
+        );
+    }
+}
@@ -1,17 +1,18 @@
 use alloc::vec::Vec;
-use core::cell::RefCell;
 
 use itertools::Itertools;
 use p3_commit::Mmcs;
 use p3_field::PackedValue;
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::row_block::RowBlockMatrix;
 use p3_matrix::stack::HorizontalPair;
 use p3_matrix::{Dimensions, Matrix};
-use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
+use p3_symmetric::{CryptographicHasher, MerkleCap, PseudoCompressionFunction};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use spin::Mutex;
 
 use crate::{MerkleTree, MerkleTreeError, MerkleTreeMmcs};
 
@@ -25,8 +26,14 @@ use crate::{MerkleTree, MerkleTreeError, MerkleTreeMmcs};
 /// (`P::Value`) is at least the target security parameter.
 ///
 /// `R` should be an appropriately seeded cryptographically secure pseudorandom number generator
-/// (CSPRNG). Something like `ThreadRng` may work, although it relies on the operating system to
-/// provide sufficient entropy.
+/// (CSPRNG). Something like `StdRng::from_entropy()` may work, although it relies on the operating
+/// system to provide sufficient entropy. The RNG is kept behind a lock so that the MMCS is `Sync`
+/// whenever `R` is `Send`, which the parallel FRI prover requires.
+///
+/// The salts are part of the opening proofs. Note that hiding the committed values only hides the
+/// rows which aren't opened; for a zero knowledge proof, the opened rows (and any evaluations
+/// derived from the committed polynomials) must reveal nothing either, which is up to the
+/// protocol using the MMCS, e.g. by blinding the committed polynomials.
 ///
 /// Generics:
 /// - `P`: a leaf value
@@ -34,15 +41,38 @@ use crate::{MerkleTree, MerkleTreeError, MerkleTreeMmcs};
 /// - `H`: the leaf hasher
 /// - `C`: the digest compression function
 /// - `R`: a random number generator for blinding leaves
-#[derive(Clone, Debug)]
-pub struct MerkleTreeHidingMmcs<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
+/// - `ARITY`: the arity of the tree, as in `MerkleTreeMmcs`
+#[derive(Debug)]
+pub struct MerkleTreeHidingMmcs<
+    P,
+    PW,
+    H,
+    C,
+    R,
+    const DIGEST_ELEMS: usize,
+    const SALT_ELEMS: usize,
+    const ARITY: usize = 2,
+> {
+    inner: MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>,
+    rng: Mutex<R>,
+}
+
+impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize, const ARITY: usize> Clone
+    for MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS, ARITY>
+where
+    MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>: Clone,
+    R: Clone,
 {
-    inner: MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS>,
-    rng: RefCell<R>,
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            rng: Mutex::new(self.rng.lock().clone()),
+        }
+    }
 }
 
-impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
-    MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS>
+impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize, const ARITY: usize>
+    MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS, ARITY>
 {
     pub fn new(hash: H, compress: C, rng: R) -> Self {
         let inner = MerkleTreeMmcs::new(hash, compress);
@@ -51,10 +81,40 @@ impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
             rng: rng.into(),
         }
     }
+
+    /// See `MerkleTreeMmcs::with_cap_height`.
+    pub fn with_cap_height(self, cap_height: usize) -> Self {
+        Self {
+            inner: self.inner.with_cap_height(cap_height),
+            rng: self.rng,
+        }
+    }
+}
+
+impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize, const ARITY: usize>
+    MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS, ARITY>
+where
+    P: PackedValue,
+    R: Rng,
+    Standard: Distribution<P::Value>,
+{
+    /// Append `SALT_ELEMS` random columns to each matrix.
+    fn salt<M: Matrix<P::Value>>(
+        &self,
+        inputs: Vec<M>,
+    ) -> Vec<HorizontalPair<M, RowMajorMatrix<P::Value>>> {
+        inputs
+            .into_iter()
+            .map(|mat| {
+                let salts = RowMajorMatrix::rand(&mut *self.rng.lock(), mat.height(), SALT_ELEMS);
+                HorizontalPair::new(mat, salts)
+            })
+            .collect()
+    }
 }
 
-impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize> Mmcs<P::Value>
-    for MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS>
+impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize, const ARITY: usize>
+    Mmcs<P::Value> for MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS, ARITY>
 where
     P: PackedValue,
     P::Value: Serialize + DeserializeOwned,
@@ -62,17 +122,22 @@ where
     H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
     H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
     H: Sync,
-    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>,
-    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
     C: Sync,
     R: Rng + Clone,
     PW::Value: Eq,
     [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
     Standard: Distribution<P::Value>,
 {
-    type ProverData<M> =
-        MerkleTree<P::Value, PW::Value, HorizontalPair<M, RowMajorMatrix<P::Value>>, DIGEST_ELEMS>;
-    type Commitment = Hash<P::Value, PW::Value, DIGEST_ELEMS>;
+    type ProverData<M> = MerkleTree<
+        P::Value,
+        PW::Value,
+        HorizontalPair<M, RowMajorMatrix<P::Value>>,
+        DIGEST_ELEMS,
+        ARITY,
+    >;
+    type Commitment = MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>;
     /// The first item is salts; the second is the usual Merkle proof (sibling digests).
     type Proof = (Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>);
     type Error = MerkleTreeError;
@@ -81,15 +146,14 @@ where
         &self,
         inputs: Vec<M>,
     ) -> (Self::Commitment, Self::ProverData<M>) {
-        let salted_inputs = inputs
-            .into_iter()
-            .map(|mat| {
-                let salts =
-                    RowMajorMatrix::rand(&mut *self.rng.borrow_mut(), mat.height(), SALT_ELEMS);
-                HorizontalPair::new(mat, salts)
-            })
-            .collect();
-        self.inner.commit(salted_inputs)
+        self.inner.commit(self.salt(inputs))
+    }
+
+    fn commit_row_blocks<M: RowBlockMatrix<P::Value>>(
+        &self,
+        inputs: Vec<M>,
+    ) -> (Self::Commitment, Self::ProverData<M>) {
+        self.inner.commit_row_blocks(self.salt(inputs))
     }
 
     fn open_batch<M: Matrix<P::Value>>(
@@ -101,14 +165,27 @@ where
         (Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>),
     ) {
         let (salted_openings, siblings) = self.inner.open_batch(index, prover_data);
-        let (openings, salts): (Vec<_>, Vec<_>) = salted_openings
+        let (openings, salts) = unsalt::<_, SALT_ELEMS>(salted_openings);
+        (openings, (salts, siblings))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn open_many<M: Matrix<P::Value>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> Vec<(
+        Vec<Vec<P::Value>>,
+        (Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>),
+    )> {
+        self.inner
+            .open_many(indices, prover_data)
             .into_iter()
-            .map(|row| {
-                let (a, b) = row.split_at(row.len() - SALT_ELEMS);
-                (a.to_vec(), b.to_vec())
+            .map(|(salted_openings, siblings)| {
+                let (openings, salts) = unsalt::<_, SALT_ELEMS>(salted_openings);
+                (openings, (salts, siblings))
             })
-            .unzip();
-        (openings, (salts, siblings))
+            .collect()
     }
 
     fn get_matrices<'a, M: Matrix<P::Value>>(
@@ -127,6 +204,12 @@ where
         proof: &Self::Proof,
     ) -> Result<(), Self::Error> {
         let (salts, siblings) = proof;
+        if salts.len() != opened_values.len() {
+            return Err(MerkleTreeError::WrongBatchSize);
+        }
+        if salts.iter().any(|salt| salt.len() != SALT_ELEMS) {
+            return Err(MerkleTreeError::WrongWidth);
+        }
 
         let opened_salted_values = opened_values
             .iter()
@@ -139,6 +222,20 @@ where
     }
 }
 
+/// Split the salts off the ends of opened rows.
+#[allow(clippy::type_complexity)]
+fn unsalt<T: Clone, const SALT_ELEMS: usize>(
+    salted_openings: Vec<Vec<T>>,
+) -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+    salted_openings
+        .into_iter()
+        .map(|row| {
+            let (a, b) = row.split_at(row.len() - SALT_ELEMS);
+            (a.to_vec(), b.to_vec())
+        })
+        .unzip()
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
@@ -217,4 +314,70 @@ mod tests {
         let (opened_values, proof) = mmcs.open_batch(17, &prover_data);
         mmcs.verify_batch(&commit, &dims, 17, &opened_values, &proof)
     }
+
+    #[test]
+    fn salts_hide_and_open() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress, thread_rng());
+
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 16, 3);
+        let (commit_1, _) = mmcs.commit(vec![mat.clone()]);
+        let (commit_2, prover_data) = mmcs.commit(vec![mat.clone()]);
+        assert_ne!(commit_1, commit_2);
+
+        let (opened_values, proof) = mmcs.open_batch(9, &prover_data);
+        assert_eq!(opened_values, vec![mat.row(9).collect_vec()]);
+        assert_eq!(proof.0.len(), 1);
+        assert_eq!(proof.0[0].len(), SALT_ELEMS);
+        mmcs.verify_batch(&commit_2, &[mat.dimensions()], 9, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let mut truncated_salt = proof.clone();
+        truncated_salt.0[0].pop();
+        assert!(matches!(
+            mmcs.verify_batch(
+                &commit_2,
+                &[mat.dimensions()],
+                9,
+                &opened_values,
+                &truncated_salt
+            ),
+            Err(MerkleTreeError::WrongWidth)
+        ));
+    }
+
+    #[test]
+    fn seeded_salts_are_reproducible() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        type SeededMmcs = MerkleTreeHidingMmcs<
+            <F as Field>::Packing,
+            <F as Field>::Packing,
+            MyHash,
+            MyCompress,
+            StdRng,
+            8,
+            SALT_ELEMS,
+        >;
+        let new_mmcs = || SeededMmcs::new(hash.clone(), compress.clone(), StdRng::seed_from_u64(1));
+
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 8, 2);
+        let mmcs_1 = new_mmcs();
+        let mmcs_2 = new_mmcs();
+        assert_eq!(
+            mmcs_1.commit(vec![mat.clone()]).0,
+            mmcs_2.commit(vec![mat]).0
+        );
+    }
 }
@@ -1,11 +1,19 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "mmap")]
+extern crate std;
 
+mod builder;
+#[cfg(feature = "checkpoint")]
+mod checkpoint;
 mod hiding_mmcs;
 mod merkle_tree;
 mod mmcs;
 
+pub use builder::*;
+#[cfg(feature = "checkpoint")]
+pub use checkpoint::*;
 pub use hiding_mmcs::*;
 pub use merkle_tree::*;
 pub use mmcs::*;
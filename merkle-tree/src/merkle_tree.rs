@@ -2,37 +2,240 @@ use alloc::vec;
 use alloc::vec::Vec;
 use core::array;
 use core::cmp::Reverse;
+use core::convert::Infallible;
 use core::marker::PhantomData;
+use core::ops::{Deref, Range};
+#[cfg(feature = "mmap")]
+use std::io;
+#[cfg(feature = "mmap")]
+use std::path::Path;
 
 use itertools::Itertools;
 use p3_field::PackedValue;
+use p3_matrix::dense::RowMajorMatrix;
+#[cfg(feature = "mmap")]
+use p3_matrix::mmap::MmapBuffer;
+use p3_matrix::row_block::RowBlockMatrix;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
-use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
-use serde::{Deserialize, Serialize};
+use p3_symmetric::{CryptographicHasher, Hash, MerkleCap, PseudoCompressionFunction};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tracing::instrument;
 
-/// A binary Merkle tree for packed data. It has leaves of type `F` and digests of type
-/// `[W; DIGEST_ELEMS]`.
+/// A Merkle tree for packed data. It has leaves of type `F` and digests of type
+/// `[W; DIGEST_ELEMS]`, and each node has `ARITY` children, where `ARITY` is a power of two.
+/// Binary trees are the default.
 ///
 /// This generally shouldn't be used directly. If you're using a Merkle tree as an MMCS,
 /// see `MerkleTreeMmcs`.
+///
+/// The leaf matrices are only needed to open rows, so once all openings are done they can be
+/// dropped with `into_digest_layers`, or moved to cheaper storage with `map_leaves`, leaving just
+/// the digests.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct MerkleTree<F, W, M, const DIGEST_ELEMS: usize> {
+pub struct MerkleTree<F, W, M, const DIGEST_ELEMS: usize, const ARITY: usize = 2> {
     pub(crate) leaves: Vec<M>,
     // Enable serialization for this type whenever the underlying array type supports it (len 1-32).
     #[serde(bound(serialize = "[W; DIGEST_ELEMS]: Serialize"))]
     // Enable deserialization for this type whenever the underlying array type supports it (len 1-32).
     #[serde(bound(deserialize = "[W; DIGEST_ELEMS]: Deserialize<'de>"))]
-    pub(crate) digest_layers: Vec<Vec<[W; DIGEST_ELEMS]>>,
+    pub(crate) digest_layers: MerkleDigestLayers<W, DIGEST_ELEMS, ARITY>,
     _phantom: PhantomData<F>,
 }
 
-impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
-    MerkleTree<F, W, M, DIGEST_ELEMS>
+/// The digest layers of a `MerkleTree`, from the leaf digests up to the root, without the leaf
+/// data itself.
+///
+/// This is all that's needed to produce authentication paths.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MerkleDigestLayers<W, const DIGEST_ELEMS: usize, const ARITY: usize = 2> {
+    #[serde(bound(serialize = "[W; DIGEST_ELEMS]: Serialize"))]
+    #[serde(bound(deserialize = "[W; DIGEST_ELEMS]: Deserialize<'de>"))]
+    layers: Vec<DigestLayer<[W; DIGEST_ELEMS]>>,
+}
+
+/// A layer of digests, held in memory, or with the `mmap` feature, in a memory-mapped file.
+#[derive(Debug)]
+pub enum DigestLayer<T> {
+    Memory(Vec<T>),
+    #[cfg(feature = "mmap")]
+    Mapped(MmapBuffer<T>),
+}
+
+/// A memory-mapped layer is cloned into memory.
+impl<T: Clone> Clone for DigestLayer<T> {
+    fn clone(&self) -> Self {
+        Self::Memory(self.to_vec())
+    }
+}
+
+impl<T> Deref for DigestLayer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Self::Memory(digests) => digests,
+            #[cfg(feature = "mmap")]
+            Self::Mapped(digests) => digests,
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for DigestLayer<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.deref().serialize(serializer)
+    }
+}
+
+/// Layers are always deserialized into memory.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for DigestLayer<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self::Memory)
+    }
+}
+
+impl<T> DigestLayer<T> {
+    /// The digests, mutably. A memory-mapped layer is updated in place.
+    fn to_mut(&mut self) -> &mut [T] {
+        match self {
+            Self::Memory(digests) => digests,
+            #[cfg(feature = "mmap")]
+            Self::Mapped(digests) => digests,
+        }
+    }
+}
+
+impl<W: Copy, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleDigestLayers<W, DIGEST_ELEMS, ARITY>
+{
+    /// The layers, starting with the digests of the tallest matrices' rows and ending with the
+    /// root.
+    pub fn layers(&self) -> &[DigestLayer<[W; DIGEST_ELEMS]>] {
+        &self.layers
+    }
+
+    /// The number of layers below the root. An authentication path has `ARITY - 1` siblings for
+    /// each of them.
+    pub fn depth(&self) -> usize {
+        self.layers.len() - 1
+    }
+
+    #[must_use]
+    pub fn root(&self) -> [W; DIGEST_ELEMS] {
+        self.layers.last().unwrap()[0]
+    }
+
+    /// The layer `cap_height` layers below the root, or the bottom layer if the tree isn't that
+    /// deep. A cap of height 0 is just the root.
+    ///
+    /// Rows mixed in above the cap aren't bound by it, since openings are only checked up to the
+    /// cap. `MerkleTreeMmcs` lowers its cap height where needed to avoid this.
+    pub fn cap(&self, cap_height: usize) -> &[[W; DIGEST_ELEMS]] {
+        &self.layers[self.depth().saturating_sub(cap_height)]
+    }
+
+    /// The siblings on the path from leaf `index` up to the root, bottom layer first. The
+    /// siblings of each node are in order, skipping the node itself.
+    pub fn authentication_path(&self, index: usize) -> Vec<[W; DIGEST_ELEMS]> {
+        self.authentication_path_to_cap(index, 0)
+    }
+
+    /// Like `authentication_path`, but stopping at the cap of height `cap_height`.
+    pub fn authentication_path_to_cap(
+        &self,
+        mut index: usize,
+        cap_height: usize,
+    ) -> Vec<[W; DIGEST_ELEMS]> {
+        let num_layers = self.depth().saturating_sub(cap_height);
+        let mut path = Vec::with_capacity(num_layers * (ARITY - 1));
+        for layer in &self.layers[..num_layers] {
+            let first_sibling = index / ARITY * ARITY;
+            path.extend(
+                (first_sibling..first_sibling + ARITY)
+                    .filter(|&i| i != index)
+                    .map(|i| layer[i]),
+            );
+            index /= ARITY;
+        }
+        path
+    }
+
+    /// The `authentication_path_to_cap` of each of `indices`, gathered a layer at a time.
+    pub fn authentication_paths_to_cap(
+        &self,
+        indices: &[usize],
+        cap_height: usize,
+    ) -> Vec<Vec<[W; DIGEST_ELEMS]>> {
+        let num_layers = self.depth().saturating_sub(cap_height);
+        let mut paths = vec![Vec::with_capacity(num_layers * (ARITY - 1)); indices.len()];
+        let mut nodes = indices.to_vec();
+        for layer in &self.layers[..num_layers] {
+            for (path, node) in paths.iter_mut().zip(&mut nodes) {
+                let first_sibling = *node / ARITY * ARITY;
+                path.extend(
+                    (first_sibling..first_sibling + ARITY)
+                        .filter(|&i| i != *node)
+                        .map(|i| layer[i]),
+                );
+                *node /= ARITY;
+            }
+        }
+        paths
+    }
+
+    /// The siblings needed to authenticate the leaves at all of `indices` at once, up to the cap
+    /// of height `cap_height`. Each layer lists, bottom layer first, the children of the nodes on
+    /// some path which aren't themselves on a path, in order. Paths share their upper nodes, so
+    /// this is much shorter than separate authentication paths when there are many indices.
+    pub fn multi_authentication_path_to_cap(
+        &self,
+        indices: &[usize],
+        cap_height: usize,
+    ) -> Vec<[W; DIGEST_ELEMS]> {
+        let num_layers = self.depth().saturating_sub(cap_height);
+        let mut nodes = indices
+            .iter()
+            .copied()
+            .sorted_unstable()
+            .dedup()
+            .collect_vec();
+        let mut path = Vec::new();
+        for layer in &self.layers[..num_layers] {
+            let parents = nodes.iter().map(|i| i / ARITY).dedup().collect_vec();
+            for &parent in &parents {
+                path.extend(
+                    (parent * ARITY..(parent + 1) * ARITY)
+                        .filter(|i| nodes.binary_search(i).is_err())
+                        .map(|i| layer[i]),
+                );
+            }
+            nodes = parents;
+        }
+        path
+    }
+
+    /// Drop all layers but the root.
+    pub fn into_root(self) -> [W; DIGEST_ELEMS] {
+        self.root()
+    }
+}
+
+impl<
+        F: Clone + Send + Sync,
+        W: Clone,
+        M: Matrix<F>,
+        const DIGEST_ELEMS: usize,
+        const ARITY: usize,
+    > MerkleTree<F, W, M, DIGEST_ELEMS, ARITY>
 {
     /// Matrix heights need not be powers of two. However, if the heights of two given matrices
     /// round up to the same power of two, they must be equal.
+    ///
+    /// Each matrix is mixed into the layer of the same (padded) height, so for trees of higher
+    /// arity, the ratio of the padded heights of any two matrices must be a power of `ARITY`, e.g.
+    /// heights `64` and `4` are fine for a 4-ary tree, but `64` and `8` are not. The exception is
+    /// the top layer, which may be narrower than `ARITY` if the tallest height isn't a power of
+    /// `ARITY`, e.g. heights `32`, `2` and `1` are fine for a 4-ary tree.
     #[instrument(name = "build merkle tree", level = "debug", skip_all,
                  fields(dimensions = alloc::format!("{:?}", leaves.iter().map(|l| l.dimensions()).collect::<Vec<_>>())))]
     pub fn new<P, PW, H, C>(h: &H, c: &C, leaves: Vec<M>) -> Self
@@ -42,14 +245,209 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
         H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
         H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
         H: Sync,
-        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], 2>,
-        C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>,
+        C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
         C: Sync,
     {
-        assert!(!leaves.is_empty(), "No matrices given?");
+        assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
 
+        Self::build(
+            leaves,
+            |tallest_matrices| {
+                first_digest_layer::<P, PW, H, M, DIGEST_ELEMS, ARITY>(h, tallest_matrices)
+            },
+            |prev_layer, matrices_to_inject| {
+                compress_and_inject::<P, PW, H, C, M, DIGEST_ELEMS, ARITY>(
+                    prev_layer,
+                    matrices_to_inject,
+                    h,
+                    c,
+                )
+            },
+            |layer| Ok::<_, Infallible>(DigestLayer::Memory(layer)),
+        )
+        .unwrap_or_else(|never| match never {})
+    }
+
+    /// Like `new`, but with the digests of the rows of the tallest matrices computed elsewhere,
+    /// e.g. on a GPU, so that only the digests of the rows of any shorter matrices and the
+    /// compressions are computed here. `leaf_digests` must have one digest per row of the tallest
+    /// matrices, as `h` would have hashed them.
+    #[instrument(name = "build merkle tree from leaf digests", level = "debug", skip_all,
+                 fields(dimensions = alloc::format!("{:?}", leaves.iter().map(|l| l.dimensions()).collect::<Vec<_>>())))]
+    pub fn from_leaf_digests<P, PW, H, C>(
+        h: &H,
+        c: &C,
+        leaves: Vec<M>,
+        mut leaf_digests: Vec<[W; DIGEST_ELEMS]>,
+    ) -> Self
+    where
+        P: PackedValue<Value = F>,
+        PW: PackedValue<Value = W>,
+        H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
+        H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
+        H: Sync,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>,
+        C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
+        C: Sync,
+        W: Copy + Default,
+    {
         assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
 
+        Self::build(
+            leaves,
+            |tallest_matrices| {
+                let max_height = tallest_matrices[0].height();
+                assert_eq!(
+                    leaf_digests.len(),
+                    max_height,
+                    "wrong number of leaf digests"
+                );
+                leaf_digests.resize(
+                    padded_len::<ARITY>(max_height),
+                    [W::default(); DIGEST_ELEMS],
+                );
+                leaf_digests
+            },
+            |prev_layer, matrices_to_inject| {
+                compress_and_inject::<P, PW, H, C, M, DIGEST_ELEMS, ARITY>(
+                    prev_layer,
+                    matrices_to_inject,
+                    h,
+                    c,
+                )
+            },
+            |layer| Ok::<_, Infallible>(DigestLayer::Memory(layer)),
+        )
+        .unwrap_or_else(|never| match never {})
+    }
+
+    /// Like `new`, but each digest layer is moved to a memory-mapped file in `dir` as soon as it's
+    /// computed, so that at most one layer is in memory at a time. With leaves which are also
+    /// held in memory-mapped files (see `MmapRowMajorMatrix`), this lets us build trees much larger
+    /// than memory, at the cost of disk bandwidth.
+    #[cfg(feature = "mmap")]
+    #[instrument(name = "build memory-mapped merkle tree", level = "debug", skip_all,
+                 fields(dimensions = alloc::format!("{:?}", leaves.iter().map(|l| l.dimensions()).collect::<Vec<_>>())))]
+    pub fn new_mmap<P, PW, H, C>(h: &H, c: &C, leaves: Vec<M>, dir: &Path) -> io::Result<Self>
+    where
+        P: PackedValue<Value = F>,
+        PW: PackedValue<Value = W>,
+        H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
+        H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
+        H: Sync,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>,
+        C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
+        C: Sync,
+        W: bytemuck::NoUninit,
+    {
+        assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
+
+        Self::build(
+            leaves,
+            |tallest_matrices| {
+                first_digest_layer::<P, PW, H, M, DIGEST_ELEMS, ARITY>(h, tallest_matrices)
+            },
+            |prev_layer, matrices_to_inject| {
+                compress_and_inject::<P, PW, H, C, M, DIGEST_ELEMS, ARITY>(
+                    prev_layer,
+                    matrices_to_inject,
+                    h,
+                    c,
+                )
+            },
+            // SAFETY: `W: NoUninit`, and arrays have no padding between their elements, so digests
+            // meet the requirements of `NoUninit` too.
+            |layer| unsafe { MmapBuffer::new_in_unchecked(dir, &layer) }.map(DigestLayer::Mapped),
+        )
+    }
+
+    /// Like `new`, but leaf data is hashed one block of rows at a time (see `RowBlockMatrix`), so
+    /// that at most one block of each matrix is materialized at once. The resulting tree is
+    /// identical to the one built by `new`.
+    #[instrument(name = "build merkle tree from row blocks", level = "debug", skip_all,
+                 fields(dimensions = alloc::format!("{:?}", leaves.iter().map(|l| l.dimensions()).collect::<Vec<_>>())))]
+    pub fn new_streaming<P, PW, H, C>(h: &H, c: &C, leaves: Vec<M>) -> Self
+    where
+        P: PackedValue<Value = F>,
+        PW: PackedValue<Value = W>,
+        H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
+        H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
+        H: Sync,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>,
+        C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
+        C: Sync,
+        W: Copy + Default,
+        M: RowBlockMatrix<F>,
+    {
+        assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
+
+        Self::build(
+            leaves,
+            |tallest_matrices| {
+                let max_height = tallest_matrices[0].height();
+                let mut digests =
+                    vec![[W::default(); DIGEST_ELEMS]; padded_len::<ARITY>(max_height)];
+                for rows in row_blocks(&tallest_matrices) {
+                    let blocks = tallest_matrices
+                        .iter()
+                        .map(|m| m.row_block(rows.clone()))
+                        .collect_vec();
+                    let block_digests = first_digest_layer::<P, PW, H, _, DIGEST_ELEMS, ARITY>(
+                        h,
+                        blocks.iter().collect(),
+                    );
+                    digests[rows.clone()].copy_from_slice(&block_digests[..rows.len()]);
+                }
+                digests
+            },
+            |prev_layer, matrices_to_inject| {
+                if matrices_to_inject.is_empty() {
+                    return compress::<PW, C, DIGEST_ELEMS, ARITY>(prev_layer, c);
+                }
+                let next_len = matrices_to_inject[0].height();
+                let default_digest = [W::default(); DIGEST_ELEMS];
+                let mut next_digests =
+                    vec![default_digest; padded_next_len::<ARITY>(prev_layer.len())];
+                for rows in row_blocks(&matrices_to_inject) {
+                    let blocks = matrices_to_inject
+                        .iter()
+                        .map(|m| m.row_block(rows.clone()))
+                        .collect_vec();
+                    let block_digests = compress_and_inject::<P, PW, H, C, _, DIGEST_ELEMS, ARITY>(
+                        &prev_layer[ARITY * rows.start..ARITY * rows.end],
+                        blocks.iter().collect(),
+                        h,
+                        c,
+                    );
+                    next_digests[rows.clone()].copy_from_slice(&block_digests[..rows.len()]);
+                }
+                // Past the height of the injected matrices, there is no leaf data to mix in.
+                #[allow(clippy::needless_range_loop)]
+                for i in next_len..(prev_layer.len() / ARITY) {
+                    let digest = c.compress(children(prev_layer, i));
+                    next_digests[i] =
+                        c.compress(inject_input(digest, default_digest, default_digest));
+                }
+                next_digests
+            },
+            |layer| Ok::<_, Infallible>(DigestLayer::Memory(layer)),
+        )
+        .unwrap_or_else(|never| match never {})
+    }
+
+    /// Builds a tree given functions which compute the first digest layer from the tallest
+    /// matrices, and which compute each subsequent layer from the previous one and the matrices
+    /// (if any) to inject at that layer. Each layer is passed to `store_layer` once computed.
+    fn build<E>(
+        leaves: Vec<M>,
+        first_layer: impl FnOnce(Vec<&M>) -> Vec<[W; DIGEST_ELEMS]>,
+        next_layer: impl Fn(&[[W; DIGEST_ELEMS]], Vec<&M>) -> Vec<[W; DIGEST_ELEMS]>,
+        mut store_layer: impl FnMut(Vec<[W; DIGEST_ELEMS]>) -> Result<DigestLayer<[W; DIGEST_ELEMS]>, E>,
+    ) -> Result<Self, E> {
+        assert!(!leaves.is_empty(), "No matrices given?");
+        assert_arity::<ARITY>();
+
         let mut leaves_largest_first = leaves
             .iter()
             .sorted_by_key(|l| Reverse(l.height()))
@@ -71,49 +469,298 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
             .peeking_take_while(|m| m.height() == max_height)
             .collect_vec();
 
-        let mut digest_layers = vec![first_digest_layer::<P, PW, H, M, DIGEST_ELEMS>(
-            h,
-            tallest_matrices,
-        )];
+        let mut digest_layers = vec![store_layer(first_layer(tallest_matrices))?];
+        // The height of the current layer, as if the tallest matrices were padded to a power of
+        // two.
+        let mut layer_height_padded = max_height.next_power_of_two();
         loop {
-            let prev_layer = digest_layers.last().unwrap().as_slice();
+            let prev_layer: &[_] = digest_layers.last().unwrap();
             if prev_layer.len() == 1 {
                 break;
             }
-            let next_layer_len = (prev_layer.len() / 2).next_power_of_two();
+            layer_height_padded = layer_height_padded.div_ceil(ARITY);
 
             // The matrices that get injected at this layer.
             let matrices_to_inject = leaves_largest_first
-                .peeking_take_while(|m| m.height().next_power_of_two() == next_layer_len)
+                .peeking_take_while(|m| m.height().next_power_of_two() == layer_height_padded)
                 .collect_vec();
 
-            let next_digests = compress_and_inject::<P, PW, H, C, M, DIGEST_ELEMS>(
-                prev_layer,
-                matrices_to_inject,
-                h,
-                c,
-            );
-            digest_layers.push(next_digests);
+            let next_digests = next_layer(prev_layer, matrices_to_inject);
+            digest_layers.push(store_layer(next_digests)?);
         }
+        assert!(
+            leaves_largest_first.peek().is_none(),
+            "matrix heights must be a power of the arity apart"
+        );
 
-        Self {
+        Ok(Self {
             leaves,
-            digest_layers,
+            digest_layers: MerkleDigestLayers {
+                layers: digest_layers,
+            },
             _phantom: PhantomData,
+        })
+    }
+}
+
+impl<F, W, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTree<F, W, RowMajorMatrix<F>, DIGEST_ELEMS, ARITY>
+where
+    F: Clone + Send + Sync,
+    W: Copy + Default,
+{
+    /// Overwrite rows `row_range` of leaf matrix `matrix_idx` with `new_rows`, given in row-major
+    /// order, and return the new root.
+    ///
+    /// Only the digests of the changed rows and the digests above them are recomputed, so this is
+    /// much cheaper than rebuilding the tree when few rows change. `h` and `c` must be those the
+    /// tree was built with. Memory-mapped layers are updated in place.
+    pub fn update_rows<H, C>(
+        &mut self,
+        h: &H,
+        c: &C,
+        matrix_idx: usize,
+        row_range: Range<usize>,
+        new_rows: &[F],
+    ) -> Hash<F, W, DIGEST_ELEMS>
+    where
+        H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>,
+    {
+        let matrix = &mut self.leaves[matrix_idx];
+        assert!(row_range.end <= matrix.height(), "rows out of range");
+        assert_eq!(
+            new_rows.len(),
+            row_range.len() * matrix.width,
+            "wrong number of values"
+        );
+        if row_range.is_empty() {
+            return self.root();
+        }
+        matrix.values[row_range.start * matrix.width..row_range.end * matrix.width]
+            .clone_from_slice(new_rows);
+
+        let leaves_by_layer = self.leaves_by_layer();
+        let first_layer = leaves_by_layer
+            .iter()
+            .position(|indices| indices.contains(&matrix_idx))
+            .unwrap();
+        let default_digest = [W::default(); DIGEST_ELEMS];
+
+        let mut nodes = row_range.collect_vec();
+        let layers = &mut self.digest_layers.layers;
+        for (layer_idx, injected) in leaves_by_layer.iter().enumerate().skip(first_layer) {
+            let injected = injected.iter().map(|&i| &self.leaves[i]).collect_vec();
+            let (lower_layers, upper_layers) = layers.split_at_mut(layer_idx);
+            let layer = upper_layers[0].to_mut();
+            for &i in &nodes {
+                let hash_rows = || h.hash_iter(injected.iter().flat_map(|m| m.row(i)));
+                layer[i] = match lower_layers.last() {
+                    None => hash_rows(),
+                    Some(prev_layer) => {
+                        let digest = c.compress(children(prev_layer, i));
+                        if injected.is_empty() {
+                            digest
+                        } else {
+                            let rows_digest = if i < injected[0].height() {
+                                hash_rows()
+                            } else {
+                                default_digest
+                            };
+                            c.compress(inject_input(digest, rows_digest, default_digest))
+                        }
+                    }
+                };
+            }
+            nodes = nodes.iter().map(|i| i / ARITY).dedup().collect();
         }
+        self.root()
     }
 
+    /// The indices of the leaf matrices mixed into each digest layer, as in `build`.
+    fn leaves_by_layer(&self) -> Vec<Vec<usize>> {
+        let indices_largest_first = (0..self.leaves.len())
+            .sorted_by_key(|&i| Reverse(self.leaves[i].height()))
+            .collect_vec();
+        let max_height = self.leaves[indices_largest_first[0]].height();
+        let mut layer_height_padded = max_height.next_power_of_two();
+        let mut remaining = indices_largest_first.into_iter().peekable();
+        let mut leaves_by_layer = vec![remaining
+            .peeking_take_while(|&i| self.leaves[i].height() == max_height)
+            .collect_vec()];
+        for _ in 1..self.digest_layers.layers.len() {
+            layer_height_padded = layer_height_padded.div_ceil(ARITY);
+            leaves_by_layer.push(
+                remaining
+                    .peeking_take_while(|&i| {
+                        self.leaves[i].height().next_power_of_two() == layer_height_padded
+                    })
+                    .collect(),
+            );
+        }
+        leaves_by_layer
+    }
+}
+
+impl<F, W, M, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTree<F, W, M, DIGEST_ELEMS, ARITY>
+{
     #[must_use]
     pub fn root(&self) -> Hash<F, W, DIGEST_ELEMS>
     where
         W: Copy,
     {
-        self.digest_layers.last().unwrap()[0].into()
+        self.digest_layers.root().into()
+    }
+
+    /// See `MerkleDigestLayers::cap`.
+    #[must_use]
+    pub fn cap(&self, cap_height: usize) -> MerkleCap<F, W, DIGEST_ELEMS>
+    where
+        W: Copy,
+    {
+        self.digest_layers.cap(cap_height).to_vec().into()
+    }
+
+    pub fn leaves(&self) -> &[M] {
+        &self.leaves
+    }
+
+    pub const fn digest_layers(&self) -> &MerkleDigestLayers<W, DIGEST_ELEMS, ARITY> {
+        &self.digest_layers
+    }
+
+    /// Assemble a tree from its leaves and its digest layers, which must have been computed from
+    /// them as `build` would have.
+    pub(crate) fn from_digest_layers(leaves: Vec<M>, layers: Vec<Vec<[W; DIGEST_ELEMS]>>) -> Self {
+        Self {
+            leaves,
+            digest_layers: MerkleDigestLayers {
+                layers: layers.into_iter().map(DigestLayer::Memory).collect(),
+            },
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Assemble a tree from its leaves and digest layers computed elsewhere, e.g. on a GPU, from
+    /// the leaf digests up to the root. Only the lengths of the layers are checked.
+    ///
+    /// # Safety
+    /// The layers must be exactly those `new` would have computed from `leaves`. Nothing here
+    /// checks this, and openings of a tree with wrong digests won't verify, or worse, may verify
+    /// against a commitment to different data.
+    pub unsafe fn from_digest_layers_unchecked(
+        leaves: Vec<M>,
+        layers: Vec<Vec<[W; DIGEST_ELEMS]>>,
+    ) -> Self
+    where
+        M: Matrix<F>,
+        F: Send + Sync,
+    {
+        let max_height = leaves
+            .iter()
+            .map(|m| m.height())
+            .max()
+            .expect("No matrices given?");
+        let mut expected_len = padded_len::<ARITY>(max_height);
+        for layer in &layers {
+            assert_eq!(layer.len(), expected_len, "wrong digest layer length");
+            expected_len = padded_next_len::<ARITY>(expected_len);
+        }
+        assert_eq!(
+            layers.last().map(Vec::len),
+            Some(1),
+            "missing digest layers"
+        );
+        Self::from_digest_layers(leaves, layers)
+    }
+
+    /// Split the tree into its leaf matrices and its digest layers.
+    pub fn into_parts(self) -> (Vec<M>, MerkleDigestLayers<W, DIGEST_ELEMS, ARITY>) {
+        (self.leaves, self.digest_layers)
+    }
+
+    /// Drop the leaf matrices, e.g. once all openings are done, keeping only the digests.
+    pub fn into_digest_layers(self) -> MerkleDigestLayers<W, DIGEST_ELEMS, ARITY> {
+        self.digest_layers
+    }
+
+    /// Replace each leaf matrix by `f` of it, e.g. to spill it to disk with a disk-backed
+    /// `Matrix`. `f` must preserve the contents of each matrix, as the digests aren't recomputed.
+    pub fn map_leaves<N>(self, f: impl FnMut(M) -> N) -> MerkleTree<F, W, N, DIGEST_ELEMS, ARITY> {
+        MerkleTree {
+            leaves: self.leaves.into_iter().map(f).collect(),
+            digest_layers: self.digest_layers,
+            _phantom: PhantomData,
+        }
     }
 }
 
+pub(crate) const fn assert_arity<const ARITY: usize>() {
+    assert!(
+        ARITY >= 2 && ARITY.is_power_of_two(),
+        "arity must be a power of two"
+    );
+}
+
+/// The length of the first digest layer for matrices of height `max_height`. We always want a
+/// multiple of `ARITY` digests, except when it's the root.
+pub(crate) const fn padded_len<const ARITY: usize>(max_height: usize) -> usize {
+    if max_height == 1 {
+        1
+    } else {
+        max_height.next_multiple_of(ARITY)
+    }
+}
+
+/// The length of the digest layer following one of length `prev_len`. We always want a multiple
+/// of `ARITY` digests, except when it's the root.
+pub(crate) const fn padded_next_len<const ARITY: usize>(prev_len: usize) -> usize {
+    if prev_len == ARITY {
+        1
+    } else {
+        (prev_len / ARITY).next_multiple_of(ARITY)
+    }
+}
+
+/// The children of node `i` of the layer above `layer`.
+pub(crate) fn children<T: Copy, const ARITY: usize>(layer: &[T], i: usize) -> [T; ARITY] {
+    array::from_fn(|j| layer[ARITY * i + j])
+}
+
+/// The input of the compression which mixes the digest of some injected rows into the digest of a
+/// node. This is `[digest, injected]` for binary trees, and is padded with `default` otherwise.
+pub(crate) fn inject_input<T: Copy, const ARITY: usize>(
+    digest: T,
+    injected: T,
+    default: T,
+) -> [T; ARITY] {
+    array::from_fn(|j| match j {
+        0 => digest,
+        1 => injected,
+        _ => default,
+    })
+}
+
+/// Splits the rows of `matrices`, which must have equal heights, into blocks no taller than any
+/// of their preferred block heights.
+fn row_blocks<F: Send + Sync, M: RowBlockMatrix<F>>(
+    matrices: &[&M],
+) -> impl Iterator<Item = Range<usize>> {
+    let height = matrices[0].height();
+    let block_height = matrices
+        .iter()
+        .map(|m| m.block_height())
+        .min()
+        .unwrap()
+        .max(1);
+    (0..height)
+        .step_by(block_height)
+        .map(move |start| start..(start + block_height).min(height))
+}
+
 #[instrument(name = "first digest layer", level = "debug", skip_all)]
-fn first_digest_layer<P, PW, H, M, const DIGEST_ELEMS: usize>(
+pub(crate) fn first_digest_layer<P, PW, H, M, const DIGEST_ELEMS: usize, const ARITY: usize>(
     h: &H,
     tallest_matrices: Vec<&M>,
 ) -> Vec<[PW::Value; DIGEST_ELEMS]>
@@ -127,21 +774,18 @@ where
 {
     let width = PW::WIDTH;
     let max_height = tallest_matrices[0].height();
-    // we always want to return an even number of digests, except when it's the root.
-    let max_height_padded = if max_height == 1 {
-        1
-    } else {
-        max_height + max_height % 2
-    };
+    let max_height_padded = padded_len::<ARITY>(max_height);
 
     let default_digest: [PW::Value; DIGEST_ELEMS] = [PW::Value::default(); DIGEST_ELEMS];
     let mut digests = vec![default_digest; max_height_padded];
 
     digests[0..max_height]
-        .par_chunks_exact_mut(width)
+        .par_chunks_mut(width)
         .enumerate()
         .for_each(|(i, digests_chunk)| {
             let first_row = i * width;
+            // If the packing width doesn't divide max_height, the lanes of the last chunk past
+            // the end wrap around to the first rows, and their digests are dropped by the zip.
             let packed_digest: [PW; DIGEST_ELEMS] = h.hash_iter(
                 tallest_matrices
                     .iter()
@@ -152,20 +796,12 @@ where
             }
         });
 
-    // If our packing width did not divide max_height, fall back to single-threaded scalar code
-    // for the last bit.
-    #[allow(clippy::needless_range_loop)]
-    for i in (max_height / width * width)..max_height {
-        digests[i] = h.hash_iter(tallest_matrices.iter().flat_map(|m| m.row(i)));
-    }
-
-    // Everything has been initialized so we can safely cast.
     digests
 }
 
-/// Compress `n` digests from the previous layer into `n/2` digests, while potentially mixing in
-/// some leaf data, if there are input matrices with (padded) height `n/2`.
-fn compress_and_inject<P, PW, H, C, M, const DIGEST_ELEMS: usize>(
+/// Compress `n` digests from the previous layer into `n/ARITY` digests, while potentially mixing
+/// in some leaf data, if there are input matrices with (padded) height `n/ARITY`.
+fn compress_and_inject<P, PW, H, C, M, const DIGEST_ELEMS: usize, const ARITY: usize>(
     prev_layer: &[[PW::Value; DIGEST_ELEMS]],
     matrices_to_inject: Vec<&M>,
     h: &H,
@@ -177,119 +813,120 @@ where
     H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
     H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
     H: Sync,
-    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>,
-    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
     C: Sync,
     M: Matrix<P::Value>,
 {
     if matrices_to_inject.is_empty() {
-        return compress::<PW, C, DIGEST_ELEMS>(prev_layer, c);
+        return compress::<PW, C, DIGEST_ELEMS, ARITY>(prev_layer, c);
     }
 
     let width = PW::WIDTH;
     let next_len = matrices_to_inject[0].height();
-    // We always want to return an even number of digests, except when it's the root.
-    let next_len_padded = if prev_layer.len() == 2 {
-        1
-    } else {
-        (prev_layer.len() / 2 + 1) & !1
-    };
+    let next_len_padded = padded_next_len::<ARITY>(prev_layer.len());
 
     let default_digest: [PW::Value; DIGEST_ELEMS] = [PW::Value::default(); DIGEST_ELEMS];
+    let packed_default_digest: [PW; DIGEST_ELEMS] = default_digest.map(|x| PW::from_fn(|_| x));
     let mut next_digests = vec![default_digest; next_len_padded];
-    next_digests[0..next_len]
-        .par_chunks_exact_mut(width)
+    // Past the height of the matrices to inject, default_digest takes the place of the digest of
+    // their rows. We only need go as far as the length of the previous layer divided by the arity.
+    next_digests[0..prev_layer.len() / ARITY]
+        .par_chunks_mut(width)
         .enumerate()
         .for_each(|(i, digests_chunk)| {
             let first_row = i * width;
-            let left = array::from_fn(|j| PW::from_fn(|k| prev_layer[2 * (first_row + k)][j]));
-            let right = array::from_fn(|j| PW::from_fn(|k| prev_layer[2 * (first_row + k) + 1][j]));
-            let mut packed_digest = c.compress([left, right]);
-            let tallest_digest = h.hash_iter(
-                matrices_to_inject
-                    .iter()
-                    .flat_map(|m| m.vertically_packed_row(first_row)),
-            );
-            packed_digest = c.compress([packed_digest, tallest_digest]);
+            let children = packed_children::<PW, DIGEST_ELEMS, ARITY>(prev_layer, first_row);
+            let packed_digest = c.compress(children);
+            let rows_digest = if first_row + width <= next_len {
+                h.hash_iter(
+                    matrices_to_inject
+                        .iter()
+                        .flat_map(|m| m.vertically_packed_row(first_row)),
+                )
+            } else if first_row < next_len {
+                // The chunk straddles the height of the matrices to inject, so the lanes past it
+                // take default_digest in place of the digest of the (wrapped around) rows.
+                let rows_digest: [PW; DIGEST_ELEMS] = h.hash_iter(
+                    matrices_to_inject
+                        .iter()
+                        .flat_map(|m| m.vertically_packed_row(first_row)),
+                );
+                rows_digest.map(|x| {
+                    PW::from_fn(|j| {
+                        if first_row + j < next_len {
+                            x.as_slice()[j]
+                        } else {
+                            PW::Value::default()
+                        }
+                    })
+                })
+            } else {
+                packed_default_digest
+            };
+            let packed_digest = c.compress(inject_input(
+                packed_digest,
+                rows_digest,
+                packed_default_digest,
+            ));
             for (dst, src) in digests_chunk.iter_mut().zip(unpack_array(packed_digest)) {
                 *dst = src;
             }
         });
 
-    // If our packing width did not divide next_len, fall back to single-threaded scalar code
-    // for the last bit.
-    for i in (next_len / width * width)..next_len {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        let digest = c.compress([left, right]);
-        let rows_digest = h.hash_iter(matrices_to_inject.iter().flat_map(|m| m.row(i)));
-        next_digests[i] = c.compress([digest, rows_digest]);
-    }
-
-    // At this point, we've exceeded the height of the matrices to inject, so we continue the
-    // process above except with default_digest in place of an input digest.
-    // We only need go as far as half the length of the previous layer.
-    for i in next_len..(prev_layer.len() / 2) {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        let digest = c.compress([left, right]);
-        next_digests[i] = c.compress([digest, default_digest]);
-    }
-
     next_digests
 }
 
-/// Compress `n` digests from the previous layer into `n/2` digests.
-fn compress<P, C, const DIGEST_ELEMS: usize>(
+/// Compress `n` digests from the previous layer into `n/ARITY` digests.
+pub(crate) fn compress<P, C, const DIGEST_ELEMS: usize, const ARITY: usize>(
     prev_layer: &[[P::Value; DIGEST_ELEMS]],
     c: &C,
 ) -> Vec<[P::Value; DIGEST_ELEMS]>
 where
     P: PackedValue,
-    C: PseudoCompressionFunction<[P::Value; DIGEST_ELEMS], 2>,
-    C: PseudoCompressionFunction<[P; DIGEST_ELEMS], 2>,
+    C: PseudoCompressionFunction<[P::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[P; DIGEST_ELEMS], ARITY>,
     C: Sync,
 {
     let width = P::WIDTH;
-    // Always return an even number of digests, except when it's the root.
-    let next_len_padded = if prev_layer.len() == 2 {
-        1
-    } else {
-        (prev_layer.len() / 2 + 1) & !1
-    };
-    let next_len = prev_layer.len() / 2;
+    let next_len_padded = padded_next_len::<ARITY>(prev_layer.len());
+    let next_len = prev_layer.len() / ARITY;
 
     let default_digest: [P::Value; DIGEST_ELEMS] = [P::Value::default(); DIGEST_ELEMS];
     let mut next_digests = vec![default_digest; next_len_padded];
 
     next_digests[0..next_len]
-        .par_chunks_exact_mut(width)
+        .par_chunks_mut(width)
         .enumerate()
         .for_each(|(i, digests_chunk)| {
             let first_row = i * width;
-            let left = array::from_fn(|j| P::from_fn(|k| prev_layer[2 * (first_row + k)][j]));
-            let right = array::from_fn(|j| P::from_fn(|k| prev_layer[2 * (first_row + k) + 1][j]));
-            let packed_digest = c.compress([left, right]);
+            let children = packed_children::<P, DIGEST_ELEMS, ARITY>(prev_layer, first_row);
+            let packed_digest = c.compress(children);
             for (dst, src) in digests_chunk.iter_mut().zip(unpack_array(packed_digest)) {
                 *dst = src;
             }
         });
 
-    // If our packing width did not divide next_len, fall back to single-threaded scalar code
-    // for the last bit.
-    for i in (next_len / width * width)..next_len {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        next_digests[i] = c.compress([left, right]);
-    }
-
-    // Everything has been initialized so we can safely cast.
     next_digests
 }
 
+/// The children of nodes `first_row..first_row + P::WIDTH` of the layer above `layer`, packed.
+/// Lanes past the last node take the children of the last node, so that a short last chunk can
+/// still be compressed packed, with the extra lanes ignored.
+#[inline]
+fn packed_children<P: PackedValue, const DIGEST_ELEMS: usize, const ARITY: usize>(
+    layer: &[[P::Value; DIGEST_ELEMS]],
+    first_row: usize,
+) -> [[P; DIGEST_ELEMS]; ARITY] {
+    let last_node = layer.len() / ARITY - 1;
+    array::from_fn(|child| {
+        array::from_fn(|j| P::from_fn(|k| layer[ARITY * (first_row + k).min(last_node) + child][j]))
+    })
+}
+
 /// Converts a packed array `[P; N]` into its underlying `P::WIDTH` scalar arrays.
 #[inline]
-fn unpack_array<P: PackedValue, const N: usize>(
+pub(crate) fn unpack_array<P: PackedValue, const N: usize>(
     packed_digest: [P; N],
 ) -> impl Iterator<Item = [P::Value; N]> {
     (0..P::WIDTH).map(move |j| packed_digest.map(|p| p.as_slice()[j]))
@@ -1,17 +1,26 @@
+use alloc::collections::BTreeMap;
+use alloc::vec;
 use alloc::vec::Vec;
+use core::array;
 use core::cmp::Reverse;
 use core::marker::PhantomData;
+use core::ops::Range;
 
-use itertools::Itertools;
+use itertools::{izip, Itertools};
 use p3_commit::Mmcs;
 use p3_field::PackedValue;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::row_block::RowBlockMatrix;
 use p3_matrix::{Dimensions, Matrix};
-use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
-use p3_util::log2_ceil_usize;
+use p3_symmetric::{CryptographicHasher, MerkleCap, PseudoCompressionFunction};
+use p3_util::{log2_ceil_usize, log2_strict_usize};
 use serde::{Deserialize, Serialize};
 
-use crate::MerkleTree;
-use crate::MerkleTreeError::{RootMismatch, WrongBatchSize, WrongHeight};
+use crate::merkle_tree::{assert_arity, inject_input, padded_len, padded_next_len, unpack_array};
+use crate::MerkleTreeError::{
+    IncompatibleHeights, RootMismatch, WrongBatchSize, WrongCapSize, WrongHeight, WrongWidth,
+};
+use crate::{MerkleTree, MerkleTreeBuilder};
 
 /// A vector commitment scheme backed by a `MerkleTree`.
 ///
@@ -19,11 +28,20 @@ use crate::MerkleTreeError::{RootMismatch, WrongBatchSize, WrongHeight};
 /// - `P`: a leaf value
 /// - `PW`: an element of a digest
 /// - `H`: the leaf hasher
-/// - `C`: the digest compression function
+/// - `C`: the digest compression function, which compresses `ARITY` digests into one
+/// - `ARITY`: the number of children of each node, a power of two
+///
+/// A higher arity makes for shallower trees, so opening a row takes fewer compressions to verify,
+/// at the cost of `ARITY - 1` siblings per layer in the proof rather than one. See `MerkleTree`
+/// for the constraints on matrix heights.
+///
+/// The commitment is the cap of height `cap_height` (see `with_cap_height`), which is just the
+/// root by default.
 #[derive(Copy, Clone, Debug)]
-pub struct MerkleTreeMmcs<P, PW, H, C, const DIGEST_ELEMS: usize> {
+pub struct MerkleTreeMmcs<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize = 2> {
     hash: H,
     compress: C,
+    cap_height: usize,
     _phantom: PhantomData<(P, PW)>,
 }
 
@@ -36,55 +54,452 @@ pub enum MerkleTreeError {
         num_siblings: usize,
     },
     RootMismatch,
+    /// The commitment isn't a cap of the expected number of digests.
+    WrongCapSize,
+    /// Some matrices' heights don't fit in the tree, so their rows couldn't be checked.
+    IncompatibleHeights,
 }
 
-impl<P, PW, H, C, const DIGEST_ELEMS: usize> MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS> {
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+{
     pub const fn new(hash: H, compress: C) -> Self {
+        assert_arity::<ARITY>();
         Self {
             hash,
             compress,
+            cap_height: 0,
             _phantom: PhantomData,
         }
     }
+
+    /// Commit to the (usually `ARITY^cap_height`) digests `cap_height` layers below the root,
+    /// rather than to the root, so that opening proofs skip those layers.
+    ///
+    /// The verifier only recomputes the layers below the cap, so every matrix must be mixed in at
+    /// or below it. Where a matrix is too short for that, the cap is lowered to the layer that
+    /// matrix is mixed into, and trees which aren't `cap_height` deep are capped at their bottom
+    /// layer (see `commitment`).
+    pub const fn with_cap_height(mut self, cap_height: usize) -> Self {
+        self.cap_height = cap_height;
+        self
+    }
+
+    pub const fn cap_height(&self) -> usize {
+        self.cap_height
+    }
+
+    /// The commitment to `prover_data`: its cap of height `cap_height`, lowered if need be so
+    /// that every matrix is mixed in at or below the cap.
+    pub fn commitment<M: Matrix<P::Value>>(
+        &self,
+        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>,
+    ) -> MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>
+    where
+        P: PackedValue,
+        PW: PackedValue,
+    {
+        prover_data.cap(self.tree_cap_height(prover_data))
+    }
+
+    /// The height of the cap committed to for `tree`, which may be lower than `cap_height`.
+    fn tree_cap_height<F: Send + Sync, W, M: Matrix<F>>(
+        &self,
+        tree: &MerkleTree<F, W, M, DIGEST_ELEMS, ARITY>,
+    ) -> usize {
+        effective_cap_height::<ARITY>(self.cap_height, tree.leaves.iter().map(|m| m.height()))
+    }
+
+    /// The number of layers below the cap for matrices of the given dimensions, checking that
+    /// `commit` is a cap of the right size.
+    fn layers_below_cap(
+        &self,
+        commit: &MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>,
+        dimensions: &[Dimensions],
+    ) -> Result<usize, MerkleTreeError>
+    where
+        P: PackedValue,
+        PW: PackedValue,
+    {
+        let max_height = dimensions.iter().map(|dims| dims.height).max().unwrap();
+        let depth = log2_ceil_usize(max_height).div_ceil(log2_strict_usize(ARITY));
+        let cap_height = effective_cap_height::<ARITY>(
+            self.cap_height,
+            dimensions.iter().map(|dims| dims.height),
+        );
+        let num_layers = depth - cap_height;
+
+        let cap_len = (0..num_layers).fold(padded_len::<ARITY>(max_height), |len, _| {
+            padded_next_len::<ARITY>(len)
+        });
+        if commit.digests().len() != cap_len {
+            return Err(WrongCapSize);
+        }
+        Ok(num_layers)
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
+    H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
+    H: Clone + Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
+    C: Clone + Sync,
+{
+    /// A builder for a tree over matrices of the given widths, whose rows can be appended as
+    /// they're produced. The finalized tree is the prover data `commit` would have returned, and
+    /// its commitment is `self.commitment(&tree)`.
+    pub fn tree_builder(
+        &self,
+        widths: &[usize],
+    ) -> MerkleTreeBuilder<P, PW, H, C, DIGEST_ELEMS, ARITY> {
+        MerkleTreeBuilder::new(self.hash.clone(), self.compress.clone(), widths)
+    }
+
+    /// Like `commit`, but with the digests of the rows of the tallest matrices computed elsewhere,
+    /// e.g. on a GPU. See `MerkleTree::from_leaf_digests`.
+    #[allow(clippy::type_complexity)]
+    pub fn commit_leaf_digests<M: Matrix<P::Value>>(
+        &self,
+        inputs: Vec<M>,
+        leaf_digests: Vec<[PW::Value; DIGEST_ELEMS]>,
+    ) -> (
+        MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>,
+        MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>,
+    ) {
+        let tree = MerkleTree::from_leaf_digests::<P, PW, H, C>(
+            &self.hash,
+            &self.compress,
+            inputs,
+            leaf_digests,
+        );
+        (self.commitment(&tree), tree)
+    }
+
+    /// Overwrite rows `row_range` of input `matrix_idx` in `prover_data` with `new_rows`, and
+    /// return the new commitment. See `MerkleTree::update_rows`.
+    pub fn update_rows(
+        &self,
+        prover_data: &mut MerkleTree<
+            P::Value,
+            PW::Value,
+            RowMajorMatrix<P::Value>,
+            DIGEST_ELEMS,
+            ARITY,
+        >,
+        matrix_idx: usize,
+        row_range: Range<usize>,
+        new_rows: &[P::Value],
+    ) -> MerkleCap<P::Value, PW::Value, DIGEST_ELEMS> {
+        prover_data.update_rows(&self.hash, &self.compress, matrix_idx, row_range, new_rows);
+        self.commitment(prover_data)
+    }
+
+    /// Like `commit`, but with the tree's digest layers held in memory-mapped files in `dir`. See
+    /// `MerkleTree::new_mmap`.
+    #[cfg(feature = "mmap")]
+    #[allow(clippy::type_complexity)]
+    pub fn commit_mmap<M: Matrix<P::Value>>(
+        &self,
+        inputs: Vec<M>,
+        dir: &std::path::Path,
+    ) -> std::io::Result<(
+        MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>,
+        MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>,
+    )>
+    where
+        PW::Value: bytemuck::NoUninit,
+    {
+        let tree = MerkleTree::new_mmap::<P, PW, H, C>(&self.hash, &self.compress, inputs, dir)?;
+        Ok((self.commitment(&tree), tree))
+    }
 }
 
-impl<P, PW, H, C, const DIGEST_ELEMS: usize> Mmcs<P::Value>
-    for MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS>
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
 where
     P: PackedValue,
     PW: PackedValue,
     H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
     H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
     H: Sync,
-    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>,
-    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
     C: Sync,
     PW::Value: Eq,
     [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
 {
-    type ProverData<M> = MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS>;
-    type Commitment = Hash<P::Value, PW::Value, DIGEST_ELEMS>;
-    type Proof = Vec<[PW::Value; DIGEST_ELEMS]>;
-    type Error = MerkleTreeError;
+    /// Open the rows at each of `indices`, like `open_batch`, with a single proof for all of
+    /// them (see `MerkleDigestLayers::multi_authentication_path_to_cap`). Indices may repeat.
+    #[allow(clippy::type_complexity)]
+    pub fn open_batch_many<M: Matrix<P::Value>>(
+        &self,
+        indices: &[usize],
+        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>,
+    ) -> (Vec<Vec<Vec<P::Value>>>, Vec<[PW::Value; DIGEST_ELEMS]>) {
+        let openings = indices
+            .iter()
+            .map(|&index| self.open_rows(index, prover_data))
+            .collect();
+        let proof = prover_data
+            .digest_layers
+            .multi_authentication_path_to_cap(indices, self.tree_cap_height(prover_data));
+        (openings, proof)
+    }
 
-    fn commit<M: Matrix<P::Value>>(
+    /// Decode prover data saved with `MerkleTree::to_checkpoint_bytes`, with `leaves` if they
+    /// weren't saved, checking that it's consistent and that `commit` is its commitment.
+    #[cfg(feature = "checkpoint")]
+    pub fn prover_data_from_checkpoint<M>(
         &self,
-        inputs: Vec<M>,
-    ) -> (Self::Commitment, Self::ProverData<M>) {
-        let tree = MerkleTree::new::<P, PW, H, C>(&self.hash, &self.compress, inputs);
-        let root = tree.root();
-        (root, tree)
+        bytes: &[u8],
+        commit: &MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>,
+        leaves: Option<Vec<M>>,
+    ) -> Result<MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>, crate::CheckpointError>
+    where
+        M: Matrix<P::Value> + for<'de> Deserialize<'de>,
+    {
+        let tree = MerkleTree::from_checkpoint_bytes::<P, PW, H, C>(
+            &self.hash,
+            &self.compress,
+            bytes,
+            leaves,
+        )?;
+        if self.commitment(&tree) == *commit {
+            Ok(tree)
+        } else {
+            Err(crate::CheckpointError::CommitmentMismatch)
+        }
     }
 
-    fn open_batch<M: Matrix<P::Value>>(
+    /// Verify the openings of `open_batch_many`, given in the same order as `indices`.
+    pub fn verify_batch_many(
+        &self,
+        commit: &MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<P::Value>>],
+        proof: &[[PW::Value; DIGEST_ELEMS]],
+    ) -> Result<(), MerkleTreeError> {
+        // Check that the openings have the correct shape.
+        if dimensions.is_empty()
+            || indices.len() != opened_values.len()
+            || opened_values
+                .iter()
+                .any(|openings| openings.len() != dimensions.len())
+        {
+            return Err(WrongBatchSize);
+        }
+
+        let max_height = dimensions.iter().map(|dim| dim.height).max().unwrap();
+        let wrong_height = || WrongHeight {
+            max_height,
+            num_siblings: proof.len(),
+        };
+        let num_layers = self.layers_below_cap(commit, dimensions)?;
+
+        let mut matrix_groups = matrix_groups(dimensions).into_iter().peekable();
+        let (mut layer_height_padded, tallest_matrices) = matrix_groups.next().unwrap();
+        // The digests of the nodes on the paths of the indices, in the current layer.
+        let mut nodes = self.hash_openings(indices, opened_values, &tallest_matrices, 1)?;
+        let mut node_size = 1;
+
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+        let mut siblings = proof.iter();
+        for _ in 0..num_layers {
+            let mut parents = BTreeMap::new();
+            for parent in nodes.keys().map(|i| i / ARITY).dedup().collect_vec() {
+                let mut children = [default_digest; ARITY];
+                for (i, child) in children.iter_mut().enumerate() {
+                    *child = match nodes.get(&(parent * ARITY + i)) {
+                        Some(&digest) => digest,
+                        None => *siblings.next().ok_or_else(wrong_height)?,
+                    };
+                }
+                parents.insert(parent, self.compress.compress(children));
+            }
+            node_size *= ARITY;
+            layer_height_padded = layer_height_padded.div_ceil(ARITY);
+
+            if let Some((_, matrices)) =
+                matrix_groups.next_if(|&(padded_height, _)| padded_height == layer_height_padded)
+            {
+                let injected = self.hash_openings(indices, opened_values, &matrices, node_size)?;
+                for (node, digest) in parents.iter_mut() {
+                    *digest = self.compress.compress(inject_input(
+                        *digest,
+                        injected[node],
+                        default_digest,
+                    ));
+                }
+            }
+            nodes = parents;
+        }
+        if siblings.next().is_some() {
+            return Err(wrong_height());
+        }
+        if matrix_groups.next().is_some() {
+            return Err(IncompatibleHeights);
+        }
+
+        if nodes
+            .iter()
+            .all(|(&node, digest)| commit.digests().get(node) == Some(digest))
+        {
+            Ok(())
+        } else {
+            Err(RootMismatch)
+        }
+    }
+
+    /// Like `verify_batch`, but without allocating: the proof's siblings are read one at a time
+    /// from `siblings`, e.g. as they're decoded from a byte stream, and only a constant number of
+    /// digests are held at once. Matrices are grouped by height by rescanning `dimensions` at each
+    /// layer rather than by sorting them, which is cheap for the handful of matrices in a batch.
+    pub fn verify_streaming<O: AsRef<[P::Value]>>(
+        &self,
+        commit: &MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>,
+        dimensions: &[Dimensions],
+        mut index: usize,
+        opened_values: &[O],
+        siblings: impl IntoIterator<Item = [PW::Value; DIGEST_ELEMS]>,
+    ) -> Result<(), MerkleTreeError> {
+        if dimensions.is_empty() || dimensions.len() != opened_values.len() {
+            return Err(WrongBatchSize);
+        }
+        // The tallest height among the matrices shorter than `bound`.
+        let tallest_below = |bound: usize| {
+            dimensions
+                .iter()
+                .map(|dims| dims.height)
+                .filter(|&height| height < bound)
+                .max()
+        };
+        let hash_rows = |height: usize| {
+            self.hash.hash_iter_slices(
+                dimensions
+                    .iter()
+                    .zip(opened_values)
+                    .filter(|(dims, _)| dims.height == height)
+                    .map(|(_, opened)| opened.as_ref()),
+            )
+        };
+
+        let max_height = tallest_below(usize::MAX).unwrap();
+        let num_layers = self.layers_below_cap(commit, dimensions)?;
+        let mut layer_height_padded = max_height.next_power_of_two();
+        let mut next_height = tallest_below(max_height);
+        let mut root = hash_rows(max_height);
+
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+        let mut siblings = siblings.into_iter();
+        let mut num_siblings = 0;
+        for _ in 0..num_layers {
+            let position = index % ARITY;
+            let mut missing = false;
+            let children = array::from_fn(|i| {
+                if i == position {
+                    return root;
+                }
+                num_siblings += 1;
+                siblings.next().unwrap_or_else(|| {
+                    missing = true;
+                    default_digest
+                })
+            });
+            if missing {
+                return Err(WrongHeight {
+                    max_height,
+                    num_siblings: num_siblings - 1,
+                });
+            }
+
+            root = self.compress.compress(children);
+            index /= ARITY;
+            layer_height_padded = layer_height_padded.div_ceil(ARITY);
+
+            if let Some(height) =
+                next_height.filter(|height| height.next_power_of_two() == layer_height_padded)
+            {
+                root =
+                    self.compress
+                        .compress(inject_input(root, hash_rows(height), default_digest));
+                next_height = tallest_below(height);
+            }
+        }
+        if siblings.next().is_some() {
+            return Err(WrongHeight {
+                max_height,
+                num_siblings: num_siblings + 1,
+            });
+        }
+        if next_height.is_some() {
+            return Err(IncompatibleHeights);
+        }
+
+        if commit.digests().get(index) == Some(&root) {
+            Ok(())
+        } else {
+            Err(RootMismatch)
+        }
+    }
+
+    /// Hash the opened rows of `matrices` for each opening, packing several openings together.
+    fn hash_rows_batch(
+        &self,
+        opened_values: &[&[Vec<P::Value>]],
+        matrices: &[usize],
+    ) -> Vec<[PW::Value; DIGEST_ELEMS]> {
+        let width = P::WIDTH;
+        let mut digests = Vec::with_capacity(opened_values.len());
+        for chunk in opened_values.chunks_exact(width) {
+            let packed_digest: [PW; DIGEST_ELEMS] =
+                self.hash.hash_iter(matrices.iter().flat_map(|&i| {
+                    (0..chunk[0][i].len()).map(move |j| P::from_fn(|k| chunk[k][i][j]))
+                }));
+            digests.extend(unpack_array(packed_digest));
+        }
+        for openings in opened_values.chunks_exact(width).remainder() {
+            digests.push(
+                self.hash
+                    .hash_iter_slices(matrices.iter().map(|&i| openings[i].as_slice())),
+            );
+        }
+        digests
+    }
+
+    /// Compress each of `inputs`, packing several of them together.
+    fn compress_batch(
+        &self,
+        inputs: &[[[PW::Value; DIGEST_ELEMS]; ARITY]],
+    ) -> Vec<[PW::Value; DIGEST_ELEMS]> {
+        let width = PW::WIDTH;
+        let mut digests = Vec::with_capacity(inputs.len());
+        for chunk in inputs.chunks_exact(width) {
+            let packed_input: [[PW; DIGEST_ELEMS]; ARITY] =
+                array::from_fn(|child| array::from_fn(|j| PW::from_fn(|k| chunk[k][child][j])));
+            digests.extend(unpack_array(self.compress.compress(packed_input)));
+        }
+        for &input in inputs.chunks_exact(width).remainder() {
+            digests.push(self.compress.compress(input));
+        }
+        digests
+    }
+
+    fn open_rows<M: Matrix<P::Value>>(
         &self,
         index: usize,
-        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS>,
-    ) -> (Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>) {
+        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>,
+    ) -> Vec<Vec<P::Value>> {
         let max_height = self.get_max_height(prover_data);
         let log_max_height = log2_ceil_usize(max_height);
 
-        let openings = prover_data
+        prover_data
             .leaves
             .iter()
             .map(|matrix| {
@@ -93,15 +508,105 @@ where
                 let reduced_index = index >> bits_reduced;
                 matrix.row(reduced_index).collect()
             })
-            .collect_vec();
+            .collect_vec()
+    }
 
-        let proof: Vec<_> = (0..log_max_height)
-            .map(|i| prover_data.digest_layers[i][(index >> i) ^ 1])
-            .collect();
+    /// Hash the opened rows of `matrices` for each index, keyed by the node of the layer they're
+    /// hashed into, which is `node_size` leaves wide. Indices of the same node must agree.
+    fn hash_openings(
+        &self,
+        indices: &[usize],
+        opened_values: &[Vec<Vec<P::Value>>],
+        matrices: &[usize],
+        node_size: usize,
+    ) -> Result<BTreeMap<usize, [PW::Value; DIGEST_ELEMS]>, MerkleTreeError> {
+        let mut digests = BTreeMap::new();
+        for (&index, openings) in indices.iter().zip(opened_values) {
+            let digest = self
+                .hash
+                .hash_iter_slices(matrices.iter().map(|&i| openings[i].as_slice()));
+            if *digests.entry(index / node_size).or_insert(digest) != digest {
+                return Err(RootMismatch);
+            }
+        }
+        Ok(digests)
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize> Mmcs<P::Value>
+    for MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
+    H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
+    H: Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
+    C: Sync,
+    PW::Value: Eq,
+    [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
+{
+    type ProverData<M> = MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>;
+    type Commitment = MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>;
+    type Proof = Vec<[PW::Value; DIGEST_ELEMS]>;
+    type Error = MerkleTreeError;
+
+    fn commit<M: Matrix<P::Value>>(
+        &self,
+        inputs: Vec<M>,
+    ) -> (Self::Commitment, Self::ProverData<M>) {
+        let tree = MerkleTree::new::<P, PW, H, C>(&self.hash, &self.compress, inputs);
+        (self.commitment(&tree), tree)
+    }
+
+    fn commit_row_blocks<M: RowBlockMatrix<P::Value>>(
+        &self,
+        inputs: Vec<M>,
+    ) -> (Self::Commitment, Self::ProverData<M>) {
+        let tree = MerkleTree::new_streaming::<P, PW, H, C>(&self.hash, &self.compress, inputs);
+        (self.commitment(&tree), tree)
+    }
+
+    fn open_batch<M: Matrix<P::Value>>(
+        &self,
+        index: usize,
+        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>,
+    ) -> (Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>) {
+        let openings = self.open_rows(index, prover_data);
+        let proof = prover_data
+            .digest_layers
+            .authentication_path_to_cap(index, self.tree_cap_height(prover_data));
 
         (openings, proof)
     }
 
+    fn open_many<M: Matrix<P::Value>>(
+        &self,
+        indices: &[usize],
+        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>,
+    ) -> Vec<(Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>)> {
+        let log_max_height = log2_ceil_usize(self.get_max_height(prover_data));
+
+        let mut openings = vec![Vec::with_capacity(prover_data.leaves.len()); indices.len()];
+        for matrix in &prover_data.leaves {
+            let bits_reduced = log_max_height - log2_ceil_usize(matrix.height());
+            // Indices often share rows of the shorter matrices, which we only read once.
+            let mut rows = BTreeMap::new();
+            for (opening, &index) in openings.iter_mut().zip(indices) {
+                let row = rows
+                    .entry(index >> bits_reduced)
+                    .or_insert_with_key(|&r| matrix.row(r).collect_vec());
+                opening.push(row.clone());
+            }
+        }
+        let proofs = prover_data
+            .digest_layers
+            .authentication_paths_to_cap(indices, self.tree_cap_height(prover_data));
+
+        openings.into_iter().zip(proofs).collect()
+    }
+
     fn get_matrices<'a, M: Matrix<P::Value>>(
         &self,
         prover_data: &'a Self::ProverData<M>,
@@ -131,8 +636,8 @@ where
 
         // TODO: Disabled for now, CirclePcs sometimes passes a height that's off by 1 bit.
         let max_height = dimensions.iter().map(|dim| dim.height).max().unwrap();
-        let log_max_height = log2_ceil_usize(max_height);
-        if proof.len() != log_max_height {
+        let num_layers = self.layers_below_cap(commit, dimensions)?;
+        if proof.len() != num_layers * (ARITY - 1) {
             return Err(WrongHeight {
                 max_height,
                 num_siblings: proof.len(),
@@ -160,16 +665,21 @@ where
                 .map(|(i, _)| opened_values[i].as_slice()),
         );
 
-        for &sibling in proof.iter() {
-            let (left, right) = if index & 1 == 0 {
-                (root, sibling)
-            } else {
-                (sibling, root)
-            };
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+        for siblings in proof.chunks_exact(ARITY - 1) {
+            let position = index % ARITY;
+            let mut siblings = siblings.iter();
+            let children = array::from_fn(|i| {
+                if i == position {
+                    root
+                } else {
+                    *siblings.next().unwrap()
+                }
+            });
 
-            root = self.compress.compress([left, right]);
-            index >>= 1;
-            curr_height_padded >>= 1;
+            root = self.compress.compress(children);
+            index /= ARITY;
+            curr_height_padded = curr_height_padded.div_ceil(ARITY);
 
             let next_height = heights_tallest_first
                 .peek()
@@ -182,11 +692,107 @@ where
                         .map(|(i, _)| opened_values[i].as_slice()),
                 );
 
-                root = self.compress.compress([root, next_height_openings_digest]);
+                root = self.compress.compress(inject_input(
+                    root,
+                    next_height_openings_digest,
+                    default_digest,
+                ));
+            }
+        }
+        if heights_tallest_first.peek().is_some() {
+            return Err(IncompatibleHeights);
+        }
+
+        if commit.digests().get(index) == Some(&root) {
+            Ok(())
+        } else {
+            Err(RootMismatch)
+        }
+    }
+
+    fn verify_batches(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        openings: &[(usize, &[Vec<P::Value>], &Self::Proof)],
+    ) -> Result<(), Self::Error> {
+        let Some(&(_, first_opened_values, _)) = openings.first() else {
+            return Ok(());
+        };
+
+        // Check that the openings have the correct shape.
+        let max_height = dimensions.iter().map(|dim| dim.height).max().unwrap();
+        let num_layers = self.layers_below_cap(commit, dimensions)?;
+        for &(_, opened_values, proof) in openings {
+            if opened_values.len() != dimensions.len() {
+                return Err(WrongBatchSize);
+            }
+            if proof.len() != num_layers * (ARITY - 1) {
+                return Err(WrongHeight {
+                    max_height,
+                    num_siblings: proof.len(),
+                });
+            }
+            // Rows of different openings are hashed together with packed values, so their widths
+            // must agree.
+            if opened_values
+                .iter()
+                .zip(first_opened_values)
+                .any(|(row, first_row)| row.len() != first_row.len())
+            {
+                return Err(WrongWidth);
+            }
+        }
+
+        // We verify all openings in lockstep, a layer at a time.
+        let opened_values = openings
+            .iter()
+            .map(|&(_, opened_values, _)| opened_values)
+            .collect_vec();
+        let mut indices = openings.iter().map(|&(index, _, _)| index).collect_vec();
+        let mut matrix_groups = matrix_groups(dimensions).into_iter().peekable();
+        let (mut layer_height_padded, tallest_matrices) = matrix_groups.next().unwrap();
+        let mut roots = self.hash_rows_batch(&opened_values, &tallest_matrices);
+
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+        for layer in 0..num_layers {
+            let inputs = izip!(&roots, &indices, openings)
+                .map(|(&root, &index, &(_, _, proof))| {
+                    let mut siblings = proof[layer * (ARITY - 1)..(layer + 1) * (ARITY - 1)].iter();
+                    array::from_fn(|i| {
+                        if i == index % ARITY {
+                            root
+                        } else {
+                            *siblings.next().unwrap()
+                        }
+                    })
+                })
+                .collect_vec();
+            roots = self.compress_batch(&inputs);
+            indices.iter_mut().for_each(|index| *index /= ARITY);
+            layer_height_padded = layer_height_padded.div_ceil(ARITY);
+
+            if let Some((_, matrices)) =
+                matrix_groups.next_if(|&(padded_height, _)| padded_height == layer_height_padded)
+            {
+                let injected = self.hash_rows_batch(&opened_values, &matrices);
+                let inputs = roots
+                    .iter()
+                    .zip(injected)
+                    .map(|(&root, injected)| inject_input(root, injected, default_digest))
+                    .collect_vec();
+                roots = self.compress_batch(&inputs);
             }
         }
+        if matrix_groups.next().is_some() {
+            return Err(IncompatibleHeights);
+        }
 
-        if commit == &root {
+        if roots
+            .iter()
+            .zip(indices)
+            .all(|(root, index)| commit.digests().get(index) == Some(root))
+        {
             Ok(())
         } else {
             Err(RootMismatch)
@@ -194,23 +800,68 @@ where
     }
 }
 
+/// The indices of the matrices, tallest first, grouped by the padded height of the layer they're
+/// hashed into.
+fn matrix_groups(dimensions: &[Dimensions]) -> Vec<(usize, Vec<usize>)> {
+    let padded_heights_tallest_first = dimensions
+        .iter()
+        .map(|dims| dims.height.next_power_of_two())
+        .enumerate()
+        .sorted_by_key(|&(_, padded_height)| Reverse(padded_height))
+        .collect_vec();
+    padded_heights_tallest_first
+        .chunk_by(|(_, a), (_, b)| a == b)
+        .map(|group| (group[0].1, group.iter().map(|&(i, _)| i).collect_vec()))
+        .collect()
+}
+
+/// The height of the cap committed to for matrices of the given heights: `cap_height`, lowered if
+/// need be so that every matrix is mixed into a layer at or below the cap, and at most the depth
+/// of the tree. The verifier only recomputes the layers below the cap, so rows mixed in above it
+/// wouldn't be bound by the commitment.
+fn effective_cap_height<const ARITY: usize>(
+    cap_height: usize,
+    heights: impl IntoIterator<Item = usize>,
+) -> usize {
+    let (log_min_height, log_max_height) = heights
+        .into_iter()
+        .map(log2_ceil_usize)
+        .minmax()
+        .into_option()
+        .unwrap();
+    let log_arity = log2_strict_usize(ARITY);
+    let depth = log_max_height.div_ceil(log_arity);
+    // The layer the shortest matrices are mixed into.
+    let shortest_layer = (log_max_height - log_min_height).div_ceil(log_arity);
+    cap_height.min(depth - shortest_layer)
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
+    use alloc::vec::Vec;
+    use core::ops::Range;
 
-    use itertools::Itertools;
+    use itertools::{izip, Itertools};
     use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
-    use p3_commit::Mmcs;
+    use p3_commit::{ExtensionMmcs, Mmcs};
+    use p3_field::extension::BinomialExtensionField;
     use p3_field::{AbstractField, Field};
+    use p3_matrix::bitrev::RowOrder;
     use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::extension::PackedExtensionMatrix;
+    use p3_matrix::row_block::RowBlockMatrix;
+    use p3_matrix::util::reverse_matrix_index_bits;
     use p3_matrix::{Dimensions, Matrix};
     use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
     use p3_symmetric::{
-        CryptographicHasher, PaddingFreeSponge, PseudoCompressionFunction, TruncatedPermutation,
+        CompressionFunctionFromHasher, CryptographicHasher, PaddingFreeSponge,
+        PseudoCompressionFunction, TruncatedPermutation,
     };
     use rand::thread_rng;
 
-    use super::MerkleTreeMmcs;
+    use super::{MerkleTreeError, MerkleTreeMmcs};
+    use crate::MerkleTree;
 
     type F = BabyBear;
 
@@ -219,6 +870,12 @@ mod tests {
     type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
     type MyMmcs =
         MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, MyCompress, 8>;
+    type MyCompress4 = CompressionFunctionFromHasher<MyHash, 4, 8>;
+    type MyMmcs4 =
+        MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, MyCompress4, 8, 4>;
+    type MyCompress8 = CompressionFunctionFromHasher<MyHash, 8, 8>;
+    type MyMmcs8 =
+        MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, MyCompress8, 8, 8>;
 
     #[test]
     fn commit_single_1x8() {
@@ -599,4 +1256,903 @@ mod tests {
         mmcs.verify_batch(&commit, &dims, 17, &opened_values, &proof)
             .expect("expected verification to succeed");
     }
+
+    /// A dense matrix which claims to prefer small row blocks, to exercise the streaming builder.
+    struct SmallBlocks(RowMajorMatrix<F>, usize);
+
+    impl Matrix<F> for SmallBlocks {
+        fn width(&self) -> usize {
+            self.0.width()
+        }
+
+        fn height(&self) -> usize {
+            self.0.height()
+        }
+
+        type Row<'a> = <RowMajorMatrix<F> as Matrix<F>>::Row<'a>;
+
+        fn row(&self, r: usize) -> Self::Row<'_> {
+            self.0.row(r)
+        }
+    }
+
+    impl RowBlockMatrix<F> for SmallBlocks {
+        fn block_height(&self) -> usize {
+            self.1
+        }
+
+        fn row_block(&self, rows: Range<usize>) -> impl Matrix<F> + '_ {
+            assert!(rows.len() <= self.1);
+            self.0.row_block(rows)
+        }
+    }
+
+    #[test]
+    fn commit_row_blocks_matches_commit() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        // Mixed heights, so that some matrices are injected at lower layers, with block heights
+        // which don't divide the matrix heights.
+        let shapes = [
+            (1000, 3, 7),
+            (1000, 1, 64),
+            (70, 8, 5),
+            (35, 2, 1),
+            (8, 8, 3),
+            (1, 4, 1),
+        ];
+        let mats = shapes
+            .iter()
+            .map(|&(height, width, _)| RowMajorMatrix::<F>::rand(&mut thread_rng(), height, width))
+            .collect_vec();
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+
+        let (commit, _) = mmcs.commit(mats.clone());
+        let (streaming_commit, prover_data) = mmcs.commit_row_blocks(
+            mats.into_iter()
+                .zip(shapes)
+                .map(|(mat, (_, _, block_height))| SmallBlocks(mat, block_height))
+                .collect_vec(),
+        );
+        assert_eq!(commit, streaming_commit);
+
+        let (opened_values, proof) = mmcs.open_batch(517, &prover_data);
+        mmcs.verify_batch(&commit, &dims, 517, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    fn open_after_moving_leaves() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 100, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 13, 5),
+        ];
+        let (commit, prover_data) = mmcs.commit(mats);
+        let (opened_values, proof) = mmcs.open_batch(42, &prover_data);
+
+        // Moving the leaves to some other storage doesn't change the openings.
+        let prover_data = prover_data.map_leaves(|mat| SmallBlocks(mat, 1));
+        assert_eq!(
+            mmcs.open_batch(42, &prover_data),
+            (opened_values, proof.clone())
+        );
+
+        // Authentication paths don't need the leaves at all.
+        let digest_layers = prover_data.into_digest_layers();
+        assert_eq!(digest_layers.authentication_path(42), proof);
+        assert_eq!(commit, digest_layers.into_root());
+    }
+
+    #[test]
+    fn commit_4ary_single_1x16() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm);
+        let compress = MyCompress4::new(hash.clone());
+        let mmcs = MyMmcs4::new(hash.clone(), compress.clone());
+
+        let v = (0..16).map(F::from_canonical_usize).collect_vec();
+        let (commit, _) = mmcs.commit_vec(v.clone());
+
+        let leaf_digests = v.iter().map(|&x| hash.hash_item(x)).collect_vec();
+        let expected_result = compress.compress(core::array::from_fn(|i| {
+            compress.compress(core::array::from_fn(|j| leaf_digests[4 * i + j]))
+        }));
+        assert_eq!(commit, expected_result);
+    }
+
+    #[test]
+    fn open_and_verify_4ary() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm);
+        let compress = MyCompress4::new(hash.clone());
+        let mmcs = MyMmcs4::new(hash, compress);
+
+        // Padded heights 64, 16, 4 and 1. The tallest height isn't a power of two, nor a power of
+        // the arity.
+        let shapes = [(50, 3, 7), (50, 1, 64), (13, 8, 5), (4, 2, 1), (1, 4, 1)];
+        let mats = shapes
+            .iter()
+            .map(|&(height, width, _)| RowMajorMatrix::<F>::rand(&mut thread_rng(), height, width))
+            .collect_vec();
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+
+        let (commit, prover_data) = mmcs.commit(mats.clone());
+        let (streaming_commit, _) = mmcs.commit_row_blocks(
+            mats.into_iter()
+                .zip(shapes)
+                .map(|(mat, (_, _, block_height))| SmallBlocks(mat, block_height))
+                .collect_vec(),
+        );
+        assert_eq!(commit, streaming_commit);
+
+        for index in [0, 17, 49] {
+            let (opened_values, proof) = mmcs.open_batch(index, &prover_data);
+            // Three layers below the root, with three siblings each.
+            assert_eq!(proof.len(), 9);
+            mmcs.verify_batch(&commit, &dims, index, &opened_values, &proof)
+                .expect("expected verification to succeed");
+
+            let mut tampered_proof = proof.clone();
+            tampered_proof[4][0] += F::ONE;
+            mmcs.verify_batch(&commit, &dims, index, &opened_values, &tampered_proof)
+                .expect_err("expected verification to fail");
+        }
+    }
+
+    #[test]
+    fn open_and_verify_8ary() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm);
+        let compress = MyCompress8::new(hash.clone());
+        let mmcs = MyMmcs8::new(hash, compress);
+
+        // Padded heights 128, 2 and 1, so the top layer has only two nodes.
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 100, 5),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 2, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1, 1),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+
+        let (commit, prover_data) = mmcs.commit(mats);
+        let (opened_values, proof) = mmcs.open_batch(99, &prover_data);
+        assert_eq!(proof.len(), 3 * 7);
+        mmcs.verify_batch(&commit, &dims, 99, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    #[should_panic(expected = "matrix heights must be a power of the arity apart")]
+    fn incompatible_heights_4ary() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm);
+        let compress = MyCompress4::new(hash.clone());
+        let mmcs = MyMmcs4::new(hash, compress);
+
+        mmcs.commit(vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 64, 1),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 8, 1),
+        ]);
+    }
+
+    #[test]
+    fn open_and_verify_with_cap() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash.clone(), compress.clone());
+        let capped_mmcs = MyMmcs::new(hash, compress.clone()).with_cap_height(2);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 100, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 13, 5),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (root, _) = mmcs.commit(mats.clone());
+        let (cap, prover_data) = capped_mmcs.commit(mats);
+
+        // The cap is the layer with four digests, which compress to the root.
+        assert_eq!(cap.len(), 4);
+        let digests = cap.digests();
+        assert_eq!(
+            root,
+            compress.compress([
+                compress.compress([digests[0], digests[1]]),
+                compress.compress([digests[2], digests[3]]),
+            ])
+        );
+
+        let (opened_values, proof) = capped_mmcs.open_batch(42, &prover_data);
+        assert_eq!(proof.len(), 7 - 2);
+        capped_mmcs
+            .verify_batch(&cap, &dims, 42, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let mut tampered_cap = digests.to_vec();
+        tampered_cap[1][0] += F::ONE;
+        capped_mmcs
+            .verify_batch(&tampered_cap.into(), &dims, 42, &opened_values, &proof)
+            .expect_err("expected verification to fail");
+    }
+
+    #[test]
+    fn cap_taller_than_tree() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash.clone(), compress).with_cap_height(5);
+
+        // The cap is the bottom layer, so the proofs are empty.
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 8, 2);
+        let (cap, prover_data) = mmcs.commit(vec![mat.clone()]);
+        assert_eq!(
+            cap.digests(),
+            &(0..8).map(|r| hash.hash_iter(mat.row(r))).collect_vec()
+        );
+
+        let (opened_values, proof) = mmcs.open_batch(5, &prover_data);
+        assert!(proof.is_empty());
+        mmcs.verify_batch(&cap, &[mat.dimensions()], 5, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    fn cap_above_short_matrix() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress).with_cap_height(4);
+
+        // The short matrix is mixed in a layer below the root, so the cap is lowered to that
+        // layer, rather than leaving its rows out of what the verifier checks.
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 64, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 2, 4),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+        assert_eq!(commit, prover_data.cap(1));
+
+        let index = 37;
+        let (opened_values, proof) = mmcs.open_batch(index, &prover_data);
+        assert_eq!(proof.len(), 5);
+        mmcs.verify_batch(&commit, &dims, index, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let mut tampered_values = opened_values.clone();
+        tampered_values[1][0] += F::ONE;
+        mmcs.verify_batch(&commit, &dims, index, &tampered_values, &proof)
+            .expect_err("expected verification to fail");
+        mmcs.verify_streaming(
+            &commit,
+            &dims,
+            index,
+            &tampered_values,
+            proof.iter().copied(),
+        )
+        .expect_err("expected verification to fail");
+        mmcs.verify_batch_many(&commit, &dims, &[index], &[tampered_values.clone()], &proof)
+            .expect_err("expected verification to fail");
+        mmcs.verify_batches(
+            &commit,
+            &dims,
+            &[(index, tampered_values.as_slice(), &proof)],
+        )
+        .expect_err("expected verification to fail");
+
+        // A cap of the requested height, above the short matrix, isn't accepted.
+        let short_proof = proof[..2].to_vec();
+        mmcs.verify_batch(
+            &prover_data.cap(4),
+            &dims,
+            index,
+            &opened_values,
+            &short_proof,
+        )
+        .expect_err("expected verification to fail");
+    }
+
+    #[test]
+    fn tree_builder_matches_commit() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress).with_cap_height(1);
+
+        // Heights which aren't powers of two, appended in batches of uneven sizes, which don't
+        // line up with the groups of siblings.
+        for (height, batch_sizes) in [
+            (1, vec![1]),
+            (2, vec![1, 1]),
+            (37, vec![37]),
+            (37, vec![5, 0, 1, 16, 15]),
+            (64, vec![3; 21].into_iter().chain([1]).collect()),
+        ] {
+            let mats = vec![
+                RowMajorMatrix::<F>::rand(&mut thread_rng(), height, 3),
+                RowMajorMatrix::<F>::rand(&mut thread_rng(), height, 9),
+            ];
+            let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+            let (commit, prover_data) = mmcs.commit(mats.clone());
+
+            let mut builder = mmcs.tree_builder(&[3, 9]);
+            let mut start = 0;
+            for batch_size in batch_sizes {
+                builder.append_rows(
+                    mats.iter()
+                        .map(|mat| rows(mat, start..start + batch_size))
+                        .collect(),
+                );
+                start += batch_size;
+                assert_eq!(builder.height(), start);
+            }
+            let tree = builder.finalize();
+            assert_eq!(mmcs.commitment(&tree), commit);
+            assert_eq!(tree.leaves(), &mats);
+
+            let index = height / 2;
+            let (opened_values, proof) = mmcs.open_batch(index, &tree);
+            assert_eq!(
+                (opened_values.clone(), proof.clone()),
+                mmcs.open_batch(index, &prover_data)
+            );
+            mmcs.verify_batch(&commit, &dims, index, &opened_values, &proof)
+                .expect("expected verification to succeed");
+        }
+    }
+
+    #[test]
+    fn tree_builder_matches_commit_4ary() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm);
+        let compress = MyCompress4::new(hash.clone());
+        let mmcs = MyMmcs4::new(hash, compress);
+
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 50, 4);
+        let (commit, _) = mmcs.commit(vec![mat.clone()]);
+
+        let mut builder = mmcs.tree_builder(&[4]);
+        for start in (0..50).step_by(7) {
+            builder.append_rows(vec![rows(&mat, start..(start + 7).min(50))]);
+        }
+        assert_eq!(builder.finalize().cap(0), commit);
+    }
+
+    #[test]
+    fn open_and_verify_many() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress).with_cap_height(1);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1000, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 70, 8),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1, 4),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        // Some indices share a leaf, and some share the nodes the shorter matrices are hashed into.
+        let indices = [517, 3, 559, 517, 516, 64, 65, 70, 300];
+        let (opened_values, proof) = mmcs.open_batch_many(&indices, &prover_data);
+        mmcs.verify_batch_many(&commit, &dims, &indices, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let separate_proofs = indices
+            .iter()
+            .zip(&opened_values)
+            .map(|(&index, opened)| {
+                let (expected_opened, proof) = mmcs.open_batch(index, &prover_data);
+                assert_eq!(opened, &expected_opened);
+                proof
+            })
+            .collect_vec();
+        assert!(proof.len() < separate_proofs.concat().len() / 2);
+
+        let mut tampered_proof = proof.clone();
+        tampered_proof[10][0] += F::ONE;
+        mmcs.verify_batch_many(&commit, &dims, &indices, &opened_values, &tampered_proof)
+            .expect_err("expected verification to fail");
+        mmcs.verify_batch_many(&commit, &dims, &indices, &opened_values, &proof[1..])
+            .expect_err("expected verification to fail");
+
+        // Openings of the same row must agree.
+        let mut tampered_values = opened_values.clone();
+        tampered_values[3][0][0] += F::ONE;
+        mmcs.verify_batch_many(&commit, &dims, &indices, &tampered_values, &proof)
+            .expect_err("expected verification to fail");
+        // As must openings of the same row of a shorter matrix.
+        let mut tampered_values = opened_values;
+        tampered_values[5][1][0] += F::ONE;
+        mmcs.verify_batch_many(&commit, &dims, &indices, &tampered_values, &proof)
+            .expect_err("expected verification to fail");
+
+        // A batch of no matrices is rejected rather than verified against nothing.
+        assert!(matches!(
+            mmcs.verify_batch_many(&commit, &[], &[], &[], &[]),
+            Err(MerkleTreeError::WrongBatchSize)
+        ));
+    }
+
+    #[test]
+    fn open_and_verify_many_4ary() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm);
+        let compress = MyCompress4::new(hash.clone());
+        let mmcs = MyMmcs4::new(hash, compress);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 50, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 13, 8),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        let indices = (0..50).step_by(3).collect_vec();
+        let (opened_values, proof) = mmcs.open_batch_many(&indices, &prover_data);
+        mmcs.verify_batch_many(&commit, &dims, &indices, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    fn verify_batches_matches_verify_batch() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress).with_cap_height(1);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1000, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 70, 8),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1, 4),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        // More openings than the packing width, so that some are verified unpacked. Rows of the
+        // matrix of height 70 only cover the first 560 leaves, so no index is past them.
+        let indices = [517, 3, 559, 517, 516, 64, 65, 70, 300, 555];
+        let (opened_values, proofs): (Vec<_>, Vec<_>) = indices
+            .iter()
+            .map(|&index| mmcs.open_batch(index, &prover_data))
+            .unzip();
+        fn openings<'a, P>(
+            indices: &[usize],
+            opened_values: &'a [Vec<Vec<F>>],
+            proofs: &'a [P],
+        ) -> Vec<(usize, &'a [Vec<F>], &'a P)> {
+            izip!(indices, opened_values, proofs)
+                .map(|(&index, opened, proof)| (index, opened.as_slice(), proof))
+                .collect_vec()
+        }
+        mmcs.verify_batches(&commit, &dims, &openings(&indices, &opened_values, &proofs))
+            .expect("expected verification to succeed");
+        mmcs.verify_batches(&commit, &dims, &[])
+            .expect("expected verification to succeed");
+
+        // Tampering with any one opening, packed or not, is caught.
+        for i in [2, 9] {
+            let mut tampered_values = opened_values.clone();
+            tampered_values[i][1][0] += F::ONE;
+            mmcs.verify_batches(
+                &commit,
+                &dims,
+                &openings(&indices, &tampered_values, &proofs),
+            )
+            .expect_err("expected verification to fail");
+        }
+        let mut tampered_openings = openings(&indices, &opened_values, &proofs);
+        tampered_openings[4].0 = 515;
+        mmcs.verify_batches(&commit, &dims, &tampered_openings)
+            .expect_err("expected verification to fail");
+    }
+
+    #[test]
+    fn verify_batches_4ary() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm);
+        let compress = MyCompress4::new(hash.clone());
+        let mmcs = MyMmcs4::new(hash, compress);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 50, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 13, 8),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        let openings = (0..50)
+            .step_by(3)
+            .map(|index| {
+                let (opened_values, proof) = mmcs.open_batch(index, &prover_data);
+                (index, opened_values, proof)
+            })
+            .collect_vec();
+        let openings = openings
+            .iter()
+            .map(|(index, opened_values, proof)| (*index, opened_values.as_slice(), proof))
+            .collect_vec();
+        mmcs.verify_batches(&commit, &dims, &openings)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    fn commit_leaf_digests_matches_commit() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash.clone(), compress).with_cap_height(1);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1000, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1000, 5),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 70, 8),
+        ];
+        let (expected_commit, expected_prover_data) = mmcs.commit(mats.clone());
+
+        // As if hashed elsewhere.
+        let leaf_digests = (0..1000)
+            .map(|r| hash.hash_iter(mats[0].row(r).chain(mats[1].row(r))))
+            .collect_vec();
+        let (commit, prover_data) = mmcs.commit_leaf_digests(mats.clone(), leaf_digests);
+        assert_eq!(commit, expected_commit);
+        assert_eq!(
+            mmcs.open_batch(517, &prover_data),
+            mmcs.open_batch(517, &expected_prover_data)
+        );
+
+        let layers = expected_prover_data
+            .digest_layers()
+            .layers()
+            .iter()
+            .map(|layer| layer.to_vec())
+            .collect_vec();
+        // SAFETY: the layers were computed from these matrices.
+        let prover_data = unsafe { MerkleTree::from_digest_layers_unchecked(mats, layers) };
+        assert_eq!(prover_data.cap(1), expected_commit);
+        assert_eq!(
+            mmcs.open_batch(517, &prover_data),
+            mmcs.open_batch(517, &expected_prover_data)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong number of leaf digests")]
+    fn commit_leaf_digests_wrong_count() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 10, 3);
+        mmcs.commit_leaf_digests(vec![mat], vec![[F::ZERO; 8]; 9]);
+    }
+
+    #[test]
+    fn commit_matches_unpacked() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash.clone(), compress.clone());
+        let unpacked_mmcs = MerkleTreeMmcs::<F, F, MyHash, MyCompress, 8>::new(hash, compress);
+
+        // Heights which the packing width doesn't divide, and a matrix injected partway through a
+        // packed chunk of its layer.
+        for heights in [vec![1003, 125], vec![17, 5, 3], vec![999, 250, 61, 1]] {
+            let mats = heights
+                .iter()
+                .map(|&height| RowMajorMatrix::<F>::rand(&mut thread_rng(), height, 3))
+                .collect_vec();
+            let (commit, _) = mmcs.commit(mats.clone());
+            let (unpacked_commit, _) = unpacked_mmcs.commit(mats);
+            assert_eq!(commit, unpacked_commit);
+        }
+    }
+
+    #[test]
+    fn open_many_matches_open_batch() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm);
+        let compress = MyCompress4::new(hash.clone());
+        let mmcs = MyMmcs4::new(hash, compress).with_cap_height(1);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 50, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 13, 8),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1, 2),
+        ];
+        let (_, prover_data) = mmcs.commit(mats);
+
+        let indices = [7, 0, 49, 7, 6, 31];
+        let openings = mmcs.open_many(&indices, &prover_data);
+        assert_eq!(openings.len(), indices.len());
+        for (&index, opening) in indices.iter().zip(openings) {
+            assert_eq!(opening, mmcs.open_batch(index, &prover_data));
+        }
+    }
+
+    #[test]
+    fn extension_commit_packed_matches_commit() {
+        type EF = BinomialExtensionField<F, 4>;
+
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = ExtensionMmcs::<F, EF, _>::new(MyMmcs::new(hash, compress));
+
+        let mats = vec![
+            RowMajorMatrix::<EF>::rand(&mut thread_rng(), 37, 3),
+            RowMajorMatrix::<EF>::rand(&mut thread_rng(), 9, 2),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let packed_mats = mats.iter().map(PackedExtensionMatrix::pack).collect_vec();
+        let (expected_commit, expected_prover_data) = mmcs.commit(mats);
+        let (commit, prover_data) = mmcs.commit_packed(packed_mats);
+        assert_eq!(commit, expected_commit);
+
+        let (opened_values, proof) = mmcs.open_batch_packed(30, &prover_data);
+        assert_eq!(
+            (opened_values.clone(), proof.clone()),
+            mmcs.open_batch(30, &expected_prover_data)
+        );
+        mmcs.verify_batch(&commit, &dims, 30, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    fn commit_with_row_orders_matches_reversed_copy() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        let natural = RowMajorMatrix::<F>::rand(&mut thread_rng(), 100, 3);
+        let bit_reversed = RowMajorMatrix::<F>::rand(&mut thread_rng(), 32, 5);
+        let (commit, prover_data) = mmcs.commit_with_row_orders(
+            vec![natural.clone(), bit_reversed.clone()],
+            &[RowOrder::Natural, RowOrder::BitReversed],
+        );
+
+        let mut reversed_copy = bit_reversed;
+        reverse_matrix_index_bits(&mut reversed_copy);
+        let (expected_commit, expected_prover_data) = mmcs.commit(vec![natural, reversed_copy]);
+        assert_eq!(commit, expected_commit);
+        for index in [0, 6, 99] {
+            assert_eq!(
+                mmcs.open_batch(index, &prover_data),
+                mmcs.open_batch(index, &expected_prover_data)
+            );
+        }
+    }
+
+    #[test]
+    fn update_rows_matches_commit() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress).with_cap_height(1);
+
+        let mut mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 100, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 13, 5),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 100, 2),
+        ];
+        let (_, mut prover_data) = mmcs.commit(mats.clone());
+
+        for (matrix_idx, row_range) in [(0, 10..14), (1, 12..13), (2, 97..100), (1, 0..0)] {
+            let new_rows = RowMajorMatrix::<F>::rand(
+                &mut thread_rng(),
+                row_range.len(),
+                mats[matrix_idx].width,
+            );
+            mats[matrix_idx].values
+                [row_range.start * new_rows.width..row_range.end * new_rows.width]
+                .copy_from_slice(&new_rows.values);
+            let commit =
+                mmcs.update_rows(&mut prover_data, matrix_idx, row_range, &new_rows.values);
+
+            let (expected_commit, expected_prover_data) = mmcs.commit(mats.clone());
+            assert_eq!(commit, expected_commit);
+            assert_eq!(
+                mmcs.open_batch(12, &prover_data),
+                mmcs.open_batch(12, &expected_prover_data)
+            );
+        }
+    }
+
+    #[test]
+    fn verify_streaming_matches_verify_batch() {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+        let mmcs = MyMmcs::new(hash, compress).with_cap_height(1);
+        let hash = MyHash::new(perm);
+        let mmcs4 = MyMmcs4::new(hash.clone(), MyCompress4::new(hash));
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1000, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 13, 8),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1000, 2),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1, 4),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+
+        // The rows of the matrix of height 13 cover the first 832 leaves.
+        let (commit, prover_data) = mmcs.commit(mats.clone());
+        for index in [0, 517, 831] {
+            let (opened_values, proof) = mmcs.open_batch(index, &prover_data);
+            mmcs.verify_streaming(&commit, &dims, index, &opened_values, proof.iter().copied())
+                .expect("expected verification to succeed");
+
+            let mut tampered_proof = proof.clone();
+            tampered_proof[3][0] += F::ONE;
+            mmcs.verify_streaming(&commit, &dims, index, &opened_values, tampered_proof)
+                .expect_err("expected verification to fail");
+            mmcs.verify_streaming(&commit, &dims, index, &opened_values, proof[1..].to_vec())
+                .expect_err("expected verification to fail");
+            let mut long_proof = proof;
+            long_proof.push(long_proof[0]);
+            mmcs.verify_streaming(&commit, &dims, index, &opened_values, long_proof)
+                .expect_err("expected verification to fail");
+        }
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 50, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 13, 8),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs4.commit(mats);
+        for index in [0, 12, 49] {
+            let (opened_values, proof) = mmcs4.open_batch(index, &prover_data);
+            mmcs4
+                .verify_streaming(&commit, &dims, index, &opened_values, proof)
+                .expect("expected verification to succeed");
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn commit_mmap_matches_commit() {
+        use p3_matrix::mmap::{MmapBuffer, MmapRowMajorMatrix};
+
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress).with_cap_height(2);
+        let dir = std::env::temp_dir();
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1000, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 70, 8),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (expected_commit, expected_prover_data) = mmcs.commit(mats.clone());
+
+        let mmap_mats = mats
+            .iter()
+            .map(|m| MmapRowMajorMatrix::new(MmapBuffer::new_in(&dir, &m.values).unwrap(), m.width))
+            .collect_vec();
+        let (commit, prover_data) = mmcs.commit_mmap(mmap_mats, &dir).unwrap();
+        assert_eq!(commit, expected_commit);
+
+        let (opened_values, proof) = mmcs.open_batch(517, &prover_data);
+        assert_eq!(
+            (opened_values.clone(), proof.clone()),
+            mmcs.open_batch(517, &expected_prover_data)
+        );
+        mmcs.verify_batch(&commit, &dims, 517, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    fn rows(mat: &RowMajorMatrix<F>, rows: Range<usize>) -> RowMajorMatrix<F> {
+        RowMajorMatrix::new(
+            mat.values[rows.start * mat.width..rows.end * mat.width].to_vec(),
+            mat.width,
+        )
+    }
 }
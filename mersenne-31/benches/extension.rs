@@ -1,12 +1,16 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use p3_field::extension::{BinomialExtensionField, Complex};
+use p3_field::{AbstractExtensionField, ExtensionField, Field, PackedValue};
 use p3_field_testing::bench_func::{
     benchmark_inv, benchmark_mul_latency, benchmark_mul_throughput, benchmark_square,
 };
-use p3_mersenne_31::Mersenne31;
+use p3_mersenne_31::{qm31_mul_generic, Mersenne31, Qm31, Qm31Algebra};
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
 
 type EF2 = BinomialExtensionField<Complex<Mersenne31>, 2>;
 type EF3 = BinomialExtensionField<Complex<Mersenne31>, 3>;
+type PackedQm31 = Qm31<<Mersenne31 as Field>::Packing>;
 
 const REPS: usize = 100;
 const L_REPS: usize = 10 * REPS;
@@ -27,7 +31,82 @@ fn bench_cubic_extension(c: &mut Criterion) {
     benchmark_mul_latency::<EF3, L_REPS>(c, name);
 }
 
+fn bench_qm31(c: &mut Criterion) {
+    let name = "Qm31";
+    benchmark_square::<Qm31>(c, name);
+    benchmark_inv::<Qm31>(c, name);
+    benchmark_mul_throughput::<Qm31, REPS>(c, name);
+    benchmark_mul_latency::<Qm31, L_REPS>(c, name);
+}
+
+fn bench_packed_qm31(c: &mut Criterion) {
+    let name = "Qm31<<Mersenne31 as Field>::Packing>";
+    benchmark_mul_throughput::<PackedQm31, REPS>(c, name);
+    benchmark_mul_latency::<PackedQm31, L_REPS>(c, name);
+
+    let x = rand::thread_rng().gen::<PackedQm31>();
+    let mut group = c.benchmark_group(format!("{} inv", name));
+    group.bench_function("inverse_lanes", |b| {
+        b.iter(|| black_box(black_box(x).inverse_lanes()))
+    });
+    group.bench_function("scalar inverse of each lane", |b| {
+        b.iter(|| {
+            let x = black_box(x);
+            let inverses: Vec<Qm31> = (0..<Mersenne31 as Field>::Packing::WIDTH)
+                .map(|lane| <Qm31 as ExtensionField<Mersenne31>>::unpack_lane(&x, lane).inverse())
+                .collect();
+            black_box(PackedQm31::from_base_fn(|i| {
+                <Mersenne31 as Field>::Packing::from_fn(|lane| inverses[lane].as_base_slice()[i])
+            }))
+        })
+    });
+}
+
+/// Compares the multiplication kernel of `AF` with the generic Karatsuba multiplication, on a
+/// chain of `L_REPS` dependent products.
+fn bench_qm31_mul_kernel<AF: Qm31Algebra + Copy>(c: &mut Criterion, name: &str)
+where
+    Standard: Distribution<AF>,
+{
+    let mut rng = rand::thread_rng();
+    let xs: Vec<[AF; 4]> = (0..L_REPS)
+        .map(|_| [rng.gen(), rng.gen(), rng.gen(), rng.gen()])
+        .collect();
+    let one = [AF::ONE, AF::ZERO, AF::ZERO, AF::ZERO];
+
+    let mut group = c.benchmark_group(format!("qm31-mul-latency/{} {}", L_REPS, name));
+    group.bench_function("kernel", |b| {
+        b.iter(|| {
+            black_box(&xs)
+                .iter()
+                .fold(one, |acc, &x| AF::qm31_mul(acc, x))
+        })
+    });
+    group.bench_function("generic", |b| {
+        b.iter(|| {
+            black_box(&xs)
+                .iter()
+                .fold(one, |acc, &x| qm31_mul_generic(acc, x))
+        })
+    });
+}
+
+fn bench_qm31_mul_kernels(c: &mut Criterion) {
+    bench_qm31_mul_kernel::<Mersenne31>(c, "Mersenne31");
+    bench_qm31_mul_kernel::<<Mersenne31 as Field>::Packing>(c, "<Mersenne31 as Field>::Packing");
+}
+
 criterion_group!(bench_mersennecomplex_ef2, bench_qudratic_extension);
 criterion_group!(bench_mersennecomplex_ef3, bench_cubic_extension);
 
-criterion_main!(bench_mersennecomplex_ef2, bench_mersennecomplex_ef3);
+criterion_group!(bench_mersenne_qm31, bench_qm31);
+criterion_group!(bench_mersenne_packed_qm31, bench_packed_qm31);
+criterion_group!(bench_mersenne_qm31_mul_kernels, bench_qm31_mul_kernels);
+
+criterion_main!(
+    bench_mersennecomplex_ef2,
+    bench_mersennecomplex_ef3,
+    bench_mersenne_qm31,
+    bench_mersenne_packed_qm31,
+    bench_mersenne_qm31_mul_kernels
+);
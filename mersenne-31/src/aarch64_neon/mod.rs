@@ -1,4 +1,5 @@
 mod packing;
 mod poseidon2;
+mod qm31;
 
 pub use packing::*;
@@ -1,5 +1,5 @@
 use alloc::vec::Vec;
-use core::arch::aarch64::{self, uint32x4_t};
+use core::arch::aarch64::{self, uint32x4_t, uint64x2_t};
 use core::iter::{Product, Sum};
 use core::mem::transmute;
 use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
@@ -13,6 +13,8 @@ use crate::Mersenne31;
 
 const WIDTH: usize = 4;
 const P: uint32x4_t = unsafe { transmute::<[u32; WIDTH], _>([0x7fffffff; WIDTH]) };
+/// `P` in each doubleword, to take the low 31 bits of 64-bit values.
+const P_U64: uint64x2_t = unsafe { transmute::<[u64; WIDTH / 2], _>([0x7fffffff; WIDTH / 2]) };
 
 /// Vectorized NEON implementation of `Mersenne31` arithmetic.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -23,7 +25,7 @@ impl PackedMersenne31Neon {
     #[inline]
     #[must_use]
     /// Get an arch-specific vector representing the packed values.
-    fn to_vector(self) -> uint32x4_t {
+    pub(crate) fn to_vector(self) -> uint32x4_t {
         unsafe {
             // Safety: `Mersenne31` is `repr(transparent)` so it can be transmuted to `u32`. It
             // follows that `[Mersenne31; WIDTH]` can be transmuted to `[u32; WIDTH]`, which can be
@@ -41,7 +43,7 @@ impl PackedMersenne31Neon {
     /// SAFETY: The caller must ensure that each element of `vector` represents a valid
     /// `Mersenne31`.  In particular, each element of vector must be in `0..=P` (i.e. it fits in 31
     /// bits).
-    unsafe fn from_vector(vector: uint32x4_t) -> Self {
+    pub(crate) unsafe fn from_vector(vector: uint32x4_t) -> Self {
         // Safety: It is up to the user to ensure that elements of `vector` represent valid
         // `Mersenne31` values. We must only reason about memory representations. `uint32x4_t` can
         // be transmuted to `[u32; WIDTH]` (since arrays elements are contiguous in memory), which
@@ -135,7 +137,7 @@ fn reduce_sum(val: uint32x4_t) -> uint32x4_t {
 /// If the inputs do not fit in 31 bits, the result is undefined.
 #[inline]
 #[must_use]
-fn add(lhs: uint32x4_t, rhs: uint32x4_t) -> uint32x4_t {
+pub(crate) fn add(lhs: uint32x4_t, rhs: uint32x4_t) -> uint32x4_t {
     // We want this to compile to:
     //      add   t.4s, lhs.4s, rhs.4s
     //      sub   u.4s, t.4s, P.4s
@@ -206,11 +208,61 @@ fn mul(lhs: uint32x4_t, rhs: uint32x4_t) -> uint32x4_t {
     }
 }
 
+/// Compute `lhs[0] * rhs[0] + ... + lhs[3] * rhs[3]` for vectors of Mersenne-31 field elements
+/// that fit in 31 bits, reducing the sum once rather than each product.
+/// If the inputs do not fit in 31 bits, the result is undefined.
+#[inline]
+#[must_use]
+pub(crate) fn dot_product_4(lhs: [uint32x4_t; 4], rhs: [uint32x4_t; 4]) -> uint32x4_t {
+    // We want this to compile to:
+    //      umull   dot_lo.2d, lhs0.2s, rhs0.2s
+    //      umull2  dot_hi.2d, lhs0.4s, rhs0.4s
+    //      umlal   dot_lo.2d, lhs1.2s, rhs1.2s
+    //      umlal2  dot_hi.2d, lhs1.4s, rhs1.4s
+    //      ... (and likewise for lhs2, rhs2 and lhs3, rhs3)
+    // followed by one reduction of dot_lo and dot_hi.
+
+    // Each product is at most P^2 < 2^62, so the sum of 4 of them fits in a doubleword. Write a
+    // sum s as s = 2^31 hi + lo with lo < 2^31, so that s = hi + lo (mod P). Folding s into
+    // hi + lo gives a value below 2^33 + 2^31, and folding that again gives a value at most P + 4,
+    // to which we apply reduce_sum.
+
+    unsafe {
+        // Safety: If this code got compiled then NEON intrinsics are available.
+        let mut dot_lo =
+            aarch64::vmull_u32(aarch64::vget_low_u32(lhs[0]), aarch64::vget_low_u32(rhs[0]));
+        let mut dot_hi = aarch64::vmull_high_u32(lhs[0], rhs[0]);
+        for (&lhs, &rhs) in lhs.iter().zip(&rhs).skip(1) {
+            dot_lo = aarch64::vmlal_u32(
+                dot_lo,
+                aarch64::vget_low_u32(lhs),
+                aarch64::vget_low_u32(rhs),
+            );
+            dot_hi = aarch64::vmlal_high_u32(dot_hi, lhs, rhs);
+        }
+
+        let fold = |s| {
+            let lo = aarch64::vandq_u64(s, P_U64);
+            let hi = aarch64::vshrq_n_u64::<31>(s);
+            aarch64::vaddq_u64(lo, hi)
+        };
+        let dot_lo = fold(fold(dot_lo));
+        let dot_hi = fold(fold(dot_hi));
+
+        // Both now fit in the bottom word of each doubleword, so we gather those.
+        let t = aarch64::vuzp1q_u32(
+            aarch64::vreinterpretq_u32_u64(dot_lo),
+            aarch64::vreinterpretq_u32_u64(dot_hi),
+        );
+        reduce_sum(t)
+    }
+}
+
 /// Negate a vector of Mersenne-31 field elements that fit in 31 bits.
 /// If the inputs do not fit in 31 bits, the result is undefined.
 #[inline]
 #[must_use]
-fn neg(val: uint32x4_t) -> uint32x4_t {
+pub(crate) fn neg(val: uint32x4_t) -> uint32x4_t {
     // We want this to compile to:
     //      eor  res.16b, val.16b, P.16b
     // throughput: .25 cyc/vec (16 els/cyc)
@@ -228,7 +280,7 @@ fn neg(val: uint32x4_t) -> uint32x4_t {
 /// If the inputs do not fit in 31 bits, the result is undefined.
 #[inline]
 #[must_use]
-fn sub(lhs: uint32x4_t, rhs: uint32x4_t) -> uint32x4_t {
+pub(crate) fn sub(lhs: uint32x4_t, rhs: uint32x4_t) -> uint32x4_t {
     // We want this to compile to:
     //      sub   res.4s, lhs.4s, rhs.4s
     //      cmhi  underflow.4s, rhs.4s, lhs.4s
@@ -9,6 +9,11 @@
 //! the "complex conjugate" of the (n-k)th element equals the kth element.
 //! The convolution theorem maintains this relationship and so these final
 //! n/2 - 1 elements are essentially redundant.
+//!
+//! Since `Mersenne31` has no large two-adic subgroup, it can't implement
+//! `TwoAdicSubgroupDft` itself; `Mersenne31Dft` instead provides batch
+//! convolutions and LDEs built on these transforms, for code which needs
+//! them outside of the circle STARK machinery.
 
 use alloc::vec::Vec;
 
@@ -18,6 +23,7 @@ use p3_field::extension::Complex;
 use p3_field::{AbstractField, Field, TwoAdicField};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
 use p3_util::log2_strict_usize;
 
 use crate::Mersenne31;
@@ -169,6 +175,44 @@ impl Mersenne31Dft {
         let dft = Dft::default();
         idft_postprocess(dft.idft_batch(idft_preprocess(mat)))
     }
+
+    /// Compute the cyclic convolution of each column of `lhs` with the
+    /// corresponding column of `rhs`.
+    ///
+    /// Both matrices must have the same dimensions, and a power of two
+    /// height of at least 2.
+    pub fn convolve_batch<Dft: TwoAdicSubgroupDft<C>>(
+        lhs: RowMajorMatrix<F>,
+        rhs: RowMajorMatrix<F>,
+    ) -> RowMajorMatrix<F> {
+        assert_eq!(lhs.width(), rhs.width());
+        assert_eq!(lhs.height(), rhs.height());
+        let mut lhs = Self::dft_batch::<Dft>(lhs);
+        let rhs = Self::dft_batch::<Dft>(rhs);
+        lhs.values
+            .par_iter_mut()
+            .zip(rhs.values.par_iter())
+            .for_each(|(x, &y)| *x *= y);
+        Self::idft_batch::<Dft>(lhs)
+    }
+
+    /// Given the DFT of each column of a matrix, as returned by
+    /// `dft_batch()`, compute the DFT of the same columns padded with
+    /// zeros to `2^added_bits` times their height.
+    ///
+    /// Viewing the columns as the coefficients of polynomials, this
+    /// extends their evaluations over the `h`-th roots of unity in
+    /// `Mersenne31Complex` to the `(h << added_bits)`-th roots of unity,
+    /// in the same packed form.
+    pub fn lde_batch<Dft: TwoAdicSubgroupDft<C>>(
+        mat: RowMajorMatrix<C>,
+        added_bits: usize,
+    ) -> RowMajorMatrix<C> {
+        let mut coeffs = Self::idft_batch::<Dft>(mat);
+        let new_len = coeffs.values.len() << added_bits;
+        coeffs.values.resize(new_len, F::ZERO);
+        Self::dft_batch::<Dft>(coeffs)
+    }
 }
 
 #[cfg(test)]
@@ -239,4 +283,46 @@ mod tests {
 
         assert_eq!(c.values, conv);
     }
+
+    #[test]
+    fn convolve_batch() {
+        const N: usize = 1 << 5;
+        const WIDTH: usize = 3;
+        let mut rng = thread_rng();
+        let a = RowMajorMatrix::<Base>::rand(&mut rng, N, WIDTH);
+        let b = RowMajorMatrix::<Base>::rand(&mut rng, N, WIDTH);
+
+        let c = Mersenne31Dft::convolve_batch::<Dft>(a.clone(), b.clone());
+
+        for col in 0..WIDTH {
+            for i in 0..N {
+                let expected = (0..N)
+                    .map(|j| a.get(j, col) * b.get((N + i - j) % N, col))
+                    .sum::<Base>();
+                assert_eq!(c.get(i, col), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn lde_batch() {
+        const LOG_N: usize = 5;
+        const WIDTH: usize = 3;
+        const ADDED_BITS: usize = 2;
+        let coeffs = RowMajorMatrix::<Base>::rand(&mut thread_rng(), 1 << LOG_N, WIDTH);
+
+        let evals = Mersenne31Dft::dft_batch::<Dft>(coeffs.clone());
+        let lde = Mersenne31Dft::lde_batch::<Dft>(evals.clone(), ADDED_BITS);
+        assert_eq!(lde.height(), (1 << (LOG_N + ADDED_BITS - 1)) + 1);
+
+        // The original evaluation points are every `2^ADDED_BITS`-th point of the larger domain.
+        for r in 0..evals.height() {
+            assert_eq!(*lde.row_slice(r << ADDED_BITS), *evals.row_slice(r));
+        }
+
+        // And the low degree extension still has the same coefficients.
+        let mut padded = coeffs.values;
+        padded.resize(WIDTH << (LOG_N + ADDED_BITS), Base::ZERO);
+        assert_eq!(Mersenne31Dft::idft_batch::<Dft>(lde).values, padded);
+    }
 }
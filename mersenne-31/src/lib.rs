@@ -18,12 +18,14 @@ mod extension;
 mod mds;
 mod mersenne_31;
 mod poseidon2;
+mod qm31;
 mod radix_2_dit;
 
 pub use dft::Mersenne31Dft;
 pub use mds::*;
 pub use mersenne_31::*;
 pub use poseidon2::*;
+pub use qm31::*;
 pub use radix_2_dit::Mersenne31ComplexRadix2Dit;
 
 #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
@@ -14,13 +14,14 @@ use p3_field::{
 };
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// The Mersenne31 prime
 const P: u32 = (1 << 31) - 1;
 
 /// The prime field `F_p` where `p = 2^31 - 1`.
-#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default)]
 #[repr(transparent)] // Packed field implementations rely on this!
 pub struct Mersenne31 {
     /// Not necessarily canonical, but must fit in 31 bits.
@@ -46,6 +47,41 @@ impl Eq for Mersenne31 {}
 
 impl Packable for Mersenne31 {}
 
+impl Serialize for Mersenne31 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Serialize the canonical value so that equal elements have equal encodings.
+        serializer.serialize_u32(self.as_canonical_u32())
+    }
+}
+
+impl<'de> Deserialize<'de> for Mersenne31 {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let val = u32::deserialize(d)?;
+        if val >= P {
+            return Err(D::Error::custom("non-canonical Mersenne31 element"));
+        }
+        Ok(Self::new(val))
+    }
+}
+
+// SAFETY: `Mersenne31` is a `repr(transparent)` wrapper of a `u32`, so it has no padding.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for Mersenne31 {}
+
+// SAFETY: as for `Zeroable`.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::NoUninit for Mersenne31 {}
+
+// SAFETY: as for `Zeroable`. The value needn't be canonical, but must fit in 31 bits.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::CheckedBitPattern for Mersenne31 {
+    type Bits = u32;
+
+    fn is_valid_bit_pattern(bits: &u32) -> bool {
+        *bits >> 31 == 0
+    }
+}
+
 impl Hash for Mersenne31 {
     fn hash<H: Hasher>(&self, state: &mut H) {
         state.write_u32(self.as_canonical_u32());
@@ -239,20 +275,7 @@ impl Field for Mersenne31 {
         }
 
         // From Fermat's little theorem, in a prime field `F_p`, the inverse of `a` is `a^(p-2)`.
-        // Here p-2 = 2147483646 = 1111111111111111111111111111101_2.
-        // Uses 30 Squares + 7 Multiplications => 37 Operations total.
-
-        let p1 = *self;
-        let p101 = p1.exp_power_of_2(2) * p1;
-        let p1111 = p101.square() * p101;
-        let p11111111 = p1111.exp_power_of_2(4) * p1111;
-        let p111111110000 = p11111111.exp_power_of_2(4);
-        let p111111111111 = p111111110000 * p1111;
-        let p1111111111111111 = p111111110000.exp_power_of_2(4) * p11111111;
-        let p1111111111111111111111111111 = p1111111111111111.exp_power_of_2(12) * p111111111111;
-        let p1111111111111111111111111111101 =
-            p1111111111111111111111111111.exp_power_of_2(3) * p101;
-        Some(p1111111111111111111111111111101)
+        Some(exp_p_minus_2(*self))
     }
 
     #[inline]
@@ -266,6 +289,23 @@ impl Field for Mersenne31 {
     }
 }
 
+/// Raises `x` to the power `p - 2`, the inverse of `x` if it's nonzero, so that packed fields can
+/// invert each of their lanes at once.
+#[inline]
+pub(crate) fn exp_p_minus_2<AF: AbstractField<F = Mersenne31>>(x: AF) -> AF {
+    // Here p-2 = 2147483646 = 1111111111111111111111111111101_2.
+    // Uses 30 Squares + 7 Multiplications => 37 Operations total.
+    let p1 = x;
+    let p101 = p1.exp_power_of_2(2) * p1.clone();
+    let p1111 = p101.square() * p101.clone();
+    let p11111111 = p1111.exp_power_of_2(4) * p1111.clone();
+    let p111111110000 = p11111111.exp_power_of_2(4);
+    let p111111111111 = p111111110000.clone() * p1111;
+    let p1111111111111111 = p111111110000.exp_power_of_2(4) * p11111111;
+    let p1111111111111111111111111111 = p1111111111111111.exp_power_of_2(12) * p111111111111;
+    p1111111111111111111111111111.exp_power_of_2(3) * p101
+}
+
 impl PrimeField for Mersenne31 {
     fn as_canonical_biguint(&self) -> BigUint {
         <Self as PrimeField32>::as_canonical_u32(self).into()
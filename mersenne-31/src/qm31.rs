@@ -0,0 +1,644 @@
+//! QM31, the degree 4 extension of Mersenne31 used as the challenge field of circle STARKs.
+
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::array;
+use core::fmt::{self, Display, Formatter};
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use num_bigint::BigUint;
+use p3_field::extension::{BinomialExtensionField, Complex};
+use p3_field::{AbstractExtensionField, AbstractField, ExtensionField, Field, Packable};
+use p3_util::convert_vec;
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::mersenne_31::{exp_p_minus_2, from_u62};
+use crate::Mersenne31;
+
+/// QM31, the extension `CM31[u] / (u^2 - 2 - i)` of `CM31 = Mersenne31[i] / (i^2 + 1)`.
+///
+/// This is the same field as `BinomialExtensionField<Complex<Mersenne31>, 2>`, but it's an
+/// extension of `Mersenne31` itself, with the basis `1, i, u, iu`, so it can be the challenge field
+/// of a circle STARK. Inversion needs a single base inversion.
+///
+/// `Qm31<<Mersenne31 as Field>::Packing>` holds one element per lane, with each coordinate in a
+/// vector. Multiplication is specialized for each algebra through `Qm31Algebra`: `Mersenne31` and
+/// the AVX2 and NEON packings compute each coordinate of a product as a dot product reduced once,
+/// and other packings use Karatsuba at both levels of the tower.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[repr(transparent)] // to make the zero_vec implementation safe
+pub struct Qm31<AF = Mersenne31> {
+    value: [AF; 4],
+}
+
+impl<AF> Qm31<AF> {
+    /// The element with coordinates `value` in the basis `1, i, u, iu`.
+    pub const fn new(value: [AF; 4]) -> Self {
+        Self { value }
+    }
+}
+
+/// The algebras `AF` over which `Qm31<AF>` is defined: `Mersenne31` and its packings.
+///
+/// The packings of Mersenne31 can override the multiplication of `Qm31`, which the circle FRI
+/// folding and the quotient computation spend most of their extension field arithmetic on.
+pub trait Qm31Algebra: AbstractField<F = Mersenne31> {
+    /// Multiplies two elements of QM31 given by their coordinates in the basis `1, i, u, iu`.
+    #[inline]
+    fn qm31_mul(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        qm31_mul_generic(a, b)
+    }
+}
+
+impl Qm31Algebra for Mersenne31 {
+    #[inline]
+    fn qm31_mul(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        //   As u^2 = 2 + i, each coordinate of the product is a dot product of the coordinates of b
+        // with linear combinations of those of a:
+        //      r0 = a0 b0 - a1 b1 + (2 a2 - a3) b2 - (a2 + 2 a3) b3
+        //      r1 = a1 b0 + a0 b1 + (a2 + 2 a3) b2 + (2 a2 - a3) b3
+        //      r2 = a2 b0 - a3 b1 + a0 b2 - a1 b3
+        //      r3 = a3 b0 + a2 b1 + a1 b2 + a0 b3
+        // This takes 16 multiplications rather than the 9 of Karatsuba, but only 4 reductions.
+        let [a0, a1, a2, a3] = a;
+        let a2_dbl_sub_a3 = a2.double() - a3;
+        let a2_add_a3_dbl = a2 + a3.double();
+        [
+            dot_product_4([a0, -a1, a2_dbl_sub_a3, -a2_add_a3_dbl], b),
+            dot_product_4([a1, a0, a2_add_a3_dbl, a2_dbl_sub_a3], b),
+            dot_product_4([a2, -a3, a0, -a1], b),
+            dot_product_4([a3, a2, a1, a0], b),
+        ]
+    }
+}
+
+/// Computes `lhs[0] * rhs[0] + ... + lhs[3] * rhs[3]`, reducing the sum once rather than each
+/// product.
+#[inline]
+fn dot_product_4(lhs: [Mersenne31; 4], rhs: [Mersenne31; 4]) -> Mersenne31 {
+    // Each product is below 2^62, so the sum fits in a u64, and folding it once at bit 31 brings
+    // it below 2^34.
+    let sum = lhs
+        .iter()
+        .zip(&rhs)
+        .map(|(l, r)| u64::from(l.value) * u64::from(r.value))
+        .sum::<u64>();
+    from_u62((sum & ((1 << 31) - 1)) + (sum >> 31))
+}
+
+#[cfg(all(
+    feature = "nightly-features",
+    target_arch = "x86_64",
+    target_feature = "avx512f"
+))]
+impl Qm31Algebra for crate::PackedMersenne31AVX512 {}
+
+/// The default `Qm31Algebra::qm31_mul`: `(a + bu)(c + du) = ac + W bd + ((a + b)(c + d) - ac - bd) u`,
+/// with Karatsuba at both levels of the tower, for 9 base multiplications.
+#[inline]
+pub fn qm31_mul_generic<AF: AbstractField>(a: [AF; 4], b: [AF; 4]) -> [AF; 4] {
+    let [a0, a1, a2, a3] = a;
+    let [c0, c1, c2, c3] = b;
+    let (a, b) = ([a0, a1], [a2, a3]);
+    let (c, d) = ([c0, c1], [c2, c3]);
+    let ac = cm31_mul(a.clone(), c.clone());
+    let bd = cm31_mul(b.clone(), d.clone());
+    let cross = cm31_mul(cm31_add(a, b), cm31_add(c, d));
+    let [r2, r3] = cm31_sub(cm31_sub(cross, ac.clone()), bd.clone());
+    let [r0, r1] = cm31_add(ac, cm31_mul_by_w(bd));
+    [r0, r1, r2, r3]
+}
+
+/// Inverts an element of QM31 given by its coordinates, using `norm_inverse` to invert its norm
+/// down to Mersenne31.
+#[inline]
+fn qm31_inverse_with<AF: AbstractField>(
+    x: [AF; 4],
+    norm_inverse: impl FnOnce(AF) -> AF,
+) -> [AF; 4] {
+    // 1 / (a + bu) = (a - bu) / (a^2 - W b^2), whose denominator is in CM31, and
+    // 1 / (n0 + n1 i) = (n0 - n1 i) / (n0^2 + n1^2).
+    let [x0, x1, x2, x3] = x;
+    let (a, b) = ([x0, x1], [x2, x3]);
+    let [n0, n1] = cm31_sub(
+        cm31_square(a.clone()),
+        cm31_mul_by_w(cm31_square(b.clone())),
+    );
+    let norm_inv = norm_inverse(n0.square() + n1.square());
+    let n_inv = [n0 * norm_inv.clone(), -n1 * norm_inv];
+    let [r0, r1] = cm31_mul(a, n_inv.clone());
+    let [c1_0, c1_1] = cm31_mul(b, n_inv);
+    [r0, r1, -c1_0, -c1_1]
+}
+
+/// Multiplies two elements of CM31, with three multiplications.
+#[inline]
+fn cm31_mul<AF: AbstractField>(a: [AF; 2], b: [AF; 2]) -> [AF; 2] {
+    let [a0, a1] = a;
+    let [b0, b1] = b;
+    let t0 = a0.clone() * b0.clone();
+    let t1 = a1.clone() * b1.clone();
+    [t0.clone() - t1.clone(), (a0 + a1) * (b0 + b1) - t0 - t1]
+}
+
+/// Squares an element of CM31, with two multiplications.
+#[inline]
+fn cm31_square<AF: AbstractField>(a: [AF; 2]) -> [AF; 2] {
+    let [a0, a1] = a;
+    [
+        (a0.clone() + a1.clone()) * (a0.clone() - a1.clone()),
+        (a0 * a1).double(),
+    ]
+}
+
+/// Multiplies an element of CM31 by `u^2 = 2 + i`, without any multiplications.
+#[inline]
+fn cm31_mul_by_w<AF: AbstractField>(a: [AF; 2]) -> [AF; 2] {
+    let [a0, a1] = a;
+    [a0.double() - a1.clone(), a0 + a1.double()]
+}
+
+#[inline]
+fn cm31_add<AF: AbstractField>(a: [AF; 2], b: [AF; 2]) -> [AF; 2] {
+    let [a0, a1] = a;
+    let [b0, b1] = b;
+    [a0 + b0, a1 + b1]
+}
+
+#[inline]
+fn cm31_sub<AF: AbstractField>(a: [AF; 2], b: [AF; 2]) -> [AF; 2] {
+    let [a0, a1] = a;
+    let [b0, b1] = b;
+    [a0 - b0, a1 - b1]
+}
+
+impl<AF: AbstractField> Qm31<AF> {
+    /// The coordinates of `1` and `u`, which are elements of CM31.
+    #[inline]
+    fn halves(&self) -> ([AF; 2], [AF; 2]) {
+        let [v0, v1, v2, v3] = self.value.clone();
+        ([v0, v1], [v2, v3])
+    }
+
+    #[inline]
+    fn from_halves(a: [AF; 2], b: [AF; 2]) -> Self {
+        let [v0, v1] = a;
+        let [v2, v3] = b;
+        Self::new([v0, v1, v2, v3])
+    }
+}
+
+impl<AF: Qm31Algebra> Qm31<AF> {
+    /// The inverse of the element in each lane, or zero in the lanes holding zero.
+    ///
+    /// Unlike `Field::inverse`, this only needs the arithmetic of `AF`: the norm is inverted by
+    /// raising it to the power `p - 2`, which inverts every lane of a packing at once.
+    #[inline]
+    pub fn inverse_lanes(&self) -> Self {
+        Self::new(qm31_inverse_with(self.value.clone(), exp_p_minus_2))
+    }
+}
+
+impl<AF: Qm31Algebra> Default for Qm31<AF> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<AF: Qm31Algebra> From<AF> for Qm31<AF> {
+    fn from(x: AF) -> Self {
+        Self::new([x, AF::ZERO, AF::ZERO, AF::ZERO])
+    }
+}
+
+impl From<BinomialExtensionField<Complex<Mersenne31>, 2>> for Qm31 {
+    fn from(x: BinomialExtensionField<Complex<Mersenne31>, 2>) -> Self {
+        let [a, b]: [Complex<Mersenne31>; 2] = [x.as_base_slice()[0], x.as_base_slice()[1]];
+        Self::new([a.real(), a.imag(), b.real(), b.imag()])
+    }
+}
+
+impl From<Qm31> for BinomialExtensionField<Complex<Mersenne31>, 2> {
+    fn from(x: Qm31) -> Self {
+        let [v0, v1, v2, v3] = x.value;
+        Self::from_base_slice(&[Complex::new(v0, v1), Complex::new(v2, v3)])
+    }
+}
+
+impl Packable for Qm31 {}
+
+impl<AF: Qm31Algebra> AbstractField for Qm31<AF> {
+    type F = Qm31;
+
+    const ZERO: Self = Self {
+        value: [AF::ZERO; 4],
+    };
+    const ONE: Self = Self {
+        value: [AF::ONE, AF::ZERO, AF::ZERO, AF::ZERO],
+    };
+    const TWO: Self = Self {
+        value: [AF::TWO, AF::ZERO, AF::ZERO, AF::ZERO],
+    };
+    const NEG_ONE: Self = Self {
+        value: [AF::NEG_ONE, AF::ZERO, AF::ZERO, AF::ZERO],
+    };
+
+    #[inline]
+    fn from_f(f: Self::F) -> Self {
+        Self::new(f.value.map(AF::from_f))
+    }
+
+    #[inline]
+    fn from_bool(b: bool) -> Self {
+        AF::from_bool(b).into()
+    }
+
+    #[inline]
+    fn from_canonical_u8(n: u8) -> Self {
+        AF::from_canonical_u8(n).into()
+    }
+
+    #[inline]
+    fn from_canonical_u16(n: u16) -> Self {
+        AF::from_canonical_u16(n).into()
+    }
+
+    #[inline]
+    fn from_canonical_u32(n: u32) -> Self {
+        AF::from_canonical_u32(n).into()
+    }
+
+    #[inline]
+    fn from_canonical_u64(n: u64) -> Self {
+        AF::from_canonical_u64(n).into()
+    }
+
+    #[inline]
+    fn from_canonical_usize(n: usize) -> Self {
+        AF::from_canonical_usize(n).into()
+    }
+
+    #[inline]
+    fn from_wrapped_u32(n: u32) -> Self {
+        AF::from_wrapped_u32(n).into()
+    }
+
+    #[inline]
+    fn from_wrapped_u64(n: u64) -> Self {
+        AF::from_wrapped_u64(n).into()
+    }
+
+    #[inline]
+    fn square(&self) -> Self {
+        // (a + bu)^2 = a^2 + W b^2 + 2ab u, with 7 base multiplications.
+        let (a, b) = self.halves();
+        let c0 = cm31_add(
+            cm31_square(a.clone()),
+            cm31_mul_by_w(cm31_square(b.clone())),
+        );
+        let [c1_0, c1_1] = cm31_mul(a, b);
+        Self::from_halves(c0, [c1_0.double(), c1_1.double()])
+    }
+
+    #[inline]
+    fn zero_vec(len: usize) -> Vec<Self> {
+        // SAFETY: this is a repr(transparent) wrapper around an array.
+        unsafe { convert_vec(AF::zero_vec(len * 4)) }
+    }
+}
+
+impl Field for Qm31 {
+    type Packing = Self;
+
+    // ```sage
+    // p = 2^31 - 1
+    // F = GF(p)
+    // R.<x> = F[]
+    // K.<i> = F.extension(x^2 + 1)
+    // S.<y> = K[]
+    // L.<u> = K.extension(y^2 - i - 2)
+    // g = u + 6
+    // for f in factor(p^4 - 1):
+    //     assert g^((p^4 - 1) // f[0]) != 1
+    // ```
+    const GENERATOR: Self = Self::new([
+        Mersenne31::new(6),
+        Mersenne31::ZERO,
+        Mersenne31::ONE,
+        Mersenne31::ZERO,
+    ]);
+
+    fn try_inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        Some(Self::new(qm31_inverse_with(self.value, |norm| {
+            norm.inverse()
+        })))
+    }
+
+    #[inline]
+    fn halve(&self) -> Self {
+        Self::new(self.value.map(|x| x.halve()))
+    }
+
+    fn order() -> BigUint {
+        Mersenne31::order().pow(4)
+    }
+}
+
+impl<AF: Qm31Algebra> AbstractExtensionField<AF> for Qm31<AF> {
+    const D: usize = 4;
+
+    #[inline]
+    fn from_base(b: AF) -> Self {
+        b.into()
+    }
+
+    #[inline]
+    fn from_base_slice(bs: &[AF]) -> Self {
+        Self::from_base_fn(|i| bs[i].clone())
+    }
+
+    #[inline]
+    fn from_base_fn<F: FnMut(usize) -> AF>(f: F) -> Self {
+        Self::new(array::from_fn(f))
+    }
+
+    #[inline]
+    fn from_base_iter<I: Iterator<Item = AF>>(iter: I) -> Self {
+        let mut res = Self::ZERO;
+        for (i, b) in iter.enumerate() {
+            res.value[i] = b;
+        }
+        res
+    }
+
+    #[inline]
+    fn as_base_slice(&self) -> &[AF] {
+        &self.value
+    }
+}
+
+impl ExtensionField<Mersenne31> for Qm31 {
+    type ExtensionPacking = Qm31<<Mersenne31 as Field>::Packing>;
+}
+
+impl Display for Qm31 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let str = self
+            .value
+            .iter()
+            .zip(["", " i", " u", " iu"])
+            .filter(|(x, _)| !x.is_zero())
+            .map(|(x, basis)| {
+                if basis.is_empty() {
+                    x.to_string()
+                } else if x.is_one() {
+                    basis.trim_start().to_string()
+                } else {
+                    format!("{x}{basis}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" + ");
+        write!(f, "{str}")
+    }
+}
+
+impl<AF: Qm31Algebra> Neg for Qm31<AF> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(self.value.map(AF::neg))
+    }
+}
+
+impl<AF: Qm31Algebra> Add for Qm31<AF> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let mut res = self.value;
+        for (r, rhs_val) in res.iter_mut().zip(rhs.value) {
+            *r += rhs_val;
+        }
+        Self::new(res)
+    }
+}
+
+impl<AF: Qm31Algebra> Add<AF> for Qm31<AF> {
+    type Output = Self;
+
+    #[inline]
+    fn add(mut self, rhs: AF) -> Self {
+        self.value[0] += rhs;
+        self
+    }
+}
+
+impl<AF: Qm31Algebra> AddAssign for Qm31<AF> {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = self.clone() + rhs;
+    }
+}
+
+impl<AF: Qm31Algebra> AddAssign<AF> for Qm31<AF> {
+    #[inline]
+    fn add_assign(&mut self, rhs: AF) {
+        self.value[0] += rhs;
+    }
+}
+
+impl<AF: Qm31Algebra> Sum for Qm31<AF> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<AF: Qm31Algebra> Sub for Qm31<AF> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let mut res = self.value;
+        for (r, rhs_val) in res.iter_mut().zip(rhs.value) {
+            *r -= rhs_val;
+        }
+        Self::new(res)
+    }
+}
+
+impl<AF: Qm31Algebra> Sub<AF> for Qm31<AF> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(mut self, rhs: AF) -> Self {
+        self.value[0] -= rhs;
+        self
+    }
+}
+
+impl<AF: Qm31Algebra> SubAssign for Qm31<AF> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = self.clone() - rhs;
+    }
+}
+
+impl<AF: Qm31Algebra> SubAssign<AF> for Qm31<AF> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: AF) {
+        self.value[0] -= rhs;
+    }
+}
+
+impl<AF: Qm31Algebra> Mul for Qm31<AF> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(AF::qm31_mul(self.value, rhs.value))
+    }
+}
+
+impl<AF: Qm31Algebra> Mul<AF> for Qm31<AF> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: AF) -> Self {
+        Self::new(self.value.map(|x| x * rhs.clone()))
+    }
+}
+
+impl<AF: Qm31Algebra> MulAssign for Qm31<AF> {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = self.clone() * rhs;
+    }
+}
+
+impl<AF: Qm31Algebra> MulAssign<AF> for Qm31<AF> {
+    #[inline]
+    fn mul_assign(&mut self, rhs: AF) {
+        *self = self.clone() * rhs;
+    }
+}
+
+impl<AF: Qm31Algebra> Product for Qm31<AF> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl Div for Qm31 {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    #[inline]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse()
+    }
+}
+
+impl DivAssign for Qm31 {
+    #[inline]
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<AF: Qm31Algebra> Distribution<Qm31<AF>> for Standard
+where
+    Standard: Distribution<AF>,
+{
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Qm31<AF> {
+        Qm31::new(array::from_fn(|_| rng.gen()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::PackedValue;
+    use p3_field_testing::{test_ext_packing_lanes, test_field};
+    use rand::random;
+
+    use super::*;
+
+    type EF2 = BinomialExtensionField<Complex<Mersenne31>, 2>;
+
+    test_field!(crate::Qm31);
+
+    #[test]
+    fn agrees_with_binomial_extension() {
+        for _ in 0..100 {
+            let (x, y): (Qm31, Qm31) = (random(), random());
+            let (bx, by) = (EF2::from(x), EF2::from(y));
+            assert_eq!(Qm31::from(bx), x);
+            assert_eq!(Qm31::from(bx * by), x * y);
+            assert_eq!(Qm31::from(bx.square()), x.square());
+            assert_eq!(Qm31::from(bx.inverse()), x.inverse());
+        }
+        assert_eq!(Qm31::from(EF2::GENERATOR), Qm31::GENERATOR);
+    }
+
+    #[test]
+    fn packing_lanes() {
+        test_ext_packing_lanes::<Mersenne31, Qm31>();
+    }
+
+    /// Random elements, with the coordinates of the first two `P - 1` and the non-canonical `P`,
+    /// the largest inputs of the multiplication kernels.
+    fn kernel_inputs(n: usize) -> Vec<Qm31> {
+        let mut xs: Vec<Qm31> = (0..n).map(|_| random()).collect();
+        xs[0] = Qm31::new([Mersenne31::NEG_ONE; 4]);
+        xs[1] = Qm31::new([Mersenne31::new((1 << 31) - 1); 4]);
+        xs
+    }
+
+    #[test]
+    fn mul_agrees_with_generic() {
+        let (xs, ys) = (kernel_inputs(100), kernel_inputs(100));
+        for (x, y) in xs.into_iter().zip(ys.into_iter().rev()) {
+            assert_eq!((x * y).value, qm31_mul_generic(x.value, y.value));
+        }
+    }
+
+    #[test]
+    fn packed_mul_and_inverse_agree_with_scalar() {
+        let width = <Mersenne31 as Field>::Packing::WIDTH;
+        let pack = |xs: &[Qm31]| {
+            Qm31::<<Mersenne31 as Field>::Packing>::from_base_fn(|i| {
+                <Mersenne31 as Field>::Packing::from_fn(|lane| xs[lane].value[i])
+            })
+        };
+        let mut xs = kernel_inputs(width.max(2));
+        let ys: Vec<Qm31> = xs.iter().rev().copied().collect();
+        if width > 2 {
+            xs[2] = Qm31::ZERO;
+        }
+        let product = pack(&xs) * pack(&ys);
+        let inverse = pack(&xs).inverse_lanes();
+        for lane in 0..width {
+            assert_eq!(
+                <Qm31 as ExtensionField<Mersenne31>>::unpack_lane(&product, lane),
+                xs[lane] * ys[lane]
+            );
+            let x_inv = xs[lane].try_inverse().unwrap_or(Qm31::ZERO);
+            assert_eq!(
+                <Qm31 as ExtensionField<Mersenne31>>::unpack_lane(&inverse, lane),
+                x_inv
+            );
+        }
+    }
+}
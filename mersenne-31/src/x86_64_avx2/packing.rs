@@ -13,6 +13,8 @@ use crate::Mersenne31;
 
 const WIDTH: usize = 8;
 const P: __m256i = unsafe { transmute::<[u32; WIDTH], _>([0x7fffffff; WIDTH]) };
+/// `P` in each quadword, to take the low 31 bits of 64-bit values.
+const P_EPI64: __m256i = unsafe { transmute::<[u64; WIDTH / 2], _>([0x7fffffff; WIDTH / 2]) };
 
 /// Vectorized AVX2 implementation of `Mersenne31` arithmetic.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -23,7 +25,7 @@ impl PackedMersenne31AVX2 {
     #[inline]
     #[must_use]
     /// Get an arch-specific vector representing the packed values.
-    fn to_vector(self) -> __m256i {
+    pub(crate) fn to_vector(self) -> __m256i {
         unsafe {
             // Safety: `Mersenne31` is `repr(transparent)` so it can be transmuted to `u32`. It
             // follows that `[Mersenne31; WIDTH]` can be transmuted to `[u32; WIDTH]`, which can be
@@ -40,7 +42,7 @@ impl PackedMersenne31AVX2 {
     ///
     /// SAFETY: The caller must ensure that each element of `vector` represents a valid
     /// `Mersenne31`. In particular, each element of vector must be in `0..=P`.
-    unsafe fn from_vector(vector: __m256i) -> Self {
+    pub(crate) unsafe fn from_vector(vector: __m256i) -> Self {
         // Safety: It is up to the user to ensure that elements of `vector` represent valid
         // `Mersenne31` values. We must only reason about memory representations. `__m256i` can be
         // transmuted to `[u32; WIDTH]` (since arrays elements are contiguous in memory), which can
@@ -118,7 +120,7 @@ impl Sub for PackedMersenne31AVX2 {
 /// If the inputs do not conform to this representation, the result is undefined.
 #[inline]
 #[must_use]
-fn add(lhs: __m256i, rhs: __m256i) -> __m256i {
+pub(crate) fn add(lhs: __m256i, rhs: __m256i) -> __m256i {
     // We want this to compile to:
     //      vpaddd   t, lhs, rhs
     //      vpsubd   u, t, P
@@ -212,11 +214,64 @@ fn mul(lhs: __m256i, rhs: __m256i) -> __m256i {
     }
 }
 
+/// Compute `lhs[0] * rhs[0] + ... + lhs[3] * rhs[3]` for vectors of Mersenne-31 field elements
+/// represented as values in {0, ..., P}, reducing the sum once rather than each product.
+/// If the inputs do not conform to this representation, the result is undefined.
+#[inline]
+#[must_use]
+pub(crate) fn dot_product_4(lhs: [__m256i; 4], rhs: [__m256i; 4]) -> __m256i {
+    // We want this to compile to, for each of the 4 pairs:
+    //      vpsrlq     lhs_odd, lhs, 32
+    //      vmovshdup  rhs_odd, rhs
+    //      vpmuludq   prod_evn, lhs, rhs
+    //      vpmuludq   prod_odd, lhs_odd, rhs_odd
+    //      vpaddq     dot_evn, dot_evn, prod_evn
+    //      vpaddq     dot_odd, dot_odd, prod_odd
+    // followed by one reduction of dot_evn and dot_odd. The shifts of lhs and rhs are shared when
+    // several dot products have operands in common, as in QM31 multiplication.
+
+    //   Each product is at most P^2 < 2^62, so the sum of 4 of them fits in a quadword. Write a
+    // sum s as s = 2^31 hi + lo with lo < 2^31, so that s = hi + lo (mod P). Folding s into
+    // hi + lo gives a value below 2^33 + 2^31, and folding that again gives a value at most P + 4,
+    // which a final conditional subtraction brings into {0, ..., P}.
+    unsafe {
+        // Safety: If this code got compiled then AVX2 intrinsics are available.
+        let mut dot_evn = x86_64::_mm256_setzero_si256();
+        let mut dot_odd = x86_64::_mm256_setzero_si256();
+        for (lhs, rhs) in lhs.into_iter().zip(rhs) {
+            // vpmuludq only reads the bottom 32 bits of every 64-bit quadword, so we move the odd
+            // doublewords down to multiply them.
+            let lhs_odd = x86_64::_mm256_srli_epi64::<32>(lhs);
+            let rhs_odd = movehdup_epi32(rhs);
+            let prod_evn = x86_64::_mm256_mul_epu32(lhs, rhs);
+            let prod_odd = x86_64::_mm256_mul_epu32(lhs_odd, rhs_odd);
+            dot_evn = x86_64::_mm256_add_epi64(dot_evn, prod_evn);
+            dot_odd = x86_64::_mm256_add_epi64(dot_odd, prod_odd);
+        }
+
+        let fold = |s| {
+            let lo = x86_64::_mm256_and_si256(s, P_EPI64);
+            let hi = x86_64::_mm256_srli_epi64::<31>(s);
+            x86_64::_mm256_add_epi64(lo, hi)
+        };
+        let dot_evn = fold(fold(dot_evn));
+        let dot_odd = fold(fold(dot_odd));
+
+        // Both now fit in the bottom doubleword of each quadword, so we move the odd ones up.
+        let t = x86_64::_mm256_blend_epi32::<0b10101010>(
+            dot_evn,
+            x86_64::_mm256_slli_epi64::<32>(dot_odd),
+        );
+        let u = x86_64::_mm256_sub_epi32(t, P);
+        x86_64::_mm256_min_epu32(t, u)
+    }
+}
+
 /// Negate a vector of Mersenne-31 field elements represented as values in {0, ..., P}.
 /// If the input does not conform to this representation, the result is undefined.
 #[inline]
 #[must_use]
-fn neg(val: __m256i) -> __m256i {
+pub(crate) fn neg(val: __m256i) -> __m256i {
     // We want this to compile to:
     //      vpxor  res, val, P
     // throughput: .33 cyc/vec (24 els/cyc)
@@ -234,7 +289,7 @@ fn neg(val: __m256i) -> __m256i {
 /// If the inputs do not conform to this representation, the result is undefined.
 #[inline]
 #[must_use]
-fn sub(lhs: __m256i, rhs: __m256i) -> __m256i {
+pub(crate) fn sub(lhs: __m256i, rhs: __m256i) -> __m256i {
     // We want this to compile to:
     //      vpsubd   t, lhs, rhs
     //      vpaddd   u, t, P
@@ -0,0 +1,44 @@
+use core::arch::x86_64::__m256i;
+
+use super::packing::{add, dot_product_4, neg, sub};
+use crate::{PackedMersenne31AVX2, Qm31Algebra};
+
+impl Qm31Algebra for PackedMersenne31AVX2 {
+    #[inline]
+    fn qm31_mul(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        let res = qm31_mul(a.map(Self::to_vector), b.map(Self::to_vector));
+        unsafe {
+            // Safety: `qm31_mul` returns values in canonical form when given values in canonical
+            // form.
+            res.map(|r| Self::from_vector(r))
+        }
+    }
+}
+
+/// Multiply vectors of QM31 elements, given by their coordinates in the basis `1, i, u, iu` with
+/// each coordinate a vector of Mersenne-31 field elements represented as values in {0, ..., P}.
+/// If the inputs do not conform to this representation, the result is undefined.
+#[inline]
+#[must_use]
+fn qm31_mul(a: [__m256i; 4], b: [__m256i; 4]) -> [__m256i; 4] {
+    //   As u^2 = 2 + i, each coordinate of the product is a dot product of the coordinates of b
+    // with linear combinations of those of a:
+    //      r0 = a0 b0 - a1 b1 + (2 a2 - a3) b2 - (a2 + 2 a3) b3
+    //      r1 = a1 b0 + a0 b1 + (a2 + 2 a3) b2 + (2 a2 - a3) b3
+    //      r2 = a2 b0 - a3 b1 + a0 b2 - a1 b3
+    //      r3 = a3 b0 + a2 b1 + a1 b2 + a0 b3
+    // This takes 16 multiplications rather than the 9 of Karatsuba, but only 4 reductions, which
+    // are most of the cost of a multiplication.
+    let [a0, a1, a2, a3] = a;
+    let neg_a1 = neg(a1);
+    let neg_a3 = neg(a3);
+    let a2_dbl_sub_a3 = sub(add(a2, a2), a3);
+    let a2_add_a3_dbl = add(a2, add(a3, a3));
+    let neg_a2_add_a3_dbl = neg(a2_add_a3_dbl);
+    [
+        dot_product_4([a0, neg_a1, a2_dbl_sub_a3, neg_a2_add_a3_dbl], b),
+        dot_product_4([a1, a0, a2_add_a3_dbl, a2_dbl_sub_a3], b),
+        dot_product_4([a2, neg_a3, a0, neg_a1], b),
+        dot_product_4([a3, a2, a1, a0], b),
+    ]
+}
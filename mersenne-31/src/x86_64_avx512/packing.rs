@@ -26,7 +26,7 @@ impl PackedMersenne31AVX512 {
     #[inline]
     #[must_use]
     /// Get an arch-specific vector representing the packed values.
-    fn to_vector(self) -> __m512i {
+    pub(crate) fn to_vector(self) -> __m512i {
         unsafe {
             // Safety: `Mersenne31` is `repr(transparent)` so it can be transmuted to `u32`. It
             // follows that `[Mersenne31; WIDTH]` can be transmuted to `[u32; WIDTH]`, which can be
@@ -43,7 +43,7 @@ impl PackedMersenne31AVX512 {
     ///
     /// SAFETY: The caller must ensure that each element of `vector` represents a valid
     /// `Mersenne31`. In particular, each element of vector must be in `0..=P`.
-    unsafe fn from_vector(vector: __m512i) -> Self {
+    pub(crate) unsafe fn from_vector(vector: __m512i) -> Self {
         // Safety: It is up to the user to ensure that elements of `vector` represent valid
         // `Mersenne31` values. We must only reason about memory representations. `__m512i` can be
         // transmuted to `[u32; WIDTH]` (since arrays elements are contiguous in memory), which can
@@ -250,6 +250,32 @@ fn neg(val: __m512i) -> __m512i {
     }
 }
 
+/// Multiply a vector of Mersenne-31 field elements represented as values in {0, ..., P} by
+/// `2^exp`, for `exp` in {0, ..., 30}. If the input does not conform to this representation, the
+/// result is undefined.
+#[inline]
+#[must_use]
+pub(crate) fn mul_2exp(val: __m512i, exp: u32) -> __m512i {
+    // We want this to compile to:
+    //      vpslld      hi, val, exp
+    //      vpsrld      lo, val, 31 - exp
+    //      vpternlogd  res, hi, P, lo
+    // latency: 2 cyc
+
+    //   Since 2^31 = 1 (mod P), multiplying by 2^exp rotates the 31 bits of val left by exp. The
+    // bits shifted past bit 30 are cleared from hi by the mask P, and come back at the bottom in
+    // lo, so res = (hi & P) | lo. If val is in {0, ..., P}, so is res: in particular P, whose 31
+    // bits are all set, is rotated to itself.
+    debug_assert!(exp < 31);
+    unsafe {
+        // Safety: If this code got compiled then AVX-512F intrinsics are available.
+        let hi = x86_64::_mm512_sll_epi32(val, x86_64::_mm_cvtsi32_si128(exp as i32));
+        let lo = x86_64::_mm512_srl_epi32(val, x86_64::_mm_cvtsi32_si128(31 - exp as i32));
+        // 0xea is the truth table of (a & b) | c.
+        x86_64::_mm512_ternarylogic_epi32::<0xea>(hi, P, lo)
+    }
+}
+
 /// Subtract vectors of Mersenne-31 field elements represented as values in {0, ..., P}.
 /// If the inputs do not conform to this representation, the result is undefined.
 #[inline]
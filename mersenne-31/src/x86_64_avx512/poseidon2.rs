@@ -1,28 +1,45 @@
-use p3_poseidon2::{matmul_internal, DiffusionPermutation};
+use p3_poseidon2::DiffusionPermutation;
 use p3_symmetric::Permutation;
 
+use super::packing::mul_2exp;
 use crate::{
-    DiffusionMatrixMersenne31, Mersenne31, PackedMersenne31AVX512,
-    POSEIDON2_INTERNAL_MATRIX_DIAG_16, POSEIDON2_INTERNAL_MATRIX_DIAG_24,
+    DiffusionMatrixMersenne31, PackedMersenne31AVX512, POSEIDON2_INTERNAL_MATRIX_DIAG_16_SHIFTS,
+    POSEIDON2_INTERNAL_MATRIX_DIAG_24_SHIFTS,
 };
 
+/// The internal layer, whose diagonal is `-2` followed by powers of two, `2^shifts[i]`.
+///
+/// Like the scalar implementation, this uses that `1 + diag[0] = -1`, so the first element is
+/// `sum - 2 x_0 = part_sum - x_0`, and that multiplying by a power of two is a rotation of the 31
+/// bits of each lane, which is much cheaper than a full multiplication.
+#[inline]
+fn internal_layer<const WIDTH: usize>(state: &mut [PackedMersenne31AVX512; WIDTH], shifts: &[u8]) {
+    let part_sum: PackedMersenne31AVX512 = state[1..].iter().copied().sum();
+    let full_sum = part_sum + state[0];
+    state[0] = part_sum - state[0];
+    for (x, &shift) in state[1..].iter_mut().zip(shifts) {
+        let shifted = unsafe {
+            // Safety: `mul_2exp` returns values in canonical form when given values in canonical
+            // form.
+            PackedMersenne31AVX512::from_vector(mul_2exp(x.to_vector(), shift as u32))
+        };
+        *x = full_sum + shifted;
+    }
+}
+
 impl Permutation<[PackedMersenne31AVX512; 16]> for DiffusionMatrixMersenne31 {
+    #[inline]
     fn permute_mut(&self, state: &mut [PackedMersenne31AVX512; 16]) {
-        matmul_internal::<Mersenne31, PackedMersenne31AVX512, 16>(
-            state,
-            POSEIDON2_INTERNAL_MATRIX_DIAG_16,
-        );
+        internal_layer(state, &POSEIDON2_INTERNAL_MATRIX_DIAG_16_SHIFTS);
     }
 }
 
 impl DiffusionPermutation<PackedMersenne31AVX512, 16> for DiffusionMatrixMersenne31 {}
 
 impl Permutation<[PackedMersenne31AVX512; 24]> for DiffusionMatrixMersenne31 {
+    #[inline]
     fn permute_mut(&self, state: &mut [PackedMersenne31AVX512; 24]) {
-        matmul_internal::<Mersenne31, PackedMersenne31AVX512, 24>(
-            state,
-            POSEIDON2_INTERNAL_MATRIX_DIAG_24,
-        );
+        internal_layer(state, &POSEIDON2_INTERNAL_MATRIX_DIAG_24_SHIFTS);
     }
 }
 
@@ -30,7 +47,7 @@ impl DiffusionPermutation<PackedMersenne31AVX512, 24> for DiffusionMatrixMersenn
 
 #[cfg(test)]
 mod tests {
-    use p3_field::AbstractField;
+    use p3_field::{AbstractField, PackedValue};
     use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
     use p3_symmetric::Permutation;
     use rand::Rng;
@@ -91,4 +108,29 @@ mod tests {
 
         assert_eq!(avx512_output, expected);
     }
+
+    /// Test that each lane of the internal layer is the same as the scalar version, on inputs which
+    /// differ between lanes.
+    #[test]
+    fn test_avx512_internal_layer_lanes() {
+        let mut rng = rand::thread_rng();
+
+        let mut state16: [PackedMersenne31AVX512; 16] = rng.gen();
+        let input16 = state16;
+        DiffusionMatrixMersenne31.permute_mut(&mut state16);
+        for lane in 0..PackedMersenne31AVX512::WIDTH {
+            let mut expected = input16.map(|x| x.0[lane]);
+            DiffusionMatrixMersenne31.permute_mut(&mut expected);
+            assert_eq!(state16.map(|x| x.0[lane]), expected);
+        }
+
+        let mut state24: [PackedMersenne31AVX512; 24] = rng.gen();
+        let input24 = state24;
+        DiffusionMatrixMersenne31.permute_mut(&mut state24);
+        for lane in 0..PackedMersenne31AVX512::WIDTH {
+            let mut expected = input24.map(|x| x.0[lane]);
+            DiffusionMatrixMersenne31.permute_mut(&mut expected);
+            assert_eq!(state24.map(|x| x.0[lane]), expected);
+        }
+    }
 }
@@ -112,6 +112,15 @@ pub trait TwoAdicData: MontyParameters {
     const INV_ROOTS_16: Self::ArrayLike;
 }
 
+/// ThreeAdicData contains constants needed to imply ThreeAdicField for Monty31 fields.
+pub trait ThreeAdicData: TwoAdicData {
+    /// Largest n such that 3^n divides p - 1.
+    const THREE_ADICITY: usize;
+
+    /// A generator of the subgroup of order 3^THREE_ADICITY.
+    const THREE_ADIC_GENERATOR: MontyField31<Self>;
+}
+
 /// TODO: This should be deleted long term once we have improved our API for defining extension fields.
 /// This allows us to implement Binomial Extensions over Monty31 fields.
 pub trait BinomialExtensionData<const DEG: usize>: MontyParameters + Sized {
@@ -11,15 +11,17 @@ use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use num_bigint::BigUint;
 use p3_field::{
-    AbstractField, Field, Packable, PrimeField, PrimeField32, PrimeField64, TwoAdicField,
+    AbstractField, Field, Packable, PrimeField, PrimeField32, PrimeField64, ThreeAdicField,
+    TwoAdicField,
 };
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
+use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::{
     from_monty, halve_u32, monty_reduce, to_monty, to_monty_64, FieldParameters, MontyParameters,
-    TwoAdicData,
+    ThreeAdicData, TwoAdicData,
 };
 
 #[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
@@ -142,10 +144,32 @@ impl<FP: FieldParameters> Serialize for MontyField31<FP> {
 impl<'de, FP: FieldParameters> Deserialize<'de> for MontyField31<FP> {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
         let val = u32::deserialize(d)?;
+        if val >= FP::PRIME {
+            return Err(D::Error::custom("non-canonical MontyField31 element"));
+        }
         Ok(MontyField31::from_canonical_u32(val))
     }
 }
 
+// SAFETY: `MontyField31` is a `repr(transparent)` wrapper of a `u32` (the `PhantomData` is zero
+// sized), so it has no padding, and all zero bits is zero in Montgomery form.
+#[cfg(feature = "bytemuck")]
+unsafe impl<MP: MontyParameters> bytemuck::Zeroable for MontyField31<MP> {}
+
+// SAFETY: as for `Zeroable`.
+#[cfg(feature = "bytemuck")]
+unsafe impl<MP: MontyParameters> bytemuck::NoUninit for MontyField31<MP> {}
+
+// SAFETY: as for `Zeroable`. Values in Montgomery form are always reduced.
+#[cfg(feature = "bytemuck")]
+unsafe impl<MP: MontyParameters> bytemuck::CheckedBitPattern for MontyField31<MP> {
+    type Bits = u32;
+
+    fn is_valid_bit_pattern(bits: &u32) -> bool {
+        *bits < MP::PRIME
+    }
+}
+
 impl<FP: FieldParameters> Packable for MontyField31<FP> {}
 
 impl<FP: FieldParameters> AbstractField for MontyField31<FP> {
@@ -302,6 +326,14 @@ impl<FP: FieldParameters + TwoAdicData> TwoAdicField for MontyField31<FP> {
     }
 }
 
+impl<FP: FieldParameters + ThreeAdicData> ThreeAdicField for MontyField31<FP> {
+    const THREE_ADICITY: usize = FP::THREE_ADICITY;
+    fn three_adic_generator(bits: usize) -> Self {
+        assert!(bits <= Self::THREE_ADICITY);
+        FP::THREE_ADIC_GENERATOR.exp_u64(3u64.pow((Self::THREE_ADICITY - bits) as u32))
+    }
+}
+
 impl<FP: MontyParameters> Add for MontyField31<FP> {
     type Output = Self;
 
@@ -46,8 +46,10 @@ pub(crate) const fn halve_u32<FP: FieldParameters>(input: u32) -> u32 {
 #[inline]
 #[must_use]
 pub(crate) const fn monty_reduce<MP: MontyParameters>(x: u64) -> u32 {
-    let t = x.wrapping_mul(MP::MONTY_MU as u64) & (MP::MONTY_MASK as u64);
-    let u = t * (MP::PRIME as u64);
+    // Only the low MONTY_BITS bits of `x * MONTY_MU` are needed, so a 32-bit multiplication
+    // suffices. This matters on 32-bit targets, where a 64-bit one takes three multiplications.
+    let t = (x as u32).wrapping_mul(MP::MONTY_MU) & MP::MONTY_MASK;
+    let u = (t as u64) * (MP::PRIME as u64);
 
     let (x_sub_u, over) = x.overflowing_sub(u);
     let x_sub_u_hi = (x_sub_u >> MP::MONTY_BITS) as u32;
@@ -1,27 +1,67 @@
-use p3_poseidon2::{matmul_internal, DiffusionPermutation};
+use core::arch::x86_64::{self, __m512i};
+
+use p3_poseidon2::DiffusionPermutation;
 use p3_symmetric::Permutation;
 
+use super::packing::{mask_movehdup_epi32, EVENS};
 use crate::{
-    DiffusionMatrixMontyField31, DiffusionMatrixParameters, FieldParameters, MontyField31,
-    PackedFieldPoseidon2Helpers, PackedMontyField31AVX512,
+    DiffusionMatrixMontyField31, DiffusionMatrixParameters, FieldParameters, MontyParametersAVX512,
+    PackedMontyField31AVX512,
 };
 
-// We need to change from the standard implementation as we are interpreting the matrix (1 + Diag(vec)) as the monty form of the matrix not the raw form.
-// matmul_internal internal performs a standard matrix multiplication so we need to additional rescale by the inverse monty constant.
-// These will be removed once we have architecture specific implementations.
+// This is a vectorized version of `DiffusionMatrixParameters::permute_state`. The matrix is
+// `1 + Diag(vec)` in monty form, so rather than multiplying by the diagonal and rescaling by the
+// inverse monty constant, we shift each element left and do a single monty reduction of the
+// (unreduced) 64-bit sums at the end.
+//
+// To do this we split each vector of 16 `u32`s into its even and odd elements, zero extended to
+// 8 `u64`s each. As the shifts are at most 24, all sums are less than `WIDTH * P + (P << 24)`,
+// which is well below `P << 32`, the bound needed by the monty reduction.
 
 impl<FP, const WIDTH: usize, MP> Permutation<[PackedMontyField31AVX512<FP>; WIDTH]>
     for DiffusionMatrixMontyField31<MP>
 where
     FP: FieldParameters,
-    MP: DiffusionMatrixParameters<FP, WIDTH> + PackedFieldPoseidon2Helpers<FP>,
+    MP: DiffusionMatrixParameters<FP, WIDTH>,
 {
+    #[inline]
     fn permute_mut(&self, state: &mut [PackedMontyField31AVX512<FP>; WIDTH]) {
-        matmul_internal::<MontyField31<FP>, PackedMontyField31AVX512<FP>, WIDTH>(
-            state,
-            MP::INTERNAL_DIAG_MONTY,
-        );
-        state.iter_mut().for_each(|i| *i *= MP::MONTY_INVERSE);
+        unsafe {
+            // Safety: If this code got compiled then AVX-512F intrinsics are available.
+            let (part_sum_evn, part_sum_odd) =
+                state[1..].iter().map(|x| split(x.to_vector())).fold(
+                    (
+                        x86_64::_mm512_setzero_si512(),
+                        x86_64::_mm512_setzero_si512(),
+                    ),
+                    |(acc_evn, acc_odd), (evn, odd)| {
+                        (
+                            x86_64::_mm512_add_epi64(acc_evn, evn),
+                            x86_64::_mm512_add_epi64(acc_odd, odd),
+                        )
+                    },
+                );
+            let (x0_evn, x0_odd) = split(state[0].to_vector());
+            let full_sum_evn = x86_64::_mm512_add_epi64(part_sum_evn, x0_evn);
+            let full_sum_odd = x86_64::_mm512_add_epi64(part_sum_odd, x0_odd);
+
+            let (neg_x0_evn, neg_x0_odd) = split((-state[0]).to_vector());
+            let s0_evn = x86_64::_mm512_add_epi64(part_sum_evn, neg_x0_evn);
+            let s0_odd = x86_64::_mm512_add_epi64(part_sum_odd, neg_x0_odd);
+            state[0] = PackedMontyField31AVX512::from_vector(monty_reduce::<FP>(s0_evn, s0_odd));
+
+            for (state_i, &shift_i) in state[1..].iter_mut().zip(MP::INTERNAL_DIAG_SHIFTS.as_ref())
+            {
+                let (evn, odd) = split(state_i.to_vector());
+                let shift = x86_64::_mm_cvtsi32_si128(shift_i as i32);
+                let si_evn =
+                    x86_64::_mm512_add_epi64(full_sum_evn, x86_64::_mm512_sll_epi64(evn, shift));
+                let si_odd =
+                    x86_64::_mm512_add_epi64(full_sum_odd, x86_64::_mm512_sll_epi64(odd, shift));
+                *state_i =
+                    PackedMontyField31AVX512::from_vector(monty_reduce::<FP>(si_evn, si_odd));
+            }
+        }
     }
 }
 
@@ -29,6 +69,49 @@ impl<FP, const WIDTH: usize, MP> DiffusionPermutation<PackedMontyField31AVX512<F
     for DiffusionMatrixMontyField31<MP>
 where
     FP: FieldParameters,
-    MP: DiffusionMatrixParameters<FP, WIDTH> + PackedFieldPoseidon2Helpers<FP>,
+    MP: DiffusionMatrixParameters<FP, WIDTH>,
 {
 }
+
+/// Split a vector of 16 `u32`s into its even and odd elements, each zero extended to a vector of
+/// 8 `u64`s.
+#[inline]
+#[must_use]
+fn split(x: __m512i) -> (__m512i, __m512i) {
+    unsafe {
+        // Safety: If this code got compiled then AVX-512F intrinsics are available.
+        (
+            x86_64::_mm512_maskz_mov_epi32(EVENS, x),
+            x86_64::_mm512_srli_epi64::<32>(x),
+        )
+    }
+}
+
+/// Monty reduce the 16 `u64`s given by their even and odd halves, as in `split`, each of which
+/// must be less than `P << 32`. The result is the vector of 16 reduced `u32`s in canonical form.
+///
+/// This is the same as the second half of `mul`, without the initial multiplication.
+#[inline]
+#[must_use]
+#[allow(non_snake_case)]
+fn monty_reduce<MPAVX512: MontyParametersAVX512>(x_evn: __m512i, x_odd: __m512i) -> __m512i {
+    unsafe {
+        // Safety: If this code got compiled then AVX-512F intrinsics are available.
+        let q_evn = x86_64::_mm512_mul_epu32(x_evn, MPAVX512::PACKED_MU);
+        let q_odd = x86_64::_mm512_mul_epu32(x_odd, MPAVX512::PACKED_MU);
+
+        // Get all the high halves of `x` as one vector.
+        let x_hi = mask_movehdup_epi32(x_odd, EVENS, x_evn);
+
+        let q_P_evn = x86_64::_mm512_mul_epu32(q_evn, MPAVX512::PACKED_P);
+        let q_P_odd = x86_64::_mm512_mul_epu32(q_odd, MPAVX512::PACKED_P);
+
+        // The low halves of `x` and `q_P` cancel out, so we only need the high halves.
+        let q_P_hi = mask_movehdup_epi32(q_P_odd, EVENS, q_P_evn);
+
+        // Subtraction `x_hi - q_P_hi` modulo `P`.
+        let underflow = x86_64::_mm512_cmplt_epu32_mask(x_hi, q_P_hi);
+        let t = x86_64::_mm512_sub_epi32(x_hi, q_P_hi);
+        x86_64::_mm512_mask_add_epi32(t, underflow, t, MPAVX512::PACKED_P)
+    }
+}
@@ -0,0 +1,14 @@
+//! A STARK for several AIRs of different heights, proven together with one transcript, one
+//! commitment per round and one opening proof.
+
+#![no_std]
+
+extern crate alloc;
+
+mod proof;
+mod prover;
+mod verifier;
+
+pub use proof::*;
+pub use prover::*;
+pub use verifier::*;
@@ -0,0 +1,49 @@
+use alloc::vec::Vec;
+
+use p3_commit::Pcs;
+use p3_uni_stark::StarkGenericConfig;
+use serde::{Deserialize, Serialize};
+
+type Com<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::Commitment;
+type PcsProof<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::Proof;
+
+/// A proof of several tables, each the trace of its own AIR.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MultiProof<SC: StarkGenericConfig> {
+    pub(crate) commitments: MultiCommitments<Com<SC>>,
+    /// The data of each table, in the order of the AIRs.
+    pub(crate) tables: Vec<TableProof<SC::Challenge>>,
+    pub(crate) opening_proof: PcsProof<SC>,
+}
+
+impl<SC: StarkGenericConfig> MultiProof<SC> {
+    pub fn num_tables(&self) -> usize {
+        self.tables.len()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MultiCommitments<Com> {
+    /// One commitment to the traces of all tables.
+    pub(crate) traces: Com,
+    /// One commitment to the quotient chunks of all tables.
+    pub(crate) quotient_chunks: Com,
+}
+
+/// The data specific to one table of a `MultiProof`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableProof<Challenge> {
+    pub(crate) degree_bits: usize,
+    /// The height of the trace before it was padded to `2^degree_bits` rows.
+    pub(crate) trace_height: usize,
+    /// The openings of the trace at each of the AIR's `window_offsets`.
+    pub(crate) trace_window: Vec<Vec<Challenge>>,
+    pub(crate) quotient_chunks: Vec<Vec<Challenge>>,
+}
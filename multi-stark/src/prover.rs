@@ -0,0 +1,415 @@
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::{izip, Itertools};
+use p3_air::{Air, MultiStageAir};
+use p3_challenger::{CanObserve, CanSample, FieldChallenger};
+use p3_commit::{LagrangeSelectors, Pcs, PolynomialSpace};
+use p3_field::{AbstractField, ExtensionField, PackedValue};
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_uni_stark::{
+    check_log_quotient_degree, get_max_constraint_degree_for_config, get_symbolic_constraints,
+    get_symbolic_lookups, log_quotient_degree_for_padding, Domain, PackedChallenge, PackedVal,
+    ProverConstraintFolder, StarkGenericConfig, SymbolicAirBuilder, Val,
+};
+use p3_util::log2_strict_usize;
+use tracing::{info_span, instrument};
+
+use crate::{MultiCommitments, MultiProof, TableProof};
+
+/// The shape of a table, as the prover and verifier both derive it from its AIR and trace height.
+pub(crate) struct TableShape<SC: StarkGenericConfig> {
+    pub(crate) trace_domain: Domain<SC>,
+    pub(crate) quotient_domain: Domain<SC>,
+    pub(crate) window_offsets: Vec<isize>,
+    pub(crate) periodic_columns: Vec<Vec<Val<SC>>>,
+    pub(crate) selector_height: usize,
+    pub(crate) log_quotient_degree: usize,
+    pub(crate) constraint_count: usize,
+}
+
+impl<SC: StarkGenericConfig> TableShape<SC> {
+    pub(crate) fn new<A>(
+        config: &SC,
+        air: &A,
+        degree_bits: usize,
+        trace_height: usize,
+        num_public_values: usize,
+    ) -> Result<Self, UnsupportedFeature>
+    where
+        A: MultiStageAir<Val<SC>> + Air<SymbolicAirBuilder<Val<SC>>>,
+    {
+        // These are checked first, as the AIR can't be evaluated symbolically without its
+        // preprocessed columns or later stages.
+        if air.preprocessed_trace().is_some() {
+            return Err(UnsupportedFeature::PreprocessedTrace);
+        }
+        if air.num_stages() > 1 {
+            return Err(UnsupportedFeature::MultiStage);
+        }
+        if !get_symbolic_lookups::<Val<SC>, A>(air, 0, num_public_values).is_empty() {
+            return Err(UnsupportedFeature::Lookups);
+        }
+        let constraint_count =
+            get_symbolic_constraints::<Val<SC>, A>(air, 0, num_public_values).len();
+        let constraint_degree =
+            get_max_constraint_degree_for_config(config, air, 0, num_public_values);
+
+        let degree = 1 << degree_bits;
+        let selector_height = config.trace_padding().selector_height(trace_height, degree);
+        let log_quotient_degree = log_quotient_degree_for_padding(
+            constraint_degree,
+            selector_height,
+            degree,
+            config.is_zk(),
+        );
+        let trace_domain = config.pcs().natural_domain_for_degree(degree);
+        let quotient_domain =
+            trace_domain.create_disjoint_domain(1 << (degree_bits + log_quotient_degree));
+        Ok(Self {
+            trace_domain,
+            quotient_domain,
+            window_offsets: air.window_offsets(),
+            periodic_columns: air.periodic_columns(),
+            selector_height,
+            log_quotient_degree,
+            constraint_count,
+        })
+    }
+
+    /// Whether the period of every periodic column is a power of two dividing the trace height.
+    pub(crate) fn has_valid_periods(&self) -> bool {
+        let degree = self.trace_domain.size();
+        self.periodic_columns
+            .iter()
+            .all(|column| column.len().is_power_of_two() && column.len() <= degree)
+    }
+}
+
+/// A feature of an AIR which multi-table proofs don't support.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnsupportedFeature {
+    /// The AIR has a preprocessed trace.
+    PreprocessedTrace,
+    /// The AIR's trace is committed to in more than one stage.
+    MultiStage,
+    /// The AIR declares lookups.
+    Lookups,
+}
+
+/// The AIR of the given table uses a feature which multi-table proofs don't support.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnsupportedAir {
+    pub table: usize,
+    pub feature: UnsupportedFeature,
+}
+
+/// The shape of each table, or the first table whose AIR isn't supported.
+pub(crate) fn table_shapes<SC, A>(
+    config: &SC,
+    airs: &[A],
+    degree_bits: impl IntoIterator<Item = usize>,
+    trace_heights: impl IntoIterator<Item = usize>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<Vec<TableShape<SC>>, UnsupportedAir>
+where
+    SC: StarkGenericConfig,
+    A: MultiStageAir<Val<SC>> + Air<SymbolicAirBuilder<Val<SC>>>,
+{
+    izip!(airs, degree_bits, trace_heights, public_values)
+        .enumerate()
+        .map(|(table, (air, degree_bits, trace_height, public_values))| {
+            TableShape::new(config, air, degree_bits, trace_height, public_values.len())
+                .map_err(|feature| UnsupportedAir { table, feature })
+        })
+        .collect()
+}
+
+/// Prove that each trace satisfies the AIR at the same index, with the public values at the same
+/// index.
+///
+/// The traces may have different heights, and are each padded as in `p3_uni_stark::prove`. They
+/// are committed to together, as are the quotients of all tables, and all tables share the
+/// challenges and the opening proof.
+///
+/// Returns an error if one of the AIRs has a preprocessed trace, more than one stage or lookups,
+/// none of which are supported here yet.
+#[instrument(skip_all, fields(num_tables = airs.len()))]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_multi<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<p3_uni_stark::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    airs: &[A],
+    challenger: &mut SC::Challenger,
+    traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<MultiProof<SC>, UnsupportedAir>
+where
+    SC: StarkGenericConfig,
+    A: MultiStageAir<Val<SC>>
+        + Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    assert_eq!(airs.len(), traces.len(), "each AIR needs a trace");
+    assert_eq!(
+        airs.len(),
+        public_values.len(),
+        "each AIR needs its public values"
+    );
+
+    let pcs = config.pcs();
+    let trace_heights = traces.iter().map(Matrix::height).collect_vec();
+    let traces = izip!(airs, traces)
+        .map(|(air, trace)| config.trace_padding().pad(trace, air.padding_row()))
+        .collect_vec();
+    let degree_bits = traces
+        .iter()
+        .map(|trace| log2_strict_usize(trace.height()))
+        .collect_vec();
+    let shapes = table_shapes(
+        config,
+        airs,
+        degree_bits.iter().copied(),
+        trace_heights.iter().copied(),
+        public_values,
+    )?;
+    for shape in &shapes {
+        check_log_quotient_degree(config, shape.log_quotient_degree)
+            .expect("the PCS can't evaluate a trace on a domain as large as its quotient's");
+        assert!(
+            shape.has_valid_periods(),
+            "the period of a periodic column must be a power of two dividing the padded height"
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    for (air, trace, public_values, shape) in izip!(airs, &traces, public_values, &shapes) {
+        if let Err(failure) = p3_uni_stark::check_constraints_with_stages::<_, SC::Challenge, _>(
+            air,
+            None,
+            trace,
+            &[],
+            &[],
+            public_values,
+            shape.selector_height,
+        ) {
+            panic!("{failure}");
+        }
+    }
+
+    let (traces_commit, traces_data) = info_span!("commit to trace data").in_scope(|| {
+        pcs.commit(
+            izip!(&shapes, traces)
+                .map(|(shape, trace)| (shape.trace_domain, trace))
+                .collect(),
+        )
+    });
+
+    // Observe the instance.
+    challenger.observe(Val::<SC>::from_canonical_usize(airs.len()));
+    for (&degree_bits, &trace_height) in izip!(&degree_bits, &trace_heights) {
+        challenger.observe(Val::<SC>::from_canonical_usize(degree_bits));
+        challenger.observe(Val::<SC>::from_canonical_usize(trace_height));
+    }
+
+    challenger.observe(traces_commit.clone());
+    for public_values in public_values {
+        challenger.observe_slice(public_values);
+    }
+    let alpha: SC::Challenge = challenger.sample_ext_element();
+
+    let mut quotient_chunks = vec![];
+    for (i, (air, shape, public_values)) in izip!(airs, &shapes, public_values).enumerate() {
+        let trace_on_quotient_domain =
+            pcs.get_evaluations_on_domain(&traces_data, i, shape.quotient_domain);
+        let sels = match config.selector_cache() {
+            Some(cache) => cache.selectors_on_coset_for_height(
+                shape.trace_domain,
+                shape.quotient_domain,
+                shape.selector_height,
+            ),
+            None => Arc::new(
+                shape
+                    .trace_domain
+                    .selectors_on_coset_for_height(shape.quotient_domain, shape.selector_height),
+            ),
+        };
+        let quotient_values = quotient_values(
+            air,
+            public_values,
+            shape,
+            &sels,
+            trace_on_quotient_domain,
+            alpha,
+        );
+        let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
+        let quotient_degree = 1 << shape.log_quotient_degree;
+        quotient_chunks.push(
+            izip!(
+                shape.quotient_domain.split_domains(quotient_degree),
+                shape
+                    .quotient_domain
+                    .split_evals(quotient_degree, quotient_flat)
+            )
+            .collect_vec(),
+        );
+    }
+    let num_quotient_chunks: usize = quotient_chunks.iter().map(Vec::len).sum();
+    let (quotient_commit, quotient_data) = info_span!("commit to quotient poly chunks")
+        .in_scope(|| pcs.commit_quotient(quotient_chunks));
+    challenger.observe(quotient_commit.clone());
+
+    let commitments = MultiCommitments {
+        traces: traces_commit,
+        quotient_chunks: quotient_commit,
+    };
+
+    let zeta: SC::Challenge = challenger.sample();
+    let trace_points = shapes
+        .iter()
+        .map(|shape| {
+            shape
+                .trace_domain
+                .rotated_points(zeta, &shape.window_offsets)
+                .unwrap()
+        })
+        .collect_vec();
+    let rounds = vec![
+        (&traces_data, trace_points),
+        (
+            &quotient_data,
+            // open every chunk of every table at zeta
+            (0..num_quotient_chunks).map(|_| vec![zeta]).collect_vec(),
+        ),
+    ];
+    let (opened_values, opening_proof) =
+        info_span!("open").in_scope(|| pcs.open(rounds, challenger));
+
+    let mut quotient_openings = opened_values[1].iter().map(|v| v[0].clone());
+    let tables = izip!(&shapes, degree_bits, trace_heights, &opened_values[0])
+        .map(
+            |(shape, degree_bits, trace_height, trace_openings)| TableProof {
+                degree_bits,
+                trace_height,
+                trace_window: trace_openings.clone(),
+                quotient_chunks: quotient_openings
+                    .by_ref()
+                    .take(1 << shape.log_quotient_degree)
+                    .collect(),
+            },
+        )
+        .collect();
+    Ok(MultiProof {
+        commitments,
+        tables,
+        opening_proof,
+    })
+}
+
+#[instrument(name = "compute quotient polynomial", skip_all)]
+fn quotient_values<SC, A, Mat>(
+    air: &A,
+    public_values: &Vec<Val<SC>>,
+    shape: &TableShape<SC>,
+    sels: &LagrangeSelectors<Vec<Val<SC>>>,
+    trace_on_quotient_domain: Mat,
+    alpha: SC::Challenge,
+) -> Vec<SC::Challenge>
+where
+    SC: StarkGenericConfig,
+    A: for<'a> Air<ProverConstraintFolder<'a, SC>>,
+    Mat: Matrix<Val<SC>> + Sync,
+{
+    let quotient_size = shape.quotient_domain.size();
+    let width = trace_on_quotient_domain.width();
+    let next_step = 1 << shape.log_quotient_degree;
+
+    let periodic_on_quotient_domain = shape
+        .periodic_columns
+        .iter()
+        .map(|column| {
+            shape
+                .trace_domain
+                .periodic_column_on_coset(column, shape.quotient_domain)
+        })
+        .collect_vec();
+
+    let mut alpha_powers = alpha.powers().take(shape.constraint_count).collect_vec();
+    alpha_powers.reverse();
+    // Broadcast once, rather than for every constraint on every packing of rows.
+    let alpha_powers = alpha_powers
+        .into_iter()
+        .map(PackedChallenge::<SC>::from_f)
+        .collect_vec();
+
+    (0..quotient_size)
+        .into_par_iter()
+        .step_by(PackedVal::<SC>::WIDTH)
+        .flat_map_iter(|i_start| {
+            let is_first_row = packed_window::<PackedVal<SC>>(&sels.is_first_row, i_start);
+            let is_last_row = packed_window::<PackedVal<SC>>(&sels.is_last_row, i_start);
+            let is_transition = packed_window::<PackedVal<SC>>(&sels.is_transition, i_start);
+            let inv_zeroifier = packed_window::<PackedVal<SC>>(&sels.inv_zeroifier, i_start);
+
+            let main = RowMajorMatrix::new(
+                trace_on_quotient_domain.vertically_packed_rows(
+                    i_start,
+                    next_step,
+                    &shape.window_offsets,
+                ),
+                width,
+            );
+
+            let periodic_values = periodic_on_quotient_domain
+                .iter()
+                .map(|column| packed_window::<PackedVal<SC>>(column, i_start))
+                .collect_vec();
+
+            let mut folder = ProverConstraintFolder {
+                preprocessed: RowMajorMatrixView::new(&[], 0),
+                main: main.as_view(),
+                stages: vec![],
+                stage_challenges: &[],
+                permutation: RowMajorMatrixView::new(&[], 0),
+                permutation_challenges: &[],
+                lookups: vec![],
+                periodic_values: &periodic_values,
+                public_values,
+                is_first_row,
+                is_last_row,
+                is_transition,
+                selectors: sels,
+                quotient_index: i_start,
+                quotient_step: next_step,
+                alpha_powers: &alpha_powers,
+                accumulator: PackedChallenge::<SC>::ZERO,
+                constraint_index: 0,
+            };
+            air.eval(&mut folder);
+
+            // quotient(x) = constraints(x) / Z_H(x)
+            let quotient = folder.accumulator * inv_zeroifier;
+
+            // Unpack the lanes, dropping any past the end of the quotient domain.
+            let num_lanes = PackedVal::<SC>::WIDTH.min(quotient_size - i_start);
+            (0..num_lanes).map(move |lane| SC::Challenge::unpack_lane(&quotient, lane))
+        })
+        .collect()
+}
+
+/// The packing of `values[start..start + P::WIDTH]`, with the lanes past the end of `values`
+/// wrapping around to its start, as `vertically_packed_row` does for matrices.
+#[inline]
+fn packed_window<P: PackedValue>(values: &[P::Value], start: usize) -> P {
+    if start + P::WIDTH <= values.len() {
+        *P::from_slice(&values[start..start + P::WIDTH])
+    } else {
+        P::from_fn(|i| values[(start + i) % values.len()])
+    }
+}
@@ -0,0 +1,240 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::{izip, Itertools};
+use p3_air::{Air, BaseAir, MultiStageAir};
+use p3_challenger::{CanObserve, CanSample, FieldChallenger};
+use p3_commit::{Pcs, PolynomialSpace};
+use p3_field::{AbstractExtensionField, AbstractField, Field};
+use p3_matrix::dense::RowMajorMatrixView;
+use p3_uni_stark::{
+    Domain, PcsError, StarkGenericConfig, SymbolicAirBuilder, Val, VerifierConstraintFolder,
+};
+use p3_util::log2_ceil_usize;
+use tracing::instrument;
+
+use crate::prover::{table_shapes, TableShape};
+use crate::{MultiProof, TableProof, UnsupportedAir};
+
+/// Verify a proof from `prove_multi`, against the same AIRs and public values in the same order.
+#[instrument(skip_all, fields(num_tables = airs.len()))]
+pub fn verify_multi<SC, A>(
+    config: &SC,
+    airs: &[A],
+    challenger: &mut SC::Challenger,
+    proof: &MultiProof<SC>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), MultiVerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: MultiStageAir<Val<SC>>
+        + Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let MultiProof {
+        commitments,
+        tables,
+        opening_proof,
+    } = proof;
+
+    if tables.len() != airs.len() || public_values.len() != airs.len() {
+        return Err(MultiVerificationError::InvalidProofShape);
+    }
+    // Each trace was padded to the next power of two.
+    if tables.iter().any(|table| {
+        table.trace_height == 0 || log2_ceil_usize(table.trace_height) != table.degree_bits
+    }) {
+        return Err(MultiVerificationError::InvalidProofShape);
+    }
+
+    let shapes = table_shapes(
+        config,
+        airs,
+        tables.iter().map(|table| table.degree_bits),
+        tables.iter().map(|table| table.trace_height),
+        public_values,
+    )
+    .map_err(MultiVerificationError::UnsupportedAir)?;
+
+    let ext_degree = <SC::Challenge as AbstractExtensionField<Val<SC>>>::D;
+    let valid_shape = izip!(airs, tables, &shapes).all(|(air, table, shape)| {
+        let air_width = <A as BaseAir<Val<SC>>>::width(air);
+        table.trace_window.len() == shape.window_offsets.len()
+            && shape.has_valid_periods()
+            && table.trace_window.iter().all(|row| row.len() == air_width)
+            && table.quotient_chunks.len() == 1 << shape.log_quotient_degree
+            && table
+                .quotient_chunks
+                .iter()
+                .all(|qc| qc.len() == ext_degree)
+    });
+    if !valid_shape {
+        return Err(MultiVerificationError::InvalidProofShape);
+    }
+
+    // Observe the instance.
+    challenger.observe(Val::<SC>::from_canonical_usize(airs.len()));
+    for table in tables {
+        challenger.observe(Val::<SC>::from_canonical_usize(table.degree_bits));
+        challenger.observe(Val::<SC>::from_canonical_usize(table.trace_height));
+    }
+
+    challenger.observe(commitments.traces.clone());
+    for public_values in public_values {
+        challenger.observe_slice(public_values);
+    }
+    let alpha: SC::Challenge = challenger.sample_ext_element();
+    challenger.observe(commitments.quotient_chunks.clone());
+
+    let zeta: SC::Challenge = challenger.sample();
+
+    let quotient_chunks_domains = shapes
+        .iter()
+        .map(|shape| {
+            shape
+                .quotient_domain
+                .split_domains(1 << shape.log_quotient_degree)
+        })
+        .collect_vec();
+    let rounds = vec![
+        (
+            commitments.traces.clone(),
+            izip!(&shapes, tables)
+                .map(|(shape, table)| {
+                    (
+                        shape.trace_domain,
+                        izip!(
+                            shape
+                                .trace_domain
+                                .rotated_points(zeta, &shape.window_offsets)
+                                .unwrap(),
+                            table.trace_window.clone()
+                        )
+                        .collect_vec(),
+                    )
+                })
+                .collect_vec(),
+        ),
+        (
+            commitments.quotient_chunks.clone(),
+            izip!(&quotient_chunks_domains, tables)
+                .flat_map(|(domains, table)| {
+                    izip!(domains, &table.quotient_chunks)
+                        .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
+                })
+                .collect_vec(),
+        ),
+    ];
+    config
+        .pcs()
+        .verify(rounds, opening_proof, challenger)
+        .map_err(MultiVerificationError::InvalidOpeningArgument)?;
+
+    for (index, (air, table, shape, domains, public_values)) in izip!(
+        airs,
+        tables,
+        &shapes,
+        &quotient_chunks_domains,
+        public_values
+    )
+    .enumerate()
+    {
+        if !verify_table_constraints(air, table, shape, domains, public_values, alpha, zeta) {
+            return Err(MultiVerificationError::OodEvaluationMismatch { table: index });
+        }
+    }
+    Ok(())
+}
+
+/// Whether the constraints of a table, folded with `alpha`, match its quotient at `zeta`.
+fn verify_table_constraints<SC, A>(
+    air: &A,
+    table: &TableProof<SC::Challenge>,
+    shape: &TableShape<SC>,
+    quotient_chunks_domains: &[Domain<SC>],
+    public_values: &Vec<Val<SC>>,
+    alpha: SC::Challenge,
+    zeta: SC::Challenge,
+) -> bool
+where
+    SC: StarkGenericConfig,
+    A: for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let zps = quotient_chunks_domains
+        .iter()
+        .enumerate()
+        .map(|(i, domain)| {
+            quotient_chunks_domains
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, other_domain)| {
+                    other_domain.zp_at_point(zeta)
+                        * other_domain.zp_at_point(domain.first_point()).inverse()
+                })
+                .product::<SC::Challenge>()
+        })
+        .collect_vec();
+
+    let quotient = table
+        .quotient_chunks
+        .iter()
+        .enumerate()
+        .map(|(ch_i, ch)| {
+            ch.iter()
+                .enumerate()
+                .map(|(e_i, &c)| zps[ch_i] * SC::Challenge::monomial(e_i) * c)
+                .sum::<SC::Challenge>()
+        })
+        .sum::<SC::Challenge>();
+
+    let sels = shape
+        .trace_domain
+        .selectors_at_point_for_height(zeta, shape.selector_height);
+
+    let periodic_values = shape
+        .periodic_columns
+        .iter()
+        .map(|column| shape.trace_domain.periodic_column_at_point(column, zeta))
+        .collect_vec();
+
+    let empty = RowMajorMatrixView::new(&[], 0);
+    let main_values = table.trace_window.concat();
+    let mut folder = VerifierConstraintFolder {
+        preprocessed: empty,
+        main: RowMajorMatrixView::new(&main_values, <A as BaseAir<Val<SC>>>::width(air)),
+        stages: vec![],
+        stage_challenges: &[],
+        permutation: empty,
+        permutation_challenges: &[],
+        lookups: vec![],
+        periodic_values: &periodic_values,
+        public_values,
+        is_first_row: sels.is_first_row,
+        is_last_row: sels.is_last_row,
+        is_transition: sels.is_transition,
+        trace_domain: shape.trace_domain,
+        selector_height: shape.selector_height,
+        zeta,
+        alpha,
+        accumulator: SC::Challenge::ZERO,
+    };
+    air.eval(&mut folder);
+
+    // folded_constraints(zeta) / Z_H(zeta) = quotient(zeta)
+    folder.accumulator * sels.inv_zeroifier == quotient
+}
+
+#[derive(Debug)]
+pub enum MultiVerificationError<PcsErr> {
+    InvalidProofShape,
+    /// An error occurred while verifying the claimed openings.
+    InvalidOpeningArgument(PcsErr),
+    /// Out-of-domain evaluation mismatch in the given table, i.e. its `constraints(zeta)` did not
+    /// match `quotient(zeta) Z_H(zeta)`.
+    OodEvaluationMismatch {
+        table: usize,
+    },
+    /// One of the AIRs uses a feature which multi-table proofs don't support.
+    UnsupportedAir(UnsupportedAir),
+}
@@ -0,0 +1,204 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, MultiStageAir};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_multi_stark::{
+    prove_multi, verify_multi, MultiVerificationError, UnsupportedAir, UnsupportedFeature,
+};
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::StarkConfig;
+use rand::thread_rng;
+
+/// The AIRs of the tables, as one type so that they can be proven together.
+enum TestAir {
+    /// Two columns stepping through the Fibonacci sequence from the public values `[a, b]`, with
+    /// the last value of the second column the public value `x`.
+    Fibonacci,
+    /// One column counting up from zero.
+    Counter,
+    /// `Counter`, with the counter also given as a preprocessed column, which multi-table proofs
+    /// don't support.
+    PreprocessedCounter,
+}
+
+impl<F: Field> BaseAir<F> for TestAir {
+    fn width(&self) -> usize {
+        match self {
+            Self::Fibonacci => 2,
+            Self::Counter | Self::PreprocessedCounter => 1,
+        }
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        match self {
+            Self::PreprocessedCounter => Some(counter_trace(1 << 6)),
+            _ => None,
+        }
+    }
+}
+
+impl<F: Field> MultiStageAir<F> for TestAir {}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for TestAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        match self {
+            Self::Fibonacci => {
+                let pis = builder.public_values();
+                let (a, b, x) = (pis[0], pis[1], pis[2]);
+
+                let mut when_first_row = builder.when_first_row();
+                when_first_row.assert_eq(local[0], a);
+                when_first_row.assert_eq(local[1], b);
+
+                let mut when_transition = builder.when_transition();
+                when_transition.assert_eq(next[0], local[1]);
+                when_transition.assert_eq(next[1], local[0] + local[1]);
+
+                builder.when_last_row().assert_eq(local[1], x);
+            }
+            Self::Counter | Self::PreprocessedCounter => {
+                builder.when_first_row().assert_zero(local[0]);
+                builder
+                    .when_transition()
+                    .assert_eq(next[0], local[0] + AB::Expr::ONE);
+            }
+        }
+    }
+}
+
+fn fibonacci_trace<F: Field>(a: u64, b: u64, n: usize) -> RowMajorMatrix<F> {
+    let mut values = Vec::with_capacity(2 * n);
+    let (mut left, mut right) = (F::from_canonical_u64(a), F::from_canonical_u64(b));
+    for _ in 0..n {
+        values.extend([left, right]);
+        (left, right) = (right, left + right);
+    }
+    RowMajorMatrix::new(values, 2)
+}
+
+fn counter_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new_col((0..n).map(F::from_canonical_usize).collect())
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+#[test]
+fn test_tables_of_different_heights() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let airs = [TestAir::Fibonacci, TestAir::Counter, TestAir::Fibonacci];
+    let traces = vec![
+        fibonacci_trace::<Val>(0, 1, 1 << 3),
+        counter_trace::<Val>(1 << 6),
+        fibonacci_trace::<Val>(2, 3, 1 << 4),
+    ];
+    let public_values = vec![
+        vec![Val::ZERO, Val::ONE, Val::from_canonical_u32(21)],
+        vec![],
+        vec![
+            Val::TWO,
+            Val::from_canonical_u32(3),
+            Val::from_canonical_u32(4181),
+        ],
+    ];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_multi(&config, &airs, &mut challenger, traces, &public_values).unwrap();
+    assert_eq!(proof.num_tables(), 3);
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify_multi(&config, &airs, &mut challenger, &proof, &public_values)
+        .expect("verification failed");
+
+    // The proof doesn't verify with a different claimed result for one of the tables.
+    let mut wrong_public_values = public_values.clone();
+    wrong_public_values[2][2] += Val::ONE;
+    let mut challenger = Challenger::new(perm.clone());
+    assert!(verify_multi(
+        &config,
+        &airs,
+        &mut challenger,
+        &proof,
+        &wrong_public_values
+    )
+    .is_err());
+
+    // An AIR with a preprocessed trace is rejected rather than verified against the wrong columns.
+    let unsupported_airs = [
+        TestAir::Fibonacci,
+        TestAir::PreprocessedCounter,
+        TestAir::Fibonacci,
+    ];
+    let unsupported = UnsupportedAir {
+        table: 1,
+        feature: UnsupportedFeature::PreprocessedTrace,
+    };
+    let mut challenger = Challenger::new(perm.clone());
+    assert!(matches!(
+        verify_multi(
+            &config,
+            &unsupported_airs,
+            &mut challenger,
+            &proof,
+            &public_values
+        ),
+        Err(MultiVerificationError::UnsupportedAir(error)) if error == unsupported
+    ));
+    let traces = vec![
+        fibonacci_trace::<Val>(0, 1, 1 << 3),
+        counter_trace::<Val>(1 << 6),
+        fibonacci_trace::<Val>(2, 3, 1 << 4),
+    ];
+    let mut challenger = Challenger::new(perm);
+    assert_eq!(
+        prove_multi(
+            &config,
+            &unsupported_airs,
+            &mut challenger,
+            traces,
+            &public_values
+        )
+        .err(),
+        Some(unsupported)
+    );
+}
@@ -4,7 +4,7 @@ use p3_baby_bear::{BabyBear, BabyBearDiffusionMatrixParameters, BabyBearParamete
 use p3_challenger::{HashChallenger, SerializingChallenger32};
 use p3_commit::ExtensionMmcs;
 use p3_field::extension::BinomialExtensionField;
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
 use p3_keccak::{Keccak256Hash, KeccakF};
 use p3_merkle_tree::MerkleTreeHidingMmcs;
 use p3_monty_31::GenericDiffusionMatrixMontyField31;
@@ -12,8 +12,8 @@ use p3_poseidon2::Poseidon2ExternalMatrixGeneral;
 use p3_poseidon2_air::{generate_vectorized_trace_rows, RoundConstants, VectorizedPoseidon2Air};
 use p3_symmetric::{CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher32To64};
 use p3_uni_stark::{prove, verify, StarkConfig};
-use rand::rngs::ThreadRng;
-use rand::{random, thread_rng};
+use rand::rngs::StdRng;
+use rand::{random, thread_rng, SeedableRng};
 #[cfg(not(target_env = "msvc"))]
 use tikv_jemallocator::Jemalloc;
 use tracing_forest::util::LevelFilter;
@@ -68,11 +68,11 @@ fn main() -> Result<(), impl Debug> {
         [u64; p3_keccak::VECTOR_LEN],
         FieldHash,
         MyCompress,
-        ThreadRng,
+        StdRng,
         4,
         4,
     >;
-    let val_mmcs = ValMmcs::new(field_hash, compress, thread_rng());
+    let val_mmcs = ValMmcs::new(field_hash, compress, StdRng::from_entropy());
 
     type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
     let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
@@ -121,8 +121,11 @@ fn main() -> Result<(), impl Debug> {
 
     let fri_config = FriConfig {
         log_blowup: 1,
+        log_final_poly_len: 0,
         num_queries: 100,
         proof_of_work_bits: 16,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
         mmcs: challenge_mmcs,
     };
     type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
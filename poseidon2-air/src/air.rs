@@ -1,6 +1,6 @@
 use core::borrow::Borrow;
 
-use p3_air::{Air, AirBuilder, BaseAir};
+use p3_air::{Air, AirBuilder, AirBuilderWithLookups, BaseAir, BaseSubAir, SubAir, SubBuilder};
 use p3_field::{AbstractField, Field};
 use p3_matrix::Matrix;
 use p3_poseidon2::{DiffusionPermutation, MdsLightPermutation};
@@ -10,6 +10,9 @@ use crate::constants::RoundConstants;
 use crate::{FullRound, PartialRound, SBox};
 
 /// Assumes the field size is at least 16 bits.
+///
+/// As a sub-AIR of a larger AIR, with `with_bus`, it also receives each exported permutation from
+/// a bus, so that other pieces of the AIR can use it with `Poseidon2Builder`.
 #[derive(Debug)]
 pub struct Poseidon2Air<
     F: Field,
@@ -24,6 +27,7 @@ pub struct Poseidon2Air<
     constants: RoundConstants<F, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
     external_linear_layer: MdsLight,
     internal_linear_layer: Diffusion,
+    bus: Option<usize>,
 }
 
 impl<
@@ -56,8 +60,16 @@ impl<
             constants,
             external_linear_layer,
             internal_linear_layer,
+            bus: None,
         }
     }
+
+    /// Receive each exported permutation from `bus`, as its inputs followed by its outputs, when
+    /// this is a sub-AIR.
+    pub fn with_bus(mut self, bus: usize) -> Self {
+        self.bus = Some(bus);
+        self
+    }
 }
 
 impl<
@@ -198,6 +210,75 @@ impl<
     }
 }
 
+impl<
+        F: Field,
+        MdsLight: Sync,
+        Diffusion: Sync,
+        const WIDTH: usize,
+        const SBOX_DEGREE: usize,
+        const SBOX_REGISTERS: usize,
+        const HALF_FULL_ROUNDS: usize,
+        const PARTIAL_ROUNDS: usize,
+    > BaseSubAir
+    for Poseidon2Air<
+        F,
+        MdsLight,
+        Diffusion,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >
+{
+    fn width(&self) -> usize {
+        num_cols::<WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>()
+    }
+}
+
+impl<
+        AB: AirBuilderWithLookups,
+        MdsLight: MdsLightPermutation<AB::Expr, WIDTH> + Sync,
+        Diffusion: DiffusionPermutation<AB::Expr, WIDTH> + Sync,
+        const WIDTH: usize,
+        const SBOX_DEGREE: usize,
+        const SBOX_REGISTERS: usize,
+        const HALF_FULL_ROUNDS: usize,
+        const PARTIAL_ROUNDS: usize,
+    > SubAir<AB>
+    for Poseidon2Air<
+        AB::F,
+        MdsLight,
+        Diffusion,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >
+{
+    fn eval(&self, builder: &mut SubBuilder<'_, AB>) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &Poseidon2Cols<
+            AB::Var,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        > = (*local).borrow();
+
+        eval(self, builder, local);
+
+        builder.assert_bool(local.export);
+        if let Some(bus) = self.bus {
+            let values = local.inputs.iter().chain(local.outputs()).copied();
+            builder.receive(bus, values, local.export);
+        }
+    }
+}
+
 #[inline]
 fn eval_full_round<
     AB: AirBuilder,
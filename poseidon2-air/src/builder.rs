@@ -0,0 +1,21 @@
+use p3_air::AirBuilderWithLookups;
+
+/// Permutations checked by a `Poseidon2Air`, for any AIR with lookups.
+///
+/// Lookups only balance within an AIR, so the `Poseidon2Air` must be a sub-AIR of the same AIR,
+/// with `Poseidon2Air::with_bus(bus)`. Each of its rows exports one permutation once, so a
+/// permutation used twice needs two rows.
+pub trait Poseidon2Builder: AirBuilderWithLookups {
+    /// Assert that `outputs` is the Poseidon2 permutation of `inputs`, `multiplicity` times.
+    fn poseidon2<I, O, M>(&mut self, bus: usize, inputs: I, outputs: O, multiplicity: M)
+    where
+        I: IntoIterator,
+        I::Item: Into<Self::Expr>,
+        O: IntoIterator<Item = I::Item>,
+        M: Into<Self::Expr>,
+    {
+        self.send(bus, inputs.into_iter().chain(outputs), multiplicity);
+    }
+}
+
+impl<AB: AirBuilderWithLookups> Poseidon2Builder for AB {}
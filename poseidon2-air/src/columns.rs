@@ -17,6 +17,8 @@ pub struct Poseidon2Cols<
     const HALF_FULL_ROUNDS: usize,
     const PARTIAL_ROUNDS: usize,
 > {
+    /// One if the permutation is received from the bus of `Poseidon2Air::with_bus`, and zero for
+    /// padding.
     pub export: T,
 
     pub inputs: [T; WIDTH],
@@ -31,6 +33,21 @@ pub struct Poseidon2Cols<
     pub ending_full_rounds: [FullRound<T, WIDTH, SBOX_DEGREE, SBOX_REGISTERS>; HALF_FULL_ROUNDS],
 }
 
+impl<
+        T,
+        const WIDTH: usize,
+        const SBOX_DEGREE: usize,
+        const SBOX_REGISTERS: usize,
+        const HALF_FULL_ROUNDS: usize,
+        const PARTIAL_ROUNDS: usize,
+    > Poseidon2Cols<T, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>
+{
+    /// The output of the permutation, i.e. the post-state of the last full round.
+    pub fn outputs(&self) -> &[T; WIDTH] {
+        &self.ending_full_rounds[HALF_FULL_ROUNDS - 1].post
+    }
+}
+
 /// Full round columns.
 #[repr(C)]
 pub struct FullRound<T, const WIDTH: usize, const SBOX_DEGREE: usize, const SBOX_REGISTERS: usize> {
@@ -20,6 +20,20 @@ pub struct RoundConstants<
 impl<F: Field, const WIDTH: usize, const HALF_FULL_ROUNDS: usize, const PARTIAL_ROUNDS: usize>
     RoundConstants<F, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>
 {
+    /// Round constants from those of the first half of the full rounds, the partial rounds and
+    /// the second half of the full rounds, e.g. to match a `p3_poseidon2::Poseidon2`.
+    pub const fn new(
+        beginning_full_round_constants: [[F; WIDTH]; HALF_FULL_ROUNDS],
+        partial_round_constants: [F; PARTIAL_ROUNDS],
+        ending_full_round_constants: [[F; WIDTH]; HALF_FULL_ROUNDS],
+    ) -> Self {
+        Self {
+            beginning_full_round_constants,
+            partial_round_constants,
+            ending_full_round_constants,
+        }
+    }
+
     pub fn from_rng<R: Rng>(rng: &mut R) -> Self
     where
         Standard: Distribution<F> + Distribution<[F; WIDTH]>,
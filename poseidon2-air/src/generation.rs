@@ -1,4 +1,5 @@
 use alloc::vec::Vec;
+use core::iter;
 use core::mem::MaybeUninit;
 
 use p3_field::PrimeField;
@@ -10,6 +11,8 @@ use tracing::instrument;
 use crate::columns::{num_cols, Poseidon2Cols};
 use crate::{FullRound, PartialRound, RoundConstants, SBox};
 
+/// Like `generate_trace_rows`, with `VECTOR_LEN` permutations per row. The inputs are padded to
+/// `VECTOR_LEN` times a power of two.
 #[instrument(name = "generate vectorized Poseidon2 trace", skip_all)]
 pub fn generate_vectorized_trace_rows<
     F: PrimeField,
@@ -27,13 +30,10 @@ pub fn generate_vectorized_trace_rows<
     external_linear_layer: &MdsLight,
     internal_linear_layer: &Diffusion,
 ) -> RowMajorMatrix<F> {
-    let n = inputs.len();
-    assert!(
-        n % VECTOR_LEN == 0 && (n / VECTOR_LEN).is_power_of_two(),
-        "Callers expected to pad inputs to VECTOR_LEN times a power of two"
-    );
+    let nrows = inputs.len().div_ceil(VECTOR_LEN).next_power_of_two();
+    let n = nrows * VECTOR_LEN;
+    let inputs = pad_inputs(inputs, n);
 
-    let nrows = n.div_ceil(VECTOR_LEN);
     let ncols = num_cols::<WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>()
         * VECTOR_LEN;
     let mut vec = Vec::with_capacity(nrows * ncols * 2);
@@ -54,15 +54,19 @@ pub fn generate_vectorized_trace_rows<
     assert!(suffix.is_empty(), "Alignment should match");
     assert_eq!(perms.len(), n);
 
-    perms.par_iter_mut().zip(inputs).for_each(|(perm, input)| {
-        generate_trace_rows_for_perm(
-            perm,
-            input,
-            round_constants,
-            external_linear_layer,
-            internal_linear_layer,
-        );
-    });
+    perms
+        .par_iter_mut()
+        .zip(inputs)
+        .for_each(|(perm, (input, export))| {
+            generate_trace_rows_for_perm(
+                perm,
+                input,
+                export,
+                round_constants,
+                external_linear_layer,
+                internal_linear_layer,
+            );
+        });
 
     unsafe {
         vec.set_len(nrows * ncols);
@@ -70,6 +74,8 @@ pub fn generate_vectorized_trace_rows<
     RowMajorMatrix::new(vec, ncols)
 }
 
+/// The trace of the permutations of `inputs`, one per row, which are exported. The inputs are
+/// padded to a power of two with permutations of zero which aren't.
 // TODO: Take generic iterable
 #[instrument(name = "generate Poseidon2 trace", skip_all)]
 pub fn generate_trace_rows<
@@ -87,11 +93,8 @@ pub fn generate_trace_rows<
     external_linear_layer: &MdsLight,
     internal_linear_layer: &Diffusion,
 ) -> RowMajorMatrix<F> {
-    let n = inputs.len();
-    assert!(
-        n.is_power_of_two(),
-        "Callers expected to pad inputs to a power of two"
-    );
+    let n = inputs.len().next_power_of_two();
+    let inputs = pad_inputs(inputs, n);
 
     let ncols = num_cols::<WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>();
     let mut vec = Vec::with_capacity(n * ncols * 2);
@@ -112,15 +115,19 @@ pub fn generate_trace_rows<
     assert!(suffix.is_empty(), "Alignment should match");
     assert_eq!(perms.len(), n);
 
-    perms.par_iter_mut().zip(inputs).for_each(|(perm, input)| {
-        generate_trace_rows_for_perm(
-            perm,
-            input,
-            constants,
-            external_linear_layer,
-            internal_linear_layer,
-        );
-    });
+    perms
+        .par_iter_mut()
+        .zip(inputs)
+        .for_each(|(perm, (input, export))| {
+            generate_trace_rows_for_perm(
+                perm,
+                input,
+                export,
+                constants,
+                external_linear_layer,
+                internal_linear_layer,
+            );
+        });
 
     unsafe {
         vec.set_len(n * ncols);
@@ -128,6 +135,19 @@ pub fn generate_trace_rows<
     RowMajorMatrix::new(vec, ncols)
 }
 
+/// Each input with whether it's exported, followed by unexported zeros, up to `n` of them.
+fn pad_inputs<F: PrimeField, const WIDTH: usize>(
+    inputs: Vec<[F; WIDTH]>,
+    n: usize,
+) -> Vec<([F; WIDTH], bool)> {
+    let padding = n - inputs.len();
+    inputs
+        .into_iter()
+        .map(|input| (input, true))
+        .chain(iter::repeat(([F::ZERO; WIDTH], false)).take(padding))
+        .collect()
+}
+
 /// `rows` will normally consist of 24 rows, with an exception for the final row.
 fn generate_trace_rows_for_perm<
     F: PrimeField,
@@ -148,11 +168,12 @@ fn generate_trace_rows_for_perm<
         PARTIAL_ROUNDS,
     >,
     mut state: [F; WIDTH],
+    export: bool,
     constants: &RoundConstants<F, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
     external_linear_layer: &MdsLight,
     internal_linear_layer: &Diffusion,
 ) {
-    perm.export.write(F::ONE);
+    perm.export.write(F::from_bool(export));
     perm.inputs
         .iter_mut()
         .zip(state.iter())
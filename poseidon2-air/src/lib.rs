@@ -5,12 +5,14 @@
 extern crate alloc;
 
 mod air;
+mod builder;
 mod columns;
 mod constants;
 mod generation;
 mod vectorized;
 
 pub use air::*;
+pub use builder::*;
 pub use columns::*;
 pub use constants::*;
 pub use generation::*;
@@ -0,0 +1,236 @@
+use core::borrow::Borrow;
+
+use p3_air::{Air, AirBuilderWithLookups, BaseAir, BaseSubAir, SubAir, SubBuilder};
+use p3_baby_bear::{
+    BabyBear, BabyBearDiffusionMatrixParameters, BabyBearParameters, DiffusionMatrixBabyBear,
+};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_monty_31::GenericDiffusionMatrixMontyField31;
+use p3_poseidon2::{
+    DiffusionPermutation, MdsLightPermutation, Poseidon2, Poseidon2ExternalMatrixGeneral,
+};
+use p3_poseidon2_air::{
+    generate_trace_rows, Poseidon2Air, Poseidon2Builder, Poseidon2Cols, RoundConstants,
+};
+use p3_symmetric::{PaddingFreeSponge, Permutation, TruncatedPermutation};
+use p3_uni_stark::{prove, verify, StarkConfig};
+use rand::distributions::{Distribution, Standard};
+use rand::{thread_rng, Rng};
+
+type Val = BabyBear;
+type MdsLight = Poseidon2ExternalMatrixGeneral;
+type Diffusion =
+    GenericDiffusionMatrixMontyField31<BabyBearParameters, BabyBearDiffusionMatrixParameters>;
+
+const SBOX_DEGREE: usize = 7;
+const SBOX_REGISTERS: usize = 1;
+const HALF_FULL_ROUNDS: usize = 4;
+const PARTIAL_ROUNDS: usize = 13;
+
+type Cols<T, const WIDTH: usize> =
+    Poseidon2Cols<T, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>;
+
+/// Random round constants for the AIR, and the native permutation with the same constants.
+fn random_constants<const WIDTH: usize>() -> (
+    RoundConstants<Val, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
+    Poseidon2<Val, MdsLight, Diffusion, WIDTH, 7>,
+)
+where
+    Standard: Distribution<[Val; WIDTH]>,
+{
+    let mut rng = thread_rng();
+    let beginning: [[Val; WIDTH]; HALF_FULL_ROUNDS] = core::array::from_fn(|_| rng.gen());
+    let partial: [Val; PARTIAL_ROUNDS] = core::array::from_fn(|_| rng.gen::<Val>());
+    let ending: [[Val; WIDTH]; HALF_FULL_ROUNDS] = core::array::from_fn(|_| rng.gen());
+    let native = Poseidon2::new(
+        2 * HALF_FULL_ROUNDS,
+        [beginning, ending].concat(),
+        MdsLight::default(),
+        PARTIAL_ROUNDS,
+        partial.to_vec(),
+        Diffusion::new(),
+    );
+    (RoundConstants::new(beginning, partial, ending), native)
+}
+
+fn check_trace_matches_native<const WIDTH: usize>()
+where
+    Standard: Distribution<[Val; WIDTH]>,
+    MdsLight: MdsLightPermutation<Val, WIDTH>,
+    Diffusion: DiffusionPermutation<Val, WIDTH>,
+    Poseidon2<Val, MdsLight, Diffusion, WIDTH, 7>: Permutation<[Val; WIDTH]>,
+{
+    let (constants, native) = random_constants::<WIDTH>();
+    let inputs: Vec<[Val; WIDTH]> = (0..5).map(|_| thread_rng().gen()).collect();
+    let trace = generate_trace_rows::<
+        Val,
+        MdsLight,
+        Diffusion,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >(
+        inputs.clone(),
+        &constants,
+        &MdsLight::default(),
+        &Diffusion::new(),
+    );
+    // Padded to a power of two.
+    assert_eq!(trace.height(), 8);
+
+    for i in 0..trace.height() {
+        let row = trace.row_slice(i);
+        let cols: &Cols<Val, WIDTH> = (*row).borrow();
+        let input = inputs.get(i).copied().unwrap_or([Val::ZERO; WIDTH]);
+        assert_eq!(cols.inputs, input);
+        assert_eq!(*cols.outputs(), native.permute(input));
+        assert_eq!(cols.export, Val::from_bool(i < inputs.len()));
+    }
+}
+
+#[test]
+fn test_width_16_matches_native() {
+    check_trace_matches_native::<16>();
+}
+
+#[test]
+fn test_width_24_matches_native() {
+    check_trace_matches_native::<24>();
+}
+
+const WIDTH: usize = 16;
+const BUS: usize = 0;
+
+/// The columns of the chip: inputs, outputs, and whether the row uses the permutation.
+const CHIP_WIDTH: usize = 2 * WIDTH + 1;
+
+/// A chip which uses the permutations of a `Poseidon2Air` in the same AIR.
+struct HashAir {
+    poseidon2: Poseidon2Air<
+        Val,
+        MdsLight,
+        Diffusion,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >,
+}
+
+impl BaseAir<Val> for HashAir {
+    fn width(&self) -> usize {
+        CHIP_WIDTH + BaseSubAir::width(&self.poseidon2)
+    }
+}
+
+impl<AB: AirBuilderWithLookups<F = Val>> Air<AB> for HashAir
+where
+    MdsLight: MdsLightPermutation<AB::Expr, WIDTH>,
+    Diffusion: DiffusionPermutation<AB::Expr, WIDTH>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local: Vec<AB::Var> = main.row_slice(0).to_vec();
+        let (inputs, outputs, enabled) =
+            (&local[..WIDTH], &local[WIDTH..2 * WIDTH], local[2 * WIDTH]);
+        builder.assert_bool(enabled);
+        builder.poseidon2(
+            BUS,
+            inputs.iter().copied(),
+            outputs.iter().copied(),
+            enabled,
+        );
+
+        let columns = CHIP_WIDTH..BaseAir::width(self);
+        SubAir::eval(&self.poseidon2, &mut SubBuilder::new(builder, columns));
+    }
+}
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+#[test]
+fn test_permutations_from_another_chip() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let (constants, native) = random_constants::<WIDTH>();
+    let inputs: Vec<[Val; WIDTH]> = (0..5).map(|_| thread_rng().gen()).collect();
+    let poseidon2_trace = generate_trace_rows::<
+        Val,
+        MdsLight,
+        Diffusion,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >(
+        inputs.clone(),
+        &constants,
+        &MdsLight::default(),
+        &Diffusion::new(),
+    );
+    let air = HashAir {
+        poseidon2: Poseidon2Air::new(constants, MdsLight::default(), Diffusion::new())
+            .with_bus(BUS),
+    };
+
+    let mut values = Vec::new();
+    for (i, poseidon2_row) in poseidon2_trace.rows().enumerate() {
+        match inputs.get(i) {
+            Some(&input) => {
+                values.extend(input);
+                values.extend(native.permute(input));
+                values.push(Val::ONE);
+            }
+            None => values.extend([Val::ZERO; CHIP_WIDTH]),
+        }
+        values.extend(poseidon2_row);
+    }
+    let trace = RowMajorMatrix::new(values, air.width());
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
+}
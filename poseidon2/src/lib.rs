@@ -11,6 +11,7 @@ extern crate alloc;
 mod diffusion;
 mod matrix;
 mod round_numbers;
+mod spec;
 use alloc::vec::Vec;
 
 pub use diffusion::{matmul_internal, DiffusionPermutation};
@@ -20,6 +21,7 @@ use p3_symmetric::{CryptographicPermutation, Permutation};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 pub use round_numbers::poseidon2_round_numbers_128;
+pub use spec::*;
 
 const SUPPORTED_WIDTHS: [usize; 8] = [2, 3, 4, 8, 12, 16, 20, 24];
 
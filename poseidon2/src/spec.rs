@@ -0,0 +1,357 @@
+//! Loading `Poseidon2` instances from externally supplied round constants, e.g. an audited set
+//! which must be used as is.
+//!
+//! A [`Poseidon2Spec`] holds the width, S-box degree and round constants of an instance, as the
+//! canonical integer representatives of the constants, so that it works for fields of any size,
+//! such as BN254. It implements `Serialize` and `Deserialize`, so it can be read from any `serde`
+//! format such as JSON, and [`Poseidon2Spec::to_bytes`] and [`Poseidon2Spec::from_bytes`] give a
+//! compact binary encoding, using the canonical encoding from `p3_util::canonical_serialization`
+//! preceded by [`POSEIDON2_SPEC_MAGIC`] and [`POSEIDON2_SPEC_VERSION`].
+//!
+//! In human-readable formats, each constant is an integer if it fits in a `u64`, and a string
+//! otherwise. Strings may be in decimal or, with a `0x` prefix, in hexadecimal, as constants of
+//! large fields are usually published.
+//!
+//! The linear layers aren't part of a spec, since they're fixed by the types used for them.
+
+use alloc::format;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+use gcd::Gcd;
+use num_bigint::BigUint;
+use p3_field::PrimeField;
+use p3_util::canonical_serialization::{
+    from_bytes_with_header, to_bytes_with_header, SerializationError,
+};
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Poseidon2, SUPPORTED_WIDTHS};
+
+/// The version of the binary encoding of a [`Poseidon2Spec`].
+pub const POSEIDON2_SPEC_VERSION: u16 = 1;
+
+/// Magic prefix of the binary encoding of a [`Poseidon2Spec`].
+pub const POSEIDON2_SPEC_MAGIC: [u8; 4] = *b"P3P2";
+
+/// The parameters of a `Poseidon2` instance, other than its linear layers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "EncodedSpec", into = "EncodedSpec")]
+pub struct Poseidon2Spec {
+    /// The width of the permutation.
+    pub width: usize,
+    /// The degree of the S-box.
+    pub sbox_degree: u64,
+    /// The number of external rounds, half of which are applied before the internal rounds.
+    pub rounds_f: usize,
+    /// The number of internal rounds.
+    pub rounds_p: usize,
+    /// The `rounds_f` rows of `width` constants of the external rounds.
+    pub external_constants: Vec<Vec<BigUint>>,
+    /// The `rounds_p` constants of the internal rounds.
+    pub internal_constants: Vec<BigUint>,
+}
+
+/// A `Poseidon2Spec` as it's serialized, with constants encoded as described in the module
+/// documentation.
+#[derive(Clone, Serialize, Deserialize)]
+struct EncodedSpec {
+    width: usize,
+    sbox_degree: u64,
+    rounds_f: usize,
+    rounds_p: usize,
+    external_constants: Vec<Vec<Constant>>,
+    internal_constants: Vec<Constant>,
+}
+
+impl From<EncodedSpec> for Poseidon2Spec {
+    fn from(spec: EncodedSpec) -> Self {
+        Self {
+            width: spec.width,
+            sbox_degree: spec.sbox_degree,
+            rounds_f: spec.rounds_f,
+            rounds_p: spec.rounds_p,
+            external_constants: spec
+                .external_constants
+                .into_iter()
+                .map(|row| row.into_iter().map(|c| c.0).collect())
+                .collect(),
+            internal_constants: spec.internal_constants.into_iter().map(|c| c.0).collect(),
+        }
+    }
+}
+
+impl From<Poseidon2Spec> for EncodedSpec {
+    fn from(spec: Poseidon2Spec) -> Self {
+        Self {
+            width: spec.width,
+            sbox_degree: spec.sbox_degree,
+            rounds_f: spec.rounds_f,
+            rounds_p: spec.rounds_p,
+            external_constants: spec
+                .external_constants
+                .into_iter()
+                .map(|row| row.into_iter().map(Constant).collect())
+                .collect(),
+            internal_constants: spec.internal_constants.into_iter().map(Constant).collect(),
+        }
+    }
+}
+
+/// A round constant, serialized as an integer or a string in human-readable formats, and as its
+/// little-endian 32-bit digits otherwise.
+#[derive(Clone)]
+struct Constant(BigUint);
+
+impl Serialize for Constant {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return self.0.to_u32_digits().serialize(serializer);
+        }
+        if self.0.bits() <= 64 {
+            serializer.serialize_u64(low_u64(&self.0))
+        } else {
+            serializer.serialize_str(&format!("{:#x}", self.0))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Constant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return Vec::<u32>::deserialize(deserializer).map(|digits| Self(BigUint::new(digits)));
+        }
+
+        struct ConstantVisitor;
+
+        impl Visitor<'_> for ConstantVisitor {
+            type Value = Constant;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                f.write_str("a non-negative integer, or a decimal or 0x-prefixed hex string")
+            }
+
+            fn visit_u64<E: Error>(self, value: u64) -> Result<Constant, E> {
+                Ok(Constant(value.into()))
+            }
+
+            fn visit_str<E: Error>(self, value: &str) -> Result<Constant, E> {
+                let parsed = match value.strip_prefix("0x") {
+                    Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+                    None => BigUint::parse_bytes(value.as_bytes(), 10),
+                };
+                parsed
+                    .map(Constant)
+                    .ok_or_else(|| E::custom(format!("invalid constant {value:?}")))
+            }
+        }
+
+        deserializer.deserialize_any(ConstantVisitor)
+    }
+}
+
+/// An error from decoding a [`Poseidon2Spec`], or from building a `Poseidon2` from one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Poseidon2SpecError {
+    /// The spec could not be decoded.
+    Serialization(SerializationError),
+    /// `Poseidon2` doesn't support this width.
+    UnsupportedWidth(usize),
+    /// The spec is for another width.
+    WidthMismatch { expected: usize, found: usize },
+    /// The spec is for another S-box degree.
+    SboxDegreeMismatch { expected: u64, found: u64 },
+    /// The S-box degree isn't a permutation of the field.
+    InvalidSboxDegree(u64),
+    /// The number of external rounds is odd.
+    OddExternalRounds(usize),
+    /// The number of rows of external constants isn't `rounds_f`.
+    ExternalRoundCount { expected: usize, found: usize },
+    /// The number of internal constants isn't `rounds_p`.
+    InternalRoundCount { expected: usize, found: usize },
+    /// A row of external constants doesn't have `width` constants.
+    ExternalRoundWidth {
+        round: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A constant isn't the canonical representative of a field element.
+    NonCanonicalConstant(BigUint),
+}
+
+impl Display for Poseidon2SpecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Serialization(err) => write!(f, "invalid spec encoding: {err}"),
+            Self::UnsupportedWidth(width) => write!(f, "width {width} is not supported"),
+            Self::WidthMismatch { expected, found } => {
+                write!(f, "expected width {expected}, found {found}")
+            }
+            Self::SboxDegreeMismatch { expected, found } => {
+                write!(f, "expected S-box degree {expected}, found {found}")
+            }
+            Self::InvalidSboxDegree(d) => write!(f, "x^{d} is not a permutation of the field"),
+            Self::OddExternalRounds(rounds_f) => {
+                write!(f, "the number of external rounds {rounds_f} is odd")
+            }
+            Self::ExternalRoundCount { expected, found } => {
+                write!(f, "expected {expected} external rounds, found {found}")
+            }
+            Self::InternalRoundCount { expected, found } => {
+                write!(f, "expected {expected} internal rounds, found {found}")
+            }
+            Self::ExternalRoundWidth {
+                round,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {expected} constants in external round {round}, found {found}"
+            ),
+            Self::NonCanonicalConstant(c) => {
+                write!(f, "constant {c} is not a canonical field element")
+            }
+        }
+    }
+}
+
+impl From<SerializationError> for Poseidon2SpecError {
+    fn from(err: SerializationError) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+impl Poseidon2Spec {
+    /// Encode the spec in the binary format described in the module documentation.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        to_bytes_with_header(POSEIDON2_SPEC_MAGIC, POSEIDON2_SPEC_VERSION, self)
+    }
+
+    /// Decode a spec encoded by `to_bytes`. The spec isn't validated until it's used.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        from_bytes_with_header(POSEIDON2_SPEC_MAGIC, POSEIDON2_SPEC_VERSION, bytes)
+    }
+}
+
+impl<F, MdsLight, Diffusion, const WIDTH: usize, const D: u64>
+    Poseidon2<F, MdsLight, Diffusion, WIDTH, D>
+where
+    F: PrimeField,
+{
+    /// Create a `Poseidon2` instance with the round constants of `spec`, checking that it is for
+    /// `WIDTH` and `D`, that the number of constants matches the number of rounds, and that all
+    /// constants are canonical.
+    pub fn from_spec(
+        spec: &Poseidon2Spec,
+        external_linear_layer: MdsLight,
+        internal_linear_layer: Diffusion,
+    ) -> Result<Self, Poseidon2SpecError> {
+        if !SUPPORTED_WIDTHS.contains(&WIDTH) {
+            return Err(Poseidon2SpecError::UnsupportedWidth(WIDTH));
+        }
+        if spec.width != WIDTH {
+            return Err(Poseidon2SpecError::WidthMismatch {
+                expected: WIDTH,
+                found: spec.width,
+            });
+        }
+        if spec.sbox_degree != D {
+            return Err(Poseidon2SpecError::SboxDegreeMismatch {
+                expected: D,
+                found: spec.sbox_degree,
+            });
+        }
+        // `gcd(D, p - 1) = gcd(D, (p - 1) mod D)`, which fits in a `u64`.
+        if D == 0 || D.gcd(low_u64(&((F::order() - 1u32) % D))) != 1 {
+            return Err(Poseidon2SpecError::InvalidSboxDegree(D));
+        }
+        if spec.rounds_f % 2 != 0 {
+            return Err(Poseidon2SpecError::OddExternalRounds(spec.rounds_f));
+        }
+        if spec.external_constants.len() != spec.rounds_f {
+            return Err(Poseidon2SpecError::ExternalRoundCount {
+                expected: spec.rounds_f,
+                found: spec.external_constants.len(),
+            });
+        }
+        if spec.internal_constants.len() != spec.rounds_p {
+            return Err(Poseidon2SpecError::InternalRoundCount {
+                expected: spec.rounds_p,
+                found: spec.internal_constants.len(),
+            });
+        }
+
+        let external_constants = spec
+            .external_constants
+            .iter()
+            .enumerate()
+            .map(|(round, row)| {
+                if row.len() != WIDTH {
+                    return Err(Poseidon2SpecError::ExternalRoundWidth {
+                        round,
+                        expected: WIDTH,
+                        found: row.len(),
+                    });
+                }
+                let mut constants = [F::ZERO; WIDTH];
+                for (constant, c) in constants.iter_mut().zip(row) {
+                    *constant = canonical_constant(c)?;
+                }
+                Ok(constants)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let internal_constants = spec
+            .internal_constants
+            .iter()
+            .map(canonical_constant)
+            .collect::<Result<Vec<F>, _>>()?;
+
+        Ok(Self::new(
+            spec.rounds_f,
+            external_constants,
+            external_linear_layer,
+            spec.rounds_p,
+            internal_constants,
+            internal_linear_layer,
+        ))
+    }
+
+    /// The spec of this instance, e.g. to pin the constants of an instance created with
+    /// `new_from_rng_128`.
+    pub fn to_spec(&self) -> Poseidon2Spec {
+        Poseidon2Spec {
+            width: WIDTH,
+            sbox_degree: D,
+            rounds_f: self.rounds_f,
+            rounds_p: self.rounds_p,
+            external_constants: self
+                .external_constants
+                .iter()
+                .map(|row| row.iter().map(|c| c.as_canonical_biguint()).collect())
+                .collect(),
+            internal_constants: self
+                .internal_constants
+                .iter()
+                .map(|c| c.as_canonical_biguint())
+                .collect(),
+        }
+    }
+}
+
+/// The lowest 64 bits of `n`.
+fn low_u64(n: &BigUint) -> u64 {
+    n.iter_u64_digits().next().unwrap_or(0)
+}
+
+/// The field element whose canonical representative is `c`.
+fn canonical_constant<F: PrimeField>(c: &BigUint) -> Result<F, Poseidon2SpecError> {
+    if *c >= F::order() {
+        return Err(Poseidon2SpecError::NonCanonicalConstant(c.clone()));
+    }
+    // Each digit is canonical: either `c` has a single digit, or the order is above `2^32`.
+    let radix = F::from_wrapped_u64(1 << 32);
+    Ok(c.to_u32_digits().iter().rev().fold(F::ZERO, |acc, &digit| {
+        acc * radix + F::from_canonical_u32(digit)
+    }))
+}
@@ -0,0 +1,147 @@
+use num_bigint::BigUint;
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_field::PrimeField64;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral, Poseidon2Spec, Poseidon2SpecError};
+use p3_symmetric::Permutation;
+use p3_util::canonical_serialization::SerializationError;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+type F = BabyBear;
+type Perm = Poseidon2<F, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+
+fn random_perm() -> Perm {
+    Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut StdRng::seed_from_u64(1),
+    )
+}
+
+fn from_spec(spec: &Poseidon2Spec) -> Result<Perm, Poseidon2SpecError> {
+    Perm::from_spec(
+        spec,
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+    )
+}
+
+fn assert_same_permutation(lhs: &Perm, rhs: &Perm) {
+    let input: [F; 16] = StdRng::seed_from_u64(2).gen();
+    assert_eq!(lhs.permute(input), rhs.permute(input));
+}
+
+#[test]
+fn json_round_trip() {
+    let perm = random_perm();
+    let json = serde_json::to_string(&perm.to_spec()).unwrap();
+    let spec: Poseidon2Spec = serde_json::from_str(&json).unwrap();
+    assert_same_permutation(&perm, &from_spec(&spec).unwrap());
+}
+
+#[test]
+fn json_string_constants() {
+    let perm = random_perm();
+    let mut json: serde_json::Value = serde_json::to_value(perm.to_spec()).unwrap();
+    // Constants may also be given as decimal or hex strings.
+    let constants = json["internal_constants"].as_array_mut().unwrap();
+    constants[0] = constants[0].to_string().into();
+    constants[1] = format!("{:#x}", constants[1].as_u64().unwrap()).into();
+    let spec: Poseidon2Spec = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(spec, perm.to_spec());
+
+    json["internal_constants"][2] = "0xg".into();
+    assert!(serde_json::from_value::<Poseidon2Spec>(json).is_err());
+}
+
+#[test]
+fn binary_round_trip() {
+    let perm = random_perm();
+    let bytes = perm.to_spec().to_bytes().unwrap();
+    let spec = Poseidon2Spec::from_bytes(&bytes).unwrap();
+    assert_eq!(spec, perm.to_spec());
+    assert_same_permutation(&perm, &from_spec(&spec).unwrap());
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] ^= 1;
+    assert!(matches!(
+        Poseidon2Spec::from_bytes(&bad_magic),
+        Err(SerializationError::BadMagic { .. })
+    ));
+    assert!(matches!(
+        Poseidon2Spec::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(SerializationError::UnexpectedEof)
+    ));
+}
+
+#[test]
+fn invalid_specs() {
+    let spec = random_perm().to_spec();
+
+    let mut wrong_width = spec.clone();
+    wrong_width.width = 24;
+    assert_eq!(
+        from_spec(&wrong_width).err(),
+        Some(Poseidon2SpecError::WidthMismatch {
+            expected: 16,
+            found: 24
+        })
+    );
+
+    let mut wrong_degree = spec.clone();
+    wrong_degree.sbox_degree = 5;
+    assert_eq!(
+        from_spec(&wrong_degree).err(),
+        Some(Poseidon2SpecError::SboxDegreeMismatch {
+            expected: 7,
+            found: 5
+        })
+    );
+
+    let mut odd_rounds = spec.clone();
+    odd_rounds.rounds_f -= 1;
+    odd_rounds.external_constants.pop();
+    assert_eq!(
+        from_spec(&odd_rounds).err(),
+        Some(Poseidon2SpecError::OddExternalRounds(spec.rounds_f - 1))
+    );
+
+    let mut missing_external = spec.clone();
+    missing_external.external_constants.pop();
+    assert_eq!(
+        from_spec(&missing_external).err(),
+        Some(Poseidon2SpecError::ExternalRoundCount {
+            expected: spec.rounds_f,
+            found: spec.rounds_f - 1
+        })
+    );
+
+    let mut extra_internal = spec.clone();
+    extra_internal.internal_constants.push(BigUint::from(0u32));
+    assert_eq!(
+        from_spec(&extra_internal).err(),
+        Some(Poseidon2SpecError::InternalRoundCount {
+            expected: spec.rounds_p,
+            found: spec.rounds_p + 1
+        })
+    );
+
+    let mut short_row = spec.clone();
+    short_row.external_constants[3].pop();
+    assert_eq!(
+        from_spec(&short_row).err(),
+        Some(Poseidon2SpecError::ExternalRoundWidth {
+            round: 3,
+            expected: 16,
+            found: 15
+        })
+    );
+
+    let p = BigUint::from(F::ORDER_U64);
+    let mut non_canonical = spec;
+    non_canonical.internal_constants[0] = p.clone();
+    assert_eq!(
+        from_spec(&non_canonical).err(),
+        Some(Poseidon2SpecError::NonCanonicalConstant(p))
+    );
+}
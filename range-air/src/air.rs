@@ -0,0 +1,76 @@
+use alloc::vec::Vec;
+
+use p3_air::{Air, AirBuilderWithLookups, BaseAir, PairBuilder};
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::{range_table, RangeCheckBuilder, RANGE_CHECK_BUS};
+
+/// Range checks `num_values` columns against a table of `0..2^bits`.
+///
+/// The table is the preprocessed trace, so the trace has exactly `2^bits` rows. Each row of the
+/// main trace has `num_values` values, each checked with a lookup on `bus`, followed by the
+/// number of times the table's value on that row is looked up. See
+/// `generate_range_check_trace`.
+#[derive(Clone, Copy, Debug)]
+pub struct RangeCheckAir {
+    pub bits: usize,
+    pub num_values: usize,
+    pub bus: usize,
+}
+
+impl RangeCheckAir {
+    pub const fn new(bits: usize, num_values: usize, bus: usize) -> Self {
+        assert!(
+            num_values > 0,
+            "a range check needs at least one value column"
+        );
+        Self {
+            bits,
+            num_values,
+            bus,
+        }
+    }
+
+    /// Checks that values fit in a byte, on `RANGE_CHECK_BUS`.
+    pub const fn u8(num_values: usize) -> Self {
+        Self::new(8, num_values, RANGE_CHECK_BUS)
+    }
+
+    /// Checks that values fit in 16 bits, on `RANGE_CHECK_BUS`.
+    pub const fn u16(num_values: usize) -> Self {
+        Self::new(16, num_values, RANGE_CHECK_BUS)
+    }
+
+    /// The height of the trace, i.e. the size of the table.
+    pub const fn height(&self) -> usize {
+        1 << self.bits
+    }
+
+    /// The column of the main trace with the multiplicities of the table.
+    pub const fn multiplicity_column(&self) -> usize {
+        self.num_values
+    }
+}
+
+impl<F: Field> BaseAir<F> for RangeCheckAir {
+    fn width(&self) -> usize {
+        self.num_values + 1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(range_table(self.bits))
+    }
+}
+
+impl<AB: PairBuilder + AirBuilderWithLookups> Air<AB> for RangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let entry = builder.preprocessed().row_slice(0)[0];
+        let main = builder.main();
+        let local: Vec<AB::Var> = main.row_slice(0).to_vec();
+
+        builder.range_check_all(self.bus, local[..self.num_values].iter().copied());
+        builder.range_check_table(self.bus, entry, local[self.multiplicity_column()]);
+    }
+}
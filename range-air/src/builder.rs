@@ -0,0 +1,36 @@
+use p3_air::AirBuilderWithLookups;
+use p3_field::AbstractField;
+
+/// Lookups into a range-check table such as `RangeCheckAir`'s, for any AIR with lookups.
+///
+/// Lookups only balance within an AIR, so the table must be part of the same AIR as the values it
+/// checks, with a receive from `range_check_table` on each of its rows.
+pub trait RangeCheckBuilder: AirBuilderWithLookups {
+    /// Assert that `value` is in the range of the table on `bus`.
+    fn range_check<I: Into<Self::Expr>>(&mut self, bus: usize, value: I) {
+        self.send(bus, [value], Self::Expr::ONE);
+    }
+
+    /// Assert that each of `values` is in the range of the table on `bus`.
+    fn range_check_all<I>(&mut self, bus: usize, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<Self::Expr>,
+    {
+        for value in values {
+            self.range_check(bus, value);
+        }
+    }
+
+    /// Provide the row of the table on `bus` with the value `entry`, which is looked up
+    /// `multiplicity` times, as counted by `RangeCheckCounts`.
+    fn range_check_table<I, M>(&mut self, bus: usize, entry: I, multiplicity: M)
+    where
+        I: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        self.receive(bus, [entry], multiplicity);
+    }
+}
+
+impl<AB: AirBuilderWithLookups> RangeCheckBuilder for AB {}
@@ -0,0 +1,86 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+
+use crate::RangeCheckAir;
+
+/// The table of `0..2^bits`, as a single column.
+pub fn range_table<F: Field>(bits: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new_col((0..1 << bits).map(F::from_canonical_usize).collect())
+}
+
+/// The number of times each value of a range-check table of `0..2^bits` is looked up, for the
+/// multiplicities a table receives with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeCheckCounts {
+    counts: Vec<u32>,
+}
+
+impl RangeCheckCounts {
+    pub fn new(bits: usize) -> Self {
+        Self {
+            counts: vec![0; 1 << bits],
+        }
+    }
+
+    /// Count a lookup of `value`, which must be in the range of the table.
+    pub fn add(&mut self, value: u32) {
+        let len = self.counts.len();
+        let count = self
+            .counts
+            .get_mut(value as usize)
+            .unwrap_or_else(|| panic!("{value} is out of the range 0..{len}"));
+        *count += 1;
+    }
+
+    pub fn extend<I: IntoIterator<Item = u32>>(&mut self, values: I) {
+        for value in values {
+            self.add(value);
+        }
+    }
+
+    pub fn count(&self, value: u32) -> u32 {
+        self.counts[value as usize]
+    }
+
+    /// The multiplicity of each row of the table.
+    pub fn multiplicities<F: Field>(&self) -> Vec<F> {
+        self.counts
+            .iter()
+            .map(|&count| F::from_canonical_u32(count))
+            .collect()
+    }
+}
+
+/// The main trace of `air` checking `values`, in row-major order. The rows after the last value
+/// are filled with zeros, which are also checked.
+pub fn generate_range_check_trace<F: Field>(
+    air: &RangeCheckAir,
+    values: &[u32],
+) -> RowMajorMatrix<F> {
+    let height = air.height();
+    let num_values = air.num_values;
+    assert!(
+        values.len() <= height * num_values,
+        "{} values don't fit in {height} rows of {num_values}",
+        values.len()
+    );
+
+    let mut padded = values.to_vec();
+    padded.resize(height * num_values, 0);
+    let mut counts = RangeCheckCounts::new(air.bits);
+    counts.extend(padded.iter().copied());
+
+    let width = num_values + 1;
+    let mut trace = Vec::with_capacity(height * width);
+    for (row, multiplicity) in padded
+        .chunks_exact(num_values)
+        .zip(counts.multiplicities::<F>())
+    {
+        trace.extend(row.iter().map(|&v| F::from_canonical_u32(v)));
+        trace.push(multiplicity);
+    }
+    RowMajorMatrix::new(trace, width)
+}
@@ -0,0 +1,17 @@
+//! An AIR for range checks against a table of `0..2^bits`, with lookups into it, and helpers for
+//! other AIRs to emit the same lookups.
+
+#![no_std]
+
+extern crate alloc;
+
+mod air;
+mod builder;
+mod generation;
+
+pub use air::*;
+pub use builder::*;
+pub use generation::*;
+
+/// The lookup bus `RangeCheckAir::u8` and `RangeCheckAir::u16` use.
+pub const RANGE_CHECK_BUS: usize = 0;
@@ -0,0 +1,108 @@
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_range_air::{generate_range_check_trace, RangeCheckAir, RangeCheckCounts};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove_with_preprocessed, setup_preprocessed, verify_with_preprocessed, StarkConfig,
+};
+use rand::{thread_rng, Rng};
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn prove_and_verify(air: &RangeCheckAir, trace: RowMajorMatrix<Val>) {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+    let preprocessed = setup_preprocessed(&config, air).unwrap();
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_preprocessed(
+        &config,
+        air,
+        &mut challenger,
+        trace,
+        &vec![],
+        Some(&preprocessed),
+    );
+
+    let mut challenger = Challenger::new(perm);
+    let vk = preprocessed.verifier_key();
+    verify_with_preprocessed(&config, air, &mut challenger, &proof, &vec![], Some(&vk))
+        .expect("verification failed");
+}
+
+#[test]
+fn test_u8_range_check() {
+    let air = RangeCheckAir::u8(2);
+    let mut rng = thread_rng();
+    // Fewer values than fit, so the last rows are padding.
+    let values: Vec<u32> = (0..400).map(|_| rng.gen_range(0..1 << 8)).collect();
+    let trace = generate_range_check_trace(&air, &values);
+    assert_eq!((trace.width(), trace.height()), (3, 256));
+    prove_and_verify(&air, trace);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "the lookups aren't balanced")]
+fn test_out_of_range_value() {
+    let air = RangeCheckAir::u8(1);
+    let mut trace = generate_range_check_trace::<Val>(&air, &[1, 2, 3]);
+    // 256 isn't in the table, whatever the multiplicities claim.
+    trace.values[0] = Val::from_canonical_u32(256);
+    prove_and_verify(&air, trace);
+}
+
+#[test]
+fn test_counts() {
+    let mut counts = RangeCheckCounts::new(8);
+    counts.extend([0, 5, 5, 255]);
+    assert_eq!(
+        (counts.count(0), counts.count(5), counts.count(255)),
+        (1, 2, 1)
+    );
+    assert_eq!(counts.multiplicities::<Val>()[5], Val::TWO);
+}
+
+#[test]
+#[should_panic(expected = "256 is out of the range 0..256")]
+fn test_counts_out_of_range() {
+    RangeCheckCounts::new(8).add(256);
+}
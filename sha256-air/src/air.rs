@@ -0,0 +1,304 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use p3_air::{AirBuilder, AirBuilderWithLookups, BaseSubAir, SubAir, SubBuilder};
+use p3_bitwise_air::BitwiseBuilder;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+
+use crate::columns::{num_cols, AddCols, Sha256Cols, SigmaCols};
+use crate::constants::{SigmaTerm, BIG_SIGMA0, BIG_SIGMA1, K, SMALL_SIGMA0, SMALL_SIGMA1};
+use crate::NUM_ROUNDS;
+
+/// A sub-AIR for the SHA-256 compression function, on words of `LIMBS` limbs of `32 / LIMBS`
+/// bits, whose bitwise operations are looked up on `bitwise_bus`.
+///
+/// Lookups only balance within an AIR, so a bitwise table on limbs of `32 / LIMBS` bits, such as
+/// that of `BitwiseAir`, must be a piece of the same AIR, where other chips can share it. `LIMBS`
+/// must be 4 or 8, i.e. the limbs are bytes, with a table of `2^16` rows, or 4 bits, with a table
+/// of `2^8` rows.
+///
+/// Each compression takes `NUM_ROUNDS` rows, so the height of the trace must be a multiple of
+/// `NUM_ROUNDS`. With `with_bus`, it also receives each exported compression from a bus, so that
+/// other pieces of the AIR can use it with `Sha256Builder`. See `generate_sha256_trace`.
+#[derive(Clone, Copy, Debug)]
+pub struct Sha256Air<const LIMBS: usize> {
+    pub bitwise_bus: usize,
+    bus: Option<usize>,
+}
+
+impl<const LIMBS: usize> Sha256Air<LIMBS> {
+    pub const fn new(bitwise_bus: usize) -> Self {
+        assert!(
+            LIMBS == 4 || LIMBS == 8,
+            "words must be split into 4 or 8 limbs"
+        );
+        Self {
+            bitwise_bus,
+            bus: None,
+        }
+    }
+
+    /// Receive each exported compression from `bus`, as the 16-bit limbs of its chaining value,
+    /// block and output, in the order of `Sha256Builder::sha256_compress`.
+    pub const fn with_bus(mut self, bus: usize) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// The number of bits of each limb, which is also that of the bitwise table.
+    pub const fn limb_bits(&self) -> usize {
+        32 / LIMBS
+    }
+}
+
+impl<const LIMBS: usize> BaseSubAir for Sha256Air<LIMBS> {
+    fn width(&self) -> usize {
+        num_cols::<LIMBS>()
+    }
+}
+
+impl<AB: AirBuilderWithLookups, const LIMBS: usize> SubAir<AB> for Sha256Air<LIMBS> {
+    fn eval(&self, builder: &mut SubBuilder<'_, AB>) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let local: &Sha256Cols<AB::Var, LIMBS> = (*local).borrow();
+        let next: &Sha256Cols<AB::Var, LIMBS> = (*next).borrow();
+        let bus = self.bitwise_bus;
+
+        // Initially, the first step flag should be 1 while the others should be 0.
+        builder.when_first_row().assert_one(local.step_flags[0]);
+        for i in 1..NUM_ROUNDS {
+            builder.when_first_row().assert_zero(local.step_flags[i]);
+        }
+        for i in 0..NUM_ROUNDS {
+            builder
+                .when_transition()
+                .assert_eq(next.step_flags[(i + 1) % NUM_ROUNDS], local.step_flags[i]);
+        }
+
+        let first_step = local.step_flags[0];
+        let final_step = local.step_flags[NUM_ROUNDS - 1];
+        let not_final_step = AB::Expr::ONE - final_step;
+
+        // The export flag must be 0 or 1, and 0 except on the final step.
+        builder.assert_bool(local.export);
+        builder
+            .when(not_final_step.clone())
+            .assert_zero(local.export);
+
+        // The compression starts from the chaining value, with the block as its message schedule.
+        let mut when_first_step = builder.when(first_step);
+        for (&var, &state) in local
+            .vars
+            .iter()
+            .flatten()
+            .zip(local.state_in.iter().flatten())
+        {
+            when_first_step.assert_eq(var, state);
+        }
+        for (w, block) in local.w.iter().zip(&local.block) {
+            for (half, &block_half) in halves::<_, AB::Expr, LIMBS>(w).into_iter().zip(block) {
+                when_first_step.assert_eq(half, block_half);
+            }
+        }
+
+        // The message schedule.
+        let small_sigma0 = eval_sigma(builder, bus, &local.w[1], &local.small_sigma0, SMALL_SIGMA0);
+        let small_sigma1 = eval_sigma(
+            builder,
+            bus,
+            &local.w[14],
+            &local.small_sigma1,
+            SMALL_SIGMA1,
+        );
+        let w_terms = [
+            small_sigma1,
+            word(&local.w[9]),
+            small_sigma0,
+            word(&local.w[0]),
+        ];
+        eval_add(builder, bus, &w_terms, &local.w_next);
+
+        // The round.
+        let [a, b, c, d, e, f, g, h] = &local.vars;
+        let big_sigma0 = eval_sigma(builder, bus, a, &local.big_sigma0, BIG_SIGMA0);
+        let big_sigma1 = eval_sigma(builder, bus, e, &local.big_sigma1, BIG_SIGMA1);
+        let mask = AB::Expr::from_canonical_u32((1 << self.limb_bits()) - 1);
+        for i in 0..LIMBS {
+            builder.and(bus, e[i], f[i], local.e_and_f[i]);
+            builder.and(bus, mask.clone() - e[i], g[i], local.not_e_and_g[i]);
+            builder.and(bus, a[i], b[i], local.a_and_b[i]);
+            builder.xor(bus, a[i], b[i], local.a_xor_b[i]);
+            builder.and(bus, c[i], local.a_xor_b[i], local.c_and_a_xor_b[i]);
+        }
+        let k: [AB::Expr; LIMBS] = core::array::from_fn(|i| {
+            local
+                .step_flags
+                .iter()
+                .zip(K)
+                .map(|(&flag, k)| flag * AB::F::from_canonical_u32(limb::<LIMBS>(k, i)))
+                .sum()
+        });
+
+        let mut t1 = vec![
+            word(h),
+            big_sigma1,
+            word(&local.e_and_f),
+            word(&local.not_e_and_g),
+            k,
+            word(&local.w[0]),
+        ];
+        let a_terms: Vec<_> = t1
+            .iter()
+            .cloned()
+            .chain([big_sigma0, word(&local.a_and_b), word(&local.c_and_a_xor_b)])
+            .collect();
+        eval_add(builder, bus, &a_terms, &local.a_next);
+        t1.push(word(d));
+        eval_add(builder, bus, &t1, &local.e_next);
+
+        // The words which are only added up, and not otherwise looked up. Every word of the
+        // chaining value is `d` or `h` on one of the first four steps, and every word of the
+        // block is `W[t]` on one of the first 16, so with the lookups of the other working
+        // variables, all the inputs are range checked.
+        for input in [&local.w[0], d, h] {
+            for &limb in input {
+                range_check(builder, bus, limb);
+            }
+        }
+
+        // The next step continues the compression, except after the final step. Since the height
+        // of the trace is a multiple of `NUM_ROUNDS`, the last row is a final step, so this
+        // doesn't need to be restricted to transitions.
+        let vars_after = [&local.a_next.output, a, b, c, &local.e_next.output, e, f, g];
+        let mut when_same_compression = builder.when(not_final_step);
+        for (next_var, var) in next.vars.iter().zip(vars_after) {
+            for (&next_limb, &limb) in next_var.iter().zip(var) {
+                when_same_compression.assert_eq(next_limb, limb);
+            }
+        }
+        let w_after = local.w[1..].iter().chain([&local.w_next.output]);
+        for (next_w, w) in next.w.iter().zip(w_after) {
+            for (&next_limb, &limb) in next_w.iter().zip(w) {
+                when_same_compression.assert_eq(next_limb, limb);
+            }
+        }
+        for (&next_state, &state) in next
+            .state_in
+            .iter()
+            .flatten()
+            .zip(local.state_in.iter().flatten())
+        {
+            when_same_compression.assert_eq(next_state, state);
+        }
+        for (&next_half, &half) in next
+            .block
+            .iter()
+            .flatten()
+            .zip(local.block.iter().flatten())
+        {
+            when_same_compression.assert_eq(next_half, half);
+        }
+
+        // The output, which is only meaningful on the final step.
+        for ((state, var), out) in local.state_in.iter().zip(vars_after).zip(&local.state_out) {
+            eval_add(builder, bus, &[word(state), word(var)], out);
+        }
+
+        if let Some(export_bus) = self.bus {
+            let state_in = local.state_in.iter().flat_map(halves::<_, AB::Expr, LIMBS>);
+            let block = local.block.iter().flatten().map(|&half| half.into());
+            let state_out = local
+                .state_out
+                .iter()
+                .flat_map(|out| halves::<_, AB::Expr, LIMBS>(&out.output));
+            builder.receive(
+                export_bus,
+                state_in.chain(block).chain(state_out),
+                local.export,
+            );
+        }
+    }
+}
+
+/// The `i`th limb of `word`, split into `LIMBS` limbs.
+pub(crate) const fn limb<const LIMBS: usize>(word: u32, i: usize) -> u32 {
+    let bits = 32 / LIMBS;
+    (word >> (bits * i)) & ((1 << bits) - 1)
+}
+
+fn word<Var, Expr, const LIMBS: usize>(limbs: &[Var; LIMBS]) -> [Expr; LIMBS]
+where
+    Var: Into<Expr> + Copy,
+{
+    limbs.map(Into::into)
+}
+
+/// The 16-bit halves of the word given by `limbs`, least significant first.
+fn halves<Var, Expr, const LIMBS: usize>(limbs: &[Var; LIMBS]) -> [Expr; 2]
+where
+    Var: Into<Expr> + Copy,
+    Expr: AbstractField,
+{
+    let base = Expr::from_canonical_u32(1 << (32 / LIMBS));
+    core::array::from_fn(|half| {
+        limbs[half * LIMBS / 2..(half + 1) * LIMBS / 2]
+            .iter()
+            .rev()
+            .fold(Expr::ZERO, |acc, &limb| acc * base.clone() + limb.into())
+    })
+}
+
+/// Check that `limb` is a limb of the bitwise table on `bus`.
+fn range_check<AB: AirBuilderWithLookups>(builder: &mut AB, bus: usize, limb: AB::Var) {
+    builder.xor(bus, limb, AB::Expr::ZERO, limb);
+}
+
+/// Assert that `cols` is the XOR of `terms` of `input`, and return its limbs.
+fn eval_sigma<AB: AirBuilderWithLookups, const LIMBS: usize>(
+    builder: &mut AB,
+    bus: usize,
+    input: &[AB::Var; LIMBS],
+    cols: &SigmaCols<AB::Var, LIMBS>,
+    terms: [SigmaTerm; 3],
+) -> [AB::Expr; LIMBS] {
+    let bits = 32 / LIMBS;
+    let terms: Vec<Vec<AB::Expr>> = terms
+        .iter()
+        .zip(&cols.witnesses)
+        .map(|(&term, witnesses)| match term {
+            SigmaTerm::Rotr(r) => {
+                builder.rotate_left(bus, bits, &input[..], &witnesses[..], 32 - r)
+            }
+            SigmaTerm::Shr(s) => builder.shift_right(bus, bits, &input[..], &witnesses[..], s),
+        })
+        .collect();
+    for (i, (&xor, &output)) in cols.xor.iter().zip(&cols.output).enumerate() {
+        builder.xor(bus, terms[0][i].clone(), terms[1][i].clone(), xor);
+        builder.xor(bus, xor, terms[2][i].clone(), output);
+    }
+    word(&cols.output)
+}
+
+/// Assert that `cols` is the sum of `terms` modulo `2^32`, and range check its limbs and carries.
+fn eval_add<AB: AirBuilderWithLookups, const LIMBS: usize>(
+    builder: &mut AB,
+    bus: usize,
+    terms: &[[AB::Expr; LIMBS]],
+    cols: &AddCols<AB::Var, LIMBS>,
+) {
+    let base = AB::F::from_canonical_u32(1 << (32 / LIMBS));
+    for i in 0..LIMBS {
+        let carry_in = if i == 0 {
+            AB::Expr::ZERO
+        } else {
+            cols.carries[i - 1].into()
+        };
+        let sum = terms.iter().map(|term| term[i].clone()).sum::<AB::Expr>() + carry_in;
+        builder.assert_eq(sum, cols.carries[i] * base + cols.output[i]);
+        range_check(builder, bus, cols.output[i]);
+        range_check(builder, bus, cols.carries[i]);
+    }
+}
@@ -0,0 +1,34 @@
+use p3_air::AirBuilderWithLookups;
+
+/// Compressions checked by a `Sha256Air`, for any AIR with lookups.
+///
+/// Lookups only balance within an AIR, so the `Sha256Air` must be a sub-AIR of the same AIR, with
+/// `Sha256Air::with_bus(bus)`. Each compression it exports is received once, so a compression used
+/// twice needs to be proven twice.
+pub trait Sha256Builder: AirBuilderWithLookups {
+    /// Assert that `output` is the SHA-256 compression of `block` from the chaining value `state`,
+    /// `multiplicity` times, where each word is given by its 16-bit halves, least significant
+    /// first, as in `word_halves`.
+    fn sha256_compress<S, B, O, M>(
+        &mut self,
+        bus: usize,
+        state: S,
+        block: B,
+        output: O,
+        multiplicity: M,
+    ) where
+        S: IntoIterator,
+        S::Item: Into<Self::Expr>,
+        B: IntoIterator<Item = S::Item>,
+        O: IntoIterator<Item = S::Item>,
+        M: Into<Self::Expr>,
+    {
+        self.send(
+            bus,
+            state.into_iter().chain(block).chain(output),
+            multiplicity,
+        );
+    }
+}
+
+impl<AB: AirBuilderWithLookups> Sha256Builder for AB {}
@@ -0,0 +1,132 @@
+use core::borrow::{Borrow, BorrowMut};
+use core::mem::size_of;
+
+use crate::NUM_ROUNDS;
+
+/// The columns of a round of the SHA-256 compression function, where each 32-bit word is split
+/// into `LIMBS` limbs of `32 / LIMBS` bits, least significant first.
+///
+/// Round `t` of a compression is on row `t`. The values computed from the working variables and
+/// the message schedule of the round are checked with lookups into a bitwise table on limbs of
+/// `32 / LIMBS` bits, which also range checks the limbs of the words added up.
+#[derive(Debug)]
+#[repr(C)]
+pub struct Sha256Cols<T, const LIMBS: usize> {
+    /// The `i`th value is set to 1 if we are in the `i`th round, otherwise 0.
+    pub step_flags: [T; NUM_ROUNDS],
+
+    /// 1 on the last round of a compression whose input and output are exported to other chips,
+    /// otherwise 0.
+    pub export: T,
+
+    /// The chaining value the compression starts from, the same on every round.
+    pub state_in: [[T; LIMBS]; 8],
+
+    /// The message block, as the 16-bit halves of each word, least significant first, the same
+    /// on every round.
+    pub block: [[T; 2]; 16],
+
+    /// The working variables `a, ..., h` at the start of the round.
+    pub vars: [[T; LIMBS]; 8],
+
+    /// The message schedule `W[t], ..., W[t + 15]` of round `t`.
+    pub w: [[T; LIMBS]; 16],
+
+    /// `σ0(W[t + 1])`.
+    pub small_sigma0: SigmaCols<T, LIMBS>,
+
+    /// `σ1(W[t + 14])`.
+    pub small_sigma1: SigmaCols<T, LIMBS>,
+
+    /// ```ignore
+    /// W[t + 16] = σ1(W[t + 14]) + W[t + 9] + σ0(W[t + 1]) + W[t]
+    /// ```
+    pub w_next: AddCols<T, LIMBS>,
+
+    /// `Σ0(a)`.
+    pub big_sigma0: SigmaCols<T, LIMBS>,
+
+    /// `Σ1(e)`.
+    pub big_sigma1: SigmaCols<T, LIMBS>,
+
+    /// `e & f` and `!e & g`, which have no bits in common, so
+    /// ```ignore
+    /// Ch(e, f, g) = (e & f) ^ (!e & g) = (e & f) + (!e & g)
+    /// ```
+    pub e_and_f: [T; LIMBS],
+    pub not_e_and_g: [T; LIMBS],
+
+    /// `a & b`, `a ^ b` and `c & (a ^ b)`, where the first and the last have no bits in common, so
+    /// ```ignore
+    /// Maj(a, b, c) = (a & b) ^ (a & c) ^ (b & c) = (a & b) + (c & (a ^ b))
+    /// ```
+    pub a_and_b: [T; LIMBS],
+    pub a_xor_b: [T; LIMBS],
+    pub c_and_a_xor_b: [T; LIMBS],
+
+    /// ```ignore
+    /// a' = h + Σ1(e) + Ch(e, f, g) + K[t] + W[t] + Σ0(a) + Maj(a, b, c)
+    /// ```
+    pub a_next: AddCols<T, LIMBS>,
+
+    /// ```ignore
+    /// e' = d + h + Σ1(e) + Ch(e, f, g) + K[t] + W[t]
+    /// ```
+    pub e_next: AddCols<T, LIMBS>,
+
+    /// The chaining value plus the working variables after the round, which is the output of the
+    /// compression on its last round.
+    pub state_out: [AddCols<T, LIMBS>; 8],
+}
+
+/// The columns of one of the σ or Σ functions of SHA-256, which XOR three rotations or shifts of
+/// a word.
+#[derive(Debug)]
+#[repr(C)]
+pub struct SigmaCols<T, const LIMBS: usize> {
+    /// The witnesses of each term: the shifted limbs of `BitwiseBuilder::rotate_left` for a
+    /// rotation, or the low bits of `BitwiseBuilder::shift_right` for a shift.
+    pub witnesses: [[T; LIMBS]; 3],
+
+    /// The XOR of the first two terms.
+    pub xor: [T; LIMBS],
+
+    /// The XOR of all three terms.
+    pub output: [T; LIMBS],
+}
+
+/// The columns of a sum of words modulo `2^32`, which is computed limb by limb.
+#[derive(Debug)]
+#[repr(C)]
+pub struct AddCols<T, const LIMBS: usize> {
+    pub output: [T; LIMBS],
+
+    /// The carry out of each limb of the sum, into the next limb.
+    pub carries: [T; LIMBS],
+}
+
+pub const fn num_cols<const LIMBS: usize>() -> usize {
+    size_of::<Sha256Cols<u8, LIMBS>>()
+}
+
+impl<T, const LIMBS: usize> Borrow<Sha256Cols<T, LIMBS>> for [T] {
+    fn borrow(&self) -> &Sha256Cols<T, LIMBS> {
+        debug_assert_eq!(self.len(), num_cols::<LIMBS>());
+        let (prefix, shorts, suffix) = unsafe { self.align_to::<Sha256Cols<T, LIMBS>>() };
+        debug_assert!(prefix.is_empty(), "Alignment should match");
+        debug_assert!(suffix.is_empty(), "Alignment should match");
+        debug_assert_eq!(shorts.len(), 1);
+        &shorts[0]
+    }
+}
+
+impl<T, const LIMBS: usize> BorrowMut<Sha256Cols<T, LIMBS>> for [T] {
+    fn borrow_mut(&mut self) -> &mut Sha256Cols<T, LIMBS> {
+        debug_assert_eq!(self.len(), num_cols::<LIMBS>());
+        let (prefix, shorts, suffix) = unsafe { self.align_to_mut::<Sha256Cols<T, LIMBS>>() };
+        debug_assert!(prefix.is_empty(), "Alignment should match");
+        debug_assert!(suffix.is_empty(), "Alignment should match");
+        debug_assert_eq!(shorts.len(), 1);
+        &mut shorts[0]
+    }
+}
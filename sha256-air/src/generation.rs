@@ -0,0 +1,217 @@
+use alloc::vec::Vec;
+
+use p3_bitwise_air::{rotate_limbs_left, shift_limbs_right, BitwiseCounts, BitwiseOp};
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use tracing::instrument;
+
+use crate::air::limb;
+use crate::columns::{num_cols, AddCols, Sha256Cols, SigmaCols};
+use crate::constants::{SigmaTerm, BIG_SIGMA0, BIG_SIGMA1, K, SMALL_SIGMA0, SMALL_SIGMA1};
+use crate::{Sha256Air, NUM_ROUNDS};
+
+/// The trace of `air` for the compressions of `inputs`, each a chaining value and a block, on
+/// `height` rows, which must be a multiple of `NUM_ROUNDS`. Each compression is exported on its
+/// last row. The rows after the compressions are padding: compressions of a zero block from a zero
+/// chaining value, which aren't exported.
+///
+/// The lookups into the bitwise table are counted in `counts`, which must be for limbs of
+/// `air.limb_bits()` bits, and which the other chips sharing the table can add theirs to.
+#[instrument(name = "generate SHA-256 trace", skip_all)]
+pub fn generate_sha256_trace<F: Field, const LIMBS: usize>(
+    air: &Sha256Air<LIMBS>,
+    inputs: &[([u32; 8], [u32; 16])],
+    height: usize,
+    counts: &mut BitwiseCounts,
+) -> RowMajorMatrix<F> {
+    assert_eq!(
+        height % NUM_ROUNDS,
+        0,
+        "the height must be a multiple of {NUM_ROUNDS}"
+    );
+    assert!(
+        inputs.len() * NUM_ROUNDS <= height,
+        "{} compressions don't fit in {height} rows",
+        inputs.len()
+    );
+
+    let ncols = num_cols::<LIMBS>();
+    let mut trace = RowMajorMatrix::new(F::zero_vec(height * ncols), ncols);
+    let (prefix, rows, suffix) = unsafe { trace.values.align_to_mut::<Sha256Cols<F, LIMBS>>() };
+    assert!(prefix.is_empty(), "Alignment should match");
+    assert!(suffix.is_empty(), "Alignment should match");
+    assert_eq!(rows.len(), height);
+
+    let mut generator = RowGenerator {
+        bits: air.limb_bits(),
+        counts,
+    };
+    for (i, rows) in rows.chunks_exact_mut(NUM_ROUNDS).enumerate() {
+        match inputs.get(i) {
+            Some(&(state, block)) => generator.compression(rows, state, block, true),
+            None => generator.compression(rows, [0; 8], [0; 16], false),
+        }
+    }
+
+    trace
+}
+
+/// Fills in the rows of compressions, counting their lookups into the bitwise table.
+struct RowGenerator<'a> {
+    bits: usize,
+    counts: &'a mut BitwiseCounts,
+}
+
+impl RowGenerator<'_> {
+    fn compression<F: Field, const LIMBS: usize>(
+        &mut self,
+        rows: &mut [Sha256Cols<F, LIMBS>],
+        state: [u32; 8],
+        block: [u32; 16],
+        export: bool,
+    ) {
+        let mut w = block.to_vec();
+        let mut vars = state;
+
+        for (t, row) in rows.iter_mut().enumerate() {
+            row.step_flags[t] = F::ONE;
+            row.export = F::from_bool(export && t == NUM_ROUNDS - 1);
+            row.state_in = state.map(field_limbs);
+            row.block = block.map(|word| [word & 0xFFFF, word >> 16].map(F::from_canonical_u32));
+            row.vars = vars.map(field_limbs);
+            row.w = core::array::from_fn(|k| field_limbs(w[t + k]));
+
+            // The message schedule.
+            let small_sigma0 = self.sigma(&mut row.small_sigma0, w[t + 1], SMALL_SIGMA0);
+            let small_sigma1 = self.sigma(&mut row.small_sigma1, w[t + 14], SMALL_SIGMA1);
+            let w_next = self.add(
+                &mut row.w_next,
+                &[small_sigma1, w[t + 9], small_sigma0, w[t]],
+            );
+            w.push(w_next);
+
+            // The round.
+            let [a, b, c, d, e, f, g, h] = vars;
+            let big_sigma0 = self.sigma(&mut row.big_sigma0, a, BIG_SIGMA0);
+            let big_sigma1 = self.sigma(&mut row.big_sigma1, e, BIG_SIGMA1);
+            let e_and_f = self.bitwise(&mut row.e_and_f, BitwiseOp::And, e, f);
+            let not_e_and_g = self.bitwise(&mut row.not_e_and_g, BitwiseOp::And, !e, g);
+            let a_and_b = self.bitwise(&mut row.a_and_b, BitwiseOp::And, a, b);
+            let a_xor_b = self.bitwise(&mut row.a_xor_b, BitwiseOp::Xor, a, b);
+            let c_and_a_xor_b = self.bitwise(&mut row.c_and_a_xor_b, BitwiseOp::And, c, a_xor_b);
+
+            let t1 = [h, big_sigma1, e_and_f, not_e_and_g, K[t], w[t]];
+            let a_next = self.add(
+                &mut row.a_next,
+                &[&t1[..], &[big_sigma0, a_and_b, c_and_a_xor_b]].concat(),
+            );
+            let e_next = self.add(&mut row.e_next, &[&t1[..], &[d]].concat());
+
+            for word in [w[t], d, h] {
+                self.range_check::<LIMBS>(word);
+            }
+
+            vars = [a_next, a, b, c, e_next, e, f, g];
+            for ((out, state_word), var) in row.state_out.iter_mut().zip(state).zip(vars) {
+                self.add(out, &[state_word, var]);
+            }
+        }
+    }
+
+    /// Fill in `cols` for the XOR of `terms` of `input`, and return it.
+    fn sigma<F: Field, const LIMBS: usize>(
+        &mut self,
+        cols: &mut SigmaCols<F, LIMBS>,
+        input: u32,
+        terms: [SigmaTerm; 3],
+    ) -> u32 {
+        let input = limbs::<LIMBS>(input);
+        let terms: Vec<Vec<u32>> = terms
+            .iter()
+            .zip(&mut cols.witnesses)
+            .map(|(&term, witnesses)| {
+                let (witness, output) = match term {
+                    SigmaTerm::Rotr(r) => (
+                        self.counts.rotate_left(&input, 32 - r),
+                        rotate_limbs_left(&input, self.bits, 32 - r),
+                    ),
+                    SigmaTerm::Shr(s) => (
+                        self.counts.shift_right(&input, s),
+                        shift_limbs_right(&input, self.bits, s),
+                    ),
+                };
+                for (col, witness) in witnesses.iter_mut().zip(witness) {
+                    *col = F::from_canonical_u32(witness);
+                }
+                output
+            })
+            .collect();
+
+        let xor: [u32; LIMBS] =
+            core::array::from_fn(|i| self.counts.add(BitwiseOp::Xor, terms[0][i], terms[1][i]));
+        let output: [u32; LIMBS] =
+            core::array::from_fn(|i| self.counts.add(BitwiseOp::Xor, xor[i], terms[2][i]));
+        cols.xor = xor.map(F::from_canonical_u32);
+        cols.output = output.map(F::from_canonical_u32);
+        self.word(&output)
+    }
+
+    /// Fill in `cols` for `op` on `a` and `b`, and return it.
+    fn bitwise<F: Field, const LIMBS: usize>(
+        &mut self,
+        cols: &mut [F; LIMBS],
+        op: BitwiseOp,
+        a: u32,
+        b: u32,
+    ) -> u32 {
+        let (a, b) = (limbs::<LIMBS>(a), limbs::<LIMBS>(b));
+        let output: [u32; LIMBS] = core::array::from_fn(|i| self.counts.add(op, a[i], b[i]));
+        *cols = output.map(F::from_canonical_u32);
+        self.word(&output)
+    }
+
+    /// Fill in `cols` for the sum of `terms` modulo `2^32`, and return it.
+    fn add<F: Field, const LIMBS: usize>(
+        &mut self,
+        cols: &mut AddCols<F, LIMBS>,
+        terms: &[u32],
+    ) -> u32 {
+        let mask = (1 << self.bits) - 1;
+        let mut carry = 0;
+        for i in 0..LIMBS {
+            let sum = terms
+                .iter()
+                .map(|&term| limb::<LIMBS>(term, i))
+                .sum::<u32>()
+                + carry;
+            let output = sum & mask;
+            carry = sum >> self.bits;
+            self.counts.add(BitwiseOp::Xor, output, 0);
+            self.counts.add(BitwiseOp::Xor, carry, 0);
+            cols.output[i] = F::from_canonical_u32(output);
+            cols.carries[i] = F::from_canonical_u32(carry);
+        }
+        terms.iter().fold(0, |acc, &term| acc.wrapping_add(term))
+    }
+
+    fn range_check<const LIMBS: usize>(&mut self, word: u32) {
+        for limb in limbs::<LIMBS>(word) {
+            self.counts.add(BitwiseOp::Xor, limb, 0);
+        }
+    }
+
+    fn word(&self, limbs: &[u32]) -> u32 {
+        limbs
+            .iter()
+            .rev()
+            .fold(0, |acc, &limb| (acc << self.bits) | limb)
+    }
+}
+
+fn limbs<const LIMBS: usize>(word: u32) -> [u32; LIMBS] {
+    core::array::from_fn(|i| limb::<LIMBS>(word, i))
+}
+
+fn field_limbs<F: Field, const LIMBS: usize>(word: u32) -> [F; LIMBS] {
+    limbs::<LIMBS>(word).map(F::from_canonical_u32)
+}
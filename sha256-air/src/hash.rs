@@ -0,0 +1,65 @@
+use alloc::vec::Vec;
+
+use p3_field::AbstractField;
+
+use crate::constants::K;
+
+/// The blocks compressed by SHA-256 for `message`, i.e. the message with its padding and its
+/// length in bits, as big-endian words.
+pub fn sha256_blocks(message: &[u8]) -> Vec<[u32; 16]> {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    padded.resize((padded.len() + 8).next_multiple_of(64) - 8, 0);
+    padded.extend((message.len() as u64 * 8).to_be_bytes());
+
+    padded
+        .chunks_exact(64)
+        .map(|block| {
+            core::array::from_fn(|i| {
+                u32::from_be_bytes(block[4 * i..4 * i + 4].try_into().unwrap())
+            })
+        })
+        .collect()
+}
+
+/// The SHA-256 compression of `block` from the chaining value `state`.
+pub fn sha256_compress(state: [u32; 8], block: [u32; 16]) -> [u32; 8] {
+    let mut w = block.to_vec();
+    for t in 16..64 {
+        let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+        let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+        w.push(
+            s1.wrapping_add(w[t - 7])
+                .wrapping_add(s0)
+                .wrapping_add(w[t - 16]),
+        );
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for t in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(K[t])
+            .wrapping_add(w[t]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+    }
+
+    let vars = [a, b, c, d, e, f, g, h];
+    core::array::from_fn(|i| state[i].wrapping_add(vars[i]))
+}
+
+/// The 16-bit halves of `words`, least significant first, as a compression is sent to a bus with
+/// `Sha256Builder`.
+pub fn word_halves<F: AbstractField>(words: &[u32]) -> Vec<F> {
+    words
+        .iter()
+        .flat_map(|&word| [word & 0xFFFF, word >> 16])
+        .map(F::from_canonical_u32)
+        .collect()
+}
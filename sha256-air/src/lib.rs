@@ -0,0 +1,28 @@
+//! An AIR for the SHA-256 compression function, with the XORs, ANDs, rotations and shifts of its
+//! words checked by lookups into a bitwise table such as `p3-bitwise-air`'s, so that the table can
+//! be shared with other chips.
+
+#![no_std]
+
+extern crate alloc;
+
+mod air;
+mod builder;
+mod columns;
+mod constants;
+mod generation;
+mod hash;
+
+pub use air::*;
+pub use builder::*;
+pub use columns::*;
+pub use constants::*;
+pub use generation::*;
+pub use hash::*;
+
+/// The number of rounds of a compression, each of which takes a row.
+pub const NUM_ROUNDS: usize = 64;
+
+/// The number of 16-bit limbs a compression is sent to a bus with, as in `Sha256Builder`: those
+/// of the chaining value, the block and the output.
+pub const SHA256_COMPRESS_LIMBS: usize = 2 * (8 + 16 + 8);
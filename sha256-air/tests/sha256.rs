@@ -0,0 +1,237 @@
+use core::borrow::Borrow;
+use core::ops::Range;
+
+use p3_air::{Air, AirBuilderWithLookups, BaseAir, BaseSubAir, PairBuilder, SubAir, SubBuilder};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_bitwise_air::{bitwise_table, BitwiseBuilder, BitwiseCounts, NUM_BITWISE_OPS};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field, PrimeField32};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_sha256::Sha256;
+use p3_sha256_air::{
+    generate_sha256_trace, sha256_blocks, sha256_compress, word_halves, Sha256Air, Sha256Builder,
+    Sha256Cols, NUM_ROUNDS, SHA256_COMPRESS_LIMBS, SHA256_IV,
+};
+use p3_symmetric::{CryptographicHasher, PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove_with_preprocessed, setup_preprocessed, verify_with_preprocessed, StarkConfig,
+};
+use rand::{thread_rng, Rng};
+
+type Val = BabyBear;
+
+/// Limbs of 4 bits, so that the bitwise table has 256 rows.
+const LIMBS: usize = 8;
+const BITS: usize = 32 / LIMBS;
+const HEIGHT: usize = 1 << (2 * BITS);
+
+fn random_inputs(n: usize) -> Vec<([u32; 8], [u32; 16])> {
+    let mut rng = thread_rng();
+    (0..n).map(|_| (rng.gen(), rng.gen())).collect()
+}
+
+#[test]
+fn test_sha256_compress() {
+    for len in [0, 3, 55, 56, 200] {
+        let message: Vec<u8> = (0..len).map(|_| thread_rng().gen()).collect();
+        let state = sha256_blocks(&message)
+            .into_iter()
+            .fold(SHA256_IV, sha256_compress);
+        let digest: Vec<u8> = state.iter().flat_map(|word| word.to_be_bytes()).collect();
+        assert_eq!(digest, Sha256.hash_iter(message));
+    }
+}
+
+#[test]
+fn test_trace_outputs() {
+    let air = Sha256Air::<LIMBS>::new(0);
+    let inputs = random_inputs(3);
+    let mut counts = BitwiseCounts::new(BITS);
+    let trace = generate_sha256_trace::<Val, LIMBS>(&air, &inputs, HEIGHT, &mut counts);
+    assert_eq!(trace.height(), HEIGHT);
+
+    for i in 0..trace.height() {
+        let row = trace.row_slice(i);
+        let cols: &Sha256Cols<Val, LIMBS> = (*row).borrow();
+        let compression = i / NUM_ROUNDS;
+        let last = i % NUM_ROUNDS == NUM_ROUNDS - 1;
+        assert_eq!(
+            cols.export,
+            Val::from_bool(last && compression < inputs.len())
+        );
+        if last {
+            let (state, block) = inputs.get(compression).copied().unwrap_or_default();
+            let output: Vec<u32> = cols
+                .state_out
+                .iter()
+                .map(|out| {
+                    out.output
+                        .iter()
+                        .rev()
+                        .fold(0, |acc, limb| (acc << BITS) | limb.as_canonical_u32())
+                })
+                .collect();
+            assert_eq!(output, sha256_compress(state, block));
+        }
+    }
+}
+
+const BUS: usize = 0;
+const BITWISE_BUS: usize = 1;
+
+/// The columns of the chip: the halves of a chaining value, a block and an output, and whether
+/// the row uses the compression.
+const CHIP_WIDTH: usize = SHA256_COMPRESS_LIMBS + 1;
+
+/// A chip which uses the compressions of a `Sha256Air` in the same AIR, along with the bitwise
+/// table the `Sha256Air` looks up, whose multiplicities are the last columns.
+struct Sha256ChipAir {
+    sha256: Sha256Air<LIMBS>,
+}
+
+impl Sha256ChipAir {
+    fn new() -> Self {
+        Self {
+            sha256: Sha256Air::new(BITWISE_BUS).with_bus(BUS),
+        }
+    }
+
+    fn sha256_columns(&self) -> Range<usize> {
+        CHIP_WIDTH..CHIP_WIDTH + self.sha256.width()
+    }
+}
+
+impl<F: Field> BaseAir<F> for Sha256ChipAir {
+    fn width(&self) -> usize {
+        CHIP_WIDTH + self.sha256.width() + NUM_BITWISE_OPS
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(bitwise_table(BITS))
+    }
+}
+
+impl<AB: PairBuilder + AirBuilderWithLookups> Air<AB> for Sha256ChipAir {
+    fn eval(&self, builder: &mut AB) {
+        let table: Vec<AB::Var> = builder.preprocessed().row_slice(0).to_vec();
+        let main = builder.main();
+        let local: Vec<AB::Var> = main.row_slice(0).to_vec();
+        let (state, rest) = local.split_at(16);
+        let (block, rest) = rest.split_at(32);
+        let (output, rest) = rest.split_at(16);
+        let enabled = rest[0];
+        builder.assert_bool(enabled);
+        builder.sha256_compress(
+            BUS,
+            state.iter().copied(),
+            block.iter().copied(),
+            output.iter().copied(),
+            enabled,
+        );
+
+        self.sha256
+            .eval(&mut SubBuilder::new(builder, self.sha256_columns()));
+        builder.bitwise_table(BITWISE_BUS, &table, &local[local.len() - NUM_BITWISE_OPS..]);
+    }
+}
+
+fn generate_trace(air: &Sha256ChipAir, inputs: &[([u32; 8], [u32; 16])]) -> RowMajorMatrix<Val> {
+    let mut counts = BitwiseCounts::new(BITS);
+    let sha256 = generate_sha256_trace::<Val, LIMBS>(&air.sha256, inputs, HEIGHT, &mut counts);
+    let multiplicities = counts.multiplicities::<Val>();
+
+    let mut values = Vec::with_capacity(HEIGHT * BaseAir::<Val>::width(air));
+    for (i, (sha256_row, multiplicities)) in sha256.rows().zip(multiplicities.rows()).enumerate() {
+        let compression = i / NUM_ROUNDS;
+        match inputs.get(compression) {
+            Some(&(state, block)) if i % NUM_ROUNDS == NUM_ROUNDS - 1 => {
+                values.extend(word_halves::<Val>(&state));
+                values.extend(word_halves::<Val>(&block));
+                values.extend(word_halves::<Val>(&sha256_compress(state, block)));
+                values.push(Val::ONE);
+            }
+            _ => values.extend([Val::ZERO; CHIP_WIDTH]),
+        }
+        values.extend(sha256_row);
+        values.extend(multiplicities);
+    }
+    RowMajorMatrix::new(values, BaseAir::<Val>::width(air))
+}
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn prove_and_verify(air: &Sha256ChipAir, trace: RowMajorMatrix<Val>) {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+    let preprocessed = setup_preprocessed(&config, air).unwrap();
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_preprocessed(
+        &config,
+        air,
+        &mut challenger,
+        trace,
+        &vec![],
+        Some(&preprocessed),
+    );
+
+    let mut challenger = Challenger::new(perm);
+    let vk = preprocessed.verifier_key();
+    verify_with_preprocessed(&config, air, &mut challenger, &proof, &vec![], Some(&vk))
+        .expect("verification failed");
+}
+
+#[test]
+fn test_compressions_from_another_chip() {
+    // Three compressions and one of padding fill the 256 rows of the bitwise table.
+    let air = Sha256ChipAir::new();
+    let trace = generate_trace(&air, &random_inputs(3));
+    prove_and_verify(&air, trace);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "the lookups aren't balanced")]
+fn test_wrong_output() {
+    let air = Sha256ChipAir::new();
+    let mut trace = generate_trace(&air, &random_inputs(1));
+    // The first half of the output the chip claims for the first compression.
+    let width = BaseAir::<Val>::width(&air);
+    trace.values[(NUM_ROUNDS - 1) * width + 16 + 32] += Val::ONE;
+    prove_and_verify(&air, trace);
+}
@@ -1,3 +1,5 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use core::borrow::Borrow;
 use core::marker::PhantomData;
 
@@ -56,3 +58,66 @@ impl<F, W, const DIGEST_ELEMS: usize> AsRef<[W; DIGEST_ELEMS]> for Hash<F, W, DI
         &self.value
     }
 }
+
+/// The cap of a Merkle tree: the digests of one of its layers, committed to in place of the root.
+///
+/// Stopping a tree some layers short of the root makes opening proofs shorter by that many layers,
+/// at the cost of a larger commitment. A cap of a single digest is just the root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(serialize = "[W; DIGEST_ELEMS]: Serialize"))]
+#[serde(bound(deserialize = "[W; DIGEST_ELEMS]: Deserialize<'de>"))]
+pub struct MerkleCap<F, W, const DIGEST_ELEMS: usize> {
+    digests: Vec<[W; DIGEST_ELEMS]>,
+    _marker: PhantomData<F>,
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> MerkleCap<F, W, DIGEST_ELEMS> {
+    pub fn digests(&self) -> &[[W; DIGEST_ELEMS]] {
+        &self.digests
+    }
+
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> From<Vec<[W; DIGEST_ELEMS]>>
+    for MerkleCap<F, W, DIGEST_ELEMS>
+{
+    fn from(digests: Vec<[W; DIGEST_ELEMS]>) -> Self {
+        Self {
+            digests,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> From<Hash<F, W, DIGEST_ELEMS>>
+    for MerkleCap<F, W, DIGEST_ELEMS>
+{
+    fn from(root: Hash<F, W, DIGEST_ELEMS>) -> Self {
+        vec![root.value].into()
+    }
+}
+
+/// A cap equals a digest if it consists of just that digest, i.e. if the digest is the root.
+impl<F, W: PartialEq, const DIGEST_ELEMS: usize> PartialEq<[W; DIGEST_ELEMS]>
+    for MerkleCap<F, W, DIGEST_ELEMS>
+{
+    fn eq(&self, other: &[W; DIGEST_ELEMS]) -> bool {
+        self.digests.len() == 1 && self.digests[0] == *other
+    }
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> IntoIterator for MerkleCap<F, W, DIGEST_ELEMS> {
+    type Item = [W; DIGEST_ELEMS];
+    type IntoIter = vec::IntoIter<[W; DIGEST_ELEMS]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.digests.into_iter()
+    }
+}
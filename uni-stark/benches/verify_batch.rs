@@ -0,0 +1,130 @@
+//! Compares verifying a batch of proofs one at a time with `verify` against `verify_batch`.
+//!
+//! Run with `cargo bench -p p3-uni-stark --features parallel --bench verify_batch`; without the
+//! `parallel` feature, `verify_batch` only saves the repeated symbolic evaluation of the AIR.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use itertools::Itertools;
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{prove, verify, verify_batch, StarkConfig};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Dft = Radix2DitParallel<Val>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<MyPcs, Challenge, Challenger>;
+
+const NUM_COLS: usize = 16;
+const LOG_HEIGHT: usize = 10;
+
+/// `NUM_COLS` columns which each cube the previous row's value and add one.
+struct CubesAir;
+
+impl<F> BaseAir<F> for CubesAir {
+    fn width(&self) -> usize {
+        NUM_COLS
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for CubesAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        for (&x, &y) in local.iter().zip(next.iter()) {
+            let x: AB::Expr = x.into();
+            builder
+                .when_transition()
+                .assert_eq(y, x.clone() * x.clone() * x + AB::Expr::ONE);
+        }
+    }
+}
+
+fn cubes_trace(start: u32) -> RowMajorMatrix<Val> {
+    let mut values = Vec::with_capacity(NUM_COLS << LOG_HEIGHT);
+    values.extend((0..NUM_COLS as u32).map(|i| Val::from_canonical_u32(start + i)));
+    for _ in 1..1 << LOG_HEIGHT {
+        let row = values[values.len() - NUM_COLS..]
+            .iter()
+            .map(|&x| x.cube() + Val::ONE)
+            .collect_vec();
+        values.extend(row);
+    }
+    RowMajorMatrix::new(values, NUM_COLS)
+}
+
+fn bench_verify_batch(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut rng,
+    );
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 50,
+        proof_of_work_bits: 0,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+    };
+    let config = MyConfig::new(MyPcs::new(Dft::default(), val_mmcs, fri_config));
+    let challenger = Challenger::new(perm);
+
+    let mut group = c.benchmark_group("verify_batch");
+    group.sample_size(10);
+
+    for num_proofs in [1, 8, 32] {
+        let proofs = (0..num_proofs)
+            .map(|i| {
+                prove(
+                    &config,
+                    &CubesAir,
+                    &mut challenger.clone(),
+                    cubes_trace(i),
+                    &vec![],
+                )
+            })
+            .collect_vec();
+        let public_values = vec![vec![]; num_proofs as usize];
+
+        group.bench_function(BenchmarkId::new("each", num_proofs), |b| {
+            b.iter(|| {
+                for proof in &proofs {
+                    verify(&config, &CubesAir, &mut challenger.clone(), proof, &vec![]).unwrap();
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("batch", num_proofs), |b| {
+            b.iter(|| {
+                verify_batch(&config, &CubesAir, &challenger, &proofs, &public_values).unwrap()
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_verify_batch);
+criterion_main!(benches);
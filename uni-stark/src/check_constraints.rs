@@ -1,63 +1,187 @@
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::fmt;
 
-use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues};
-use p3_field::Field;
+use itertools::{izip, Itertools};
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithLookups, AirBuilderWithPublicValues, AirBuilderWithRowSelectors,
+    ExtensionBuilder, Lookup, MultiStageAirBuilder, PairBuilder, PeriodicAirBuilder,
+};
+use p3_field::{ExtensionField, Field};
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
-use p3_matrix::stack::VerticalPair;
 use p3_matrix::Matrix;
 use tracing::instrument;
 
-#[instrument(name = "check constraints", skip_all)]
-pub(crate) fn check_constraints<F, A>(air: &A, main: &RowMajorMatrix<F>, public_values: &Vec<F>)
+use crate::padding::is_transition_window_row;
+
+/// The first constraint which doesn't hold, as found by `check_constraints`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintFailure<EF> {
+    pub row: usize,
+    /// The index of the constraint in the order the AIR asserts them, as in
+    /// `get_symbolic_constraints`.
+    pub constraint_index: usize,
+    /// The name given with `AirBuilder::name_constraints` or `AirBuilder::assert_zero_named`, if
+    /// any.
+    pub name: Option<String>,
+    /// The nonzero value of the constraint.
+    pub value: EF,
+}
+
+impl<EF: fmt::Display> fmt::Display for ConstraintFailure<EF> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constraints had nonzero value on row {}: constraint {}",
+            self.row, self.constraint_index
+        )?;
+        if let Some(name) = &self.name {
+            write!(f, " ({name})")?;
+        }
+        write!(f, " evaluated to {}", self.value)
+    }
+}
+
+/// Check the constraints of `air` on every row of `main`, without any padding, and return the
+/// first which doesn't hold.
+///
+/// This is much cheaper than proving, so it's a quick way to find bugs in trace generation. Use
+/// `check_constraints_with_stages` for an AIR with a preprocessed trace or later stages.
+pub fn check_constraints<F, A>(
+    air: &A,
+    main: &RowMajorMatrix<F>,
+    public_values: &Vec<F>,
+) -> Result<(), ConstraintFailure<F>>
 where
     F: Field,
     A: for<'a> Air<DebugConstraintBuilder<'a, F>>,
+{
+    check_constraints_with_stages(air, None, main, &[], &[], public_values, main.height())
+}
+
+/// Like `check_constraints`, with the selectors of
+/// `PolynomialSpace::selectors_at_point_for_height` for `selector_height`.
+///
+/// `stages` are the traces of the stages after the main trace, and `stage_challenges` the
+/// challenges sampled before each.
+#[instrument(name = "check constraints", skip_all)]
+pub fn check_constraints_with_stages<F, EF, A>(
+    air: &A,
+    preprocessed: Option<&RowMajorMatrix<F>>,
+    main: &RowMajorMatrix<F>,
+    stages: &[RowMajorMatrix<F>],
+    stage_challenges: &[Vec<EF>],
+    public_values: &Vec<F>,
+    selector_height: usize,
+) -> Result<(), ConstraintFailure<EF>>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: for<'a> Air<DebugConstraintBuilder<'a, F, EF>>,
 {
     let height = main.height();
+    let window_offsets = air.window_offsets();
+    let periodic_columns = air.periodic_columns();
 
-    (0..height).for_each(|i| {
+    for i in 0..height {
         let i_next = (i + 1) % height;
+        let pair = |mat: &RowMajorMatrix<F>| {
+            [mat.row_slice(i).to_vec(), mat.row_slice(i_next).to_vec()].concat()
+        };
 
-        let local = main.row_slice(i);
-        let next = main.row_slice(i_next);
-        let main = VerticalPair::new(
-            RowMajorMatrixView::new_row(&*local),
-            RowMajorMatrixView::new_row(&*next),
+        let main_window = window_offsets
+            .iter()
+            .flat_map(|&offset| {
+                let row = (i as isize + offset).rem_euclid(height as isize) as usize;
+                main.row_slice(row).to_vec()
+            })
+            .collect_vec();
+        let main = RowMajorMatrixView::new(&main_window, main.width());
+
+        let preprocessed_rows = preprocessed.map(pair).unwrap_or_default();
+        let preprocessed = RowMajorMatrixView::new(
+            &preprocessed_rows,
+            preprocessed.map_or(0, |prep| prep.width()),
         );
 
+        let stage_rows = stages.iter().map(pair).collect_vec();
+        let stages = izip!(stages, &stage_rows)
+            .map(|(stage, rows)| RowMajorMatrixView::new(rows, stage.width()))
+            .collect();
+
+        let periodic_values = periodic_columns
+            .iter()
+            .map(|column| column[i % column.len()])
+            .collect();
+
         let mut builder = DebugConstraintBuilder {
             row_index: i,
+            height,
+            selector_height,
+            preprocessed,
             main,
+            stages,
+            stage_challenges,
+            periodic_values,
             public_values,
             is_first_row: F::from_bool(i == 0),
-            is_last_row: F::from_bool(i == height - 1),
-            is_transition: F::from_bool(i != height - 1),
+            is_last_row: F::from_bool(i == selector_height - 1),
+            constraint_index: 0,
+            constraint_name: None,
+            failure: None,
         };
 
         air.eval(&mut builder);
-    });
+        if let Some(failure) = builder.failure {
+            return Err(failure);
+        }
+    }
+    Ok(())
 }
 
-/// An `AirBuilder` which asserts that each constraint is zero, allowing any failed constraints to
-/// be detected early.
+/// An `AirBuilder` which evaluates each constraint on a row, and records the first which isn't
+/// zero, allowing any failed constraints to be detected early.
 #[derive(Debug)]
-pub struct DebugConstraintBuilder<'a, F: Field> {
+pub struct DebugConstraintBuilder<'a, F: Field, EF: ExtensionField<F> = F> {
     row_index: usize,
-    main: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
+    height: usize,
+    selector_height: usize,
+    preprocessed: RowMajorMatrixView<'a, F>,
+    main: RowMajorMatrixView<'a, F>,
+    stages: Vec<RowMajorMatrixView<'a, F>>,
+    stage_challenges: &'a [Vec<EF>],
+    periodic_values: Vec<F>,
     public_values: &'a [F],
     is_first_row: F,
     is_last_row: F,
-    is_transition: F,
+    constraint_index: usize,
+    constraint_name: Option<String>,
+    failure: Option<ConstraintFailure<EF>>,
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> DebugConstraintBuilder<'a, F, EF> {
+    fn check(&mut self, value: EF) {
+        if !value.is_zero() && self.failure.is_none() {
+            self.failure = Some(ConstraintFailure {
+                row: self.row_index,
+                constraint_index: self.constraint_index,
+                name: self.constraint_name.clone(),
+                value,
+            });
+        }
+        self.constraint_index += 1;
+    }
 }
 
-impl<'a, F> AirBuilder for DebugConstraintBuilder<'a, F>
+impl<'a, F, EF> AirBuilder for DebugConstraintBuilder<'a, F, EF>
 where
     F: Field,
+    EF: ExtensionField<F>,
 {
     type F = F;
     type Expr = F;
     type Var = F;
-    type M = VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>;
+    type M = RowMajorMatrixView<'a, F>;
 
     fn main(&self) -> Self::M {
         self.main
@@ -72,37 +196,98 @@ where
     }
 
     fn is_transition_window(&self, size: usize) -> Self::Expr {
-        if size == 2 {
-            self.is_transition
-        } else {
-            panic!("only supports a window size of 2")
-        }
+        F::from_bool(is_transition_window_row(
+            self.row_index,
+            size,
+            self.height,
+            self.selector_height,
+        ))
     }
 
     fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
-        assert_eq!(
-            x.into(),
-            F::ZERO,
-            "constraints had nonzero value on row {}",
-            self.row_index
-        );
+        self.check(EF::from_base(x.into()));
     }
 
-    fn assert_eq<I1: Into<Self::Expr>, I2: Into<Self::Expr>>(&mut self, x: I1, y: I2) {
-        let x = x.into();
-        let y = y.into();
-        assert_eq!(
-            x, y,
-            "values didn't match on row {}: {} != {}",
-            self.row_index, x, y
-        );
+    fn name_constraints(&mut self, name: &str) {
+        self.constraint_name = Some(name.to_string());
+    }
+
+    fn assert_zero_named<I: Into<Self::Expr>>(&mut self, name: &str, x: I) {
+        let group_name = self.constraint_name.replace(name.to_string());
+        self.assert_zero(x);
+        self.constraint_name = group_name;
     }
 }
 
-impl<'a, F: Field> AirBuilderWithPublicValues for DebugConstraintBuilder<'a, F> {
+impl<'a, F: Field, EF: ExtensionField<F>> AirBuilderWithPublicValues
+    for DebugConstraintBuilder<'a, F, EF>
+{
     type PublicVar = Self::F;
 
     fn public_values(&self) -> &[Self::F] {
         self.public_values
     }
 }
+
+impl<'a, F: Field, EF: ExtensionField<F>> PeriodicAirBuilder for DebugConstraintBuilder<'a, F, EF> {
+    type PeriodicVar = Self::F;
+
+    fn periodic_values(&self) -> &[Self::F] {
+        &self.periodic_values
+    }
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> AirBuilderWithRowSelectors
+    for DebugConstraintBuilder<'a, F, EF>
+{
+    fn is_row(&self, row: usize) -> Self::Expr {
+        F::from_bool(self.row_index == row)
+    }
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> PairBuilder for DebugConstraintBuilder<'a, F, EF> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+/// Lookups aren't checked row by row; instead the prover checks that they're balanced when it
+/// computes their cumulative sum.
+impl<'a, F: Field, EF: ExtensionField<F>> AirBuilderWithLookups
+    for DebugConstraintBuilder<'a, F, EF>
+{
+    fn lookup(&mut self, _lookup: Lookup<Self::Expr>) {}
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> ExtensionBuilder for DebugConstraintBuilder<'a, F, EF> {
+    type EF = EF;
+    type ExprEF = EF;
+    type VarEF = EF;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.check(x.into());
+    }
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> MultiStageAirBuilder
+    for DebugConstraintBuilder<'a, F, EF>
+{
+    type Challenge = EF;
+
+    fn stage(&self, stage: usize) -> Self::M {
+        match stage {
+            0 => self.main,
+            _ => self.stages[stage - 1],
+        }
+    }
+
+    fn stage_challenges(&self, stage: usize) -> &[Self::Challenge] {
+        match stage {
+            0 => &[],
+            _ => &self.stage_challenges[stage - 1],
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use core::marker::PhantomData;
 
 use p3_challenger::{CanObserve, CanSample, FieldChallenger};
-use p3_commit::{Pcs, PolynomialSpace};
+use p3_commit::{LagrangeSelectorCache, Pcs, PolynomialSpace};
 use p3_field::{ExtensionField, Field};
 
+use crate::TracePadding;
+
 pub type PcsError<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenge,
     <SC as StarkGenericConfig>::Challenger,
@@ -34,11 +36,30 @@ pub trait StarkGenericConfig {
         + CanSample<Self::Challenge>;
 
     fn pcs(&self) -> &Self::Pcs;
+
+    /// How traces whose height isn't a power of two are padded.
+    fn trace_padding(&self) -> TracePadding {
+        TracePadding::default()
+    }
+
+    /// A cache for the selectors over the quotient domain, which are then only computed once for
+    /// all proofs with this config and the same trace height.
+    fn selector_cache(&self) -> Option<&LagrangeSelectorCache> {
+        None
+    }
+
+    /// Whether the PCS is zero-knowledge, in which case the committed polynomials have twice the
+    /// degree of the traces, as in `Pcs::ZK`.
+    fn is_zk(&self) -> bool {
+        <Self::Pcs as Pcs<Self::Challenge, Self::Challenger>>::ZK
+    }
 }
 
 #[derive(Debug)]
 pub struct StarkConfig<Pcs, Challenge, Challenger> {
     pcs: Pcs,
+    trace_padding: TracePadding,
+    selector_cache: LagrangeSelectorCache,
     _phantom: PhantomData<(Challenge, Challenger)>,
 }
 
@@ -46,9 +67,16 @@ impl<Pcs, Challenge, Challenger> StarkConfig<Pcs, Challenge, Challenger> {
     pub const fn new(pcs: Pcs) -> Self {
         Self {
             pcs,
+            trace_padding: TracePadding::RepeatLastRow,
+            selector_cache: LagrangeSelectorCache::new(),
             _phantom: PhantomData,
         }
     }
+
+    pub const fn with_trace_padding(mut self, trace_padding: TracePadding) -> Self {
+        self.trace_padding = trace_padding;
+        self
+    }
 }
 
 impl<Pcs, Challenge, Challenger> StarkGenericConfig for StarkConfig<Pcs, Challenge, Challenger>
@@ -66,4 +94,12 @@ where
     fn pcs(&self) -> &Self::Pcs {
         &self.pcs
     }
+
+    fn trace_padding(&self) -> TracePadding {
+        self.trace_padding
+    }
+
+    fn selector_cache(&self) -> Option<&LagrangeSelectorCache> {
+        Some(&self.selector_cache)
+    }
 }
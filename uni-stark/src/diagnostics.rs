@@ -0,0 +1,53 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use p3_field::Field;
+
+/// Why the constraints at `zeta` didn't match the quotient, reported by
+/// `VerificationError::OodEvaluationMismatch` with the `debug-diagnostics` feature.
+///
+/// The values are formatted, since the error isn't generic over the challenge field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OodMismatchDiagnostics {
+    /// The constraints of the AIR folded with `alpha`, at `zeta`.
+    pub folded_constraints: String,
+    /// The quotient the constraints imply, `folded_constraints / Z_H(zeta)`.
+    pub expected_quotient: String,
+    /// The quotient recomposed from the opened chunks.
+    pub quotient: String,
+    /// `expected_quotient - quotient`.
+    pub delta: String,
+    /// The term each opened quotient chunk contributes to `quotient`, by chunk index.
+    pub quotient_chunks: Vec<String>,
+}
+
+impl OodMismatchDiagnostics {
+    pub(crate) fn new<Challenge: Field>(
+        folded_constraints: Challenge,
+        expected_quotient: Challenge,
+        quotient_chunks: &[Challenge],
+    ) -> Self {
+        let quotient = quotient_chunks.iter().copied().sum::<Challenge>();
+        Self {
+            folded_constraints: folded_constraints.to_string(),
+            expected_quotient: expected_quotient.to_string(),
+            quotient: quotient.to_string(),
+            delta: (expected_quotient - quotient).to_string(),
+            quotient_chunks: quotient_chunks.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+impl fmt::Display for OodMismatchDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "folded constraints: {}", self.folded_constraints)?;
+        writeln!(f, "expected quotient: {}", self.expected_quotient)?;
+        writeln!(f, "recomposed quotient: {}", self.quotient)?;
+        write!(f, "delta: {}", self.delta)?;
+        for (i, chunk) in self.quotient_chunks.iter().enumerate() {
+            write!(f, "\nquotient chunk {i}: {chunk}")?;
+        }
+        Ok(())
+    }
+}
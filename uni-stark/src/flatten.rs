@@ -0,0 +1,801 @@
+//! Flattening proofs into vectors of field elements, for verifying them in a circuit.
+//!
+//! The layout mirrors the canonical encoding of `p3_util::canonical_serialization`, except that it
+//! is made of field elements instead of bytes:
+//! - every integer is a single element, so it must be less than the field's order; the fields in
+//!   this repository serialize their canonical integer representative, so each field element,
+//!   including each coefficient of an extension field element, is one element,
+//! - `bool`s and `Option` tags are 0 or 1, and enum variants are their variant index,
+//! - sequences, maps, strings and byte strings are prefixed by their length,
+//! - tuples, fixed-size arrays and structs are the concatenation of their elements, in order.
+//!
+//! A [`ProofLayout`] names the range of elements holding each part of a flattened proof, so
+//! circuit builders don't need to know how each type serializes.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+
+use p3_field::PrimeField64;
+use serde::de::value::U32Deserializer;
+use serde::de::{DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::{de, ser, Serialize};
+
+use crate::{Proof, StarkGenericConfig, Val};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlattenError {
+    /// An integer doesn't fit in one field element, or an element doesn't fit in the integer type
+    /// it should be decoded as.
+    IntegerOutOfRange,
+    /// The elements ended before the proof was fully decoded.
+    UnexpectedEnd,
+    /// The proof was decoded, but elements remained.
+    TrailingElements(usize),
+    /// An element which should have been a `bool` or `Option` tag was neither 0 nor 1.
+    InvalidTag,
+    /// A decoded string was not valid UTF-8, or a `char` was not a valid scalar value.
+    InvalidUtf8,
+    /// The proof uses a `serde` feature which can't be flattened.
+    Unsupported(&'static str),
+    /// An error raised by a `Serialize` or `Deserialize` implementation.
+    Custom(String),
+}
+
+impl Display for FlattenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::IntegerOutOfRange => write!(f, "integer out of range of a field element"),
+            Self::UnexpectedEnd => write!(f, "unexpected end of elements"),
+            Self::TrailingElements(n) => write!(f, "{n} trailing elements after decoded proof"),
+            Self::InvalidTag => write!(f, "invalid tag element"),
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            Self::Unsupported(what) => write!(f, "{what} can't be flattened"),
+            Self::Custom(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl ser::StdError for FlattenError {}
+
+impl ser::Error for FlattenError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for FlattenError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// The parts of a flattened proof, in order.
+///
+/// Each part is named by its path within the proof, made of struct field names and vector
+/// indices, e.g. `opened_values.trace_window[1][0]`. The length of a vector is at its path
+/// followed by `.len`, the tag of an `Option` at its path followed by `.is_some`, and the variant
+/// of an enum at its path followed by `.variant`. The elements of tuples and arrays, such as the
+/// coefficients of an extension field element (at its path followed by `.value`) or the elements
+/// of a digest, share their path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofLayout {
+    pub segments: Vec<LayoutSegment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutSegment {
+    pub path: String,
+    /// The elements of the flattened proof holding this part.
+    pub range: Range<usize>,
+}
+
+impl ProofLayout {
+    /// The number of elements in the flattened proof.
+    pub fn num_elements(&self) -> usize {
+        self.segments.last().map_or(0, |segment| segment.range.end)
+    }
+
+    /// The elements holding the part at `path`, if the proof has it.
+    pub fn get(&self, path: &str) -> Option<Range<usize>> {
+        self.segments
+            .iter()
+            .find(|segment| segment.path == path)
+            .map(|segment| segment.range.clone())
+    }
+}
+
+impl<SC: StarkGenericConfig> Proof<SC>
+where
+    Val<SC>: PrimeField64,
+{
+    /// Flatten this proof into field elements: the canonical encoding of [`Proof::to_bytes`],
+    /// without its header, with each integer, length and tag a single element. Each field
+    /// element, including each coefficient of an extension field element, is one element; see
+    /// [`ProofLayout`] for where each part of the proof ends up.
+    ///
+    /// # Panics
+    /// Panics if the commitments or opening proof contain an integer which isn't less than the
+    /// field's order, e.g. a `u64` digest of a byte-oriented hash.
+    pub fn flatten(&self) -> Vec<Val<SC>> {
+        flatten_with_layout::<Val<SC>, _>(self)
+            .expect("the proof can't be flattened")
+            .0
+    }
+
+    /// The layout of [`Proof::flatten`], which only depends on the shape of the proof.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`Proof::flatten`].
+    pub fn flatten_layout(&self) -> ProofLayout {
+        flatten_with_layout::<Val<SC>, _>(self)
+            .expect("the proof can't be flattened")
+            .1
+    }
+
+    /// Rebuild a proof from the elements produced by [`Proof::flatten`].
+    ///
+    /// This only checks that the elements are well formed; the proof's shape is checked by
+    /// `verify`.
+    pub fn unflatten(elements: &[Val<SC>]) -> Result<Self, FlattenError> {
+        unflatten(elements)
+    }
+}
+
+fn flatten_with_layout<F: PrimeField64, T: Serialize + ?Sized>(
+    value: &T,
+) -> Result<(Vec<F>, ProofLayout), FlattenError> {
+    let mut serializer = FlatSerializer {
+        output: Vec::new(),
+        layout: ProofLayout::default(),
+        path: String::new(),
+    };
+    value.serialize(&mut serializer)?;
+    Ok((serializer.output, serializer.layout))
+}
+
+fn unflatten<F: PrimeField64, T: DeserializeOwned>(elements: &[F]) -> Result<T, FlattenError> {
+    let mut deserializer = FlatDeserializer { input: elements };
+    let value = T::deserialize(&mut deserializer)?;
+    if deserializer.input.is_empty() {
+        Ok(value)
+    } else {
+        Err(FlattenError::TrailingElements(deserializer.input.len()))
+    }
+}
+
+struct FlatSerializer<F> {
+    output: Vec<F>,
+    layout: ProofLayout,
+    /// The path of the value being serialized.
+    path: String,
+}
+
+impl<F: PrimeField64> FlatSerializer<F> {
+    /// Append `value` to the part at the current path followed by `suffix`.
+    fn push(&mut self, suffix: &str, value: u64) -> Result<(), FlattenError> {
+        if value >= F::ORDER_U64 {
+            return Err(FlattenError::IntegerOutOfRange);
+        }
+        let index = self.output.len();
+        self.output.push(F::from_canonical_u64(value));
+
+        let path = format!("{}{suffix}", self.path);
+        match self.layout.segments.last_mut() {
+            Some(segment) if segment.path == path && segment.range.end == index => {
+                segment.range.end += 1;
+            }
+            _ => self.layout.segments.push(LayoutSegment {
+                path,
+                range: index..index + 1,
+            }),
+        }
+        Ok(())
+    }
+
+    fn push_len(&mut self, len: usize) -> Result<(), FlattenError> {
+        self.push(".len", len as u64)
+    }
+
+    /// Serialize `value` at the current path followed by `part`.
+    fn nested<T: Serialize + ?Sized>(&mut self, part: &str, value: &T) -> Result<(), FlattenError> {
+        let parent_len = self.path.len();
+        if !part.starts_with('[') && !self.path.is_empty() {
+            self.path.push('.');
+        }
+        self.path.push_str(part);
+        let result = value.serialize(&mut *self);
+        self.path.truncate(parent_len);
+        result
+    }
+}
+
+impl<'a, F: PrimeField64> ser::Serializer for &'a mut FlatSerializer<F> {
+    type Ok = ();
+    type Error = FlattenError;
+    type SerializeSeq = Compound<'a, F>;
+    type SerializeTuple = Compound<'a, F>;
+    type SerializeTupleStruct = Compound<'a, F>;
+    type SerializeTupleVariant = Compound<'a, F>;
+    type SerializeMap = Compound<'a, F>;
+    type SerializeStruct = Compound<'a, F>;
+    type SerializeStructVariant = Compound<'a, F>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), FlattenError> {
+        self.push("", v as u64)
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<(), FlattenError> {
+        Err(FlattenError::Unsupported("signed integers"))
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<(), FlattenError> {
+        Err(FlattenError::Unsupported("signed integers"))
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<(), FlattenError> {
+        Err(FlattenError::Unsupported("signed integers"))
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<(), FlattenError> {
+        Err(FlattenError::Unsupported("signed integers"))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), FlattenError> {
+        self.push("", v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), FlattenError> {
+        self.push("", v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), FlattenError> {
+        self.push("", v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), FlattenError> {
+        self.push("", v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), FlattenError> {
+        let v = u64::try_from(v).map_err(|_| FlattenError::IntegerOutOfRange)?;
+        self.push("", v)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), FlattenError> {
+        Err(FlattenError::Unsupported("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), FlattenError> {
+        Err(FlattenError::Unsupported("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), FlattenError> {
+        self.push("", v as u64)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), FlattenError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), FlattenError> {
+        self.push_len(v.len())?;
+        v.iter().try_for_each(|&byte| self.push("", byte as u64))
+    }
+
+    fn serialize_none(self) -> Result<(), FlattenError> {
+        self.push(".is_some", 0)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), FlattenError> {
+        self.push(".is_some", 1)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), FlattenError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), FlattenError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), FlattenError> {
+        self.push(".variant", variant_index as u64)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), FlattenError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), FlattenError> {
+        self.push(".variant", variant_index as u64)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Compound<'a, F>, FlattenError> {
+        let len = len.ok_or(FlattenError::Unsupported("a sequence of unknown length"))?;
+        self.push_len(len)?;
+        Ok(Compound::indexed(self))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a, F>, FlattenError> {
+        Ok(Compound::flat(self))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, F>, FlattenError> {
+        Ok(Compound::flat(self))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, F>, FlattenError> {
+        self.push(".variant", variant_index as u64)?;
+        Ok(Compound::flat(self))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Compound<'a, F>, FlattenError> {
+        let len = len.ok_or(FlattenError::Unsupported("a map of unknown length"))?;
+        self.push_len(len)?;
+        Ok(Compound::indexed(self))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, F>, FlattenError> {
+        Ok(Compound::flat(self))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, F>, FlattenError> {
+        self.push(".variant", variant_index as u64)?;
+        Ok(Compound::flat(self))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Serializes the elements of a sequence, map, tuple or struct.
+struct Compound<'a, F> {
+    ser: &'a mut FlatSerializer<F>,
+    /// The index of the next element, for sequences and maps, whose elements each get their own
+    /// path; the elements of tuples share the path of the tuple.
+    next_index: Option<usize>,
+}
+
+impl<'a, F: PrimeField64> Compound<'a, F> {
+    fn indexed(ser: &'a mut FlatSerializer<F>) -> Self {
+        Self {
+            ser,
+            next_index: Some(0),
+        }
+    }
+
+    fn flat(ser: &'a mut FlatSerializer<F>) -> Self {
+        Self {
+            ser,
+            next_index: None,
+        }
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FlattenError> {
+        match self.next_index {
+            Some(index) => self.ser.nested(&format!("[{index}]"), value),
+            None => value.serialize(&mut *self.ser),
+        }
+    }
+}
+
+macro_rules! impl_compound_serializer {
+    ($trait:ident, $method:ident) => {
+        impl<'a, F: PrimeField64> ser::$trait for Compound<'a, F> {
+            type Ok = ();
+            type Error = FlattenError;
+
+            fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+                self.element(value)?;
+                if let Some(index) = &mut self.next_index {
+                    *index += 1;
+                }
+                Ok(())
+            }
+
+            fn end(self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_compound_serializer!(SerializeSeq, serialize_element);
+impl_compound_serializer!(SerializeTuple, serialize_element);
+impl_compound_serializer!(SerializeTupleStruct, serialize_field);
+impl_compound_serializer!(SerializeTupleVariant, serialize_field);
+
+impl<'a, F: PrimeField64> ser::SerializeMap for Compound<'a, F> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.element(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.element(value)?;
+        if let Some(index) = &mut self.next_index {
+            *index += 1;
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_struct_serializer {
+    ($trait:ident) => {
+        impl<'a, F: PrimeField64> ser::$trait for Compound<'a, F> {
+            type Ok = ();
+            type Error = FlattenError;
+
+            fn serialize_field<T: Serialize + ?Sized>(
+                &mut self,
+                key: &'static str,
+                value: &T,
+            ) -> Result<(), Self::Error> {
+                self.ser.nested(key, value)
+            }
+
+            fn end(self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_struct_serializer!(SerializeStruct);
+impl_struct_serializer!(SerializeStructVariant);
+
+struct FlatDeserializer<'a, F> {
+    input: &'a [F],
+}
+
+impl<'a, F: PrimeField64> FlatDeserializer<'a, F> {
+    fn read(&mut self) -> Result<u64, FlattenError> {
+        let (first, rest) = self
+            .input
+            .split_first()
+            .ok_or(FlattenError::UnexpectedEnd)?;
+        self.input = rest;
+        Ok(first.as_canonical_u64())
+    }
+
+    fn read_int<T: TryFrom<u64>>(&mut self) -> Result<T, FlattenError> {
+        T::try_from(self.read()?).map_err(|_| FlattenError::IntegerOutOfRange)
+    }
+
+    fn read_len(&mut self) -> Result<usize, FlattenError> {
+        self.read_int()
+    }
+
+    fn read_tag(&mut self) -> Result<bool, FlattenError> {
+        match self.read()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(FlattenError::InvalidTag),
+        }
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>, FlattenError> {
+        let len = self.read_len()?;
+        if self.input.len() < len {
+            return Err(FlattenError::UnexpectedEnd);
+        }
+        (0..len).map(|_| self.read_int()).collect()
+    }
+}
+
+macro_rules! deserialize_int {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+            visitor.$visit(self.read_int::<$ty>()?)
+        }
+    };
+}
+
+impl<'de, 'a, F: PrimeField64> de::Deserializer<'de> for &mut FlatDeserializer<'a, F> {
+    type Error = FlattenError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, FlattenError> {
+        Err(FlattenError::Unsupported("self-describing deserialization"))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+        visitor.visit_bool(self.read_tag()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, FlattenError> {
+        Err(FlattenError::Unsupported("signed integers"))
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, FlattenError> {
+        Err(FlattenError::Unsupported("signed integers"))
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, FlattenError> {
+        Err(FlattenError::Unsupported("signed integers"))
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, FlattenError> {
+        Err(FlattenError::Unsupported("signed integers"))
+    }
+
+    deserialize_int!(deserialize_u8, visit_u8, u8);
+    deserialize_int!(deserialize_u16, visit_u16, u16);
+    deserialize_int!(deserialize_u32, visit_u32, u32);
+    deserialize_int!(deserialize_u64, visit_u64, u64);
+    deserialize_int!(deserialize_u128, visit_u128, u128);
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, FlattenError> {
+        Err(FlattenError::Unsupported("f32"))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, FlattenError> {
+        Err(FlattenError::Unsupported("f64"))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+        let c = char::from_u32(self.read_int()?).ok_or(FlattenError::InvalidUtf8)?;
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+        let s = String::from_utf8(self.read_bytes()?).map_err(|_| FlattenError::InvalidUtf8)?;
+        visitor.visit_string(s)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+        visitor.visit_byte_buf(self.read_bytes()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+        if self.read_tag()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, FlattenError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, FlattenError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+        let len = self.read_len()?;
+        visitor.visit_seq(Elements {
+            de: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, FlattenError> {
+        visitor.visit_seq(Elements {
+            de: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, FlattenError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FlattenError> {
+        let len = self.read_len()?;
+        visitor.visit_map(Elements {
+            de: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, FlattenError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, FlattenError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, FlattenError> {
+        Err(FlattenError::Unsupported("identifiers"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, FlattenError> {
+        Err(FlattenError::Unsupported("ignored values"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Gives a visitor access to a known number of consecutive elements (or key-value pairs).
+struct Elements<'b, 'a, F> {
+    de: &'b mut FlatDeserializer<'a, F>,
+    remaining: usize,
+}
+
+impl<'b, 'a, 'de, F: PrimeField64> de::SeqAccess<'de> for Elements<'b, 'a, F> {
+    type Error = FlattenError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, FlattenError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        // Lengths come from untrusted input, so don't let visitors preallocate based on them.
+        Some(self.remaining.min(1 << 12))
+    }
+}
+
+impl<'b, 'a, 'de, F: PrimeField64> de::MapAccess<'de> for Elements<'b, 'a, F> {
+    type Error = FlattenError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, FlattenError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, FlattenError> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.min(1 << 12))
+    }
+}
+
+impl<'de, 'a, F: PrimeField64> de::EnumAccess<'de> for &mut FlatDeserializer<'a, F> {
+    type Error = FlattenError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), FlattenError> {
+        let variant_index: u32 = self.read_int()?;
+        let variant: U32Deserializer<FlattenError> = variant_index.into_deserializer();
+        let value = seed.deserialize(variant)?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'a, F: PrimeField64> de::VariantAccess<'de> for &mut FlatDeserializer<'a, F> {
+    type Error = FlattenError;
+
+    fn unit_variant(self) -> Result<(), FlattenError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, FlattenError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, FlattenError> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, FlattenError> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
@@ -1,33 +1,71 @@
 use alloc::vec::Vec;
 
-use p3_air::{AirBuilder, AirBuilderWithPublicValues};
-use p3_field::AbstractField;
+use p3_air::{
+    AirBuilder, AirBuilderWithLookups, AirBuilderWithPublicValues, AirBuilderWithRowSelectors,
+    ExtensionBuilder, Lookup, MultiStageAirBuilder, PairBuilder, PeriodicAirBuilder,
+    PermutationAirBuilder,
+};
+use p3_commit::{LagrangeSelectors, PolynomialSpace};
+use p3_field::PackedValue;
 use p3_matrix::dense::RowMajorMatrixView;
-use p3_matrix::stack::VerticalPair;
 
-use crate::{PackedChallenge, PackedVal, StarkGenericConfig, Val};
+use crate::{Domain, PackedChallenge, PackedVal, StarkGenericConfig, Val};
 
 #[derive(Debug)]
 pub struct ProverConstraintFolder<'a, SC: StarkGenericConfig> {
+    /// Of width zero if the AIR has no preprocessed trace.
+    pub preprocessed: RowMajorMatrixView<'a, PackedVal<SC>>,
     pub main: RowMajorMatrixView<'a, PackedVal<SC>>,
+    /// The stages after the main trace.
+    pub stages: Vec<RowMajorMatrixView<'a, PackedVal<SC>>>,
+    /// The challenges sampled before each stage after the main trace.
+    pub stage_challenges: &'a [Vec<PackedChallenge<SC>>],
+    /// The LogUp trace, of width zero if the AIR has no lookups.
+    pub permutation: RowMajorMatrixView<'a, PackedChallenge<SC>>,
+    /// The LogUp challenges `[gamma, beta]`, if the AIR has lookups.
+    pub permutation_challenges: &'a [PackedChallenge<SC>],
+    /// The lookups declared so far on this row, for `eval_logup`.
+    pub lookups: Vec<Lookup<PackedVal<SC>>>,
+    /// The values of the AIR's periodic columns on this packing of rows.
+    pub periodic_values: &'a [PackedVal<SC>],
     pub public_values: &'a Vec<Val<SC>>,
     pub is_first_row: PackedVal<SC>,
     pub is_last_row: PackedVal<SC>,
     pub is_transition: PackedVal<SC>,
-    pub alpha_powers: &'a [SC::Challenge],
+    /// The selectors over the whole quotient domain, which `is_row` and `is_transition_window`
+    /// rotate.
+    pub selectors: &'a LagrangeSelectors<Vec<Val<SC>>>,
+    /// The index in the quotient domain of the first point of this packing.
+    pub quotient_index: usize,
+    /// The number of points of the quotient domain per row of the trace.
+    pub quotient_step: usize,
+    /// The power of alpha each constraint is multiplied by, in every lane, so that folding a
+    /// constraint is a single packed multiplication.
+    pub alpha_powers: &'a [PackedChallenge<SC>],
     pub accumulator: PackedChallenge<SC>,
     pub constraint_index: usize,
 }
 
-type ViewPair<'a, T> = VerticalPair<RowMajorMatrixView<'a, T>, RowMajorMatrixView<'a, T>>;
-
 #[derive(Debug)]
 pub struct VerifierConstraintFolder<'a, SC: StarkGenericConfig> {
-    pub main: ViewPair<'a, SC::Challenge>,
+    pub preprocessed: RowMajorMatrixView<'a, SC::Challenge>,
+    pub main: RowMajorMatrixView<'a, SC::Challenge>,
+    pub stages: Vec<RowMajorMatrixView<'a, SC::Challenge>>,
+    pub stage_challenges: &'a [Vec<SC::Challenge>],
+    pub permutation: RowMajorMatrixView<'a, SC::Challenge>,
+    pub permutation_challenges: &'a [SC::Challenge],
+    pub lookups: Vec<Lookup<SC::Challenge>>,
+    /// The values of the AIR's periodic columns at `zeta`.
+    pub periodic_values: &'a [SC::Challenge],
     pub public_values: &'a Vec<Val<SC>>,
     pub is_first_row: SC::Challenge,
     pub is_last_row: SC::Challenge,
     pub is_transition: SC::Challenge,
+    /// The trace domain, the height of its selectors and the point the constraints are evaluated
+    /// at, for `is_row` and `is_transition_window`.
+    pub trace_domain: Domain<SC>,
+    pub selector_height: usize,
+    pub zeta: SC::Challenge,
     pub alpha: SC::Challenge,
     pub accumulator: SC::Challenge,
 }
@@ -53,20 +91,29 @@ impl<'a, SC: StarkGenericConfig> AirBuilder for ProverConstraintFolder<'a, SC> {
         self.is_last_row
     }
 
+    /// The product of `is_transition` on each of the `size - 1` rows from this one, which are a
+    /// rotation of the quotient domain.
     #[inline]
     fn is_transition_window(&self, size: usize) -> Self::Expr {
         if size == 2 {
-            self.is_transition
-        } else {
-            panic!("uni-stark only supports a window size of 2")
+            return self.is_transition;
         }
+        let len = self.selectors.is_transition.len();
+        (0..size.saturating_sub(1))
+            .map(|row| {
+                let shift = (row * self.quotient_step) % len;
+                packed_window(
+                    &self.selectors.is_transition,
+                    (self.quotient_index + shift) % len,
+                )
+            })
+            .product()
     }
 
     #[inline]
     fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
         let x: PackedVal<SC> = x.into();
-        let alpha_power = self.alpha_powers[self.constraint_index];
-        self.accumulator += PackedChallenge::<SC>::from_f(alpha_power) * x;
+        self.accumulator += self.alpha_powers[self.constraint_index] * x;
         self.constraint_index += 1;
     }
 }
@@ -80,11 +127,97 @@ impl<'a, SC: StarkGenericConfig> AirBuilderWithPublicValues for ProverConstraint
     }
 }
 
+impl<'a, SC: StarkGenericConfig> PeriodicAirBuilder for ProverConstraintFolder<'a, SC> {
+    type PeriodicVar = PackedVal<SC>;
+
+    #[inline]
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        self.periodic_values
+    }
+}
+
+/// `L_row(x) = L_0(x g^-row)`, and multiplying by `g^-row` is a rotation of the quotient domain.
+impl<'a, SC: StarkGenericConfig> AirBuilderWithRowSelectors for ProverConstraintFolder<'a, SC> {
+    fn is_row(&self, row: usize) -> Self::Expr {
+        let size = self.selectors.is_first_row.len();
+        let shift = (row * self.quotient_step) % size;
+        packed_window(
+            &self.selectors.is_first_row,
+            (self.quotient_index + size - shift) % size,
+        )
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> PairBuilder for ProverConstraintFolder<'a, SC> {
+    #[inline]
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> ExtensionBuilder for ProverConstraintFolder<'a, SC> {
+    type EF = SC::Challenge;
+    type ExprEF = PackedChallenge<SC>;
+    type VarEF = PackedChallenge<SC>;
+
+    #[inline]
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        let x: PackedChallenge<SC> = x.into();
+        self.accumulator += self.alpha_powers[self.constraint_index] * x;
+        self.constraint_index += 1;
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> PermutationAirBuilder for ProverConstraintFolder<'a, SC> {
+    type MP = RowMajorMatrixView<'a, PackedChallenge<SC>>;
+    type RandomVar = PackedChallenge<SC>;
+
+    #[inline]
+    fn permutation(&self) -> Self::MP {
+        self.permutation
+    }
+
+    #[inline]
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.permutation_challenges
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> AirBuilderWithLookups for ProverConstraintFolder<'a, SC> {
+    #[inline]
+    fn lookup(&mut self, lookup: Lookup<Self::Expr>) {
+        self.lookups.push(lookup);
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> MultiStageAirBuilder for ProverConstraintFolder<'a, SC> {
+    type Challenge = PackedChallenge<SC>;
+
+    #[inline]
+    fn stage(&self, stage: usize) -> Self::M {
+        match stage {
+            0 => self.main,
+            _ => self.stages[stage - 1],
+        }
+    }
+
+    #[inline]
+    fn stage_challenges(&self, stage: usize) -> &[Self::Challenge] {
+        match stage {
+            0 => &[],
+            _ => &self.stage_challenges[stage - 1],
+        }
+    }
+}
+
 impl<'a, SC: StarkGenericConfig> AirBuilder for VerifierConstraintFolder<'a, SC> {
     type F = Val<SC>;
     type Expr = SC::Challenge;
     type Var = SC::Challenge;
-    type M = ViewPair<'a, SC::Challenge>;
+    type M = RowMajorMatrixView<'a, SC::Challenge>;
 
     fn main(&self) -> Self::M {
         self.main
@@ -98,12 +231,22 @@ impl<'a, SC: StarkGenericConfig> AirBuilder for VerifierConstraintFolder<'a, SC>
         self.is_last_row
     }
 
+    /// The product of `is_transition` at `zeta` rotated by each of the `size - 1` rows from it.
     fn is_transition_window(&self, size: usize) -> Self::Expr {
         if size == 2 {
-            self.is_transition
-        } else {
-            panic!("uni-stark only supports a window size of 2")
+            return self.is_transition;
         }
+        (0..size.saturating_sub(1))
+            .map(|row| {
+                let point = self
+                    .trace_domain
+                    .rotated_point(self.zeta, row as isize)
+                    .unwrap();
+                self.trace_domain
+                    .selectors_at_point_for_height(point, self.selector_height)
+                    .is_transition
+            })
+            .product()
     }
 
     fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
@@ -120,3 +263,89 @@ impl<'a, SC: StarkGenericConfig> AirBuilderWithPublicValues for VerifierConstrai
         self.public_values
     }
 }
+
+impl<'a, SC: StarkGenericConfig> PeriodicAirBuilder for VerifierConstraintFolder<'a, SC> {
+    type PeriodicVar = SC::Challenge;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        self.periodic_values
+    }
+}
+
+/// `L_row(x) = L_0(x g^-row)`.
+impl<'a, SC: StarkGenericConfig> AirBuilderWithRowSelectors for VerifierConstraintFolder<'a, SC> {
+    fn is_row(&self, row: usize) -> Self::Expr {
+        let point = self
+            .trace_domain
+            .rotated_point(self.zeta, -(row as isize))
+            .unwrap();
+        self.trace_domain.selectors_at_point(point).is_first_row
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> PairBuilder for VerifierConstraintFolder<'a, SC> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> ExtensionBuilder for VerifierConstraintFolder<'a, SC> {
+    type EF = SC::Challenge;
+    type ExprEF = SC::Challenge;
+    type VarEF = SC::Challenge;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.assert_zero(x);
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> PermutationAirBuilder for VerifierConstraintFolder<'a, SC> {
+    type MP = RowMajorMatrixView<'a, SC::Challenge>;
+    type RandomVar = SC::Challenge;
+
+    fn permutation(&self) -> Self::MP {
+        self.permutation
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.permutation_challenges
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> AirBuilderWithLookups for VerifierConstraintFolder<'a, SC> {
+    fn lookup(&mut self, lookup: Lookup<Self::Expr>) {
+        self.lookups.push(lookup);
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> MultiStageAirBuilder for VerifierConstraintFolder<'a, SC> {
+    type Challenge = SC::Challenge;
+
+    fn stage(&self, stage: usize) -> Self::M {
+        match stage {
+            0 => self.main,
+            _ => self.stages[stage - 1],
+        }
+    }
+
+    fn stage_challenges(&self, stage: usize) -> &[Self::Challenge] {
+        match stage {
+            0 => &[],
+            _ => &self.stage_challenges[stage - 1],
+        }
+    }
+}
+
+/// The packing of `values[start..start + P::WIDTH]`, with the lanes past the end of `values`
+/// wrapping around to its start, as `vertically_packed_row` does for matrices.
+#[inline]
+pub(crate) fn packed_window<P: PackedValue>(values: &[P::Value], start: usize) -> P {
+    if start + P::WIDTH <= values.len() {
+        *P::from_slice(&values[start..start + P::WIDTH])
+    } else {
+        P::from_fn(|i| values[(start + i) % values.len()])
+    }
+}
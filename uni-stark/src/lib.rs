@@ -3,28 +3,50 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "metrics")]
+extern crate std;
 
+mod check_constraints;
 mod config;
+#[cfg(feature = "debug-diagnostics")]
+mod diagnostics;
+mod flatten;
 mod folder;
+mod lookup;
+mod metrics;
+mod padding;
+mod preprocessed;
 mod proof;
 mod prover;
+mod quotient_degree;
+mod serialization;
+mod stages;
 mod symbolic_builder;
+mod symbolic_dag;
 mod symbolic_expression;
 mod symbolic_variable;
+mod trace_generator;
 mod verifier;
 mod zerofier_coset;
 
-#[cfg(debug_assertions)]
-mod check_constraints;
-
-#[cfg(debug_assertions)]
 pub use check_constraints::*;
 pub use config::*;
+#[cfg(feature = "debug-diagnostics")]
+pub use diagnostics::*;
+pub use flatten::*;
 pub use folder::*;
+pub use metrics::{ProofSize, ProverMetrics};
+pub use padding::*;
+pub use preprocessed::*;
 pub use proof::*;
 pub use prover::*;
+pub use quotient_degree::*;
+pub use serialization::*;
+pub use stages::StageTrace;
 pub use symbolic_builder::*;
+pub use symbolic_dag::*;
 pub use symbolic_expression::*;
 pub use symbolic_variable::*;
+pub use trace_generator::*;
 pub use verifier::*;
 pub use zerofier_coset::*;
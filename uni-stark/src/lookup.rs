@@ -0,0 +1,158 @@
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+use p3_air::{lookup_fingerprint, Lookup};
+use p3_field::{batch_multiplicative_inverse, ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use tracing::instrument;
+
+use crate::padding::is_transition_window_row;
+use crate::{Entry, SymbolicExpression};
+
+/// The maximum degree of the constraints `eval_logup` asserts for `lookups`, as in
+/// `get_max_constraint_degree`.
+pub(crate) fn logup_constraint_degree<F>(lookups: &[Lookup<SymbolicExpression<F>>]) -> usize {
+    lookups
+        .iter()
+        .map(|lookup| {
+            let fingerprint_degree = lookup
+                .values
+                .iter()
+                .map(SymbolicExpression::degree_multiple)
+                .max()
+                .unwrap_or(0);
+            // The LogUp term times its fingerprint, against the multiplicity.
+            (fingerprint_degree + 1).max(lookup.multiplicity.degree_multiple())
+        })
+        // The running sum constraints are selectors times linear terms.
+        .fold(if lookups.is_empty() { 0 } else { 2 }, usize::max)
+}
+
+/// The permutation trace checked by `eval_logup`: a LogUp term for each lookup on each row, and
+/// their running sum.
+///
+/// The selectors are those of the prover, i.e. for `selector_height`, and the main trace is seen
+/// at the AIR's `window_offsets`. There must be at least one lookup.
+#[instrument(name = "generate LogUp trace", skip_all)]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn generate_logup_trace<F: Field, EF: ExtensionField<F>>(
+    lookups: &[Lookup<SymbolicExpression<F>>],
+    preprocessed: Option<&RowMajorMatrix<F>>,
+    main: &RowMajorMatrix<F>,
+    window_offsets: &[isize],
+    periodic_columns: &[Vec<F>],
+    public_values: &[F],
+    selector_height: usize,
+    gamma: EF,
+    beta: EF,
+) -> RowMajorMatrix<EF> {
+    let height = main.height();
+    let num_lookups = lookups.len();
+
+    // The symbolic expressions aren't `Sync`, so this is done sequentially.
+    let (fingerprints, multiplicities): (Vec<EF>, Vec<F>) = (0..height)
+        .flat_map(|r| {
+            let r_next = (r + 1) % height;
+            let row = |mat: &RowMajorMatrix<F>, r| mat.row_slice(r).to_vec();
+            let window = Window {
+                row: r,
+                height,
+                selector_height,
+                preprocessed: preprocessed
+                    .map(|prep| [row(prep, r), row(prep, r_next)])
+                    .unwrap_or_default(),
+                main: window_offsets
+                    .iter()
+                    .map(|&offset| {
+                        row(
+                            main,
+                            (r as isize + offset).rem_euclid(height as isize) as usize,
+                        )
+                    })
+                    .collect(),
+                periodic: periodic_columns
+                    .iter()
+                    .map(|column| column[r % column.len()])
+                    .collect(),
+                public_values,
+                is_first_row: F::from_bool(r == 0),
+                is_last_row: F::from_bool(r == selector_height - 1),
+            };
+            lookups
+                .iter()
+                .map(|lookup| {
+                    let values = lookup.values.iter().map(|v| window.eval(v));
+                    let fingerprint = lookup_fingerprint(lookup.bus, values, gamma, beta);
+                    (fingerprint, window.eval(&lookup.multiplicity))
+                })
+                .collect_vec()
+        })
+        .unzip();
+
+    let mut values = Vec::with_capacity(height * (num_lookups + 1));
+    let mut running_sum = EF::ZERO;
+    let inverses = batch_multiplicative_inverse(&fingerprints);
+    for (inverses, multiplicities) in inverses
+        .chunks_exact(num_lookups)
+        .zip(multiplicities.chunks_exact(num_lookups))
+    {
+        for (&inverse, &multiplicity) in inverses.iter().zip(multiplicities) {
+            let term = inverse * multiplicity;
+            running_sum += term;
+            values.push(term);
+        }
+        values.push(running_sum);
+    }
+    RowMajorMatrix::new(values, num_lookups + 1)
+}
+
+/// The values of the window of a trace on row `r`, i.e. the rows `r` and `r + 1` of the
+/// preprocessed trace, the rows of the main trace at the AIR's `window_offsets` and row `r` of the
+/// periodic columns, against which symbolic expressions are evaluated.
+struct Window<'a, F> {
+    row: usize,
+    height: usize,
+    selector_height: usize,
+    preprocessed: [Vec<F>; 2],
+    main: Vec<Vec<F>>,
+    periodic: Vec<F>,
+    public_values: &'a [F],
+    is_first_row: F,
+    is_last_row: F,
+}
+
+impl<'a, F: Field> Window<'a, F> {
+    fn is_transition_window(&self, size: usize) -> F {
+        F::from_bool(is_transition_window_row(
+            self.row,
+            size,
+            self.height,
+            self.selector_height,
+        ))
+    }
+
+    fn eval(&self, expr: &SymbolicExpression<F>) -> F {
+        match expr {
+            SymbolicExpression::Variable(v) => match v.entry {
+                Entry::Preprocessed { offset } => self.preprocessed[offset][v.index],
+                Entry::Main { offset } => self.main[offset][v.index],
+                Entry::Periodic => self.periodic[v.index],
+                Entry::Public => self.public_values[v.index],
+                Entry::Permutation { .. } | Entry::Stage { .. } | Entry::Challenge => {
+                    panic!("lookups can only depend on the preprocessed and main traces")
+                }
+            },
+            SymbolicExpression::IsFirstRow => self.is_first_row,
+            SymbolicExpression::IsLastRow => self.is_last_row,
+            SymbolicExpression::IsTransition => self.is_transition_window(2),
+            SymbolicExpression::IsTransitionWindow(size) => self.is_transition_window(*size),
+            SymbolicExpression::IsRow(row) => F::from_bool(*row == self.row),
+            SymbolicExpression::Constant(c) => *c,
+            SymbolicExpression::Add { x, y, .. } => self.eval(x) + self.eval(y),
+            SymbolicExpression::Sub { x, y, .. } => self.eval(x) - self.eval(y),
+            SymbolicExpression::Neg { x, .. } => -self.eval(x),
+            SymbolicExpression::Mul { x, y, .. } => self.eval(x) * self.eval(y),
+        }
+    }
+}
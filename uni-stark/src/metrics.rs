@@ -0,0 +1,82 @@
+use core::time::Duration;
+
+use p3_util::canonical_serialization::{to_bytes, SerializationError};
+
+use crate::{Proof, StarkGenericConfig};
+
+/// How long each phase of `prove_with_metrics` took, and the size of the proof it made, for
+/// monitoring performance and comparing configs without parsing tracing logs.
+///
+/// Peak memory isn't measured, since that needs a global allocator which counts allocations.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProverMetrics {
+    /// Committing to the main trace, the later stages and the LogUp trace, if any.
+    pub trace_commit: Duration,
+    /// Computing the quotient polynomial and committing to its chunks.
+    pub quotient: Duration,
+    /// Opening every commitment at the out-of-domain point, including the FRI proof.
+    pub open: Duration,
+    /// The whole proof, including e.g. evaluating the constraints symbolically.
+    pub total: Duration,
+    pub proof_size: ProofSize,
+}
+
+/// The number of bytes of each part of a proof encoded by `Proof::to_bytes`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofSize {
+    pub commitments: usize,
+    pub opened_values: usize,
+    /// The PCS's opening proof, e.g. FRI's query proofs.
+    pub opening_proof: usize,
+    /// The whole encoding, including its header and the instance.
+    pub total: usize,
+}
+
+impl ProofSize {
+    pub fn of<SC: StarkGenericConfig>(proof: &Proof<SC>) -> Result<Self, SerializationError> {
+        Ok(Self {
+            commitments: to_bytes(&proof.commitments)?.len(),
+            opened_values: to_bytes(&proof.opened_values)?.len(),
+            opening_proof: to_bytes(&proof.opening_proof)?.len(),
+            total: proof.to_bytes()?.len(),
+        })
+    }
+}
+
+/// A phase of proving timed in `ProverMetrics`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Phase {
+    TraceCommit,
+    Quotient,
+    Open,
+}
+
+/// Run `f`, adding the time it takes to `phase` of `metrics` if there are any.
+#[inline]
+pub(crate) fn timed<R>(
+    metrics: &mut Option<&mut ProverMetrics>,
+    phase: Phase,
+    f: impl FnOnce() -> R,
+) -> R {
+    #[cfg(feature = "metrics")]
+    if let Some(metrics) = metrics {
+        let start = std::time::Instant::now();
+        let result = f();
+        *metrics.phase_mut(phase) += start.elapsed();
+        return result;
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = (metrics, phase);
+    f()
+}
+
+impl ProverMetrics {
+    #[cfg(feature = "metrics")]
+    fn phase_mut(&mut self, phase: Phase) -> &mut Duration {
+        match phase {
+            Phase::TraceCommit => &mut self.trace_commit,
+            Phase::Quotient => &mut self.quotient,
+            Phase::Open => &mut self.open,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use alloc::vec::Vec;
+
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+/// How `prove` pads a trace whose height isn't a power of two.
+///
+/// Padding rows are constrained like any other, so which policy is correct depends on the AIR.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TracePadding {
+    /// Repeat the last row. The selectors are unchanged, so `is_last_row` selects the last
+    /// padding row, which is a copy of the last row of the trace. Transition constraints must
+    /// hold between copies of the last row.
+    #[default]
+    RepeatLastRow,
+    /// Pad with rows of zeros, and adjust the selectors so that `is_last_row` selects the last row
+    /// of the trace, and no transition is enforced from it into the padding, nor from the padding
+    /// back to the first row (see `PolynomialSpace::selectors_at_point_for_height`). Constraints
+    /// must hold on rows of zeros, which is the case if they have no constant terms other than in
+    /// boundary constraints.
+    ///
+    /// The adjusted `is_transition` has an extra root, so this may raise the quotient degree.
+    Zeros,
+    /// Pad with copies of the AIR's `BaseAir::padding_row`, or rows of zeros if it has none, and
+    /// adjust the selectors as for `Zeros`. The constraints must hold on the padding row, so this
+    /// suits AIRs with constraints which rows of zeros don't satisfy, e.g. `x * x_inv = 1`.
+    ///
+    /// Preprocessed traces are padded with rows of zeros.
+    PaddingRow,
+}
+
+impl TracePadding {
+    /// Pad `trace` to the next power of two height, with `padding_row` for `PaddingRow`.
+    pub fn pad<F: Field>(
+        self,
+        mut trace: RowMajorMatrix<F>,
+        padding_row: Option<Vec<F>>,
+    ) -> RowMajorMatrix<F> {
+        let height = trace.height();
+        assert!(height > 0, "cannot pad an empty trace");
+        let padded_height = height.next_power_of_two();
+        match self {
+            Self::RepeatLastRow => {
+                let last_row = trace.row_slice(height - 1).to_vec();
+                for _ in height..padded_height {
+                    trace.values.extend_from_slice(&last_row);
+                }
+            }
+            Self::Zeros => trace.pad_to_height(padded_height, F::ZERO),
+            Self::PaddingRow => match padding_row {
+                Some(padding_row) => {
+                    assert_eq!(
+                        padding_row.len(),
+                        trace.width(),
+                        "the padding row must be as wide as the trace"
+                    );
+                    for _ in height..padded_height {
+                        trace.values.extend_from_slice(&padding_row);
+                    }
+                }
+                None => trace.pad_to_height(padded_height, F::ZERO),
+            },
+        }
+        trace
+    }
+
+    /// The height whose last row `is_last_row` selects, for a trace of height `trace_height`
+    /// padded to `padded_height`. The selectors are those of
+    /// `PolynomialSpace::selectors_at_point_for_height` with this height.
+    pub const fn selector_height(self, trace_height: usize, padded_height: usize) -> usize {
+        match self {
+            Self::RepeatLastRow => padded_height,
+            Self::Zeros | Self::PaddingRow => trace_height,
+        }
+    }
+}
+
+/// The value of `is_transition_window(size)` on `row` of a padded trace of height `height`, with
+/// the selectors for `selector_height`: whether none of the `size - 1` rows from `row` is the last
+/// row of either.
+pub(crate) fn is_transition_window_row(
+    row: usize,
+    size: usize,
+    height: usize,
+    selector_height: usize,
+) -> bool {
+    (row..row + size.saturating_sub(1)).all(|r| {
+        let r = r % height;
+        r != height - 1 && r != selector_height - 1
+    })
+}
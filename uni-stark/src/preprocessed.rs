@@ -0,0 +1,89 @@
+use alloc::vec;
+
+use p3_air::BaseAir;
+use p3_commit::Pcs;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{Com, PcsProverData, StarkGenericConfig, Val};
+
+/// The prover's side of the preprocessed trace of an AIR, as produced by `setup_preprocessed`.
+pub struct PreprocessedProverData<SC: StarkGenericConfig> {
+    /// The padded preprocessed trace.
+    pub(crate) trace: RowMajorMatrix<Val<SC>>,
+    pub(crate) degree_bits: usize,
+    pub(crate) commitment: Com<SC>,
+    pub(crate) prover_data: PcsProverData<SC>,
+}
+
+/// The verifier's side of the preprocessed trace of an AIR: its commitment and shape.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PreprocessedVerifierKey<SC: StarkGenericConfig> {
+    pub(crate) width: usize,
+    pub(crate) degree_bits: usize,
+    pub(crate) commitment: Com<SC>,
+}
+
+impl<SC: StarkGenericConfig> Clone for PreprocessedVerifierKey<SC> {
+    fn clone(&self) -> Self {
+        Self {
+            width: self.width,
+            degree_bits: self.degree_bits,
+            commitment: self.commitment.clone(),
+        }
+    }
+}
+
+impl<SC: StarkGenericConfig> PreprocessedProverData<SC> {
+    pub fn width(&self) -> usize {
+        self.trace.width()
+    }
+
+    /// The key for verifying proofs made with this data.
+    pub fn verifier_key(&self) -> PreprocessedVerifierKey<SC> {
+        PreprocessedVerifierKey {
+            width: self.width(),
+            degree_bits: self.degree_bits,
+            commitment: self.commitment.clone(),
+        }
+    }
+}
+
+impl<SC: StarkGenericConfig> PreprocessedVerifierKey<SC> {
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn commitment(&self) -> &Com<SC> {
+        &self.commitment
+    }
+}
+
+/// Commit to the preprocessed trace of `air`, if it has one, padded as in `prove`.
+///
+/// This only needs to be done once per AIR. The main traces of proofs made with the result must
+/// have the same padded height as the preprocessed trace.
+#[instrument(skip_all)]
+pub fn setup_preprocessed<SC, A>(config: &SC, air: &A) -> Option<PreprocessedProverData<SC>>
+where
+    SC: StarkGenericConfig,
+    A: BaseAir<Val<SC>>,
+{
+    let trace = config.trace_padding().pad(air.preprocessed_trace()?, None);
+    let degree = trace.height();
+    let degree_bits = log2_strict_usize(degree);
+
+    let pcs = config.pcs();
+    let domain = pcs.natural_domain_for_degree(degree);
+    let (commitment, prover_data) = pcs.commit(vec![(domain, trace.clone())]);
+    Some(PreprocessedProverData {
+        trace,
+        degree_bits,
+        commitment,
+        prover_data,
+    })
+}
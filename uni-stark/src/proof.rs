@@ -5,10 +5,14 @@ use serde::{Deserialize, Serialize};
 
 use crate::StarkGenericConfig;
 
-type Com<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+pub(crate) type Com<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenge,
     <SC as StarkGenericConfig>::Challenger,
 >>::Commitment;
+pub(crate) type PcsProverData<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::ProverData;
 type PcsProof<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenge,
     <SC as StarkGenericConfig>::Challenger,
@@ -21,17 +25,40 @@ pub struct Proof<SC: StarkGenericConfig> {
     pub(crate) opened_values: OpenedValues<SC::Challenge>,
     pub(crate) opening_proof: PcsProof<SC>,
     pub(crate) degree_bits: usize,
+    /// The height of the trace before it was padded to `2^degree_bits` rows.
+    pub(crate) trace_height: usize,
+    /// The sum of the LogUp terms of all lookups, if the AIR has any, which is zero if they're
+    /// balanced.
+    pub(crate) cumulative_sum: Option<SC::Challenge>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Commitments<Com> {
     pub(crate) trace: Com,
+    /// The commitments to the stages after the main trace, in order.
+    pub(crate) stages: Vec<Com>,
+    /// The commitment to the LogUp trace, if the AIR has lookups.
+    pub(crate) permutation: Option<Com>,
     pub(crate) quotient_chunks: Com,
 }
 
+/// The row offsets, relative to `zeta`, at which the preprocessed, later stage and LogUp traces, if
+/// any, are opened: `*_local` and `*_next` respectively. The main trace is opened at the AIR's
+/// `window_offsets` instead.
+pub(crate) const TRACE_ROTATIONS: [isize; 2] = [0, 1];
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OpenedValues<Challenge> {
-    pub(crate) trace_local: Vec<Challenge>,
-    pub(crate) trace_next: Vec<Challenge>,
+    /// Empty if the AIR has no preprocessed trace.
+    pub(crate) preprocessed_local: Vec<Challenge>,
+    pub(crate) preprocessed_next: Vec<Challenge>,
+    /// The openings of the main trace at each of the AIR's `window_offsets`.
+    pub(crate) trace_window: Vec<Vec<Challenge>>,
+    /// The openings of each stage after the main trace.
+    pub(crate) stages_local: Vec<Vec<Challenge>>,
+    pub(crate) stages_next: Vec<Vec<Challenge>>,
+    /// The openings of the base field columns of the LogUp trace; empty if the AIR has no lookups.
+    pub(crate) permutation_local: Vec<Challenge>,
+    pub(crate) permutation_next: Vec<Challenge>,
     pub(crate) quotient_chunks: Vec<Vec<Challenge>>,
 }
@@ -1,27 +1,36 @@
+use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::mem;
 
 use itertools::{izip, Itertools};
-use p3_air::Air;
+use p3_air::{eval_logup, num_logup_constraints, Air, MultiStageAir};
 use p3_challenger::{CanObserve, CanSample, FieldChallenger};
-use p3_commit::{Pcs, PolynomialSpace};
-use p3_field::{AbstractExtensionField, AbstractField, PackedValue};
+use p3_commit::{LagrangeSelectors, Pcs, PolynomialSpace};
+use p3_field::{AbstractExtensionField, AbstractField, ExtensionField, PackedValue};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
-use p3_util::{log2_ceil_usize, log2_strict_usize};
+use p3_util::log2_strict_usize;
 use tracing::{info_span, instrument};
 
+use crate::folder::packed_window;
+use crate::lookup::generate_logup_trace;
+use crate::metrics::{timed, Phase};
+use crate::proof::TRACE_ROTATIONS;
+use crate::stages::{StageLayout, StageTrace};
+use crate::symbolic_builder::{get_symbolic_constraints_and_lookups, max_constraint_degree};
 use crate::{
-    get_symbolic_constraints, Commitments, Domain, OpenedValues, PackedChallenge, PackedVal, Proof,
-    ProverConstraintFolder, StarkGenericConfig, SymbolicAirBuilder, SymbolicExpression, Val,
+    check_log_quotient_degree, log_quotient_degree_for_padding, Commitments, Domain, OpenedValues,
+    PackedChallenge, PackedVal, PreprocessedProverData, Proof, ProverConstraintFolder,
+    ProverMetrics, StarkGenericConfig, SymbolicAirBuilder, Val,
 };
 
 #[instrument(skip_all)]
 #[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
 pub fn prove<
     SC,
-    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
     #[cfg(not(debug_assertions))] A,
 >(
     config: &SC,
@@ -34,85 +43,407 @@ where
     SC: StarkGenericConfig,
     A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
 {
-    #[cfg(debug_assertions)]
-    crate::check_constraints::check_constraints(air, &trace, public_values);
+    prove_with_preprocessed(config, air, challenger, trace, public_values, None)
+}
 
+/// Like `prove`, for an AIR with a preprocessed trace, committed to by `setup_preprocessed`.
+///
+/// The main trace must have the same padded height as the preprocessed trace.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_preprocessed<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+    preprocessed: Option<&PreprocessedProverData<SC>>,
+) -> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    prove_stages(
+        config,
+        air,
+        challenger,
+        trace,
+        public_values,
+        preprocessed,
+        &StageLayout::default(),
+        &mut |_, _, _| unreachable!("the AIR only has a main trace"),
+        None,
+    )
+}
+
+/// Like `prove_with_preprocessed`, also returning how long each phase of proving took and the
+/// size of the proof.
+#[cfg(feature = "metrics")]
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_metrics<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+    preprocessed: Option<&PreprocessedProverData<SC>>,
+) -> (Proof<SC>, ProverMetrics)
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    let start = std::time::Instant::now();
+    let mut metrics = ProverMetrics::default();
+    let proof = prove_stages(
+        config,
+        air,
+        challenger,
+        trace,
+        public_values,
+        preprocessed,
+        &StageLayout::default(),
+        &mut |_, _, _| unreachable!("the AIR only has a main trace"),
+        Some(&mut metrics),
+    );
+    metrics.total = start.elapsed();
+    metrics.proof_size = crate::ProofSize::of(&proof).expect("failed to serialize the proof");
+    (proof, metrics)
+}
+
+/// Like `prove_with_preprocessed`, for a `MultiStageAir`.
+///
+/// `trace` is the main trace, i.e. stage 0. Each later stage is generated by
+/// `generate_stage(stage, traces, challenges)` from the traces of the stages before it, padded as
+/// in `prove`, and the challenges sampled after committing to them. It must have the width given
+/// by the AIR, and the same height as the padded main trace. A stage which `is_extension_stage`
+/// can be generated as a `StageTrace::Extension`, which is flattened to base field columns.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_multi_stage<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+    G,
+    T,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+    preprocessed: Option<&PreprocessedProverData<SC>>,
+    mut generate_stage: G,
+) -> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    A: MultiStageAir<Val<SC>>
+        + Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+    G: FnMut(usize, &[RowMajorMatrix<Val<SC>>], &[SC::Challenge]) -> T,
+    T: Into<StageTrace<Val<SC>, SC::Challenge>>,
+{
+    let ext_degree = <SC::Challenge as AbstractExtensionField<Val<SC>>>::D;
+    prove_stages(
+        config,
+        air,
+        challenger,
+        trace,
+        public_values,
+        preprocessed,
+        &StageLayout::of(air, ext_degree),
+        &mut |stage, traces, challenges| generate_stage(stage, traces, challenges).into().flatten(),
+        None,
+    )
+}
+
+#[allow(
+    clippy::multiple_bound_locations,
+    clippy::too_many_arguments,
+    clippy::type_complexity
+)]
+fn prove_stages<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+    preprocessed: Option<&PreprocessedProverData<SC>>,
+    stages: &StageLayout,
+    generate_stage: &mut dyn FnMut(
+        usize,
+        &[RowMajorMatrix<Val<SC>>],
+        &[SC::Challenge],
+    ) -> RowMajorMatrix<Val<SC>>,
+    mut metrics: Option<&mut ProverMetrics>,
+) -> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    let trace_height = trace.height();
+    let trace_padding = config.trace_padding();
+    let trace = trace_padding.pad(trace, air.padding_row());
     let degree = trace.height();
     let log_degree = log2_strict_usize(degree);
-
-    let symbolic_constraints = get_symbolic_constraints::<Val<SC>, A>(air, 0, public_values.len());
-    let constraint_count = symbolic_constraints.len();
-    let constraint_degree = symbolic_constraints
-        .iter()
-        .map(SymbolicExpression::degree_multiple)
-        .max()
-        .unwrap_or(0);
-    let log_quotient_degree = log2_ceil_usize(constraint_degree - 1);
-    let quotient_degree = 1 << log_quotient_degree;
+    let selector_height = trace_padding.selector_height(trace_height, degree);
+    if let Some(preprocessed) = preprocessed {
+        assert_eq!(
+            preprocessed.degree_bits, log_degree,
+            "the main and preprocessed traces must have the same padded height"
+        );
+    }
+    let preprocessed_width = preprocessed.map_or(0, PreprocessedProverData::width);
+    let window_offsets = air.window_offsets();
+    let periodic_columns = air.periodic_columns();
+    for column in &periodic_columns {
+        assert!(
+            column.len().is_power_of_two() && column.len() <= degree,
+            "the period of a periodic column must be a power of two dividing the padded height"
+        );
+    }
 
     let pcs = config.pcs();
     let trace_domain = pcs.natural_domain_for_degree(degree);
 
-    let (trace_commit, trace_data) =
-        info_span!("commit to trace data").in_scope(|| pcs.commit(vec![(trace_domain, trace)]));
+    let (symbolic_constraints, lookups) = get_symbolic_constraints_and_lookups::<Val<SC>, A>(
+        air,
+        preprocessed_width,
+        public_values.len(),
+        stages,
+        trace_domain.has_linear_transition_selector(),
+    );
+    let has_lookups = !lookups.is_empty();
+    let mut constraint_count = symbolic_constraints.len();
+    if has_lookups {
+        constraint_count += num_logup_constraints(lookups.len());
+    }
+
+    let constraint_degree = max_constraint_degree(&symbolic_constraints, &lookups);
+    let log_quotient_degree =
+        log_quotient_degree_for_padding(constraint_degree, selector_height, degree, config.is_zk());
+    check_log_quotient_degree(config, log_quotient_degree)
+        .expect("the PCS can't evaluate the trace on a domain as large as the quotient's");
+    let quotient_degree = 1 << log_quotient_degree;
+
+    // The later stages and the LogUp trace are generated from the traces committed to before them,
+    // and the constraints are checked once every stage is committed to.
+    let mut traces = vec![];
+    if cfg!(debug_assertions) || has_lookups || !stages.is_empty() {
+        traces.push(trace.clone());
+    }
+    let (trace_commit, trace_data) = timed(&mut metrics, Phase::TraceCommit, || {
+        info_span!("commit to trace data").in_scope(|| pcs.commit(vec![(trace_domain, trace)]))
+    });
 
     // Observe the instance.
     challenger.observe(Val::<SC>::from_canonical_usize(log_degree));
+    challenger.observe(Val::<SC>::from_canonical_usize(trace_height));
     // TODO: Might be best practice to include other instance data here; see verifier comment.
 
+    if let Some(preprocessed) = preprocessed {
+        challenger.observe(preprocessed.commitment.clone());
+    }
     challenger.observe(trace_commit.clone());
     challenger.observe_slice(public_values);
+
+    let mut stage_challenges = vec![];
+    let mut stage_commits = vec![];
+    let mut stage_data = vec![];
+    for (i, (&width, &num_challenges)) in izip!(&stages.widths, &stages.num_challenges).enumerate()
+    {
+        let stage = i + 1;
+        let challenges: Vec<SC::Challenge> = (0..num_challenges)
+            .map(|_| challenger.sample_ext_element())
+            .collect();
+        let stage_trace = generate_stage(stage, &traces, &challenges);
+        assert_eq!(
+            (stage_trace.width(), stage_trace.height()),
+            (width, degree),
+            "stage {stage} has the wrong dimensions"
+        );
+        let (stage_commit, data) = timed(&mut metrics, Phase::TraceCommit, || {
+            info_span!("commit to stage trace", stage)
+                .in_scope(|| pcs.commit(vec![(trace_domain, stage_trace.clone())]))
+        });
+        challenger.observe(stage_commit.clone());
+        traces.push(stage_trace);
+        stage_challenges.push(challenges);
+        stage_commits.push(stage_commit);
+        stage_data.push(data);
+    }
+
+    let mut permutation_challenges = vec![];
+    let mut cumulative_sum = None;
+    let permutation = has_lookups.then(|| {
+        let gamma: SC::Challenge = challenger.sample_ext_element();
+        let beta: SC::Challenge = challenger.sample_ext_element();
+        permutation_challenges = vec![gamma, beta];
+        let permutation_trace = generate_logup_trace(
+            &lookups,
+            preprocessed.map(|preprocessed| &preprocessed.trace),
+            &traces[0],
+            &window_offsets,
+            &periodic_columns,
+            public_values,
+            selector_height,
+            gamma,
+            beta,
+        );
+        let sum = *permutation_trace.values.last().unwrap();
+        debug_assert_eq!(
+            sum,
+            SC::Challenge::ZERO,
+            "the lookups aren't balanced: sends and receives don't match"
+        );
+        cumulative_sum = Some(sum);
+        let (permutation_commit, permutation_data) =
+            timed(&mut metrics, Phase::TraceCommit, || {
+                info_span!("commit to LogUp trace").in_scope(|| {
+                    pcs.commit(vec![(trace_domain, permutation_trace.flatten_to_base())])
+                })
+            });
+        challenger.observe(permutation_commit.clone());
+        challenger.observe_ext_element(sum);
+        (permutation_commit, permutation_data)
+    });
+
+    #[cfg(debug_assertions)]
+    if let Err(failure) = crate::check_constraints::check_constraints_with_stages(
+        air,
+        preprocessed.map(|preprocessed| &preprocessed.trace),
+        &traces[0],
+        &traces[1..],
+        &stage_challenges,
+        public_values,
+        selector_height,
+    ) {
+        panic!("{failure}");
+    }
+    drop(traces);
+
     let alpha: SC::Challenge = challenger.sample_ext_element();
 
     let quotient_domain =
         trace_domain.create_disjoint_domain(1 << (log_degree + log_quotient_degree));
 
-    let trace_on_quotient_domain = pcs.get_evaluations_on_domain(&trace_data, 0, quotient_domain);
+    let (quotient_commit, quotient_data) = timed(&mut metrics, Phase::Quotient, || {
+        let trace_on_quotient_domain =
+            pcs.get_evaluations_on_domain(&trace_data, 0, quotient_domain);
+        let preprocessed_on_quotient_domain = preprocessed.map(|preprocessed| {
+            pcs.get_evaluations_on_domain(&preprocessed.prover_data, 0, quotient_domain)
+        });
+        let permutation_on_quotient_domain = permutation
+            .as_ref()
+            .map(|(_, data)| pcs.get_evaluations_on_domain(data, 0, quotient_domain));
+        let stages_on_quotient_domain = stage_data
+            .iter()
+            .map(|data| pcs.get_evaluations_on_domain(data, 0, quotient_domain))
+            .collect_vec();
 
-    let quotient_values = quotient_values(
-        air,
-        public_values,
-        trace_domain,
-        quotient_domain,
-        trace_on_quotient_domain,
-        alpha,
-        constraint_count,
-    );
-    let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
-    let quotient_chunks = quotient_domain.split_evals(quotient_degree, quotient_flat);
-    let qc_domains = quotient_domain.split_domains(quotient_degree);
+        let sels = match config.selector_cache() {
+            Some(cache) => {
+                cache.selectors_on_coset_for_height(trace_domain, quotient_domain, selector_height)
+            }
+            None => Arc::new(
+                trace_domain.selectors_on_coset_for_height(quotient_domain, selector_height),
+            ),
+        };
+        let quotient_values = quotient_values(
+            air,
+            public_values,
+            &window_offsets,
+            &periodic_columns,
+            trace_domain,
+            &sels,
+            quotient_domain,
+            preprocessed_on_quotient_domain,
+            trace_on_quotient_domain,
+            stages_on_quotient_domain,
+            &stage_challenges,
+            permutation_on_quotient_domain,
+            &permutation_challenges,
+            cumulative_sum.unwrap_or(SC::Challenge::ZERO),
+            alpha,
+            constraint_count,
+        );
+        let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
+        let quotient_chunks = quotient_domain.split_evals(quotient_degree, quotient_flat);
+        let qc_domains = quotient_domain.split_domains(quotient_degree);
 
-    let (quotient_commit, quotient_data) = info_span!("commit to quotient poly chunks")
-        .in_scope(|| pcs.commit(izip!(qc_domains, quotient_chunks).collect_vec()));
+        info_span!("commit to quotient poly chunks").in_scope(|| {
+            pcs.commit_quotient(vec![izip!(qc_domains, quotient_chunks).collect_vec()])
+        })
+    });
     challenger.observe(quotient_commit.clone());
 
+    let (permutation_commit, permutation_data) = permutation.unzip();
     let commitments = Commitments {
         trace: trace_commit,
+        stages: stage_commits,
+        permutation: permutation_commit,
         quotient_chunks: quotient_commit,
     };
 
     let zeta: SC::Challenge = challenger.sample();
-    let zeta_next = trace_domain.next_point(zeta).unwrap();
-
-    let (opened_values, opening_proof) = info_span!("open").in_scope(|| {
-        pcs.open(
-            vec![
-                (&trace_data, vec![vec![zeta, zeta_next]]),
-                (
-                    &quotient_data,
-                    // open every chunk at zeta
-                    (0..quotient_degree).map(|_| vec![zeta]).collect_vec(),
-                ),
-            ],
-            challenger,
-        )
+    let window_points = trace_domain.rotated_points(zeta, &window_offsets).unwrap();
+    let trace_points = trace_domain.rotated_points(zeta, &TRACE_ROTATIONS).unwrap();
+
+    let mut rounds = vec![
+        (&trace_data, vec![window_points]),
+        (
+            &quotient_data,
+            // open every chunk at zeta
+            (0..quotient_degree).map(|_| vec![zeta]).collect_vec(),
+        ),
+    ];
+    if let Some(preprocessed) = preprocessed {
+        rounds.push((&preprocessed.prover_data, vec![trace_points.clone()]));
+    }
+    if let Some(permutation_data) = &permutation_data {
+        rounds.push((permutation_data, vec![trace_points.clone()]));
+    }
+    for data in &stage_data {
+        rounds.push((data, vec![trace_points.clone()]));
+    }
+    let (opened_values, opening_proof) = timed(&mut metrics, Phase::Open, || {
+        info_span!("open").in_scope(|| pcs.open(rounds, challenger))
     });
-    let trace_local = opened_values[0][0][0].clone();
-    let trace_next = opened_values[0][0][1].clone();
+    let trace_window = opened_values[0][0].clone();
     let quotient_chunks = opened_values[1].iter().map(|v| v[0].clone()).collect_vec();
+    // The optional rounds follow, in order.
+    let mut next_round = 2;
+    let mut optional_round = |present: bool| {
+        if !present {
+            return (vec![], vec![]);
+        }
+        let round = &opened_values[next_round];
+        next_round += 1;
+        (round[0][0].clone(), round[0][1].clone())
+    };
+    let (preprocessed_local, preprocessed_next) = optional_round(preprocessed.is_some());
+    let (permutation_local, permutation_next) = optional_round(permutation_data.is_some());
+    let (stages_local, stages_next) = stage_data.iter().map(|_| optional_round(true)).unzip();
     let opened_values = OpenedValues {
-        trace_local,
-        trace_next,
+        preprocessed_local,
+        preprocessed_next,
+        trace_window,
+        stages_local,
+        stages_next,
+        permutation_local,
+        permutation_next,
         quotient_chunks,
     };
     Proof {
@@ -120,16 +451,28 @@ where
         opened_values,
         opening_proof,
         degree_bits: log_degree,
+        trace_height,
+        cumulative_sum,
     }
 }
 
 #[instrument(name = "compute quotient polynomial", skip_all)]
+#[allow(clippy::too_many_arguments)]
 fn quotient_values<SC, A, Mat>(
     air: &A,
     public_values: &Vec<Val<SC>>,
+    window_offsets: &[isize],
+    periodic_columns: &[Vec<Val<SC>>],
     trace_domain: Domain<SC>,
+    sels: &LagrangeSelectors<Vec<Val<SC>>>,
     quotient_domain: Domain<SC>,
+    preprocessed_on_quotient_domain: Option<Mat>,
     trace_on_quotient_domain: Mat,
+    stages_on_quotient_domain: Vec<Mat>,
+    stage_challenges: &[Vec<SC::Challenge>],
+    permutation_on_quotient_domain: Option<Mat>,
+    permutation_challenges: &[SC::Challenge],
+    cumulative_sum: SC::Challenge,
     alpha: SC::Challenge,
     constraint_count: usize,
 ) -> Vec<SC::Challenge>
@@ -140,61 +483,128 @@ where
 {
     let quotient_size = quotient_domain.size();
     let width = trace_on_quotient_domain.width();
-    let mut sels = trace_domain.selectors_on_coset(quotient_domain);
+    let preprocessed_width = preprocessed_on_quotient_domain
+        .as_ref()
+        .map_or(0, Matrix::width);
+    let ext_degree = <SC::Challenge as AbstractExtensionField<Val<SC>>>::D;
+    let permutation_width = permutation_on_quotient_domain
+        .as_ref()
+        .map_or(0, |perm| perm.width() / ext_degree);
+    let permutation_challenges = permutation_challenges
+        .iter()
+        .map(|&c| PackedChallenge::<SC>::from_f(c))
+        .collect_vec();
+    let cumulative_sum = PackedChallenge::<SC>::from_f(cumulative_sum);
+    let stage_challenges = stage_challenges
+        .iter()
+        .map(|challenges| {
+            challenges
+                .iter()
+                .map(|&c| PackedChallenge::<SC>::from_f(c))
+                .collect_vec()
+        })
+        .collect_vec();
+
+    let periodic_on_quotient_domain = periodic_columns
+        .iter()
+        .map(|column| trace_domain.periodic_column_on_coset(column, quotient_domain))
+        .collect_vec();
 
     let qdb = log2_strict_usize(quotient_domain.size()) - log2_strict_usize(trace_domain.size());
     let next_step = 1 << qdb;
 
-    // We take PackedVal::<SC>::WIDTH worth of values at a time from a quotient_size slice, so we need to
-    // pad with default values in the case where quotient_size is smaller than PackedVal::<SC>::WIDTH.
-    for _ in quotient_size..PackedVal::<SC>::WIDTH {
-        sels.is_first_row.push(Val::<SC>::default());
-        sels.is_last_row.push(Val::<SC>::default());
-        sels.is_transition.push(Val::<SC>::default());
-        sels.inv_zeroifier.push(Val::<SC>::default());
-    }
-
     let mut alpha_powers = alpha.powers().take(constraint_count).collect_vec();
     alpha_powers.reverse();
+    // Broadcast once, rather than for every constraint on every packing of rows.
+    let alpha_powers = alpha_powers
+        .into_iter()
+        .map(PackedChallenge::<SC>::from_f)
+        .collect_vec();
 
     (0..quotient_size)
         .into_par_iter()
         .step_by(PackedVal::<SC>::WIDTH)
         .flat_map_iter(|i_start| {
-            let i_range = i_start..i_start + PackedVal::<SC>::WIDTH;
-
-            let is_first_row = *PackedVal::<SC>::from_slice(&sels.is_first_row[i_range.clone()]);
-            let is_last_row = *PackedVal::<SC>::from_slice(&sels.is_last_row[i_range.clone()]);
-            let is_transition = *PackedVal::<SC>::from_slice(&sels.is_transition[i_range.clone()]);
-            let inv_zeroifier = *PackedVal::<SC>::from_slice(&sels.inv_zeroifier[i_range.clone()]);
+            let is_first_row = packed_window::<PackedVal<SC>>(&sels.is_first_row, i_start);
+            let is_last_row = packed_window::<PackedVal<SC>>(&sels.is_last_row, i_start);
+            let is_transition = packed_window::<PackedVal<SC>>(&sels.is_transition, i_start);
+            let inv_zeroifier = packed_window::<PackedVal<SC>>(&sels.inv_zeroifier, i_start);
 
             let main = RowMajorMatrix::new(
-                trace_on_quotient_domain.vertically_packed_row_pair(i_start, next_step),
+                trace_on_quotient_domain.vertically_packed_rows(i_start, next_step, window_offsets),
                 width,
             );
 
+            let preprocessed = RowMajorMatrix::new(
+                preprocessed_on_quotient_domain
+                    .as_ref()
+                    .map(|prep| prep.vertically_packed_row_pair(i_start, next_step))
+                    .unwrap_or_default(),
+                preprocessed_width,
+            );
+
+            let stages = stages_on_quotient_domain
+                .iter()
+                .map(|stage| {
+                    RowMajorMatrix::new(
+                        stage.vertically_packed_row_pair(i_start, next_step),
+                        stage.width(),
+                    )
+                })
+                .collect_vec();
+
+            // The LogUp trace is committed to as its base field columns.
+            let permutation = RowMajorMatrix::new(
+                permutation_on_quotient_domain
+                    .as_ref()
+                    .map(|perm| {
+                        perm.vertically_packed_row_pair::<PackedVal<SC>>(i_start, next_step)
+                            .chunks_exact(ext_degree)
+                            .map(PackedChallenge::<SC>::from_base_slice)
+                            .collect_vec()
+                    })
+                    .unwrap_or_default(),
+                permutation_width,
+            );
+
+            let periodic_values = periodic_on_quotient_domain
+                .iter()
+                .map(|column| packed_window::<PackedVal<SC>>(column, i_start))
+                .collect_vec();
+
             let accumulator = PackedChallenge::<SC>::ZERO;
             let mut folder = ProverConstraintFolder {
+                preprocessed: preprocessed.as_view(),
                 main: main.as_view(),
+                stages: stages.iter().map(RowMajorMatrix::as_view).collect(),
+                stage_challenges: &stage_challenges,
+                permutation: permutation.as_view(),
+                permutation_challenges: &permutation_challenges,
+                lookups: vec![],
+                periodic_values: &periodic_values,
                 public_values,
                 is_first_row,
                 is_last_row,
                 is_transition,
+                selectors: sels,
+                quotient_index: i_start,
+                quotient_step: next_step,
                 alpha_powers: &alpha_powers,
                 accumulator,
                 constraint_index: 0,
             };
             air.eval(&mut folder);
+            if permutation_on_quotient_domain.is_some() {
+                let lookups = mem::take(&mut folder.lookups);
+                eval_logup(&mut folder, &lookups, cumulative_sum);
+            }
 
             // quotient(x) = constraints(x) / Z_H(x)
             let quotient = folder.accumulator * inv_zeroifier;
 
-            // "Transpose" D packed base coefficients into WIDTH scalar extension coefficients.
-            (0..core::cmp::min(quotient_size, PackedVal::<SC>::WIDTH)).map(move |idx_in_packing| {
-                SC::Challenge::from_base_fn(|coeff_idx| {
-                    quotient.as_base_slice()[coeff_idx].as_slice()[idx_in_packing]
-                })
-            })
+            // Unpack the lanes, dropping any past the end of the quotient domain.
+            let num_lanes = PackedVal::<SC>::WIDTH.min(quotient_size - i_start);
+            (0..num_lanes).map(move |lane| SC::Challenge::unpack_lane(&quotient, lane))
         })
         .collect()
 }
@@ -0,0 +1,92 @@
+use p3_air::Air;
+use p3_commit::{Pcs, PolynomialSpace};
+
+use crate::stages::StageLayout;
+use crate::symbolic_builder::{
+    get_symbolic_constraints_and_lookups, log_quotient_degree_for_padding, max_constraint_degree,
+};
+use crate::{StarkGenericConfig, SymbolicAirBuilder, Val};
+
+/// The quotient of an AIR has more chunks than the PCS can evaluate the trace for, e.g. because
+/// the FRI blowup is smaller than the number of chunks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InsufficientBlowupError {
+    /// The log of the number of quotient chunks.
+    pub log_quotient_degree: usize,
+    /// The PCS's `max_log_evaluation_blowup`.
+    pub max_log_blowup: usize,
+}
+
+/// The log of the number of chunks `prove` splits the quotient of `air` into with `config`, for a
+/// trace of any height, inferred from the degree of its constraints.
+///
+/// Returns an error if the PCS of `config` can't evaluate the trace on a domain that many times
+/// larger, which `prove` would otherwise only find out once it has committed to the trace.
+pub fn get_log_quotient_degree_for_config<SC, A>(
+    config: &SC,
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+) -> Result<usize, InsufficientBlowupError>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>>,
+{
+    let constraint_degree =
+        get_max_constraint_degree_for_config(config, air, preprocessed_width, num_public_values);
+    // Padding which shortens the selectors raises the quotient degree of traces whose height
+    // isn't a power of two, so assume the trace is one of those.
+    let selector_height = config.trace_padding().selector_height(1, 2);
+    let log_quotient_degree =
+        log_quotient_degree_for_padding(constraint_degree, selector_height, 2, config.is_zk());
+    check_log_quotient_degree(config, log_quotient_degree)?;
+    Ok(log_quotient_degree)
+}
+
+/// Like `get_max_constraint_degree`, but over the trace domains of `config`'s PCS. Where
+/// `is_transition` isn't linear, it adds to the degree of the constraints it selects.
+pub fn get_max_constraint_degree_for_config<SC, A>(
+    config: &SC,
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+) -> usize
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>>,
+{
+    let (constraints, lookups) = get_symbolic_constraints_and_lookups(
+        air,
+        preprocessed_width,
+        num_public_values,
+        &StageLayout::default(),
+        has_linear_transition_selector(config),
+    );
+    max_constraint_degree(&constraints, &lookups)
+}
+
+/// Whether the trace domains of `config`'s PCS have a linear `is_transition`, as in
+/// `PolynomialSpace::has_linear_transition_selector`.
+pub(crate) fn has_linear_transition_selector<SC: StarkGenericConfig>(config: &SC) -> bool {
+    config
+        .pcs()
+        .natural_domain_for_degree(2)
+        .has_linear_transition_selector()
+}
+
+/// Check that the PCS of `config` can evaluate a trace on a domain `2^log_quotient_degree` times
+/// larger, as computing the quotient needs.
+pub fn check_log_quotient_degree<SC: StarkGenericConfig>(
+    config: &SC,
+    log_quotient_degree: usize,
+) -> Result<(), InsufficientBlowupError> {
+    match config.pcs().max_log_evaluation_blowup() {
+        Some(max_log_blowup) if log_quotient_degree > max_log_blowup => {
+            Err(InsufficientBlowupError {
+                log_quotient_degree,
+                max_log_blowup,
+            })
+        }
+        _ => Ok(()),
+    }
+}
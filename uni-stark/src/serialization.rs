@@ -0,0 +1,38 @@
+//! A stable byte encoding of uni-stark proofs.
+//!
+//! An encoded proof starts with [`STARK_PROOF_MAGIC`] and [`STARK_FORMAT_VERSION`]. The payload
+//! uses the canonical encoding from `p3_util::canonical_serialization`, so every dimension is
+//! explicit: each vector of commitments, opened rows or values is prefixed by its length as a
+//! `u32`, and the fields of `Proof`, `Commitments` and `OpenedValues` are written in declaration
+//! order. Commitments and the opening proof are encoded by the PCS's own `Serialize`
+//! implementations; see e.g. `p3_fri::FRI_FORMAT_VERSION` for those of `TwoAdicFriPcs`.
+//!
+//! Any change to the layout of the types encoded here must bump [`STARK_FORMAT_VERSION`].
+
+use alloc::vec::Vec;
+
+use p3_util::canonical_serialization::{
+    from_bytes_with_header, to_bytes_with_header, SerializationError,
+};
+
+use crate::{Proof, StarkGenericConfig};
+
+/// The version of the proof encoding produced by this module.
+pub const STARK_FORMAT_VERSION: u16 = 1;
+
+/// Magic prefix of an encoded [`Proof`].
+pub const STARK_PROOF_MAGIC: [u8; 4] = *b"P3SP";
+
+impl<SC: StarkGenericConfig> Proof<SC> {
+    /// Encode this proof in the canonical, versioned format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        to_bytes_with_header(STARK_PROOF_MAGIC, STARK_FORMAT_VERSION, self)
+    }
+
+    /// Decode a proof produced by [`Proof::to_bytes`].
+    ///
+    /// This only checks that the bytes are well formed; the proof's shape is checked by `verify`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        from_bytes_with_header(STARK_PROOF_MAGIC, STARK_FORMAT_VERSION, bytes)
+    }
+}
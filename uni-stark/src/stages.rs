@@ -0,0 +1,75 @@
+use alloc::vec::Vec;
+
+use p3_air::MultiStageAir;
+use p3_field::{ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+
+/// The trace of a stage after the main trace, as generated for `prove_multi_stage`: base field
+/// columns, or extension field columns, which are flattened to `D` base field columns each before
+/// they're committed to, for a stage which `is_extension_stage`.
+#[derive(Clone, Debug)]
+pub enum StageTrace<F, EF> {
+    Base(RowMajorMatrix<F>),
+    Extension(RowMajorMatrix<EF>),
+}
+
+impl<F: Field, EF: ExtensionField<F>> StageTrace<F, EF> {
+    /// The base field columns which are committed to.
+    pub fn flatten(self) -> RowMajorMatrix<F> {
+        match self {
+            Self::Base(trace) => trace,
+            Self::Extension(trace) => trace.flatten_to_base(),
+        }
+    }
+}
+
+impl<F, EF> From<RowMajorMatrix<F>> for StageTrace<F, EF> {
+    fn from(trace: RowMajorMatrix<F>) -> Self {
+        Self::Base(trace)
+    }
+}
+
+/// The shapes of the stages of a `MultiStageAir` after the main trace.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StageLayout {
+    /// The width of each stage after the main trace, in base field columns.
+    pub(crate) widths: Vec<usize>,
+    /// The number of challenges sampled before each stage after the main trace.
+    pub(crate) num_challenges: Vec<usize>,
+    /// The number of base field columns of each extension field column.
+    pub(crate) ext_degree: usize,
+}
+
+impl StageLayout {
+    /// The layout of the stages of `air`, whose extension field columns have `ext_degree` base
+    /// field columns each.
+    pub(crate) fn of<F, A: MultiStageAir<F>>(air: &A, ext_degree: usize) -> Self {
+        let stages = 1..air.num_stages();
+        Self {
+            widths: stages
+                .clone()
+                .map(|stage| {
+                    let width = air.stage_width(stage);
+                    if air.is_extension_stage(stage) {
+                        width * ext_degree
+                    } else {
+                        width
+                    }
+                })
+                .collect(),
+            num_challenges: stages
+                .map(|stage| air.num_stage_challenges(stage))
+                .collect(),
+            ext_degree,
+        }
+    }
+
+    /// The number of stages after the main trace.
+    pub(crate) fn len(&self) -> usize {
+        self.widths.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.widths.is_empty()
+    }
+}
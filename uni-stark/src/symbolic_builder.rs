@@ -1,12 +1,19 @@
+use alloc::string::{String, ToString};
 use alloc::vec;
 use alloc::vec::Vec;
 
-use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, PairBuilder};
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithLookups, AirBuilderWithPublicValues, AirBuilderWithRowSelectors,
+    ExtensionBuilder, Lookup, MultiStageAirBuilder, PairBuilder, PeriodicAirBuilder,
+};
 use p3_field::Field;
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
 use p3_util::log2_ceil_usize;
 use tracing::instrument;
 
+use crate::lookup::logup_constraint_degree;
+use crate::stages::StageLayout;
 use crate::symbolic_expression::SymbolicExpression;
 use crate::symbolic_variable::SymbolicVariable;
 use crate::Entry;
@@ -31,6 +38,36 @@ where
     log2_ceil_usize(constraint_degree - 1)
 }
 
+/// The log of the quotient degree, given the maximum constraint degree (as in
+/// `get_max_constraint_degree`), for a trace whose selectors are those for `selector_height`
+/// rows of a domain of size `degree`; see `TracePadding`. `is_zk` is whether the trace is committed
+/// to with a zero-knowledge PCS, as in `Pcs::ZK`.
+pub fn log_quotient_degree_for_padding(
+    constraint_degree: usize,
+    selector_height: usize,
+    degree: usize,
+    is_zk: bool,
+) -> usize {
+    // We pad to at least degree 2, as in `get_log_quotient_degree`.
+    let mut constraint_degree = constraint_degree.max(2);
+    if is_zk {
+        // The committed polynomials are blinded to degree 2n, so the numerator has degree up to
+        // 2dn, or (2d + 1)n with a padded trace's `is_transition`, which the constraint degree
+        // doesn't count, and the quotient up to 2dn. As 2d - 1 is odd, rounding it up to a power
+        // of two gives at least 2d chunks of n coefficients, which covers both.
+        return log2_ceil_usize(2 * constraint_degree - 1);
+    }
+    if selector_height < degree {
+        // `is_transition` is then quadratic rather than linear. With a transition constraint of
+        // degree 2, the numerator has degree 2n, so the quotient no longer fits in n
+        // coefficients. Higher degrees already leave enough room.
+        constraint_degree = constraint_degree.max(3);
+    }
+    log2_ceil_usize(constraint_degree - 1)
+}
+
+/// The maximum degree of the constraints, as a multiple of the trace length, over a domain with a
+/// linear `is_transition`; see `get_max_constraint_degree_for_config` for other domains.
 #[instrument(name = "infer constraint degree", skip_all, level = "debug")]
 pub fn get_max_constraint_degree<F, A>(
     air: &A,
@@ -41,11 +78,26 @@ where
     F: Field,
     A: Air<SymbolicAirBuilder<F>>,
 {
-    get_symbolic_constraints(air, preprocessed_width, num_public_values)
+    let (constraints, lookups) = get_symbolic_constraints_and_lookups(
+        air,
+        preprocessed_width,
+        num_public_values,
+        &StageLayout::default(),
+        true,
+    );
+    max_constraint_degree(&constraints, &lookups)
+}
+
+pub(crate) fn max_constraint_degree<F>(
+    constraints: &[SymbolicExpression<F>],
+    lookups: &[Lookup<SymbolicExpression<F>>],
+) -> usize {
+    constraints
         .iter()
         .map(|c| c.degree_multiple())
         .max()
         .unwrap_or(0)
+        .max(logup_constraint_degree(lookups))
 }
 
 #[instrument(name = "evaluate constraints symbolically", skip_all, level = "debug")]
@@ -58,9 +110,106 @@ where
     F: Field,
     A: Air<SymbolicAirBuilder<F>>,
 {
-    let mut builder = SymbolicAirBuilder::new(preprocessed_width, air.width(), num_public_values);
+    get_symbolic_constraints_and_lookups(
+        air,
+        preprocessed_width,
+        num_public_values,
+        &StageLayout::default(),
+        true,
+    )
+    .0
+}
+
+/// The lookups of the AIR, declared with `AirBuilderWithLookups`, in terms of its columns.
+pub fn get_symbolic_lookups<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+) -> Vec<Lookup<SymbolicExpression<F>>>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    get_symbolic_constraints_and_lookups(
+        air,
+        preprocessed_width,
+        num_public_values,
+        &StageLayout::default(),
+        true,
+    )
+    .1
+}
+
+/// The name of each constraint of the AIR, in the order of `get_symbolic_constraints`, as given
+/// with `AirBuilder::name_constraints` or `AirBuilder::assert_zero_named`. This identifies a
+/// constraint by its index, e.g. from `ConstraintFailure`.
+pub fn get_constraint_names<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+) -> Vec<Option<String>>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    eval_symbolically(
+        air,
+        preprocessed_width,
+        num_public_values,
+        &StageLayout::default(),
+        true,
+    )
+    .constraint_names
+}
+
+/// The constraints and lookups of the AIR. `linear_transition` is whether the trace domain has a
+/// linear `is_transition`, as in `PolynomialSpace::has_linear_transition_selector`.
+pub(crate) fn get_symbolic_constraints_and_lookups<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+    stages: &StageLayout,
+    linear_transition: bool,
+) -> (
+    Vec<SymbolicExpression<F>>,
+    Vec<Lookup<SymbolicExpression<F>>>,
+)
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    let builder = eval_symbolically(
+        air,
+        preprocessed_width,
+        num_public_values,
+        stages,
+        linear_transition,
+    );
+    (builder.constraints, builder.lookups)
+}
+
+pub(crate) fn eval_symbolically<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+    stages: &StageLayout,
+    linear_transition: bool,
+) -> SymbolicAirBuilder<F>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    let mut builder = SymbolicAirBuilder::new(
+        preprocessed_width,
+        air.width(),
+        air.window_offsets().len(),
+        air.periodic_columns().len(),
+        num_public_values,
+    )
+    .with_stages(stages)
+    .with_linear_transition(linear_transition);
     air.eval(&mut builder);
-    builder.constraints()
+    builder
 }
 
 /// An `AirBuilder` for evaluating constraints symbolically, and recording them for later use.
@@ -68,12 +217,33 @@ where
 pub struct SymbolicAirBuilder<F: Field> {
     preprocessed: RowMajorMatrix<SymbolicVariable<F>>,
     main: RowMajorMatrix<SymbolicVariable<F>>,
+    /// The stages after the main trace.
+    stages: Vec<RowMajorMatrix<SymbolicVariable<F>>>,
+    stage_challenges: Vec<Vec<SymbolicVariable<F>>>,
+    /// The number of base field columns of each extension field column of a stage.
+    stage_ext_degree: usize,
+    periodic_values: Vec<SymbolicVariable<F>>,
     public_values: Vec<SymbolicVariable<F>>,
     constraints: Vec<SymbolicExpression<F>>,
+    /// The name of each constraint, if any.
+    constraint_names: Vec<Option<String>>,
+    /// The name from the last `name_constraints`.
+    constraint_name: Option<String>,
+    lookups: Vec<Lookup<SymbolicExpression<F>>>,
+    /// Whether `is_transition` is linear on the trace domain; see `is_transition_window`.
+    linear_transition: bool,
 }
 
 impl<F: Field> SymbolicAirBuilder<F> {
-    pub(crate) fn new(preprocessed_width: usize, width: usize, num_public_values: usize) -> Self {
+    /// A builder whose main trace has a window of `window_size` rows, as in
+    /// `BaseAir::window_offsets`.
+    pub(crate) fn new(
+        preprocessed_width: usize,
+        width: usize,
+        window_size: usize,
+        num_periodic_columns: usize,
+        num_public_values: usize,
+    ) -> Self {
         let prep_values = [0, 1]
             .into_iter()
             .flat_map(|offset| {
@@ -81,25 +251,95 @@ impl<F: Field> SymbolicAirBuilder<F> {
                     .map(move |index| SymbolicVariable::new(Entry::Preprocessed { offset }, index))
             })
             .collect();
-        let main_values = [0, 1]
-            .into_iter()
+        let main_values = (0..window_size)
             .flat_map(|offset| {
                 (0..width).map(move |index| SymbolicVariable::new(Entry::Main { offset }, index))
             })
             .collect();
+        let periodic_values = (0..num_periodic_columns)
+            .map(|index| SymbolicVariable::new(Entry::Periodic, index))
+            .collect();
         let public_values = (0..num_public_values)
             .map(move |index| SymbolicVariable::new(Entry::Public, index))
             .collect();
         Self {
             preprocessed: RowMajorMatrix::new(prep_values, preprocessed_width),
             main: RowMajorMatrix::new(main_values, width),
+            stages: vec![],
+            stage_challenges: vec![],
+            stage_ext_degree: 1,
+            periodic_values,
             public_values,
             constraints: vec![],
+            constraint_names: vec![],
+            constraint_name: None,
+            lookups: vec![],
+            linear_transition: true,
         }
     }
 
-    pub(crate) fn constraints(self) -> Vec<SymbolicExpression<F>> {
-        self.constraints
+    /// Set whether `is_transition` is linear on the trace domain, as in
+    /// `PolynomialSpace::has_linear_transition_selector`.
+    pub(crate) const fn with_linear_transition(mut self, linear_transition: bool) -> Self {
+        self.linear_transition = linear_transition;
+        self
+    }
+
+    /// Add the stages after the main trace, with their challenges.
+    pub(crate) fn with_stages(mut self, stages: &StageLayout) -> Self {
+        self.stages = stages
+            .widths
+            .iter()
+            .enumerate()
+            .map(|(i, &width)| {
+                let values = [0, 1]
+                    .into_iter()
+                    .flat_map(|offset| {
+                        (0..width).map(move |index| {
+                            SymbolicVariable::new(
+                                Entry::Stage {
+                                    stage: i + 1,
+                                    offset,
+                                },
+                                index,
+                            )
+                        })
+                    })
+                    .collect();
+                RowMajorMatrix::new(values, width)
+            })
+            .collect();
+        let mut index = 0;
+        self.stage_challenges = stages
+            .num_challenges
+            .iter()
+            .map(|&num_challenges| {
+                let challenges = (index..index + num_challenges)
+                    .map(|i| SymbolicVariable::new(Entry::Challenge, i))
+                    .collect();
+                index += num_challenges;
+                challenges
+            })
+            .collect();
+        self.stage_ext_degree = stages.ext_degree.max(1);
+        self
+    }
+
+    /// The constraints, the lookups and the name of each constraint.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn into_parts(
+        self,
+    ) -> (
+        Vec<SymbolicExpression<F>>,
+        Vec<Lookup<SymbolicExpression<F>>>,
+        Vec<Option<String>>,
+    ) {
+        (self.constraints, self.lookups, self.constraint_names)
+    }
+
+    fn push_constraint(&mut self, constraint: SymbolicExpression<F>) {
+        self.constraints.push(constraint);
+        self.constraint_names.push(self.constraint_name.clone());
     }
 }
 
@@ -121,16 +361,28 @@ impl<F: Field> AirBuilder for SymbolicAirBuilder<F> {
         SymbolicExpression::IsLastRow
     }
 
+    /// Over a domain whose `is_transition` isn't linear, it's given as a window of two rows, so that
+    /// it adds to the degree of the constraints it selects.
     fn is_transition_window(&self, size: usize) -> Self::Expr {
-        if size == 2 {
-            SymbolicExpression::IsTransition
-        } else {
-            panic!("uni-stark only supports a window size of 2")
+        match size {
+            0 | 1 => SymbolicExpression::Constant(F::ONE),
+            2 if self.linear_transition => SymbolicExpression::IsTransition,
+            _ => SymbolicExpression::IsTransitionWindow(size),
         }
     }
 
     fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
-        self.constraints.push(x.into());
+        self.push_constraint(x.into());
+    }
+
+    fn name_constraints(&mut self, name: &str) {
+        self.constraint_name = Some(name.to_string());
+    }
+
+    fn assert_zero_named<I: Into<Self::Expr>>(&mut self, name: &str, x: I) {
+        let group_name = self.constraint_name.replace(name.to_string());
+        self.push_constraint(x.into());
+        self.constraint_name = group_name;
     }
 }
 
@@ -141,8 +393,82 @@ impl<F: Field> AirBuilderWithPublicValues for SymbolicAirBuilder<F> {
     }
 }
 
+impl<F: Field> PeriodicAirBuilder for SymbolicAirBuilder<F> {
+    type PeriodicVar = SymbolicVariable<F>;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        &self.periodic_values
+    }
+}
+
+impl<F: Field> AirBuilderWithRowSelectors for SymbolicAirBuilder<F> {
+    fn is_row(&self, row: usize) -> Self::Expr {
+        SymbolicExpression::IsRow(row)
+    }
+}
+
 impl<F: Field> PairBuilder for SymbolicAirBuilder<F> {
     fn preprocessed(&self) -> Self::M {
         self.preprocessed.clone()
     }
 }
+
+impl<F: Field> AirBuilderWithLookups for SymbolicAirBuilder<F> {
+    fn lookup(&mut self, lookup: Lookup<Self::Expr>) {
+        self.lookups.push(lookup);
+    }
+}
+
+/// Symbolically, extension field expressions are just expressions, as the degrees are the same.
+impl<F: Field> ExtensionBuilder for SymbolicAirBuilder<F> {
+    type EF = F;
+    type ExprEF = SymbolicExpression<F>;
+    type VarEF = SymbolicVariable<F>;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.push_constraint(x.into());
+    }
+
+    /// Symbolically there's no basis to multiply by, so this is the sum of the coefficients, which
+    /// has the same degree.
+    fn ext_from_base<I>(&self, coeffs: I) -> Self::ExprEF
+    where
+        I: IntoIterator<Item = Self::Expr>,
+    {
+        coeffs.into_iter().sum()
+    }
+}
+
+impl<F: Field> MultiStageAirBuilder for SymbolicAirBuilder<F> {
+    type Challenge = SymbolicVariable<F>;
+
+    fn stage(&self, stage: usize) -> Self::M {
+        match stage {
+            0 => self.main.clone(),
+            _ => self.stages[stage - 1].clone(),
+        }
+    }
+
+    fn stage_challenges(&self, stage: usize) -> &[Self::Challenge] {
+        match stage {
+            0 => &[],
+            _ => &self.stage_challenges[stage - 1],
+        }
+    }
+
+    /// Symbolically `EF` is `F`, so this groups the columns by the extension degree of the
+    /// stages instead.
+    fn stage_ext(&self, stage: usize) -> [Vec<Self::ExprEF>; 2] {
+        let columns = self.stage(stage);
+        [0, 1].map(|row| {
+            columns
+                .row_slice(row)
+                .chunks_exact(self.stage_ext_degree)
+                .map(|coeffs| self.ext_from_base(coeffs.iter().map(|&c| c.into())))
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,186 @@
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use hashbrown::HashMap;
+use p3_air::{Air, BaseAir, Lookup};
+use p3_field::{AbstractField, Field};
+
+use crate::stages::StageLayout;
+use crate::symbolic_builder::{eval_symbolically, SymbolicAirBuilder};
+use crate::symbolic_expression::SymbolicExpression;
+use crate::Entry;
+
+/// A node of a `SymbolicDag`. The operands of an operation are the indices of earlier nodes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SymbolicNode<F> {
+    /// Column `index` of `entry`, as in `SymbolicVariable`.
+    Variable {
+        entry: Entry,
+        index: usize,
+    },
+    IsFirstRow,
+    IsLastRow,
+    IsTransition,
+    /// The selector of `AirBuilder::is_transition_window` for a window of more than two rows.
+    IsTransitionWindow(usize),
+    /// The selector of the given row, as in `AirBuilderWithRowSelectors::is_row`.
+    IsRow(usize),
+    Constant(F),
+    Add(usize, usize),
+    Sub(usize, usize),
+    Neg(usize),
+    Mul(usize, usize),
+}
+
+/// The constraints and lookups of an AIR as a DAG of symbolic expressions, for code generators
+/// such as recursive verifiers and circuit compilers to consume the AIR directly.
+///
+/// Equal subexpressions are a single node, so each node is distinct.
+#[derive(Clone, Debug)]
+pub struct SymbolicDag<F> {
+    pub preprocessed_width: usize,
+    /// The width of the main trace.
+    pub width: usize,
+    /// The rows of the main trace that `Entry::Main { offset }` refers to, as in
+    /// `BaseAir::window_offsets`.
+    pub window_offsets: Vec<isize>,
+    pub num_periodic_columns: usize,
+    pub num_public_values: usize,
+    /// The nodes, each after its operands.
+    pub nodes: Vec<SymbolicNode<F>>,
+    /// The node of each constraint, in the order of `get_symbolic_constraints`.
+    pub constraints: Vec<usize>,
+    /// The name of each constraint, as in `get_constraint_names`.
+    pub constraint_names: Vec<Option<String>>,
+    /// The lookups, with the node of each value and multiplicity.
+    pub lookups: Vec<Lookup<usize>>,
+}
+
+impl<F: Field> SymbolicDag<F> {
+    /// Evaluate the constraints, given the value of each leaf: a variable, a selector or a
+    /// constant.
+    pub fn eval_constraints<E: AbstractField>(
+        &self,
+        mut leaf: impl FnMut(&SymbolicNode<F>) -> E,
+    ) -> Vec<E> {
+        let mut values: Vec<E> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let value = match *node {
+                SymbolicNode::Add(x, y) => values[x].clone() + values[y].clone(),
+                SymbolicNode::Sub(x, y) => values[x].clone() - values[y].clone(),
+                SymbolicNode::Neg(x) => -values[x].clone(),
+                SymbolicNode::Mul(x, y) => values[x].clone() * values[y].clone(),
+                _ => leaf(node),
+            };
+            values.push(value);
+        }
+        self.constraints
+            .iter()
+            .map(|&node| values[node].clone())
+            .collect()
+    }
+}
+
+/// The constraints and lookups of `air` as a `SymbolicDag`.
+pub fn get_symbolic_dag<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+) -> SymbolicDag<F>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    let builder = eval_symbolically(
+        air,
+        preprocessed_width,
+        num_public_values,
+        &StageLayout::default(),
+        true,
+    );
+    let (constraints, lookups, constraint_names) = builder.into_parts();
+
+    let mut dag = DagBuilder::default();
+    let constraints = constraints.iter().map(|c| dag.add(c)).collect();
+    let lookups = lookups
+        .iter()
+        .map(|lookup| Lookup {
+            bus: lookup.bus,
+            values: lookup.values.iter().map(|v| dag.add(v)).collect(),
+            multiplicity: dag.add(&lookup.multiplicity),
+        })
+        .collect();
+
+    SymbolicDag {
+        preprocessed_width,
+        width: BaseAir::<F>::width(air),
+        window_offsets: air.window_offsets(),
+        num_periodic_columns: air.periodic_columns().len(),
+        num_public_values,
+        nodes: dag.nodes,
+        constraints,
+        constraint_names,
+        lookups,
+    }
+}
+
+/// Adds expressions to a DAG, merging equal nodes.
+struct DagBuilder<F> {
+    nodes: Vec<SymbolicNode<F>>,
+    indices: HashMap<SymbolicNode<F>, usize>,
+    /// The node of each subexpression already added, by address, so that subexpressions shared
+    /// with `Rc` are only visited once.
+    visited: HashMap<*const SymbolicExpression<F>, usize>,
+}
+
+impl<F> Default for DagBuilder<F> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            indices: HashMap::new(),
+            visited: HashMap::new(),
+        }
+    }
+}
+
+impl<F: Field> DagBuilder<F> {
+    fn add(&mut self, expr: &SymbolicExpression<F>) -> usize {
+        let node = match expr {
+            SymbolicExpression::Variable(v) => SymbolicNode::Variable {
+                entry: v.entry,
+                index: v.index,
+            },
+            SymbolicExpression::IsFirstRow => SymbolicNode::IsFirstRow,
+            SymbolicExpression::IsLastRow => SymbolicNode::IsLastRow,
+            SymbolicExpression::IsTransition => SymbolicNode::IsTransition,
+            SymbolicExpression::IsTransitionWindow(size) => SymbolicNode::IsTransitionWindow(*size),
+            SymbolicExpression::IsRow(row) => SymbolicNode::IsRow(*row),
+            SymbolicExpression::Constant(c) => SymbolicNode::Constant(*c),
+            SymbolicExpression::Add { x, y, .. } => {
+                SymbolicNode::Add(self.add_rc(x), self.add_rc(y))
+            }
+            SymbolicExpression::Sub { x, y, .. } => {
+                SymbolicNode::Sub(self.add_rc(x), self.add_rc(y))
+            }
+            SymbolicExpression::Neg { x, .. } => SymbolicNode::Neg(self.add_rc(x)),
+            SymbolicExpression::Mul { x, y, .. } => {
+                SymbolicNode::Mul(self.add_rc(x), self.add_rc(y))
+            }
+        };
+        *self.indices.entry(node).or_insert_with_key(|node| {
+            self.nodes.push(node.clone());
+            self.nodes.len() - 1
+        })
+    }
+
+    fn add_rc(&mut self, expr: &Rc<SymbolicExpression<F>>) -> usize {
+        let ptr = Rc::as_ptr(expr);
+        if let Some(&index) = self.visited.get(&ptr) {
+            return index;
+        }
+        let index = self.add(expr);
+        self.visited.insert(ptr, index);
+        index
+    }
+}
@@ -14,7 +14,13 @@ pub enum SymbolicExpression<F> {
     Variable(SymbolicVariable<F>),
     IsFirstRow,
     IsLastRow,
+    /// The selector of `AirBuilder::is_transition` over a domain where it's linear.
     IsTransition,
+    /// The selector of `AirBuilder::is_transition_window` for a window of more than two rows, or of
+    /// two rows over a domain where `is_transition` isn't linear.
+    IsTransitionWindow(usize),
+    /// The selector of the given row, as in `AirBuilderWithRowSelectors::is_row`.
+    IsRow(usize),
     Constant(F),
     Add {
         x: Rc<Self>,
@@ -44,7 +50,12 @@ impl<F> SymbolicExpression<F> {
             SymbolicExpression::Variable(v) => v.degree_multiple(),
             SymbolicExpression::IsFirstRow => 1,
             SymbolicExpression::IsLastRow => 1,
+            SymbolicExpression::IsRow(_) => 1,
             SymbolicExpression::IsTransition => 0,
+            // A product of `size - 1` transition selectors, which is small next to the trace
+            // length but isn't covered by padding the degree to 2 as `IsTransition` is. The same
+            // goes for a single selector which isn't linear.
+            SymbolicExpression::IsTransitionWindow(_) => 1,
             SymbolicExpression::Constant(_) => 0,
             SymbolicExpression::Add {
                 degree_multiple, ..
@@ -7,14 +7,29 @@ use crate::symbolic_expression::SymbolicExpression;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Entry {
-    Preprocessed { offset: usize },
-    Main { offset: usize },
-    Permutation { offset: usize },
+    Preprocessed {
+        offset: usize,
+    },
+    /// A column of the main trace, in the row of the window at index `offset`, i.e. at the
+    /// AIR's `window_offsets()[offset]`.
+    Main {
+        offset: usize,
+    },
+    Permutation {
+        offset: usize,
+    },
+    /// A column of a stage after the main trace, which is stage 0.
+    Stage {
+        stage: usize,
+        offset: usize,
+    },
     Public,
     Challenge,
+    /// A column of `BaseAir::periodic_columns`, on the current row.
+    Periodic,
 }
 
-/// A variable within the evaluation window, i.e. a column in either the local or next row.
+/// A variable within the evaluation window, i.e. a column in one of its rows.
 #[derive(Copy, Clone, Debug)]
 pub struct SymbolicVariable<F> {
     pub entry: Entry,
@@ -33,7 +48,11 @@ impl<F> SymbolicVariable<F> {
 
     pub const fn degree_multiple(&self) -> usize {
         match self.entry {
-            Entry::Preprocessed { .. } | Entry::Main { .. } | Entry::Permutation { .. } => 1,
+            Entry::Preprocessed { .. }
+            | Entry::Main { .. }
+            | Entry::Permutation { .. }
+            | Entry::Stage { .. }
+            | Entry::Periodic => 1,
             Entry::Public | Entry::Challenge => 0,
         }
     }
@@ -0,0 +1,141 @@
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+
+use p3_field::{Field, PackedValue};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
+use tracing::instrument;
+
+/// A trace made of a group of `rows_per_input` rows for each of a list of inputs, e.g. the rounds
+/// of a hash of each input, which `generate_trace` generates in parallel.
+///
+/// The inputs are generated in chunks of `F::Packing::WIDTH`, so that `generate_packed_rows` can
+/// compute a chunk in the lanes of packed field elements.
+///
+/// # Safety
+///
+/// `generate_trace` writes the trace to uninitialized memory and then reads it, so:
+/// - `generate_rows` and `generate_packed_rows` must write every value of `rows`.
+/// - `width` and `rows_per_input` must always return the same values.
+pub unsafe trait TraceGenerator<F: Field>: Sync {
+    type Input: Sync;
+
+    /// The number of columns of the trace.
+    fn width(&self) -> usize;
+
+    /// The number of rows generated from each input.
+    fn rows_per_input(&self) -> usize {
+        1
+    }
+
+    /// Write the `rows_per_input` rows of `input` to `rows`. Every value must be written, as
+    /// required by the trait's safety contract.
+    fn generate_rows(&self, input: &Self::Input, rows: &mut [MaybeUninit<F>]);
+
+    /// Write the rows of `inputs`, at most `F::Packing::WIDTH` of them, one group of rows after
+    /// another, to `rows`. Every value must be written, as required by the trait's safety contract.
+    ///
+    /// Defaults to `generate_rows` for each input. Implementations can instead compute each lane
+    /// of packed field elements from an input, and write them with `write_packed_column`.
+    fn generate_packed_rows(&self, inputs: &[Self::Input], rows: &mut [MaybeUninit<F>]) {
+        let group_len = self.rows_per_input() * self.width();
+        for (input, rows) in inputs.iter().zip(rows.chunks_exact_mut(group_len)) {
+            self.generate_rows(input, rows);
+        }
+    }
+}
+
+/// The trace of `inputs`, followed by copies of `padding`, if any, up to the next power of two
+/// height. The last group of rows is cut short if `rows_per_input` doesn't divide the height.
+///
+/// Without `padding`, the trace has `rows_per_input` rows per input, and `prove` pads it as its
+/// config's `TracePadding` does.
+///
+/// Panics if the generator's `width` or `rows_per_input` is zero.
+#[instrument(name = "generate trace", skip_all)]
+pub fn generate_trace<F, G>(
+    generator: &G,
+    inputs: &[G::Input],
+    padding: Option<&G::Input>,
+) -> RowMajorMatrix<F>
+where
+    F: Field,
+    G: TraceGenerator<F>,
+    G::Input: Clone,
+{
+    let rows_per_input = generator.rows_per_input();
+    let width = generator.width();
+    assert!(width > 0, "the trace must have at least one column");
+    assert!(
+        rows_per_input > 0,
+        "each input must generate at least one row"
+    );
+    let mut height = inputs.len() * rows_per_input;
+    let mut padded_inputs;
+    let inputs = match padding {
+        Some(padding) => {
+            height = height.next_power_of_two();
+            padded_inputs = inputs.to_vec();
+            padded_inputs.resize(height.div_ceil(rows_per_input), padding.clone());
+            &padded_inputs[..]
+        }
+        None => inputs,
+    };
+
+    // The groups of rows are all generated in full, and those past `height` are then dropped.
+    let mut values = uninit_trace_values(inputs.len() * rows_per_input, width);
+    let inputs_per_chunk = F::Packing::WIDTH;
+    values.spare_capacity_mut()[..inputs.len() * rows_per_input * width]
+        .par_chunks_mut(inputs_per_chunk * rows_per_input * width)
+        .zip(inputs.par_chunks(inputs_per_chunk))
+        .for_each(|(rows, inputs)| generator.generate_packed_rows(inputs, rows));
+
+    // SAFETY: `TraceGenerator` implementations write every value of the rows they're given, and
+    // the groups of rows of `inputs` cover at least `height` rows.
+    unsafe { assume_init_trace(values, height, width) }
+}
+
+/// An empty buffer for a trace of `height` rows of `width` columns, to be written through
+/// `Vec::spare_capacity_mut` without initializing it first, and then turned into a matrix by
+/// `assume_init_trace`.
+pub fn uninit_trace_values<F>(height: usize, width: usize) -> Vec<F> {
+    Vec::with_capacity(height * width)
+}
+
+/// The trace of `height` rows of `width` columns whose values were written to the spare capacity
+/// of `values`, e.g. as given by `uninit_trace_values`.
+///
+/// # Safety
+///
+/// The first `height * width` values of the spare capacity of `values` must be initialized, and
+/// `values` must be empty.
+pub unsafe fn assume_init_trace<F: Clone + Send + Sync>(
+    mut values: Vec<F>,
+    height: usize,
+    width: usize,
+) -> RowMajorMatrix<F> {
+    debug_assert!(values.is_empty());
+    assert!(height * width <= values.capacity());
+    values.set_len(height * width);
+    RowMajorMatrix::new(values, width)
+}
+
+/// Write lane `i` of `value` to `rows[i * stride + offset]`, for each lane within `rows`. E.g.
+/// in `generate_packed_rows`, with `stride` the number of values in a group of rows, lane `i`
+/// is written to the rows of the `i`th input.
+#[inline]
+pub fn write_packed_column<P: PackedValue>(
+    rows: &mut [MaybeUninit<P::Value>],
+    stride: usize,
+    offset: usize,
+    value: P,
+) {
+    for (i, &lane) in value.as_slice().iter().enumerate() {
+        match rows.get_mut(i * stride + offset) {
+            Some(dst) => {
+                dst.write(lane);
+            }
+            None => break,
+        }
+    }
+}
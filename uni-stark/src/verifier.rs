@@ -1,17 +1,31 @@
+use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::mem;
 
-use itertools::Itertools;
-use p3_air::{Air, BaseAir};
+use itertools::{izip, Itertools};
+use p3_air::{eval_logup, Air, BaseAir, MultiStageAir};
 use p3_challenger::{CanObserve, CanSample, FieldChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
 use p3_field::{AbstractExtensionField, AbstractField, Field};
 use p3_matrix::dense::RowMajorMatrixView;
-use p3_matrix::stack::VerticalPair;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_ceil_usize;
 use tracing::instrument;
 
-use crate::symbolic_builder::{get_log_quotient_degree, SymbolicAirBuilder};
-use crate::{PcsError, Proof, StarkGenericConfig, Val, VerifierConstraintFolder};
+use crate::proof::TRACE_ROTATIONS;
+use crate::quotient_degree::has_linear_transition_selector;
+use crate::stages::StageLayout;
+use crate::symbolic_builder::{
+    get_symbolic_constraints_and_lookups, log_quotient_degree_for_padding, max_constraint_degree,
+    SymbolicAirBuilder,
+};
+#[cfg(feature = "debug-diagnostics")]
+use crate::OodMismatchDiagnostics;
+use crate::{
+    get_max_constraint_degree_for_config, PcsError, PreprocessedVerifierKey, Proof,
+    StarkGenericConfig, Val, VerifierConstraintFolder,
+};
 
 #[instrument(skip_all)]
 pub fn verify<SC, A>(
@@ -24,16 +38,181 @@ pub fn verify<SC, A>(
 where
     SC: StarkGenericConfig,
     A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    verify_with_preprocessed(config, air, challenger, proof, public_values, None)
+}
+
+/// Like `verify`, for an AIR with a preprocessed trace, given the key from `setup_preprocessed`.
+#[instrument(skip_all)]
+pub fn verify_with_preprocessed<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &Proof<SC>,
+    public_values: &Vec<Val<SC>>,
+    preprocessed: Option<&PreprocessedVerifierKey<SC>>,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let preprocessed_width = preprocessed.map_or(0, PreprocessedVerifierKey::width);
+    let constraint_degree =
+        get_max_constraint_degree_for_config(config, air, preprocessed_width, public_values.len());
+    verify_with_constraint_degree(
+        config,
+        air,
+        challenger,
+        proof,
+        public_values,
+        preprocessed,
+        &StageLayout::default(),
+        constraint_degree,
+    )
+}
+
+/// Like `verify_with_preprocessed`, for a proof of a `MultiStageAir` from `prove_multi_stage`.
+#[instrument(skip_all)]
+pub fn verify_multi_stage<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &Proof<SC>,
+    public_values: &Vec<Val<SC>>,
+    preprocessed: Option<&PreprocessedVerifierKey<SC>>,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: MultiStageAir<Val<SC>>
+        + Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let stages = StageLayout::of(air, <SC::Challenge as AbstractExtensionField<Val<SC>>>::D);
+    let preprocessed_width = preprocessed.map_or(0, PreprocessedVerifierKey::width);
+    let (constraints, lookups) = get_symbolic_constraints_and_lookups::<Val<SC>, A>(
+        air,
+        preprocessed_width,
+        public_values.len(),
+        &stages,
+        has_linear_transition_selector(config),
+    );
+    verify_with_constraint_degree(
+        config,
+        air,
+        challenger,
+        proof,
+        public_values,
+        preprocessed,
+        &stages,
+        max_constraint_degree(&constraints, &lookups),
+    )
+}
+
+/// Verify a batch of proofs of the same AIR, e.g. when aggregating many proofs.
+///
+/// `challenger` should be in the state the verifier of a single proof would start from; each
+/// proof is checked against its own clone of it, so the transcripts are exactly those of `verify`.
+/// The work which doesn't depend on the proof, namely inferring the constraint degree by evaluating
+/// the constraints symbolically, is done once per distinct number of public values rather than
+/// once per proof, and the proofs are then verified in parallel.
+///
+/// Nothing else is shared between the proofs. Their Merkle openings are against different
+/// commitments, so there are no hashes in common to deduplicate. Within each proof, though, the FRI
+/// verifier verifies the openings of a commitment together (see `Mmcs::verify_batches`), and skips
+/// queries which repeat an opening it has already verified. The checks that remain per proof after
+/// the PCS verification are a handful of field element equalities, so there would be nothing to
+/// gain from folding them into a random linear combination across proofs; they are checked
+/// exactly.
+///
+/// On failure, returns the error of the first proof which didn't verify.
+#[instrument(skip_all, fields(num_proofs = proofs.len()))]
+pub fn verify_batch<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &SC::Challenger,
+    proofs: &[Proof<SC>],
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), BatchVerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig + Sync,
+    SC::Challenger: Clone + Sync,
+    Proof<SC>: Sync,
+    PcsError<SC>: Send,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>> + Sync,
+{
+    if proofs.len() != public_values.len() {
+        return Err(BatchVerificationError::WrongNumberOfPublicValues {
+            num_proofs: proofs.len(),
+            num_public_values: public_values.len(),
+        });
+    }
+
+    let mut constraint_degrees = BTreeMap::new();
+    for pis in public_values {
+        constraint_degrees
+            .entry(pis.len())
+            .or_insert_with(|| get_max_constraint_degree_for_config(config, air, 0, pis.len()));
+    }
+
+    let results: Vec<_> = proofs
+        .par_iter()
+        .zip(public_values)
+        .map(|(proof, pis)| {
+            verify_with_constraint_degree(
+                config,
+                air,
+                &mut challenger.clone(),
+                proof,
+                pis,
+                None,
+                &StageLayout::default(),
+                constraint_degrees[&pis.len()],
+            )
+        })
+        .collect();
+    results
+        .into_iter()
+        .enumerate()
+        .try_for_each(|(index, result)| {
+            result.map_err(|error| BatchVerificationError::InvalidProof { index, error })
+        })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn verify_with_constraint_degree<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &Proof<SC>,
+    public_values: &Vec<Val<SC>>,
+    preprocessed: Option<&PreprocessedVerifierKey<SC>>,
+    stages: &StageLayout,
+    constraint_degree: usize,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: for<'a> Air<VerifierConstraintFolder<'a, SC>>,
 {
     let Proof {
         commitments,
         opened_values,
         opening_proof,
         degree_bits,
+        trace_height,
+        cumulative_sum,
     } = proof;
 
+    // The trace was padded to the next power of two.
+    if *trace_height == 0 || log2_ceil_usize(*trace_height) != *degree_bits {
+        return Err(VerificationError::InvalidProofShape);
+    }
+
     let degree = 1 << degree_bits;
-    let log_quotient_degree = get_log_quotient_degree::<Val<SC>, A>(air, 0, public_values.len());
+    let selector_height = config
+        .trace_padding()
+        .selector_height(*trace_height, degree);
+    let log_quotient_degree =
+        log_quotient_degree_for_padding(constraint_degree, selector_height, degree, config.is_zk());
     let quotient_degree = 1 << log_quotient_degree;
 
     let pcs = config.pcs();
@@ -43,58 +222,156 @@ where
     let quotient_chunks_domains = quotient_domain.split_domains(quotient_degree);
 
     let air_width = <A as BaseAir<Val<SC>>>::width(air);
-    let valid_shape = opened_values.trace_local.len() == air_width
-        && opened_values.trace_next.len() == air_width
+    let window_offsets = <A as BaseAir<Val<SC>>>::window_offsets(air);
+    let periodic_columns = <A as BaseAir<Val<SC>>>::periodic_columns(air);
+    let ext_degree = <SC::Challenge as AbstractExtensionField<Val<SC>>>::D;
+    let permutation_len = opened_values.permutation_local.len();
+    let preprocessed_width = preprocessed.map_or(0, PreprocessedVerifierKey::width);
+    let valid_shape = preprocessed
+        .iter()
+        .all(|key| key.degree_bits == *degree_bits)
+        && opened_values.preprocessed_local.len() == preprocessed_width
+        && opened_values.preprocessed_next.len() == preprocessed_width
+        && opened_values.trace_window.len() == window_offsets.len()
+        && periodic_columns
+            .iter()
+            .all(|column| column.len().is_power_of_two() && column.len() <= degree)
+        && opened_values
+            .trace_window
+            .iter()
+            .all(|row| row.len() == air_width)
+        && commitments.stages.len() == stages.len()
+        && opened_values.stages_local.len() == stages.len()
+        && opened_values.stages_next.len() == stages.len()
+        && izip!(
+            &stages.widths,
+            &opened_values.stages_local,
+            &opened_values.stages_next
+        )
+        .all(|(&width, local, next)| local.len() == width && next.len() == width)
+        && commitments.permutation.is_some() == cumulative_sum.is_some()
+        && (permutation_len > 0) == cumulative_sum.is_some()
+        && permutation_len % ext_degree == 0
+        && opened_values.permutation_next.len() == permutation_len
         && opened_values.quotient_chunks.len() == quotient_degree
         && opened_values
             .quotient_chunks
             .iter()
-            .all(|qc| qc.len() == <SC::Challenge as AbstractExtensionField<Val<SC>>>::D);
+            .all(|qc| qc.len() == ext_degree);
     if !valid_shape {
         return Err(VerificationError::InvalidProofShape);
     }
 
+    // All lookups are between rows of this AIR, so they must balance.
+    if cumulative_sum.is_some_and(|sum| !sum.is_zero()) {
+        return Err(VerificationError::UnbalancedLookups);
+    }
+
     // Observe the instance.
     challenger.observe(Val::<SC>::from_canonical_usize(proof.degree_bits));
+    challenger.observe(Val::<SC>::from_canonical_usize(proof.trace_height));
     // TODO: Might be best practice to include other instance data here in the transcript, like some
     // encoding of the AIR. This protects against transcript collisions between distinct instances.
     // Practically speaking though, the only related known attack is from failing to include public
     // values. It's not clear if failing to include other instance data could enable a transcript
     // collision, since most such changes would completely change the set of satisfying witnesses.
 
+    if let Some(preprocessed) = preprocessed {
+        challenger.observe(preprocessed.commitment.clone());
+    }
     challenger.observe(commitments.trace.clone());
     challenger.observe_slice(public_values);
+
+    let stage_challenges = izip!(&stages.num_challenges, &commitments.stages)
+        .map(|(&num_challenges, stage_commit)| {
+            let challenges: Vec<SC::Challenge> = (0..num_challenges)
+                .map(|_| challenger.sample_ext_element())
+                .collect();
+            challenger.observe(stage_commit.clone());
+            challenges
+        })
+        .collect_vec();
+
+    let mut permutation_challenges = vec![];
+    if let (Some(permutation_commit), Some(sum)) = (&commitments.permutation, cumulative_sum) {
+        let gamma: SC::Challenge = challenger.sample_ext_element();
+        let beta: SC::Challenge = challenger.sample_ext_element();
+        permutation_challenges = vec![gamma, beta];
+        challenger.observe(permutation_commit.clone());
+        challenger.observe_ext_element(*sum);
+    }
+
     let alpha: SC::Challenge = challenger.sample_ext_element();
     challenger.observe(commitments.quotient_chunks.clone());
 
     let zeta: SC::Challenge = challenger.sample();
-    let zeta_next = trace_domain.next_point(zeta).unwrap();
-
-    pcs.verify(
-        vec![
-            (
-                commitments.trace.clone(),
-                vec![(
-                    trace_domain,
-                    vec![
-                        (zeta, opened_values.trace_local.clone()),
-                        (zeta_next, opened_values.trace_next.clone()),
-                    ],
-                )],
-            ),
-            (
-                commitments.quotient_chunks.clone(),
-                quotient_chunks_domains
-                    .iter()
-                    .zip(&opened_values.quotient_chunks)
-                    .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
-                    .collect_vec(),
-            ),
-        ],
-        opening_proof,
-        challenger,
-    )
-    .map_err(VerificationError::InvalidOpeningArgument)?;
+    let window_points = trace_domain.rotated_points(zeta, &window_offsets).unwrap();
+    let trace_points = trace_domain.rotated_points(zeta, &TRACE_ROTATIONS).unwrap();
+
+    let mut rounds = vec![
+        (
+            commitments.trace.clone(),
+            vec![(
+                trace_domain,
+                izip!(window_points, opened_values.trace_window.clone()).collect_vec(),
+            )],
+        ),
+        (
+            commitments.quotient_chunks.clone(),
+            quotient_chunks_domains
+                .iter()
+                .zip(&opened_values.quotient_chunks)
+                .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
+                .collect_vec(),
+        ),
+    ];
+    if let Some(preprocessed) = preprocessed {
+        rounds.push((
+            preprocessed.commitment.clone(),
+            vec![(
+                trace_domain,
+                izip!(
+                    trace_points.clone(),
+                    [
+                        opened_values.preprocessed_local.clone(),
+                        opened_values.preprocessed_next.clone(),
+                    ]
+                )
+                .collect_vec(),
+            )],
+        ));
+    }
+    if let Some(permutation_commit) = &commitments.permutation {
+        rounds.push((
+            permutation_commit.clone(),
+            vec![(
+                trace_domain,
+                izip!(
+                    trace_points.clone(),
+                    [
+                        opened_values.permutation_local.clone(),
+                        opened_values.permutation_next.clone(),
+                    ]
+                )
+                .collect_vec(),
+            )],
+        ));
+    }
+    for (stage_commit, local, next) in izip!(
+        &commitments.stages,
+        &opened_values.stages_local,
+        &opened_values.stages_next
+    ) {
+        rounds.push((
+            stage_commit.clone(),
+            vec![(
+                trace_domain,
+                izip!(trace_points.clone(), [local.clone(), next.clone()]).collect_vec(),
+            )],
+        ));
+    }
+    pcs.verify(rounds, opening_proof, challenger)
+        .map_err(VerificationError::InvalidOpeningArgument)?;
 
     let zps = quotient_chunks_domains
         .iter()
@@ -112,7 +389,7 @@ where
         })
         .collect_vec();
 
-    let quotient = opened_values
+    let quotient_chunks = opened_values
         .quotient_chunks
         .iter()
         .enumerate()
@@ -122,31 +399,96 @@ where
                 .map(|(e_i, &c)| zps[ch_i] * SC::Challenge::monomial(e_i) * c)
                 .sum::<SC::Challenge>()
         })
-        .sum::<SC::Challenge>();
+        .collect_vec();
+    let quotient = quotient_chunks.iter().copied().sum::<SC::Challenge>();
 
-    let sels = trace_domain.selectors_at_point(zeta);
+    let sels = trace_domain.selectors_at_point_for_height(zeta, selector_height);
 
-    let main = VerticalPair::new(
-        RowMajorMatrixView::new_row(&opened_values.trace_local),
-        RowMajorMatrixView::new_row(&opened_values.trace_next),
-    );
+    let main_values = opened_values.trace_window.concat();
+    let main = RowMajorMatrixView::new(&main_values, air_width);
+
+    let preprocessed_values = [
+        opened_values.preprocessed_local.clone(),
+        opened_values.preprocessed_next.clone(),
+    ]
+    .concat();
+    let preprocessed = RowMajorMatrixView::new(&preprocessed_values, preprocessed_width);
+
+    let stage_values = izip!(&opened_values.stages_local, &opened_values.stages_next)
+        .map(|(local, next)| [local.clone(), next.clone()].concat())
+        .collect_vec();
+    let stage_views = izip!(&stages.widths, &stage_values)
+        .map(|(&width, values)| RowMajorMatrixView::new(values, width))
+        .collect_vec();
+
+    // The LogUp trace was committed to as its base field columns.
+    let to_ext = |values: &[SC::Challenge]| {
+        values
+            .chunks_exact(ext_degree)
+            .map(|coeffs| {
+                coeffs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &c)| SC::Challenge::monomial(i) * c)
+                    .sum::<SC::Challenge>()
+            })
+            .collect_vec()
+    };
+    let permutation_local = to_ext(&opened_values.permutation_local);
+    let permutation_values = [
+        permutation_local.clone(),
+        to_ext(&opened_values.permutation_next),
+    ]
+    .concat();
+    let permutation = RowMajorMatrixView::new(&permutation_values, permutation_local.len());
+
+    let periodic_values = periodic_columns
+        .iter()
+        .map(|column| trace_domain.periodic_column_at_point(column, zeta))
+        .collect_vec();
 
     let mut folder = VerifierConstraintFolder {
+        preprocessed,
         main,
+        stages: stage_views,
+        stage_challenges: &stage_challenges,
+        permutation,
+        permutation_challenges: &permutation_challenges,
+        lookups: vec![],
+        periodic_values: &periodic_values,
         public_values,
         is_first_row: sels.is_first_row,
         is_last_row: sels.is_last_row,
         is_transition: sels.is_transition,
+        trace_domain,
+        selector_height,
+        zeta,
         alpha,
         accumulator: SC::Challenge::ZERO,
     };
     air.eval(&mut folder);
+    let lookups = mem::take(&mut folder.lookups);
+    match cumulative_sum {
+        Some(sum) if !lookups.is_empty() && permutation_local.len() == lookups.len() + 1 => {
+            eval_logup(&mut folder, &lookups, *sum);
+        }
+        None if lookups.is_empty() => {}
+        _ => return Err(VerificationError::InvalidProofShape),
+    }
     let folded_constraints = folder.accumulator;
 
     // Finally, check that
     //     folded_constraints(zeta) / Z_H(zeta) = quotient(zeta)
-    if folded_constraints * sels.inv_zeroifier != quotient {
-        return Err(VerificationError::OodEvaluationMismatch);
+    let expected_quotient = folded_constraints * sels.inv_zeroifier;
+    if expected_quotient != quotient {
+        return Err(VerificationError::OodEvaluationMismatch {
+            #[cfg(feature = "debug-diagnostics")]
+            diagnostics: OodMismatchDiagnostics::new(
+                folded_constraints,
+                expected_quotient,
+                &quotient_chunks,
+            ),
+        });
     }
 
     Ok(())
@@ -159,5 +501,25 @@ pub enum VerificationError<PcsErr> {
     InvalidOpeningArgument(PcsErr),
     /// Out-of-domain evaluation mismatch, i.e. `constraints(zeta)` did not match
     /// `quotient(zeta) Z_H(zeta)`.
-    OodEvaluationMismatch,
+    OodEvaluationMismatch {
+        /// The values which didn't match.
+        #[cfg(feature = "debug-diagnostics")]
+        diagnostics: OodMismatchDiagnostics,
+    },
+    /// The cumulative sum of the LogUp terms wasn't zero, i.e. the lookups weren't balanced.
+    UnbalancedLookups,
+}
+
+#[derive(Debug)]
+pub enum BatchVerificationError<PcsErr> {
+    /// The batch didn't have one vector of public values per proof.
+    WrongNumberOfPublicValues {
+        num_proofs: usize,
+        num_public_values: usize,
+    },
+    /// The proof at `index` in the batch didn't verify.
+    InvalidProof {
+        index: usize,
+        error: VerificationError<PcsErr>,
+    },
 }
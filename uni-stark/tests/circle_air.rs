@@ -0,0 +1,263 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, PeriodicAirBuilder};
+use p3_challenger::{HashChallenger, SerializingChallenger32};
+use p3_circle::{CirclePcs, HidingCirclePcs};
+use p3_commit::ExtensionMmcs;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption};
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::{MerkleTreeHidingMmcs, MerkleTreeMmcs};
+use p3_mersenne_31::{Mersenne31, Qm31};
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+use p3_uni_stark::{
+    get_log_quotient_degree_for_config, get_max_constraint_degree_for_config, prove, verify,
+    StarkConfig, VerificationError,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// A MiMC-like permutation in a single column: each transition adds a round constant, repeating
+/// every `round_constants.len()` rows, and cubes the sum. The public values are the first and
+/// last values.
+struct CubeRoundsAir {
+    round_constants: Vec<u32>,
+}
+
+impl<F: Field> BaseAir<F> for CubeRoundsAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        vec![self
+            .round_constants
+            .iter()
+            .map(|&c| F::from_canonical_u32(c))
+            .collect()]
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues + PeriodicAirBuilder> Air<AB> for CubeRoundsAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+        let round_constant: AB::Expr = builder.periodic_values()[0].into();
+        let public_values = builder.public_values();
+        let (input, output) = (public_values[0], public_values[1]);
+
+        builder.when_first_row().assert_eq(local, input);
+        builder
+            .when_transition()
+            .assert_eq(next, (local + round_constant).cube());
+        builder.when_last_row().assert_eq(local, output);
+    }
+}
+
+/// A counter whose first and last values are given by their squares, so that its only constraints
+/// of degree 3 are boundary constraints.
+struct SquaredBoundsAir;
+
+impl<F> BaseAir<F> for SquaredBoundsAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for SquaredBoundsAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+        let public_values = builder.public_values();
+        let (first_squared, last_squared) = (public_values[0], public_values[1]);
+
+        builder
+            .when_first_row()
+            .assert_eq(local * local, first_squared);
+        builder
+            .when_transition()
+            .assert_eq(next, local + AB::Expr::ONE);
+        builder
+            .when_last_row()
+            .assert_eq(local * local, last_squared);
+    }
+}
+
+fn generate_trace<F: Field>(round_constants: &[u32], input: F, n: usize) -> RowMajorMatrix<F> {
+    let mut values = vec![input];
+    for i in 0..n - 1 {
+        let round_constant = F::from_canonical_u32(round_constants[i % round_constants.len()]);
+        values.push((values[i] + round_constant).cube());
+    }
+    RowMajorMatrix::new_col(values)
+}
+
+type Val = Mersenne31;
+type Challenge = Qm31;
+type ByteHash = Keccak256Hash;
+type FieldHash = SerializingHasher32<ByteHash>;
+type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> MyConfig {
+    let val_mmcs = ValMmcs::new(FieldHash::new(ByteHash {}), MyCompress::new(ByteHash {}));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 40,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    MyConfig::new(Pcs::new(val_mmcs, fri_config))
+}
+
+// The same config, with salted commitments and blinded polynomials, so that the proofs are
+// zero-knowledge.
+type HidingValMmcs = MerkleTreeHidingMmcs<Val, u8, FieldHash, MyCompress, StdRng, 32, 4>;
+type HidingChallengeMmcs = ExtensionMmcs<Val, Challenge, HidingValMmcs>;
+type HidingPcs = HidingCirclePcs<Val, HidingValMmcs, HidingChallengeMmcs, StdRng>;
+type HidingConfig = StarkConfig<HidingPcs, Challenge, Challenger>;
+
+fn setup_hiding() -> HidingConfig {
+    let val_mmcs = HidingValMmcs::new(
+        FieldHash::new(ByteHash {}),
+        MyCompress::new(ByteHash {}),
+        StdRng::seed_from_u64(1),
+    );
+    let challenge_mmcs = HidingChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 40,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    HidingConfig::new(HidingPcs::new(
+        val_mmcs,
+        fri_config,
+        StdRng::seed_from_u64(2),
+    ))
+}
+
+fn challenger() -> Challenger {
+    Challenger::from_hasher(vec![], ByteHash {})
+}
+
+fn trace_and_public_values(air: &CubeRoundsAir, n: usize) -> (RowMajorMatrix<Val>, Vec<Val>) {
+    let input = Val::from_canonical_u32(7);
+    let trace = generate_trace(&air.round_constants, input, n);
+    let output = trace.values[n - 1];
+    (trace, vec![input, output])
+}
+
+#[test]
+fn test_circle_stark() {
+    let config = setup();
+    let air = CubeRoundsAir {
+        round_constants: vec![3, 1, 4, 1, 5, 9, 2, 6],
+    };
+    for log_n in [3, 6, 9] {
+        let (trace, public_values) = trace_and_public_values(&air, 1 << log_n);
+        let proof = prove(&config, &air, &mut challenger(), trace, &public_values);
+        verify(&config, &air, &mut challenger(), &proof, &public_values)
+            .expect("verification failed");
+    }
+}
+
+#[test]
+fn test_zk_circle_stark() {
+    let config = setup_hiding();
+    let air = CubeRoundsAir {
+        round_constants: vec![3, 1, 4, 1, 5, 9, 2, 6],
+    };
+    // The blinded trace has degree 2n, so the quotient has degree up to 7n rather than 3n, and
+    // twice as many chunks.
+    assert_eq!(
+        get_log_quotient_degree_for_config(&config, &air, 0, 2),
+        Ok(3)
+    );
+    for log_n in [3, 6] {
+        let (trace, public_values) = trace_and_public_values(&air, 1 << log_n);
+        let proof = prove(&config, &air, &mut challenger(), trace, &public_values);
+        verify(&config, &air, &mut challenger(), &proof, &public_values)
+            .expect("verification failed");
+    }
+}
+
+#[test]
+fn test_zk_circle_stark_rejects_wrong_public_values() {
+    let config = setup_hiding();
+    let air = CubeRoundsAir {
+        round_constants: vec![3, 1, 4, 1, 5, 9, 2, 6],
+    };
+    let (trace, mut public_values) = trace_and_public_values(&air, 1 << 6);
+    let proof = prove(&config, &air, &mut challenger(), trace, &public_values);
+    public_values[1] += Val::ONE;
+    assert!(verify(&config, &air, &mut challenger(), &proof, &public_values).is_err());
+}
+
+#[test]
+fn test_circle_periodic_columns_bind_the_verifier() {
+    let config = setup();
+    let air = CubeRoundsAir {
+        round_constants: vec![3, 1, 4, 1, 5, 9, 2, 6],
+    };
+    let (trace, public_values) = trace_and_public_values(&air, 1 << 6);
+    let proof = prove(&config, &air, &mut challenger(), trace, &public_values);
+
+    let other_air = CubeRoundsAir {
+        round_constants: vec![3, 1, 4, 1, 5, 9, 2, 7],
+    };
+    let result = verify(
+        &config,
+        &other_air,
+        &mut challenger(),
+        &proof,
+        &public_values,
+    );
+    assert!(matches!(
+        result,
+        Err(VerificationError::OodEvaluationMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_circle_quotient_degree_only_counts_selected_transitions() {
+    let config = setup();
+
+    // The transition constraint has degree 1, and 2 with its selector, which is less than the
+    // degree of the boundary constraints.
+    let air = SquaredBoundsAir;
+    assert_eq!(get_max_constraint_degree_for_config(&config, &air, 0, 2), 3);
+    assert_eq!(
+        get_log_quotient_degree_for_config(&config, &air, 0, 2),
+        Ok(1)
+    );
+    let n = 1 << 6;
+    let trace = RowMajorMatrix::new_col((5..5 + n).map(Val::from_canonical_u32).collect());
+    let public_values = vec![
+        Val::from_canonical_u32(25),
+        Val::from_canonical_u32((4 + n).pow(2)),
+    ];
+    let proof = prove(&config, &air, &mut challenger(), trace, &public_values);
+    verify(&config, &air, &mut challenger(), &proof, &public_values).expect("verification failed");
+
+    // Here the transition constraint has degree 3, and 4 with its selector.
+    let air = CubeRoundsAir {
+        round_constants: vec![3, 1, 4, 1, 5, 9, 2, 6],
+    };
+    assert_eq!(get_max_constraint_degree_for_config(&config, &air, 0, 2), 4);
+    assert_eq!(
+        get_log_quotient_degree_for_config(&config, &air, 0, 2),
+        Ok(2)
+    );
+}
@@ -7,13 +7,15 @@ use p3_commit::ExtensionMmcs;
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{AbstractField, Field, PrimeField64};
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
-use p3_uni_stark::{prove, verify, StarkConfig};
+use p3_uni_stark::{
+    check_constraints, get_constraint_names, prove, verify, StarkConfig, TracePadding,
+};
 use rand::thread_rng;
 
 /// For testing the public values feature
@@ -40,7 +42,8 @@ impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
 
         let mut when_first_row = builder.when_first_row();
 
-        when_first_row.assert_eq(local.left, a);
+        let left: AB::Expr = local.left.into();
+        when_first_row.assert_zero_named("initial left", left - a.into());
         when_first_row.assert_eq(local.right, b);
 
         let mut when_transition = builder.when_transition();
@@ -51,13 +54,12 @@ impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
         // b' <- a + b
         when_transition.assert_eq(local.left + local.right, next.right);
 
+        builder.name_constraints("final value");
         builder.when_last_row().assert_eq(local.right, x);
     }
 }
 
 pub fn generate_trace_rows<F: PrimeField64>(a: u64, b: u64, n: usize) -> RowMajorMatrix<F> {
-    assert!(n.is_power_of_two());
-
     let mut trace = RowMajorMatrix::new(F::zero_vec(n * NUM_FIBONACCI_COLS), NUM_FIBONACCI_COLS);
 
     let (prefix, rows, suffix) = unsafe { trace.values.align_to_mut::<FibonacciRow<F>>() };
@@ -127,8 +129,11 @@ fn test_public_value() {
     let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
     let fri_config = FriConfig {
         log_blowup: 2,
+        log_final_poly_len: 0,
         num_queries: 28,
         proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
         mmcs: challenge_mmcs,
     };
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
@@ -144,9 +149,46 @@ fn test_public_value() {
     verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis).expect("verification failed");
 }
 
+#[test]
+fn test_public_value_non_power_of_two_height() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+    let trace = generate_trace_rows::<Val>(0, 1, 13);
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(dft, val_mmcs, fri_config);
+    // Repeating the last row would break the transition constraints, but the constraints hold on
+    // rows of zeros.
+    let config = MyConfig::new(pcs).with_trace_padding(TracePadding::Zeros);
+    let mut challenger = Challenger::new(perm.clone());
+    let pis = vec![
+        BabyBear::from_canonical_u64(0),
+        BabyBear::from_canonical_u64(1),
+        BabyBear::from_canonical_u64(233),
+    ];
+    let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis).expect("verification failed");
+}
+
 #[cfg(debug_assertions)]
 #[test]
-#[should_panic(expected = "assertion `left == right` failed: constraints had nonzero value")]
+#[should_panic(expected = "constraints had nonzero value on row 7: constraint 4 (final value)")]
 fn test_incorrect_public_value() {
     let perm = Perm::new_from_rng_128(
         Poseidon2ExternalMatrixGeneral,
@@ -160,8 +202,11 @@ fn test_incorrect_public_value() {
     let dft = Dft::default();
     let fri_config = FriConfig {
         log_blowup: 2,
+        log_final_poly_len: 0,
         num_queries: 28,
         proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
         mmcs: challenge_mmcs,
     };
     let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
@@ -175,3 +220,95 @@ fn test_incorrect_public_value() {
     ];
     prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
 }
+
+#[test]
+fn test_check_constraints_reports_failure() {
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let mut pis = vec![
+        BabyBear::from_canonical_u64(0),
+        BabyBear::from_canonical_u64(1),
+        BabyBear::from_canonical_u64(21),
+    ];
+    assert_eq!(check_constraints(&FibonacciAir {}, &trace, &pis), Ok(()));
+
+    pis[2] = BabyBear::from_canonical_u64(22);
+    let failure = check_constraints(&FibonacciAir {}, &trace, &pis).unwrap_err();
+    assert_eq!(failure.row, 7);
+    assert_eq!(failure.constraint_index, 4);
+    assert_eq!(failure.name.as_deref(), Some("final value"));
+    assert_eq!(failure.value, -BabyBear::ONE);
+}
+
+#[test]
+fn test_constraint_names() {
+    let names = get_constraint_names::<Val, _>(&FibonacciAir {}, 0, 3);
+    assert_eq!(
+        names,
+        vec![
+            Some("initial left".to_string()),
+            None,
+            None,
+            None,
+            Some("final value".to_string()),
+        ]
+    );
+
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let pis = vec![
+        BabyBear::from_canonical_u64(1),
+        BabyBear::from_canonical_u64(1),
+        BabyBear::from_canonical_u64(21),
+    ];
+    let failure = check_constraints(&FibonacciAir {}, &trace, &pis).unwrap_err();
+    assert_eq!((failure.row, failure.constraint_index), (0, 0));
+    assert_eq!(failure.name, names[0]);
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_prove_with_metrics() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(dft, val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+    let mut challenger = Challenger::new(perm.clone());
+    let pis = vec![
+        BabyBear::from_canonical_u64(0),
+        BabyBear::from_canonical_u64(1),
+        BabyBear::from_canonical_u64(21),
+    ];
+    let (proof, metrics) = p3_uni_stark::prove_with_metrics(
+        &config,
+        &FibonacciAir {},
+        &mut challenger,
+        trace,
+        &pis,
+        None,
+    );
+    assert!(metrics.trace_commit + metrics.quotient + metrics.open <= metrics.total);
+    let size = &metrics.proof_size;
+    assert_eq!(size.total, proof.to_bytes().unwrap().len());
+    assert!(size.commitments + size.opened_values + size.opening_proof < size.total);
+    assert!(size.opening_proof > size.commitments);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis).expect("verification failed");
+}
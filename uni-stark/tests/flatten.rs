@@ -0,0 +1,116 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{prove, verify, FlattenError, Proof, StarkConfig};
+use rand::thread_rng;
+
+/// A single column counting up from zero.
+struct CounterAir;
+
+impl<F> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+        builder.when_first_row().assert_zero(local);
+        builder
+            .when_transition()
+            .assert_eq(next, local + AB::Expr::ONE);
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+#[test]
+fn test_flatten_round_trip() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let trace = RowMajorMatrix::new_col((0..1 << 4).map(Val::from_canonical_u32).collect());
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &CounterAir, &mut challenger, trace, &vec![]);
+
+    let elements = proof.flatten();
+    let layout = proof.flatten_layout();
+    assert_eq!(layout.num_elements(), elements.len());
+
+    // The layout finds each part of the proof.
+    let degree_bits = layout.get("degree_bits").unwrap();
+    assert_eq!(elements[degree_bits], [Val::from_canonical_u32(4)]);
+    // The trace commitment is a Merkle cap of a single digest, the root.
+    let cap_len = layout.get("commitments.trace.digests.len").unwrap();
+    assert_eq!(elements[cap_len], [Val::ONE]);
+    assert_eq!(layout.get("commitments.trace.digests[0]").unwrap().len(), 8);
+    let trace_window = layout.get("opened_values.trace_window.len").unwrap();
+    assert_eq!(elements[trace_window], [Val::TWO]);
+    assert_eq!(
+        layout
+            .get("opened_values.trace_window[1][0].value")
+            .unwrap()
+            .len(),
+        4
+    );
+    assert_eq!(layout.get("opened_values.trace_window[2][0]"), None);
+
+    // Unflattening gives back the same proof, which still verifies.
+    let unflattened = Proof::<MyConfig>::unflatten(&elements).unwrap();
+    assert_eq!(unflattened.flatten(), elements);
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &CounterAir, &mut challenger, &unflattened, &vec![])
+        .expect("verification failed");
+
+    assert_eq!(
+        Proof::<MyConfig>::unflatten(&elements[..elements.len() - 1]).err(),
+        Some(FlattenError::UnexpectedEnd)
+    );
+    let mut trailing = elements;
+    trailing.push(Val::ZERO);
+    assert_eq!(
+        Proof::<MyConfig>::unflatten(&trailing).err(),
+        Some(FlattenError::TrailingElements(1))
+    );
+}
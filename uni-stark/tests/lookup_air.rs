@@ -0,0 +1,202 @@
+use p3_air::{Air, AirBuilderWithLookups, BaseAir, PairBuilder};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove_with_preprocessed, setup_preprocessed, verify_with_preprocessed, StarkConfig,
+};
+use rand::{thread_rng, Rng};
+
+const RANGE_BUS: usize = 0;
+const LOG_HEIGHT: usize = 6;
+
+/// Range checks a column against a preprocessed table of `0..height`, with a lookup from each
+/// value into the table. The main trace has the values, and the number of times each row of the
+/// table is looked up.
+struct RangeCheckAir {
+    log_height: usize,
+}
+
+impl<F: Field> BaseAir<F> for RangeCheckAir {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        let table = (0..1 << self.log_height).map(F::from_canonical_usize);
+        Some(RowMajorMatrix::new_col(table.collect()))
+    }
+}
+
+impl<AB: PairBuilder + AirBuilderWithLookups> Air<AB> for RangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let table = builder.preprocessed().row_slice(0)[0];
+        let main = builder.main();
+        let (value, multiplicity) = (main.row_slice(0)[0], main.row_slice(0)[1]);
+
+        builder.send(RANGE_BUS, [value], AB::Expr::ONE);
+        builder.receive(RANGE_BUS, [table], multiplicity);
+    }
+}
+
+fn generate_trace<F: Field>(log_height: usize, values: &[usize]) -> RowMajorMatrix<F> {
+    let height = 1 << log_height;
+    let mut multiplicities = vec![0; height];
+    for &value in values {
+        multiplicities[value] += 1;
+    }
+    let rows = values
+        .iter()
+        .zip(multiplicities)
+        .flat_map(|(&value, m)| [F::from_canonical_usize(value), F::from_canonical_usize(m)]);
+    RowMajorMatrix::new(rows.collect(), 2)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn do_test(trace: RowMajorMatrix<Val>) {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let air = RangeCheckAir {
+        log_height: LOG_HEIGHT,
+    };
+    let preprocessed = setup_preprocessed(&config, &air).unwrap();
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_preprocessed(
+        &config,
+        &air,
+        &mut challenger,
+        trace,
+        &vec![],
+        Some(&preprocessed),
+    );
+
+    let mut challenger = Challenger::new(perm);
+    let vk = preprocessed.verifier_key();
+    verify_with_preprocessed(&config, &air, &mut challenger, &proof, &vec![], Some(&vk))
+        .expect("verification failed");
+}
+
+// The same AIR over Mersenne31, with a circle PCS. The LogUp running sums are opened at two
+// points, like the main trace, and the preprocessed table at one.
+mod circle {
+    use p3_challenger::{HashChallenger, SerializingChallenger32};
+    use p3_circle::CirclePcs;
+    use p3_keccak::Keccak256Hash;
+    use p3_mersenne_31::{Mersenne31, Qm31};
+    use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+
+    use super::*;
+
+    type Val = Mersenne31;
+    type Challenge = Qm31;
+    type ByteHash = Keccak256Hash;
+    type FieldHash = SerializingHasher32<ByteHash>;
+    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+    type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+    #[test]
+    fn test_range_check_lookups_circle() {
+        let val_mmcs = ValMmcs::new(FieldHash::new(ByteHash {}), MyCompress::new(ByteHash {}));
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            log_final_poly_len: 0,
+            num_queries: 40,
+            proof_of_work_bits: 8,
+            target_soundness_bits: None,
+            security_assumption: SecurityAssumption::CapacityBound,
+            mmcs: challenge_mmcs,
+        };
+        let config = MyConfig::new(Pcs::new(val_mmcs, fri_config));
+
+        let air = RangeCheckAir {
+            log_height: LOG_HEIGHT,
+        };
+        let preprocessed = setup_preprocessed(&config, &air).unwrap();
+
+        let mut rng = thread_rng();
+        let values: Vec<usize> = (0..1 << LOG_HEIGHT)
+            .map(|_| rng.gen_range(0..1 << LOG_HEIGHT))
+            .collect();
+        let trace = generate_trace::<Val>(LOG_HEIGHT, &values);
+
+        let challenger = || Challenger::from_hasher(vec![], ByteHash {});
+        let proof = prove_with_preprocessed(
+            &config,
+            &air,
+            &mut challenger(),
+            trace,
+            &vec![],
+            Some(&preprocessed),
+        );
+
+        let vk = preprocessed.verifier_key();
+        verify_with_preprocessed(&config, &air, &mut challenger(), &proof, &vec![], Some(&vk))
+            .expect("verification failed");
+    }
+}
+
+#[test]
+fn test_range_check_lookups() {
+    let mut rng = thread_rng();
+    let values: Vec<usize> = (0..1 << LOG_HEIGHT)
+        .map(|_| rng.gen_range(0..1 << LOG_HEIGHT))
+        .collect();
+    do_test(generate_trace(LOG_HEIGHT, &values));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "the lookups aren't balanced")]
+fn test_unbalanced_lookups() {
+    let values: Vec<usize> = (0..1 << LOG_HEIGHT).collect();
+    let mut trace = generate_trace::<Val>(LOG_HEIGHT, &values);
+    // Claim that the first row of the table is looked up twice.
+    trace.values[1] += Val::ONE;
+    do_test(trace);
+}
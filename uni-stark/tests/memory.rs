@@ -0,0 +1,223 @@
+use std::ops::Range;
+
+use p3_air::{
+    generate_memory_trace, Air, AirBuilderWithLookups, BaseAir, BaseSubAir, MemoryAccess,
+    MemoryAir, MemoryBuilder, PairBuilder, SubAir, SubBuilder,
+};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field, PrimeField64};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    check_constraints_with_stages, prove_with_preprocessed, setup_preprocessed,
+    verify_with_preprocessed, StarkConfig,
+};
+use rand::{thread_rng, Rng};
+
+const MEMORY_BUS: usize = 0;
+const RANGE_BUS: usize = 1;
+
+/// The columns of the chip: an access and whether the row makes it.
+const CHIP_WIDTH: usize = 5;
+
+/// A chip making one memory access per row, the memory checking them, and a table of bytes for
+/// the range checks of the memory, whose multiplicities are the last column.
+struct MemoryTestAir {
+    memory: MemoryAir,
+}
+
+impl MemoryTestAir {
+    fn new() -> Self {
+        Self {
+            memory: MemoryAir::new(MEMORY_BUS, RANGE_BUS, 8, 2),
+        }
+    }
+
+    fn memory_columns(&self) -> Range<usize> {
+        CHIP_WIDTH..CHIP_WIDTH + self.memory.width()
+    }
+}
+
+impl<F: Field> BaseAir<F> for MemoryTestAir {
+    fn width(&self) -> usize {
+        CHIP_WIDTH + self.memory.width() + 1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(RowMajorMatrix::new_col(
+            (0..1 << 8).map(F::from_canonical_usize).collect(),
+        ))
+    }
+}
+
+impl<AB: PairBuilder + AirBuilderWithLookups> Air<AB> for MemoryTestAir {
+    fn eval(&self, builder: &mut AB) {
+        let byte = builder.preprocessed().row_slice(0)[0];
+        let main = builder.main();
+        let local: Vec<AB::Var> = main.row_slice(0).to_vec();
+
+        let access = MemoryAccess {
+            addr: local[0],
+            timestamp: local[1],
+            value: local[2],
+            is_write: local[3],
+        };
+        let enabled = local[4];
+        builder.assert_bool(access.is_write);
+        builder.assert_bool(enabled);
+        builder.memory_access(MEMORY_BUS, access, enabled);
+
+        self.memory
+            .eval(&mut SubBuilder::new(builder, self.memory_columns()));
+        builder.receive(RANGE_BUS, [byte], local[local.len() - 1]);
+    }
+}
+
+/// `n` random accesses to a few addresses, at consecutive timestamps, starting with a write to
+/// each address.
+fn random_accesses<F: Field>(n: usize) -> Vec<MemoryAccess<F>> {
+    let mut rng = thread_rng();
+    let mut memory: [Option<u32>; 8] = [None; 8];
+    (1..=n)
+        .map(|timestamp| {
+            let addr = rng.gen_range(0..memory.len());
+            let (addr_f, timestamp_f) = (
+                F::from_canonical_usize(addr),
+                F::from_canonical_usize(timestamp),
+            );
+            match memory[addr] {
+                Some(value) if rng.gen() => {
+                    MemoryAccess::read(addr_f, timestamp_f, F::from_canonical_u32(value))
+                }
+                _ => {
+                    let value = rng.gen_range(0..1 << 30);
+                    memory[addr] = Some(value);
+                    MemoryAccess::write(addr_f, timestamp_f, F::from_canonical_u32(value))
+                }
+            }
+        })
+        .collect()
+}
+
+fn generate_trace<F: PrimeField64>(
+    air: &MemoryTestAir,
+    accesses: &[MemoryAccess<F>],
+    height: usize,
+) -> RowMajorMatrix<F> {
+    let memory = generate_memory_trace(&air.memory, accesses, height);
+    let mut byte_counts = vec![0; 1 << 8];
+    for row in memory.rows() {
+        for limb in row.skip(air.memory.limb_columns().start) {
+            byte_counts[limb.as_canonical_u64() as usize] += 1;
+        }
+    }
+
+    let mut values = Vec::with_capacity(height * BaseAir::<F>::width(air));
+    for (i, memory_row) in memory.rows().enumerate() {
+        match accesses.get(i) {
+            Some(access) => {
+                values.extend(access.into_values());
+                values.push(F::ONE);
+            }
+            None => values.extend([F::ZERO; CHIP_WIDTH]),
+        }
+        values.extend(memory_row);
+        values.push(F::from_canonical_u32(byte_counts[i]));
+    }
+    RowMajorMatrix::new(values, BaseAir::<F>::width(air))
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+#[test]
+fn test_memory_consistency() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let air = MemoryTestAir::new();
+    let accesses = random_accesses::<Val>(200);
+    let trace = generate_trace(&air, &accesses, 1 << 8);
+    let preprocessed = setup_preprocessed(&config, &air).unwrap();
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_preprocessed(
+        &config,
+        &air,
+        &mut challenger,
+        trace,
+        &vec![],
+        Some(&preprocessed),
+    );
+
+    let mut challenger = Challenger::new(perm);
+    let vk = preprocessed.verifier_key();
+    verify_with_preprocessed(&config, &air, &mut challenger, &proof, &vec![], Some(&vk))
+        .expect("verification failed");
+}
+
+#[test]
+fn test_stale_read() {
+    let air = MemoryTestAir::new();
+    let preprocessed = BaseAir::<Val>::preprocessed_trace(&air).unwrap();
+    let mut accesses = random_accesses::<Val>(200);
+    let trace = generate_trace(&air, &accesses, 1 << 8);
+    let check = |trace: &RowMajorMatrix<Val>| {
+        check_constraints_with_stages::<Val, Val, _>(
+            &air,
+            Some(&preprocessed),
+            trace,
+            &[],
+            &[],
+            &vec![],
+            trace.height(),
+        )
+    };
+    assert_eq!(check(&trace), Ok(()));
+
+    // A read of a value which was never written, which the chip and the memory agree on, so that
+    // only the memory's constraints can catch it.
+    let read = accesses
+        .iter_mut()
+        .find(|access| access.is_write.is_zero())
+        .unwrap();
+    read.value += Val::ONE;
+    let trace = generate_trace(&air, &accesses, 1 << 8);
+    assert!(check(&trace).is_err());
+}
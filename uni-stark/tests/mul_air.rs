@@ -4,14 +4,14 @@ use std::marker::PhantomData;
 use itertools::Itertools;
 use p3_air::{Air, AirBuilder, BaseAir};
 use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
-use p3_challenger::{DuplexChallenger, HashChallenger, SerializingChallenger32};
+use p3_challenger::{CanObserve, DuplexChallenger, HashChallenger, SerializingChallenger32};
 use p3_circle::CirclePcs;
 use p3_commit::testing::TrivialPcs;
 use p3_commit::ExtensionMmcs;
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{AbstractField, Field};
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
 use p3_keccak::Keccak256Hash;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
@@ -21,7 +21,10 @@ use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 use p3_symmetric::{
     CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher32, TruncatedPermutation,
 };
-use p3_uni_stark::{prove, verify, StarkConfig, StarkGenericConfig, Val};
+use p3_uni_stark::{
+    get_log_quotient_degree_for_config, prove, verify, verify_batch, BatchVerificationError,
+    InsufficientBlowupError, StarkConfig, StarkGenericConfig, Val,
+};
 use rand::distributions::{Distribution, Standard};
 use rand::{thread_rng, Rng};
 
@@ -194,6 +197,63 @@ fn prove_bb_trivial_deg4() -> Result<(), impl Debug> {
     do_test_bb_trivial(4, 8)
 }
 
+#[test]
+fn verify_batch_bb_trivial() {
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+
+    type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    let challenger = Challenger::new(perm);
+
+    type Pcs = TrivialPcs<Val, Radix2DitParallel<Val>>;
+    let log_n = 6;
+    let pcs = TrivialPcs {
+        dft: Radix2DitParallel::default(),
+        log_n,
+        _phantom: PhantomData,
+    };
+
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+    let config = MyConfig::new(pcs);
+    let air = MulAir::default();
+
+    let mut proofs = (0..3)
+        .map(|_| {
+            let trace = air.random_valid_trace(1 << log_n, true);
+            prove(&config, &air, &mut challenger.clone(), trace, &vec![])
+        })
+        .collect_vec();
+    let public_values = vec![vec![]; proofs.len()];
+    verify_batch(&config, &air, &challenger, &proofs, &public_values).expect("verification failed");
+
+    // A proof made from a different transcript.
+    let mut other_challenger = challenger.clone();
+    other_challenger.observe(Val::ONE);
+    let trace = air.random_valid_trace(1 << log_n, true);
+    proofs[1] = prove(&config, &air, &mut other_challenger, trace, &vec![]);
+    let err = verify_batch(&config, &air, &challenger, &proofs, &public_values).unwrap_err();
+    assert!(matches!(
+        err,
+        BatchVerificationError::InvalidProof { index: 1, .. }
+    ));
+
+    let err = verify_batch(&config, &air, &challenger, &proofs, &public_values[1..]).unwrap_err();
+    assert!(matches!(
+        err,
+        BatchVerificationError::WrongNumberOfPublicValues {
+            num_proofs: 3,
+            num_public_values: 2
+        }
+    ));
+}
+
 fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<(), impl Debug> {
     type Val = BabyBear;
     type Challenge = BinomialExtensionField<Val, 4>;
@@ -225,8 +285,11 @@ fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
 
     let fri_config = FriConfig {
         log_blowup,
+        log_final_poly_len: 0,
         num_queries: 40,
         proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
         mmcs: challenge_mmcs,
     };
     type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
@@ -263,6 +326,58 @@ fn prove_bb_twoadic_deg5() -> Result<(), impl Debug> {
     do_test_bb_twoadic(2, 5, 6)
 }
 
+#[test]
+fn quotient_degree_for_config_checks_blowup() {
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+    type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type ValMmcs =
+        MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Dft = Radix2DitParallel<Val>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 40,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+    };
+    let config = MyConfig::new(Pcs::new(Dft::default(), val_mmcs, fri_config));
+
+    // Degree 3 constraints need 2 quotient chunks, which a blowup of 2 allows, but degree 4
+    // constraints need 4.
+    let air = MulAir::default();
+    assert_eq!(
+        get_log_quotient_degree_for_config(&config, &air, 0, 0),
+        Ok(1)
+    );
+    let air = MulAir {
+        degree: 4,
+        ..Default::default()
+    };
+    assert_eq!(
+        get_log_quotient_degree_for_config(&config, &air, 0, 0),
+        Err(InsufficientBlowupError {
+            log_quotient_degree: 2,
+            max_log_blowup: 1,
+        })
+    );
+}
+
 fn do_test_m31_circle(log_blowup: usize, degree: u64, log_n: usize) -> Result<(), impl Debug> {
     type Val = Mersenne31;
     type Challenge = BinomialExtensionField<Val, 3>;
@@ -285,17 +400,16 @@ fn do_test_m31_circle(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
 
     let fri_config = FriConfig {
         log_blowup,
+        log_final_poly_len: 0,
         num_queries: 40,
         proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
         mmcs: challenge_mmcs,
     };
 
     type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
-    let pcs = Pcs {
-        mmcs: val_mmcs,
-        fri_config,
-        _phantom: PhantomData,
-    };
+    let pcs = Pcs::new(val_mmcs, fri_config);
 
     type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
     let config = MyConfig::new(pcs);
@@ -0,0 +1,193 @@
+use p3_air::{Air, BaseAir, ExtensionBuilder, MultiStageAir, MultiStageAirBuilder};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, ExtensionField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{prove_multi_stage, verify_multi_stage, StageTrace, StarkConfig};
+use rand::{thread_rng, Rng};
+
+const EXT_DEGREE: usize = 4;
+
+/// Accumulates the grand product `prod_i (r - x_i)` of the main column `x`, for a challenge `r`
+/// sampled after committing to it. The second stage holds the running product, as the base field
+/// coefficients of an extension element, or as an extension field column if `extension`.
+struct GrandProductAir {
+    extension: bool,
+}
+
+impl<F> BaseAir<F> for GrandProductAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> MultiStageAir<F> for GrandProductAir {
+    fn num_stages(&self) -> usize {
+        2
+    }
+
+    fn stage_width(&self, stage: usize) -> usize {
+        match stage {
+            0 => 1,
+            1 if self.extension => 1,
+            1 => EXT_DEGREE,
+            _ => unreachable!(),
+        }
+    }
+
+    fn num_stage_challenges(&self, stage: usize) -> usize {
+        match stage {
+            0 => 0,
+            1 => 1,
+            _ => unreachable!(),
+        }
+    }
+
+    fn is_extension_stage(&self, stage: usize) -> bool {
+        self.extension && stage == 1
+    }
+}
+
+impl<AB: MultiStageAirBuilder> Air<AB> for GrandProductAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (x, x_next): (AB::Expr, AB::Expr) =
+            (main.row_slice(0)[0].into(), main.row_slice(1)[0].into());
+        let (product, product_next) = if self.extension {
+            let [local, next] = builder.stage_ext(1);
+            (local[0].clone(), next[0].clone())
+        } else {
+            let products = builder.stage(1);
+            let (local, next) = (products.row_slice(0), products.row_slice(1));
+            (
+                builder.ext_from_base(local.iter().map(|&v| v.into())),
+                builder.ext_from_base(next.iter().map(|&v| v.into())),
+            )
+        };
+        let r: AB::ExprEF = builder.stage_challenges(1)[0].into();
+
+        builder
+            .when_first_row()
+            .assert_eq_ext(product.clone(), r.clone() - x);
+        builder
+            .when_transition()
+            .assert_eq_ext(product_next, product * (r - x_next));
+    }
+}
+
+fn generate_products<F: Field, EF: ExtensionField<F>>(
+    main: &RowMajorMatrix<F>,
+    r: EF,
+    extension: bool,
+) -> StageTrace<F, EF> {
+    let mut product = EF::ONE;
+    let products: Vec<EF> = main
+        .values
+        .iter()
+        .map(|&x| {
+            product *= r - x;
+            product
+        })
+        .collect();
+    if extension {
+        StageTrace::Extension(RowMajorMatrix::new_col(products))
+    } else {
+        let products = products
+            .iter()
+            .flat_map(|product| product.as_base_slice().to_vec())
+            .collect();
+        StageTrace::Base(RowMajorMatrix::new(products, EXT_DEGREE))
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, EXT_DEGREE>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+/// Prove and verify the grand product of a random column, with `offset` added to the challenge
+/// when generating the second stage.
+fn do_test(offset: Challenge, extension: bool) {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let mut rng = thread_rng();
+    let trace = RowMajorMatrix::new_col((0..1 << 6).map(|_| rng.gen()).collect());
+    let air = GrandProductAir { extension };
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_multi_stage(
+        &config,
+        &air,
+        &mut challenger,
+        trace,
+        &vec![],
+        None,
+        |stage, traces, challenges| {
+            assert_eq!((stage, traces.len(), challenges.len()), (1, 1, 1));
+            generate_products(&traces[0], challenges[0] + offset, extension)
+        },
+    );
+
+    let mut challenger = Challenger::new(perm);
+    verify_multi_stage(&config, &air, &mut challenger, &proof, &vec![], None)
+        .expect("verification failed");
+}
+
+#[test]
+fn test_grand_product() {
+    do_test(Challenge::ZERO, false);
+}
+
+#[test]
+fn test_grand_product_in_extension_stage() {
+    do_test(Challenge::ZERO, true);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "constraints had nonzero value")]
+fn test_stage_with_wrong_challenge() {
+    do_test(Challenge::ONE, false);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "constraints had nonzero value")]
+fn test_extension_stage_with_wrong_challenge() {
+    do_test(Challenge::ONE, true);
+}
@@ -0,0 +1,136 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{prove, verify, StarkConfig, TracePadding};
+use rand::thread_rng;
+
+/// Repeated squaring of a nonzero value, which is shown to be nonzero by its inverse. Rows of zeros
+/// don't satisfy the constraints, and repeating the last row breaks the transition, but rows of
+/// ones satisfy both.
+struct SquaringAir;
+
+impl<F: Field> BaseAir<F> for SquaringAir {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn padding_row(&self) -> Option<Vec<F>> {
+        Some(vec![F::ONE, F::ONE])
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for SquaringAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let (x, x_inv) = (local[0], local[1]);
+
+        builder.assert_one(x * x_inv);
+        builder.when_transition().assert_eq(next[0], x * x);
+    }
+}
+
+fn generate_trace<F: Field>(x: F, n: usize) -> RowMajorMatrix<F> {
+    let mut values = Vec::with_capacity(2 * n);
+    let mut x = x;
+    for _ in 0..n {
+        values.extend([x, x.inverse()]);
+        x = x.square();
+    }
+    RowMajorMatrix::new(values, 2)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup(trace_padding: TracePadding) -> (MyConfig, Perm) {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs).with_trace_padding(trace_padding), perm)
+}
+
+#[test]
+fn test_padding_row() {
+    let (config, perm) = setup(TracePadding::PaddingRow);
+    let trace = generate_trace(Val::from_canonical_u32(3), 13);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &SquaringAir, &mut challenger, trace, &vec![]);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &SquaringAir, &mut challenger, &proof, &vec![]).expect("verification failed");
+}
+
+#[test]
+fn test_padding_row_power_of_two_height() {
+    let (config, perm) = setup(TracePadding::PaddingRow);
+    let trace = generate_trace(Val::from_canonical_u32(3), 1 << 4);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &SquaringAir, &mut challenger, trace, &vec![]);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &SquaringAir, &mut challenger, &proof, &vec![]).expect("verification failed");
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "constraints had nonzero value on row 13")]
+fn test_zeros_break_the_constraints() {
+    let (config, perm) = setup(TracePadding::Zeros);
+    let trace = generate_trace(Val::from_canonical_u32(3), 13);
+
+    let mut challenger = Challenger::new(perm);
+    prove(&config, &SquaringAir, &mut challenger, trace, &vec![]);
+}
+
+#[test]
+fn test_padded_rows() {
+    let trace = generate_trace(Val::from_canonical_u32(3), 5);
+    let padded = TracePadding::PaddingRow.pad(trace.clone(), Some(vec![Val::ONE, Val::TWO]));
+    assert_eq!(padded.height(), 8);
+    assert_eq!(padded.values[..10], trace.values[..]);
+    for i in 5..8 {
+        assert_eq!(*padded.row_slice(i), [Val::ONE, Val::TWO]);
+    }
+
+    let padded = TracePadding::PaddingRow.pad(trace, None);
+    assert!(padded.values[10..].iter().all(|x| x.is_zero()));
+}
@@ -0,0 +1,188 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, PeriodicAirBuilder};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{check_constraints, prove, verify, StarkConfig, VerificationError};
+use rand::thread_rng;
+
+/// A running sum in a single column which adds a round constant, repeating every
+/// `round_constants.len()` rows, and a parity bit, repeating every two rows, on each transition.
+/// The public value is the last sum.
+struct RoundConstantAir {
+    round_constants: Vec<u32>,
+}
+
+impl<F: Field> BaseAir<F> for RoundConstantAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        vec![
+            self.round_constants
+                .iter()
+                .map(|&c| F::from_canonical_u32(c))
+                .collect(),
+            vec![F::ZERO, F::ONE],
+        ]
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues + PeriodicAirBuilder> Air<AB> for RoundConstantAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+        let periodic = builder.periodic_values();
+        let round_constant: AB::Expr = periodic[0].into();
+        let parity: AB::Expr = periodic[1].into();
+        let x = builder.public_values()[0];
+
+        builder.when_first_row().assert_zero(local);
+        builder
+            .when_transition()
+            .assert_eq(next, local + round_constant + parity);
+        builder.when_last_row().assert_eq(local, x);
+    }
+}
+
+fn generate_trace<F: Field>(round_constants: &[u32], n: usize) -> RowMajorMatrix<F> {
+    let mut values = vec![F::ZERO];
+    for i in 0..n - 1 {
+        let round_constant = F::from_canonical_u32(round_constants[i % round_constants.len()]);
+        let parity = F::from_canonical_usize(i % 2);
+        values.push(values[i] + round_constant + parity);
+    }
+    RowMajorMatrix::new_col(values)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+/// The sum over 15 transitions: three full periods of the round constants plus the first three,
+/// and seven odd rows.
+const LAST_SUM: u32 = 3 * (3 + 1 + 4 + 1) + (3 + 1 + 4) + 7;
+
+#[test]
+fn test_periodic_columns() {
+    let (config, perm) = setup();
+    let air = RoundConstantAir {
+        round_constants: vec![3, 1, 4, 1],
+    };
+    let trace = generate_trace::<Val>(&air.round_constants, 1 << 4);
+    let public_values = vec![Val::from_canonical_u32(LAST_SUM)];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &public_values);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &air, &mut challenger, &proof, &public_values).expect("verification failed");
+}
+
+#[test]
+fn test_periodic_columns_bind_the_verifier() {
+    let (config, perm) = setup();
+    let air = RoundConstantAir {
+        round_constants: vec![3, 1, 4, 1],
+    };
+    let trace = generate_trace::<Val>(&air.round_constants, 1 << 4);
+    let public_values = vec![Val::from_canonical_u32(LAST_SUM)];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &public_values);
+
+    // The same sum, from different round constants.
+    let other_air = RoundConstantAir {
+        round_constants: vec![1, 3, 4, 1],
+    };
+    let mut challenger = Challenger::new(perm);
+    let result = verify(&config, &other_air, &mut challenger, &proof, &public_values);
+    assert!(matches!(
+        result,
+        Err(VerificationError::OodEvaluationMismatch { .. })
+    ));
+}
+
+#[cfg(feature = "debug-diagnostics")]
+#[test]
+fn test_ood_mismatch_diagnostics() {
+    let (config, perm) = setup();
+    let air = RoundConstantAir {
+        round_constants: vec![3, 1, 4, 1],
+    };
+    let trace = generate_trace::<Val>(&air.round_constants, 1 << 4);
+    let public_values = vec![Val::from_canonical_u32(LAST_SUM)];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &public_values);
+
+    let other_air = RoundConstantAir {
+        round_constants: vec![1, 3, 4, 1],
+    };
+    let mut challenger = Challenger::new(perm);
+    let result = verify(&config, &other_air, &mut challenger, &proof, &public_values);
+    let Err(VerificationError::OodEvaluationMismatch { diagnostics }) = result else {
+        panic!("expected an OOD evaluation mismatch, got {result:?}");
+    };
+    assert_ne!(diagnostics.expected_quotient, diagnostics.quotient);
+    assert_ne!(diagnostics.delta, Challenge::ZERO.to_string());
+    assert!(!diagnostics.quotient_chunks.is_empty());
+}
+
+#[test]
+fn test_check_constraints_with_periodic_columns() {
+    let air = RoundConstantAir {
+        round_constants: vec![3, 1, 4, 1],
+    };
+    let trace = generate_trace::<Val>(&air.round_constants, 1 << 4);
+    let public_values = vec![Val::from_canonical_u32(LAST_SUM)];
+    assert_eq!(check_constraints(&air, &trace, &public_values), Ok(()));
+
+    let other_air = RoundConstantAir {
+        round_constants: vec![3, 1, 5, 0],
+    };
+    let failure = check_constraints(&other_air, &trace, &public_values).unwrap_err();
+    assert_eq!(failure.row, 2);
+    assert_eq!(failure.constraint_index, 1);
+}
@@ -0,0 +1,146 @@
+use p3_air::{Air, AirBuilder, BaseAir, PairBuilder};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::Field;
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove_with_preprocessed, setup_preprocessed, verify_with_preprocessed, StarkConfig,
+};
+use rand::thread_rng;
+
+/// Adds a fixed round constant to a running sum on each transition, i.e.
+/// `x' = x + c`, where `c` is a preprocessed column.
+struct RoundConstantAir {
+    constants: Vec<u32>,
+}
+
+impl<F: Field> BaseAir<F> for RoundConstantAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(RowMajorMatrix::new_col(
+            self.constants
+                .iter()
+                .map(|&c| F::from_canonical_u32(c))
+                .collect(),
+        ))
+    }
+}
+
+impl<AB: PairBuilder> Air<AB> for RoundConstantAir {
+    fn eval(&self, builder: &mut AB) {
+        let preprocessed = builder.preprocessed();
+        let main = builder.main();
+        let constant = preprocessed.row_slice(0)[0];
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+
+        builder.when_first_row().assert_zero(local);
+        builder.when_transition().assert_eq(local + constant, next);
+    }
+}
+
+impl RoundConstantAir {
+    fn new(n: u32) -> Self {
+        Self {
+            constants: (0..n).map(|i| i * i + 3).collect(),
+        }
+    }
+
+    fn generate_trace<F: Field>(&self) -> RowMajorMatrix<F> {
+        let mut x = F::ZERO;
+        let values = self
+            .constants
+            .iter()
+            .map(|&c| {
+                let value = x;
+                x += F::from_canonical_u32(c);
+                value
+            })
+            .collect();
+        RowMajorMatrix::new_col(values)
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+#[test]
+fn test_preprocessed_trace() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let air = RoundConstantAir::new(1 << 5);
+    let preprocessed = setup_preprocessed(&config, &air).expect("the AIR has a preprocessed trace");
+    let vk = preprocessed.verifier_key();
+    assert_eq!(vk.width(), 1);
+
+    let trace = air.generate_trace::<Val>();
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_preprocessed(
+        &config,
+        &air,
+        &mut challenger,
+        trace,
+        &vec![],
+        Some(&preprocessed),
+    );
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify_with_preprocessed(&config, &air, &mut challenger, &proof, &vec![], Some(&vk))
+        .expect("verification failed");
+
+    // The proof doesn't verify against the commitment to different constants.
+    let mut other_air = RoundConstantAir::new(1 << 5);
+    other_air.constants[7] += 1;
+    let other_vk = setup_preprocessed(&config, &other_air)
+        .unwrap()
+        .verifier_key();
+    let mut challenger = Challenger::new(perm);
+    assert!(verify_with_preprocessed(
+        &config,
+        &other_air,
+        &mut challenger,
+        &proof,
+        &vec![],
+        Some(&other_vk)
+    )
+    .is_err());
+}
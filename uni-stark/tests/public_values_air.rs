@@ -0,0 +1,144 @@
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPublicValues, AirBuilderWithRowSelectors, BaseAir,
+    ExtensionBuilder,
+};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractExtensionField, AbstractField, ExtensionField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{prove, verify, StarkConfig};
+use rand::thread_rng;
+
+const EXT_DEGREE: usize = 4;
+const LOG_HEIGHT: usize = 4;
+/// The row whose counter is a public value.
+const COUNTER_ROW: usize = 5;
+
+/// The coefficients of `w = X + 2`.
+const W: [u32; EXT_DEGREE] = [2, 1, 0, 0];
+
+/// Accumulates the powers of `w` in the extension field, in the first `EXT_DEGREE` columns, next
+/// to a counter. The public values are the counter on row `COUNTER_ROW`, then the coefficients of
+/// the last power, which are bound to the trace by the row selectors.
+struct PowersAir;
+
+impl<F> BaseAir<F> for PowersAir {
+    fn width(&self) -> usize {
+        EXT_DEGREE + 1
+    }
+}
+
+impl<AB> Air<AB> for PowersAir
+where
+    AB: AirBuilderWithPublicValues + AirBuilderWithRowSelectors + ExtensionBuilder,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let power = builder.ext_from_base(local[..EXT_DEGREE].iter().map(|&v| v.into()));
+        let power_next = builder.ext_from_base(next[..EXT_DEGREE].iter().map(|&v| v.into()));
+        let w = builder.ext_from_base(W.map(AB::Expr::from_canonical_u32));
+        let (counter, counter_next) = (local[EXT_DEGREE], next[EXT_DEGREE]);
+
+        builder
+            .when_first_row()
+            .assert_eq_ext(power.clone(), AB::ExprEF::ONE);
+        builder
+            .when_transition()
+            .assert_eq_ext(power_next, power * w);
+        builder.when_first_row().assert_zero(counter);
+        builder
+            .when_transition()
+            .assert_eq(counter_next, counter + AB::Expr::ONE);
+
+        builder.bind_public_value(COUNTER_ROW, EXT_DEGREE, 0);
+        builder.bind_public_value_ext((1 << LOG_HEIGHT) - 1, 0, 1);
+    }
+}
+
+fn generate_trace<F: Field, EF: ExtensionField<F>>(w: EF) -> RowMajorMatrix<F> {
+    let values = w
+        .powers()
+        .take(1 << LOG_HEIGHT)
+        .enumerate()
+        .flat_map(|(i, power)| {
+            let mut row = power.as_base_slice().to_vec();
+            row.push(F::from_canonical_usize(i));
+            row
+        })
+        .collect();
+    RowMajorMatrix::new(values, EXT_DEGREE + 1)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, EXT_DEGREE>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+#[test]
+fn test_public_values_at_rows() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let w = Challenge::from_base_fn(|i| Val::from_canonical_u32(W[i]));
+    let trace = generate_trace::<Val, Challenge>(w);
+    let last_power = w.exp_u64((1 << LOG_HEIGHT) - 1);
+    let mut public_values = vec![Val::from_canonical_usize(COUNTER_ROW)];
+    public_values.extend_from_slice(last_power.as_base_slice());
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &PowersAir, &mut challenger, trace, &public_values);
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify(&config, &PowersAir, &mut challenger, &proof, &public_values)
+        .expect("verification failed");
+
+    // Neither the counter nor the last power can be claimed to be something else.
+    for i in [0, 2] {
+        let mut wrong_public_values = public_values.clone();
+        wrong_public_values[i] += Val::ONE;
+        let mut challenger = Challenger::new(perm.clone());
+        assert!(verify(
+            &config,
+            &PowersAir,
+            &mut challenger,
+            &proof,
+            &wrong_public_values
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::testing::TrivialPcs;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{prove, verify, Proof, StarkConfig, STARK_FORMAT_VERSION, STARK_PROOF_MAGIC};
+use p3_util::canonical_serialization::SerializationError;
+use rand::thread_rng;
+
+/// A single column counting up from zero.
+struct CounterAir;
+
+impl<F> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+        builder.when_first_row().assert_zero(local);
+        builder
+            .when_transition()
+            .assert_eq(next, local + AB::Expr::ONE);
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+type TrivialConfig = StarkConfig<TrivialPcs<Val, Dft>, Challenge, Challenger>;
+
+fn new_perm() -> Perm {
+    Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    )
+}
+
+#[test]
+fn test_proof_round_trip() {
+    let perm = new_perm();
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let trace = RowMajorMatrix::new_col((0..1 << 4).map(Val::from_canonical_u32).collect());
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &CounterAir, &mut challenger, trace, &vec![]);
+
+    let bytes = proof.to_bytes().unwrap();
+    assert_eq!(&bytes[..4], &STARK_PROOF_MAGIC);
+    assert_eq!(&bytes[4..6], &STARK_FORMAT_VERSION.to_le_bytes());
+
+    // Decoding and encoding again gives the same bytes, and the decoded proof still verifies.
+    let decoded = Proof::<MyConfig>::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.to_bytes().unwrap(), bytes);
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &CounterAir, &mut challenger, &decoded, &vec![]).expect("verification failed");
+}
+
+/// A proof for `TrivialConfig`, whose commitments are vectors of coefficient vectors and whose
+/// opening proofs are empty.
+#[rustfmt::skip]
+fn golden_trivial_proof() -> Vec<u8> {
+    let ext = |c: [u32; 4]| c.map(u32::to_le_bytes).concat();
+    [
+        &b"P3SP"[..], &[1, 0],
+        // commitments: trace [[1, 2]], no stages, no permutation, quotient chunks [[3]]
+        &[1, 0, 0, 0], &[2, 0, 0, 0], &[1, 0, 0, 0], &[2, 0, 0, 0],
+        &[0, 0, 0, 0],
+        &[0],
+        &[1, 0, 0, 0], &[1, 0, 0, 0], &[3, 0, 0, 0],
+        // opened values: no preprocessed trace, a main window of two rows, no stages or
+        // permutation, one quotient chunk
+        &[0, 0, 0, 0], &[0, 0, 0, 0],
+        &[2, 0, 0, 0], &[1, 0, 0, 0], &ext([4, 0, 0, 0]), &[1, 0, 0, 0], &ext([5, 6, 0, 0]),
+        &[0, 0, 0, 0], &[0, 0, 0, 0],
+        &[0, 0, 0, 0], &[0, 0, 0, 0],
+        &[1, 0, 0, 0], &[1, 0, 0, 0], &ext([7, 0, 0, 0]),
+        // degree bits, trace height
+        &[1, 0, 0, 0, 0, 0, 0, 0], &[2, 0, 0, 0, 0, 0, 0, 0],
+        // cumulative sum
+        &[1], &ext([8, 0, 0, 9]),
+    ]
+    .concat()
+}
+
+#[test]
+fn test_proof_golden_encoding() {
+    // The layout of an encoded proof is part of the format; this must only change along with
+    // `STARK_FORMAT_VERSION`.
+    let bytes = golden_trivial_proof();
+    let proof = Proof::<TrivialConfig>::from_bytes(&bytes).unwrap();
+    assert_eq!(proof.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn test_rejects_malformed_proofs() {
+    let bytes = golden_trivial_proof();
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[..4].copy_from_slice(b"P3FP");
+    assert_eq!(
+        Proof::<TrivialConfig>::from_bytes(&bad_magic).err(),
+        Some(SerializationError::BadMagic {
+            expected: STARK_PROOF_MAGIC,
+            found: *b"P3FP",
+        })
+    );
+
+    let mut future_version = bytes.clone();
+    future_version[4] = 2;
+    assert!(matches!(
+        Proof::<TrivialConfig>::from_bytes(&future_version),
+        Err(SerializationError::UnsupportedVersion { found: 2, .. })
+    ));
+
+    assert_eq!(
+        Proof::<TrivialConfig>::from_bytes(&bytes[..bytes.len() - 1]).err(),
+        Some(SerializationError::UnexpectedEof)
+    );
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        Proof::<TrivialConfig>::from_bytes(&trailing).err(),
+        Some(SerializationError::TrailingBytes(1))
+    );
+
+    // An `Option` tag other than 0 or 1.
+    let mut bad_tag = bytes;
+    let tag = bad_tag.len() - 17;
+    bad_tag[tag] = 2;
+    assert_eq!(
+        Proof::<TrivialConfig>::from_bytes(&bad_tag).err(),
+        Some(SerializationError::InvalidTag(2))
+    );
+}
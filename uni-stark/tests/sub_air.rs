@@ -0,0 +1,132 @@
+use p3_air::{AirBuilder, BaseAir, BaseSubAir, SubAir, SubBuilder};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{check_constraints, prove, verify, StarkConfig};
+use rand::thread_rng;
+
+/// A column counting the rows from zero.
+struct Counter;
+
+impl BaseSubAir for Counter {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> SubAir<AB> for Counter {
+    fn eval(&self, builder: &mut SubBuilder<'_, AB>) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0)[0], main.row_slice(1)[0]);
+        builder.when_first_row().assert_zero(local);
+        builder
+            .when_transition()
+            .assert_eq(next, local + AB::Expr::ONE);
+    }
+}
+
+/// Two columns holding consecutive Fibonacci numbers from 0 and 1.
+struct Fibonacci;
+
+impl BaseSubAir for Fibonacci {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilder> SubAir<AB> for Fibonacci {
+    fn eval(&self, builder: &mut SubBuilder<'_, AB>) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let (left, right) = (local[0], local[1]);
+        let (next_left, next_right) = (next[0], next[1]);
+
+        let mut when_first_row = builder.when_first_row();
+        when_first_row.assert_zero(left);
+        when_first_row.assert_one(right);
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(next_left, right);
+        when_transition.assert_eq(next_right, left + right);
+    }
+}
+
+fn generate_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    let mut values = Vec::with_capacity(3 * n);
+    let (mut left, mut right) = (F::ZERO, F::ONE);
+    for i in 0..n {
+        values.extend([F::from_canonical_usize(i), left, right]);
+        (left, right) = (right, left + right);
+    }
+    RowMajorMatrix::new(values, 3)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+#[test]
+fn test_composed_air() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let air = Counter.then(Fibonacci);
+    assert_eq!(BaseAir::<Val>::width(&air), 3);
+    assert_eq!(air.second_columns(), 1..3);
+
+    let trace = generate_trace::<Val>(1 << 3);
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
+}
+
+#[test]
+fn test_composed_air_offsets_columns() {
+    let air = Counter.then(Fibonacci);
+    let mut trace = generate_trace::<Val>(1 << 3);
+    assert_eq!(check_constraints(&air, &trace, &vec![]), Ok(()));
+
+    // Break the Fibonacci piece, whose second column is column 2 of the trace, on row 3.
+    trace.values[3 * 3 + 2] += Val::ONE;
+    let failure = check_constraints(&air, &trace, &vec![]).unwrap_err();
+    // The counter's two constraints come first; then the transition into row 3 fails.
+    assert_eq!((failure.row, failure.constraint_index), (2, 5));
+}
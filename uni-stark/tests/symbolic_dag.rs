@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithLookups, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use p3_uni_stark::{
+    get_constraint_names, get_symbolic_constraints, get_symbolic_dag, Entry, SymbolicNode,
+};
+
+type Val = BabyBear;
+
+/// Repeated `x' = x^2 + y`, starting from a public value, with `x^2` used twice, and `x` sent to
+/// a bus.
+struct SquareAddAir;
+
+impl<F> BaseAir<F> for SquareAddAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues + AirBuilderWithLookups> Air<AB> for SquareAddAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let (x, y, next_x) = (local[0], local[1], next[0]);
+        let start = builder.public_values()[0];
+
+        builder.when_first_row().assert_eq(x, start);
+        let x_squared = x * x;
+        builder
+            .when_transition()
+            .assert_eq(next_x, x_squared.clone() + y);
+        builder.assert_zero_named("bounded", x_squared * y);
+        builder.send(0, [x], AB::Expr::ONE);
+    }
+}
+
+#[test]
+fn test_symbolic_dag() {
+    let dag = get_symbolic_dag::<Val, _>(&SquareAddAir, 0, 1);
+    assert_eq!((dag.width, dag.num_public_values), (2, 1));
+    assert_eq!(dag.window_offsets, vec![0, 1]);
+    assert_eq!(
+        dag.constraints.len(),
+        get_symbolic_constraints::<Val, _>(&SquareAddAir, 0, 1).len()
+    );
+    assert_eq!(
+        dag.constraint_names,
+        get_constraint_names::<Val, _>(&SquareAddAir, 0, 1)
+    );
+
+    // The nodes are distinct, and come after their operands.
+    let distinct: HashSet<_> = dag.nodes.iter().collect();
+    assert_eq!(distinct.len(), dag.nodes.len());
+    for (i, node) in dag.nodes.iter().enumerate() {
+        let operands = match *node {
+            SymbolicNode::Add(x, y) | SymbolicNode::Sub(x, y) | SymbolicNode::Mul(x, y) => {
+                vec![x, y]
+            }
+            SymbolicNode::Neg(x) => vec![x],
+            _ => vec![],
+        };
+        assert!(operands.into_iter().all(|operand| operand < i));
+    }
+
+    // `x^2` is a single node.
+    let x = dag
+        .nodes
+        .iter()
+        .position(|node| {
+            *node
+                == SymbolicNode::Variable {
+                    entry: Entry::Main { offset: 0 },
+                    index: 0,
+                }
+        })
+        .unwrap();
+    let squares = dag
+        .nodes
+        .iter()
+        .filter(|node| **node == SymbolicNode::Mul(x, x))
+        .count();
+    assert_eq!(squares, 1);
+
+    assert_eq!(dag.lookups.len(), 1);
+    assert_eq!(dag.lookups[0].values, vec![x]);
+    assert_eq!(
+        dag.nodes[dag.lookups[0].multiplicity],
+        SymbolicNode::Constant(Val::ONE)
+    );
+}
+
+#[test]
+fn test_eval_symbolic_dag() {
+    let dag = get_symbolic_dag::<Val, _>(&SquareAddAir, 0, 1);
+    // x = 3, y = 5, x' = 14 = 3^2 + 5, a public value of 2, and every selector set.
+    let values = dag.eval_constraints(|node| match *node {
+        SymbolicNode::Variable { entry, index } => Val::from_canonical_u32(match (entry, index) {
+            (Entry::Main { offset: 0 }, 0) => 3,
+            (Entry::Main { offset: 0 }, 1) => 5,
+            (Entry::Main { offset: 1 }, 0) => 14,
+            (Entry::Public, 0) => 2,
+            _ => 0,
+        }),
+        SymbolicNode::Constant(c) => c,
+        _ => Val::ONE,
+    });
+    let expected = [1, 0, 45].map(Val::from_canonical_u32);
+    assert_eq!(values, expected);
+}
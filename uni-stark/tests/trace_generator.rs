@@ -0,0 +1,130 @@
+use core::mem::MaybeUninit;
+
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field, PackedValue};
+use p3_matrix::Matrix;
+use p3_uni_stark::{generate_trace, write_packed_column, TraceGenerator};
+
+type Val = BabyBear;
+
+const ROUNDS: usize = 3;
+
+/// Repeated squaring of each input, with a row `[x, x^2]` per round.
+struct SquaringGenerator;
+
+// SAFETY: each chunk of `rows` is a full row, and both of its values are written.
+unsafe impl<F: Field> TraceGenerator<F> for SquaringGenerator {
+    type Input = F;
+
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn rows_per_input(&self) -> usize {
+        ROUNDS
+    }
+
+    fn generate_rows(&self, &input: &F, rows: &mut [MaybeUninit<F>]) {
+        let mut x = input;
+        for row in rows.chunks_exact_mut(2) {
+            row[0].write(x);
+            x = x.square();
+            row[1].write(x);
+        }
+    }
+}
+
+/// `SquaringGenerator`, squaring each chunk of inputs in the lanes of packed field elements.
+struct PackedSquaringGenerator;
+
+// SAFETY: every round writes both columns of each input's rows.
+unsafe impl<F: Field> TraceGenerator<F> for PackedSquaringGenerator {
+    type Input = F;
+
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn rows_per_input(&self) -> usize {
+        ROUNDS
+    }
+
+    fn generate_rows(&self, input: &F, rows: &mut [MaybeUninit<F>]) {
+        self.generate_packed_rows(core::slice::from_ref(input), rows);
+    }
+
+    fn generate_packed_rows(&self, inputs: &[F], rows: &mut [MaybeUninit<F>]) {
+        let stride = ROUNDS * 2;
+        let mut x = F::Packing::from_fn(|i| inputs.get(i).copied().unwrap_or_default());
+        for round in 0..ROUNDS {
+            write_packed_column(rows, stride, 2 * round, x);
+            x = x * x;
+            write_packed_column(rows, stride, 2 * round + 1, x);
+        }
+    }
+}
+
+fn expected_rows(inputs: &[Val], height: usize) -> Vec<Val> {
+    inputs
+        .iter()
+        .flat_map(|&input| {
+            (0..ROUNDS).flat_map(move |round| {
+                let x = input.exp_power_of_2(round);
+                [x, x.square()]
+            })
+        })
+        .take(height * 2)
+        .collect()
+}
+
+#[test]
+fn test_generate_trace() {
+    let inputs: Vec<Val> = (1..=37).map(Val::from_canonical_u32).collect();
+    let trace = generate_trace::<Val, _>(&SquaringGenerator, &inputs, None);
+    assert_eq!((trace.height(), trace.width()), (37 * ROUNDS, 2));
+    assert_eq!(trace.values, expected_rows(&inputs, 37 * ROUNDS));
+}
+
+#[test]
+fn test_generate_trace_with_padding() {
+    // 15 rows of inputs, padded to 16, the last of which is the first row of the padding.
+    let inputs: Vec<Val> = (2..7).map(Val::from_canonical_u32).collect();
+    let padding = Val::ONE;
+    let trace = generate_trace::<Val, _>(&SquaringGenerator, &inputs, Some(&padding));
+    assert_eq!(trace.height(), 16);
+
+    let mut padded_inputs = inputs.clone();
+    padded_inputs.push(padding);
+    assert_eq!(trace.values, expected_rows(&padded_inputs, 16));
+}
+
+#[test]
+fn test_generate_packed_trace() {
+    for num_inputs in [1, 5, 37] {
+        let inputs: Vec<Val> = (1..=num_inputs).map(Val::from_canonical_u32).collect();
+        let padding = Val::TWO;
+        let trace = generate_trace::<Val, _>(&PackedSquaringGenerator, &inputs, Some(&padding));
+        let expected = generate_trace::<Val, _>(&SquaringGenerator, &inputs, Some(&padding));
+        assert_eq!(trace.values, expected.values);
+    }
+}
+
+/// A generator with no columns.
+struct EmptyGenerator;
+
+// SAFETY: there are no values to write.
+unsafe impl<F: Field> TraceGenerator<F> for EmptyGenerator {
+    type Input = F;
+
+    fn width(&self) -> usize {
+        0
+    }
+
+    fn generate_rows(&self, _input: &F, _rows: &mut [MaybeUninit<F>]) {}
+}
+
+#[test]
+#[should_panic(expected = "the trace must have at least one column")]
+fn test_generate_trace_no_columns() {
+    generate_trace::<Val, _>(&EmptyGenerator, &[Val::ONE], None);
+}
@@ -0,0 +1,132 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{prove, verify, StarkConfig};
+use rand::thread_rng;
+
+/// The Fibonacci sequence from the public values `[a, b]` in a single column, with each value
+/// constrained by the two before it. The window also has the previous row, which on the first row
+/// is the last row of the trace, holding the public value `x`.
+struct FibonacciWindowAir;
+
+impl<F> BaseAir<F> for FibonacciWindowAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn window_offsets(&self) -> Vec<isize> {
+        vec![-1, 0, 1, 2]
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciWindowAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (prev, local, next, next_next) = (
+            main.row_slice(0)[0],
+            main.row_slice(1)[0],
+            main.row_slice(2)[0],
+            main.row_slice(3)[0],
+        );
+        let pis = builder.public_values();
+        let (a, b, x) = (pis[0], pis[1], pis[2]);
+
+        let mut when_first_row = builder.when_first_row();
+        when_first_row.assert_eq(local, a);
+        when_first_row.assert_eq(next, b);
+        when_first_row.assert_eq(prev, x);
+
+        builder
+            .when_transition_window(3)
+            .assert_eq(next_next, local + next);
+    }
+}
+
+fn generate_trace<F: Field>(a: u64, b: u64, n: usize) -> RowMajorMatrix<F> {
+    let mut values = vec![F::from_canonical_u64(a), F::from_canonical_u64(b)];
+    while values.len() < n {
+        values.push(values[values.len() - 2] + values[values.len() - 1]);
+    }
+    RowMajorMatrix::new_col(values)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+#[test]
+fn test_window_with_negative_and_distant_rows() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        target_soundness_bits: None,
+        security_assumption: SecurityAssumption::CapacityBound,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let trace = generate_trace::<Val>(0, 1, 1 << 4);
+    let public_values = vec![Val::ZERO, Val::ONE, Val::from_canonical_u32(610)];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(
+        &config,
+        &FibonacciWindowAir,
+        &mut challenger,
+        trace,
+        &public_values,
+    );
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify(
+        &config,
+        &FibonacciWindowAir,
+        &mut challenger,
+        &proof,
+        &public_values,
+    )
+    .expect("verification failed");
+
+    let mut wrong_public_values = public_values.clone();
+    wrong_public_values[2] += Val::ONE;
+    let mut challenger = Challenger::new(perm);
+    assert!(verify(
+        &config,
+        &FibonacciWindowAir,
+        &mut challenger,
+        &proof,
+        &wrong_public_values
+    )
+    .is_err());
+}
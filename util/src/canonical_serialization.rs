@@ -0,0 +1,830 @@
+//! A canonical, versioned binary encoding built on top of `serde`.
+//!
+//! Unlike self-describing formats, the encoding produced here carries no type information, so
+//! every value has exactly one byte representation:
+//! - unsigned and signed integers are written as fixed-width little-endian values,
+//! - `bool`s are a single `0` or `1` byte,
+//! - sequences, maps, strings and byte strings are prefixed by their length as a `u32`,
+//! - tuples, fixed-size arrays and structs are the concatenation of their elements, in order,
+//! - `Option`s and enum variants are prefixed by a `u8` tag and a `u32` variant index respectively.
+//!
+//! Floating point numbers are rejected, since they have no canonical encoding. Field elements are
+//! encoded however their `Serialize` implementation chooses; the fields in this repository all
+//! serialize their canonical integer representative.
+//!
+//! [`to_bytes_with_header`] and [`from_bytes_with_header`] additionally prefix the payload with a
+//! four byte magic and a `u16` format version, so that incompatible layouts are detected instead of
+//! silently misread.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+use serde::de::{DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::{de, ser, Serialize};
+
+/// The number of bytes occupied by the header written by [`to_bytes_with_header`].
+pub const HEADER_LEN: usize = 6;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SerializationError {
+    /// The input ended before the value was fully decoded.
+    UnexpectedEof,
+    /// The value was decoded, but unread bytes remained.
+    TrailingBytes(usize),
+    /// The header magic did not match the expected magic.
+    BadMagic { expected: [u8; 4], found: [u8; 4] },
+    /// The header version is not the one this decoder understands.
+    UnsupportedVersion { expected: u16, found: u16 },
+    /// A length did not fit in the `u32` length prefix.
+    LengthOverflow(usize),
+    /// A byte which should have been a `bool` or `Option` tag was neither 0 nor 1.
+    InvalidTag(u8),
+    /// A string was not valid UTF-8, or a `char` was not a valid scalar value.
+    InvalidUtf8,
+    /// The value uses a `serde` feature with no canonical encoding.
+    Unsupported(&'static str),
+    /// An error raised by a `Serialize` or `Deserialize` implementation.
+    Custom(String),
+}
+
+impl Display for SerializationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnexpectedEof => write!(f, "unexpected end of input"),
+            Self::TrailingBytes(n) => write!(f, "{n} trailing bytes after decoded value"),
+            Self::BadMagic { expected, found } => {
+                write!(f, "bad magic: expected {expected:?}, found {found:?}")
+            }
+            Self::UnsupportedVersion { expected, found } => {
+                write!(f, "unsupported version: expected {expected}, found {found}")
+            }
+            Self::LengthOverflow(len) => write!(f, "length {len} does not fit in a u32 prefix"),
+            Self::InvalidTag(tag) => write!(f, "invalid tag byte {tag}"),
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            Self::Unsupported(what) => write!(f, "{what} has no canonical encoding"),
+            Self::Custom(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl ser::StdError for SerializationError {}
+
+impl ser::Error for SerializationError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for SerializationError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// Encode `value` without any header.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SerializationError> {
+    let mut serializer = CanonicalSerializer { output: Vec::new() };
+    value.serialize(&mut serializer)?;
+    Ok(serializer.output)
+}
+
+/// Decode a value previously encoded with [`to_bytes`], rejecting any trailing bytes.
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, SerializationError> {
+    let mut deserializer = CanonicalDeserializer { input: bytes };
+    let value = T::deserialize(&mut deserializer)?;
+    if deserializer.input.is_empty() {
+        Ok(value)
+    } else {
+        Err(SerializationError::TrailingBytes(deserializer.input.len()))
+    }
+}
+
+/// Encode `value`, preceded by `magic` and `version`.
+pub fn to_bytes_with_header<T: Serialize + ?Sized>(
+    magic: [u8; 4],
+    version: u16,
+    value: &T,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut serializer = CanonicalSerializer {
+        output: Vec::with_capacity(HEADER_LEN),
+    };
+    serializer.output.extend_from_slice(&magic);
+    serializer.output.extend_from_slice(&version.to_le_bytes());
+    value.serialize(&mut serializer)?;
+    Ok(serializer.output)
+}
+
+/// Decode a value previously encoded with [`to_bytes_with_header`], checking that the header
+/// matches `magic` and `version`.
+pub fn from_bytes_with_header<T: DeserializeOwned>(
+    magic: [u8; 4],
+    version: u16,
+    bytes: &[u8],
+) -> Result<T, SerializationError> {
+    if bytes.len() < HEADER_LEN {
+        return Err(SerializationError::UnexpectedEof);
+    }
+    let (header, payload) = bytes.split_at(HEADER_LEN);
+    let found_magic: [u8; 4] = header[..4].try_into().unwrap();
+    if found_magic != magic {
+        return Err(SerializationError::BadMagic {
+            expected: magic,
+            found: found_magic,
+        });
+    }
+    let found_version = u16::from_le_bytes(header[4..].try_into().unwrap());
+    if found_version != version {
+        return Err(SerializationError::UnsupportedVersion {
+            expected: version,
+            found: found_version,
+        });
+    }
+    from_bytes(payload)
+}
+
+struct CanonicalSerializer {
+    output: Vec<u8>,
+}
+
+impl CanonicalSerializer {
+    fn write_len(&mut self, len: usize) -> Result<(), SerializationError> {
+        let len = u32::try_from(len).map_err(|_| SerializationError::LengthOverflow(len))?;
+        self.output.extend_from_slice(&len.to_le_bytes());
+        Ok(())
+    }
+}
+
+impl ser::Serializer for &mut CanonicalSerializer {
+    type Ok = ();
+    type Error = SerializationError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), SerializationError> {
+        self.output.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), SerializationError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), SerializationError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), SerializationError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), SerializationError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), SerializationError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), SerializationError> {
+        self.output.push(v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), SerializationError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), SerializationError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), SerializationError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), SerializationError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), SerializationError> {
+        Err(SerializationError::Unsupported("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), SerializationError> {
+        Err(SerializationError::Unsupported("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), SerializationError> {
+        self.serialize_u32(v as u32)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SerializationError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), SerializationError> {
+        self.write_len(v.len())?;
+        self.output.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), SerializationError> {
+        self.output.push(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SerializationError> {
+        self.output.push(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerializationError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), SerializationError> {
+        self.serialize_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), SerializationError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), SerializationError> {
+        self.serialize_u32(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, SerializationError> {
+        let len = len.ok_or(SerializationError::Unsupported(
+            "a sequence of unknown length",
+        ))?;
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, SerializationError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self, SerializationError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, SerializationError> {
+        self.serialize_u32(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, SerializationError> {
+        let len = len.ok_or(SerializationError::Unsupported("a map of unknown length"))?;
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self, SerializationError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, SerializationError> {
+        self.serialize_u32(variant_index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+macro_rules! impl_compound_serializer {
+    ($trait:ident, $method:ident) => {
+        impl ser::$trait for &mut CanonicalSerializer {
+            type Ok = ();
+            type Error = SerializationError;
+
+            fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+                value.serialize(&mut **self)
+            }
+
+            fn end(self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_compound_serializer!(SerializeSeq, serialize_element);
+impl_compound_serializer!(SerializeTuple, serialize_element);
+impl_compound_serializer!(SerializeTupleStruct, serialize_field);
+impl_compound_serializer!(SerializeTupleVariant, serialize_field);
+
+impl ser::SerializeMap for &mut CanonicalSerializer {
+    type Ok = ();
+    type Error = SerializationError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut CanonicalSerializer {
+    type Ok = ();
+    type Error = SerializationError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut CanonicalSerializer {
+    type Ok = ();
+    type Error = SerializationError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+struct CanonicalDeserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> CanonicalDeserializer<'de> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], SerializationError> {
+        if self.input.len() < N {
+            return Err(SerializationError::UnexpectedEof);
+        }
+        let (head, rest) = self.input.split_at(N);
+        self.input = rest;
+        Ok(head.try_into().unwrap())
+    }
+
+    fn take_slice(&mut self, len: usize) -> Result<&'de [u8], SerializationError> {
+        if self.input.len() < len {
+            return Err(SerializationError::UnexpectedEof);
+        }
+        let (head, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(head)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, SerializationError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn read_len(&mut self) -> Result<usize, SerializationError> {
+        Ok(self.read_u32()? as usize)
+    }
+
+    fn read_tag(&mut self) -> Result<bool, SerializationError> {
+        match self.take::<1>()?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(SerializationError::InvalidTag(tag)),
+        }
+    }
+}
+
+macro_rules! deserialize_int {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializationError> {
+            visitor.$visit(<$ty>::from_le_bytes(self.take()?))
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &mut CanonicalDeserializer<'de> {
+    type Error = SerializationError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, SerializationError> {
+        Err(SerializationError::Unsupported(
+            "self-describing deserialization",
+        ))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializationError> {
+        visitor.visit_bool(self.read_tag()?)
+    }
+
+    deserialize_int!(deserialize_i8, visit_i8, i8);
+    deserialize_int!(deserialize_i16, visit_i16, i16);
+    deserialize_int!(deserialize_i32, visit_i32, i32);
+    deserialize_int!(deserialize_i64, visit_i64, i64);
+    deserialize_int!(deserialize_i128, visit_i128, i128);
+    deserialize_int!(deserialize_u8, visit_u8, u8);
+    deserialize_int!(deserialize_u16, visit_u16, u16);
+    deserialize_int!(deserialize_u32, visit_u32, u32);
+    deserialize_int!(deserialize_u64, visit_u64, u64);
+    deserialize_int!(deserialize_u128, visit_u128, u128);
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, SerializationError> {
+        Err(SerializationError::Unsupported("f32"))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, SerializationError> {
+        Err(SerializationError::Unsupported("f64"))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializationError> {
+        let c = char::from_u32(self.read_u32()?).ok_or(SerializationError::InvalidUtf8)?;
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializationError> {
+        let len = self.read_len()?;
+        let bytes = self.take_slice(len)?;
+        let s = core::str::from_utf8(bytes).map_err(|_| SerializationError::InvalidUtf8)?;
+        visitor.visit_borrowed_str(s)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        let len = self.read_len()?;
+        visitor.visit_borrowed_bytes(self.take_slice(len)?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        if self.read_tag()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializationError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializationError> {
+        let len = self.read_len()?;
+        visitor.visit_seq(Elements {
+            de: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        visitor.visit_seq(Elements {
+            de: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializationError> {
+        let len = self.read_len()?;
+        visitor.visit_map(Elements {
+            de: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        Err(SerializationError::Unsupported("identifiers"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        Err(SerializationError::Unsupported("ignored values"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Gives a visitor access to a known number of consecutive elements (or key-value pairs).
+struct Elements<'a, 'de> {
+    de: &'a mut CanonicalDeserializer<'de>,
+    remaining: usize,
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for Elements<'a, 'de> {
+    type Error = SerializationError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerializationError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        // Lengths come from untrusted input, so don't let visitors preallocate based on them.
+        Some(self.remaining.min(1 << 12))
+    }
+}
+
+impl<'a, 'de> de::MapAccess<'de> for Elements<'a, 'de> {
+    type Error = SerializationError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerializationError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerializationError> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.min(1 << 12))
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut CanonicalDeserializer<'de> {
+    type Error = SerializationError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), SerializationError> {
+        let variant_index = self.read_u32()?;
+        let value = seed.deserialize(variant_index.into_deserializer())?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut CanonicalDeserializer<'de> {
+    type Error = SerializationError;
+
+    fn unit_variant(self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerializationError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerializationError> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        a: u32,
+        b: Vec<u64>,
+        c: Option<(bool, [u16; 2])>,
+        d: Kind,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Kind {
+        Empty,
+        Wrapped(u8),
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            a: 7,
+            b: vec![1, 2],
+            c: Some((true, [3, 4])),
+            d: Kind::Wrapped(9),
+        }
+    }
+
+    #[test]
+    fn test_golden_encoding() {
+        #[rustfmt::skip]
+        let expected = vec![
+            7, 0, 0, 0,
+            2, 0, 0, 0,
+            1, 0, 0, 0, 0, 0, 0, 0,
+            2, 0, 0, 0, 0, 0, 0, 0,
+            1, 1, 3, 0, 4, 0,
+            1, 0, 0, 0, 9,
+        ];
+        assert_eq!(to_bytes(&sample()).unwrap(), expected);
+        assert_eq!(from_bytes::<Sample>(&expected).unwrap(), sample());
+        assert_eq!(to_bytes(&Kind::Empty).unwrap(), vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let bytes = to_bytes_with_header(*b"TEST", 3, &sample()).unwrap();
+        assert_eq!(&bytes[..HEADER_LEN], &[b'T', b'E', b'S', b'T', 3, 0]);
+        let decoded: Sample = from_bytes_with_header(*b"TEST", 3, &bytes).unwrap();
+        assert_eq!(decoded, sample());
+
+        assert_eq!(
+            from_bytes_with_header::<Sample>(*b"ABCD", 3, &bytes),
+            Err(SerializationError::BadMagic {
+                expected: *b"ABCD",
+                found: *b"TEST",
+            })
+        );
+        assert_eq!(
+            from_bytes_with_header::<Sample>(*b"TEST", 4, &bytes),
+            Err(SerializationError::UnsupportedVersion {
+                expected: 4,
+                found: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        let mut bytes = to_bytes(&sample()).unwrap();
+        assert_eq!(
+            from_bytes::<Sample>(&bytes[..bytes.len() - 1]),
+            Err(SerializationError::UnexpectedEof)
+        );
+
+        bytes.push(0);
+        assert_eq!(
+            from_bytes::<Sample>(&bytes),
+            Err(SerializationError::TrailingBytes(1))
+        );
+
+        assert_eq!(
+            from_bytes::<Option<u8>>(&[2, 0]),
+            Err(SerializationError::InvalidTag(2))
+        );
+    }
+}